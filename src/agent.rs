@@ -4,11 +4,19 @@
 //! `multi_turn()` streaming via [`Provider::stream_with_tools`]. The actual
 //! send→tool→feedback iteration is handled entirely by rig-core; this module
 //! renders stream events and captures the final assistant text response.
+//!
+//! [`deadline`] and [`timeout_summary`] support the per-turn wall-clock
+//! limit (`--turn-timeout`, `turn_timeout_secs`): callers race the loop
+//! against the deadline, which drops the in-flight request and waits for no
+//! further tool calls, then report how far the turn got.
 
 use crate::hooks::KazeHook;
 use anyhow::Result;
+use std::time::Duration;
+use tracing::Instrument;
 
 use crate::message::Message;
+use crate::metrics::TurnMetrics;
 use crate::output::Renderer;
 use crate::provider::Provider;
 use crate::tools::ToolRegistry;
@@ -31,7 +39,58 @@ pub async fn agent_loop(
 ) -> Result<String> {
     let response = provider
         .stream_with_tools(messages, tools, renderer, max_iterations, hook)
+        .instrument(tracing::info_span!("agent_turn", max_iterations))
         .await?;
     messages.push(Message::assistant(&response));
     Ok(response)
 }
+
+/// Resolves once `limit` has passed, or never when there is no limit.
+pub async fn deadline(limit: Option<Duration>) {
+    match limit {
+        Some(limit) => tokio::time::sleep(limit).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Describes how far a turn got before it hit its time limit, e.g.
+/// `Turn timed out after 5m: 3 tool calls (read_file, grep, bash), 1,204 characters of response kept`.
+pub fn timeout_summary(limit: Duration, metrics: &TurnMetrics, partial: &str) -> String {
+    let mut progress = Vec::new();
+    match metrics.tool_calls {
+        0 => {}
+        1 => progress.push(format!("1 tool call ({})", metrics.tools.join(", "))),
+        n => progress.push(format!("{} tool calls ({})", n, metrics.tools.join(", "))),
+    }
+    if !metrics.modified_files.is_empty() {
+        progress.push(format!("modified {}", metrics.modified_files.join(", ")));
+    }
+    let chars = partial.trim().chars().count();
+    if chars > 0 {
+        progress.push(format!(
+            "{} characters of response kept",
+            crate::tokens::format_number(chars)
+        ));
+    }
+    if progress.is_empty() {
+        progress.push("no output yet".to_string());
+    }
+    format!(
+        "Turn timed out after {}: {}",
+        format_limit(limit),
+        progress.join(", ")
+    )
+}
+
+/// Formats a whole-second limit as `90s`, `5m`, `5m 30s`, or `1h 10m`.
+fn format_limit(limit: Duration) -> String {
+    let secs = limit.as_secs();
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+    match (h, m, s) {
+        (0, 0, s) => format!("{}s", s),
+        (0, m, 0) => format!("{}m", m),
+        (0, m, s) => format!("{}m {}s", m, s),
+        (h, 0, _) => format!("{}h", h),
+        (h, m, _) => format!("{}h {}m", h, m),
+    }
+}
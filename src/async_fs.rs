@@ -0,0 +1,54 @@
+//! Async filesystem facade shared by tools and hooks.
+//!
+//! Everything on the agent's async path goes through these helpers instead
+//! of `std::fs`, so a large file read never stalls the single-threaded
+//! runtime (and with it streaming and the TUI's 60 fps loop). Backed by
+//! `tokio::fs`, which runs each operation on the blocking pool.
+
+use std::io;
+use std::path::Path;
+
+/// Reads an entire file into bytes.
+pub async fn read(path: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+    tokio::fs::read(path).await
+}
+
+/// Reads an entire file into a UTF-8 string.
+pub async fn read_to_string(path: impl AsRef<Path>) -> io::Result<String> {
+    tokio::fs::read_to_string(path).await
+}
+
+/// Writes `contents` to a file, replacing it if it exists.
+pub async fn write(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    tokio::fs::write(path, contents).await
+}
+
+/// Returns metadata for a path, following symlinks.
+pub async fn metadata(path: impl AsRef<Path>) -> io::Result<std::fs::Metadata> {
+    tokio::fs::metadata(path).await
+}
+
+/// Recursively creates a directory and all missing parents.
+pub async fn create_dir_all(path: impl AsRef<Path>) -> io::Result<()> {
+    tokio::fs::create_dir_all(path).await
+}
+
+/// Returns whether a path exists; errors (e.g. permission denied) count as absent.
+pub async fn exists(path: impl AsRef<Path>) -> bool {
+    tokio::fs::try_exists(path).await.unwrap_or(false)
+}
+
+/// Renames a file, replacing the destination if it exists.
+pub async fn rename(from: impl AsRef<Path>, to: impl AsRef<Path>) -> io::Result<()> {
+    tokio::fs::rename(from, to).await
+}
+
+/// Removes a file.
+pub async fn remove_file(path: impl AsRef<Path>) -> io::Result<()> {
+    tokio::fs::remove_file(path).await
+}
+
+/// Sets a file's permissions.
+pub async fn set_permissions(path: impl AsRef<Path>, perm: std::fs::Permissions) -> io::Result<()> {
+    tokio::fs::set_permissions(path, perm).await
+}
@@ -0,0 +1,162 @@
+//! Audit log of tool executions.
+//!
+//! Every tool call the agent runs, and every `!` command typed in chat,
+//! appends one [`AuditRecord`] to `~/.local/share/kaze/audit.jsonl`, one
+//! JSON object per line: the tool, its arguments, how much it returned,
+//! whether it failed, and the session it ran in. Calls that were denied or declined never ran and aren't
+//! logged. `kaze audit show` reads the log back through [`read_log`].
+//!
+//! Recording is off until a frontend calls [`AuditLog::start`], so tests
+//! and `kaze selftest` leave the user's log alone.
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::constants::AUDIT_ARG_MAX_CHARS;
+use crate::tools::ToolResult;
+
+/// One executed tool call.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// RFC 3339 timestamp of when the call finished.
+    pub timestamp: String,
+    /// Session the call ran in; `None` for one-shot `kaze ask`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    pub tool: String,
+    /// The call's arguments, with long strings (file contents, patches)
+    /// cut to their start and length.
+    pub args: Value,
+    /// Bytes of output the tool returned.
+    pub result_bytes: usize,
+    /// `"ok"` or `"error"`.
+    pub status: String,
+    /// The command's exit code, for bash calls that exited non-zero.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    pub duration_ms: u64,
+}
+
+impl AuditRecord {
+    /// Builds a record of `tool` called with `args`, stamped with the
+    /// current time.
+    pub fn new(
+        session_id: Option<String>,
+        tool: &str,
+        args: &Value,
+        result: &ToolResult,
+        duration: Duration,
+    ) -> Self {
+        let exit_code = (tool == "bash" && result.is_error)
+            .then(|| result.content.rsplit_once("\nExit code: "))
+            .flatten()
+            .and_then(|(_, code)| code.trim().parse().ok());
+        Self {
+            timestamp: Utc::now().to_rfc3339(),
+            session_id,
+            tool: tool.to_string(),
+            args: shorten(args),
+            result_bytes: result.content.len(),
+            status: if result.is_error { "error" } else { "ok" }.to_string(),
+            exit_code,
+            duration_ms: duration.as_millis() as u64,
+        }
+    }
+
+    /// Appends this record to the audit log.
+    pub fn append(&self) -> Result<()> {
+        let path = log_path()?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open audit log: {}", path.display()))?;
+        // One write per record, so concurrent appends don't interleave
+        let line = serde_json::to_string(self)? + "\n";
+        file.write_all(line.as_bytes())
+            .with_context(|| "Failed to write audit log")?;
+        Ok(())
+    }
+}
+
+/// Where a tool registry's calls are recorded. Clones share the session,
+/// so subagents' calls land under their parent's.
+#[derive(Clone, Default)]
+pub struct AuditLog(Arc<Mutex<Option<Recording>>>);
+
+struct Recording {
+    session_id: Option<String>,
+}
+
+impl AuditLog {
+    /// Starts recording calls against `session_id`, or switches to it.
+    pub fn start(&self, session_id: Option<&str>) {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner()) = Some(Recording {
+            session_id: session_id.map(str::to_string),
+        });
+    }
+
+    /// Records a finished call, if recording has started. Failing to write
+    /// the log must not fail the call, so write errors are dropped.
+    pub fn record(&self, tool: &str, args: &Value, result: &ToolResult, duration: Duration) {
+        let session_id = match &*self.0.lock().unwrap_or_else(|e| e.into_inner()) {
+            Some(recording) => recording.session_id.clone(),
+            None => return,
+        };
+        let _ = AuditRecord::new(session_id, tool, args, result, duration).append();
+    }
+}
+
+/// `args` with every string longer than `AUDIT_ARG_MAX_CHARS` cut short.
+fn shorten(args: &Value) -> Value {
+    match args {
+        Value::String(text) if text.chars().count() > AUDIT_ARG_MAX_CHARS => {
+            let start: String = text.chars().take(AUDIT_ARG_MAX_CHARS).collect();
+            Value::String(format!("{}… ({} bytes)", start, text.len()))
+        }
+        Value::Array(items) => Value::Array(items.iter().map(shorten).collect()),
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, value)| (key.clone(), shorten(value)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// Reads every record in the audit log, oldest first.
+///
+/// A missing log reads as empty; lines that fail to read or parse (e.g. a
+/// partial write) are skipped.
+pub fn read_log() -> Result<Vec<AuditRecord>> {
+    let path = log_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let file = std::fs::File::open(&path)
+        .with_context(|| format!("Failed to open audit log: {}", path.display()))?;
+    // Split on bytes so one non-UTF-8 line is skipped rather than ending
+    // the read; only an I/O error does that
+    Ok(BufReader::new(file)
+        .split(b'\n')
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_slice(&line).ok())
+        .collect())
+}
+
+/// Returns the audit log path (`~/.local/share/kaze/audit.jsonl`).
+pub fn log_path() -> Result<PathBuf> {
+    Ok(Config::data_dir()?.join("audit.jsonl"))
+}
@@ -0,0 +1,72 @@
+//! Command output attached to the conversation (`kaze chat --attach-cmd`).
+//!
+//! The command runs through `sh -c` in the project directory. Its combined
+//! stdout and stderr goes into a `<command_output>` block that is prepended
+//! to the next user message, so a debugging session starts from current
+//! diagnostics. `/refresh-cmd` runs it again.
+
+use anyhow::{Context, Result};
+use std::time::Duration;
+
+use crate::constants::{ATTACH_CMD_MAX_TOKENS, ATTACH_CMD_TIMEOUT_SECS};
+
+/// Output of one run of the attached command.
+pub struct CommandOutput {
+    /// The `<command_output>` block to send with the next message.
+    pub block: String,
+    /// One-line description for the user, e.g. `cargo check: exit 101, 42 lines`.
+    pub summary: String,
+}
+
+/// Runs `command` and formats its output for the model.
+///
+/// Output over the token budget keeps its last lines. A non-zero exit is
+/// not an error — failing diagnostics are usually the point.
+pub async fn run(command: &str, model: &str) -> Result<CommandOutput> {
+    let child = tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("Failed to run `{}`", command))?;
+    let timeout = Duration::from_secs(ATTACH_CMD_TIMEOUT_SECS);
+    let output = tokio::time::timeout(timeout, child.wait_with_output())
+        .await
+        .map_err(|_| anyhow::anyhow!("`{}` timed out after {}s", command, ATTACH_CMD_TIMEOUT_SECS))?
+        .with_context(|| format!("Failed to run `{}`", command))?;
+
+    let mut text = String::from_utf8_lossy(&output.stdout).to_string();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !stderr.trim().is_empty() {
+        if !text.is_empty() && !text.ends_with('\n') {
+            text.push('\n');
+        }
+        text.push_str(&stderr);
+    }
+
+    let budget = ATTACH_CMD_MAX_TOKENS.min(crate::tokens::context_window_size(model) / 2);
+    let lines = text.lines().count();
+    let (text, dropped) = crate::tokens::truncate_to_last_tokens(text.trim_end(), budget, model);
+    let exit = output
+        .status
+        .code()
+        .map_or("signal".to_string(), |code| code.to_string());
+
+    let mut summary = format!("{}: exit {}, {} lines", command, exit, lines);
+    if dropped > 0 {
+        summary.push_str(&format!(
+            ", first {} dropped to fit the token budget",
+            dropped
+        ));
+    }
+    let block = format!(
+        "<command_output command=\"{}\" exit_code=\"{}\">\n{}\n</command_output>",
+        command.replace('"', "&quot;"),
+        exit,
+        text
+    );
+    Ok(CommandOutput { block, summary })
+}
@@ -1,87 +1,801 @@
 //! Slash command handlers for the chat REPL.
 //!
-//! Dispatches `/history`, `/clear`, `/help`, and `/compact` commands.
+//! Dispatches `/history`, `/clear`, `/help`, `/compact`, `/model`,
+//! `/view`, `/rate`, `/prompt`, `/todos`, `/copy`, `/title`, `/review`,
+//! `/rewind`, `/undo`, `/expand`, `/extract`, `/export`, `/context`,
+//! `/auto`, and `/refresh-cmd` commands, plus the user's own from [`super::custom`].
 //! Returns a [`CommandAction`] so the REPL loop can decide how to proceed.
+//! [`handle_shell_escape`] runs `!command` lines.
+//! Output goes through a [`Renderer`] so the TUI shares the same handlers.
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use colored::Colorize;
+use std::path::Path;
 
+use crate::async_fs;
 use crate::compaction::CompactionResult;
+use crate::constants::{BINARY_DETECTION_BYTES, VIEW_MAX_LINES};
 use crate::format;
-use crate::message::Role;
-use crate::provider::Provider;
-use crate::session::Session;
+use crate::highlight::{self, Language};
+use crate::i18n::{t, tf};
+use crate::message::{Message, Role};
+use crate::metrics::plural;
+use crate::output::{NoticeLevel, Renderer};
+use crate::session::Rating;
+use crate::tools::todo::format_todos;
 
-use super::context;
+use super::engine::ChatEngine;
+
+/// Runs a `!command` line through the `bash` tool and shows its output.
+/// `!>command` also attaches the output to the next message.
+pub async fn handle_shell_escape(
+    line: &str,
+    engine: &mut ChatEngine,
+    renderer: &mut dyn Renderer,
+) -> Result<()> {
+    let line = line.strip_prefix('!').unwrap_or(line);
+    let (command, attach) = match line.strip_prefix('>') {
+        Some(command) => (command.trim(), true),
+        None => (line.trim(), false),
+    };
+    if command.is_empty() {
+        renderer.notice(
+            NoticeLevel::Plain,
+            "Usage: !<command>, or !><command> to attach the output",
+        );
+        return Ok(());
+    }
+    match engine.run_shell(command, attach).await {
+        Ok(result) => {
+            let level = if result.is_error {
+                NoticeLevel::Warning
+            } else {
+                NoticeLevel::Plain
+            };
+            if !result.content.is_empty() {
+                renderer.notice(level, &result.content);
+            }
+            if attach {
+                renderer.notice(NoticeLevel::Info, t("shell.attached"));
+            }
+        }
+        Err(e) => renderer.render_error(&format!("{:#}", e)),
+    }
+    Ok(())
+}
+
+/// Names of the built-in commands, which custom commands can't replace.
+const BUILTIN_COMMANDS: &[&str] = &[
+    "history",
+    "clear",
+    "help",
+    "retry",
+    "rewind",
+    "expand",
+    "undo",
+    "extract",
+    "export",
+    "copy",
+    "title",
+    "review",
+    "context",
+    "auto",
+    "todos",
+    "refresh-cmd",
+    "compact",
+    "model",
+    "view",
+    "rate",
+    "prompt",
+];
+
+/// A warning for each custom command named like a built-in, which never
+/// runs because the built-in wins.
+pub fn shadowed_command_warnings() -> Vec<String> {
+    super::custom::load()
+        .values()
+        .filter(|command| BUILTIN_COMMANDS.contains(&command.name.as_str()))
+        .map(|command| {
+            tf(
+                "custom.shadowed",
+                &[
+                    ("name", &command.name),
+                    ("path", &command.path.display().to_string()),
+                ],
+            )
+        })
+        .collect()
+}
 
 /// Action returned by slash command handling.
-pub(crate) enum CommandAction {
+pub enum CommandAction {
     /// Command was handled successfully; continue the REPL loop.
     Continue,
     /// Unknown command was entered.
     Unknown(String),
+    /// Send this text as a chat message (e.g. `/retry`).
+    Send(String),
 }
 
 /// Dispatch and handle a slash command.
 ///
-/// Matches the input against known commands and executes the appropriate
-/// handler. Returns [`CommandAction::Unknown`] for unrecognized commands.
-pub(crate) async fn handle_slash_command(
+/// Matches the input against the built-in commands, then the custom ones,
+/// and executes the appropriate handler. Returns [`CommandAction::Unknown`]
+/// for unrecognized commands.
+pub async fn handle_slash_command(
     command: &str,
-    session: &mut Session,
-    provider: &Provider,
-    model_name: &str,
-    keep_recent: usize,
+    engine: &mut ChatEngine,
+    renderer: &mut dyn Renderer,
 ) -> Result<CommandAction> {
-    match command {
+    let (name, args) = command
+        .split_once(char::is_whitespace)
+        .map(|(name, args)| (name, args.trim()))
+        .unwrap_or((command, ""));
+
+    match name {
         "/history" => {
-            for msg in &session.messages {
+            for msg in &engine.session.messages {
                 if msg.role == Role::System {
                     continue;
                 }
-                println!("{}", format::format_message(msg));
-                println!();
+                renderer.notice(
+                    NoticeLevel::Plain,
+                    &format!("{}\n", format::format_message(msg)),
+                );
             }
             Ok(CommandAction::Continue)
         }
         "/clear" => {
-            session.messages.retain(|m| m.role == Role::System);
-            println!("{}", "History cleared.".dimmed());
+            engine.session.messages.retain(|m| m.role == Role::System);
+            renderer.notice(NoticeLevel::Info, "History cleared.");
             Ok(CommandAction::Continue)
         }
         "/help" => {
-            println!("{}", "Commands:".bold());
-            println!("  {} - show conversation history", "/history".cyan());
-            println!("  {} - clear conversation", "/clear".cyan());
-            println!(
-                "  {} - summarize old context to free tokens",
-                "/compact".cyan()
-            );
-            println!("  {} - show this help", "/help".cyan());
-            println!("  {} - exit", "Ctrl+D".cyan());
+            renderer.notice(NoticeLevel::Plain, &t("help.title").bold().to_string());
+            for (name, description) in [
+                ("/history", t("help.history")),
+                ("/clear", t("help.clear")),
+                ("/compact", t("help.compact")),
+                ("/model <[provider/]model>", t("help.model")),
+                ("/view <path> [start:end]", t("help.view")),
+                ("/prompt show", t("help.prompt")),
+                ("/rate good|bad [note]", t("help.rate")),
+                ("/retry [--model m] [instruction]", t("help.retry")),
+                ("/rewind [n]", t("help.rewind")),
+                ("/undo [--force]", t("help.undo")),
+                ("/expand [n]", t("help.expand")),
+                ("/copy last|all|code", t("help.copy")),
+                ("/extract [dir]", t("help.extract")),
+                ("/export [path]", t("help.export")),
+                ("/title [text|--clear]", t("help.title_cmd")),
+                ("/review [path|--staged]", t("help.review")),
+                ("/todos", t("help.todos")),
+                ("/context [clear]", t("help.context")),
+                ("/refresh-cmd", t("help.refresh_cmd")),
+                ("/auto on|off", t("help.auto")),
+                ("/help", t("help.help")),
+                ("Ctrl+D", t("help.exit")),
+            ] {
+                renderer.notice(
+                    NoticeLevel::Plain,
+                    &format!("  {} - {}", name.cyan(), description),
+                );
+            }
+            let mut custom = super::custom::load();
+            custom.retain(|name, _| !BUILTIN_COMMANDS.contains(&name.as_str()));
+            if !custom.is_empty() {
+                renderer.notice(NoticeLevel::Plain, &t("help.custom").bold().to_string());
+                for command in custom.values() {
+                    renderer.notice(
+                        NoticeLevel::Plain,
+                        &format!(
+                            "  {} - {} {}",
+                            format!("/{}", command.name).cyan(),
+                            command.description,
+                            format!("({})", command.path.display()).dimmed()
+                        ),
+                    );
+                }
+            }
+            Ok(CommandAction::Continue)
+        }
+        "/retry" => {
+            let (model, instruction) = match args.strip_prefix("--model") {
+                Some(rest) if rest.is_empty() || rest.starts_with(' ') => {
+                    let rest = rest.trim_start();
+                    let (model, instruction) = rest.split_once(' ').unwrap_or((rest, ""));
+                    if model.is_empty() {
+                        renderer.notice(
+                            NoticeLevel::Plain,
+                            "Usage: /retry [--model <[provider/]model>] [instruction]",
+                        );
+                        return Ok(CommandAction::Continue);
+                    }
+                    (Some(model), instruction.trim())
+                }
+                _ => (None, args),
+            };
+            match engine.regenerate(model, instruction) {
+                Ok(Some(prompt)) => Ok(CommandAction::Send(prompt)),
+                Ok(None) => {
+                    renderer.notice(NoticeLevel::Info, t("retry.nothing"));
+                    Ok(CommandAction::Continue)
+                }
+                Err(e) => {
+                    renderer.render_error(&format!("retry failed: {}", e));
+                    Ok(CommandAction::Continue)
+                }
+            }
+        }
+        "/rewind" => {
+            let turns = if args.is_empty() {
+                Ok(1)
+            } else {
+                args.parse::<usize>()
+            };
+            match turns {
+                Ok(turns) => match engine.rewind(turns) {
+                    Ok(messages) => renderer.notice(
+                        NoticeLevel::Info,
+                        &tf(
+                            "rewind.done",
+                            &[
+                                ("turns", &plural(turns, "turn", "turns")),
+                                ("messages", &plural(messages, "message", "messages")),
+                            ],
+                        ),
+                    ),
+                    Err(e) => renderer.notice(NoticeLevel::Warning, &e.to_string()),
+                },
+                Err(_) => renderer.notice(NoticeLevel::Plain, "Usage: /rewind [n]"),
+            }
+            Ok(CommandAction::Continue)
+        }
+        "/expand" => {
+            let count = if args.is_empty() {
+                Ok(1)
+            } else {
+                args.parse::<usize>()
+            };
+            let Ok(count) = count else {
+                renderer.notice(NoticeLevel::Plain, "Usage: /expand [n]");
+                return Ok(CommandAction::Continue);
+            };
+            let calls: Vec<_> = engine.recent_tool_calls(count).collect();
+            if calls.is_empty() {
+                renderer.notice(NoticeLevel::Info, t("expand.nothing"));
+            }
+            for call in calls {
+                let args = serde_json::to_string_pretty(&call.arguments)
+                    .unwrap_or_else(|_| call.arguments.to_string());
+                let result = call.result.as_deref().unwrap_or("(no result)");
+                renderer.notice(
+                    NoticeLevel::Plain,
+                    &format!("{} {}\n{}\n", call.name.bold(), args, result),
+                );
+            }
+            Ok(CommandAction::Continue)
+        }
+        "/undo" => {
+            let force = match args {
+                "" => false,
+                "--force" => true,
+                _ => {
+                    renderer.notice(NoticeLevel::Plain, "Usage: /undo [--force]");
+                    return Ok(CommandAction::Continue);
+                }
+            };
+            match engine.undo_file_change(force).await {
+                Ok(Some(undone)) => {
+                    let cwd = std::env::current_dir().unwrap_or_default();
+                    let files: Vec<String> = undone
+                        .paths
+                        .iter()
+                        .map(|p| p.strip_prefix(&cwd).unwrap_or(p).display().to_string())
+                        .collect();
+                    renderer.notice(
+                        NoticeLevel::Info,
+                        &tf(
+                            "undo.done",
+                            &[("tool", &undone.tool), ("files", &files.join(", "))],
+                        ),
+                    );
+                }
+                Ok(None) => renderer.notice(NoticeLevel::Plain, t("undo.nothing")),
+                Err(e) => renderer.notice(NoticeLevel::Warning, &e.to_string()),
+            }
+            Ok(CommandAction::Continue)
+        }
+        "/extract" => {
+            extract_code(engine, renderer, args).await?;
+            Ok(CommandAction::Continue)
+        }
+        "/export" => {
+            let path = if args.is_empty() {
+                let id = &engine.session.id;
+                format!("kaze-session-{}.md", &id[..8.min(id.len())])
+            } else {
+                args.to_string()
+            };
+            let markdown = crate::session::export::render_markdown(&engine.session, false);
+            match async_fs::write(&path, markdown).await {
+                Ok(()) => {
+                    renderer.notice(NoticeLevel::Info, &tf("export.done", &[("path", &path)]))
+                }
+                Err(e) => renderer.notice(
+                    NoticeLevel::Warning,
+                    &format!("Failed to write {}: {}", path, e),
+                ),
+            }
+            Ok(CommandAction::Continue)
+        }
+        "/copy" => {
+            let what = if args.is_empty() { "last" } else { args };
+            match copy_selection(&engine.session.messages, what) {
+                Ok((text, label)) => match crate::clipboard::copy(&text) {
+                    Ok(method) => renderer.notice(
+                        NoticeLevel::Info,
+                        &tf(
+                            "copy.done",
+                            &[("what", label), ("method", &method.to_string())],
+                        ),
+                    ),
+                    Err(e) => renderer.render_error(&format!("copy failed: {:#}", e)),
+                },
+                Err(message) => renderer.notice(NoticeLevel::Info, &message),
+            }
+            Ok(CommandAction::Continue)
+        }
+        "/title" => {
+            if args.is_empty() {
+                let title = engine.session.title();
+                renderer.notice(
+                    NoticeLevel::Plain,
+                    &tf(
+                        "title.current",
+                        &[("title", title.as_deref().unwrap_or("(untitled)"))],
+                    ),
+                );
+                return Ok(CommandAction::Continue);
+            }
+            let title = (args != "--clear").then_some(args);
+            match engine.session.rename(title) {
+                Ok(()) => renderer.notice(
+                    NoticeLevel::Info,
+                    &tf(
+                        "title.set",
+                        &[(
+                            "title",
+                            engine.session.title().as_deref().unwrap_or("(untitled)"),
+                        )],
+                    ),
+                ),
+                Err(e) => renderer.render_error(&format!("title failed: {:#}", e)),
+            }
+            Ok(CommandAction::Continue)
+        }
+        "/review" => {
+            let extra = engine.config.prompt.modes.get("review").cloned();
+            match super::review::prepare(args, &engine.config.model, extra.as_deref()).await {
+                Ok(review) => {
+                    renderer.notice(
+                        NoticeLevel::Info,
+                        &tf("review.reviewing", &[("summary", &review.summary)]),
+                    );
+                    Ok(CommandAction::Send(review.message))
+                }
+                Err(e) => {
+                    renderer.render_error(&format!("review failed: {:#}", e));
+                    Ok(CommandAction::Continue)
+                }
+            }
+        }
+        "/context" => {
+            match args {
+                "" => {
+                    let files = engine.tracked_files();
+                    if files.is_empty() {
+                        renderer.notice(NoticeLevel::Info, t("context.empty"));
+                    } else {
+                        renderer.notice(NoticeLevel::Plain, &files.join("\n"));
+                    }
+                }
+                "clear" => {
+                    engine.clear_tracked_files();
+                    renderer.notice(NoticeLevel::Info, t("context.cleared"));
+                }
+                _ => renderer.notice(NoticeLevel::Plain, "Usage: /context [clear]"),
+            }
+            Ok(CommandAction::Continue)
+        }
+        "/auto" => {
+            match args {
+                "on" => {
+                    engine.set_auto_approve(true);
+                    renderer.notice(
+                        NoticeLevel::Critical,
+                        &format!("{} {}", t("auto.banner"), t("auto.off_hint")),
+                    );
+                }
+                "off" => {
+                    engine.set_auto_approve(false);
+                    renderer.notice(NoticeLevel::Info, t("auto.off"));
+                }
+                "" if engine.auto_approve() => renderer.notice(
+                    NoticeLevel::Critical,
+                    &format!("{} {}", t("auto.banner"), t("auto.off_hint")),
+                ),
+                "" => renderer.notice(NoticeLevel::Info, t("auto.status_off")),
+                _ => renderer.notice(NoticeLevel::Plain, "Usage: /auto [on|off]"),
+            }
+            Ok(CommandAction::Continue)
+        }
+        "/todos" => {
+            let items = engine.todos().items();
+            if items.is_empty() {
+                renderer.notice(NoticeLevel::Info, t("todos.empty"));
+            } else {
+                renderer.notice(NoticeLevel::Plain, &format_todos(&items));
+            }
+            Ok(CommandAction::Continue)
+        }
+        "/refresh-cmd" => {
+            match engine.refresh_attachment().await {
+                Ok(Some(summary)) => renderer.notice(
+                    NoticeLevel::Info,
+                    &tf("attach.attached", &[("summary", &summary)]),
+                ),
+                Ok(None) => renderer.notice(NoticeLevel::Info, t("attach.none")),
+                Err(e) => renderer.render_error(&format!("{:#}", e)),
+            }
             Ok(CommandAction::Continue)
         }
         "/compact" => {
-            match context::perform_compaction(
-                session,
-                provider,
-                model_name,
-                keep_recent,
-                "Compacted",
-                "compaction",
-            )
-            .await
-            {
+            match engine.compact(renderer).await {
                 Ok(CompactionResult::NothingToCompact) => {
-                    eprintln!("{}", "Nothing to compact.".dimmed());
+                    renderer.notice(NoticeLevel::Info, t("compact.nothing"));
                 }
                 Ok(CompactionResult::Compacted { .. }) => {}
                 Err(e) => {
-                    eprintln!("{} compaction failed: {}", "error:".red().bold(), e);
+                    renderer.render_error(&format!("compaction failed: {}", e));
+                }
+            }
+            Ok(CommandAction::Continue)
+        }
+        "/model" => {
+            if args.is_empty() {
+                renderer.notice(
+                    NoticeLevel::Plain,
+                    &format!(
+                        "Current model: {}. Usage: /model <[provider/]model>",
+                        engine.model_name().yellow()
+                    ),
+                );
+                return Ok(CommandAction::Continue);
+            }
+            match engine.switch_model(args) {
+                Ok(selection) => {
+                    renderer.notice(
+                        NoticeLevel::Info,
+                        &format!(
+                            "Switched to {}/{}",
+                            selection.provider.as_str(),
+                            selection.model
+                        ),
+                    );
+                    // The new model may have a smaller context window
+                    engine.manage_context(renderer).await?;
+                }
+                Err(e) => renderer.render_error(&format!("model switch failed: {}", e)),
+            }
+            Ok(CommandAction::Continue)
+        }
+        "/view" => {
+            match view_file(args).await {
+                Ok(text) => renderer.notice(NoticeLevel::Plain, &text),
+                Err(e) => renderer.render_error(&format!("view failed: {:#}", e)),
+            }
+            Ok(CommandAction::Continue)
+        }
+        "/rate" => {
+            let (rating, note) = args
+                .split_once(char::is_whitespace)
+                .map(|(rating, note)| (rating, Some(note.trim().to_string())))
+                .unwrap_or((args, None));
+            let rating = match rating.parse::<Rating>() {
+                Ok(rating) => rating,
+                Err(_) => {
+                    renderer.notice(NoticeLevel::Plain, "Usage: /rate good|bad [note]");
+                    return Ok(CommandAction::Continue);
                 }
+            };
+            match engine.session.annotate(rating, note) {
+                Ok(_) => renderer.notice(
+                    NoticeLevel::Info,
+                    &format!("Rated the last response {}.", rating),
+                ),
+                Err(e) => renderer.render_error(&format!("rate failed: {:#}", e)),
             }
             Ok(CommandAction::Continue)
         }
-        _ => Ok(CommandAction::Unknown(command.to_string())),
+        "/prompt" => {
+            if !matches!(args, "" | "show") {
+                renderer.notice(NoticeLevel::Plain, "Usage: /prompt show");
+                return Ok(CommandAction::Continue);
+            }
+            show_prompt(engine, renderer);
+            Ok(CommandAction::Continue)
+        }
+        _ => match name.strip_prefix('/').and_then(super::custom::find) {
+            Some(custom) => Ok(CommandAction::Send(custom.expand(args))),
+            None => Ok(CommandAction::Unknown(command.to_string())),
+        },
+    }
+}
+
+/// The text `/copy <what>` copies and a label for the confirmation, or a
+/// message explaining why there is nothing to copy.
+fn copy_selection(messages: &[Message], what: &str) -> Result<(String, &'static str), String> {
+    let last = messages
+        .iter()
+        .rev()
+        .find(|m| m.role == Role::Assistant && !m.text().trim().is_empty());
+    match what {
+        "last" => match last {
+            Some(msg) => Ok((msg.text().to_string(), "the last response")),
+            None => Err(t("copy.nothing").to_string()),
+        },
+        "all" => {
+            let transcript: Vec<String> = messages
+                .iter()
+                .filter(|m| matches!(m.role, Role::User | Role::Assistant))
+                .filter(|m| !m.text().trim().is_empty())
+                .map(|m| {
+                    let role = if m.role == Role::User {
+                        "User"
+                    } else {
+                        "Assistant"
+                    };
+                    format!("## {}\n\n{}", role, m.text().trim_end())
+                })
+                .collect();
+            if transcript.is_empty() {
+                return Err(t("copy.nothing").to_string());
+            }
+            Ok((transcript.join("\n\n"), "the transcript"))
+        }
+        "code" => {
+            let Some(msg) = last else {
+                return Err(t("copy.nothing").to_string());
+            };
+            let blocks = format::code_blocks(msg.text());
+            if blocks.is_empty() {
+                return Err(t("copy.no_code").to_string());
+            }
+            let label = if blocks.len() == 1 {
+                "1 code block"
+            } else {
+                "the code blocks"
+            };
+            Ok((blocks.join("\n\n"), label))
+        }
+        _ => Err(t("copy.usage").to_string()),
+    }
+}
+
+/// Writes the last response's code blocks to the files they name, under
+/// `dir` (default: the current directory), once the user confirms.
+async fn extract_code(engine: &ChatEngine, renderer: &mut dyn Renderer, dir: &str) -> Result<()> {
+    let Some(last) = engine
+        .session
+        .messages
+        .iter()
+        .rev()
+        .find(|m| m.role == Role::Assistant)
+    else {
+        renderer.notice(NoticeLevel::Plain, t("extract.nothing"));
+        return Ok(());
+    };
+    let dir = if dir.is_empty() {
+        std::env::current_dir()?
+    } else {
+        std::path::PathBuf::from(dir)
+    };
+    let (planned, unnamed) = super::extract::plan(last.text(), &dir);
+    if planned.is_empty() {
+        let message = if unnamed == 0 {
+            t("copy.no_code").to_string()
+        } else {
+            tf(
+                "extract.no_names",
+                &[("blocks", &plural(unnamed, "code block", "code blocks"))],
+            )
+        };
+        renderer.notice(NoticeLevel::Plain, &message);
+        return Ok(());
+    }
+
+    for file in &planned {
+        let state = if file.exists { "overwrite" } else { "new" };
+        renderer.notice(
+            NoticeLevel::Plain,
+            &format!(
+                "  {} ({}, {})",
+                file.path.display(),
+                state,
+                plural(file.code.lines().count(), "line", "lines")
+            ),
+        );
+    }
+    if unnamed > 0 {
+        renderer.notice(
+            NoticeLevel::Plain,
+            &tf(
+                "extract.skipped",
+                &[("blocks", &plural(unnamed, "code block", "code blocks"))],
+            ),
+        );
+    }
+    let files = plural(planned.len(), "file", "files");
+    if !engine
+        .confirm(&tf("extract.confirm", &[("files", &files)]))
+        .await?
+    {
+        renderer.notice(NoticeLevel::Info, t("extract.cancelled"));
+        return Ok(());
+    }
+    match engine.write_extracted(&planned).await {
+        Ok(()) => renderer.notice(NoticeLevel::Info, &tf("extract.done", &[("files", &files)])),
+        Err(e) => renderer.notice(NoticeLevel::Warning, &format!("{:#}", e)),
+    }
+    Ok(())
+}
+
+/// Prints each system prompt layer under a heading, and notes when the
+/// session was started with a different prompt than the current config
+/// composes.
+fn show_prompt(engine: &ChatEngine, renderer: &mut dyn Renderer) {
+    let config = &engine.config;
+    let layers = config.prompt_layers();
+    if layers.is_empty() {
+        renderer.notice(NoticeLevel::Info, "No system prompt is configured.");
+    }
+    for (layer, text) in &layers {
+        let heading = match (layer, config.prompt.mode.as_deref()) {
+            (crate::config::PromptLayer::Mode, Some(mode)) => format!("[mode: {}]", mode),
+            _ => format!("[{}]", layer),
+        };
+        renderer.notice(
+            NoticeLevel::Plain,
+            &format!("{}\n{}\n", heading.cyan().bold(), text),
+        );
+    }
+    if let Some(mode) = config.prompt.mode.as_deref() {
+        if !config.prompt.modes.contains_key(mode) {
+            renderer.notice(
+                NoticeLevel::Warning,
+                &format!("Mode '{}' is not defined in [prompt.modes].", mode),
+            );
+        }
+    }
+
+    let session_prompt = engine
+        .session
+        .messages
+        .iter()
+        .find(|m| m.role == Role::System)
+        .map(|m| m.text().to_string());
+    if session_prompt != config.system_preamble() {
+        renderer.notice(
+            NoticeLevel::Info,
+            "This session was started with a different system prompt; start a new session to use the one above.",
+        );
+    }
+}
+
+/// Renders a file (or line range) with numbered, highlighted lines for
+/// `/view`. Nothing is sent to the model.
+///
+/// `args` is `<path> [start:end]` with 1-based inclusive bounds; either
+/// bound may be omitted (`10:`, `:40`). Without a range, output stops at
+/// [`VIEW_MAX_LINES`].
+async fn view_file(args: &str) -> Result<String> {
+    let mut parts = args.split_whitespace();
+    let path_arg = parts
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("usage: /view <path> [start:end]"))?;
+    let range = parts.next().map(parse_range).transpose()?;
+
+    let path = Path::new(path_arg);
+    let bytes = async_fs::read(path)
+        .await
+        .with_context(|| format!("cannot read {}", path_arg))?;
+    let check_len = bytes.len().min(BINARY_DETECTION_BYTES);
+    anyhow::ensure!(!bytes[..check_len].contains(&0), "binary file");
+    let content = String::from_utf8_lossy(&bytes);
+    let lines: Vec<&str> = content.lines().collect();
+    if lines.is_empty() {
+        return Ok(format!("{} {}", path.display(), "(empty file)".dimmed()));
+    }
+
+    let (start, end) = match range {
+        Some((start, end)) => (start.max(1), end.unwrap_or(lines.len()).min(lines.len())),
+        None => (1, lines.len().min(VIEW_MAX_LINES)),
+    };
+    anyhow::ensure!(
+        start <= end,
+        "range is outside the file ({} lines)",
+        lines.len()
+    );
+
+    let lang = Language::from_path(path);
+    let width = end.to_string().len();
+    let mut out = format!("{}\n", path.display().to_string().bold());
+    for (n, line) in lines[start - 1..end].iter().enumerate() {
+        let number = format!("{:>width$}", start + n);
+        out.push_str(&format!(
+            "{} {}\n",
+            number.dimmed(),
+            highlight::highlight_line(line, lang)
+        ));
+    }
+    if range.is_none() && lines.len() > end {
+        out.push_str(
+            &format!(
+                "... {} more lines (use /view {} {}:)",
+                lines.len() - end,
+                path_arg,
+                end + 1
+            )
+            .dimmed()
+            .to_string(),
+        );
+    }
+    Ok(out.trim_end().to_string())
+}
+
+/// Parses `start:end`, `start:`, `:end`, or a single line number.
+fn parse_range(spec: &str) -> Result<(usize, Option<usize>)> {
+    let parse = |s: &str| -> Result<usize> {
+        s.parse()
+            .map_err(|_| anyhow::anyhow!("invalid line number: {}", s))
+    };
+    match spec.split_once(':') {
+        Some((start, end)) => {
+            let start = if start.is_empty() { 1 } else { parse(start)? };
+            let end = if end.is_empty() {
+                None
+            } else {
+                Some(parse(end)?)
+            };
+            Ok((start, end))
+        }
+        None => {
+            let line = parse(spec)?;
+            Ok((line, Some(line)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_copy_selection_takes_the_last_response_or_its_code() {
+        let messages = [
+            Message::user("Write a script"),
+            Message::assistant("Here:\n\n```sh\necho one\n```\n\nand\n\n```py\nprint(2)\n```\n"),
+            Message::user("Thanks"),
+            Message::assistant(""),
+        ];
+        let (last, _) = copy_selection(&messages, "last").unwrap();
+        assert!(last.starts_with("Here:"));
+        let (code, label) = copy_selection(&messages, "code").unwrap();
+        assert_eq!(code, "echo one\n\nprint(2)");
+        assert_eq!(label, "the code blocks");
+
+        assert!(copy_selection(&messages[..1], "code").is_err());
+        assert!(copy_selection(&messages, "everything").is_err());
     }
 }
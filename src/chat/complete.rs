@@ -0,0 +1,83 @@
+//! Tab completion of repository identifiers in the chat REPL.
+//!
+//! The word before the cursor is completed from a [`SymbolIndex`] after an
+//! `@` (`@parse_pa` → `@parse_patch`), or in plain text once at least
+//! [`SYMBOL_COMPLETE_MIN_PREFIX`] characters are typed. The index is built
+//! on a background thread at startup, or read from the cache; until it is
+//! ready, Tab does nothing.
+
+use rustyline::completion::{Completer, Pair};
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::{Context, Helper};
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+
+use crate::constants::{SYMBOL_COMPLETE_MAX_CANDIDATES, SYMBOL_COMPLETE_MIN_PREFIX};
+use crate::symbols::SymbolIndex;
+
+/// rustyline helper that completes identifiers defined in the project.
+pub struct ChatHelper {
+    index: Arc<OnceLock<SymbolIndex>>,
+}
+
+impl ChatHelper {
+    /// Starts indexing `root` in the background.
+    pub fn new(root: PathBuf) -> Self {
+        let index = Arc::new(OnceLock::new());
+        let slot = Arc::clone(&index);
+        std::thread::spawn(move || {
+            let _ = slot.set(SymbolIndex::load(&root));
+        });
+        Self { index }
+    }
+}
+
+impl Completer for ChatHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let Some(index) = self.index.get() else {
+            return Ok((pos, Vec::new()));
+        };
+        // Slash commands take paths and model names, not identifiers
+        if line.starts_with('/') {
+            return Ok((pos, Vec::new()));
+        }
+        let before = &line[..pos];
+        let start = before
+            .rfind(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .map_or(0, |i| i + 1);
+        let word = &before[start..];
+        let mentioned = before[..start].ends_with('@');
+        if word.is_empty() || (!mentioned && word.len() < SYMBOL_COMPLETE_MIN_PREFIX) {
+            return Ok((pos, Vec::new()));
+        }
+
+        let candidates = index
+            .complete(word, SYMBOL_COMPLETE_MAX_CANDIDATES)
+            .into_iter()
+            .map(|name| Pair {
+                display: name.to_string(),
+                replacement: name.to_string(),
+            })
+            .collect();
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for ChatHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ChatHelper {}
+
+impl Validator for ChatHelper {}
+
+impl Helper for ChatHelper {}
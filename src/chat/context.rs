@@ -1,43 +1,47 @@
 //! Context management for the chat REPL.
 //!
-//! Handles token counting display, auto-compaction decisions,
-//! truncation of oldest messages, and the compaction helper.
+//! Handles token counting display, auto-compaction before each request, the rolling
+//! window (`context.strategy = "rolling"`), truncation of oldest messages,
+//! and the compaction helper.
 
 use anyhow::Result;
-use colored::Colorize;
 
 use crate::compaction::{self, CompactionResult};
-use crate::config::Config;
+use crate::config::{Config, ContextStrategy};
 use crate::message::{Message, Role};
+use crate::output::{NoticeLevel, Renderer};
 use crate::provider::Provider;
 use crate::session::Session;
 use crate::tokens::ContextStatus;
 
-/// Handle token counting display and auto-compaction after a successful response.
+/// Handle token counting display after a successful response.
 ///
-/// Counts tokens across the full conversation, displays usage with appropriate
-/// coloring, and triggers compaction or truncation when context limits are reached.
+/// Counts tokens across the full conversation and displays usage with
+/// appropriate coloring. Compaction waits for the next request (see
+/// [`compact_before_request`]), which is the one that has to fit.
+///
+/// With the rolling strategy, messages that left the window are folded into
+/// the running summary first.
 pub(crate) async fn handle_context_management(
     session: &mut Session,
     provider: &Provider,
     model_name: &str,
     config: &Config,
+    renderer: &mut dyn Renderer,
 ) -> Result<()> {
-    // Count tokens across the full conversation
-    let msg_pairs: Vec<(String, String)> = session
-        .messages
-        .iter()
-        .map(|m| (m.role.to_string(), m.text().to_string()))
-        .collect();
-    let token_count = crate::tokens::count_conversation_tokens(&msg_pairs, model_name)?;
-    let status = crate::tokens::check_context_usage(token_count, model_name);
+    if config.context_strategy() == ContextStrategy::Rolling {
+        let window = config.rolling_window_tokens(model_name);
+        if let Err(e) = perform_rolling(session, provider, model_name, window, renderer).await {
+            renderer.warn(&format!("rolling summary failed: {}", e));
+        }
+    }
 
-    let mut already_compacted = false;
-    match status {
+    let token_count = count_tokens(session, model_name)?;
+    match crate::tokens::check_context_usage(token_count, model_name) {
         ContextStatus::Ok { used, limit } => {
-            println!(
-                "{}",
-                format!("Tokens: {}", crate::tokens::format_token_usage(used, limit)).dimmed()
+            renderer.notice(
+                NoticeLevel::Info,
+                &format!("Tokens: {}", crate::tokens::format_token_usage(used, limit)),
             );
         }
         ContextStatus::Warning {
@@ -45,14 +49,13 @@ pub(crate) async fn handle_context_management(
             limit,
             percent,
         } => {
-            println!(
-                "{}",
-                format!(
+            renderer.notice(
+                NoticeLevel::Warning,
+                &format!(
                     "Tokens: {} ({}%) -- consider /compact",
                     crate::tokens::format_token_usage(used, limit),
                     percent,
-                )
-                .yellow()
+                ),
             );
         }
         ContextStatus::Critical {
@@ -60,73 +63,99 @@ pub(crate) async fn handle_context_management(
             limit,
             percent,
         } => {
-            println!(
-                "{}",
-                format!(
-                    "Tokens: {} ({}%) -- compacting...",
+            renderer.notice(
+                NoticeLevel::Critical,
+                &format!(
+                    "Tokens: {} ({}%) -- compacting before the next request",
                     crate::tokens::format_token_usage(used, limit),
                     percent,
-                )
-                .red()
+                ),
             );
+        }
+    }
+    Ok(())
+}
+
+/// Compacts the conversation, just before it is sent, when the request
+/// would otherwise go out too large.
+///
+/// Auto-compaction runs once usage reaches `compaction.auto_threshold` of
+/// the effective limit (the context window less `compaction.reserved`); it
+/// is skipped with the rolling strategy, which keeps its own window. If
+/// usage is still critical afterwards, compaction is tried regardless of
+/// those settings, and the oldest messages are truncated if even that
+/// leaves it critical.
+pub(crate) async fn compact_before_request(
+    session: &mut Session,
+    provider: &Provider,
+    model_name: &str,
+    config: &Config,
+    renderer: &mut dyn Renderer,
+) -> Result<()> {
+    let mut compacted = false;
+    let rolling = config.context_strategy() == ContextStrategy::Rolling;
+    if !rolling && config.compaction_auto_enabled() {
+        let limit = crate::tokens::context_window_size(model_name);
+        let effective_limit = limit.saturating_sub(config.compaction_reserved());
+        let ratio = count_tokens(session, model_name)? as f64 / effective_limit.max(1) as f64;
+        if ratio >= config.compaction_threshold() {
             match perform_compaction(
                 session,
                 provider,
                 model_name,
                 config.compaction_keep_recent(),
-                "Compacted",
-                "compaction",
+                "Auto-compacted",
+                "auto_compaction",
+                renderer,
             )
             .await
             {
-                Ok(CompactionResult::Compacted { .. }) => {
-                    already_compacted = true;
-                }
-                Ok(CompactionResult::NothingToCompact) => {
-                    // Fallback to truncation if compaction has nothing to do
-                    truncate_oldest_messages(&mut session.messages, model_name);
-                }
-                Err(_) => {
-                    // Fallback to truncation if compaction fails
-                    truncate_oldest_messages(&mut session.messages, model_name);
-                }
+                Ok(result) => compacted = matches!(result, CompactionResult::Compacted { .. }),
+                Err(e) => renderer.warn(&format!("auto-compaction failed: {}", e)),
             }
         }
     }
 
-    // Auto-compaction: trigger when usage exceeds threshold
-    if !already_compacted && config.compaction_auto_enabled() {
-        let limit = crate::tokens::context_window_size(model_name);
-        let reserved = config.compaction_reserved();
-        let effective_limit = limit.saturating_sub(reserved);
-        let ratio = token_count as f64 / effective_limit.max(1) as f64;
-        if ratio >= config.compaction_threshold() {
-            match perform_compaction(
+    let critical = |session: &Session| -> Result<bool> {
+        let used = count_tokens(session, model_name)?;
+        Ok(matches!(
+            crate::tokens::check_context_usage(used, model_name),
+            ContextStatus::Critical { .. }
+        ))
+    };
+    if critical(session)? {
+        if !compacted {
+            if let Err(e) = perform_compaction(
                 session,
                 provider,
                 model_name,
                 config.compaction_keep_recent(),
-                "Auto-compacted",
-                "auto_compaction",
+                "Compacted",
+                "compaction",
+                renderer,
             )
             .await
             {
-                Ok(CompactionResult::Compacted { .. }) => {}
-                Ok(CompactionResult::NothingToCompact) => {}
-                Err(e) => {
-                    eprintln!(
-                        "{} auto-compaction failed: {}",
-                        "warning:".yellow().bold(),
-                        e
-                    );
-                }
+                renderer.warn(&format!("compaction failed: {}", e));
             }
         }
+        if critical(session)? {
+            truncate_oldest_messages(&mut session.messages, model_name);
+        }
     }
-
     Ok(())
 }
 
+/// Tokens across the whole conversation.
+fn count_tokens(session: &Session, model_name: &str) -> Result<usize> {
+    let msg_pairs: Vec<(String, String)> = session
+        .messages
+        .iter()
+        .map(|m| (m.role.to_string(), m.text().to_string()))
+        .collect();
+    crate::tokens::count_conversation_tokens(&msg_pairs, model_name)
+}
+
 /// Remove the oldest non-system messages until under 70% of context window.
 pub(crate) fn truncate_oldest_messages(messages: &mut Vec<Message>, model: &str) {
     let limit = crate::tokens::context_window_size(model);
@@ -160,28 +189,55 @@ pub(crate) async fn perform_compaction(
     keep_recent: usize,
     label: &str,
     event_name: &str,
+    renderer: &mut dyn Renderer,
 ) -> Result<CompactionResult> {
     let result =
         compaction::compact(&mut session.messages, provider, model_name, keep_recent).await?;
+    report(session, &result, label, event_name, renderer);
+    Ok(result)
+}
 
+/// Fold messages that left the rolling window into the running summary.
+///
+/// Reported like compaction, under a `rolling_summary` session event.
+pub(crate) async fn perform_rolling(
+    session: &mut Session,
+    provider: &Provider,
+    model_name: &str,
+    window_tokens: usize,
+    renderer: &mut dyn Renderer,
+) -> Result<CompactionResult> {
+    let result =
+        compaction::roll(&mut session.messages, provider, model_name, window_tokens).await?;
+    report(session, &result, "Summarized", "rolling_summary", renderer);
+    Ok(result)
+}
+
+/// Print a `Compacted` result's summary line and record it as a session event.
+fn report(
+    session: &mut Session,
+    result: &CompactionResult,
+    label: &str,
+    event_name: &str,
+    renderer: &mut dyn Renderer,
+) {
     if let CompactionResult::Compacted {
         messages_removed,
         tokens_before,
         tokens_after,
-    } = &result
+    } = result
     {
         let saved = tokens_before.saturating_sub(*tokens_after);
-        eprintln!(
-            "{}",
-            format!(
+        renderer.notice(
+            NoticeLevel::Info,
+            &format!(
                 "{} {} messages ({} → {} tokens, saved {})",
                 label,
                 messages_removed,
                 crate::tokens::format_number(*tokens_before),
                 crate::tokens::format_number(*tokens_after),
                 crate::tokens::format_number(saved),
-            )
-            .dimmed()
+            ),
         );
         let _ = session.append_event(&serde_json::json!({
             "event": event_name,
@@ -190,6 +246,4 @@ pub(crate) async fn perform_compaction(
             "tokens_after": tokens_after,
         }));
     }
-
-    Ok(result)
 }
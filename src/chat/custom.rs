@@ -0,0 +1,137 @@
+//! User-defined slash commands: prompt templates in
+//! `~/.config/kaze/commands/*.md` and the project's `.kaze/commands/*.md`.
+//!
+//! `triage.md` becomes `/triage`. Its contents are sent as the message,
+//! with `$ARGUMENTS` replaced by whatever follows the command name; if the
+//! template doesn't mention `$ARGUMENTS`, the arguments are appended. The
+//! first non-empty line doubles as the description shown in `/help`.
+//! Project commands win over global ones of the same name. Built-in
+//! commands win over both, so a checked-in `.kaze/commands/undo.md` can't
+//! stand in for `/undo`; chat warns about such files when it starts.
+//! Files are reread on every use, so edits apply without restarting.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::constants::{COMMANDS_DIRNAME, COMMAND_ARGUMENTS, PROJECT_COMMANDS_DIR};
+
+/// A prompt template invoked as `/name`.
+#[derive(Debug, Clone)]
+pub struct CustomCommand {
+    pub name: String,
+    pub description: String,
+    pub template: String,
+    pub path: PathBuf,
+}
+
+impl CustomCommand {
+    /// The message to send for `/name args`.
+    pub fn expand(&self, args: &str) -> String {
+        if self.template.contains(COMMAND_ARGUMENTS) {
+            self.template.replace(COMMAND_ARGUMENTS, args)
+        } else if args.is_empty() {
+            self.template.clone()
+        } else {
+            format!("{}\n\n{}", self.template.trim_end(), args)
+        }
+    }
+}
+
+/// The global and project commands, by name. Unreadable directories and
+/// files are skipped.
+pub fn load() -> BTreeMap<String, CustomCommand> {
+    let mut dirs = Vec::new();
+    if let Ok(dir) = Config::config_dir() {
+        dirs.push(dir.join(COMMANDS_DIRNAME));
+    }
+    if let Ok(root) = Config::project_root_dir() {
+        dirs.push(root.join(PROJECT_COMMANDS_DIR));
+    }
+    let mut commands = BTreeMap::new();
+    for dir in dirs {
+        for command in load_dir(&dir) {
+            commands.insert(command.name.clone(), command);
+        }
+    }
+    commands
+}
+
+/// Finds the command `/name`.
+pub fn find(name: &str) -> Option<CustomCommand> {
+    load().remove(name)
+}
+
+/// The commands in `dir`, in no particular order.
+pub(crate) fn load_dir(dir: &Path) -> Vec<CustomCommand> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "md"))
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_str()?.to_string();
+            let valid = !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "-_".contains(c));
+            if !valid {
+                return None;
+            }
+            let template = std::fs::read_to_string(&path).ok()?;
+            let description = template
+                .lines()
+                .map(|line| line.trim().trim_start_matches('#').trim())
+                .find(|line| !line.is_empty())
+                .unwrap_or_default()
+                .to_string();
+            Some(CustomCommand {
+                name,
+                description,
+                template,
+                path,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_command_load_dir_and_expand() {
+        let dir = std::env::temp_dir().join(format!("kaze_test_commands_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("review.md"),
+            "\n# Review a file\n\nReview $ARGUMENTS for bugs.\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("plain.md"), "Summarize the changes.\n").unwrap();
+        // Not a command: wrong extension, and a name with a space
+        std::fs::write(dir.join("notes.txt"), "ignored").unwrap();
+        std::fs::write(dir.join("two words.md"), "ignored").unwrap();
+
+        let mut commands = load_dir(&dir);
+        commands.sort_by(|a, b| a.name.cmp(&b.name));
+        let names: Vec<&str> = commands.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["plain", "review"]);
+
+        let (plain, review) = (&commands[0], &commands[1]);
+        assert_eq!(review.description, "Review a file");
+        assert_eq!(
+            review.expand("src/main.rs"),
+            "\n# Review a file\n\nReview src/main.rs for bugs.\n"
+        );
+        assert_eq!(plain.expand(""), "Summarize the changes.\n");
+        assert_eq!(
+            plain.expand("only the tests"),
+            "Summarize the changes.\n\nonly the tests"
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
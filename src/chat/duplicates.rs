@@ -0,0 +1,144 @@
+//! Spotting a question that was already asked, in this session or a
+//! recent one, so the earlier answer can be pointed to before the same
+//! tokens are spent again.
+//!
+//! Questions are compared by the distinct words they share (Jaccard
+//! similarity), which catches rephrasings that only reorder or change a
+//! word or two without needing an embedding model.
+
+use std::collections::HashSet;
+
+use crate::constants::{
+    DUPLICATE_ANSWER_PREVIEW_CHARS, DUPLICATE_MAX_CHARS, DUPLICATE_MIN_WORDS,
+    DUPLICATE_SCAN_SESSIONS, DUPLICATE_SIMILARITY,
+};
+use crate::i18n::tf;
+use crate::message::{Message, Role};
+use crate::session::Session;
+
+/// A question and the answer it got.
+pub(crate) struct Exchange {
+    /// The session it was asked in; `None` for the current one.
+    session: Option<String>,
+    /// Last update of that session, as RFC 3339.
+    when: String,
+    question: String,
+    answer: String,
+    words: HashSet<String>,
+}
+
+/// Questions and answers from `messages`, skipping those too long or too
+/// short to compare.
+fn exchanges(messages: &[Message], session: Option<&str>, when: &str) -> Vec<Exchange> {
+    let mut found = Vec::new();
+    let mut pending: Option<&Message> = None;
+    for msg in messages {
+        match msg.role {
+            Role::User => pending = Some(msg),
+            Role::Assistant => {
+                let Some(question) = pending.take() else {
+                    continue;
+                };
+                let question = question.text();
+                if question.len() > DUPLICATE_MAX_CHARS {
+                    continue;
+                }
+                let words = words(question);
+                if words.len() < DUPLICATE_MIN_WORDS {
+                    continue;
+                }
+                found.push(Exchange {
+                    session: session.map(str::to_string),
+                    when: when.to_string(),
+                    question: question.trim().to_string(),
+                    answer: msg.text().to_string(),
+                    words,
+                });
+            }
+            _ => {}
+        }
+    }
+    found
+}
+
+/// Exchanges from the most recently updated sessions other than
+/// `current`. Sessions that fail to load are skipped.
+pub(crate) fn past_exchanges(current: &str) -> Vec<Exchange> {
+    let mut sessions = Session::list_all().unwrap_or_default();
+    sessions.retain(|s| s.id != current && s.message_count > 0);
+    sessions.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    sessions
+        .iter()
+        .take(DUPLICATE_SCAN_SESSIONS)
+        .filter_map(|meta| {
+            let session = Session::load(&meta.id).ok()?;
+            Some(exchanges(
+                &session.messages,
+                Some(&meta.id),
+                &meta.updated_at,
+            ))
+        })
+        .flatten()
+        .collect()
+}
+
+/// Lowercase words of two or more letters or digits.
+fn words(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() >= 2)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// The hint for the closest earlier exchange to `input`, if one in
+/// `current` (this session's messages) or `past` is similar enough.
+/// The current session's are preferred on a tie.
+pub(crate) fn hint(input: &str, current: &[Message], past: &[Exchange]) -> Option<String> {
+    if input.len() > DUPLICATE_MAX_CHARS {
+        return None;
+    }
+    let asked = words(input);
+    if asked.len() < DUPLICATE_MIN_WORDS {
+        return None;
+    }
+    let here = exchanges(current, None, "");
+    let mut best: Option<(&Exchange, f64)> = None;
+    for exchange in here.iter().chain(past) {
+        let shared = asked.intersection(&exchange.words).count();
+        let similarity = shared as f64 / asked.union(&exchange.words).count() as f64;
+        if similarity >= DUPLICATE_SIMILARITY && best.is_none_or(|(_, s)| similarity > s) {
+            best = Some((exchange, similarity));
+        }
+    }
+    best.map(|(exchange, _)| describe(exchange))
+}
+
+fn describe(exchange: &Exchange) -> String {
+    let question = one_line(&exchange.question, DUPLICATE_ANSWER_PREVIEW_CHARS);
+    let answer = one_line(&exchange.answer, DUPLICATE_ANSWER_PREVIEW_CHARS);
+    match exchange.session {
+        None => tf(
+            "duplicate.this_session",
+            &[("question", &question), ("answer", &answer)],
+        ),
+        Some(ref id) => tf(
+            "duplicate.other_session",
+            &[
+                ("id", &id[..8.min(id.len())]),
+                ("date", exchange.when.get(..10).unwrap_or(&exchange.when)),
+                ("question", &question),
+                ("answer", &answer),
+            ],
+        ),
+    }
+}
+
+/// `text` on one line, cut at `max` characters.
+fn one_line(text: &str, max: usize) -> String {
+    let flat = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if flat.chars().count() <= max {
+        return flat;
+    }
+    let cut: String = flat.chars().take(max).collect();
+    format!("{}...", cut.trim_end())
+}
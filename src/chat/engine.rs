@@ -0,0 +1,975 @@
+//! Frontend-agnostic chat orchestration.
+//!
+//! [`ChatEngine`] owns everything a conversation turn needs — the session,
+//! provider, tool registry, and permission hook — so the readline REPL and
+//! the TUI drive the exact same logic and only differ in how they render.
+
+use anyhow::Result;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::{mpsc, Notify};
+
+use super::attach;
+use super::context;
+use super::duplicates::{self, Exchange};
+use super::environment::Environment;
+use super::extract::Extracted;
+use super::file_context::{FileContext, Refresh};
+use super::repo_map::RepoMap;
+use super::retry::{self, PendingRetry, ResponseIssue};
+use crate::compaction::CompactionResult;
+use crate::config::Config;
+use crate::hooks::KazeHook;
+use crate::i18n::tf;
+use crate::message::{Message, Role};
+use crate::metrics::{plural, MeteredRenderer, TurnMetrics};
+use crate::output::{self, NoticeLevel, RecordedToolCall, Renderer, TeeRenderer};
+use crate::permissions::{PermissionManager, PermissionRequest, PromptResponse};
+use crate::provider::{
+    self, resolve_model_spec, ModelSelection, Provider, ProviderKind, RequestParams,
+};
+use crate::session::{PrunePolicy, Session};
+use crate::tools::file_history::Undone;
+use crate::tools::todo::{TodoItem, TodoList};
+use crate::tools::{ToolRegistry, ToolResult};
+use crate::usage::{format_cost, TokenUsage, UsageRecord};
+
+/// Frontend-independent switches set from the command line.
+#[derive(Debug, Clone, Default)]
+pub struct ChatOptions {
+    /// Print a per-turn performance report after each response.
+    pub verbose: bool,
+    /// Append each turn's raw stream and tool events to this file.
+    pub tee: Option<PathBuf>,
+    /// Shell command whose output is attached at the start of the session
+    /// and again on `/refresh-cmd`.
+    pub attach_cmd: Option<String>,
+}
+
+/// Shared chat state and turn logic used by every chat frontend.
+pub struct ChatEngine {
+    /// The active session; messages are persisted as they are appended.
+    pub session: Session,
+    /// Loaded configuration, with `model` set to the resolved selection.
+    pub config: Config,
+    provider: Provider,
+    tools: ToolRegistry,
+    hook: KazeHook,
+    resumed: bool,
+    options: ChatOptions,
+    /// The open `--tee` file, if any.
+    tee: Option<File>,
+    /// The most recent successful turn, reported by
+    /// [`manage_context`](Self::manage_context).
+    last_turn: Option<TurnReport>,
+    /// The last turn, if its response was empty or a refusal and was kept
+    /// out of the conversation. Warned about by
+    /// [`manage_context`](Self::manage_context); re-sent by `/retry`.
+    retry: Option<PendingRetry>,
+    /// Whether `retry` still needs its warning shown.
+    retry_unreported: bool,
+    /// Notified to cancel the turn in progress.
+    interrupt: Arc<Notify>,
+    /// `--attach-cmd` output waiting to be prepended to the next message.
+    attachment: Option<String>,
+    /// Output of `!>` commands waiting to be prepended to the next message.
+    shell_outputs: Vec<String>,
+    /// The todo list as last written to the session.
+    saved_todos: Vec<TodoItem>,
+    /// The scratchpad note as last written to the session.
+    saved_scratchpad: String,
+    /// Whether the user answered "always" to a large-turn confirmation.
+    large_turns_approved: bool,
+    /// Questions from recent sessions, loaded on the first duplicate check.
+    past_exchanges: Option<Vec<Exchange>>,
+    /// The message a duplicate hint was last shown for.
+    hinted: Option<String>,
+    /// The most recent tool calls in full, oldest first, for `/expand`.
+    tool_calls: VecDeque<RecordedToolCall>,
+    /// Files the model has read or written, re-diffed at each turn start.
+    file_context: FileContext,
+    /// The `provider/model` to switch back to after a `/retry --model` turn.
+    restore_model: Option<String>,
+    /// The request parameters last recorded in the session.
+    recorded_params: Option<RequestParams>,
+    /// The environment facts sent with each request, unless `[context]
+    /// environment` is off.
+    environment: Option<Environment>,
+    /// The repo map sent with each request, when `[context]
+    /// repo_map_tokens` sets a budget.
+    repo_map: Option<RepoMap>,
+}
+
+impl ChatEngine {
+    /// Builds the provider, tools, and permission hook, then creates or
+    /// resumes the session.
+    ///
+    /// New sessions get the composed system prompt
+    /// ([`Config::system_preamble`]) as their first message.
+    pub fn new(
+        config: Config,
+        session_id: Option<String>,
+        selection: &ModelSelection,
+    ) -> Result<Self> {
+        let provider = Provider::from_config(&config, selection)?;
+        let project_root = std::env::current_dir()?;
+        let permission_manager = Arc::new(
+            PermissionManager::new(config.permissions.clone())
+                .with_write_ceiling(config.write_file_confirm_bytes()),
+        );
+        let file_context = FileContext::new(project_root.clone());
+        let environment = config
+            .environment_facts()
+            .then(|| Environment::new(project_root.clone()));
+        let repo_map = config
+            .repo_map_tokens()
+            .map(|budget| RepoMap::new(project_root.clone(), budget, &config.model));
+        let tools = ToolRegistry::with_settings(project_root.clone(), &config.tools);
+        let hook = KazeHook::new(permission_manager, project_root.clone())
+            .with_file_history(tools.file_history().clone());
+        let tools = tools.with_task(&config, selection, project_root, hook.clone())?;
+
+        let resumed = session_id.is_some();
+        let session = if let Some(ref id) = session_id {
+            Session::load(id)?
+        } else {
+            let mut s = Session::new(&config.model)?;
+            if let Some(sp) = config.system_preamble() {
+                s.append(Message::system(sp))?;
+            }
+            s
+        };
+        tools.audit().start(Some(&session.id));
+        // Resumed sessions pick up the plan where they left off
+        let saved_todos = session.todos()?;
+        tools.todos().set(saved_todos.clone());
+        let saved_scratchpad = session.scratchpad()?;
+        tools.scratchpad().set(saved_scratchpad.clone());
+
+        Ok(Self {
+            session,
+            config,
+            provider,
+            tools,
+            hook,
+            resumed,
+            options: ChatOptions::default(),
+            tee: None,
+            last_turn: None,
+            retry: None,
+            retry_unreported: false,
+            interrupt: Arc::new(Notify::new()),
+            attachment: None,
+            shell_outputs: Vec::new(),
+            saved_todos,
+            saved_scratchpad,
+            large_turns_approved: false,
+            past_exchanges: None,
+            hinted: None,
+            tool_calls: VecDeque::new(),
+            file_context,
+            restore_model: None,
+            recorded_params: None,
+            environment,
+            repo_map,
+        })
+    }
+
+    /// Routes permission prompts to `tx` instead of reading stdin.
+    ///
+    /// Used by frontends (the TUI) that own the terminal and must render
+    /// the prompt themselves.
+    pub fn with_prompt_channel(mut self, tx: mpsc::UnboundedSender<PermissionRequest>) -> Self {
+        self.hook = self.hook.with_prompt_channel(tx);
+        self
+    }
+
+    /// Applies command-line options such as `--verbose`.
+    ///
+    /// Fails if the `--tee` file cannot be opened.
+    pub fn with_options(mut self, options: ChatOptions) -> Result<Self> {
+        self.tee = options.tee.as_deref().map(output::open_tee).transpose()?;
+        self.options = options;
+        Ok(self)
+    }
+
+    /// The `--attach-cmd` command, if one was given.
+    pub fn attach_cmd(&self) -> Option<&str> {
+        self.options.attach_cmd.as_deref()
+    }
+
+    /// Runs the `--attach-cmd` command and queues its output for the next
+    /// message, replacing output that has not been sent yet.
+    ///
+    /// Returns a one-line summary of the run, or `None` without a command.
+    pub async fn refresh_attachment(&mut self) -> Result<Option<String>> {
+        let Some(command) = self.options.attach_cmd.clone() else {
+            return Ok(None);
+        };
+        let output = attach::run(&command, &self.config.model).await?;
+        self.attachment = Some(output.block);
+        Ok(Some(output.summary))
+    }
+
+    /// Runs `command` through the `bash` tool, as `!` does in chat; the
+    /// user typed it, so no permission is asked, but it is audited like the
+    /// model's calls. With `attach` (`!>`) the output is also queued for the
+    /// next message.
+    pub async fn run_shell(&mut self, command: &str, attach: bool) -> Result<ToolResult> {
+        let input = serde_json::json!({ "command": command });
+        let started = std::time::Instant::now();
+        let result = self.tools.execute("bash", input.clone()).await;
+        let audited = match &result {
+            Ok(result) => result.clone(),
+            Err(e) => ToolResult::error(format!("Error: {}", e)),
+        };
+        self.tools
+            .audit()
+            .record("bash", &input, &audited, started.elapsed());
+        let result = result?;
+        if attach {
+            self.shell_outputs.push(format!(
+                "<command_output command=\"{}\">\n{}\n</command_output>",
+                command.replace('"', "&quot;"),
+                result.content.trim()
+            ));
+        }
+        Ok(result)
+    }
+
+    /// `--attach-cmd` and `!>` output for the next message, if any.
+    fn attached(&self) -> Option<String> {
+        let blocks: Vec<&str> = self
+            .attachment
+            .as_deref()
+            .into_iter()
+            .chain(self.shell_outputs.iter().map(String::as_str))
+            .collect();
+        (!blocks.is_empty()).then(|| blocks.join("\n\n"))
+    }
+
+    /// The plan kept by the `todo` tool, updated live during a turn.
+    pub fn todos(&self) -> &TodoList {
+        self.tools.todos()
+    }
+
+    /// Writes the todo list to the session if the turn changed it.
+    fn save_todos(&mut self, renderer: &mut dyn Renderer) {
+        let items = self.tools.todos().items();
+        if items == self.saved_todos {
+            return;
+        }
+        match self.session.save_todos(&items) {
+            Ok(()) => self.saved_todos = items,
+            Err(e) => renderer.warn(&format!("Failed to save todo list: {}", e)),
+        }
+    }
+
+    /// Writes the scratchpad to the session if the turn changed it.
+    fn save_scratchpad(&mut self, renderer: &mut dyn Renderer) {
+        let text = self.tools.scratchpad().text();
+        if text == self.saved_scratchpad {
+            return;
+        }
+        match self.session.save_scratchpad(&text) {
+            Ok(()) => self.saved_scratchpad = text,
+            Err(e) => renderer.warn(&format!("Failed to save scratchpad: {}", e)),
+        }
+    }
+
+    /// The history to send instead of the session's messages when there
+    /// are environment facts, a repo map, or a scratchpad note: a copy with
+    /// them appended to the system prompt.
+    ///
+    /// They are added per request rather than stored as messages, so
+    /// compaction and truncation never drop them and resumed sessions get
+    /// current facts.
+    fn request_history(&self) -> Option<Vec<Message>> {
+        let note = self.tools.scratchpad().condensed().map(|note| {
+            format!(
+                "Your scratchpad (kept across turns and compaction; update it with the \
+                 scratchpad tool):\n<scratchpad>\n{}\n</scratchpad>",
+                note
+            )
+        });
+        let blocks: Vec<String> = self
+            .environment
+            .as_ref()
+            .map(Environment::block)
+            .into_iter()
+            .chain(self.repo_map.as_ref().and_then(RepoMap::block))
+            .chain(note)
+            .collect();
+        if blocks.is_empty() {
+            return None;
+        }
+        let block = blocks.join("\n\n");
+        let mut messages = self.session.messages.clone();
+        match messages.iter_mut().find(|m| m.role == Role::System) {
+            Some(system) => *system = Message::system(format!("{}\n\n{}", system.text(), block)),
+            None => messages.insert(0, Message::system(block)),
+        }
+        Some(messages)
+    }
+
+    /// Whether this engine resumed an existing session.
+    pub fn is_resumed(&self) -> bool {
+        self.resumed
+    }
+
+    /// The model a resumed session was recorded with, when it differs from
+    /// the model this engine was built for.
+    ///
+    /// Resuming builds the provider from the CLI/config selection, so
+    /// without a check the header would show one model while another
+    /// answers.
+    pub fn stale_model(&self) -> Option<&str> {
+        (self.resumed && self.session.model != self.config.model)
+            .then_some(self.session.model.as_str())
+    }
+
+    /// Records the configured model in the session index, resolving a
+    /// [`stale_model`](Self::stale_model) mismatch in its favor.
+    pub fn adopt_configured_model(&mut self) -> Result<()> {
+        let model = self.config.model.clone();
+        self.session.set_model(&model)
+    }
+
+    /// The model used for requests and token counting.
+    pub fn model_name(&self) -> &str {
+        &self.config.model
+    }
+
+    /// Switches to another model mid-session (the `/model` command).
+    ///
+    /// `spec` is a model name for the current provider, or
+    /// `provider/model` to change providers (`openrouter/openai/gpt-4o`
+    /// for slash-containing OpenRouter names). The provider is rebuilt and
+    /// the session's recorded model is updated.
+    pub fn switch_model(&mut self, spec: &str) -> Result<ModelSelection> {
+        let selection = resolve_model_spec(spec, self.provider.kind(), &self.config)?;
+
+        self.provider = Provider::from_config(&self.config, &selection)?;
+        self.config.model = selection.model.clone();
+        self.session.set_model(&selection.model)?;
+        Ok(selection)
+    }
+
+    /// Applies the `[sessions]` retention policy when `auto_prune` is on,
+    /// never touching the open session. Returns a notice when sessions were
+    /// removed or pruning failed.
+    pub fn auto_prune(&self) -> Option<String> {
+        if !self.config.sessions.auto_prune.unwrap_or(false) {
+            return None;
+        }
+        let mut policy = PrunePolicy::from_config(&self.config.sessions);
+        if policy.is_empty() {
+            return None;
+        }
+        policy.keep = Some(self.session.id.clone());
+        match Session::prune(&policy, false) {
+            Ok(report) if report.removed.is_empty() => None,
+            Ok(report) => Some(tf(
+                if policy.archive {
+                    "sessions.auto_archived"
+                } else {
+                    "sessions.auto_pruned"
+                },
+                &[("count", &report.removed.len().to_string())],
+            )),
+            Err(e) => Some(tf(
+                "sessions.prune_failed",
+                &[("error", &format!("{:#}", e))],
+            )),
+        }
+    }
+
+    /// Checks that the provider can be reached before the first message.
+    ///
+    /// When it can't and `offline_fallback` is set, switches to that model.
+    /// Returns a notice for the user if the network looks down. Local
+    /// Ollama models skip the check.
+    pub async fn check_network(&mut self) -> Option<String> {
+        let kind = self.provider.kind();
+        if kind == ProviderKind::Ollama {
+            return None;
+        }
+        let failure = provider::probe(kind, &self.config).await?;
+        let Some(fallback) = self.config.offline_fallback.clone() else {
+            return Some(provider::explain(&failure, kind, &self.config));
+        };
+        let host = failure.host().unwrap_or(kind.as_str()).to_string();
+        Some(match self.switch_model(&fallback) {
+            Ok(selection) => tf(
+                "offline.switched",
+                &[
+                    ("host", &host),
+                    (
+                        "model",
+                        &format!("{}/{}", selection.provider.as_str(), selection.model),
+                    ),
+                ],
+            ),
+            Err(e) => format!(
+                "{} {}",
+                provider::explain(&failure, kind, &self.config),
+                tf(
+                    "offline.unusable",
+                    &[("model", &fallback), ("error", &format!("{:#}", e))]
+                )
+            ),
+        })
+    }
+
+    /// Sends one user message through the tool-enabled agent loop.
+    ///
+    /// Pending `--attach-cmd` output is prepended to the message and
+    /// cleared once the turn is kept in the conversation.
+    ///
+    /// The user message is persisted before the provider call for crash
+    /// safety. On success the assistant reply is appended; on failure the
+    /// user message is popped so the turn can be retried.
+    ///
+    /// Each successful turn is timed and recorded in the usage ledger, and
+    /// its cost is added to the session's running total.
+    ///
+    /// The stream is abandoned on Ctrl+C (SIGINT) or when the
+    /// [`interrupt_handle`](Self::interrupt_handle) is notified. Any text
+    /// received so far is saved as an assistant message marked incomplete,
+    /// so resuming the session keeps what was already said. The same happens
+    /// when the turn outlasts `turn_timeout_secs` (or `--turn-timeout`),
+    /// and the error then says how far the turn got.
+    ///
+    /// A model picked with `/retry --model` is switched back afterwards.
+    pub async fn send(&mut self, input: &str, renderer: &mut dyn Renderer) -> Result<String> {
+        let result = self.send_turn(input, renderer).await;
+        if let Err(e) = self.restore_model() {
+            renderer.warn(&format!("Failed to switch the model back: {}", e));
+        }
+        result
+    }
+
+    async fn send_turn(&mut self, input: &str, renderer: &mut dyn Renderer) -> Result<String> {
+        let tokens_before = self.conversation_tokens();
+        let mut content = match self.attached() {
+            Some(blocks) => format!("{}\n\n{}", blocks, input),
+            None => input.to_string(),
+        };
+        let refresh = self
+            .config
+            .file_refresh_tokens()
+            .and_then(|budget| self.file_context.changes(budget, &self.config.model));
+        if let Some(ref refresh) = refresh {
+            renderer.notice(
+                NoticeLevel::Info,
+                &tf(
+                    "context.refreshed",
+                    &[("files", &plural(refresh.changed, "file", "files"))],
+                ),
+            );
+            content = format!("{}\n\n{}", refresh.note, content);
+        }
+        self.record_params(renderer);
+        self.session.append(Message::user(content))?;
+        context::compact_before_request(
+            &mut self.session,
+            &self.provider,
+            &self.config.model,
+            &self.config,
+            renderer,
+        )
+        .await?;
+        if let Some(environment) = self.environment.as_mut() {
+            environment.refresh().await;
+        }
+        if let Some(repo_map) = self.repo_map.as_mut() {
+            repo_map.load().await;
+        }
+        let augmented = self.request_history();
+        let history = augmented.as_deref().unwrap_or(&self.session.messages);
+
+        let mut tee;
+        let inner: &mut dyn Renderer = match self.tee.as_mut() {
+            Some(file) => {
+                if let Err(e) = writeln!(file, "\n> {}\n", input) {
+                    renderer.warn(&format!("Failed to write tee file: {}", e));
+                }
+                tee = TeeRenderer::new(renderer, file);
+                &mut tee
+            }
+            None => renderer,
+        };
+        let limit = self.config.turn_timeout(None);
+        let mut timed_out = false;
+        let mut metered = MeteredRenderer::new(inner);
+        let result = tokio::select! {
+            result = self.provider.stream_with_tools(
+                history,
+                &self.tools,
+                &mut metered,
+                crate::constants::MAX_AGENT_ITERATIONS,
+                self.hook.clone(),
+            ) => Some(result),
+            _ = interrupted(&self.interrupt) => None,
+            _ = crate::agent::deadline(limit) => {
+                timed_out = true;
+                None
+            }
+        };
+        let Some(result) = result else {
+            let partial = metered.text().to_string();
+            metered.render_done();
+            let calls = metered.take_tool_calls();
+            let metrics = metered.finish();
+            let seen = seen_files(&calls, &metrics);
+            self.keep_tool_calls(calls);
+            self.save_todos(renderer);
+            self.save_scratchpad(renderer);
+            let summary = timed_out.then(|| {
+                crate::agent::timeout_summary(limit.unwrap_or_default(), &metrics, &partial)
+            });
+            let saved = self.save_partial(partial)?;
+            if saved {
+                self.keep_file_context(refresh, &seen);
+            }
+            if let Some(summary) = summary {
+                anyhow::bail!(summary);
+            }
+            if saved {
+                anyhow::bail!("Interrupted; partial response saved to the session");
+            }
+            anyhow::bail!("Interrupted");
+        };
+        let calls = metered.take_tool_calls();
+        let metrics = metered.finish();
+        let seen = seen_files(&calls, &metrics);
+        self.keep_tool_calls(calls);
+        self.save_todos(renderer);
+        self.save_scratchpad(renderer);
+
+        match result {
+            Ok(response) => {
+                // A turn that ran tools has had its effect, and retrying it
+                // would run them again, so it is kept even without text
+                let issue = retry::classify(&response).filter(|_| metrics.tool_calls == 0);
+                match issue {
+                    // Keep the blank or declined turn out of the context, as on error
+                    Some(issue) => {
+                        self.session.messages.pop();
+                        self.retry = Some(PendingRetry {
+                            input: input.to_string(),
+                            issue,
+                        });
+                        self.retry_unreported = true;
+                    }
+                    None => {
+                        let reply = if response.trim().is_empty() {
+                            crate::constants::TOOL_ONLY_REPLY.to_string()
+                        } else {
+                            response.clone()
+                        };
+                        self.session.append(Message::assistant(reply))?;
+                        self.session.checkpoint()?;
+                        self.retry = None;
+                        self.attachment = None;
+                        self.shell_outputs.clear();
+                        self.keep_file_context(refresh, &seen);
+                    }
+                }
+                let output_tokens =
+                    crate::tokens::count_tokens(&response, &self.config.model).unwrap_or(0);
+                let record = UsageRecord::new(
+                    self.provider.kind().as_str(),
+                    &self.config.model,
+                    Some(&self.session.id),
+                    output_tokens,
+                    &metrics,
+                );
+                if let Err(e) = record.append() {
+                    renderer.warn(&format!("Failed to record usage: {}", e));
+                }
+                if let Some(cost) = record.cost_usd {
+                    self.session.add_cost(cost)?;
+                }
+                self.last_turn = Some(TurnReport {
+                    metrics,
+                    tokens_before,
+                    tokens_after: self.conversation_tokens(),
+                    cost: record.cost_usd.map(|cost| (cost, self.session.cost_usd)),
+                });
+                Ok(response)
+            }
+            Err(e) => {
+                self.session.messages.pop();
+                Err(provider::explain_error(e, self.provider.kind(), &self.config).await)
+            }
+        }
+    }
+
+    /// Records the request parameters in the session when they differ from
+    /// those last recorded.
+    fn record_params(&mut self, renderer: &mut dyn Renderer) {
+        let params = self.provider.request_params(&self.tools);
+        if self.recorded_params.as_ref() == Some(&params) {
+            return;
+        }
+        match self.session.save_params(&params) {
+            Ok(()) => self.recorded_params = Some(params),
+            Err(e) => renderer.warn(&format!("Failed to record request parameters: {}", e)),
+        }
+    }
+
+    /// Drops the last `turns` exchanges from the history and the session
+    /// (`/rewind`), returning how many messages were removed.
+    ///
+    /// A pending `/retry` is forgotten with them.
+    pub fn rewind(&mut self, turns: usize) -> Result<usize> {
+        let dropped = self.session.rewind(turns)?;
+        self.retry = None;
+        self.retry_unreported = false;
+        Ok(dropped)
+    }
+
+    /// Adds a turn's tool calls to those `/expand` can show, dropping the
+    /// oldest beyond [`EXPAND_MAX_CALLS`](crate::constants::EXPAND_MAX_CALLS).
+    fn keep_tool_calls(&mut self, calls: Vec<RecordedToolCall>) {
+        self.tool_calls.extend(calls);
+        let excess = self
+            .tool_calls
+            .len()
+            .saturating_sub(crate::constants::EXPAND_MAX_CALLS);
+        self.tool_calls.drain(..excess);
+    }
+
+    /// Records what a kept turn showed the model: the refreshed contents
+    /// and the files its tools read or wrote.
+    fn keep_file_context(&mut self, refresh: Option<Refresh>, seen: &[String]) {
+        if let Some(refresh) = refresh {
+            self.file_context.apply(refresh);
+        }
+        for path in seen {
+            self.file_context.track(path);
+        }
+    }
+
+    /// Files kept fresh at turn start (`/context`), sorted.
+    pub fn tracked_files(&self) -> Vec<String> {
+        self.file_context.paths().map(str::to_string).collect()
+    }
+
+    /// Whether tool calls run without asking (`--yes`, `/auto`).
+    pub fn auto_approve(&self) -> bool {
+        self.hook.permissions().auto_approve()
+    }
+
+    /// Turns auto-approve mode on or off for the rest of the session,
+    /// subagents included.
+    pub fn set_auto_approve(&self, on: bool) {
+        self.hook.permissions().set_auto_approve(on);
+    }
+
+    /// Stops refreshing every tracked file (`/context clear`).
+    pub fn clear_tracked_files(&mut self) {
+        self.file_context.clear();
+    }
+
+    /// The last `n` tool calls of this chat, oldest first.
+    pub fn recent_tool_calls(&self, n: usize) -> impl Iterator<Item = &RecordedToolCall> {
+        self.tool_calls
+            .iter()
+            .skip(self.tool_calls.len().saturating_sub(n))
+    }
+
+    /// Reverts the most recent file change made by a tool (`/undo`); `None`
+    /// when there is none left this run.
+    pub async fn undo_file_change(&self, force: bool) -> Result<Option<Undone>> {
+        self.tools.file_history().undo(force).await
+    }
+
+    /// Writes code blocks planned by `/extract`, as one change `/undo`
+    /// can revert.
+    pub(crate) async fn write_extracted(&self, planned: &[Extracted]) -> Result<()> {
+        super::extract::write(self.tools.file_history(), planned).await
+    }
+
+    /// A hint pointing to the earlier answer when `input` repeats a
+    /// question from this session or a recent one (`[sessions]
+    /// duplicate_hints`).
+    ///
+    /// Sending the same input again right after its hint gets `None`, so
+    /// repeating a message asks anyway.
+    pub fn duplicate_hint(&mut self, input: &str) -> Option<String> {
+        if !self.config.duplicate_hints() || self.hinted.take().as_deref() == Some(input) {
+            return None;
+        }
+        let past = self
+            .past_exchanges
+            .get_or_insert_with(|| duplicates::past_exchanges(&self.session.id));
+        let hint = duplicates::hint(input, &self.session.messages, past)?;
+        self.hinted = Some(input.to_string());
+        Some(hint)
+    }
+
+    /// Asks before sending `input` when the prompt it makes is estimated
+    /// above `[context] confirm_above_tokens`, showing the token count and
+    /// its input cost. Returns whether to send it.
+    ///
+    /// The question goes to the frontend's prompt channel when there is
+    /// one, otherwise to the terminal. Answering "always" stops asking for
+    /// the rest of the session.
+    pub async fn confirm_turn(&mut self, input: &str) -> Result<bool> {
+        let Some(threshold) = self.config.confirm_above_tokens() else {
+            return Ok(true);
+        };
+        if self.large_turns_approved {
+            return Ok(true);
+        }
+        let model = self.config.model.clone();
+        let count = |text: &str| crate::tokens::count_tokens(text, &model).unwrap_or(0);
+        let mut tokens = self.conversation_tokens() + count(input);
+        tokens += self.attached().as_deref().map_or(0, count);
+        tokens += self
+            .tools
+            .scratchpad()
+            .condensed()
+            .as_deref()
+            .map_or(0, count);
+        if tokens <= threshold {
+            return Ok(true);
+        }
+
+        let usage = TokenUsage {
+            input_tokens: tokens as u64,
+            output_tokens: 0,
+        };
+        let tokens = crate::tokens::format_number(tokens);
+        let question = match usage.cost(self.provider.kind().as_str(), &model) {
+            Some(cost) => tf(
+                "cost.confirm",
+                &[("tokens", &tokens), ("cost", &format_cost(cost))],
+            ),
+            None => tf("cost.confirm_unpriced", &[("tokens", &tokens)]),
+        };
+        match self.ask(question).await? {
+            PromptResponse::Yes => Ok(true),
+            PromptResponse::Always => {
+                self.large_turns_approved = true;
+                Ok(true)
+            }
+            PromptResponse::No | PromptResponse::PerHunk => {
+                self.restore_model()?;
+                Ok(false)
+            }
+        }
+    }
+
+    /// Asks a yes/no question through the frontend's prompt channel when
+    /// there is one, otherwise on the terminal. "always" counts as yes.
+    pub async fn confirm(&self, question: &str) -> Result<bool> {
+        Ok(matches!(
+            self.ask(question.to_string()).await?,
+            PromptResponse::Yes | PromptResponse::Always
+        ))
+    }
+
+    async fn ask(&self, question: String) -> Result<PromptResponse> {
+        let answer = match self.hook.prompt_channel() {
+            Some(tx) => {
+                let (reply, answer) = tokio::sync::oneshot::channel();
+                let request = PermissionRequest {
+                    tool_name: String::new(),
+                    args: String::new(),
+                    diff: None,
+                    reply: Some(reply),
+                    question: Some(question),
+                };
+                if tx.send(request).is_err() {
+                    return Ok(PromptResponse::No);
+                }
+                answer.await.unwrap_or(PromptResponse::No)
+            }
+            None => PermissionManager::prompt_question(&question)?,
+        };
+        Ok(answer)
+    }
+
+    /// The message `/retry` sends: the adjusted prompt of the last empty or
+    /// refused turn, or else the last user message, with its answer (and
+    /// the tool calls behind it) rewound so the reply is generated afresh.
+    /// `None` when there is nothing to retry.
+    ///
+    /// A non-empty `instruction` is appended to the message. With `model`,
+    /// that model answers this one turn and the current one is restored
+    /// after it.
+    pub fn regenerate(&mut self, model: Option<&str>, instruction: &str) -> Result<Option<String>> {
+        self.retry_unreported = false;
+        let prompt = match &self.retry {
+            Some(pending) => pending.adjusted_prompt(),
+            None => {
+                let floor = self
+                    .session
+                    .messages
+                    .iter()
+                    .rposition(|m| m.role == Role::System)
+                    .map_or(0, |i| i + 1);
+                let Some(last) = self.session.messages[floor..]
+                    .iter()
+                    .rfind(|m| m.role == Role::User)
+                else {
+                    return Ok(None);
+                };
+                last.text().to_string()
+            }
+        };
+        // An unknown model leaves the conversation as it was
+        if let Some(spec) = model {
+            let current = format!("{}/{}", self.provider.kind().as_str(), self.config.model);
+            self.switch_model(spec)?;
+            self.restore_model = Some(current);
+        }
+        if self.retry.take().is_none() {
+            self.session.rewind(1)?;
+        }
+        let prompt = if instruction.is_empty() {
+            prompt
+        } else {
+            format!("{}\n\n{}", prompt, instruction)
+        };
+        // Asking the same question again is the point, so skip the hint
+        self.hinted = Some(prompt.clone());
+        Ok(Some(prompt))
+    }
+
+    /// Switches back from a model picked for one `/retry` turn.
+    fn restore_model(&mut self) -> Result<()> {
+        match self.restore_model.take() {
+            Some(spec) => self.switch_model(&spec).map(|_| ()),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns a handle that cancels the in-flight [`send`](Self::send).
+    ///
+    /// Frontends that run the turn concurrently (the TUI) notify it on quit
+    /// or shutdown, then await the turn so the partial response is saved.
+    pub fn interrupt_handle(&self) -> Arc<Notify> {
+        self.interrupt.clone()
+    }
+
+    /// Persists the partial response of an interrupted or timed-out turn,
+    /// returning whether there was any.
+    ///
+    /// With nothing streamed yet the user message is dropped, as on error.
+    fn save_partial(&mut self, partial: String) -> Result<bool> {
+        if partial.trim().is_empty() {
+            self.session.messages.pop();
+            return Ok(false);
+        }
+        self.session
+            .append(Message::assistant(partial).mark_incomplete())?;
+        self.session.checkpoint()?;
+        self.attachment = None;
+        self.shell_outputs.clear();
+        Ok(true)
+    }
+
+    /// Displays token usage; compaction happens before the next request.
+    ///
+    /// The previous turn's summary line (duration, tool calls, files
+    /// modified, token growth, cost) is shown first, followed by its detailed
+    /// timings under `--verbose`. This runs after the REPL reprints the
+    /// formatted response, so status lines never land inside the region it
+    /// erases.
+    pub async fn manage_context(&mut self, renderer: &mut dyn Renderer) -> Result<()> {
+        if let Some(turn) = self.last_turn.take() {
+            renderer.notice(
+                NoticeLevel::Info,
+                &turn
+                    .metrics
+                    .turn_line(turn.tokens_before, turn.tokens_after, turn.cost),
+            );
+            if self.options.verbose {
+                renderer.notice(NoticeLevel::Info, &turn.metrics.summary());
+            }
+        }
+        if self.retry_unreported {
+            self.retry_unreported = false;
+            if let Some(ref pending) = self.retry {
+                let key = match pending.issue {
+                    ResponseIssue::Empty => "response.empty",
+                    ResponseIssue::Refusal => "response.refusal",
+                };
+                renderer.notice(NoticeLevel::Warning, crate::i18n::t(key));
+            }
+        }
+        context::handle_context_management(
+            &mut self.session,
+            &self.provider,
+            &self.config.model,
+            &self.config,
+            renderer,
+        )
+        .await
+    }
+
+    /// Token count of the whole conversation, or 0 if counting fails.
+    fn conversation_tokens(&self) -> usize {
+        let pairs: Vec<(String, String)> = self
+            .session
+            .messages
+            .iter()
+            .map(|m| (m.role.to_string(), m.text().to_string()))
+            .collect();
+        crate::tokens::count_conversation_tokens(&pairs, &self.config.model).unwrap_or(0)
+    }
+
+    /// Summarizes old messages on demand (the `/compact` command).
+    pub async fn compact(&mut self, renderer: &mut dyn Renderer) -> Result<CompactionResult> {
+        context::perform_compaction(
+            &mut self.session,
+            &self.provider,
+            &self.config.model,
+            self.config.compaction_keep_recent(),
+            "Compacted",
+            "compaction",
+            renderer,
+        )
+        .await
+    }
+}
+
+/// What happened during a turn, kept until the next status display.
+struct TurnReport {
+    metrics: TurnMetrics,
+    tokens_before: usize,
+    tokens_after: usize,
+    /// Cost of this turn and of the session so far, when priced.
+    cost: Option<(f64, f64)>,
+}
+
+/// Paths of the files a turn's `read_file` calls read and its tools wrote.
+fn seen_files(calls: &[RecordedToolCall], metrics: &TurnMetrics) -> Vec<String> {
+    calls
+        .iter()
+        .filter(|call| call.name == "read_file")
+        .filter_map(|call| call.arguments.get("path")?.as_str())
+        .map(str::to_string)
+        .chain(metrics.modified_files.iter().cloned())
+        .collect()
+}
+
+/// Resolves when `interrupt` is notified or the process receives Ctrl+C.
+async fn interrupted(interrupt: &Notify) {
+    tokio::select! {
+        _ = interrupt.notified() => {}
+        result = tokio::signal::ctrl_c() => {
+            if result.is_err() {
+                // No signal handler available; only the notify can cancel
+                std::future::pending::<()>().await;
+            }
+        }
+    }
+}
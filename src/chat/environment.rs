@@ -0,0 +1,176 @@
+//! Facts about the machine and the working tree, given to the model with
+//! every chat request so it doesn't have to ask or guess.
+//!
+//! The OS, the user's shell, and the toolchain versions found on `PATH` are
+//! gathered once per session, before its first request. The git branch and
+//! the number of changed files are re-read before a request once they are
+//! older than `ENVIRONMENT_REFRESH_SECS`, since the model's own edits change
+//! them. Like the scratchpad, the block is appended to the system prompt per
+//! request rather than stored, so resumed sessions get current facts.
+//!
+//! Programs are resolved like help_lookup resolves them, never from inside
+//! the project, and each gets `ENVIRONMENT_COMMAND_TIMEOUT_SECS` to answer.
+//! Toolchains run outside the project too, so a checked-in toolchain file
+//! can't make `--version` install or run anything.
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+
+use crate::constants::{
+    ENVIRONMENT_COMMAND_TIMEOUT_SECS, ENVIRONMENT_REFRESH_SECS, ENVIRONMENT_TOOLCHAINS,
+};
+use crate::metrics::plural;
+use crate::tools::help_lookup::resolve_program;
+
+/// The facts block for one chat session.
+pub(crate) struct Environment {
+    root: PathBuf,
+    /// The lines that don't change during a session, once gathered.
+    fixed: Option<Vec<String>>,
+    /// The git line, or `None` outside a repository.
+    git: Option<String>,
+    /// When the git line was last read, `None` before the first refresh.
+    refreshed: Option<Instant>,
+}
+
+impl Environment {
+    /// An environment for a session working in `root`, gathered on the
+    /// first [`refresh`](Self::refresh).
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            fixed: None,
+            git: None,
+            refreshed: None,
+        }
+    }
+
+    /// Gathers the fixed facts if they haven't been yet, and re-reads the
+    /// git state if it is older than the refresh interval.
+    pub async fn refresh(&mut self) {
+        if self.fixed.is_none() {
+            self.fixed = Some(fixed_lines(&self.root).await);
+        }
+        let interval = Duration::from_secs(ENVIRONMENT_REFRESH_SECS);
+        if self.refreshed.is_some_and(|at| at.elapsed() < interval) {
+            return;
+        }
+        self.git = git_line(&self.root).await;
+        self.refreshed = Some(Instant::now());
+    }
+
+    /// The block to append to the system prompt.
+    pub fn block(&self) -> String {
+        let lines: Vec<&str> = self
+            .fixed
+            .iter()
+            .flatten()
+            .chain(&self.git)
+            .map(String::as_str)
+            .collect();
+        format!(
+            "The environment you are working in:\n<environment>\n{}\n</environment>",
+            lines.join("\n")
+        )
+    }
+}
+
+/// The OS, shell, and toolchain lines, probing the toolchains at once.
+async fn fixed_lines(root: &Path) -> Vec<String> {
+    let mut fixed = vec![
+        format!("os: {} ({})", std::env::consts::OS, std::env::consts::ARCH),
+        format!("shell: {}", shell()),
+    ];
+    let outside = std::env::temp_dir();
+    let versions = futures::future::join_all(
+        ENVIRONMENT_TOOLCHAINS
+            .iter()
+            .map(|(_, program)| first_line(root, &outside, program, &["--version"])),
+    )
+    .await;
+    let mut found: Vec<&str> = Vec::new();
+    for ((label, _), version) in ENVIRONMENT_TOOLCHAINS.iter().zip(versions) {
+        if found.contains(label) {
+            continue;
+        }
+        if let Some(version) = version {
+            fixed.push(format!("{}: {}", label, version));
+            found.push(label);
+        }
+    }
+    fixed
+}
+
+/// The user's shell from `$SHELL` (`%COMSPEC%` on Windows). The bash tool
+/// itself always runs `sh -c`.
+fn shell() -> String {
+    let var = if cfg!(windows) { "COMSPEC" } else { "SHELL" };
+    let name = std::env::var(var).ok().and_then(|path| {
+        Path::new(&path)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+    });
+    match name {
+        Some(name) => format!("{} (the bash tool runs sh -c)", name),
+        None => "unknown (the bash tool runs sh -c)".to_string(),
+    }
+}
+
+/// `git: branch main, 3 changed files`, or `None` outside a repository.
+pub(crate) async fn git_line(root: &Path) -> Option<String> {
+    // Reading the status mustn't take the index lock from a git command the
+    // user or the model is running
+    let status = output(
+        root,
+        root,
+        "git",
+        &["--no-optional-locks", "status", "--porcelain"],
+    )
+    .await?;
+    // Unlike rev-parse, symbolic-ref names a branch with no commits yet
+    let branch = match first_line(root, root, "git", &["symbolic-ref", "--short", "HEAD"]).await {
+        Some(branch) => format!("branch {}", branch),
+        None => "detached HEAD".to_string(),
+    };
+    let changed = match status.lines().count() {
+        0 => "clean".to_string(),
+        n => plural(n, "changed file", "changed files"),
+    };
+    Some(format!("git: {}, {}", branch, changed))
+}
+
+/// The first non-blank line `program` prints, or `None` if it isn't
+/// installed, fails, or times out.
+async fn first_line(root: &Path, dir: &Path, program: &str, args: &[&str]) -> Option<String> {
+    let text = output(root, dir, program, args).await?;
+    text.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
+}
+
+/// Runs `program` from `PATH`, outside the project `root`, in `dir`.
+async fn output(root: &Path, dir: &Path, program: &str, args: &[&str]) -> Option<String> {
+    let path = resolve_program(root, program)?;
+    let child = tokio::process::Command::new(path)
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .ok()?;
+    let output = tokio::time::timeout(
+        Duration::from_secs(ENVIRONMENT_COMMAND_TIMEOUT_SECS),
+        child.wait_with_output(),
+    )
+    .await
+    .ok()?
+    .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
@@ -0,0 +1,149 @@
+//! `/extract`: writing the code blocks of a response to the files they
+//! name, for answers given inline instead of through `write_file`.
+//!
+//! A block's file comes from its fence info string (```` ```rust src/main.rs ````,
+//! ```` ```python:app.py ````, ```` ```rust title="lib.rs" ````) or, failing
+//! that, from the last line before the fence when it names a path
+//! (`` `src/main.rs`: ``, `**File: app.py**`). Blocks that name no file are
+//! skipped. The writes are backed up like a tool's, so `/undo` reverts them.
+
+use anyhow::{Context, Result};
+use std::path::{Component, Path, PathBuf};
+
+use crate::async_fs;
+use crate::tools::file_history::{read_existing, FileEdit, FileHistory};
+
+/// A code block and the file it will be written to.
+pub(crate) struct Extracted {
+    /// The file name as the response gave it.
+    pub name: String,
+    pub path: PathBuf,
+    pub code: String,
+    /// Whether `path` already exists.
+    pub exists: bool,
+}
+
+/// Code blocks in `text` with the files they name, relative to `dir`, and
+/// how many blocks named none. Later blocks for the same file win.
+pub(crate) fn plan(text: &str, dir: &Path) -> (Vec<Extracted>, usize) {
+    let mut planned: Vec<Extracted> = Vec::new();
+    let mut unnamed = 0;
+    let mut previous = "";
+    let mut block: Option<(Option<String>, Vec<&str>)> = None;
+    for line in text.lines() {
+        if let Some(info) = line.strip_prefix("```") {
+            match block.take() {
+                Some((name, lines)) => {
+                    // A label belongs to the block right after it only
+                    previous = "";
+                    match name {
+                        Some(name) => {
+                            planned.retain(|p| p.name != name);
+                            let mut code = lines.join("\n");
+                            code.push('\n');
+                            planned.push(Extracted {
+                                path: dir.join(&name),
+                                exists: dir.join(&name).exists(),
+                                name,
+                                code,
+                            });
+                        }
+                        None => unnamed += 1,
+                    }
+                }
+                None => {
+                    let name = info_name(info).or_else(|| line_name(previous));
+                    block = Some((name, Vec::new()));
+                }
+            }
+        } else if let Some((_, ref mut lines)) = block {
+            lines.push(line);
+        } else if !line.trim().is_empty() {
+            previous = line;
+        }
+    }
+    // An unterminated block is probably cut off, so it isn't written
+    if block.is_some() {
+        unnamed += 1;
+    }
+    (planned, unnamed)
+}
+
+/// A file name in a fence info string: a word after the language,
+/// `lang:path`, or `title="path"`.
+fn info_name(info: &str) -> Option<String> {
+    let info = info.trim();
+    if let Some((lang, path)) = info.split_once(':') {
+        if !lang.contains(char::is_whitespace) && !path.starts_with("//") {
+            return as_path(path);
+        }
+    }
+    info.split_whitespace().find_map(|word| {
+        let word = word
+            .strip_prefix("title=")
+            .or_else(|| word.strip_prefix("file="))
+            .unwrap_or(word);
+        as_path(word)
+    })
+}
+
+/// A file name on the line before a fence, such as `` `src/main.rs`: ``
+/// or `**File: app.py**`.
+fn line_name(line: &str) -> Option<String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    // Prose that mentions a file in passing isn't a label
+    if words.len() > 4 {
+        return None;
+    }
+    words.into_iter().rev().find_map(as_path)
+}
+
+/// `word` as a relative file path, if it looks like one: stripped of
+/// Markdown and quotes, with an extension or a directory, and staying
+/// inside the target directory.
+fn as_path(word: &str) -> Option<String> {
+    let word = word.trim_matches(|c: char| "`*_\"'():,".contains(c) || c.is_whitespace());
+    if word.is_empty() || word.contains("://") || word.starts_with('-') {
+        return None;
+    }
+    let path = Path::new(word);
+    let has_extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| !e.is_empty() && e.len() <= 8 && e.chars().all(|c| c.is_alphanumeric()));
+    if !has_extension && !word.contains('/') {
+        return None;
+    }
+    let inside = path
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+    inside.then(|| word.to_string())
+}
+
+/// Writes the planned files, backed up in `history` as one change.
+pub(crate) async fn write(history: &FileHistory, planned: &[Extracted]) -> Result<()> {
+    let mut before = Vec::with_capacity(planned.len());
+    for file in planned {
+        before.push(read_existing(&file.path).await?);
+    }
+    let edits: Vec<FileEdit> = planned
+        .iter()
+        .zip(&before)
+        .map(|(file, before)| FileEdit {
+            path: &file.path,
+            before: before.as_deref(),
+            after: Some(file.code.as_bytes()),
+        })
+        .collect();
+    let change = history.backup("extract", &edits).await?;
+    for file in planned {
+        if let Some(parent) = file.path.parent() {
+            async_fs::create_dir_all(parent).await?;
+        }
+        async_fs::write(&file.path, &file.code)
+            .await
+            .with_context(|| format!("Failed to write {}", file.path.display()))?;
+    }
+    history.record(change);
+    Ok(())
+}
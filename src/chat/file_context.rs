@@ -0,0 +1,147 @@
+//! Keeping files the model has seen fresh when they change on disk.
+//!
+//! Files the model reads or writes join the tracked set along with the
+//! contents it saw. At the start of each turn the tracked files are re-read,
+//! and any that were edited outside the conversation are sent as unified
+//! diffs ahead of the message, so the model works from current code without
+//! re-reading whole files. Diffs past the token budget are only named, with
+//! a nudge to read the file again.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::constants::FILE_REFRESH_MAX_BYTES;
+use crate::diff;
+
+/// The tracked files and the contents the model last saw.
+pub(crate) struct FileContext {
+    root: PathBuf,
+    /// Paths as the tools were given them, relative to `root`.
+    files: BTreeMap<String, String>,
+}
+
+/// The changes found at the start of a turn.
+pub(crate) struct Refresh {
+    /// The block to put ahead of the user's message.
+    pub note: String,
+    /// How many files changed.
+    pub changed: usize,
+    /// New contents to remember once the turn is kept; `None` for files
+    /// that stop being tracked.
+    updates: Vec<(String, Option<String>)>,
+}
+
+impl FileContext {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            files: BTreeMap::new(),
+        }
+    }
+
+    /// Starts tracking `path` at its current contents. Missing, binary, and
+    /// large files are skipped.
+    pub fn track(&mut self, path: &str) {
+        match self.read(path) {
+            Some(contents) => {
+                self.files.insert(path.to_string(), contents);
+            }
+            None => {
+                self.files.remove(path);
+            }
+        }
+    }
+
+    /// The tracked paths, sorted.
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.files.keys().map(String::as_str)
+    }
+
+    pub fn clear(&mut self) {
+        self.files.clear();
+    }
+
+    /// Diffs of tracked files that changed since the model saw them, in at
+    /// most `budget` tokens; `None` when nothing changed.
+    pub fn changes(&self, budget: usize, model: &str) -> Option<Refresh> {
+        let mut sections = Vec::new();
+        let mut updates = Vec::new();
+        let mut spent = 0;
+        for (path, seen) in &self.files {
+            let Some(current) = self.read(path) else {
+                // Deleted, or grown too large or binary: name it and stop tracking
+                let note = if self.root.join(path).exists() {
+                    format!("{} changed; read it again if you need it.", path)
+                } else {
+                    format!("{} was deleted.", path)
+                };
+                sections.push(note);
+                updates.push((path.clone(), None));
+                continue;
+            };
+            if &current == seen {
+                continue;
+            }
+            let diff = diff::plain_diff(seen, &current, path);
+            let tokens = crate::tokens::count_tokens(&diff, model).unwrap_or(diff.len() / 4);
+            if spent + tokens <= budget {
+                spent += tokens;
+                sections.push(format!("```diff\n{}```", diff));
+            } else {
+                let (added, removed) = line_counts(&diff);
+                sections.push(format!(
+                    "{} changed (+{} -{}); read it again if you need it.",
+                    path, added, removed
+                ));
+            }
+            updates.push((path.clone(), Some(current)));
+        }
+        if updates.is_empty() {
+            return None;
+        }
+        Some(Refresh {
+            note: format!(
+                "[Files changed on disk since you last saw them]\n\n{}",
+                sections.join("\n\n")
+            ),
+            changed: updates.len(),
+            updates,
+        })
+    }
+
+    /// Remembers the contents a kept turn showed the model.
+    pub fn apply(&mut self, refresh: Refresh) {
+        for (path, contents) in refresh.updates {
+            match contents {
+                Some(contents) => {
+                    self.files.insert(path, contents);
+                }
+                None => {
+                    self.files.remove(&path);
+                }
+            }
+        }
+    }
+
+    fn read(&self, path: &str) -> Option<String> {
+        let path = self.root.join(path);
+        let meta = std::fs::metadata(&path).ok()?;
+        if !meta.is_file() || meta.len() > FILE_REFRESH_MAX_BYTES {
+            return None;
+        }
+        // Binary files fail the UTF-8 check
+        std::fs::read_to_string(path).ok()
+    }
+}
+
+/// Added and removed lines in a unified diff.
+fn line_counts(diff: &str) -> (usize, usize) {
+    let lines = || {
+        diff.lines()
+            .filter(|l| !l.starts_with("+++") && !l.starts_with("---"))
+    };
+    (
+        lines().filter(|l| l.starts_with('+')).count(),
+        lines().filter(|l| l.starts_with('-')).count(),
+    )
+}
@@ -1,107 +1,215 @@
 //! Interactive chat REPL for kaze.
 //!
 //! Provides a multi-turn conversation loop using [`rustyline`] for readline
-//! support (history, line editing). The full conversation history is sent
-//! with each request so the LLM maintains context across turns.
+//! support (history, line editing, identifier completion). The full conversation history is sent
+//! with each request so the LLM maintains context across turns. Turn logic
+//! lives in [`ChatEngine`], which the TUI shares.
 
-mod commands;
+mod attach;
+pub mod commands;
+mod complete;
 mod context;
+pub(crate) mod custom;
+mod duplicates;
+mod engine;
+pub(crate) mod environment;
+mod extract;
+mod file_context;
+pub(crate) mod repo_map;
+pub mod retry;
+mod review;
+
+pub use engine::{ChatEngine, ChatOptions};
 
 use anyhow::Result;
 use colored::Colorize;
 use rustyline::error::ReadlineError;
-use rustyline::DefaultEditor;
-use std::io::{self, Write};
-use std::sync::Arc;
+use rustyline::history::DefaultHistory;
+use rustyline::{CompletionType, Editor};
+use std::io::{self, IsTerminal, Write};
 
 use crate::config::Config;
 use crate::format;
-use crate::message::Message;
-use crate::output::StdoutRenderer;
-use crate::provider::{ModelSelection, Provider};
-use crate::session::Session;
-use crate::tools::ToolRegistry;
+use crate::i18n::{t, tf};
+use crate::output::{self, StdoutRenderer};
+use crate::provider::ModelSelection;
+
+/// Warns when a resumed session was recorded with a different model than
+/// the one configured now, and asks which to continue with.
+///
+/// The choice is written back to the session index. Without an interactive
+/// stdin the configured model wins. Called by both frontends before they
+/// print their header, so the header names the model that will answer.
+pub fn reconcile_session_model(engine: &mut ChatEngine) -> Result<()> {
+    let Some(recorded) = engine.stale_model().map(str::to_string) else {
+        return Ok(());
+    };
+    let configured = engine.model_name().to_string();
+    eprintln!(
+        "{} {}",
+        "!".yellow(),
+        tf(
+            "session.stale_model",
+            &[
+                ("recorded", &recorded.yellow().to_string()),
+                ("configured", &configured.yellow().to_string()),
+            ]
+        ),
+    );
+
+    if !io::stdin().is_terminal() {
+        eprintln!(
+            "{}",
+            tf("session.continuing_with", &[("model", &configured)])
+        );
+        return engine.adopt_configured_model();
+    }
+
+    eprint!(
+        "{}",
+        tf(
+            "session.model_choice",
+            &[("recorded", &recorded), ("configured", &configured)]
+        )
+    );
+    io::stderr().flush()?;
+    let mut response = String::new();
+    io::stdin().read_line(&mut response)?;
+
+    match response.trim().to_lowercase().as_str() {
+        "s" | "session" => {
+            if let Err(e) = engine.switch_model(&recorded) {
+                eprintln!(
+                    "{} {}",
+                    "!".yellow(),
+                    tf(
+                        "session.model_unusable",
+                        &[
+                            ("model", &recorded),
+                            ("error", &format!("{:#}", e)),
+                            ("configured", &configured),
+                        ]
+                    )
+                );
+                return engine.adopt_configured_model();
+            }
+            Ok(())
+        }
+        _ => engine.adopt_configured_model(),
+    }
+}
 
 /// Runs the interactive chat REPL.
 ///
-/// Loads config, builds the provider, and enters a readline loop where each
-/// user input is appended to a [`Session`] which persists messages as JSONL. The entire
-/// history is sent with each request via [`Provider::stream_with_history`]
-/// so the LLM sees all prior context.
+/// Builds a [`ChatEngine`] and enters a readline loop where each user input
+/// is appended to the session, which persists messages as JSONL. The entire
+/// history is sent with each request so the LLM sees all prior context.
 ///
 /// # Readline behavior
 ///
 /// - **Ctrl+C**: cancels current input, stays in REPL
 /// - **Ctrl+D**: exits cleanly with "goodbye."
 /// - Readline history is persisted to `~/.cache/kaze/chat_history.txt`
+/// - **Tab** completes identifiers defined in the project (after `@`, or
+///   after three characters of plain text)
 ///
 /// # Arguments
 ///
 /// * `config` — The loaded kaze configuration.
 /// * `session_id` — Optional session ID to resume an existing session.
 /// * `selection` — The resolved provider + model to use.
+/// * `options` — Command-line switches such as `--verbose`.
 pub async fn run_chat(
     config: Config,
     session_id: Option<String>,
     selection: &ModelSelection,
+    options: ChatOptions,
 ) -> Result<()> {
-    let provider = Provider::from_config(&config, selection)?;
-    let project_root = std::env::current_dir()?;
-    let tools = ToolRegistry::with_builtins(project_root.clone());
-
-    let permission_manager = Arc::new(crate::permissions::PermissionManager::new(
-        config.permissions.clone(),
-    ));
-    let hook = crate::hooks::KazeHook::new(permission_manager, project_root);
-
-    // Create or resume session
-    let mut session = if let Some(ref id) = session_id {
-        let s = Session::load(id)?;
-        let short = &s.id[..8];
+    let mut engine = ChatEngine::new(config, session_id, selection)?.with_options(options)?;
+    reconcile_session_model(&mut engine)?;
+    // Before the header, so it names the fallback model if one took over
+    if let Some(notice) = engine.check_network().await {
+        eprintln!("{} {}", "!".yellow(), notice);
+    }
+    if let Some(notice) = engine.auto_prune() {
+        eprintln!("{} {}", "!".yellow(), notice);
+    }
+
+    let short = engine.session.id[..8].to_string();
+    if engine.is_resumed() {
         println!(
             "{} [session: {}] [model: {}]",
-            "resuming".bold().cyan(),
+            t("chat.resuming").bold().cyan(),
             short.yellow(),
-            s.model.yellow(),
+            engine.session.model.yellow(),
         );
         println!();
         // Display previous messages
-        for msg in &s.messages {
+        for msg in &engine.session.messages {
             if msg.role == crate::message::Role::System {
                 continue;
             }
             println!("{}", format::format_message(msg));
             println!();
         }
-        s
     } else {
-        let mut s = Session::new(&config.model)?;
-        let short = &s.id[..8];
         println!(
-            "{} [session: {}] [model: {}] (Ctrl+D to exit)",
+            "{} [session: {}] [model: {}] {}",
             "kaze chat".bold().cyan(),
             short.yellow(),
-            config.model.yellow(),
+            engine.model_name().yellow(),
+            t("chat.exit_hint"),
         );
         println!();
-        // Add system prompt if configured
-        if let Some(ref sp) = config.system_prompt {
-            s.append(Message::system(sp.clone()))?;
+    }
+    if engine.auto_approve() {
+        println!(
+            "{} {}",
+            t("auto.banner").red().bold(),
+            t("auto.off_hint").red()
+        );
+        println!();
+    }
+    for warning in commands::shadowed_command_warnings() {
+        println!("{}", warning.yellow());
+        println!();
+    }
+
+    // --attach-cmd output goes out with the first message
+    match engine.refresh_attachment().await {
+        Ok(Some(summary)) => {
+            println!(
+                "{}",
+                tf("attach.attached", &[("summary", &summary)]).dimmed()
+            );
+            println!();
         }
-        s
-    };
+        Ok(None) => {}
+        Err(e) => eprintln!("{} {:#}", t("error.label").red().bold(), e),
+    }
 
-    // Set up readline with persistent history
-    let mut rl = DefaultEditor::new()?;
+    // Set up readline with persistent history and identifier completion
+    let rl_config = rustyline::Config::builder()
+        .completion_type(CompletionType::List)
+        .build();
+    let mut rl: Editor<complete::ChatHelper, DefaultHistory> = Editor::with_config(rl_config)?;
+    rl.set_helper(Some(complete::ChatHelper::new(std::env::current_dir()?)));
     let history_path = Config::cache_dir()?.join(crate::constants::HISTORY_FILENAME);
     if history_path.exists() {
         let _ = rl.load_history(&history_path);
     }
 
-    let model_name = config.model.clone();
+    // Accessible mode labels each side of the conversation in words and
+    // leaves the streamed text in place instead of erasing and reprinting it.
+    let accessible = output::is_accessible();
+    let prompt = if accessible {
+        "You: ".to_string()
+    } else {
+        format!("{} ", ">".green().bold())
+    };
 
     loop {
-        let readline = rl.readline(&format!("{} ", ">".green().bold()));
+        let readline = rl.readline(&prompt);
 
         match readline {
             Ok(line) => {
@@ -110,44 +218,59 @@ pub async fn run_chat(
                     continue;
                 }
 
-                // Slash commands
-                if line.starts_with('/') {
-                    match commands::handle_slash_command(
-                        &line,
-                        &mut session,
-                        &provider,
-                        &model_name,
-                        config.compaction_keep_recent(),
-                    )
-                    .await?
-                    {
+                if line.starts_with('!') {
+                    let _ = rl.add_history_entry(&line);
+                    let mut renderer = StdoutRenderer::new();
+                    commands::handle_shell_escape(&line, &mut engine, &mut renderer).await?;
+                    println!();
+                    continue;
+                }
+
+                // Slash commands (a few, like /retry, turn into a message)
+                let is_command = line.starts_with('/');
+                let mut line = line;
+                if is_command {
+                    let mut renderer = StdoutRenderer::new();
+                    match commands::handle_slash_command(&line, &mut engine, &mut renderer).await? {
                         commands::CommandAction::Continue => continue,
+                        commands::CommandAction::Send(text) => line = text,
                         commands::CommandAction::Unknown(cmd) => {
-                            println!("{} Unknown command: {}", "?".yellow(), cmd);
+                            println!(
+                                "{} {}",
+                                "?".yellow(),
+                                tf("chat.unknown_command", &[("command", &cmd)])
+                            );
                             continue;
                         }
                     }
                 }
 
-                let _ = rl.add_history_entry(&line);
-
-                // Add user message to session (before provider call for crash safety)
-                session.append(Message::user(&line))?;
+                if !is_command {
+                    let _ = rl.add_history_entry(&line);
+                }
+                if let Some(hint) = engine.duplicate_hint(&line) {
+                    println!("{}", hint.yellow());
+                    println!("{}", t("duplicate.resend").dimmed());
+                    println!();
+                    continue;
+                }
+                if !engine.confirm_turn(&line).await? {
+                    println!("{}", t("cost.not_sent").dimmed());
+                    println!();
+                    continue;
+                }
                 println!();
 
                 let mut renderer = StdoutRenderer::new();
+                if accessible {
+                    println!("kaze:");
+                }
 
                 // Stream response
-                match provider
-                    .stream_with_tools(
-                        &session.messages,
-                        &tools,
-                        &mut renderer,
-                        crate::constants::MAX_AGENT_ITERATIONS,
-                        hook.clone(),
-                    )
-                    .await
-                {
+                match engine.send(&line, &mut renderer).await {
+                    Ok(_) if accessible => {
+                        engine.manage_context(&mut renderer).await?;
+                    }
                     Ok(response) => {
                         // Erase raw streamed output and reprint with formatting
                         let total_lines = renderer.visual_line_count();
@@ -158,21 +281,12 @@ pub async fn run_chat(
                         // Reprint with markdown-lite formatting (no role label in chat)
                         println!("{}", format::render_markdown_lite(&response));
                         println!();
-                        session.append(Message::assistant(response.clone()))?;
 
                         // Token counting, display, and auto-compaction
-                        context::handle_context_management(
-                            &mut session,
-                            &provider,
-                            &model_name,
-                            &config,
-                        )
-                        .await?;
+                        engine.manage_context(&mut renderer).await?;
                     }
                     Err(e) => {
-                        // Pop the failed user message so user can retry
-                        session.messages.pop();
-                        eprintln!("{} {}", "error:".red().bold(), e);
+                        eprintln!("{} {}", t("error.label").red().bold(), e);
                     }
                 }
                 println!();
@@ -182,7 +296,7 @@ pub async fn run_chat(
                 continue;
             }
             Err(ReadlineError::Eof) => {
-                println!("{}", "goodbye.".dimmed());
+                println!("{}", t("chat.goodbye").dimmed());
                 break;
             }
             Err(e) => {
@@ -0,0 +1,64 @@
+//! The repo map chat can send the model: the project's source files and
+//! the names each defines (see [`crate::symbols::repo_map`]), so it can
+//! find its way around without listing and grepping first.
+//!
+//! The map is built once per session, before its first request, from the
+//! cache when the tree hasn't changed, and cut to `[context]
+//! repo_map_tokens`. Like the environment facts, it is appended to the
+//! system prompt per request rather than stored.
+
+use std::path::PathBuf;
+
+use crate::symbols::load_repo_map;
+use crate::tokens::truncate_to_first_tokens;
+
+/// The repo map block for one chat session.
+pub(crate) struct RepoMap {
+    root: PathBuf,
+    budget: usize,
+    model: String,
+    /// The map as sent, once built; empty when there was nothing to map.
+    text: Option<String>,
+}
+
+impl RepoMap {
+    /// A map of `root` cut to `budget` tokens of `model`, built on the first
+    /// [`load`](Self::load).
+    pub fn new(root: PathBuf, budget: usize, model: &str) -> Self {
+        Self {
+            root,
+            budget,
+            model: model.to_string(),
+            text: None,
+        }
+    }
+
+    /// Builds the map if it hasn't been yet, off the async runtime.
+    pub async fn load(&mut self) {
+        if self.text.is_some() {
+            return;
+        }
+        let (root, budget, model) = (self.root.clone(), self.budget, self.model.clone());
+        let text = tokio::task::spawn_blocking(move || {
+            let (mut kept, dropped) =
+                truncate_to_first_tokens(&load_repo_map(&root), budget, &model);
+            if dropped > 0 {
+                kept.push_str(&format!("\n[{} more files left out]", dropped));
+            }
+            kept
+        })
+        .await
+        .unwrap_or_default();
+        self.text = Some(text);
+    }
+
+    /// The block to append to the system prompt, once there is a map.
+    pub fn block(&self) -> Option<String> {
+        let text = self.text.as_deref().filter(|text| !text.is_empty())?;
+        Some(format!(
+            "The project's source files and the names each defines, as a starting point \
+             (read a file before relying on it):\n<repo_map>\n{}\n</repo_map>",
+            text
+        ))
+    }
+}
@@ -0,0 +1,52 @@
+//! Detection of empty and refusal-style responses, and the adjusted prompt
+//! `/retry` sends in their place.
+
+use crate::constants::{REFUSAL_MAX_CHARS, REFUSAL_PREFIXES, RETRY_EMPTY_NOTE, RETRY_REFUSAL_NOTE};
+
+/// Why a response was kept out of the conversation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseIssue {
+    /// No text at all (or only whitespace).
+    Empty,
+    /// A short reply that declines the request.
+    Refusal,
+}
+
+/// A turn that can be re-sent with `/retry`.
+#[derive(Debug, Clone)]
+pub struct PendingRetry {
+    /// The user's original message.
+    pub input: String,
+    pub issue: ResponseIssue,
+}
+
+impl PendingRetry {
+    /// The original message with a note steering the model past the issue.
+    pub fn adjusted_prompt(&self) -> String {
+        let note = match self.issue {
+            ResponseIssue::Empty => RETRY_EMPTY_NOTE,
+            ResponseIssue::Refusal => RETRY_REFUSAL_NOTE,
+        };
+        format!("{}\n\n{}", self.input, note)
+    }
+}
+
+/// Flags a response that is empty or reads as a refusal.
+///
+/// Refusal detection is a heuristic on the opening of short responses, so
+/// an answer that merely starts apologetically and then helps is not
+/// flagged.
+pub fn classify(response: &str) -> Option<ResponseIssue> {
+    let trimmed = response.trim();
+    if trimmed.is_empty() {
+        return Some(ResponseIssue::Empty);
+    }
+    if trimmed.chars().count() > REFUSAL_MAX_CHARS {
+        return None;
+    }
+    let opening = trimmed.to_lowercase().replace('\u{2019}', "'");
+    REFUSAL_PREFIXES
+        .iter()
+        .any(|prefix| opening.starts_with(prefix))
+        .then_some(ResponseIssue::Refusal)
+}
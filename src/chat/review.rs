@@ -0,0 +1,115 @@
+//! Material for `/review`: a git diff or a file, wrapped in review
+//! instructions.
+//!
+//! With no argument the uncommitted changes are reviewed (`git diff HEAD`);
+//! `--staged` takes only what is staged. A path reviews its uncommitted
+//! changes, or the whole file when it has none. The message is sent as an
+//! ordinary turn, so the findings stream inline and stay in the
+//! conversation for follow-up questions.
+
+use anyhow::{Context, Result};
+use std::path::Path;
+
+use crate::constants::{REVIEW_MAX_TOKENS, REVIEW_PROMPT};
+
+/// A prepared `/review` turn.
+pub struct Review {
+    /// The message to send.
+    pub message: String,
+    /// One-line description for the user, e.g. `staged changes: 3 files, 120 lines`.
+    pub summary: String,
+}
+
+/// Builds the `/review` message for `args` (empty, `--staged`, or a path).
+///
+/// `extra` is the user's `review` prompt mode, appended to the
+/// instructions when configured.
+pub async fn prepare(args: &str, model: &str, extra: Option<&str>) -> Result<Review> {
+    let (label, source, text) = match args {
+        "" => {
+            let diff = git_diff(&["HEAD"]).await?;
+            ("uncommitted changes".to_string(), "git diff HEAD", diff)
+        }
+        "--staged" | "--cached" => {
+            let diff = git_diff(&["--cached"]).await?;
+            ("staged changes".to_string(), "git diff --cached", diff)
+        }
+        path => {
+            let diff = git_diff(&["HEAD", "--", path]).await.unwrap_or_default();
+            if !diff.trim().is_empty() {
+                (format!("changes to {}", path), "git diff HEAD", diff)
+            } else if Path::new(path).is_file() {
+                let content = tokio::fs::read_to_string(path)
+                    .await
+                    .with_context(|| format!("Failed to read {}", path))?;
+                (path.to_string(), "file", content)
+            } else if Path::new(path).exists() {
+                anyhow::bail!("No uncommitted changes under {}", path);
+            } else {
+                anyhow::bail!("{} does not exist", path);
+            }
+        }
+    };
+    if text.trim().is_empty() {
+        anyhow::bail!("Nothing to review: no {}", label);
+    }
+
+    let files = if source == "file" {
+        1
+    } else {
+        text.lines()
+            .filter(|l| l.starts_with("diff --git "))
+            .count()
+    };
+    let lines = text.lines().count();
+    let budget = REVIEW_MAX_TOKENS.min(crate::tokens::context_window_size(model) / 2);
+    let (text, dropped) = crate::tokens::truncate_to_first_tokens(text.trim_end(), budget, model);
+
+    let mut summary = format!(
+        "{}: {} file{}, {} lines",
+        label,
+        files,
+        if files == 1 { "" } else { "s" },
+        lines
+    );
+    let mut note = String::new();
+    if dropped > 0 {
+        summary.push_str(&format!(", last {} cut to fit the token budget", dropped));
+        note = format!(
+            "\n(The last {} lines were cut; mention that the review is partial.)",
+            dropped
+        );
+    }
+
+    let mut instructions = REVIEW_PROMPT.to_string();
+    if let Some(extra) = extra {
+        instructions.push_str("\n\n");
+        instructions.push_str(extra.trim());
+    }
+    let tag = if source == "file" { "file" } else { "diff" };
+    let message = format!(
+        "{}\n\n<{tag} source=\"{}\">\n{}\n</{tag}>{}",
+        instructions,
+        if source == "file" { &label } else { source },
+        text,
+        note,
+        tag = tag
+    );
+    Ok(Review { message, summary })
+}
+
+/// Runs `git diff` with `args` in the current directory and returns stdout.
+async fn git_diff(args: &[&str]) -> Result<String> {
+    let output = tokio::process::Command::new("git")
+        .args(["diff", "--no-color"])
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .await
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("git diff {} failed: {}", args.join(" "), stderr.trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
@@ -0,0 +1,421 @@
+//! One-shot `kaze ask` handling.
+//!
+//! Runs a single prompt through the tool-enabled agent loop, with an
+//! optional response cache in front of the provider. Output is either
+//! streamed and colored for a terminal, or, with `--json`, a single JSON
+//! object on stdout for scripts.
+//!
+//! Piped stdin (`cat build.log | kaze ask "why did this fail?"`) is attached
+//! to the question as a delimited context block.
+//!
+//! With `--schema`, the answer is instead a JSON document constrained by a
+//! JSON Schema, validated and printed alone on stdout, so scripts can
+//! consume it directly.
+
+use anyhow::Result;
+use colored::Colorize;
+use serde::Serialize;
+use std::io::{IsTerminal, Read};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use kaze::chat::retry::ResponseIssue;
+use kaze::message::ImagePart;
+use kaze::metrics::{MeteredRenderer, TurnMetrics};
+use kaze::output::{self, RecordedToolCall, RecordingRenderer, Renderer, TeeRenderer};
+use kaze::schema::OutputSchema;
+use kaze::usage::{format_cost, TokenUsage, UsageRecord};
+use kaze::{agent, chat, config, message::Message, provider, response_cache, tools::ToolRegistry};
+
+/// Arguments of the `ask` subcommand.
+pub(crate) struct AskOptions {
+    pub prompt: String,
+    pub model: Option<String>,
+    pub provider: Option<String>,
+    pub no_cache: bool,
+    pub json: bool,
+    /// Ignore stdin even when it is not a terminal.
+    pub no_stdin: bool,
+    /// Append the raw stream and tool events to this file.
+    pub tee: Option<PathBuf>,
+    /// `--image` paths, URLs, or data URIs.
+    pub images: Vec<String>,
+    /// `--schema`: answer with JSON conforming to this schema file.
+    pub schema: Option<PathBuf>,
+    /// `--turn-timeout` in seconds; overrides `turn_timeout_secs`.
+    pub turn_timeout: Option<u64>,
+    pub verbose: bool,
+}
+
+/// Content read from a piped stdin.
+struct PipedInput {
+    /// The (possibly truncated) text.
+    text: String,
+    /// Total lines received.
+    lines: usize,
+    /// Lines dropped from the start to fit the token budget.
+    dropped: usize,
+}
+
+/// The object printed by `kaze ask --json`.
+#[derive(Serialize)]
+struct AskReport<'a> {
+    response: &'a str,
+    provider: &'a str,
+    model: &'a str,
+    /// True when the answer came from the response cache.
+    cached: bool,
+    tool_calls: &'a [RecordedToolCall],
+    /// Provider-reported token counts; `null` if the stream had none.
+    usage: Option<TokenUsage>,
+    cost_usd: Option<f64>,
+    duration_ms: u64,
+    /// True when the turn hit its time limit and `response` is partial.
+    timed_out: bool,
+}
+
+/// Answers a single prompt and exits.
+pub(crate) async fn handle_ask(opts: AskOptions) -> Result<()> {
+    let started = Instant::now();
+    let question = opts.prompt;
+
+    let config = config::Config::load()?;
+
+    let selection =
+        provider::resolve_model(opts.provider.as_deref(), opts.model.as_deref(), &config)?;
+    let provider_name = selection.provider.as_str();
+    let images = opts
+        .images
+        .iter()
+        .map(|spec| ImagePart::parse(spec))
+        .collect::<Result<Vec<_>>>()?;
+    if !images.is_empty() && !kaze::models::supports_vision(&selection.model) {
+        anyhow::bail!(kaze::i18n::tf(
+            "model.no_vision",
+            &[("model", &selection.model)]
+        ));
+    }
+
+    let piped = if opts.no_stdin {
+        None
+    } else {
+        read_piped_stdin(&selection.model)?
+    };
+    if question.is_empty() && piped.is_none() && images.is_empty() {
+        anyhow::bail!(kaze::i18n::t("ask.no_prompt"));
+    }
+    let prompt = match piped {
+        Some(ref input) => with_stdin_block(&question, &input.text),
+        None => question.clone(),
+    };
+
+    if let Some(ref path) = opts.schema {
+        let schema = OutputSchema::load(path)?;
+        let message = Message::user_with_images(&prompt, images);
+        let limit = config.turn_timeout(opts.turn_timeout);
+        return ask_structured(&config, &selection, message, &schema, opts.no_cache, limit).await;
+    }
+
+    if !opts.json {
+        println!(
+            "{} [model: {}]",
+            "kaze".bold().cyan(),
+            selection.model.yellow(),
+        );
+        println!();
+        println!("{} {}", ">".green().bold(), question);
+        if let Some(ref input) = piped {
+            let note = if input.dropped > 0 {
+                format!(
+                    "(+ stdin: {} lines, first {} dropped to fit the token budget)",
+                    input.lines, input.dropped
+                )
+            } else {
+                format!("(+ stdin: {} lines)", input.lines)
+            };
+            println!("{}", note.dimmed());
+        }
+        for spec in &opts.images {
+            println!("{}", format!("(+ image: {})", spec).dimmed());
+        }
+        println!();
+    }
+
+    let system_prompt = config.system_preamble();
+    // The cache key covers text only, so requests with images always go out
+    let use_cache = config.cache_enabled() && !opts.no_cache && images.is_empty();
+    let cache_key = response_cache::request_key(
+        provider_name,
+        &selection.model,
+        system_prompt.as_deref(),
+        &prompt,
+    );
+    if use_cache {
+        if let Some(cached) = response_cache::lookup(&cache_key, config.cache_ttl_secs()) {
+            if opts.json {
+                return print_report(&AskReport {
+                    response: &cached,
+                    provider: provider_name,
+                    model: &selection.model,
+                    cached: true,
+                    tool_calls: &[],
+                    usage: None,
+                    cost_usd: None,
+                    duration_ms: started.elapsed().as_millis() as u64,
+                    timed_out: false,
+                });
+            }
+            let mut renderer = output::StdoutRenderer::new();
+            renderer.render_token(&cached);
+            renderer.render_done();
+            println!();
+            println!(
+                "{}",
+                "(cached response, use --no-cache to refresh)".dimmed()
+            );
+            return Ok(());
+        }
+    }
+
+    let provider = provider::Provider::from_config(&config, &selection)?;
+    let project_root = std::env::current_dir()?;
+
+    let mut messages = Vec::new();
+    if let Some(sp) = system_prompt {
+        messages.push(Message::system(sp));
+    }
+    messages.push(Message::user_with_images(&prompt, images));
+
+    let permission_manager = Arc::new(
+        kaze::permissions::PermissionManager::new(config.permissions.clone())
+            .with_write_ceiling(config.write_file_confirm_bytes()),
+    );
+    let hook = kaze::hooks::KazeHook::new(permission_manager, project_root.clone());
+    let tools = ToolRegistry::with_settings(project_root.clone(), &config.tools).with_task(
+        &config,
+        &selection,
+        project_root,
+        hook.clone(),
+    )?;
+    tools.audit().start(None);
+
+    let mut tee_file = opts.tee.as_deref().map(output::open_tee).transpose()?;
+    if let Some(ref mut file) = tee_file {
+        use std::io::Write;
+        writeln!(file, "\n> {}\n", question)?;
+    }
+    let mut stdout_renderer = output::StdoutRenderer::new();
+    let mut recorder = RecordingRenderer::new();
+    let display: &mut dyn Renderer = if opts.json {
+        &mut recorder
+    } else {
+        &mut stdout_renderer
+    };
+    let mut tee;
+    let inner: &mut dyn Renderer = match tee_file.as_mut() {
+        Some(file) => {
+            tee = TeeRenderer::new(display, file);
+            &mut tee
+        }
+        None => display,
+    };
+    let limit = config.turn_timeout(opts.turn_timeout);
+    let mut metered = MeteredRenderer::new(inner);
+    let result = tokio::select! {
+        result = agent::agent_loop(
+            &provider,
+            &mut messages,
+            &tools,
+            &mut metered,
+            kaze::constants::MAX_AGENT_ITERATIONS,
+            hook,
+        ) => Some(result),
+        _ = agent::deadline(limit) => None,
+    };
+    let (response, timed_out) = match result {
+        Some(Ok(response)) => (response, false),
+        Some(Err(e)) => return Err(provider::explain_error(e, selection.provider, &config).await),
+        None => {
+            metered.render_done();
+            (metered.text().to_string(), true)
+        }
+    };
+    let metrics = metered.finish();
+    let timeout =
+        timed_out.then(|| agent::timeout_summary(limit.unwrap_or_default(), &metrics, &response));
+    // Empty, declined, and cut-short answers are never cached
+    let issue = chat::retry::classify(&response);
+    match issue {
+        _ if timed_out => {}
+        Some(ResponseIssue::Empty) => warn("The model returned an empty response."),
+        Some(ResponseIssue::Refusal) => warn("The model declined this request."),
+        None if use_cache => {
+            if let Err(e) = response_cache::store(&cache_key, &response) {
+                warn(&format!("Failed to cache response: {}", e));
+            }
+        }
+        None => {}
+    }
+
+    let token_count = kaze::tokens::count_tokens(&response, &selection.model)?;
+    let record = UsageRecord::new(provider_name, &selection.model, None, token_count, &metrics);
+    if let Err(e) = record.append() {
+        warn(&format!("Failed to record usage: {}", e));
+    }
+
+    if opts.json {
+        print_report(&AskReport {
+            response: &response,
+            provider: provider_name,
+            model: &selection.model,
+            cached: false,
+            tool_calls: &recorder.tool_calls,
+            usage: metrics.usage,
+            cost_usd: record.cost_usd,
+            duration_ms: started.elapsed().as_millis() as u64,
+            timed_out,
+        })?;
+    } else {
+        print_footer(token_count, &record, &metrics, opts.verbose);
+    }
+    match timeout {
+        Some(summary) => anyhow::bail!(summary),
+        None => Ok(()),
+    }
+}
+
+/// Answers with JSON conforming to `schema` and prints it on stdout.
+///
+/// The cache key covers the schema as well as the prompt, and cached
+/// answers are validated again in case the schema file changed meaning.
+async fn ask_structured(
+    config: &config::Config,
+    selection: &provider::ModelSelection,
+    message: Message,
+    schema: &OutputSchema,
+    no_cache: bool,
+    limit: Option<Duration>,
+) -> Result<()> {
+    let started = Instant::now();
+    let provider_name = selection.provider.as_str();
+    let system_prompt = config.system_preamble();
+    let use_cache = config.cache_enabled() && !no_cache && message.images().next().is_none();
+    let cache_key = response_cache::request_key(
+        provider_name,
+        &selection.model,
+        system_prompt.as_deref(),
+        &format!("{}\n\n<schema>{}</schema>", message.text(), schema.value()),
+    );
+    if use_cache {
+        let cached = response_cache::lookup(&cache_key, config.cache_ttl_secs())
+            .and_then(|text| serde_json::from_str::<serde_json::Value>(&text).ok())
+            .filter(|value| schema.validate(value).is_ok());
+        if let Some(value) = cached {
+            println!("{}", serde_json::to_string_pretty(&value)?);
+            return Ok(());
+        }
+    }
+
+    let provider = provider::Provider::from_config(config, selection)?;
+    let result = tokio::select! {
+        result = provider.prompt_structured(system_prompt.as_deref(), &message, schema) => result,
+        _ = agent::deadline(limit) => {
+            let summary = agent::timeout_summary(limit.unwrap_or_default(), &TurnMetrics::default(), "");
+            anyhow::bail!(summary);
+        }
+    };
+    let (answer, usage) = match result {
+        Ok(done) => done,
+        Err(e) => return Err(provider::explain_error(e, selection.provider, config).await),
+    };
+
+    let metrics = TurnMetrics {
+        total: started.elapsed(),
+        usage: Some(usage),
+        ..TurnMetrics::default()
+    };
+    let record = UsageRecord::new(provider_name, &selection.model, None, 0, &metrics);
+    if let Err(e) = record.append() {
+        warn(&format!("Failed to record usage: {}", e));
+    }
+
+    schema.validate(&answer)?;
+    let text = serde_json::to_string_pretty(&answer)?;
+    if use_cache {
+        if let Err(e) = response_cache::store(&cache_key, &text) {
+            warn(&format!("Failed to cache response: {}", e));
+        }
+    }
+    println!("{}", text);
+    Ok(())
+}
+
+/// Reads stdin when it is piped rather than a terminal.
+///
+/// Returns `None` for a terminal or empty input. Input over the token
+/// budget keeps its last lines. Permission prompts read stdin too, so tools
+/// set to "ask" are denied while input is piped.
+fn read_piped_stdin(model: &str) -> Result<Option<PipedInput>> {
+    let mut stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        return Ok(None);
+    }
+    let mut bytes = Vec::new();
+    stdin.read_to_end(&mut bytes)?;
+    let text = String::from_utf8_lossy(&bytes);
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+
+    let budget =
+        kaze::constants::STDIN_CONTEXT_MAX_TOKENS.min(kaze::tokens::context_window_size(model) / 2);
+    let lines = text.lines().count();
+    let (text, dropped) = kaze::tokens::truncate_to_last_tokens(&text, budget, model);
+    Ok(Some(PipedInput {
+        text,
+        lines,
+        dropped,
+    }))
+}
+
+/// Appends piped input to the question inside `<stdin>` tags, so the model
+/// can tell the data apart from the instructions.
+fn with_stdin_block(question: &str, input: &str) -> String {
+    let block = format!("<stdin>\n{}\n</stdin>", input);
+    if question.is_empty() {
+        block
+    } else {
+        format!("{}\n\n{}", question, block)
+    }
+}
+
+/// Prints token usage, cost, and (with `--verbose`) timings after a
+/// streamed answer.
+fn print_footer(token_count: usize, record: &UsageRecord, metrics: &TurnMetrics, verbose: bool) {
+    let limit = 128_000;
+    println!();
+    println!(
+        "{}",
+        format!(
+            "Tokens: {}",
+            kaze::tokens::format_token_usage(token_count, limit)
+        )
+        .dimmed()
+    );
+    if let Some(cost) = record.cost_usd {
+        println!("{}", format!("Cost: {}", format_cost(cost)).dimmed());
+    }
+    if verbose {
+        println!("{}", metrics.summary().dimmed());
+    }
+}
+
+fn print_report(report: &AskReport) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(report)?);
+    Ok(())
+}
+
+fn warn(message: &str) {
+    eprintln!("{} {}", "warning:".yellow().bold(), message);
+}
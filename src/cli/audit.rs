@@ -0,0 +1,110 @@
+//! `kaze audit`: the tool calls the agent ran, read back from the audit log.
+
+use anyhow::Result;
+use colored::Colorize;
+
+use kaze::audit::{self, AuditRecord};
+use kaze::tokens::format_number;
+
+use super::AuditAction;
+
+pub(crate) fn handle_audit(action: AuditAction) -> Result<()> {
+    match action {
+        AuditAction::Show {
+            session,
+            limit,
+            json,
+        } => audit_show(session.as_deref(), limit, json),
+    }
+}
+
+/// Prints the last `limit` calls (0 for all), oldest first, optionally only
+/// those of sessions whose ID starts with `session`.
+fn audit_show(session: Option<&str>, limit: usize, json: bool) -> Result<()> {
+    let records: Vec<AuditRecord> = audit::read_log()?
+        .into_iter()
+        .filter(|record| match session {
+            Some(prefix) => record
+                .session_id
+                .as_deref()
+                .is_some_and(|id| id.starts_with(prefix)),
+            None => true,
+        })
+        .collect();
+    if records.is_empty() {
+        match session {
+            Some(prefix) => println!(
+                "{}",
+                format!("No tool calls recorded for session {}.", prefix).dimmed()
+            ),
+            None => println!("{}", "No tool calls recorded yet.".dimmed()),
+        }
+        println!(
+            "Tool calls are recorded to {}",
+            audit::log_path()?.display()
+        );
+        return Ok(());
+    }
+
+    let skip = match limit {
+        0 => 0,
+        limit => records.len().saturating_sub(limit),
+    };
+    if skip > 0 && !json {
+        println!(
+            "{}",
+            format!(
+                "({} earlier calls not shown; --limit 0 shows all)",
+                format_number(skip)
+            )
+            .dimmed()
+        );
+    }
+    for record in &records[skip..] {
+        if json {
+            println!("{}", serde_json::to_string(record)?);
+        } else {
+            print_record(record);
+        }
+    }
+    Ok(())
+}
+
+fn print_record(record: &AuditRecord) {
+    let time = chrono::DateTime::parse_from_rfc3339(&record.timestamp)
+        .map(|t| {
+            t.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_else(|_| record.timestamp.clone());
+    let session = record
+        .session_id
+        .as_deref()
+        .map_or("-", |id| id.get(..8).unwrap_or(id));
+    // Pad first, then colorize to avoid ANSI escape code width issues
+    let status = match record.exit_code {
+        Some(code) => format!("exit {}", code),
+        None => record.status.clone(),
+    };
+    let status = format!("{:<7}", status);
+    let status = if record.status == "ok" {
+        status.green()
+    } else {
+        status.red()
+    };
+    println!(
+        "{}  {}  {}  {}  {}  {}",
+        time.dimmed(),
+        format!("{:<8}", session).dimmed(),
+        format!("{:<12}", record.tool).bold(),
+        status,
+        format!(
+            "{:>9} B {:>6}ms",
+            format_number(record.result_bytes),
+            record.duration_ms
+        )
+        .dimmed(),
+        record.args,
+    );
+}
@@ -1,15 +1,30 @@
 //! Command-line interface definition and dispatch for kaze.
 //!
 //! Uses [`clap`] for argument parsing with derive macros. Each subcommand is
-//! routed to its handler — session operations live in the [`session`] submodule.
+//! routed to its handler — one-shot questions live in the [`ask`] submodule,
+//! headless agent runs in [`run`] (scheduled through [`schedule`]), session
+//! operations in the [`session`] submodule, usage statistics in [`stats`], and
+//! the tool call log in [`audit`].
 
+mod ask;
+mod audit;
+mod parallel;
+mod run;
+mod schedule;
+mod selftest;
+mod serve;
 mod session;
+mod stats;
+mod suggest;
+
+#[cfg(test)]
+mod tests;
 
-use crate::{agent, chat, config, message::Message, output, provider, tools::ToolRegistry};
 use anyhow::Result;
 use clap::{Parser, Subcommand};
 use colored::Colorize;
-use std::sync::Arc;
+use kaze::{chat, config, provider};
+use std::io::{IsTerminal, Write};
 
 /// Top-level CLI structure for kaze.
 ///
@@ -18,6 +33,25 @@ use std::sync::Arc;
 #[derive(Parser)]
 #[command(name = "kaze", about = "A memory-minimal AI coding agent")]
 pub struct Cli {
+    /// Show per-turn timing (first token, model vs tool time)
+    #[arg(short, long, global = true)]
+    pub verbose: bool,
+    /// Allow running with file tools from the home directory or `/`
+    #[arg(long, global = true)]
+    pub allow_broad_root: bool,
+    /// Deny every tool that can change files (write_file, edit, multi_edit,
+    /// apply_patch, bash, and git beyond status, diff, log, show, blame)
+    #[arg(long, global = true)]
+    pub read_only: bool,
+    /// Run tool calls without asking, for long unattended work (denied
+    /// tools, git pushes, and --read-only still hold; `/auto off` in chat
+    /// turns it off)
+    #[arg(short = 'y', long, global = true)]
+    pub yes: bool,
+    /// Write a Chrome trace of the agent loop, model calls, tools, and
+    /// rendering to this file (open it in Perfetto or speedscope)
+    #[arg(long, global = true, value_name = "PATH")]
+    pub profile: Option<std::path::PathBuf>,
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -38,12 +72,109 @@ pub enum Commands {
         /// Provider to use (anthropic, openai, openrouter, ollama)
         #[arg(short, long)]
         provider: Option<String>,
+        /// Bypass the response cache for this request
+        #[arg(long)]
+        no_cache: bool,
+        /// Print a single JSON object (response, tool calls, usage, timing)
+        /// instead of streamed output
+        #[arg(long)]
+        json: bool,
+        /// Don't attach piped stdin to the prompt
+        #[arg(long)]
+        no_stdin: bool,
+        /// Append the raw streamed response and tool events to this file
+        #[arg(long, value_name = "PATH")]
+        tee: Option<std::path::PathBuf>,
+        /// Attach an image (file, http(s) URL, or data URI) for vision-capable
+        /// models; repeat for several
+        #[arg(long, value_name = "PATH|URL")]
+        image: Vec<String>,
+        /// Answer with JSON matching this JSON Schema file (no tools); the
+        /// answer is validated before it is printed
+        #[arg(long, value_name = "PATH", conflicts_with = "json")]
+        schema: Option<std::path::PathBuf>,
+        /// Cancel the turn after this many seconds, keeping what it produced
+        /// (overrides turn_timeout_secs; 0 disables)
+        #[arg(long, value_name = "SECS")]
+        turn_timeout: Option<u64>,
+    },
+    /// Run a task with tools and no REPL (for CI jobs and git hooks)
+    ///
+    /// Exits 0 on success, 1 on failure, 3 if the turn limit is hit, and 4
+    /// if the turn times out.
+    Run {
+        /// The task to perform (added to the template's task with --template)
+        task: Vec<String>,
+        /// Run the `[[task]]` entries of this TOML file, each in its own git
+        /// worktree, and print a table of the results
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["task", "template"])]
+        parallel: Option<std::path::PathBuf>,
+        /// Tasks run at once with --parallel (default: the file's
+        /// `concurrency`, else 4)
+        #[arg(short, long, requires = "parallel")]
+        jobs: Option<usize>,
+        /// Keep the worktrees and branches of --parallel tasks that failed
+        /// or changed nothing, which are otherwise removed
+        #[arg(long, requires = "parallel")]
+        keep: bool,
+        /// Run a task saved under `[templates.<name>]` in the config
+        #[arg(short, long)]
+        template: Option<String>,
+        /// Model to use (overrides config)
+        #[arg(short, long)]
+        model: Option<String>,
+        /// Provider to use (anthropic, openai, openrouter, ollama)
+        #[arg(short, long)]
+        provider: Option<String>,
+        /// Maximum agent turns before giving up (default: the template's, else 25)
+        #[arg(long)]
+        max_turns: Option<usize>,
+        /// Allow tools whose permission is "ask" (denied tools stay denied)
+        #[arg(long)]
+        auto_approve: bool,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = run::RunOutput::Text)]
+        output: run::RunOutput,
+        /// Cancel the turn after this many seconds, keeping what it produced
+        /// (overrides turn_timeout_secs; 0 disables)
+        #[arg(long, value_name = "SECS")]
+        turn_timeout: Option<u64>,
+        /// Sampling seed, for reproducible runs where the provider supports
+        /// one; implies --temperature 0 (overrides `[generation] seed`)
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Sampling temperature (overrides `[generation] temperature`)
+        #[arg(long)]
+        temperature: Option<f64>,
+    },
+    /// Suggest one shell command for a request (for shell widgets)
+    ///
+    /// Prints only the command on stdout. Uses `[suggest] model` when set.
+    Suggest {
+        /// What the command should do
+        request: Vec<String>,
+        /// Model to use (overrides `[suggest] model`)
+        #[arg(short, long)]
+        model: Option<String>,
+        /// Provider to use (anthropic, openai, openrouter, ollama)
+        #[arg(short, long)]
+        provider: Option<String>,
+        /// Shell to write the command for (defaults to $SHELL)
+        #[arg(long)]
+        shell: Option<String>,
+        /// Print a widget that binds Alt+K to kaze suggest, for eval in
+        /// your shell's startup file
+        #[arg(long, value_enum, value_name = "SHELL", conflicts_with_all = ["request", "shell"])]
+        init: Option<suggest::WidgetShell>,
     },
     /// Start an interactive chat session
     Chat {
         /// Resume a specific session
         #[arg(short, long)]
         session: Option<String>,
+        /// Resume the most recently updated session of this project
+        #[arg(short = 'c', long = "continue", conflicts_with = "session")]
+        continue_last: bool,
         /// Provider to use (anthropic, openai, openrouter, ollama)
         #[arg(long)]
         provider: Option<String>,
@@ -53,9 +184,32 @@ pub enum Commands {
         /// Open the terminal UI
         #[arg(long)]
         tui: bool,
+        /// Append each raw streamed response and tool events to this file
+        #[arg(long, value_name = "PATH")]
+        tee: Option<std::path::PathBuf>,
+        /// Run this shell command and attach its output to the first message
+        /// (rerun with /refresh-cmd)
+        #[arg(long, value_name = "CMD")]
+        attach_cmd: Option<String>,
+        /// Cancel the turn after this many seconds, keeping what it produced
+        /// (overrides turn_timeout_secs; 0 disables)
+        #[arg(long, value_name = "SECS")]
+        turn_timeout: Option<u64>,
+        /// Sampling seed, for reproducible runs where the provider supports
+        /// one; implies --temperature 0 (overrides `[generation] seed`)
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Sampling temperature (overrides `[generation] temperature`)
+        #[arg(long)]
+        temperature: Option<f64>,
     },
     /// List available models
-    Models,
+    Models {
+        /// Send a tiny prompt to each configured provider and report whether
+        /// it answers and how fast the first token arrives
+        #[arg(long)]
+        probe: bool,
+    },
     /// Manage configuration
     Config {
         #[command(subcommand)]
@@ -66,6 +220,55 @@ pub enum Commands {
         #[command(subcommand)]
         action: SessionAction,
     },
+    /// Serve a read-only, auto-refreshing HTML view of a session, to
+    /// follow a run from another window or share it while pairing
+    Serve {
+        /// Session to show (a unique prefix of its ID is enough)
+        #[arg(long, value_name = "SESSION_ID")]
+        watch: String,
+        /// Port to listen on (0 picks a free one)
+        #[arg(long, default_value_t = kaze::constants::SERVE_DEFAULT_PORT)]
+        port: u16,
+        /// Address to listen on; anyone who can reach it can read the session
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+    },
+    /// Review the tool calls the agent ran, from the audit log
+    Audit {
+        #[command(subcommand)]
+        action: AuditAction,
+    },
+    /// Show usage statistics across sessions
+    Stats {
+        /// Rows to show in the tool and file tables
+        #[arg(long, default_value_t = kaze::constants::STATS_TOP_ROWS)]
+        top: usize,
+        /// Recent weeks to show in the spend table
+        #[arg(long, default_value_t = kaze::constants::STATS_WEEKS)]
+        weeks: usize,
+    },
+    /// Run a `[templates]` entry on a recurring schedule (systemd timer or cron)
+    Schedule {
+        #[command(subcommand)]
+        action: ScheduleAction,
+    },
+    /// Bundle recent errors, redacted config, version and OS info, and the
+    /// last session's tail into a tarball to attach to a GitHub issue
+    Report {
+        /// Where to write the archive (default: ./kaze-report-<time>.tar.gz)
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+        /// Leave out the last session's messages
+        #[arg(long)]
+        no_session: bool,
+    },
+    /// Check the agent loop, tools, permissions, and sessions end to end
+    /// against a scripted offline model
+    Selftest {
+        /// Keep the scratch directory instead of removing it
+        #[arg(long)]
+        keep: bool,
+    },
 }
 
 /// Subcommands for the `config` command.
@@ -78,6 +281,84 @@ pub enum ConfigAction {
     Show,
     /// Set a config value
     Set { key: String, value: String },
+    /// Export the current config as a shareable bundle
+    Export {
+        /// Destination file for the bundle
+        path: std::path::PathBuf,
+        /// Strip API keys, provider headers, and account IDs from the exported bundle
+        #[arg(long)]
+        no_secrets: bool,
+    },
+    /// Import a config bundle into the global config, after showing what
+    /// it changes
+    Import {
+        /// Bundle file to import
+        path: std::path::PathBuf,
+        /// Import without asking (needed without a terminal)
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Pin the resolved provider and model in the project's kaze.toml
+    Pin {
+        /// Model to pin (defaults to the currently resolved one)
+        #[arg(short, long)]
+        model: Option<String>,
+        /// Provider to pin (anthropic, openai, openrouter, ollama, bedrock)
+        #[arg(short, long)]
+        provider: Option<String>,
+        /// Remove the pin instead
+        #[arg(long, conflicts_with_all = ["model", "provider"])]
+        clear: bool,
+    },
+    /// Approve the permissions and provider endpoints the project's
+    /// kaze.toml sets beyond the global config, so they apply without a
+    /// prompt (e.g. in CI)
+    Trust,
+}
+
+/// Subcommands for the `audit` command.
+#[derive(Subcommand)]
+pub enum AuditAction {
+    /// List recorded tool calls, oldest first
+    Show {
+        /// Only calls from this session (a unique prefix of its ID is enough)
+        #[arg(long, value_name = "SESSION_ID")]
+        session: Option<String>,
+        /// Most recent calls to show (0 for all)
+        #[arg(long, default_value_t = kaze::constants::AUDIT_SHOW_LIMIT)]
+        limit: usize,
+        /// Print the records as JSON lines
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// Subcommands for the `schedule` command.
+#[derive(Subcommand)]
+pub enum ScheduleAction {
+    /// Schedule `kaze run --template <TEMPLATE>` from the current directory
+    Add {
+        template: String,
+        /// Time of day to run, as HH:MM
+        #[arg(long, value_name = "HH:MM")]
+        at: String,
+        /// Only run on these days (e.g. mon,fri); every day when omitted
+        #[arg(long, value_delimiter = ',', value_name = "DAYS")]
+        days: Vec<String>,
+        /// Time zone of --at, such as Europe/Berlin (systemd only; default: the system's)
+        #[arg(long, value_name = "ZONE")]
+        tz: Option<String>,
+        /// Scheduler to register with
+        #[arg(long, value_enum, default_value_t = schedule::Backend::Auto)]
+        backend: schedule::Backend,
+        /// Print the generated units or crontab entry instead of installing them
+        #[arg(long)]
+        print: bool,
+    },
+    /// List scheduled runs
+    List,
+    /// Remove a template's schedule
+    Remove { template: String },
 }
 
 /// Subcommands for the `session` command.
@@ -88,9 +369,161 @@ pub enum SessionAction {
     /// List all sessions
     List,
     /// Resume a session by ID (supports partial IDs)
-    Resume { id: String },
+    Resume {
+        #[arg(required_unless_present = "last")]
+        id: Option<String>,
+        /// Resume the most recently updated session of this project
+        #[arg(long, conflicts_with = "id")]
+        last: bool,
+    },
     /// Delete a session by ID (supports partial IDs)
     Delete { id: String },
+    /// Give a session a title, replacing the one derived from its first message
+    Rename {
+        id: String,
+        /// The new title
+        #[arg(required_unless_present = "clear")]
+        title: Option<String>,
+        /// Go back to the derived title
+        #[arg(long, conflicts_with = "title")]
+        clear: bool,
+    },
+    /// Rebuild session index metadata from the session files
+    Reindex,
+    /// Delete (or archive) old sessions and compact the index
+    ///
+    /// Limits default to `[sessions] max_age_days` and `max_count`.
+    Prune {
+        /// Remove sessions not updated for this many days
+        #[arg(long, value_name = "DAYS")]
+        max_age_days: Option<u64>,
+        /// Keep only this many of the most recently updated sessions
+        #[arg(long, value_name = "N")]
+        max_count: Option<usize>,
+        /// Move pruned sessions to sessions/archive/ instead of deleting them
+        #[arg(long)]
+        archive: bool,
+        /// Show what would be removed without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Export a session transcript as JSON, Markdown, or HTML
+    Export {
+        id: String,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = session::ExportFormat::Json)]
+        format: session::ExportFormat,
+        /// Include tool calls and results in Markdown and HTML output
+        #[arg(long)]
+        include_tools: bool,
+        /// Attach `/rate` annotations to the messages they rate (JSON only)
+        #[arg(long)]
+        annotations: bool,
+    },
+}
+
+/// Records approval of the project config's permission escalations and
+/// endpoint overrides.
+fn config_trust() -> Result<()> {
+    match config::Config::trust_project_config()? {
+        None => println!("{}", "No kaze.toml found for this project.".dimmed()),
+        Some((path, found)) if found.is_empty() => println!(
+            "{} allows nothing beyond the global config; nothing to approve.",
+            path.display()
+        ),
+        Some((path, found)) => {
+            println!("{} {}:", "Approved for".green(), path.display());
+            for line in &found {
+                println!("  {}", line);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Shows what a bundle changes in the global config, and what it loosens,
+/// and writes it once the user agrees (or `yes` is set).
+fn config_import(path: &std::path::Path, yes: bool) -> Result<()> {
+    let import = config::Config::plan_import(path)?;
+    if import.diff.is_empty() {
+        println!(
+            "{}",
+            format!("{} changes nothing in the global config.", path.display()).dimmed()
+        );
+        return Ok(());
+    }
+    println!("{}", import.diff.trim_end());
+    if !import.review.is_empty() {
+        println!(
+            "\n{}",
+            "The bundle loosens permissions or redirects provider requests; \
+             the global config is trusted as is, so these apply everywhere:"
+                .yellow()
+        );
+        for line in &import.review {
+            println!("  {}", line);
+        }
+    }
+    if !yes {
+        if !std::io::stdin().is_terminal() {
+            anyhow::bail!("Not imported: pass --yes to import without a terminal to confirm on");
+        }
+        print!(
+            "\nWrite these changes to {}? [y/N] ",
+            import.config_path.display()
+        );
+        std::io::stdout().flush()?;
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+            println!("{}", "Not imported.".dimmed());
+            return Ok(());
+        }
+    }
+    import.write()?;
+    println!(
+        "{} {} into {}",
+        "Imported".green(),
+        path.display(),
+        import.config_path.display()
+    );
+    Ok(())
+}
+
+/// Writes or removes the project's model pin.
+///
+/// Without flags, pins whatever kaze currently resolves to (ignoring any
+/// existing pin, so re-pinning picks up config changes).
+fn config_pin(model: Option<&str>, provider: Option<&str>, clear: bool) -> Result<()> {
+    if clear {
+        match config::Config::clear_pin()? {
+            Some(path) => println!("{} {}", "Removed pin from".green(), path.display()),
+            None => println!("{}", "No pinned model.".dimmed()),
+        }
+        return Ok(());
+    }
+    let mut config = config::Config::load()?;
+    config.pin = config::PinConfig::default();
+    let selection = provider::resolve_model(provider, model, &config)?;
+    let provider_name = selection.provider.as_str();
+    let path = config::Config::write_pin(provider_name, &selection.model)?;
+    println!(
+        "{} {}/{} in {}",
+        "Pinned".green(),
+        provider_name,
+        selection.model,
+        path.display()
+    );
+    let known = kaze::models::known_models(provider_name);
+    if known.is_some_and(|models| !models.iter().any(|m| m.name == selection.model)) {
+        eprintln!(
+            "{} {} is not in kaze's model list for {}",
+            "warning:".yellow().bold(),
+            selection.model,
+            provider_name
+        );
+    }
+    Ok(())
 }
 
 /// Parses command-line arguments into a [`Cli`] struct.
@@ -105,95 +538,164 @@ pub fn parse() -> Cli {
 /// Routes each [`Commands`] variant to the appropriate implementation.
 /// All handlers are currently stubs that print `TODO` messages.
 pub async fn run(cli: Cli) -> Result<()> {
+    let _profile = cli
+        .profile
+        .as_deref()
+        .map(kaze::profile::start)
+        .transpose()?;
+    // Every command that registers file tools uses the current directory
+    // as the project root
+    let uses_tools = matches!(
+        cli.command,
+        Commands::Ask { .. }
+            | Commands::Run { .. }
+            | Commands::Chat { .. }
+            | Commands::Session {
+                action: SessionAction::New | SessionAction::Resume { .. }
+            }
+    );
+    if cli.read_only {
+        kaze::permissions::force_read_only();
+    }
+    if cli.yes {
+        kaze::permissions::start_auto_approved();
+        if matches!(cli.command, Commands::Ask { .. } | Commands::Run { .. }) {
+            eprintln!("{}", kaze::i18n::t("auto.banner").red().bold());
+        }
+    }
+    if uses_tools {
+        kaze::tools::check_project_root(&std::env::current_dir()?, cli.allow_broad_root)?;
+    }
     match cli.command {
         Commands::Ask {
             prompt,
             model,
-            provider: provider_name,
+            provider,
+            no_cache,
+            json,
+            no_stdin,
+            tee,
+            image,
+            schema,
+            turn_timeout,
         } => {
-            let prompt = prompt.join(" ");
-            if prompt.is_empty() {
-                anyhow::bail!("No prompt provided. Usage: kaze ask \"your question here\"");
+            ask::handle_ask(ask::AskOptions {
+                prompt: prompt.join(" "),
+                model,
+                provider,
+                no_cache,
+                json,
+                no_stdin,
+                tee,
+                images: image,
+                schema,
+                turn_timeout,
+                verbose: cli.verbose,
+            })
+            .await
+        }
+        Commands::Run {
+            task,
+            parallel,
+            jobs,
+            keep,
+            template,
+            model,
+            provider,
+            max_turns,
+            auto_approve,
+            output,
+            turn_timeout,
+            seed,
+            temperature,
+        } => {
+            let opts = run::RunOptions {
+                task: task.join(" "),
+                template,
+                model,
+                provider,
+                max_turns,
+                auto_approve,
+                output,
+                turn_timeout,
+                seed,
+                temperature,
+            };
+            match parallel {
+                Some(file) => parallel::handle_parallel(&file, jobs, keep, opts).await,
+                None => run::handle_run(opts).await,
             }
-
-            let config = config::Config::load()?;
-
-            let selection =
-                provider::resolve_model(provider_name.as_deref(), model.as_deref(), &config)?;
-
-            println!(
-                "{} [model: {}]",
-                "kaze".bold().cyan(),
-                selection.model.yellow(),
-            );
-            println!();
-            println!("{} {}", ">".green().bold(), prompt);
-            println!();
-
-            let provider = provider::Provider::from_config(&config, &selection)?;
-            let project_root = std::env::current_dir()?;
-            let tools = ToolRegistry::with_builtins(project_root.clone());
-
-            let mut messages = Vec::new();
-            if let Some(ref sp) = config.system_prompt {
-                messages.push(Message::system(sp.clone()));
+        }
+        Commands::Suggest {
+            request,
+            model,
+            provider,
+            shell,
+            init,
+        } => {
+            if let Some(shell) = init {
+                suggest::print_widget(shell);
+                return Ok(());
             }
-            messages.push(Message::user(&prompt));
-
-            let permission_manager = Arc::new(crate::permissions::PermissionManager::new(
-                config.permissions.clone(),
-            ));
-            let hook = crate::hooks::KazeHook::new(permission_manager, project_root);
-
-            let mut renderer = output::StdoutRenderer::new();
-            let response = agent::agent_loop(
-                &provider,
-                &mut messages,
-                &tools,
-                &mut renderer,
-                crate::constants::MAX_AGENT_ITERATIONS,
-                hook,
-            )
-            .await?;
-            // Show token usage
-            let token_count = crate::tokens::count_tokens(&response, &selection.model)?;
-            let limit = 128_000;
-            println!();
-            println!(
-                "{}",
-                format!(
-                    "Tokens: {}",
-                    crate::tokens::format_token_usage(token_count, limit)
-                )
-                .dimmed()
-            );
-
-            Ok(())
+            suggest::handle_suggest(suggest::SuggestOptions {
+                request: request.join(" "),
+                model,
+                provider,
+                shell,
+            })
+            .await
         }
         Commands::Chat {
             session,
+            continue_last,
             provider: provider_name,
             model,
             tui,
+            tee,
+            attach_cmd,
+            turn_timeout,
+            seed,
+            temperature,
         } => {
-            if tui {
-                crate::tui::run_tui().await
+            let mut config = config::Config::load()?;
+            let selection =
+                provider::resolve_model(provider_name.as_deref(), model.as_deref(), &config)?;
+            config.model = selection.model.clone();
+            if turn_timeout.is_some() {
+                config.turn_timeout_secs = turn_timeout;
+            }
+            config.override_generation(seed, temperature);
+            let options = chat::ChatOptions {
+                verbose: cli.verbose,
+                tee,
+                attach_cmd,
+            };
+            let session = if continue_last {
+                Some(session::latest_session_id()?)
+            } else {
+                session
+            };
+            if tui && config.accessible() {
+                eprintln!("Accessible output is on; using the line-based chat instead of the TUI.");
+                chat::run_chat(config, session, &selection, options).await
+            } else if tui {
+                kaze::tui::run_tui(config, session, &selection, options).await
             } else {
-                let mut config = config::Config::load()?;
-                let selection =
-                    provider::resolve_model(provider_name.as_deref(), model.as_deref(), &config)?;
-                config.model = selection.model.clone();
-                chat::run_chat(config, session, &selection).await
+                chat::run_chat(config, session, &selection, options).await
             }
         }
-        Commands::Models => {
+        Commands::Models { probe } => {
             let config = config::Config::load()?;
-            crate::provider::list_models(&config).await
+            kaze::provider::list_models(&config).await?;
+            if probe {
+                kaze::provider::probe_models(&config).await?;
+            }
+            Ok(())
         }
         Commands::Config { action } => {
-            let config = config::Config::load()?;
             match action {
                 ConfigAction::Show => {
+                    let config = config::Config::load()?;
                     let path = config::Config::config_path()?;
                     println!("{} {}", "Config path:".bold(), path.display());
                     println!();
@@ -203,9 +705,50 @@ pub async fn run(cli: Cli) -> Result<()> {
                 ConfigAction::Set { key, value } => {
                     println!("TODO: set {} = {}", key, value);
                 }
+                ConfigAction::Export { path, no_secrets } => {
+                    let config = config::Config::load_unresolved()?;
+                    let bundle = config.export_bundle(no_secrets)?;
+                    std::fs::write(&path, bundle)?;
+                    println!("{} {}", "Exported config to".green(), path.display());
+                }
+                ConfigAction::Import { path, yes } => config_import(&path, yes)?,
+                ConfigAction::Pin {
+                    model,
+                    provider,
+                    clear,
+                } => config_pin(model.as_deref(), provider.as_deref(), clear)?,
+                ConfigAction::Trust => config_trust()?,
+            }
+            Ok(())
+        }
+        Commands::Session { action } => {
+            let options = chat::ChatOptions {
+                verbose: cli.verbose,
+                tee: None,
+                attach_cmd: None,
+            };
+            session::handle_session(action, options).await
+        }
+        Commands::Serve { watch, port, host } => serve::handle_serve(&watch, &host, port).await,
+        Commands::Audit { action } => audit::handle_audit(action),
+        Commands::Stats { top, weeks } => stats::handle_stats(top, weeks),
+        Commands::Schedule { action } => schedule::handle_schedule(action),
+        Commands::Report { output, no_session } => {
+            let report = kaze::report::write_report(output, !no_session)?;
+            println!("{} {}", "Wrote".green(), report.path.display());
+            for file in &report.files {
+                println!("  {}", file);
+            }
+            if report.files.iter().any(|f| f == "session.jsonl") {
+                println!(
+                    "{}",
+                    "session.jsonl holds your last messages; review it before attaching, \
+                     or rerun with --no-session."
+                        .dimmed()
+                );
             }
             Ok(())
         }
-        Commands::Session { action } => session::handle_session(action).await,
+        Commands::Selftest { keep } => selftest::handle_selftest(keep).await,
     }
 }
@@ -0,0 +1,437 @@
+//! `kaze run --parallel`: independent tasks from a file, each run headless
+//! in its own git worktree and session, a few at a time.
+//!
+//! The file is TOML with one `[[task]]` table per task:
+//!
+//! ```toml
+//! concurrency = 3
+//!
+//! [[task]]
+//! name = "rename-config"
+//! task = "Rename Config::load_unresolved to Config::load_raw"
+//!
+//! [[task]]
+//! name = "docs"
+//! template = "docs"
+//! model = "claude-haiku-4-5"
+//! ```
+//!
+//! Besides `name`, a task takes the same settings as a `[templates]` entry
+//! plus `template` itself; `kaze run` options fill in whatever a task
+//! leaves unset. Each task gets a worktree on a new `kaze/<stamp>/<name>`
+//! branch off `HEAD`, so the changes can be reviewed and merged one by one,
+//! and the run ends with a table of the results. The worktree and branch of
+//! a task that failed or changed nothing are removed again unless `--keep`
+//! is given. Git runs through `tokio::process`, so setting up one task
+//! never holds up the others' streams.
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tokio::process::Command;
+
+use kaze::constants::{EXIT_FAILURE, MAX_AGENT_ITERATIONS, PARALLEL_JOBS_DEFAULT};
+use kaze::output::RecordingRenderer;
+use kaze::session::Session;
+use kaze::usage::format_cost;
+use kaze::{config, provider};
+
+use super::run::{self, Job, RunOptions, RunOutput, RunStatus};
+
+/// A `--parallel` task file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TaskFile {
+    /// How many tasks run at once; `--jobs` overrides it.
+    concurrency: Option<usize>,
+    #[serde(rename = "task", default)]
+    tasks: Vec<TaskEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TaskEntry {
+    /// Names the worktree, branch, and session.
+    name: String,
+    #[serde(default)]
+    task: String,
+    template: Option<String>,
+    model: Option<String>,
+    provider: Option<String>,
+    max_turns: Option<usize>,
+    auto_approve: Option<bool>,
+}
+
+/// How one task went, as printed in the results table.
+#[derive(Serialize)]
+struct TaskResult {
+    name: String,
+    status: RunStatus,
+    response: Option<String>,
+    error: Option<String>,
+    tool_calls: usize,
+    /// Files changed in the worktree, per `git status`.
+    files_changed: usize,
+    duration_ms: u64,
+    cost_usd: Option<f64>,
+    branch: Option<String>,
+    worktree: Option<PathBuf>,
+    session: Option<String>,
+}
+
+impl TaskResult {
+    fn failed(name: &str, error: String) -> Self {
+        Self {
+            name: name.to_string(),
+            status: RunStatus::Failed,
+            response: None,
+            error: Some(error),
+            tool_calls: 0,
+            files_changed: 0,
+            duration_ms: 0,
+            cost_usd: None,
+            branch: None,
+            worktree: None,
+            session: None,
+        }
+    }
+}
+
+/// Runs every task in `file` and exits non-zero if any of them did not
+/// succeed. `defaults` holds the `kaze run` options given alongside.
+pub(crate) async fn handle_parallel(
+    file: &Path,
+    jobs: Option<usize>,
+    keep: bool,
+    defaults: RunOptions,
+) -> Result<()> {
+    let mut config = config::Config::load()?;
+    config.override_generation(defaults.seed, defaults.temperature);
+    let text = std::fs::read_to_string(file)
+        .with_context(|| format!("Failed to read {}", file.display()))?;
+    let tasks: TaskFile =
+        toml::from_str(&text).with_context(|| format!("Failed to parse {}", file.display()))?;
+    validate(&tasks.tasks)?;
+    let concurrency = jobs
+        .or(tasks.concurrency)
+        .unwrap_or(PARALLEL_JOBS_DEFAULT)
+        .max(1);
+
+    let repo = git(Path::new("."), &["rev-parse", "--show-toplevel"])
+        .await
+        .context("--parallel needs a git repository to create worktrees in")?;
+    let repo = PathBuf::from(repo.trim());
+    // Every task starts from the same commit, and shows whether it added any
+    let base = git(&repo, &["rev-parse", "HEAD"]).await?;
+    let base = base.trim().to_string();
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S").to_string();
+    let repo_name = repo
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "repo".to_string());
+    let worktrees = config::Config::data_dir()?
+        .join("worktrees")
+        .join(format!("{}-{}", repo_name, stamp));
+
+    let json = defaults.output == RunOutput::Json;
+    if !json {
+        eprintln!(
+            "{} {} tasks, {} at a time, worktrees in {}",
+            "kaze run --parallel".bold().cyan(),
+            tasks.tasks.len(),
+            concurrency.min(tasks.tasks.len()),
+            worktrees.display()
+        );
+    }
+
+    let config = &config;
+    let defaults = &defaults;
+    let place = Place {
+        repo: &repo,
+        base: &base,
+        worktrees: &worktrees,
+        stamp: &stamp,
+        keep,
+    };
+    let place = &place;
+    let mut results: Vec<(usize, TaskResult)> =
+        futures::stream::iter(tasks.tasks.into_iter().enumerate())
+            .map(|(index, entry)| async move {
+                let result = run_task(config, defaults, &entry, place)
+                    .await
+                    .unwrap_or_else(|e| TaskResult::failed(&entry.name, format!("{:#}", e)));
+                if !json {
+                    eprintln!(
+                        "{} {} ({})",
+                        status_marker(result.status),
+                        result.name.bold(),
+                        run::status_label(result.status)
+                    );
+                }
+                (index, result)
+            })
+            .buffer_unordered(concurrency)
+            .collect()
+            .await;
+    results.sort_by_key(|(index, _)| *index);
+    let results: Vec<TaskResult> = results.into_iter().map(|(_, result)| result).collect();
+    // Gone once every task's worktree was removed
+    let _ = std::fs::remove_dir(&worktrees);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&results)?);
+    } else {
+        print_table(&results);
+    }
+    if results.iter().any(|r| r.status != RunStatus::Success) {
+        std::process::exit(EXIT_FAILURE);
+    }
+    Ok(())
+}
+
+/// Checks that there are tasks and that their names are unique and usable
+/// in branch and directory names.
+fn validate(tasks: &[TaskEntry]) -> Result<()> {
+    anyhow::ensure!(!tasks.is_empty(), "The task file has no [[task]] entries");
+    let mut seen = std::collections::HashSet::new();
+    for entry in tasks {
+        let name = entry.name.as_str();
+        anyhow::ensure!(
+            !name.is_empty()
+                && !name.starts_with(['.', '-'])
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c)),
+            "Task name {:?} must be letters, digits, '-', '_' or '.'",
+            name
+        );
+        anyhow::ensure!(seen.insert(name), "Task name {:?} is used twice", name);
+    }
+    Ok(())
+}
+
+/// Where the tasks of one `--parallel` run are set up.
+struct Place<'a> {
+    repo: &'a Path,
+    /// The commit every task's branch starts from.
+    base: &'a str,
+    worktrees: &'a Path,
+    stamp: &'a str,
+    /// Keep the worktrees of tasks that failed or changed nothing.
+    keep: bool,
+}
+
+/// Creates the task's worktree and session and runs it there, then removes
+/// the worktree and branch again if the task failed or left them as they
+/// started.
+async fn run_task(
+    config: &config::Config,
+    defaults: &RunOptions,
+    entry: &TaskEntry,
+    place: &Place<'_>,
+) -> Result<TaskResult> {
+    let started = Instant::now();
+    let mut opts = RunOptions {
+        task: entry.task.clone(),
+        template: entry.template.clone(),
+        model: entry.model.clone().or_else(|| defaults.model.clone()),
+        provider: entry.provider.clone().or_else(|| defaults.provider.clone()),
+        max_turns: entry.max_turns.or(defaults.max_turns),
+        auto_approve: entry.auto_approve.unwrap_or(defaults.auto_approve),
+        output: defaults.output,
+        turn_timeout: defaults.turn_timeout,
+        seed: defaults.seed,
+        temperature: defaults.temperature,
+    };
+    if let Some(name) = opts.template.clone() {
+        run::apply_template(&mut opts, config, &name)?;
+    }
+    anyhow::ensure!(!opts.task.trim().is_empty(), "No task given");
+    let max_turns = opts.max_turns.unwrap_or(MAX_AGENT_ITERATIONS).max(1);
+    let selection =
+        provider::resolve_model(opts.provider.as_deref(), opts.model.as_deref(), config)?;
+
+    let worktree = place.worktrees.join(&entry.name);
+    let branch = format!("kaze/{}/{}", place.stamp, entry.name);
+    tokio::fs::create_dir_all(place.worktrees)
+        .await
+        .with_context(|| format!("Failed to create {}", place.worktrees.display()))?;
+    git(
+        place.repo,
+        &[
+            "worktree",
+            "add",
+            "--quiet",
+            "-b",
+            &branch,
+            &worktree.to_string_lossy(),
+            place.base,
+        ],
+    )
+    .await?;
+
+    let result = run_in_worktree(config, &selection, &opts, max_turns, entry, &worktree).await;
+    let (files_changed, commits) = changes(&worktree, place.base).await;
+    let kept = match &result {
+        Ok((outcome, _)) => {
+            place.keep || outcome.status == RunStatus::Success && (files_changed > 0 || commits > 0)
+        }
+        Err(_) => place.keep,
+    };
+    if !kept {
+        remove_worktree(place.repo, &worktree, &branch).await;
+    }
+    let (outcome, session) = result?;
+
+    Ok(TaskResult {
+        name: entry.name.clone(),
+        status: outcome.status,
+        response: outcome.response,
+        error: outcome.error,
+        tool_calls: outcome.metrics.tool_calls,
+        files_changed,
+        duration_ms: started.elapsed().as_millis() as u64,
+        cost_usd: outcome.cost_usd,
+        branch: kept.then_some(branch),
+        worktree: kept.then_some(worktree),
+        session: Some(session),
+    })
+}
+
+/// Runs the task headless in `worktree`, saving it as a session; returns
+/// the outcome and the session's ID.
+async fn run_in_worktree(
+    config: &config::Config,
+    selection: &provider::ModelSelection,
+    opts: &RunOptions,
+    max_turns: usize,
+    entry: &TaskEntry,
+    worktree: &Path,
+) -> Result<(run::Outcome, String)> {
+    let mut session = Session::new(&selection.model)?;
+    let mut recorder = RecordingRenderer::new();
+    let job = Job {
+        task: &opts.task,
+        max_turns,
+        auto_approve: opts.auto_approve,
+        turn_timeout: opts.turn_timeout,
+        project_root: worktree.to_path_buf(),
+        session_id: Some(&session.id),
+    };
+    let mut outcome = run::execute(config, selection, job, &mut recorder).await?;
+    session.save_params(&outcome.params)?;
+    for message in std::mem::take(&mut outcome.messages) {
+        session.append(message)?;
+    }
+    session.rename(Some(&format!("parallel: {}", entry.name)))?;
+    if let Some(cost) = outcome.cost_usd {
+        session.add_cost(cost)?;
+    }
+    Ok((outcome, session.id))
+}
+
+/// Files changed in `worktree` per `git status`, and commits made on its
+/// branch since `base`.
+pub(super) async fn changes(worktree: &Path, base: &str) -> (usize, usize) {
+    let files = git(worktree, &["status", "--porcelain"])
+        .await
+        .map(|out| out.lines().count())
+        .unwrap_or(0);
+    let commits = git(
+        worktree,
+        &["rev-list", "--count", &format!("{}..HEAD", base)],
+    )
+    .await
+    .ok()
+    .and_then(|out| out.trim().parse().ok())
+    .unwrap_or(0);
+    (files, commits)
+}
+
+/// Removes a task's worktree and deletes its branch. Failures leave them
+/// for `git worktree prune` and `git branch -D`.
+pub(super) async fn remove_worktree(repo: &Path, worktree: &Path, branch: &str) {
+    let _ = git(
+        repo,
+        &["worktree", "remove", "--force", &worktree.to_string_lossy()],
+    )
+    .await;
+    let _ = git(repo, &["branch", "-D", branch]).await;
+}
+
+/// Runs `git` in `dir`, returning its stdout.
+pub(super) async fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let out = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .await
+        .context("Failed to run git")?;
+    anyhow::ensure!(
+        out.status.success(),
+        "git {} failed: {}",
+        args.first().unwrap_or(&""),
+        String::from_utf8_lossy(&out.stderr).trim()
+    );
+    Ok(String::from_utf8_lossy(&out.stdout).into_owned())
+}
+
+fn status_marker(status: RunStatus) -> colored::ColoredString {
+    match status {
+        RunStatus::Success => "✓".green(),
+        _ => "✗".red(),
+    }
+}
+
+/// Prints one row per task, then the errors of those that failed.
+fn print_table(results: &[TaskResult]) {
+    let width = results
+        .iter()
+        .map(|r| r.name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+    println!();
+    println!(
+        "{} {} {} {} {} {} {}",
+        format!("{:<width$}", "TASK").bold(),
+        format!("{:<18}", "STATUS").bold(),
+        format!("{:>5}", "TOOLS").bold(),
+        format!("{:>5}", "FILES").bold(),
+        format!("{:>7}", "TIME").bold(),
+        format!("{:>8}", "COST").bold(),
+        "BRANCH".bold()
+    );
+    for r in results {
+        let status = format!("{:<18}", run::status_label(r.status));
+        let status = match r.status {
+            RunStatus::Success => status.green(),
+            _ => status.red(),
+        };
+        println!(
+            "{:<width$} {} {:>5} {:>5} {:>6.1}s {:>8} {}",
+            r.name,
+            status,
+            r.tool_calls,
+            r.files_changed,
+            r.duration_ms as f64 / 1000.0,
+            r.cost_usd
+                .map(format_cost)
+                .unwrap_or_else(|| "-".to_string()),
+            r.branch.as_deref().unwrap_or("-")
+        );
+    }
+    for r in results.iter().filter(|r| r.error.is_some()) {
+        println!();
+        println!(
+            "{} {}: {}",
+            "error:".red().bold(),
+            r.name,
+            r.error.as_deref().unwrap_or_default()
+        );
+    }
+}
@@ -0,0 +1,377 @@
+//! Headless `kaze run` handling.
+//!
+//! Executes a task through the full tool-enabled agent loop with no REPL
+//! and nobody to answer permission prompts, for CI jobs and git hooks.
+//! Tools set to "ask" are refused unless `--auto-approve` is given; tools
+//! set to "deny" are always refused. The exit code reports the outcome:
+//! 0 on success, [`EXIT_FAILURE`] on error, [`EXIT_MAX_TURNS`] when the
+//! turn limit is hit, and [`EXIT_TIMEOUT`] when `--turn-timeout` (or
+//! `turn_timeout_secs`) runs out, in which case the text streamed so far is
+//! still printed or reported.
+
+use anyhow::Result;
+use clap::ValueEnum;
+use colored::Colorize;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Instant;
+
+use kaze::constants::{EXIT_FAILURE, EXIT_MAX_TURNS, EXIT_TIMEOUT, MAX_AGENT_ITERATIONS};
+use kaze::metrics::{MeteredRenderer, TurnMetrics};
+use kaze::output::{self, RecordedToolCall, RecordingRenderer, Renderer};
+use kaze::provider::RequestParams;
+use kaze::usage::{format_cost, TokenUsage, UsageRecord};
+use kaze::{agent, config, message::Message, provider, tools::ToolRegistry};
+
+/// Output format of `kaze run`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum RunOutput {
+    /// Stream the response to stdout, tool activity to stderr
+    Text,
+    /// Print a single JSON object when the run ends
+    Json,
+}
+
+/// Arguments of the `run` subcommand.
+pub(crate) struct RunOptions {
+    pub task: String,
+    /// `--template`: a `[templates]` entry whose settings fill in the
+    /// options left unset.
+    pub template: Option<String>,
+    pub model: Option<String>,
+    pub provider: Option<String>,
+    pub max_turns: Option<usize>,
+    pub auto_approve: bool,
+    pub output: RunOutput,
+    /// `--turn-timeout` in seconds; overrides `turn_timeout_secs`.
+    pub turn_timeout: Option<u64>,
+    /// `--seed` and `--temperature`; override `[generation]`.
+    pub seed: Option<u64>,
+    pub temperature: Option<f64>,
+}
+
+/// How a run ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum RunStatus {
+    Success,
+    Failed,
+    MaxTurns,
+    TimedOut,
+}
+
+impl RunStatus {
+    pub(crate) fn exit_code(self) -> i32 {
+        match self {
+            RunStatus::Success => 0,
+            RunStatus::Failed => EXIT_FAILURE,
+            RunStatus::MaxTurns => EXIT_MAX_TURNS,
+            RunStatus::TimedOut => EXIT_TIMEOUT,
+        }
+    }
+}
+
+/// The object printed by `kaze run --output json`.
+#[derive(Serialize)]
+struct RunReport<'a> {
+    status: RunStatus,
+    response: Option<&'a str>,
+    error: Option<&'a str>,
+    provider: &'a str,
+    model: &'a str,
+    params: &'a RequestParams,
+    tool_calls: &'a [RecordedToolCall],
+    usage: Option<TokenUsage>,
+    cost_usd: Option<f64>,
+    duration_ms: u64,
+}
+
+/// One agent run, with templates already applied.
+pub(crate) struct Job<'a> {
+    pub task: &'a str,
+    pub max_turns: usize,
+    pub auto_approve: bool,
+    /// `--turn-timeout` in seconds; overrides `turn_timeout_secs`.
+    pub turn_timeout: Option<u64>,
+    /// Where the tools work: the current directory, or a task's worktree.
+    pub project_root: PathBuf,
+    /// The session the usage is recorded against, if any.
+    pub session_id: Option<&'a str>,
+}
+
+/// What a run produced.
+pub(crate) struct Outcome {
+    pub status: RunStatus,
+    pub response: Option<String>,
+    pub error: Option<String>,
+    /// The conversation, from the system prompt to the final response.
+    pub messages: Vec<Message>,
+    pub metrics: TurnMetrics,
+    pub cost_usd: Option<f64>,
+    /// What the requests were sent with.
+    pub params: RequestParams,
+}
+
+/// Fills in `opts` from the `[templates]` entry `name`. Options given on
+/// the command line win; a task given on the command line is appended to
+/// the template's.
+pub(crate) fn apply_template(
+    opts: &mut RunOptions,
+    config: &config::Config,
+    name: &str,
+) -> Result<()> {
+    let template = find_template(config, name)?;
+    opts.task = if opts.task.trim().is_empty() {
+        template.task.clone()
+    } else {
+        format!("{}\n\n{}", template.task, opts.task)
+    };
+    opts.model = opts.model.take().or_else(|| template.model.clone());
+    opts.provider = opts.provider.take().or_else(|| template.provider.clone());
+    opts.max_turns = opts.max_turns.or(template.max_turns);
+    opts.auto_approve |= template.auto_approve.unwrap_or(false);
+    Ok(())
+}
+
+/// The `[templates]` entry `name`, or an error listing the defined ones.
+pub(crate) fn find_template<'a>(
+    config: &'a config::Config,
+    name: &str,
+) -> Result<&'a config::RunTemplate> {
+    config.templates.get(name).ok_or_else(|| {
+        let defined: Vec<&str> = config.templates.keys().map(String::as_str).collect();
+        let defined = if defined.is_empty() {
+            "none".to_string()
+        } else {
+            defined.join(", ")
+        };
+        anyhow::anyhow!(kaze::i18n::tf(
+            "run.unknown_template",
+            &[("name", name), ("defined", &defined)]
+        ))
+    })
+}
+
+/// Runs a task to completion and exits with a status code.
+///
+/// Setup errors (bad config, unknown provider) are returned as usual and
+/// exit with code 1. Once the agent loop has started, the process exits
+/// from here so the code can distinguish a turn-limit stop from a failure.
+pub(crate) async fn handle_run(mut opts: RunOptions) -> Result<()> {
+    let started = Instant::now();
+    let mut config = config::Config::load()?;
+    config.override_generation(opts.seed, opts.temperature);
+    if let Some(name) = opts.template.clone() {
+        apply_template(&mut opts, &config, &name)?;
+    }
+    if opts.task.trim().is_empty() {
+        anyhow::bail!(kaze::i18n::t("run.no_task"));
+    }
+    let max_turns = opts.max_turns.unwrap_or(MAX_AGENT_ITERATIONS);
+    if max_turns == 0 {
+        anyhow::bail!("--max-turns must be at least 1");
+    }
+
+    let selection =
+        provider::resolve_model(opts.provider.as_deref(), opts.model.as_deref(), &config)?;
+    let provider_name = selection.provider.as_str();
+
+    let json = opts.output == RunOutput::Json;
+    if !json {
+        eprintln!(
+            "{} [model: {}] {}",
+            "kaze run".bold().cyan(),
+            selection.model.yellow(),
+            opts.task.dimmed()
+        );
+    }
+
+    let mut stdout_renderer = output::StdoutRenderer::new();
+    let mut recorder = RecordingRenderer::new();
+    let renderer: &mut dyn Renderer = if json {
+        &mut recorder
+    } else {
+        &mut stdout_renderer
+    };
+    let job = Job {
+        task: &opts.task,
+        max_turns,
+        auto_approve: opts.auto_approve,
+        turn_timeout: opts.turn_timeout,
+        project_root: std::env::current_dir()?,
+        session_id: None,
+    };
+    let Outcome {
+        status,
+        response,
+        error,
+        metrics,
+        cost_usd,
+        params,
+        ..
+    } = execute(&config, &selection, job, renderer).await?;
+
+    if json {
+        let report = RunReport {
+            status,
+            response: response.as_deref(),
+            error: error.as_deref(),
+            provider: provider_name,
+            model: &selection.model,
+            params: &params,
+            tool_calls: &recorder.tool_calls,
+            usage: metrics.usage,
+            cost_usd,
+            duration_ms: started.elapsed().as_millis() as u64,
+        };
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        // Stream errors were already reported by the renderer.
+        if status == RunStatus::MaxTurns {
+            eprintln!(
+                "{} stopped after {} turns without finishing (raise --max-turns)",
+                "error:".red().bold(),
+                max_turns
+            );
+        }
+        if status == RunStatus::TimedOut {
+            if let Some(ref summary) = error {
+                eprintln!("{} {}", "error:".red().bold(), summary);
+            }
+        }
+        let mut summary = format!(
+            "({} · {} tool calls · {:.1}s",
+            status_label(status),
+            metrics.tool_calls,
+            started.elapsed().as_secs_f64()
+        );
+        if let Some(cost) = cost_usd {
+            summary.push_str(&format!(" · {}", format_cost(cost)));
+        }
+        summary.push(')');
+        eprintln!("{}", summary.dimmed());
+    }
+
+    match status.exit_code() {
+        0 => Ok(()),
+        code => std::process::exit(code),
+    }
+}
+
+/// Runs `job` through the agent loop, rendering to `renderer`, and records
+/// its usage.
+///
+/// Only setup errors are returned; failures of the run itself are in the
+/// [`Outcome`].
+pub(crate) async fn execute(
+    config: &config::Config,
+    selection: &provider::ModelSelection,
+    job: Job<'_>,
+    renderer: &mut dyn Renderer,
+) -> Result<Outcome> {
+    let provider = provider::Provider::from_config(config, selection)?;
+
+    let mut messages = Vec::new();
+    if let Some(sp) = config.system_preamble() {
+        messages.push(Message::system(sp));
+    }
+    messages.push(Message::user(job.task));
+
+    let permission_manager = Arc::new(
+        kaze::permissions::PermissionManager::new(config.permissions.clone())
+            .with_write_ceiling(config.write_file_confirm_bytes())
+            .unattended(job.auto_approve),
+    );
+    let hook = kaze::hooks::KazeHook::new(permission_manager, job.project_root.clone());
+    let tools = ToolRegistry::with_settings(job.project_root.clone(), &config.tools).with_task(
+        config,
+        selection,
+        job.project_root,
+        hook.clone(),
+    )?;
+    tools.audit().start(job.session_id);
+    let params = provider.request_params(&tools);
+
+    let limit = config.turn_timeout(job.turn_timeout);
+    let mut metered = MeteredRenderer::new(renderer);
+    let result = tokio::select! {
+        result = agent::agent_loop(
+            &provider,
+            &mut messages,
+            &tools,
+            &mut metered,
+            job.max_turns,
+            hook,
+        ) => Some(result),
+        _ = agent::deadline(limit) => None,
+    };
+    let partial = metered.text().to_string();
+    if result.is_none() {
+        metered.render_done();
+    }
+    let metrics = metered.finish();
+
+    let (status, response, error) = match result {
+        Some(Ok(response)) => (RunStatus::Success, Some(response), None),
+        None => {
+            let summary = agent::timeout_summary(limit.unwrap_or_default(), &metrics, &partial);
+            let partial = (!partial.trim().is_empty()).then_some(partial);
+            (RunStatus::TimedOut, partial, Some(summary))
+        }
+        Some(Err(e)) => {
+            let e = provider::explain_error(e, selection.provider, config).await;
+            let message = format!("{:#}", e);
+            let status = if is_max_turns_error(&message) {
+                RunStatus::MaxTurns
+            } else {
+                RunStatus::Failed
+            };
+            (status, None, Some(message))
+        }
+    };
+
+    let token_count = match response {
+        Some(ref r) => kaze::tokens::count_tokens(r, &selection.model)?,
+        None => 0,
+    };
+    let record = UsageRecord::new(
+        selection.provider.as_str(),
+        &selection.model,
+        job.session_id,
+        token_count,
+        &metrics,
+    );
+    if let Err(e) = record.append() {
+        eprintln!(
+            "{} Failed to record usage: {}",
+            "warning:".yellow().bold(),
+            e
+        );
+    }
+    Ok(Outcome {
+        status,
+        response,
+        error,
+        messages,
+        metrics,
+        cost_usd: record.cost_usd,
+        params,
+    })
+}
+
+/// Whether an agent loop error means the turn limit was reached.
+///
+/// rig reports this as `MaxTurnError: (reached max turn limit: N)`.
+fn is_max_turns_error(message: &str) -> bool {
+    message.contains("MaxTurnError") || message.contains("max turn limit")
+}
+
+pub(crate) fn status_label(status: RunStatus) -> &'static str {
+    match status {
+        RunStatus::Success => "done",
+        RunStatus::Failed => "failed",
+        RunStatus::MaxTurns => "turn limit reached",
+        RunStatus::TimedOut => "timed out",
+    }
+}
@@ -0,0 +1,468 @@
+//! `kaze schedule`: recurring headless runs of a `[templates]` entry.
+//!
+//! Schedules are handed to the system scheduler rather than kept by a
+//! daemon of our own: a systemd user timer (`kaze-<template>.timer` and
+//! `.service`) where systemd is available, otherwise a tagged crontab
+//! entry. Either one runs `kaze run --template <name>` from the directory
+//! the schedule was added in, so the project's `kaze.toml` applies.
+//! systemd timers honour `--tz`; cron has no portable per-entry time zone,
+//! so the cron backend only accepts local times.
+
+use anyhow::{Context, Result};
+use chrono::{Timelike, Weekday};
+use clap::ValueEnum;
+use colored::Colorize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use super::run::find_template;
+use super::ScheduleAction;
+use kaze::config::Config;
+use kaze::constants::{SCHEDULE_CRON_MARKER, SCHEDULE_LOG_DIR, SCHEDULE_UNIT_PREFIX};
+
+/// Which system scheduler holds the schedule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Backend {
+    /// systemd if `systemctl` is available, otherwise cron
+    Auto,
+    /// A systemd user timer
+    Systemd,
+    /// An entry in the user's crontab
+    Cron,
+}
+
+/// When a schedule fires: a time of day, optionally limited to weekdays.
+pub(super) struct Calendar {
+    hour: u32,
+    minute: u32,
+    /// Empty for every day.
+    days: Vec<Weekday>,
+    /// IANA zone such as `Europe/Berlin`; `None` for the system's.
+    tz: Option<String>,
+}
+
+impl Calendar {
+    pub(super) fn parse(at: &str, days: &[String], tz: Option<String>) -> Result<Self> {
+        let time = chrono::NaiveTime::parse_from_str(at, "%H:%M")
+            .with_context(|| format!("Invalid --at {:?}; expected HH:MM", at))?;
+        let days = days
+            .iter()
+            .map(|d| {
+                d.parse::<Weekday>()
+                    .map_err(|_| anyhow::anyhow!("Invalid day {:?}; expected mon..sun", d))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if let Some(ref zone) = tz {
+            let zoneinfo = Path::new("/usr/share/zoneinfo");
+            if zoneinfo.is_dir() && !zoneinfo.join(zone).is_file() {
+                anyhow::bail!(
+                    "Unknown time zone {:?} (not in {})",
+                    zone,
+                    zoneinfo.display()
+                );
+            }
+        }
+        Ok(Self {
+            hour: time.hour(),
+            minute: time.minute(),
+            days,
+            tz,
+        })
+    }
+
+    /// The systemd `OnCalendar=` value, e.g. `Mon,Fri *-*-* 03:00:00 Europe/Berlin`.
+    fn on_calendar(&self) -> String {
+        let mut spec = String::new();
+        if !self.days.is_empty() {
+            let days: Vec<String> = self.days.iter().map(|d| d.to_string()).collect();
+            spec.push_str(&days.join(","));
+            spec.push(' ');
+        }
+        spec.push_str(&format!("*-*-* {:02}:{:02}:00", self.hour, self.minute));
+        if let Some(ref tz) = self.tz {
+            spec.push(' ');
+            spec.push_str(tz);
+        }
+        spec
+    }
+
+    /// The five crontab time fields, e.g. `0 3 * * 1,5`.
+    fn cron(&self) -> String {
+        let days = if self.days.is_empty() {
+            "*".to_string()
+        } else {
+            let days: Vec<String> = self
+                .days
+                .iter()
+                .map(|d| d.num_days_from_sunday().to_string())
+                .collect();
+            days.join(",")
+        };
+        format!("{} {} * * {}", self.minute, self.hour, days)
+    }
+}
+
+/// Dispatches a `kaze schedule` subcommand.
+pub(crate) fn handle_schedule(action: ScheduleAction) -> Result<()> {
+    match action {
+        ScheduleAction::Add {
+            template,
+            at,
+            days,
+            tz,
+            backend,
+            print,
+        } => {
+            let calendar = Calendar::parse(&at, &days, tz)?;
+            add(&template, &calendar, backend, print)
+        }
+        ScheduleAction::List => list(),
+        ScheduleAction::Remove { template } => remove(&template),
+    }
+}
+
+fn add(name: &str, calendar: &Calendar, backend: Backend, print: bool) -> Result<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        anyhow::bail!(
+            "Template names used with schedule may only contain letters, digits, '-' and '_'"
+        );
+    }
+    find_template(&Config::load()?, name)?;
+    let exe = std::env::current_exe().context("Failed to locate the kaze binary")?;
+    let dir = std::env::current_dir()?;
+
+    let backend = match backend {
+        Backend::Auto if has_systemd() => Backend::Systemd,
+        Backend::Auto => Backend::Cron,
+        other => other,
+    };
+    if backend == Backend::Cron && calendar.tz.is_some() {
+        anyhow::bail!(
+            "cron has no portable per-entry time zone; use --backend systemd or give a local time without --tz"
+        );
+    }
+
+    match backend {
+        Backend::Systemd => {
+            let (service, timer) = systemd_units(name, calendar, &exe, &dir);
+            if print {
+                println!("{}\n{}", service, timer);
+                return Ok(());
+            }
+            install_systemd(name, &service, &timer)?;
+            println!(
+                "{} {} ({})",
+                "Scheduled".green(),
+                name,
+                calendar.on_calendar()
+            );
+            println!(
+                "{}",
+                format!(
+                    "Output goes to the journal: journalctl --user -u {}{}",
+                    SCHEDULE_UNIT_PREFIX, name
+                )
+                .dimmed()
+            );
+        }
+        _ => {
+            let log = Config::cache_dir()?
+                .join(SCHEDULE_LOG_DIR)
+                .join(format!("{}.log", name));
+            let entry = cron_entry(name, calendar, &exe, &dir, &log);
+            if print {
+                println!("{}", entry);
+                return Ok(());
+            }
+            if let Some(parent) = log.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let mut crontab = without_cron_entry(&read_crontab()?, name);
+            crontab.push_str(&entry);
+            crontab.push('\n');
+            write_crontab(&crontab)?;
+            println!("{} {} ({})", "Scheduled".green(), name, calendar.cron());
+            println!(
+                "{}",
+                format!("Output is appended to {}", log.display()).dimmed()
+            );
+        }
+    }
+    println!(
+        "{}",
+        format!(
+            "Runs from {}; API keys must come from the config or a .env there, \
+             since the scheduler doesn't see your shell's environment.",
+            dir.display()
+        )
+        .dimmed()
+    );
+    Ok(())
+}
+
+/// Prints every schedule kaze has registered, from both backends.
+fn list() -> Result<()> {
+    let mut found = 0;
+    if let Ok(entries) = std::fs::read_dir(systemd_unit_dir()?) {
+        let mut timers: Vec<PathBuf> = entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| {
+                let name = p.file_name().unwrap_or_default().to_string_lossy();
+                name.starts_with(SCHEDULE_UNIT_PREFIX) && name.ends_with(".timer")
+            })
+            .collect();
+        timers.sort();
+        for timer in timers {
+            let stem = timer.file_stem().unwrap_or_default().to_string_lossy();
+            let name = &stem[SCHEDULE_UNIT_PREFIX.len()..];
+            let calendar = std::fs::read_to_string(&timer)
+                .unwrap_or_default()
+                .lines()
+                .find_map(|l| l.strip_prefix("OnCalendar=").map(str::to_string))
+                .unwrap_or_default();
+            println!("{:<20} {:<8} {}", name.bold(), "systemd", calendar);
+            found += 1;
+        }
+    }
+    let crontab = read_crontab().unwrap_or_default();
+    let mut lines = crontab.lines();
+    while let Some(line) = lines.next() {
+        if let Some(name) = line.strip_prefix(SCHEDULE_CRON_MARKER) {
+            let fields: Vec<&str> = lines
+                .next()
+                .unwrap_or_default()
+                .split_whitespace()
+                .take(5)
+                .collect();
+            println!(
+                "{:<20} {:<8} {}",
+                name.trim().bold(),
+                "cron",
+                fields.join(" ")
+            );
+            found += 1;
+        }
+    }
+    if found == 0 {
+        println!("{}", "No scheduled runs.".dimmed());
+    }
+    Ok(())
+}
+
+/// Removes the schedule for `name` from whichever backend holds it.
+fn remove(name: &str) -> Result<()> {
+    let mut removed = false;
+    let unit_dir = systemd_unit_dir()?;
+    let unit = format!("{}{}", SCHEDULE_UNIT_PREFIX, name);
+    let timer = unit_dir.join(format!("{}.timer", unit));
+    if timer.exists() {
+        let _ = systemctl(&["disable", "--now", &format!("{}.timer", unit)]);
+        std::fs::remove_file(&timer)?;
+        let _ = std::fs::remove_file(unit_dir.join(format!("{}.service", unit)));
+        let _ = systemctl(&["daemon-reload"]);
+        removed = true;
+    }
+    if let Ok(crontab) = read_crontab() {
+        let kept = without_cron_entry(&crontab, name);
+        if kept != crontab {
+            write_crontab(&kept)?;
+            removed = true;
+        }
+    }
+    if removed {
+        println!("{} {}", "Removed schedule".green(), name);
+    } else {
+        println!("{}", format!("No schedule for {}.", name).dimmed());
+    }
+    Ok(())
+}
+
+/// The `.service` and `.timer` unit files for `name`.
+fn systemd_units(name: &str, calendar: &Calendar, exe: &Path, dir: &Path) -> (String, String) {
+    let service = format!(
+        "[Unit]\n\
+         Description=kaze run --template {name}\n\
+         \n\
+         [Service]\n\
+         Type=oneshot\n\
+         WorkingDirectory={dir}\n\
+         ExecStart={exe} run --template {name}\n",
+        name = name,
+        dir = systemd_escape(&dir.to_string_lossy()),
+        exe = systemd_quote(&exe.to_string_lossy()),
+    );
+    let timer = format!(
+        "[Unit]\n\
+         Description=Scheduled kaze run --template {name}\n\
+         \n\
+         [Timer]\n\
+         OnCalendar={calendar}\n\
+         Persistent=true\n\
+         \n\
+         [Install]\n\
+         WantedBy=timers.target\n",
+        name = name,
+        calendar = calendar.on_calendar(),
+    );
+    (service, timer)
+}
+
+/// Writes the unit files and enables the timer.
+///
+/// When `systemctl` can't be reached (no user session, e.g. over SSH
+/// without lingering), the files stay in place and the command to enable
+/// them later is printed instead.
+fn install_systemd(name: &str, service: &str, timer: &str) -> Result<()> {
+    let unit_dir = systemd_unit_dir()?;
+    std::fs::create_dir_all(&unit_dir)
+        .with_context(|| format!("Failed to create {}", unit_dir.display()))?;
+    let unit = format!("{}{}", SCHEDULE_UNIT_PREFIX, name);
+    std::fs::write(unit_dir.join(format!("{}.service", unit)), service)?;
+    std::fs::write(unit_dir.join(format!("{}.timer", unit)), timer)?;
+    let enable = ["enable", "--now", &format!("{}.timer", unit)];
+    if let Err(e) = systemctl(&["daemon-reload"]).and_then(|_| systemctl(&enable)) {
+        eprintln!(
+            "{} {:#}\nThe units are in {}; enable them with: systemctl --user {}",
+            "warning:".yellow().bold(),
+            e,
+            unit_dir.display(),
+            enable.join(" ")
+        );
+    }
+    Ok(())
+}
+
+fn systemd_unit_dir() -> Result<PathBuf> {
+    Ok(dirs::config_dir()
+        .ok_or_else(|| anyhow::anyhow!("Could not determine config directory"))?
+        .join("systemd")
+        .join("user"))
+}
+
+fn has_systemd() -> bool {
+    cfg!(target_os = "linux")
+        && Command::new("systemctl")
+            .arg("--version")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|s| s.success())
+}
+
+fn systemctl(args: &[&str]) -> Result<()> {
+    let out = Command::new("systemctl")
+        .arg("--user")
+        .args(args)
+        .output()
+        .context("Failed to run systemctl")?;
+    if !out.status.success() {
+        anyhow::bail!(
+            "systemctl --user {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&out.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// `%` starts a specifier in unit files.
+fn systemd_escape(s: &str) -> String {
+    s.replace('%', "%%")
+}
+
+/// One `ExecStart=` word, quoted when it holds spaces.
+fn systemd_quote(s: &str) -> String {
+    let s = systemd_escape(s);
+    if s.contains(char::is_whitespace) || s.contains('"') {
+        format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        s
+    }
+}
+
+/// The marker comment and crontab line for `name`.
+pub(super) fn cron_entry(
+    name: &str,
+    calendar: &Calendar,
+    exe: &Path,
+    dir: &Path,
+    log: &Path,
+) -> String {
+    format!(
+        "{} {}\n{} cd {} && {} run --template {} >> {} 2>&1",
+        SCHEDULE_CRON_MARKER,
+        name,
+        calendar.cron(),
+        cron_quote(&dir.to_string_lossy()),
+        cron_quote(&exe.to_string_lossy()),
+        name,
+        cron_quote(&log.to_string_lossy()),
+    )
+}
+
+/// Single-quotes `s` for the shell cron runs, escaping cron's `%`.
+fn cron_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''")).replace('%', r"\%")
+}
+
+/// `crontab` with the entry for `name` (its marker and the line after) removed.
+pub(super) fn without_cron_entry(crontab: &str, name: &str) -> String {
+    let mut kept = String::new();
+    let mut lines = crontab.lines();
+    while let Some(line) = lines.next() {
+        if line
+            .strip_prefix(SCHEDULE_CRON_MARKER)
+            .is_some_and(|n| n.trim() == name)
+        {
+            lines.next();
+            continue;
+        }
+        kept.push_str(line);
+        kept.push('\n');
+    }
+    kept
+}
+
+/// The user's crontab; empty when they have none yet.
+fn read_crontab() -> Result<String> {
+    let out = Command::new("crontab")
+        .arg("-l")
+        .output()
+        .context("Failed to run crontab")?;
+    crontab_listing(out.status.success(), &out.stdout, &out.stderr)
+}
+
+/// The crontab `crontab -l` printed. It fails with "no crontab for <user>"
+/// before the first entry, which reads as empty; any other failure is an
+/// error, so a crontab that couldn't be read is never overwritten.
+pub(super) fn crontab_listing(success: bool, stdout: &[u8], stderr: &[u8]) -> Result<String> {
+    if success {
+        return Ok(String::from_utf8_lossy(stdout).into_owned());
+    }
+    let stderr = String::from_utf8_lossy(stderr);
+    if stderr.contains("no crontab for") {
+        return Ok(String::new());
+    }
+    anyhow::bail!("crontab -l failed: {}", stderr.trim())
+}
+
+fn write_crontab(contents: &str) -> Result<()> {
+    let mut child = Command::new("crontab")
+        .arg("-")
+        .stdin(Stdio::piped())
+        .spawn()
+        .context("Failed to run crontab")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(contents.as_bytes())?;
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        anyhow::bail!("crontab exited with {}", status);
+    }
+    Ok(())
+}
@@ -0,0 +1,218 @@
+//! `kaze selftest`: the agent loop, tools, permissions, and session
+//! persistence exercised end to end against the scripted mock provider,
+//! offline and in a scratch directory.
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use futures::future::LocalBoxFuture;
+use serde_json::json;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::mpsc;
+
+use kaze::config::{Config, SessionStorage};
+use kaze::constants::{EXIT_FAILURE, MAX_AGENT_ITERATIONS};
+use kaze::hooks::KazeHook;
+use kaze::message::{Message, Role};
+use kaze::output::RecordingRenderer;
+use kaze::permissions::{Permission, PermissionConfig, PermissionManager, PromptResponse};
+use kaze::provider::{MockReply, MockScript, Provider};
+use kaze::session::{self, Session};
+use kaze::tools::ToolRegistry;
+
+type Check = for<'a> fn(&'a Path) -> LocalBoxFuture<'a, Result<()>>;
+
+const CHECKS: [(&str, Check); 4] = [
+    ("agent loop", |dir| Box::pin(agent_loop(dir))),
+    ("tools", |dir| Box::pin(tools(dir))),
+    ("permissions", |dir| Box::pin(permissions(dir))),
+    ("sessions", |dir| Box::pin(sessions(dir))),
+];
+
+/// Runs every check, printing one line each, and exits non-zero if any
+/// failed. With `keep`, the scratch directory is left for inspection.
+pub(crate) async fn handle_selftest(keep: bool) -> Result<()> {
+    let root = std::env::temp_dir().join(format!("kaze-selftest-{}", std::process::id()));
+    println!("{} in {}", "kaze selftest".bold().cyan(), root.display());
+
+    let mut failed = 0;
+    for (name, check) in CHECKS {
+        let dir = root.join(name.replace(' ', "-"));
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+        let started = Instant::now();
+        let result = check(&dir).await;
+        let elapsed = format!("({}ms)", started.elapsed().as_millis()).dimmed();
+        match result {
+            Ok(()) => println!("  {} {} {}", "✓".green(), name, elapsed),
+            Err(e) => {
+                failed += 1;
+                println!("  {} {} {}: {:#}", "✗".red(), name, elapsed, e);
+            }
+        }
+    }
+
+    if !keep {
+        let _ = std::fs::remove_dir_all(&root);
+    }
+    println!("\n{} passed, {} failed", CHECKS.len() - failed, failed);
+    if failed > 0 {
+        std::process::exit(EXIT_FAILURE);
+    }
+    Ok(())
+}
+
+/// Runs `script` through the agent loop in `dir` with the given tool
+/// permissions. Permission prompts go through a channel, as in chat, and
+/// are answered no.
+async fn run_agent(
+    dir: &Path,
+    script: &MockScript,
+    permissions: &[(&str, Permission)],
+) -> Result<String> {
+    let provider = Provider::mock(&Config::default(), script.clone());
+    let tools = ToolRegistry::with_builtins(dir.to_path_buf());
+    let config = PermissionConfig {
+        tools: permissions
+            .iter()
+            .map(|(tool, permission)| (tool.to_string(), permission.clone()))
+            .collect(),
+        ..PermissionConfig::default()
+    };
+    let (prompt_tx, mut prompts) = mpsc::unbounded_channel();
+    let hook = KazeHook::new(Arc::new(PermissionManager::new(config)), dir.to_path_buf())
+        .with_prompt_channel(prompt_tx);
+    let mut messages = vec![
+        Message::system("You are kaze's self-test."),
+        Message::user("ping"),
+    ];
+    let mut renderer = RecordingRenderer::new();
+    let agent = kaze::agent::agent_loop(
+        &provider,
+        &mut messages,
+        &tools,
+        &mut renderer,
+        MAX_AGENT_ITERATIONS,
+        hook,
+    );
+    let refuse = async {
+        while let Some(request) = prompts.recv().await {
+            if let Some(reply) = request.reply {
+                let _ = reply.send(PromptResponse::No);
+            }
+        }
+        std::future::pending().await
+    };
+    tokio::select! {
+        result = agent => result,
+        never = refuse => never,
+    }
+}
+
+/// The text of the last message of request `index`, as the mock saw it.
+fn sent(script: &MockScript, index: usize) -> Result<String> {
+    script
+        .requests()
+        .get(index)
+        .map(|message| message.to_string())
+        .with_context(|| format!("the model got {} requests", script.requests().len()))
+}
+
+/// A plain answer comes back, and the model saw the prompt.
+async fn agent_loop(dir: &Path) -> Result<()> {
+    let script = MockScript::new([MockReply::text("pong")]);
+    let answer = run_agent(dir, &script, &[]).await?;
+    anyhow::ensure!(answer == "pong", "answer was {:?}", answer);
+    anyhow::ensure!(
+        sent(&script, 0)?.contains("ping"),
+        "the prompt was not sent"
+    );
+    Ok(())
+}
+
+/// A written file lands on disk and reading it back shows the model its
+/// contents.
+async fn tools(dir: &Path) -> Result<()> {
+    let contents = "written by kaze selftest\n";
+    let script = MockScript::new([
+        MockReply::tool(
+            "write_file",
+            json!({ "path": "notes/hello.txt", "content": contents }),
+        ),
+        MockReply::tool("read_file", json!({ "path": "notes/hello.txt" })),
+        MockReply::text("done"),
+    ]);
+    let allowed = [
+        ("write_file", Permission::Allow),
+        ("read_file", Permission::Allow),
+    ];
+    let answer = run_agent(dir, &script, &allowed).await?;
+    anyhow::ensure!(answer == "done", "answer was {:?}", answer);
+    let written = std::fs::read_to_string(dir.join("notes/hello.txt"))
+        .context("write_file did not create the file")?;
+    anyhow::ensure!(written == contents, "the file holds {:?}", written);
+    anyhow::ensure!(
+        sent(&script, 2)?.contains("written by kaze selftest"),
+        "read_file's result did not reach the model"
+    );
+    Ok(())
+}
+
+/// Denied tools never run, and neither do ones the user declines.
+async fn permissions(dir: &Path) -> Result<()> {
+    for (permission, refusal) in [
+        (Permission::Deny, "disabled by user configuration"),
+        (Permission::Ask, "User rejected"),
+    ] {
+        let script = MockScript::new([
+            MockReply::tool("bash", json!({ "command": "touch ran.txt" })),
+            MockReply::text("ok"),
+        ]);
+        run_agent(dir, &script, &[("bash", permission.clone())]).await?;
+        anyhow::ensure!(
+            !dir.join("ran.txt").exists(),
+            "bash ran with permission {:?}",
+            permission
+        );
+        anyhow::ensure!(
+            sent(&script, 1)?.contains(refusal),
+            "the model was not told the call was refused ({:?})",
+            permission
+        );
+    }
+    Ok(())
+}
+
+/// Messages, titles, and rewinds survive a reload.
+async fn sessions(dir: &Path) -> Result<()> {
+    let store = session::open_store(SessionStorage::Jsonl, dir.join("sessions"))?;
+    let mut session = Session::new_in(store.clone(), "echo");
+    session.append(Message::system("system"))?;
+    for turn in ["first", "second"] {
+        session.append(Message::user(turn))?;
+        session.append(Message::assistant(format!("re: {}", turn)))?;
+        session.checkpoint()?;
+    }
+    session.rename(Some("selftest"))?;
+
+    let loaded = Session::load_from(store.clone(), &session.id)?;
+    anyhow::ensure!(
+        loaded.messages.len() == 5,
+        "reloaded {} of 5 messages",
+        loaded.messages.len()
+    );
+    anyhow::ensure!(
+        loaded.title().as_deref() == Some("selftest"),
+        "the title was lost"
+    );
+
+    session.rewind(1)?;
+    let rewound = Session::load_from(store, &session.id)?;
+    let last = rewound.messages.last().context("no messages")?;
+    anyhow::ensure!(
+        rewound.messages.len() == 3 && last.role == Role::Assistant && last.text() == "re: first",
+        "the rewind was not persisted"
+    );
+    Ok(())
+}
@@ -0,0 +1,125 @@
+//! `kaze serve --watch <id>`: a read-only live view of a session in the
+//! browser, for following a long agent run from another window or sharing
+//! one while pairing.
+//!
+//! A small HTTP server answers `/` with the transcript page and
+//! `/transcript` with just its sections, reloading the session from the
+//! store on every request; the page polls the latter and swaps it in. The
+//! session is never written. Messages appear as the running kaze saves
+//! them, so an answer shows up once it has finished streaming. Tool calls
+//! aren't saved in the session, so the view has only the conversation.
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use kaze::constants::{SERVE_POLL_MS, SERVE_REQUEST_MAX_BYTES, SERVE_REQUEST_TIMEOUT_SECS};
+use kaze::session::{export, Session};
+
+/// Path the live page polls for the transcript.
+const TRANSCRIPT_PATH: &str = "/transcript";
+
+/// Serves the live view of session `id` (a prefix will do) until Ctrl-C.
+pub(crate) async fn handle_serve(id: &str, host: &str, port: u16) -> Result<()> {
+    let id = super::session::resolve_session_id(id)?;
+    let session = Session::load(&id)?;
+    let listener = TcpListener::bind((host, port))
+        .await
+        .with_context(|| format!("Failed to listen on {}:{}", host, port))?;
+    let url = format!("http://{}/", listener.local_addr()?);
+    println!(
+        "{} {} at {}",
+        "Watching".bold().cyan(),
+        session.title().unwrap_or_else(|| id.clone()),
+        url.underline()
+    );
+    println!("{}", "Read-only; Ctrl-C to stop.".dimmed());
+
+    loop {
+        let stream = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => stream,
+                Err(_) => continue,
+            },
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        };
+        // A client that hangs up or sends garbage only loses its own answer
+        let _ = respond(stream, &id).await;
+    }
+}
+
+/// Answers one request and closes the connection.
+async fn respond(mut stream: TcpStream, id: &str) -> Result<()> {
+    let request = tokio::time::timeout(
+        Duration::from_secs(SERVE_REQUEST_TIMEOUT_SECS),
+        read_head(&mut stream),
+    )
+    .await
+    .context("The client sent no request")??;
+    let mut words = request.split_whitespace();
+    let method = words.next().unwrap_or_default();
+    let path = words.next().unwrap_or_default();
+    let path = path.split('?').next().unwrap_or_default();
+
+    let html = "text/html; charset=utf-8";
+    let (status, content_type, body) = match (method, path) {
+        ("GET", "/") | ("GET", TRANSCRIPT_PATH) => match Session::load(id) {
+            Ok(session) if path == "/" => (
+                "200 OK",
+                html,
+                export::render_html_live(&session, TRANSCRIPT_PATH, SERVE_POLL_MS),
+            ),
+            Ok(session) => (
+                "200 OK",
+                html,
+                export::render_html_transcript(&session, false),
+            ),
+            Err(e) => (
+                "500 Internal Server Error",
+                "text/plain; charset=utf-8",
+                format!("{:#}", e),
+            ),
+        },
+        ("GET", _) => (
+            "404 Not Found",
+            "text/plain; charset=utf-8",
+            "Not found".into(),
+        ),
+        _ => (
+            "405 Method Not Allowed",
+            "text/plain; charset=utf-8",
+            "Read-only".into(),
+        ),
+    };
+
+    let head = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n\
+         Cache-Control: no-store\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(body.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+/// Reads up to the end of the request's headers; the body, if any, is
+/// never needed.
+async fn read_head(stream: &mut TcpStream) -> Result<String> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+        anyhow::ensure!(
+            head.len() < SERVE_REQUEST_MAX_BYTES,
+            "The request is too large"
+        );
+        let read = stream.read(&mut buf).await?;
+        anyhow::ensure!(read > 0, "The client hung up");
+        head.extend_from_slice(&buf[..read]);
+    }
+    Ok(String::from_utf8_lossy(&head).into_owned())
+}
@@ -1,38 +1,97 @@
 //! Session management CLI operations for kaze.
 //!
-//! Handles listing, resuming, and deleting chat sessions through the
+//! Handles listing, resuming (by ID or the project's latest), renaming, deleting, pruning, and exporting chat sessions through the
 //! `kaze session` subcommand family. Provides table-formatted output
 //! and partial session ID matching (git-style short IDs).
 
 use anyhow::Result;
+use clap::ValueEnum;
 use colored::Colorize;
 
 use super::SessionAction;
-use crate::{chat, config, provider, session};
+use kaze::session::export;
+use kaze::usage::format_cost;
+use kaze::{chat, config, provider, session};
+
+/// Output format of `kaze session export`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// The full transcript as pretty JSON
+    Json,
+    /// A Markdown document
+    Md,
+    /// A standalone HTML page
+    Html,
+}
 
 /// Dispatches a session subcommand to its handler.
-pub(crate) async fn handle_session(action: SessionAction) -> Result<()> {
+pub(crate) async fn handle_session(
+    action: SessionAction,
+    options: chat::ChatOptions,
+) -> Result<()> {
     match action {
         SessionAction::New => {
             let config = config::Config::load()?;
             let selection = provider::resolve_model(None, None, &config)?;
             let mut config = config;
             config.model = selection.model.clone();
-            chat::run_chat(config, None, &selection).await
+            chat::run_chat(config, None, &selection, options).await
         }
         SessionAction::List => session_list(),
-        SessionAction::Resume { id } => {
+        SessionAction::Resume { id, last } => {
             let config = config::Config::load()?;
             let selection = provider::resolve_model(None, None, &config)?;
             let mut config = config;
             config.model = selection.model.clone();
-            let full_id = resolve_session_id(&id)?;
-            chat::run_chat(config, Some(full_id), &selection).await
+            let full_id = match id {
+                Some(id) if !last => resolve_session_id(&id)?,
+                _ => latest_session_id()?,
+            };
+            chat::run_chat(config, Some(full_id), &selection, options).await
         }
         SessionAction::Delete { id } => {
             let full_id = resolve_session_id(&id)?;
             session_delete(&full_id)
         }
+        SessionAction::Rename { id, title, clear } => {
+            let full_id = resolve_session_id(&id)?;
+            session_rename(&full_id, if clear { None } else { title.as_deref() })
+        }
+        SessionAction::Reindex => session_reindex().await,
+        SessionAction::Prune {
+            max_age_days,
+            max_count,
+            archive,
+            dry_run,
+        } => {
+            let config = config::Config::load()?;
+            let mut policy = session::PrunePolicy::from_config(&config.sessions);
+            policy.max_age_days = max_age_days.or(policy.max_age_days);
+            policy.max_count = max_count.or(policy.max_count);
+            policy.archive |= archive;
+            session_prune(&policy, dry_run)
+        }
+        SessionAction::Export {
+            id,
+            format,
+            include_tools,
+            annotations,
+        } => {
+            let full_id = resolve_session_id(&id)?;
+            match format {
+                ExportFormat::Json => session_export(&full_id, annotations),
+                ExportFormat::Md => {
+                    let session = session::Session::load(&full_id)?;
+                    print!("{}", export::render_markdown(&session, include_tools));
+                    Ok(())
+                }
+                ExportFormat::Html => {
+                    let session = session::Session::load(&full_id)?;
+                    print!("{}", export::render_html(&session, include_tools));
+                    Ok(())
+                }
+            }
+        }
     }
 }
 
@@ -41,11 +100,7 @@ pub(crate) async fn handle_session(action: SessionAction) -> Result<()> {
 /// Matches the given prefix against all known session IDs. Returns an error
 /// if zero or multiple sessions match.
 pub(crate) fn resolve_session_id(partial: &str) -> Result<String> {
-    let sessions = session::Session::list_all()?;
-    let matches: Vec<_> = sessions
-        .iter()
-        .filter(|s| s.id.starts_with(partial))
-        .collect();
+    let matches = session::Session::find(partial)?;
     match matches.len() {
         0 => anyhow::bail!("No session found matching '{}'", partial),
         1 => Ok(matches[0].id.clone()),
@@ -64,6 +119,18 @@ pub(crate) fn resolve_session_id(partial: &str) -> Result<String> {
     }
 }
 
+/// The ID of the most recently updated session of the current project,
+/// for `kaze chat --continue` and `kaze session resume --last`.
+pub(crate) fn latest_session_id() -> Result<String> {
+    match session::Session::latest_in_project()? {
+        Some(meta) => Ok(meta.id),
+        None => anyhow::bail!(
+            "No sessions for {} yet; start one with `kaze chat`",
+            session::current_project_dir().unwrap_or_else(|| "this directory".to_string())
+        ),
+    }
+}
+
 /// Lists all saved sessions in a formatted table.
 ///
 /// Displays session ID, title, message count, last-updated timestamp,
@@ -82,8 +149,8 @@ pub(crate) fn session_list() -> Result<()> {
         .map(|(w, _)| w.0 as usize)
         .unwrap_or(80);
 
-    // Fixed column widths: ID=10, MSGS=6, UPDATED=18, MODEL~20, gaps between columns
-    let fixed_cols = 10 + 6 + 18 + 20;
+    // Fixed column widths: ID=10, MSGS=6, UPDATED=18, COST=9, MODEL~20, gaps between columns
+    let fixed_cols = 10 + 6 + 18 + 9 + 20;
     // Find the longest actual title
     let max_title_len = sessions
         .iter()
@@ -94,15 +161,16 @@ pub(crate) fn session_list() -> Result<()> {
     // Title width = actual content width, capped by terminal space and max 50
     let max_from_terminal = term_width.saturating_sub(fixed_cols).min(50);
     let title_width = max_title_len.max(5).min(max_from_terminal);
-    let header_width = 10 + title_width + 2 + 6 + 18 + 20; // +2 for TITLE padding
+    let header_width = 10 + title_width + 2 + 6 + 18 + 9 + 20; // +2 for TITLE padding
 
     // Print header
     println!(
-        "{:<10} {:<tw$} {:<6} {:<18} {}",
+        "{:<10} {:<tw$} {:<6} {:<18} {:<9} {}",
         format!("{:<10}", "ID").bold(),
         format!("{:<tw$}", "TITLE", tw = title_width + 2).bold(),
         format!("{:<6}", "MSGS").bold(),
         format!("{:<18}", "UPDATED").bold(),
+        format!("{:<9}", "COST").bold(),
         "MODEL".bold(),
         tw = title_width + 2,
     );
@@ -134,13 +202,16 @@ pub(crate) fn session_list() -> Result<()> {
         let title_col = format!("{:<tw$}", title, tw = title_width + 2);
         let msgs_col = format!("{:<6}", s.message_count);
         let updated_col = format!("{:<18}", updated);
+        let cost = s.cost_usd.map(format_cost).unwrap_or_else(|| "-".into());
+        let cost_col = format!("{:<9}", cost);
 
         println!(
-            "{} {} {} {} {}",
+            "{} {} {} {} {} {}",
             id_col.cyan(),
             title_col,
             msgs_col.yellow(),
             updated_col.dimmed(),
+            cost_col.green(),
             s.model.dimmed(),
         );
     }
@@ -167,3 +238,118 @@ pub(crate) fn session_delete(id: &str) -> Result<()> {
     println!("{}", "Deleted.".green());
     Ok(())
 }
+
+/// Backfills session index metadata from the JSONL files on disk.
+pub(crate) async fn session_reindex() -> Result<()> {
+    let report = session::Session::reindex().await?;
+    println!(
+        "{} {} session files ({} updated, {} added)",
+        "Reindexed".green(),
+        report.scanned,
+        report.updated,
+        report.added
+    );
+    Ok(())
+}
+
+/// Removes sessions outside `policy`, listing each one.
+pub(crate) fn session_prune(policy: &session::PrunePolicy, dry_run: bool) -> Result<()> {
+    if policy.is_empty() {
+        anyhow::bail!(
+            "Nothing to prune by. Pass --max-age-days or --max-count, or set them under [sessions] in config.toml"
+        );
+    }
+    let report = session::Session::prune(policy, dry_run)?;
+    for meta in &report.removed {
+        let updated = chrono::DateTime::parse_from_rfc3339(&meta.updated_at)
+            .map(|dt| dt.format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|_| meta.updated_at.clone());
+        println!(
+            "  {} {} {}",
+            meta.id[..8.min(meta.id.len())].cyan(),
+            updated.dimmed(),
+            meta.title.as_deref().unwrap_or("(untitled)")
+        );
+    }
+    let count = report.removed.len();
+    let verb = match (dry_run, &report.archive_dir) {
+        (true, Some(_)) => "Would archive",
+        (true, None) => "Would delete",
+        (false, Some(_)) => "Archived",
+        (false, None) => "Deleted",
+    };
+    let mut summary = format!(
+        "{} {} session{}",
+        verb,
+        count,
+        if count == 1 { "" } else { "s" }
+    );
+    if let (Some(dir), true) = (&report.archive_dir, count > 0) {
+        summary.push_str(&format!(" to {}", dir.display()));
+    }
+    if report.stale > 0 {
+        summary.push_str(&format!(
+            "; {} {} stale index entr{}",
+            if dry_run { "would drop" } else { "dropped" },
+            report.stale,
+            if report.stale == 1 { "y" } else { "ies" }
+        ));
+    }
+    println!("{}", summary.green());
+    Ok(())
+}
+
+/// Sets or clears a session's custom title.
+pub(crate) fn session_rename(id: &str, title: Option<&str>) -> Result<()> {
+    let mut session = session::Session::load(id)?;
+    session.rename(title)?;
+    let short = &id[..8.min(id.len())];
+    let title = session.title().unwrap_or_else(|| "(untitled)".into());
+    if session.custom_title.is_some() {
+        println!("{} {} {}", "Renamed".green(), short, title);
+    } else {
+        println!(
+            "{} {} {}",
+            "Cleared the custom title of".green(),
+            short,
+            format!("(now: {})", title).dimmed()
+        );
+    }
+    Ok(())
+}
+
+/// Prints a session transcript as pretty JSON on stdout.
+///
+/// With `annotations`, each rated message carries an `annotations` array
+/// of its `/rate` entries.
+pub(crate) fn session_export(id: &str, annotations: bool) -> Result<()> {
+    let session = session::Session::load(id)?;
+    let ratings = if annotations {
+        session.annotations()?
+    } else {
+        Vec::new()
+    };
+
+    let messages: Vec<serde_json::Value> = session
+        .messages
+        .iter()
+        .enumerate()
+        .map(|(i, msg)| {
+            let mut value = serde_json::to_value(msg)?;
+            let attached: Vec<_> = ratings.iter().filter(|a| a.message_index == i).collect();
+            if !attached.is_empty() {
+                value["annotations"] = serde_json::to_value(attached)?;
+            }
+            Ok(value)
+        })
+        .collect::<Result<_>>()?;
+
+    let export = serde_json::json!({
+        "id": session.id,
+        "model": session.model,
+        "params": session.params()?,
+        "messages": messages,
+    });
+    println!("{}", serde_json::to_string_pretty(&export)?);
+    Ok(())
+}
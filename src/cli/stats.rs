@@ -0,0 +1,98 @@
+//! `kaze stats`: how the agent has been used, aggregated from the usage ledger.
+
+use anyhow::Result;
+use colored::Colorize;
+
+use kaze::stats::{self, UsageStats};
+use kaze::tokens::format_number;
+use kaze::usage::{self, format_cost};
+
+/// Prints totals, tool frequency, most-edited files, and weekly spend.
+///
+/// `top` caps the tool and file tables; `weeks` caps the spend table to
+/// the most recent weeks with activity.
+pub(crate) fn handle_stats(top: usize, weeks: usize) -> Result<()> {
+    let records = usage::read_ledger()?;
+    if records.is_empty() {
+        println!("{}", "No usage recorded yet.".dimmed());
+        println!("Turns are recorded to {}", usage::ledger_path()?.display());
+        return Ok(());
+    }
+    let stats = stats::aggregate(&records);
+
+    print_totals(&stats);
+    if !stats.tools.is_empty() {
+        println!();
+        println!("{}", "Tool calls".bold());
+        print_ranked(&stats.tools, top);
+    }
+    if !stats.files.is_empty() {
+        println!();
+        println!("{}", "Most edited files".bold());
+        print_ranked(&stats.files, top);
+    }
+
+    println!();
+    println!("{}", "Spend per week".bold());
+    let skip = stats.weeks.len().saturating_sub(weeks);
+    for week in &stats.weeks[skip..] {
+        // Pad first, then colorize to avoid ANSI escape code width issues
+        println!(
+            "  {}  {}  {}",
+            week.week_start.format("%Y-%m-%d").to_string().dimmed(),
+            format!("{:>8}", format_cost(week.cost_usd)).green(),
+            count_of(week.turns, "turn", "turns").dimmed(),
+        );
+    }
+    Ok(())
+}
+
+fn print_totals(stats: &UsageStats) {
+    let label = |name: &str| format!("{:<16}", name).dimmed();
+    println!(
+        "{} {} ({} in {}, {} one-shot)",
+        label("turns"),
+        format_number(stats.turns),
+        format_number(stats.turns - stats.one_shot_turns),
+        count_of(stats.sessions, "session", "sessions"),
+        format_number(stats.one_shot_turns),
+    );
+    println!(
+        "{} {}",
+        label("tool calls"),
+        format_number(stats.tool_calls)
+    );
+    if let Some(ms) = stats.avg_turn_ms {
+        println!("{} {:.1}s", label("avg turn"), ms / 1000.0);
+    }
+    if let Some(ms) = stats.avg_ttft_ms {
+        println!("{} {:.0}ms", label("avg first token"), ms);
+    }
+    println!(
+        "{} {}",
+        label("total spend"),
+        format_cost(stats.cost_usd).green()
+    );
+}
+
+/// Prints `(name, count)` rows aligned on the count, at most `top` of them.
+fn print_ranked(rows: &[(String, usize)], top: usize) {
+    let shown = &rows[..rows.len().min(top)];
+    let width = shown
+        .iter()
+        .map(|(_, count)| format_number(*count).len())
+        .max()
+        .unwrap_or(1);
+    for (name, count) in shown {
+        let count = format!("{:>width$}", format_number(*count), width = width);
+        println!("  {}  {}", count.yellow(), name);
+    }
+    if rows.len() > top {
+        println!("  {}", format!("... {} more", rows.len() - top).dimmed());
+    }
+}
+
+/// Formats a count with its singular or plural noun, e.g. `1 turn`.
+fn count_of(n: usize, one: &str, many: &str) -> String {
+    format!("{} {}", format_number(n), if n == 1 { one } else { many })
+}
@@ -0,0 +1,171 @@
+//! `kaze suggest`: one shell command for a natural-language request.
+//!
+//! Built for shell widgets: stdout carries the bare command and nothing
+//! else, errors go to stderr with a non-zero exit, and the request uses the
+//! `[suggest] model` (meant to be small and fast) through a single
+//! non-streaming call with no tools. `kaze suggest --init <shell>` prints a
+//! widget for zsh, fish, or bash that replaces the command line with the
+//! suggestion for what was typed when Alt+K is pressed.
+
+use anyhow::Result;
+use clap::ValueEnum;
+
+use kaze::constants::SUGGEST_SYSTEM_PROMPT;
+use kaze::{config, provider};
+
+/// Shells with a `--init` widget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum WidgetShell {
+    Zsh,
+    Fish,
+    Bash,
+}
+
+/// Arguments of the `suggest` subcommand.
+pub(crate) struct SuggestOptions {
+    pub request: String,
+    pub model: Option<String>,
+    pub provider: Option<String>,
+    /// Shell to write the command for; defaults to `$SHELL`.
+    pub shell: Option<String>,
+}
+
+/// Prints a single suggested command on stdout.
+pub(crate) async fn handle_suggest(opts: SuggestOptions) -> Result<()> {
+    if opts.request.trim().is_empty() {
+        anyhow::bail!(
+            "Describe the command you want, e.g. kaze suggest \"list the largest files\""
+        );
+    }
+    let config = config::Config::load()?;
+    let selection = match config.suggest.model.as_deref() {
+        Some(spec) if opts.model.is_none() && opts.provider.is_none() => {
+            let current = provider::resolve_model(None, None, &config)?;
+            provider::resolve_model_spec(spec, current.provider, &config)?
+        }
+        _ => provider::resolve_model(opts.provider.as_deref(), opts.model.as_deref(), &config)?,
+    };
+    let provider = provider::Provider::from_config(&config, &selection)?;
+
+    let shell = opts.shell.unwrap_or_else(default_shell);
+    let cwd = std::env::current_dir()?;
+    let system_prompt = SUGGEST_SYSTEM_PROMPT
+        .replace("{shell}", &shell)
+        .replace("{os}", std::env::consts::OS)
+        .replace("{cwd}", &cwd.display().to_string());
+
+    let response = match provider
+        .prompt_with_system(&system_prompt, &opts.request)
+        .await
+    {
+        Ok(response) => response,
+        Err(e) => return Err(provider::explain_error(e, selection.provider, &config).await),
+    };
+    match first_command(&response) {
+        Some(command) => {
+            println!("{}", command);
+            Ok(())
+        }
+        None => anyhow::bail!("The model returned no command"),
+    }
+}
+
+/// The user's shell name from `$SHELL`, e.g. `zsh`.
+fn default_shell() -> String {
+    std::env::var("SHELL")
+        .ok()
+        .and_then(|path| {
+            std::path::Path::new(&path)
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| {
+            if cfg!(windows) {
+                "powershell".to_string()
+            } else {
+                "sh".to_string()
+            }
+        })
+}
+
+/// The first command in a model reply, without code fences, inline
+/// backticks, or a leading `$ ` prompt. Lines ending in `\` continue onto
+/// the next line and are kept together.
+fn first_command(reply: &str) -> Option<String> {
+    let mut lines = reply
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.trim_start().starts_with("```"))
+        .skip_while(|line| line.trim().is_empty());
+
+    let first = lines.next()?.trim();
+    let first = first
+        .strip_prefix('`')
+        .and_then(|rest| rest.strip_suffix('`'))
+        .unwrap_or(first);
+    let first = first
+        .strip_prefix("$ ")
+        .or_else(|| first.strip_prefix("> "))
+        .unwrap_or(first)
+        .trim();
+    if first.is_empty() {
+        return None;
+    }
+
+    let mut command = first.to_string();
+    while command.ends_with('\\') {
+        let Some(next) = lines.next() else { break };
+        command.push('\n');
+        command.push_str(next);
+    }
+    Some(command)
+}
+
+/// Prints the widget that binds Alt+K to `kaze suggest` in `shell`.
+pub(crate) fn print_widget(shell: WidgetShell) {
+    let script = match shell {
+        WidgetShell::Zsh => ZSH_WIDGET,
+        WidgetShell::Fish => FISH_WIDGET,
+        WidgetShell::Bash => BASH_WIDGET,
+    };
+    print!("{}", script);
+}
+
+const ZSH_WIDGET: &str = r#"# kaze suggest: press Alt+K to turn the command line into a command.
+# Add to ~/.zshrc:  eval "$(kaze suggest --init zsh)"
+_kaze_suggest() {
+  [[ -z $BUFFER ]] && return
+  local cmd
+  zle -R "kaze: thinking..."
+  cmd=$(kaze suggest --shell zsh -- "$BUFFER" 2>/dev/null) || { zle -R ""; return 1; }
+  BUFFER=$cmd
+  CURSOR=${#BUFFER}
+  zle redisplay
+}
+zle -N _kaze_suggest
+bindkey '^[k' _kaze_suggest
+"#;
+
+const FISH_WIDGET: &str = r#"# kaze suggest: press Alt+K to turn the command line into a command.
+# Add to ~/.config/fish/config.fish:  kaze suggest --init fish | source
+function _kaze_suggest
+    set -l line (commandline)
+    test -z "$line"; and return
+    set -l cmd (kaze suggest --shell fish -- "$line" 2>/dev/null | string collect); or return
+    commandline -r -- $cmd
+    commandline -f repaint
+end
+bind \ek _kaze_suggest
+"#;
+
+const BASH_WIDGET: &str = r#"# kaze suggest: press Alt+K to turn the command line into a command.
+# Add to ~/.bashrc:  eval "$(kaze suggest --init bash)"
+_kaze_suggest() {
+  [[ -z $READLINE_LINE ]] && return
+  local cmd
+  cmd=$(kaze suggest --shell bash -- "$READLINE_LINE" 2>/dev/null) || return
+  READLINE_LINE=$cmd
+  READLINE_POINT=${#cmd}
+}
+bind -x '"\ek": _kaze_suggest'
+"#;
@@ -0,0 +1,100 @@
+use super::schedule::{cron_entry, crontab_listing, without_cron_entry, Calendar};
+use kaze::constants::SCHEDULE_CRON_MARKER;
+use std::path::Path;
+
+fn nightly_entry(name: &str) -> String {
+    let calendar = Calendar::parse("03:00", &["mon".to_string()], None).unwrap();
+    cron_entry(
+        name,
+        &calendar,
+        Path::new("/usr/bin/kaze"),
+        Path::new("/home/me/50% done"),
+        Path::new("/tmp/kaze.log"),
+    )
+}
+
+#[test]
+fn test_cron_entry_quotes_and_escapes() {
+    let entry = nightly_entry("nightly");
+    let (marker, line) = entry.split_once('\n').unwrap();
+    assert_eq!(marker, format!("{} nightly", SCHEDULE_CRON_MARKER));
+    assert_eq!(
+        line,
+        r"0 3 * * 1 cd '/home/me/50\% done' && '/usr/bin/kaze' run --template nightly >> '/tmp/kaze.log' 2>&1"
+    );
+}
+
+#[test]
+fn test_without_cron_entry_keeps_other_lines() {
+    let crontab = format!(
+        "MAILTO=me\n{}\n*/5 * * * * backup\n{}\n",
+        nightly_entry("nightly"),
+        nightly_entry("weekly")
+    );
+    let kept = without_cron_entry(&crontab, "nightly");
+    assert_eq!(
+        kept,
+        format!(
+            "MAILTO=me\n*/5 * * * * backup\n{}\n",
+            nightly_entry("weekly")
+        )
+    );
+    // Nothing to remove leaves the crontab as it was
+    assert_eq!(without_cron_entry(&kept, "nightly"), kept);
+}
+
+#[test]
+fn test_crontab_listing_only_treats_missing_crontab_as_empty() {
+    assert_eq!(
+        crontab_listing(true, b"0 * * * * job\n", b"").unwrap(),
+        "0 * * * * job\n"
+    );
+    assert_eq!(
+        crontab_listing(false, b"", b"no crontab for me\n").unwrap(),
+        ""
+    );
+    let err = crontab_listing(false, b"", b"crontab: cannot open spool\n").unwrap_err();
+    assert!(err.to_string().contains("cannot open spool"));
+}
+
+#[tokio::test]
+async fn test_parallel_worktree_changes_and_removal() {
+    use super::parallel::{changes, git, remove_worktree};
+
+    let dir = std::env::temp_dir().join(format!("kaze_test_parallel_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let repo = dir.join("repo");
+    std::fs::create_dir_all(&repo).unwrap();
+    for args in [
+        &["init", "-q"][..],
+        &["config", "user.name", "Test"],
+        &["config", "user.email", "test@example.com"],
+        &["commit", "-q", "--allow-empty", "-m", "Start"],
+    ] {
+        git(&repo, args).await.unwrap();
+    }
+    let base = git(&repo, &["rev-parse", "HEAD"]).await.unwrap();
+    let base = base.trim();
+    let worktree = dir.join("task");
+    let path = worktree.to_string_lossy();
+    git(
+        &repo,
+        &["worktree", "add", "-q", "-b", "kaze/t/task", &path, base],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(changes(&worktree, base).await, (0, 0));
+    std::fs::write(worktree.join("a.txt"), "a\n").unwrap();
+    assert_eq!(changes(&worktree, base).await, (1, 0));
+    git(&worktree, &["add", "a.txt"]).await.unwrap();
+    git(&worktree, &["commit", "-qm", "Add a"]).await.unwrap();
+    assert_eq!(changes(&worktree, base).await, (0, 1));
+
+    remove_worktree(&repo, &worktree, "kaze/t/task").await;
+    assert!(!worktree.exists());
+    let branches = git(&repo, &["branch", "--list", "kaze/*"]).await.unwrap();
+    assert!(branches.trim().is_empty(), "{}", branches);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
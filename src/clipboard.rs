@@ -0,0 +1,109 @@
+//! Copying text to the system clipboard, for `/copy`.
+//!
+//! Locally, text is piped to the platform's clipboard command (`pbcopy`,
+//! `wl-copy`, `xclip`, `xsel`, or `clip.exe`). Over SSH, or when none of
+//! those is available, kaze writes an OSC 52 escape sequence instead, which
+//! asks the terminal emulator itself to set its clipboard; this reaches the
+//! local machine through any number of SSH hops and through tmux (with
+//! `set-clipboard on`), provided the terminal supports OSC 52.
+
+use anyhow::{Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::constants::OSC52_MAX_BYTES;
+
+/// How the text reached the clipboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// Piped to this clipboard command.
+    Command(&'static str),
+    /// Sent to the terminal as an OSC 52 sequence.
+    Osc52,
+}
+
+impl std::fmt::Display for Method {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Method::Command(program) => write!(f, "{}", program),
+            Method::Osc52 => write!(f, "OSC 52"),
+        }
+    }
+}
+
+/// Copies `text` to the clipboard, preferring OSC 52 over SSH and a
+/// clipboard command otherwise.
+pub fn copy(text: &str) -> Result<Method> {
+    let remote =
+        std::env::var_os("SSH_TTY").is_some() || std::env::var_os("SSH_CONNECTION").is_some();
+    if !remote {
+        for (program, args) in clipboard_commands() {
+            if pipe_to(program, args, text).is_ok() {
+                return Ok(Method::Command(program));
+            }
+        }
+    }
+    osc52(text)?;
+    Ok(Method::Osc52)
+}
+
+/// Clipboard commands worth trying on this platform, in order.
+fn clipboard_commands() -> Vec<(&'static str, &'static [&'static str])> {
+    if cfg!(target_os = "macos") {
+        return vec![("pbcopy", &[])];
+    }
+    if cfg!(windows) {
+        return vec![("clip.exe", &[])];
+    }
+    let mut commands: Vec<(&'static str, &'static [&'static str])> = Vec::new();
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        commands.push(("wl-copy", &[]));
+    }
+    if std::env::var_os("DISPLAY").is_some() {
+        commands.push(("xclip", &["-selection", "clipboard"]));
+        commands.push(("xsel", &["--clipboard", "--input"]));
+    }
+    // WSL has no display server, but can reach the Windows clipboard
+    commands.push(("clip.exe", &[]));
+    commands
+}
+
+fn pipe_to(program: &str, args: &[&str], text: &str) -> Result<()> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    child
+        .stdin
+        .take()
+        .context("clipboard command has no stdin")?
+        .write_all(text.as_bytes())?;
+    let status = child.wait()?;
+    anyhow::ensure!(status.success(), "{} exited with {}", program, status);
+    Ok(())
+}
+
+/// Writes the OSC 52 "set clipboard" sequence for `text` to the terminal,
+/// wrapped in a passthrough sequence when running inside tmux.
+fn osc52(text: &str) -> Result<()> {
+    anyhow::ensure!(
+        text.len() <= OSC52_MAX_BYTES,
+        "{} bytes is too much to copy through the terminal (limit {})",
+        text.len(),
+        OSC52_MAX_BYTES
+    );
+    let sequence = format!("\x1b]52;c;{}\x07", BASE64.encode(text));
+    let sequence = if std::env::var_os("TMUX").is_some() {
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else {
+        sequence
+    };
+    let mut stdout = std::io::stdout();
+    stdout.write_all(sequence.as_bytes())?;
+    stdout.flush()?;
+    Ok(())
+}
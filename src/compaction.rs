@@ -3,14 +3,20 @@
 //! When the conversation context window fills up, compaction summarizes
 //! older messages into a single system-level summary, preserving key
 //! decisions and technical details while freeing token budget.
+//!
+//! The rolling strategy ([`roll`]) instead keeps a fixed window of recent
+//! messages and folds each message that leaves it into a running summary.
 
 use anyhow::{Context, Result};
 
-use crate::message::Message;
+use crate::message::{Message, Role};
 use crate::provider::Provider;
 use crate::tokens;
 
-use crate::constants::COMPACTION_PROMPT;
+use crate::constants::{COMPACTION_PROMPT, ROLLING_SUMMARY_PROMPT};
+
+/// Prefix of the system message holding the rolling strategy's summary.
+pub const ROLLING_SUMMARY_PREFIX: &str = "[Rolling summary]: ";
 
 /// Result of a compaction attempt.
 pub enum CompactionResult {
@@ -45,6 +51,7 @@ pub async fn compact(
     model: &str,
     keep_recent: usize,
 ) -> Result<CompactionResult> {
+    let _span = tracing::info_span!("compaction", messages = messages.len());
     // Need at least: system prompt + something to compact + keep_recent messages
     if messages.len() <= 1 + keep_recent {
         return Ok(CompactionResult::NothingToCompact);
@@ -98,3 +105,103 @@ pub async fn compact(
         tokens_after,
     })
 }
+
+/// Fold messages older than the last `window_tokens` into a running summary.
+///
+/// The leading system messages (the system prompt and any earlier
+/// compaction summary) are always kept. Walking back from the newest
+/// message, messages are kept verbatim while they fit in `window_tokens`;
+/// the window always starts at a user message so a turn is never split,
+/// and the latest turn is kept even when it alone exceeds the window.
+/// Everything older is merged into the `[Rolling summary]` system message,
+/// which is created after the leading system messages on first use.
+pub async fn roll(
+    messages: &mut Vec<Message>,
+    provider: &Provider,
+    model: &str,
+    window_tokens: usize,
+) -> Result<CompactionResult> {
+    let head = messages
+        .iter()
+        .position(|m| m.role != Role::System)
+        .unwrap_or(messages.len());
+
+    let mut keep_from = messages.len();
+    let mut used = 0;
+    while keep_from > head {
+        let msg = &messages[keep_from - 1];
+        let cost = tokens::count_conversation_tokens(
+            &[(msg.role.to_string(), msg.text().to_string())],
+            model,
+        )
+        .unwrap_or(0);
+        if used + cost > window_tokens {
+            break;
+        }
+        used += cost;
+        keep_from -= 1;
+    }
+    let start = match messages[keep_from..]
+        .iter()
+        .position(|m| m.role == Role::User)
+    {
+        Some(offset) => keep_from + offset,
+        None => match messages[head..keep_from]
+            .iter()
+            .rposition(|m| m.role == Role::User)
+        {
+            Some(offset) => head + offset,
+            None => return Ok(CompactionResult::NothingToCompact),
+        },
+    };
+    if start <= head {
+        return Ok(CompactionResult::NothingToCompact);
+    }
+
+    let msg_pairs_before: Vec<(String, String)> = messages
+        .iter()
+        .map(|m| (m.role.to_string(), m.text().to_string()))
+        .collect();
+    let tokens_before = tokens::count_conversation_tokens(&msg_pairs_before, model).unwrap_or(0);
+
+    let existing = messages[..head]
+        .iter()
+        .position(|m| m.text().starts_with(ROLLING_SUMMARY_PREFIX));
+    let mut text_blob = String::new();
+    if let Some(i) = existing {
+        text_blob.push_str(&format!(
+            "Current summary:\n{}\n\n",
+            &messages[i].text()[ROLLING_SUMMARY_PREFIX.len()..]
+        ));
+    }
+    text_blob.push_str("New messages:\n");
+    for msg in &messages[head..start] {
+        text_blob.push_str(&format!("[{}]: {}\n\n", msg.role, msg.text()));
+    }
+
+    let prompt_text = format!("{}{}", ROLLING_SUMMARY_PROMPT, text_blob);
+    let summary = provider
+        .prompt(&prompt_text)
+        .await
+        .context("Failed to update rolling summary")?;
+
+    let messages_removed = start - head;
+    messages.drain(head..start);
+    let summary = Message::system(format!("{}{}", ROLLING_SUMMARY_PREFIX, summary.trim()));
+    match existing {
+        Some(i) => messages[i] = summary,
+        None => messages.insert(head, summary),
+    }
+
+    let msg_pairs_after: Vec<(String, String)> = messages
+        .iter()
+        .map(|m| (m.role.to_string(), m.text().to_string()))
+        .collect();
+    let tokens_after = tokens::count_conversation_tokens(&msg_pairs_after, model).unwrap_or(0);
+
+    Ok(CompactionResult::Compacted {
+        messages_removed,
+        tokens_before,
+        tokens_after,
+    })
+}
@@ -0,0 +1,172 @@
+//! Config bundle export and import for team sharing.
+//!
+//! A bundle is a plain kaze TOML file a team lead can distribute. Exporting
+//! keeps `{env:VAR}` placeholders unresolved so secrets never leave the
+//! machine by accident; `--no-secrets` strips API keys, provider headers,
+//! and account IDs altogether.
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::types::{Config, ProviderEntry};
+
+impl Config {
+    /// Serializes the unresolved config as a shareable TOML bundle.
+    ///
+    /// When `no_secrets` is set, every provider and tool `api_key` is removed,
+    /// along with provider headers (which often carry auth) and OpenAI
+    /// organization and project IDs, so the bundle only carries permissions,
+    /// prompts, and other vetted settings.
+    pub fn export_bundle(&self, no_secrets: bool) -> Result<String> {
+        let mut bundle = self.clone();
+        if no_secrets {
+            for entry in [
+                &mut bundle.provider.openai,
+                &mut bundle.provider.anthropic,
+                &mut bundle.provider.ollama,
+                &mut bundle.provider.openrouter,
+                &mut bundle.provider.bedrock,
+            ] {
+                Self::strip_secrets(entry);
+            }
+            bundle.tools.web_search.api_key = None;
+        }
+        toml::to_string_pretty(&bundle).context("Failed to serialize config bundle")
+    }
+
+    /// Works out what importing a bundle file would do to the global config,
+    /// without writing anything; [`BundleImport::write`] applies it.
+    ///
+    /// Bundle values win, using the same precedence as a project `kaze.toml`,
+    /// and the global config is what project configs are later reviewed
+    /// against, so the bundle goes through the same review (see the `trust`
+    /// module) for the caller to show before asking.
+    pub fn plan_import(path: &Path) -> Result<BundleImport> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config bundle {:?}", path))?;
+        let bundle: Config = toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse config bundle {:?}", path))?;
+        Self::plan_bundle(Self::load_global()?, bundle, Self::config_path()?)
+    }
+
+    fn plan_bundle(global: Config, bundle: Config, config_path: PathBuf) -> Result<BundleImport> {
+        let review = super::trust::review_lines(&global, &bundle);
+        let current = toml::to_string_pretty(&global)?;
+        let merged = toml::to_string_pretty(&Self::merge(global, bundle))?;
+        let diff = if merged == current {
+            String::new()
+        } else {
+            crate::diff::unified_diff(&current, &merged, "config.toml")
+        };
+        Ok(BundleImport {
+            config_path,
+            merged,
+            diff,
+            review,
+        })
+    }
+
+    /// Drops the API key, headers, and account IDs from a provider entry,
+    /// if present.
+    fn strip_secrets(entry: &mut Option<ProviderEntry>) {
+        if let Some(ref mut e) = entry {
+            e.api_key = None;
+            e.organization = None;
+            e.project = None;
+            e.headers.clear();
+        }
+    }
+}
+
+/// A bundle import, worked out by [`Config::plan_import`] but not written.
+pub struct BundleImport {
+    /// The global config file the bundle goes into.
+    pub config_path: PathBuf,
+    /// The global config with the bundle applied, as it will be written.
+    merged: String,
+    /// What the import changes in the global config, colored; empty when
+    /// the bundle changes nothing.
+    pub diff: String,
+    /// Permissions the bundle loosens and provider endpoints it redirects,
+    /// as the project config review shows them.
+    pub review: Vec<String>,
+}
+
+impl BundleImport {
+    /// Writes the merged config to the global config file.
+    pub fn write(&self) -> Result<()> {
+        fs::write(&self.config_path, &self.merged)
+            .with_context(|| format!("Failed to write config to {:?}", self.config_path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_plan_shows_changes_and_escalations() {
+        let global: Config = toml::from_str("[permissions.tools]\nbash = \"ask\"\n").unwrap();
+        let bundle: Config = toml::from_str(
+            r#"
+[permissions.tools]
+bash = "allow"
+
+[templates.nightly]
+task = "triage"
+auto_approve = true
+
+[provider.openai]
+base_url = "https://llm.example.com/v1"
+"#,
+        )
+        .unwrap();
+
+        let path = PathBuf::from("/nonexistent/kaze/config.toml");
+        let import = Config::plan_bundle(global.clone(), bundle, path.clone()).unwrap();
+        assert!(import.diff.contains("llm.example.com"));
+        assert_eq!(
+            import.review,
+            [
+                "tools.bash: ask → allow",
+                "templates.nightly.auto_approve: ask → allow",
+                "provider.openai.base_url = https://llm.example.com/v1",
+            ]
+        );
+        assert!(!path.exists());
+
+        let unchanged = Config::plan_bundle(global.clone(), global, path).unwrap();
+        assert!(unchanged.diff.is_empty(), "{}", unchanged.diff);
+        assert!(unchanged.review.is_empty());
+    }
+
+    #[test]
+    fn test_no_secrets_drops_headers_and_account_ids() {
+        let config: Config = toml::from_str(
+            r#"
+[provider.openai]
+api_key = "{env:OPENAI_API_KEY}"
+organization = "org-123"
+project = "proj-456"
+model = "gpt-4o"
+
+[provider.openai.headers]
+X-Tenant = "acme"
+"#,
+        )
+        .unwrap();
+
+        let bundle = config.export_bundle(true).unwrap();
+        for secret in ["OPENAI_API_KEY", "org-123", "proj-456", "acme"] {
+            assert!(!bundle.contains(secret), "{} in {}", secret, bundle);
+        }
+        assert!(bundle.contains("gpt-4o"));
+
+        let report = crate::report::redacted_config(&config).unwrap();
+        for secret in ["OPENAI_API_KEY", "org-123", "proj-456", "acme"] {
+            assert!(!report.contains(secret), "{} in {}", secret, report);
+        }
+        assert!(report.contains("gpt-4o"));
+    }
+}
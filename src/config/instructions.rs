@@ -0,0 +1,102 @@
+//! Project instruction files (`KAZE.md`, `AGENTS.md`) added to the system
+//! prompt.
+//!
+//! Every directory from the git root down to the current one may hold one;
+//! where both exist, `KAZE.md` wins. The files are joined outermost first,
+//! so instructions closer to where kaze runs come last, and the whole is
+//! capped at `[prompt] instructions_tokens`.
+
+use std::path::{Path, PathBuf};
+
+use super::types::Config;
+use crate::constants::{PROJECT_INSTRUCTIONS_TOKENS_DEFAULT, PROJECT_INSTRUCTION_FILES};
+use crate::context_cache;
+
+impl Config {
+    /// Reads the project instruction files into the instructions layer.
+    /// Unreadable files are skipped.
+    ///
+    /// The assembled, truncated text is cached (see [`context_cache`]) by
+    /// the files' paths, sizes, and modification times as well as the tree,
+    /// since an instruction file may be one git ignores.
+    pub(super) fn load_instructions(&mut self) {
+        let budget = self
+            .prompt
+            .instructions_tokens
+            .unwrap_or(PROJECT_INSTRUCTIONS_TOKENS_DEFAULT);
+        if budget == 0 {
+            return;
+        }
+        let Ok(cwd) = std::env::current_dir() else {
+            return;
+        };
+        let (root, files) = instruction_files(&cwd);
+        if files.is_empty() {
+            return;
+        }
+        let mut inputs = format!("{} {}", budget, self.model);
+        for path in &files {
+            let meta = std::fs::metadata(path).ok();
+            let modified = meta.as_ref().and_then(|m| m.modified().ok());
+            let size = meta.map(|m| m.len());
+            inputs.push_str(&format!("\n{} {:?} {:?}", path.display(), size, modified));
+        }
+        let build = || assemble(&root, &files, budget, &self.model);
+        let text =
+            context_cache::get_or_compute_with(&root, "instructions", &inputs, || Ok(build()))
+                .unwrap_or_else(|_| build());
+        if !text.is_empty() {
+            self.prompt_layers.instructions = Some(text);
+        }
+    }
+}
+
+/// The instruction files' text joined and cut to `budget` tokens; empty
+/// when none has any.
+fn assemble(root: &Path, files: &[PathBuf], budget: usize, model: &str) -> String {
+    let sections: Vec<String> = files
+        .iter()
+        .filter_map(|path| {
+            let text = std::fs::read_to_string(path).ok()?;
+            let text = text.trim();
+            let shown = path.strip_prefix(root).unwrap_or(path);
+            (!text.is_empty())
+                .then(|| format!("Project instructions from {}:\n\n{}", shown.display(), text))
+        })
+        .collect();
+    if sections.is_empty() {
+        return String::new();
+    }
+    let text = sections.join("\n\n");
+    let (mut kept, dropped) = crate::tokens::truncate_to_first_tokens(&text, budget, model);
+    if dropped > 0 {
+        kept.push_str(&format!(
+            "\n\n[{} more lines of project instructions left out]",
+            dropped
+        ));
+    }
+    kept
+}
+
+/// The instruction file of each directory from the git root (outside a
+/// repository, the filesystem root) down to `dir`, outermost first, with
+/// the directory the search stopped at.
+fn instruction_files(dir: &Path) -> (PathBuf, Vec<PathBuf>) {
+    let mut files = Vec::new();
+    let mut dir = dir.to_path_buf();
+    loop {
+        if let Some(file) = PROJECT_INSTRUCTION_FILES
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|path| path.is_file())
+        {
+            files.push(file);
+        }
+        // Stop at git root or filesystem root
+        if dir.join(".git").exists() || !dir.pop() {
+            break;
+        }
+    }
+    files.reverse();
+    (dir, files)
+}
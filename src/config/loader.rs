@@ -3,7 +3,12 @@
 use anyhow::{Context, Result};
 use std::fs;
 
-use super::types::{default_model, CompactionConfig, Config};
+use super::types::{
+    default_model, CacheConfig, CompactionConfig, Config, ContextConfig, GenerationConfig,
+    OutputConfig, PrivacyConfig, PromptConfig, PromptLayers, ProviderConfig, ProviderEntry,
+    SessionsConfig, SuggestConfig, TaskConfig, ToolsConfig, WebSearchConfig, WriteFileConfig,
+};
+use crate::permissions::PermissionConfig;
 
 impl Config {
     /// Loads the global config from `~/.config/kaze/config.toml`.
@@ -39,32 +44,30 @@ base_url = "http://localhost:11434"
                 .with_context(|| format!("Failed to write default config to {:?}", path))?;
             let config: Config = toml::from_str(&default_toml)
                 .with_context(|| "Failed to parse default config".to_string())?;
-            return Ok(config);
+            return Ok(config.with_global_prompt_layer());
         }
 
         let contents = fs::read_to_string(&path)
             .with_context(|| format!("Failed to read config from {:?}", path))?;
         let config: Config = toml::from_str(&contents)
             .with_context(|| format!("Failed to parse config at {:?}", path))?;
-        Ok(config)
+        Ok(config.with_global_prompt_layer())
+    }
+
+    /// Records this file's `system_prompt` as the global prompt layer.
+    fn with_global_prompt_layer(mut self) -> Self {
+        self.prompt_layers.global = self.system_prompt.clone();
+        self
     }
 
     /// Look for kaze.toml in current dir, then walk up to git root.
     pub(super) fn load_project() -> Result<Option<Config>> {
-        let mut dir = std::env::current_dir()?;
-        loop {
-            let candidate = dir.join(crate::constants::PROJECT_CONFIG_FILENAME);
-            if candidate.exists() {
-                let contents = fs::read_to_string(&candidate)?;
-                let config: Config = toml::from_str(&contents)?;
-                return Ok(Some(config));
-            }
-            // Stop at git root or filesystem root
-            if dir.join(".git").exists() || !dir.pop() {
-                break;
-            }
-        }
-        Ok(None)
+        let Some(path) = Self::project_config_path()? else {
+            return Ok(None);
+        };
+        let contents = fs::read_to_string(&path)?;
+        let config: Config = toml::from_str(&contents)?;
+        Ok(Some(config))
     }
 
     /// Merge project config over global config.
@@ -76,8 +79,28 @@ base_url = "http://localhost:11434"
             } else {
                 global.model
             },
-            provider: global.provider, // TODO: deep merge providers
+            provider: ProviderConfig::merge(global.provider, project.provider),
+            prompt_layers: PromptLayers {
+                global: global.prompt_layers.global,
+                project: project.system_prompt.clone(),
+                instructions: None,
+            },
             system_prompt: project.system_prompt.or(global.system_prompt),
+            prompt: PromptConfig {
+                order: project.prompt.order.or(global.prompt.order),
+                mode: project.prompt.mode.or(global.prompt.mode),
+                // Project modes add to the global ones, winning on name clashes
+                modes: global
+                    .prompt
+                    .modes
+                    .into_iter()
+                    .chain(project.prompt.modes)
+                    .collect(),
+                instructions_tokens: project
+                    .prompt
+                    .instructions_tokens
+                    .or(global.prompt.instructions_tokens),
+            },
             default_provider: project.default_provider.or(global.default_provider),
             compaction: CompactionConfig {
                 auto_threshold: project
@@ -91,14 +114,191 @@ base_url = "http://localhost:11434"
                     .or(global.compaction.keep_recent),
                 reserved: project.compaction.reserved.or(global.compaction.reserved),
             },
-            // Project permissions override global; fall back to global if project has none
-            permissions: if project.permissions.tools.is_empty()
-                && project.permissions.bash_commands.is_empty()
-            {
-                global.permissions
+            context: ContextConfig {
+                strategy: project.context.strategy.or(global.context.strategy),
+                window_tokens: project
+                    .context
+                    .window_tokens
+                    .or(global.context.window_tokens),
+                confirm_above_tokens: project
+                    .context
+                    .confirm_above_tokens
+                    .or(global.context.confirm_above_tokens),
+                refresh_tokens: project
+                    .context
+                    .refresh_tokens
+                    .or(global.context.refresh_tokens),
+                environment: project.context.environment.or(global.context.environment),
+                repo_map_tokens: project
+                    .context
+                    .repo_map_tokens
+                    .or(global.context.repo_map_tokens),
+            },
+            generation: GenerationConfig {
+                temperature: project
+                    .generation
+                    .temperature
+                    .or(global.generation.temperature),
+                seed: project.generation.seed.or(global.generation.seed),
+            },
+            permissions: merge_permissions(project.permissions, global.permissions),
+            cache: CacheConfig {
+                enabled: project.cache.enabled.or(global.cache.enabled),
+                ttl_secs: project.cache.ttl_secs.or(global.cache.ttl_secs),
+            },
+            output: OutputConfig {
+                accessible: project.output.accessible.or(global.output.accessible),
+                tool_args_chars: project
+                    .output
+                    .tool_args_chars
+                    .or(global.output.tool_args_chars),
+                tool_result_chars: project
+                    .output
+                    .tool_result_chars
+                    .or(global.output.tool_result_chars),
+            },
+            tools: ToolsConfig {
+                web_search: WebSearchConfig {
+                    backend: project
+                        .tools
+                        .web_search
+                        .backend
+                        .or(global.tools.web_search.backend),
+                    api_key: project
+                        .tools
+                        .web_search
+                        .api_key
+                        .or(global.tools.web_search.api_key),
+                    base_url: project
+                        .tools
+                        .web_search
+                        .base_url
+                        .or(global.tools.web_search.base_url),
+                    max_results: project
+                        .tools
+                        .web_search
+                        .max_results
+                        .or(global.tools.web_search.max_results),
+                },
+                task: TaskConfig {
+                    model: project.tools.task.model.or(global.tools.task.model),
+                    max_turns: project.tools.task.max_turns.or(global.tools.task.max_turns),
+                },
+                write_file: WriteFileConfig {
+                    confirm_above_bytes: project
+                        .tools
+                        .write_file
+                        .confirm_above_bytes
+                        .or(global.tools.write_file.confirm_above_bytes),
+                },
+            },
+            suggest: SuggestConfig {
+                model: project.suggest.model.or(global.suggest.model),
+            },
+            sessions: SessionsConfig {
+                max_age_days: project
+                    .sessions
+                    .max_age_days
+                    .or(global.sessions.max_age_days),
+                max_count: project.sessions.max_count.or(global.sessions.max_count),
+                archive: project.sessions.archive.or(global.sessions.archive),
+                auto_prune: project.sessions.auto_prune.or(global.sessions.auto_prune),
+                duplicate_hints: project
+                    .sessions
+                    .duplicate_hints
+                    .or(global.sessions.duplicate_hints),
+                storage: global.sessions.storage,
+            },
+            locale: project.locale.or(global.locale),
+            offline_fallback: project.offline_fallback.or(global.offline_fallback),
+            turn_timeout_secs: project.turn_timeout_secs.or(global.turn_timeout_secs),
+            privacy: PrivacyConfig {
+                offline_extras: either(
+                    project.privacy.offline_extras,
+                    global.privacy.offline_extras,
+                ),
+            },
+            // A pin is all-or-nothing; never mix fields from both files
+            pin: if project.pin.model.is_some() {
+                project.pin
             } else {
-                project.permissions
+                global.pin
             },
+            // Project templates add to the global ones, winning on name clashes
+            templates: global
+                .templates
+                .into_iter()
+                .chain(project.templates)
+                .collect(),
         }
     }
 }
+
+/// Merges a restriction either file can turn on: it holds if either asks
+/// for it, so a project can't lift one the global config sets.
+fn either(project: Option<bool>, global: Option<bool>) -> Option<bool> {
+    match (project, global) {
+        (None, None) => None,
+        (project, global) => Some(project == Some(true) || global == Some(true)),
+    }
+}
+
+impl ProviderConfig {
+    /// Merges provider by provider, so a project can override one field of
+    /// one provider (say `ollama.base_url`) and keep the rest of the global
+    /// entries, API keys included. Overrides that would send those keys
+    /// elsewhere have been through the trust review by now.
+    fn merge(global: ProviderConfig, project: ProviderConfig) -> ProviderConfig {
+        ProviderConfig {
+            openai: ProviderEntry::merge(global.openai, project.openai),
+            anthropic: ProviderEntry::merge(global.anthropic, project.anthropic),
+            ollama: ProviderEntry::merge(global.ollama, project.ollama),
+            openrouter: ProviderEntry::merge(global.openrouter, project.openrouter),
+            bedrock: ProviderEntry::merge(global.bedrock, project.bedrock),
+        }
+    }
+}
+
+impl ProviderEntry {
+    /// Field by field, project values winning; headers are merged by name.
+    fn merge(
+        global: Option<ProviderEntry>,
+        project: Option<ProviderEntry>,
+    ) -> Option<ProviderEntry> {
+        let (global, project) = match (global, project) {
+            (Some(global), Some(project)) => (global, project),
+            (global, project) => return project.or(global),
+        };
+        Some(ProviderEntry {
+            api_key: project.api_key.or(global.api_key),
+            base_url: project.base_url.or(global.base_url),
+            model: project.model.or(global.model),
+            region: project.region.or(global.region),
+            profile: project.profile.or(global.profile),
+            organization: project.organization.or(global.organization),
+            project: project.project.or(global.project),
+            headers: global.headers.into_iter().chain(project.headers).collect(),
+        })
+    }
+}
+
+/// Project permissions replace the global ones whole when the project sets
+/// any, and fall back to them otherwise. `read_only` can be turned on by
+/// either file.
+pub(super) fn merge_permissions(
+    project: PermissionConfig,
+    global: PermissionConfig,
+) -> PermissionConfig {
+    let read_only = either(project.read_only, global.read_only);
+    let mut permissions = if project.tools.is_empty()
+        && project.bash_commands.is_empty()
+        && project.git_commands.is_empty()
+        && project.network.is_empty()
+    {
+        global
+    } else {
+        project
+    };
+    permissions.read_only = read_only;
+    permissions
+}
@@ -4,34 +4,99 @@
 //! (e.g. `~/.config/kaze/config.toml` on Linux) and session data under the
 //! XDG data directory (`~/.local/share/kaze/`).
 
+mod bundle;
+mod instructions;
 mod loader;
 mod paths;
+mod pin;
 mod resolve;
+mod trust;
 mod types;
 
+pub use bundle::BundleImport;
+pub use trust::{
+    endpoint_overrides, escalations, template_escalations, EndpointOverride, Escalation,
+};
+#[allow(unused_imports)]
+pub use types::CacheConfig;
 #[allow(unused_imports)]
 pub use types::CompactionConfig;
 pub use types::Config;
 #[allow(unused_imports)]
+pub use types::ContextConfig;
+pub use types::ContextStrategy;
+#[allow(unused_imports)]
+pub use types::GenerationConfig;
+#[allow(unused_imports)]
+pub use types::OutputConfig;
+#[allow(unused_imports)]
+pub use types::PinConfig;
+#[allow(unused_imports)]
+pub use types::PrivacyConfig;
+#[allow(unused_imports)]
+pub use types::PromptConfig;
+#[allow(unused_imports)]
+pub use types::PromptLayer;
+#[allow(unused_imports)]
 pub use types::ProviderConfig;
 #[allow(unused_imports)]
 pub use types::ProviderEntry;
+#[allow(unused_imports)]
+pub use types::RunTemplate;
+#[allow(unused_imports)]
+pub use types::SessionStorage;
+#[allow(unused_imports)]
+pub use types::SessionsConfig;
+#[allow(unused_imports)]
+pub use types::SuggestConfig;
+#[allow(unused_imports)]
+pub use types::TaskConfig;
+#[allow(unused_imports)]
+pub use types::ToolsConfig;
+#[allow(unused_imports)]
+pub use types::WebSearchConfig;
+#[allow(unused_imports)]
+pub use types::WriteFileConfig;
 
 use anyhow::Result;
 
 impl Config {
     /// Load config with precedence: project > global > defaults.
     /// Creates default config file if none exists.
+    ///
+    /// Project permissions that go beyond the global config's, and provider
+    /// endpoints that would get the user's keys, are honored only once the
+    /// user approves them (see the `trust` module).
     pub fn load() -> Result<Self> {
-        let global = Self::load_global()?;
-        let project = Self::load_project()?;
-
-        let mut config = global;
-        if let Some(proj) = project {
-            config = Self::merge(config, proj);
-        }
-
+        let mut config = Self::load_unresolved()?;
         config.resolve_substitutions();
+        config.apply_privacy();
+        config.load_instructions();
+        crate::i18n::init(config.locale.as_deref());
+        if config.accessible() {
+            crate::output::set_accessible(true);
+        }
+        crate::output::set_tool_display_chars(config.tool_args_chars(), config.tool_result_chars());
         Ok(config)
     }
+
+    /// Load config with the same precedence as [`Config::load`], but leave
+    /// `{env:VAR}` placeholders unresolved.
+    ///
+    /// Used when writing config back out (e.g. bundle export) so that
+    /// environment secrets are never baked into files. The project config
+    /// is reviewed as for [`Config::load`], so nothing unapproved in a
+    /// cloned `kaze.toml` is passed on.
+    pub fn load_unresolved() -> Result<Self> {
+        let global = Self::load_global()?;
+        match Self::load_project()? {
+            Some(mut proj) => {
+                if let Some(path) = Self::project_config_path()? {
+                    Self::review_project_config(&global, &mut proj, &path)?;
+                }
+                Ok(Self::merge(global, proj))
+            }
+            None => Ok(global),
+        }
+    }
 }
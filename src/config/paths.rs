@@ -60,4 +60,35 @@ impl Config {
     pub fn config_path() -> Result<PathBuf> {
         Ok(Self::config_dir()?.join(crate::constants::CONFIG_FILENAME))
     }
+
+    /// Returns the project's `kaze.toml`, searching the current directory
+    /// and then each parent up to the git root. `None` if there is none.
+    pub fn project_config_path() -> Result<Option<PathBuf>> {
+        let mut dir = std::env::current_dir()?;
+        loop {
+            let candidate = dir.join(crate::constants::PROJECT_CONFIG_FILENAME);
+            if candidate.exists() {
+                return Ok(Some(candidate));
+            }
+            // Stop at git root or filesystem root
+            if dir.join(".git").exists() || !dir.pop() {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Where a new `kaze.toml` belongs: the enclosing git root, or the
+    /// current directory outside a repository.
+    pub fn project_root_dir() -> Result<PathBuf> {
+        let cwd = std::env::current_dir()?;
+        let mut dir = cwd.clone();
+        loop {
+            if dir.join(".git").exists() {
+                return Ok(dir);
+            }
+            if !dir.pop() {
+                return Ok(cwd);
+            }
+        }
+    }
 }
@@ -0,0 +1,110 @@
+//! Project model pinning (`kaze config pin`).
+//!
+//! The pin lives in a `[pin]` table of the project's `kaze.toml`. It is
+//! rewritten as text rather than by re-serializing the whole file, so
+//! comments and layout elsewhere in `kaze.toml` survive.
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::PathBuf;
+
+use super::types::Config;
+
+impl Config {
+    /// The pinned `(provider, model)`, if the config has a complete pin.
+    pub fn pinned_selection(&self) -> Option<(&str, &str)> {
+        Some((self.pin.provider.as_deref()?, self.pin.model.as_deref()?))
+    }
+
+    /// A warning when the pinned model is missing from kaze's model list
+    /// for its provider. Providers without a fixed list are never flagged.
+    pub fn pin_warning(&self) -> Option<String> {
+        let (provider, model) = self.pinned_selection()?;
+        let known = crate::models::known_models(provider)?;
+        if known.iter().any(|m| m.name == model) {
+            return None;
+        }
+        let since = self
+            .pin
+            .pinned_at
+            .as_deref()
+            .map(|date| format!(" on {}", date))
+            .unwrap_or_default();
+        Some(format!(
+            "Pinned model {}/{} (pinned{}) is no longer available; run `kaze config pin` to update the pin",
+            provider, model, since
+        ))
+    }
+
+    /// Writes a `[pin]` table for `provider`/`model` into the project's
+    /// `kaze.toml`, creating the file at the git root if there is none.
+    /// Returns the file written.
+    pub fn write_pin(provider: &str, model: &str) -> Result<PathBuf> {
+        let path = match Self::project_config_path()? {
+            Some(path) => path,
+            None => Self::project_root_dir()?.join(crate::constants::PROJECT_CONFIG_FILENAME),
+        };
+        let existing = if path.exists() {
+            fs::read_to_string(&path).with_context(|| format!("Failed to read {:?}", path))?
+        } else {
+            String::new()
+        };
+
+        let mut contents = without_pin_table(&existing);
+        if !contents.is_empty() {
+            contents.push('\n');
+        }
+        contents.push_str(&format!(
+            "[pin]\nprovider = {}\nmodel = {}\npinned_at = \"{}\"\nkaze_version = \"{}\"\n",
+            toml::Value::String(provider.to_string()),
+            toml::Value::String(model.to_string()),
+            chrono::Local::now().format("%Y-%m-%d"),
+            env!("CARGO_PKG_VERSION"),
+        ));
+        // Refuse to write a file kaze could no longer load
+        toml::from_str::<Config>(&contents)
+            .with_context(|| format!("Pinning would leave {:?} unparseable", path))?;
+        fs::write(&path, contents).with_context(|| format!("Failed to write {:?}", path))?;
+        Ok(path)
+    }
+
+    /// Removes the `[pin]` table from the project's `kaze.toml`.
+    /// Returns the file changed, or `None` if nothing was pinned.
+    pub fn clear_pin() -> Result<Option<PathBuf>> {
+        let Some(path) = Self::project_config_path()? else {
+            return Ok(None);
+        };
+        let existing =
+            fs::read_to_string(&path).with_context(|| format!("Failed to read {:?}", path))?;
+        let contents = without_pin_table(&existing);
+        if contents.trim_end() == existing.trim_end() {
+            return Ok(None);
+        }
+        fs::write(&path, contents).with_context(|| format!("Failed to write {:?}", path))?;
+        Ok(Some(path))
+    }
+}
+
+/// Drops the `[pin]` table (header through the line before the next table
+/// header) and trailing blank lines.
+fn without_pin_table(toml: &str) -> String {
+    let mut kept = Vec::new();
+    let mut in_pin = false;
+    for line in toml.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            in_pin = trimmed == "[pin]";
+        }
+        if !in_pin {
+            kept.push(line);
+        }
+    }
+    while kept.last().is_some_and(|line| line.trim().is_empty()) {
+        kept.pop();
+    }
+    let mut out = kept.join("\n");
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    out
+}
@@ -1,19 +1,34 @@
 //! Environment variable substitution and API key resolution.
 
-use super::types::{Config, ProviderEntry};
+use std::time::Duration;
+
+use super::types::{default_system_prompt, Config, ContextStrategy, PromptLayer, ProviderEntry};
 
 use crate::constants::{
     COMPACTION_AUTO_DEFAULT, COMPACTION_KEEP_RECENT_DEFAULT, COMPACTION_RESERVED_DEFAULT,
-    COMPACTION_THRESHOLD_DEFAULT,
+    COMPACTION_THRESHOLD_DEFAULT, CONFIRM_ABOVE_TOKENS_DEFAULT, ENVIRONMENT_FACTS_DEFAULT,
+    FILE_REFRESH_TOKENS_DEFAULT, OFFLINE_EXTRAS_DENIED_TOOLS, REPO_MAP_TOKENS_DEFAULT,
+    RESPONSE_CACHE_TTL_DEFAULT_SECS, ROLLING_WINDOW_MAX_RATIO, WRITE_FILE_CONFIRM_BYTES_DEFAULT,
 };
+use crate::permissions::Permission;
 
 impl Config {
     /// Resolve {env:VAR_NAME} patterns in string fields.
     pub(super) fn resolve_substitutions(&mut self) {
         self.model = Self::resolve_str(&self.model);
-        if let Some(ref mut sp) = self.system_prompt {
+        for sp in [
+            &mut self.system_prompt,
+            &mut self.prompt_layers.global,
+            &mut self.prompt_layers.project,
+        ]
+        .into_iter()
+        .flatten()
+        {
             *sp = Self::resolve_str(sp);
         }
+        for text in self.prompt.modes.values_mut() {
+            *text = Self::resolve_str(text);
+        }
         if let Some(ref mut dp) = self.default_provider {
             *dp = Self::resolve_str(dp);
         }
@@ -21,9 +36,18 @@ impl Config {
         Self::resolve_provider_entry(&mut self.provider.anthropic);
         Self::resolve_provider_entry(&mut self.provider.ollama);
         Self::resolve_provider_entry(&mut self.provider.openrouter);
+        Self::resolve_provider_entry(&mut self.provider.bedrock);
+        let search = &mut self.tools.web_search;
+        for value in [&mut search.api_key, &mut search.base_url]
+            .into_iter()
+            .flatten()
+        {
+            *value = Self::resolve_str(value);
+        }
     }
 
-    /// Resolves `{env:VAR}` patterns in a single provider entry's `api_key` and `base_url`.
+    /// Resolves `{env:VAR}` patterns in a single provider entry's string fields
+    /// and header values.
     fn resolve_provider_entry(entry: &mut Option<ProviderEntry>) {
         if let Some(ref mut e) = entry {
             if let Some(ref mut key) = e.api_key {
@@ -32,6 +56,18 @@ impl Config {
             if let Some(ref mut url) = e.base_url {
                 *url = Self::resolve_str(url);
             }
+            if let Some(ref mut region) = e.region {
+                *region = Self::resolve_str(region);
+            }
+            if let Some(ref mut profile) = e.profile {
+                *profile = Self::resolve_str(profile);
+            }
+            for value in [&mut e.organization, &mut e.project].into_iter().flatten() {
+                *value = Self::resolve_str(value);
+            }
+            for value in e.headers.values_mut() {
+                *value = Self::resolve_str(value);
+            }
         }
     }
 
@@ -96,6 +132,56 @@ impl Config {
         }
     }
 
+    /// The non-empty system prompt layers, in the configured order.
+    ///
+    /// The global layer falls back to the built-in prompt; the mode layer
+    /// is present only when `[prompt] mode` names an entry of
+    /// `[prompt.modes]`.
+    pub fn prompt_layers(&self) -> Vec<(PromptLayer, String)> {
+        let order = self
+            .prompt
+            .order
+            .clone()
+            .unwrap_or_else(|| PromptLayer::DEFAULT_ORDER.to_vec());
+        order
+            .into_iter()
+            .filter_map(|layer| {
+                let text = match layer {
+                    PromptLayer::Global => self
+                        .prompt_layers
+                        .global
+                        .clone()
+                        .or_else(default_system_prompt),
+                    PromptLayer::Project => self.prompt_layers.project.clone(),
+                    PromptLayer::Instructions => self.prompt_layers.instructions.clone(),
+                    PromptLayer::Mode => self
+                        .prompt
+                        .mode
+                        .as_ref()
+                        .and_then(|mode| self.prompt.modes.get(mode))
+                        .cloned(),
+                }?;
+                (!text.trim().is_empty()).then_some((layer, text))
+            })
+            .collect()
+    }
+
+    /// The composed system prompt sent as the preamble: every layer from
+    /// [`prompt_layers`](Self::prompt_layers) joined by blank lines.
+    pub fn system_preamble(&self) -> Option<String> {
+        let layers = self.prompt_layers();
+        if layers.is_empty() {
+            return None;
+        }
+        Some(
+            layers
+                .into_iter()
+                .map(|(_, text)| text)
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+        )
+    }
+
     /// Whether automatic context compaction is enabled.
     pub fn compaction_auto_enabled(&self) -> bool {
         self.compaction.auto.unwrap_or(COMPACTION_AUTO_DEFAULT)
@@ -121,4 +207,150 @@ impl Config {
             .reserved
             .unwrap_or(COMPACTION_RESERVED_DEFAULT)
     }
+
+    /// How chat keeps the conversation within the context window.
+    pub fn context_strategy(&self) -> ContextStrategy {
+        self.context.strategy.unwrap_or_default()
+    }
+
+    /// Tokens of recent conversation the rolling strategy keeps verbatim
+    /// for `model`: `window_tokens`, or half the context window, and never
+    /// more than [`ROLLING_WINDOW_MAX_RATIO`] of it.
+    pub fn rolling_window_tokens(&self, model: &str) -> usize {
+        let limit = crate::tokens::context_window_size(model);
+        let max = (limit as f64 * ROLLING_WINDOW_MAX_RATIO) as usize;
+        self.context.window_tokens.unwrap_or(limit / 2).min(max)
+    }
+
+    /// Estimated prompt size above which chat confirms a turn, or `None`
+    /// when `confirm_above_tokens` is 0.
+    pub fn confirm_above_tokens(&self) -> Option<usize> {
+        Some(
+            self.context
+                .confirm_above_tokens
+                .unwrap_or(CONFIRM_ABOVE_TOKENS_DEFAULT),
+        )
+        .filter(|&tokens| tokens > 0)
+    }
+
+    /// Token budget for refreshing changed files at turn start, or `None`
+    /// when `refresh_tokens` is 0.
+    pub fn file_refresh_tokens(&self) -> Option<usize> {
+        Some(
+            self.context
+                .refresh_tokens
+                .unwrap_or(FILE_REFRESH_TOKENS_DEFAULT),
+        )
+        .filter(|&tokens| tokens > 0)
+    }
+
+    /// Token budget for the repo map chat sends, or `None` when
+    /// `repo_map_tokens` is 0.
+    pub fn repo_map_tokens(&self) -> Option<usize> {
+        Some(
+            self.context
+                .repo_map_tokens
+                .unwrap_or(REPO_MAP_TOKENS_DEFAULT),
+        )
+        .filter(|&tokens| tokens > 0)
+    }
+
+    /// Whether chat sends the model an environment facts block.
+    pub fn environment_facts(&self) -> bool {
+        self.context
+            .environment
+            .unwrap_or(ENVIRONMENT_FACTS_DEFAULT)
+    }
+
+    /// The sampling temperature to request: `temperature`, or 0 when only
+    /// a `seed` is set, so seeded runs are as repeatable as they can be.
+    pub fn temperature(&self) -> Option<f64> {
+        self.generation
+            .temperature
+            .or(self.generation.seed.map(|_| 0.0))
+    }
+
+    /// Applies `--seed` and `--temperature`, which win over `[generation]`.
+    pub fn override_generation(&mut self, seed: Option<u64>, temperature: Option<f64>) {
+        if seed.is_some() {
+            self.generation.seed = seed;
+        }
+        if temperature.is_some() {
+            self.generation.temperature = temperature;
+        }
+    }
+
+    /// Whether chat points out questions that were asked before.
+    pub fn duplicate_hints(&self) -> bool {
+        self.sessions.duplicate_hints.unwrap_or(true)
+    }
+
+    /// Whether `kaze ask` responses are cached.
+    pub fn cache_enabled(&self) -> bool {
+        self.cache.enabled.unwrap_or(false)
+    }
+
+    /// How long cached `kaze ask` responses stay valid, in seconds.
+    pub fn cache_ttl_secs(&self) -> u64 {
+        self.cache
+            .ttl_secs
+            .unwrap_or(RESPONSE_CACHE_TTL_DEFAULT_SECS)
+    }
+
+    /// The per-turn time limit: `flag` (`--turn-timeout`) when given,
+    /// otherwise `turn_timeout_secs`. Zero in either place means none.
+    pub fn turn_timeout(&self, flag: Option<u64>) -> Option<Duration> {
+        flag.or(self.turn_timeout_secs)
+            .filter(|&secs| secs > 0)
+            .map(Duration::from_secs)
+    }
+
+    /// Whether screen-reader friendly output is enabled.
+    pub fn accessible(&self) -> bool {
+        self.output.accessible.unwrap_or(false)
+    }
+
+    /// Whether `privacy.offline_extras` limits kaze to the provider
+    /// endpoints.
+    pub fn offline_extras(&self) -> bool {
+        self.privacy.offline_extras.unwrap_or(false)
+    }
+
+    /// Size above which write_file asks first, or `None` when
+    /// `confirm_above_bytes` is 0.
+    pub fn write_file_confirm_bytes(&self) -> Option<usize> {
+        Some(
+            self.tools
+                .write_file
+                .confirm_above_bytes
+                .unwrap_or(WRITE_FILE_CONFIRM_BYTES_DEFAULT),
+        )
+        .filter(|&bytes| bytes > 0)
+    }
+
+    /// Denies the network tools when `privacy.offline_extras` is set,
+    /// whatever either config file says about them.
+    pub(super) fn apply_privacy(&mut self) {
+        if self.offline_extras() {
+            for tool in OFFLINE_EXTRAS_DENIED_TOOLS {
+                self.permissions
+                    .tools
+                    .insert(tool.to_string(), Permission::Deny);
+            }
+        }
+    }
+
+    /// Characters of tool arguments shown while a tool runs; 0 is no limit.
+    pub fn tool_args_chars(&self) -> usize {
+        self.output
+            .tool_args_chars
+            .unwrap_or(crate::constants::TOOL_ARGS_DISPLAY_CHARS)
+    }
+
+    /// Characters of a tool result shown when it finishes; 0 is no limit.
+    pub fn tool_result_chars(&self) -> usize {
+        self.output
+            .tool_result_chars
+            .unwrap_or(crate::constants::TOOL_RESULT_DISPLAY_CHARS)
+    }
 }
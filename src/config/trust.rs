@@ -0,0 +1,640 @@
+//! Confirmation for project configs that loosen permissions or redirect
+//! provider requests.
+//!
+//! A `kaze.toml` arrives with whatever repository was cloned, so one that
+//! leaves a permission looser than the global config does (`bash =
+//! "allow"`, say, or a `[permissions]` table that drops the global
+//! `git push` deny) is not honored until the user approves it. The same
+//! goes for a `[templates]` entry that turns on `auto_approve`, a provider
+//! `base_url` that points the user's credentials at another server, and an
+//! `api_key` or header that reads the user's environment.
+//! The approval is remembered per file in `trusted_permissions.json` in the
+//! data directory and covers exactly the entries that were shown, so a later
+//! change to the file asks again. Without a terminal to ask on, the entries
+//! fall back to the global config's values with a warning; `kaze config
+//! trust` approves them ahead of time.
+
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use super::types::{Config, ProviderConfig, ProviderEntry, RunTemplate};
+use crate::i18n::{t, tf};
+use crate::permissions::{default_permission, Permission, PermissionConfig};
+
+/// One permission a project config loosens.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Escalation {
+    /// Where it is set, e.g. `tools.bash` or `bash_commands.cargo *`.
+    pub key: String,
+    /// What the global config would have decided.
+    pub global: Permission,
+    /// What the merged config decides.
+    pub project: Permission,
+}
+
+fn level_name(level: &Permission) -> &'static str {
+    match level {
+        Permission::Allow => "allow",
+        Permission::Ask => "ask",
+        Permission::Deny => "deny",
+    }
+}
+
+impl std::fmt::Display for Escalation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} → {}",
+            self.key,
+            level_name(&self.global),
+            level_name(&self.project)
+        )
+    }
+}
+
+/// How permissive a level is, for telling whether a change loosens it.
+fn rank(level: &Permission) -> u8 {
+    match level {
+        Permission::Deny => 0,
+        Permission::Ask => 1,
+        Permission::Allow => 2,
+    }
+}
+
+/// The level of a tool, as the permission manager falls back to it.
+fn tool_level(config: &PermissionConfig, tool: &str) -> Permission {
+    config
+        .tools
+        .get(tool)
+        .cloned()
+        .unwrap_or_else(|| default_permission(tool))
+}
+
+/// The level of a git subcommand, following the git tool's safe defaults:
+/// read-only subcommands are allowed and pushes denied.
+fn git_level(config: &PermissionConfig, key: &str) -> Permission {
+    if let Some(perm) = config.git_commands.get(key) {
+        return perm.clone();
+    }
+    let tool = tool_level(config, "git");
+    if tool == Permission::Deny {
+        return Permission::Deny;
+    }
+    if crate::tools::git_tool::GIT_READ_ONLY.contains(&key) {
+        return Permission::Allow;
+    }
+    if key.starts_with("push") {
+        return Permission::Deny;
+    }
+    tool
+}
+
+/// The level `config` gives the entry `name` of `table`, falling back to
+/// the tool's level when the table doesn't list it.
+fn level(config: &PermissionConfig, table: &str, name: &str) -> Permission {
+    let (map, tool) = match table {
+        "tools" => return tool_level(config, name),
+        "git_commands" => return git_level(config, name),
+        "bash_commands" => (&config.bash_commands, "bash"),
+        _ => (&config.network, "web_fetch"),
+    };
+    map.get(name)
+        .cloned()
+        .unwrap_or_else(|| tool_level(config, tool))
+}
+
+fn tables(config: &PermissionConfig) -> [(&'static str, &HashMap<String, Permission>); 4] {
+    [
+        ("tools", &config.tools),
+        ("bash_commands", &config.bash_commands),
+        ("git_commands", &config.git_commands),
+        ("network", &config.network),
+    ]
+}
+
+/// Every entry the merged config leaves looser than `global` does, sorted.
+///
+/// `Config::merge` takes the project's permissions whole when it has any,
+/// so besides the project's own entries this catches global denies and
+/// asks the project leaves out, which then fall back to a looser default.
+pub fn escalations(global: &PermissionConfig, project: &PermissionConfig) -> Vec<Escalation> {
+    let merged = super::loader::merge_permissions(project.clone(), global.clone());
+    let mut keys = BTreeSet::new();
+    for config in [global, &merged] {
+        for (table, map) in tables(config) {
+            keys.extend(map.keys().map(|name| (table, name.clone())));
+        }
+    }
+    keys.into_iter()
+        .filter_map(|(table, name)| {
+            let global_level = level(global, table, &name);
+            let merged_level = level(&merged, table, &name);
+            (rank(&merged_level) > rank(&global_level)).then(|| Escalation {
+                key: format!("{}.{}", table, name),
+                global: global_level,
+                project: merged_level,
+            })
+        })
+        .collect()
+}
+
+/// The project's `[templates]` entries that turn on `auto_approve`, which
+/// would let a scheduled run skip every "ask" prompt. A project template
+/// replaces a global one of the same name whole, so each counts.
+pub fn template_escalations(templates: &BTreeMap<String, RunTemplate>) -> Vec<Escalation> {
+    templates
+        .iter()
+        .filter(|(_, template)| template.auto_approve == Some(true))
+        .map(|(name, _)| Escalation {
+            key: format!("templates.{}.auto_approve", name),
+            global: Permission::Ask,
+            project: Permission::Allow,
+        })
+        .collect()
+}
+
+/// Sets each escalated entry in `project` to the global level, adding the
+/// ones the project left out.
+fn revert(project: &mut Config, escalations: &[Escalation]) {
+    for e in escalations {
+        let (table, name) = e.key.split_once('.').unwrap_or(("", ""));
+        let permissions = &mut project.permissions;
+        let map = match table {
+            "tools" => &mut permissions.tools,
+            "bash_commands" => &mut permissions.bash_commands,
+            "git_commands" => &mut permissions.git_commands,
+            "network" => &mut permissions.network,
+            "templates" => {
+                let name = name.strip_suffix(".auto_approve").unwrap_or(name);
+                if let Some(template) = project.templates.get_mut(name) {
+                    template.auto_approve = None;
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        map.insert(name.to_string(), e.global.clone());
+    }
+}
+
+/// A provider setting in a project config that would send the user's
+/// credentials, or values read from their environment, somewhere the
+/// global config doesn't.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EndpointOverride {
+    pub provider: &'static str,
+    /// `base_url`, `api_key`, `organization`, `project`, or `headers.<name>`.
+    pub field: String,
+    /// The value as the project wrote it, `{env:VAR}` unresolved.
+    pub value: String,
+}
+
+impl std::fmt::Display for EndpointOverride {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "provider.{}.{} = {}",
+            self.provider, self.field, self.value
+        )
+    }
+}
+
+fn provider_entries(config: &ProviderConfig) -> [(&'static str, Option<&ProviderEntry>); 5] {
+    [
+        ("anthropic", config.anthropic.as_ref()),
+        ("openai", config.openai.as_ref()),
+        ("openrouter", config.openrouter.as_ref()),
+        ("ollama", config.ollama.as_ref()),
+        ("bedrock", config.bedrock.as_ref()),
+    ]
+}
+
+fn reads_env(value: &str) -> bool {
+    value.contains("{env:")
+}
+
+/// Every provider setting in `project` that needs approval.
+///
+/// A `base_url` the global config doesn't set counts unless the requests
+/// would carry nothing of the user's: an `*_API_KEY` variable wins over
+/// any key the project sets, Bedrock signs with the user's AWS
+/// credentials, and Ollama sends only the global entry's headers. Keys,
+/// attribution IDs and header values count when they read `{env:VAR}`,
+/// wherever the requests go.
+pub fn endpoint_overrides(
+    global: &ProviderConfig,
+    project: &ProviderConfig,
+) -> Vec<EndpointOverride> {
+    let mut found = Vec::new();
+    for ((provider, global), (_, project)) in provider_entries(global)
+        .into_iter()
+        .zip(provider_entries(project))
+    {
+        let Some(project) = project else {
+            continue;
+        };
+        let mut flag = |field: String, value: &str| {
+            found.push(EndpointOverride {
+                provider,
+                field,
+                value: value.to_string(),
+            })
+        };
+        if let Some(url) = project.base_url.as_deref() {
+            let moved = global.and_then(|g| g.base_url.as_deref()) != Some(url);
+            let credentials = provider != "ollama" || global.is_some_and(|g| !g.headers.is_empty());
+            if moved && credentials {
+                flag("base_url".to_string(), url);
+            }
+        }
+        let fields = [
+            (
+                "api_key",
+                &project.api_key,
+                global.and_then(|g| g.api_key.as_ref()),
+            ),
+            (
+                "organization",
+                &project.organization,
+                global.and_then(|g| g.organization.as_ref()),
+            ),
+            (
+                "project",
+                &project.project,
+                global.and_then(|g| g.project.as_ref()),
+            ),
+        ];
+        for (field, value, global_value) in fields {
+            if let Some(value) = value.as_ref().filter(|v| reads_env(v)) {
+                if global_value != Some(value) {
+                    flag(field.to_string(), value);
+                }
+            }
+        }
+        for (name, value) in project.headers.iter().filter(|(_, v)| reads_env(v)) {
+            if global.and_then(|g| g.headers.get(name)) != Some(value) {
+                flag(format!("headers.{}", name), value);
+            }
+        }
+    }
+    found
+}
+
+/// Drops each overriding setting from `project`, so the merge falls back
+/// to the global entry's.
+fn revert_endpoints(project: &mut ProviderConfig, overrides: &[EndpointOverride]) {
+    for o in overrides {
+        let entry = match o.provider {
+            "anthropic" => &mut project.anthropic,
+            "openai" => &mut project.openai,
+            "openrouter" => &mut project.openrouter,
+            "ollama" => &mut project.ollama,
+            "bedrock" => &mut project.bedrock,
+            _ => continue,
+        };
+        let Some(entry) = entry else {
+            continue;
+        };
+        match o.field.as_str() {
+            "base_url" => entry.base_url = None,
+            "api_key" => entry.api_key = None,
+            "organization" => entry.organization = None,
+            "project" => entry.project = None,
+            field => {
+                if let Some(name) = field.strip_prefix("headers.") {
+                    entry.headers.remove(name);
+                }
+            }
+        }
+    }
+}
+
+/// Everything `project` needs approved.
+fn review_items(global: &Config, project: &Config) -> (Vec<Escalation>, Vec<EndpointOverride>) {
+    let mut found = escalations(&global.permissions, &project.permissions);
+    found.extend(template_escalations(&project.templates));
+    (
+        found,
+        endpoint_overrides(&global.provider, &project.provider),
+    )
+}
+
+/// The keys to remember an approval by. Endpoint keys include the value,
+/// so pointing the provider somewhere else asks again.
+fn approval_keys(found: &[Escalation], endpoints: &[EndpointOverride]) -> Vec<String> {
+    found
+        .iter()
+        .map(|e| e.key.clone())
+        .chain(endpoints.iter().map(ToString::to_string))
+        .collect()
+}
+
+fn display_lines(found: &[Escalation], endpoints: &[EndpointOverride]) -> Vec<String> {
+    found
+        .iter()
+        .map(ToString::to_string)
+        .chain(endpoints.iter().map(ToString::to_string))
+        .collect()
+}
+
+/// Everything `project` would need approved on top of `global`, as the
+/// review shows it; also used to review a bundle before it is imported.
+pub(super) fn review_lines(global: &Config, project: &Config) -> Vec<String> {
+    let (found, endpoints) = review_items(global, project);
+    display_lines(&found, &endpoints)
+}
+
+/// Approved escalations, by project config path.
+type TrustStore = BTreeMap<String, Vec<String>>;
+
+fn store_path() -> Result<PathBuf> {
+    Ok(Config::data_dir()?.join("trusted_permissions.json"))
+}
+
+fn load_store() -> Result<TrustStore> {
+    let path = store_path()?;
+    if !path.exists() {
+        return Ok(TrustStore::new());
+    }
+    let text = fs::read_to_string(&path).with_context(|| format!("Failed to read {:?}", path))?;
+    serde_json::from_str(&text).with_context(|| format!("Failed to parse {:?}", path))
+}
+
+fn save_store(store: &TrustStore) -> Result<()> {
+    let path = store_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, serde_json::to_string_pretty(store)?)
+        .with_context(|| format!("Failed to write {:?}", path))
+}
+
+fn store_key(path: &Path) -> String {
+    fs::canonicalize(path)
+        .unwrap_or_else(|_| path.to_path_buf())
+        .display()
+        .to_string()
+}
+
+/// Whether every key was approved for `path` before.
+fn is_trusted(path: &Path, keys: &[String]) -> Result<bool> {
+    let store = load_store()?;
+    let Some(approved) = store.get(&store_key(path)) else {
+        return Ok(false);
+    };
+    Ok(keys.iter().all(|key| approved.contains(key)))
+}
+
+/// Remembers that the keys in `path` are approved.
+fn trust(path: &Path, keys: &[String]) -> Result<()> {
+    let mut store = load_store()?;
+    store.insert(store_key(path), keys.to_vec());
+    save_store(&store)
+}
+
+impl Config {
+    /// Decides whether `project`'s permissions and templates may go beyond
+    /// `global`'s and its provider settings may take the user's credentials
+    /// elsewhere, asking the user the first time, and reverts the entries
+    /// if not. A decision is made once per file and process.
+    pub(super) fn review_project_config(
+        global: &Config,
+        project: &mut Config,
+        path: &Path,
+    ) -> Result<()> {
+        static DECIDED: OnceLock<Mutex<HashMap<PathBuf, bool>>> = OnceLock::new();
+
+        let (found, endpoints) = review_items(global, project);
+        if found.is_empty() && endpoints.is_empty() {
+            return Ok(());
+        }
+        let decided = DECIDED.get_or_init(Default::default);
+        let earlier = decided
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(path)
+            .copied();
+        let honored = match earlier {
+            Some(honored) => honored,
+            None => {
+                let keys = approval_keys(&found, &endpoints);
+                let lines = display_lines(&found, &endpoints);
+                let honored = is_trusted(path, &keys)? || confirm(path, &lines, &keys)?;
+                decided
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .insert(path.to_path_buf(), honored);
+                honored
+            }
+        };
+        if !honored {
+            revert(project, &found);
+            revert_endpoints(&mut project.provider, &endpoints);
+        }
+        Ok(())
+    }
+
+    /// Approves the project config's permission escalations and endpoint
+    /// overrides without a prompt (`kaze config trust`). Returns them as
+    /// shown in the review, with the file's path; the list is empty when
+    /// there is nothing to approve.
+    pub fn trust_project_config() -> Result<Option<(PathBuf, Vec<String>)>> {
+        let Some(path) = Self::project_config_path()? else {
+            return Ok(None);
+        };
+        let global = Self::load_global()?;
+        let project = Self::load_project()?.unwrap_or_default();
+        let (found, endpoints) = review_items(&global, &project);
+        if !found.is_empty() || !endpoints.is_empty() {
+            trust(&path, &approval_keys(&found, &endpoints))?;
+        }
+        Ok(Some((path, display_lines(&found, &endpoints))))
+    }
+}
+
+/// Shows the entries and asks whether to honor them; without a terminal,
+/// warns that they are ignored instead.
+fn confirm(path: &Path, lines: &[String], keys: &[String]) -> Result<bool> {
+    let list: Vec<String> = lines.iter().map(|line| format!("  {}", line)).collect();
+    eprintln!(
+        "\n{}\n{}",
+        tf("trust.header", &[("path", &path.display().to_string())]),
+        list.join("\n")
+    );
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        eprintln!("{}\n", t("trust.ignored"));
+        return Ok(false);
+    }
+    eprint!("{}: ", t("trust.prompt"));
+    io::stderr().flush()?;
+    let mut response = String::new();
+    io::stdin().read_line(&mut response)?;
+    eprintln!();
+    if matches!(response.trim().to_lowercase().as_str(), "y" | "yes") {
+        trust(path, keys)?;
+        return Ok(true);
+    }
+    eprintln!("{}\n", t("trust.ignored"));
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A permission config with the given `(table, name, level)` entries.
+    fn permissions(entries: &[(&str, &str, Permission)]) -> PermissionConfig {
+        let mut config = PermissionConfig::default();
+        for (table, name, level) in entries {
+            let map = match *table {
+                "tools" => &mut config.tools,
+                "bash_commands" => &mut config.bash_commands,
+                "git_commands" => &mut config.git_commands,
+                _ => &mut config.network,
+            };
+            map.insert(name.to_string(), level.clone());
+        }
+        config
+    }
+
+    #[test]
+    fn test_escalations_flag_added_allow() {
+        let global = permissions(&[("tools", "bash", Permission::Ask)]);
+        let project = permissions(&[
+            ("tools", "bash", Permission::Ask),
+            ("bash_commands", "cargo *", Permission::Allow),
+        ]);
+        let found = escalations(&global, &project);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].to_string(), "bash_commands.cargo *: ask → allow");
+    }
+
+    #[test]
+    fn test_escalations_flag_dropped_global_deny() {
+        let global = permissions(&[
+            ("tools", "bash", Permission::Allow),
+            ("tools", "git", Permission::Allow),
+            ("bash_commands", "rm -rf*", Permission::Deny),
+            ("git_commands", "push", Permission::Allow),
+        ]);
+        // The project's table replaces the global one, losing its rm deny
+        let project = permissions(&[("tools", "bash", Permission::Allow)]);
+        let keys: Vec<String> = escalations(&global, &project)
+            .into_iter()
+            .map(|e| e.to_string())
+            .collect();
+        // tools.git and the push allow get stricter, which needs no approval
+        assert_eq!(keys, ["bash_commands.rm -rf*: deny → allow"]);
+    }
+
+    #[test]
+    fn test_escalations_flag_changed_tool_default() {
+        let global = permissions(&[("tools", "web_fetch", Permission::Deny)]);
+        let project = permissions(&[("tools", "web_fetch", Permission::Ask)]);
+        let found = escalations(&global, &project);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].to_string(), "tools.web_fetch: deny → ask");
+        // Tightening is never an escalation
+        assert!(escalations(&project, &global).is_empty());
+    }
+
+    #[test]
+    fn test_template_auto_approve_needs_review() {
+        let templates: BTreeMap<String, RunTemplate> = toml::from_str(
+            r#"
+[nightly]
+task = "Fix the lints"
+auto_approve = true
+
+[docs]
+task = "Update the docs"
+auto_approve = false
+"#,
+        )
+        .unwrap();
+        let found: Vec<String> = template_escalations(&templates)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(found, ["templates.nightly.auto_approve: ask → allow"]);
+    }
+
+    fn providers(text: &str) -> ProviderConfig {
+        toml::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn test_endpoint_overrides_flag_moved_keyed_endpoint() {
+        let global = providers(
+            r#"
+[openai]
+api_key = "{env:OPENAI_API_KEY}"
+
+[ollama]
+base_url = "http://localhost:11434"
+"#,
+        );
+        let project = providers(
+            r#"
+[openai]
+base_url = "https://proxy.example.com"
+api_key = "sk-project"
+
+[ollama]
+base_url = "http://localhost:8080"
+"#,
+        );
+        let found: Vec<String> = endpoint_overrides(&global, &project)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        // The env key wins over the project's own, so the move needs approval;
+        // a keyless Ollama without global headers sends nothing of the user's
+        assert_eq!(
+            found,
+            ["provider.openai.base_url = https://proxy.example.com"]
+        );
+    }
+
+    #[test]
+    fn test_endpoint_overrides_flag_env_reads() {
+        let global = providers(
+            r#"
+[ollama]
+headers = { Authorization = "Bearer {env:OLLAMA_TOKEN}" }
+"#,
+        );
+        let project = providers(
+            r#"
+[ollama]
+base_url = "http://gpu.example.com:11434"
+
+[anthropic]
+api_key = "{env:GITHUB_TOKEN}"
+headers = { X-Team = "kaze", X-Trace = "{env:HOME}" }
+
+[bedrock]
+base_url = "https://bedrock.example.com"
+"#,
+        );
+        let found: Vec<String> = endpoint_overrides(&global, &project)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            found,
+            [
+                "provider.anthropic.api_key = {env:GITHUB_TOKEN}",
+                "provider.anthropic.headers.X-Trace = {env:HOME}",
+                "provider.ollama.base_url = http://gpu.example.com:11434",
+                "provider.bedrock.base_url = https://bedrock.example.com",
+            ]
+        );
+        // Repeating the global config's own values needs no approval
+        assert!(endpoint_overrides(&global, &global).is_empty());
+    }
+}
@@ -2,6 +2,7 @@
 
 use crate::permissions::PermissionConfig;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Root configuration for kaze, deserialized from `config.toml`.
 ///
@@ -18,15 +19,68 @@ pub struct Config {
     /// Default provider name (e.g., "anthropic", "openai").
     #[serde(default)]
     pub default_provider: Option<String>,
-    /// Optional system prompt prepended to all conversations.
-    #[serde(default = "default_system_prompt")]
+    /// System prompt for this config file's layer. The global config's
+    /// prompt defaults to the built-in one; a project's is added on top.
+    /// See [`Config::system_preamble`] for the composed result.
+    #[serde(default)]
     pub system_prompt: Option<String>,
+    /// How the system prompt layers are combined.
+    #[serde(default)]
+    pub prompt: PromptConfig,
     /// Context compaction settings.
     #[serde(default)]
     pub compaction: CompactionConfig,
+    /// How chat keeps the conversation within the context window.
+    #[serde(default)]
+    pub context: ContextConfig,
+    /// Sampling settings sent with every request.
+    #[serde(default)]
+    pub generation: GenerationConfig,
     /// Permission settings for tool execution.
     #[serde(default)]
     pub permissions: PermissionConfig,
+    /// Response cache settings for `kaze ask`.
+    #[serde(default)]
+    pub cache: CacheConfig,
+    /// Terminal output settings.
+    #[serde(default)]
+    pub output: OutputConfig,
+    /// Settings for individual built-in tools.
+    #[serde(default)]
+    pub tools: ToolsConfig,
+    /// `kaze suggest` settings.
+    #[serde(default)]
+    pub suggest: SuggestConfig,
+    /// Session retention (`kaze session prune`).
+    #[serde(default)]
+    pub sessions: SessionsConfig,
+    /// UI language (e.g. `"de"`), looked up as `locales/<locale>.toml` in
+    /// the config directory. Unset uses `KAZE_LANG` or the system locale.
+    #[serde(default)]
+    pub locale: Option<String>,
+    /// Model to switch to when the provider can't be reached at startup,
+    /// as `provider/model` (e.g. `"ollama/llama3"`).
+    #[serde(default)]
+    pub offline_fallback: Option<String>,
+    /// Wall-clock limit for a single turn, in seconds, after which the
+    /// agent loop is cancelled. Unset or 0 means no limit.
+    #[serde(default)]
+    pub turn_timeout_secs: Option<u64>,
+    /// Network guarantees for restricted environments.
+    #[serde(default)]
+    pub privacy: PrivacyConfig,
+    /// Provider and model pinned by `kaze config pin`, used instead of
+    /// `default_provider` and `model` unless a CLI flag overrides it.
+    #[serde(default)]
+    pub pin: PinConfig,
+    /// Named `kaze run` tasks, such as a nightly dependency audit, run with
+    /// `kaze run --template <name>` and scheduled by `kaze schedule`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub templates: BTreeMap<String, RunTemplate>,
+    /// The global and project `system_prompt` values, kept apart by the
+    /// loader so they can be layered instead of one replacing the other.
+    #[serde(skip)]
+    pub(crate) prompt_layers: PromptLayers,
 }
 
 /// Returns the default model identifier (`"claude-sonnet-4-5"`).
@@ -38,9 +92,8 @@ pub(super) fn default_model() -> String {
 
 /// Returns the default system prompt for new conversations.
 ///
-/// Used by serde's `#[serde(default)]` attribute during deserialization
-/// so configs without an explicit `system_prompt` still get a sensible default.
-fn default_system_prompt() -> Option<String> {
+/// Used as the global layer when the global config sets no `system_prompt`.
+pub(super) fn default_system_prompt() -> Option<String> {
     Some(crate::constants::DEFAULT_SYSTEM_PROMPT.to_string())
 }
 
@@ -58,12 +111,14 @@ pub struct ProviderConfig {
     pub ollama: Option<ProviderEntry>,
     /// Configuration for the OpenRouter API provider.
     pub openrouter: Option<ProviderEntry>,
+    /// Configuration for AWS Bedrock (`region` and `profile`).
+    pub bedrock: Option<ProviderEntry>,
 }
 
 /// Connection details for a single LLM provider.
 ///
-/// Allows overriding the API key, endpoint URL, and model on a
-/// per-provider basis.
+/// Allows overriding the API key, endpoint URL, model, and extra request
+/// headers on a per-provider basis.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ProviderEntry {
     /// API key for authentication. Can also be set via environment variables.
@@ -72,6 +127,26 @@ pub struct ProviderEntry {
     pub base_url: Option<String>,
     /// Model identifier to use with this provider, overriding the global default.
     pub model: Option<String>,
+    /// AWS region (Bedrock only). Falls back to `AWS_REGION` and the profile.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    /// AWS shared-config profile name (Bedrock only). Falls back to `AWS_PROFILE`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// Organization ID for usage attribution (OpenAI only, sent as
+    /// `OpenAI-Organization`). Falls back to `OPENAI_ORG_ID`. Anthropic
+    /// attributes usage to the API key's workspace and has no such header.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub organization: Option<String>,
+    /// Project ID for usage attribution (OpenAI only, sent as
+    /// `OpenAI-Project`). Falls back to `OPENAI_PROJECT_ID`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    /// Extra HTTP headers sent with every request to this provider, e.g.
+    /// tenant or tracing headers an API gateway requires. Values support
+    /// `{env:VAR}`. Not used by Bedrock, which signs requests itself.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
 }
 
 /// Configuration for LLM-based context compaction.
@@ -90,15 +165,303 @@ pub struct CompactionConfig {
     pub reserved: Option<usize>,
 }
 
+/// How chat keeps a long conversation within the model's context window.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ContextStrategy {
+    /// Summarize old messages once usage nears the limit (see `[compaction]`).
+    #[default]
+    Compact,
+    /// Keep the last `window_tokens` verbatim and fold older messages into a
+    /// summary after every turn. Suits small local models, where waiting for
+    /// the window to fill leaves too little room to summarize well.
+    Rolling,
+}
+
+/// Context window settings (`[context]`).
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct ContextConfig {
+    /// `"compact"` (the default) or `"rolling"`.
+    pub strategy: Option<ContextStrategy>,
+    /// Rolling strategy: tokens of recent conversation kept verbatim.
+    /// Defaults to half the model's context window.
+    pub window_tokens: Option<usize>,
+    /// Ask before sending a chat turn whose prompt is estimated above this
+    /// many tokens, showing the estimate and its cost. 0 never asks.
+    pub confirm_above_tokens: Option<usize>,
+    /// Budget for the diffs of files edited outside the conversation that
+    /// chat adds at the start of a turn. 0 turns refreshes off.
+    pub refresh_tokens: Option<usize>,
+    /// Tell the model the OS, shell, toolchain versions, and git state
+    /// with every chat request. On by default.
+    pub environment: Option<bool>,
+    /// Budget for a map of the project's source files and the names each
+    /// defines, sent with every chat request. 0 (the default) leaves it out.
+    pub repo_map_tokens: Option<usize>,
+}
+
+/// Sampling settings (`[generation]`).
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct GenerationConfig {
+    /// Sampling temperature. Unset leaves the provider's default, unless
+    /// `seed` is set, which implies 0.
+    pub temperature: Option<f64>,
+    /// Sampling seed, for reproducible runs with providers that support
+    /// one (currently OpenRouter). Others get only the temperature.
+    pub seed: Option<u64>,
+}
+
+/// A source of system prompt text, in the order given by `[prompt] order`.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PromptLayer {
+    /// `system_prompt` from the global config (or the built-in default).
+    Global,
+    /// `system_prompt` from the project's `kaze.toml`.
+    Project,
+    /// `KAZE.md` or `AGENTS.md` files from the git root down to the
+    /// current directory.
+    Instructions,
+    /// The entry of `[prompt.modes]` selected by `[prompt] mode`.
+    Mode,
+}
+
+impl PromptLayer {
+    /// Layer order used when `[prompt] order` is not set.
+    pub const DEFAULT_ORDER: [PromptLayer; 4] = [
+        PromptLayer::Global,
+        PromptLayer::Project,
+        PromptLayer::Instructions,
+        PromptLayer::Mode,
+    ];
+}
+
+impl std::fmt::Display for PromptLayer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PromptLayer::Global => write!(f, "global"),
+            PromptLayer::Project => write!(f, "project"),
+            PromptLayer::Instructions => write!(f, "instructions"),
+            PromptLayer::Mode => write!(f, "mode"),
+        }
+    }
+}
+
+/// System prompt layering settings.
+///
+/// Layers are joined with blank lines in `order`; leaving a layer out of
+/// the list drops it (e.g. `order = ["project"]` to replace the global
+/// prompt entirely).
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct PromptConfig {
+    /// Layers to include, in order. Defaults to global, project,
+    /// instructions, mode.
+    pub order: Option<Vec<PromptLayer>>,
+    /// Name of the `modes` entry to append as the mode layer.
+    pub mode: Option<String>,
+    /// Named prompt snippets, such as `review = "Focus on bugs."`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub modes: BTreeMap<String, String>,
+    /// Most tokens of `KAZE.md`/`AGENTS.md` instructions added to the
+    /// system prompt. 0 leaves them out.
+    pub instructions_tokens: Option<usize>,
+}
+
+/// Raw per-file system prompts gathered while loading.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct PromptLayers {
+    pub global: Option<String>,
+    pub project: Option<String>,
+    /// The project instruction files, read by [`Config::load`].
+    pub instructions: Option<String>,
+}
+
+/// Configuration for the one-shot response cache.
+///
+/// When enabled, `kaze ask` reuses a stored answer for an identical
+/// provider, model, system prompt, and question instead of calling the API.
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct CacheConfig {
+    /// Whether `kaze ask` responses are cached (off by default).
+    pub enabled: Option<bool>,
+    /// How long a cached response stays valid, in seconds.
+    pub ttl_secs: Option<u64>,
+}
+
+/// Configuration for terminal output.
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct OutputConfig {
+    /// Screen-reader friendly output (off by default): no colors, spinners,
+    /// or cursor movement; responses and tool activity are printed as
+    /// labeled lines, and `chat --tui` uses the line-based REPL instead.
+    pub accessible: Option<bool>,
+    /// Characters of a tool call's arguments shown while it runs (80 by
+    /// default; 0 shows them whole).
+    pub tool_args_chars: Option<usize>,
+    /// Characters of a tool's result shown when it finishes (200 by
+    /// default; 0 shows it whole). `/expand` prints the full text.
+    pub tool_result_chars: Option<usize>,
+}
+
+/// Session retention policy (`[sessions]`), applied by
+/// `kaze session prune` and, with `auto_prune`, when chat starts.
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct SessionsConfig {
+    /// Remove sessions not updated for this many days.
+    pub max_age_days: Option<u64>,
+    /// Keep at most this many sessions, the most recently updated.
+    pub max_count: Option<usize>,
+    /// Move pruned sessions to `sessions/archive/` instead of deleting them.
+    pub archive: Option<bool>,
+    /// Prune whenever chat starts (off by default).
+    pub auto_prune: Option<bool>,
+    /// Point to the earlier answer when a chat message repeats a question
+    /// from this session or a recent one (on by default).
+    pub duplicate_hints: Option<bool>,
+    /// `"jsonl"` (the default) or `"sqlite"`. Read from the global config
+    /// only, so a project can't move where sessions are kept.
+    pub storage: Option<SessionStorage>,
+}
+
+/// Where sessions are kept (`[sessions] storage`).
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionStorage {
+    /// One append-only JSONL file per session plus `sessions/index.json`.
+    #[default]
+    Jsonl,
+    /// A single `sessions/sessions.db` database, for transactional index
+    /// updates and fast listing with thousands of sessions. Needs a build
+    /// with the `sqlite` feature.
+    Sqlite,
+}
+
+/// Configuration for `kaze suggest` (`[suggest]`).
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct SuggestConfig {
+    /// Model for suggestions, as `model` or `provider/model`. A small, fast
+    /// one (e.g. `anthropic/claude-haiku-4-5` or `ollama/qwen2.5-coder`)
+    /// keeps the shell widget responsive. Defaults to `model`.
+    pub model: Option<String>,
+}
+
+/// Settings for configurable built-in tools (`[tools.*]`).
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct ToolsConfig {
+    /// The `web_search` tool.
+    #[serde(default)]
+    pub web_search: WebSearchConfig,
+    /// The `task` tool's subagents.
+    #[serde(default)]
+    pub task: TaskConfig,
+    /// The `write_file` tool.
+    #[serde(default)]
+    pub write_file: WriteFileConfig,
+}
+
+/// Configuration for the `write_file` tool (`[tools.write_file]`).
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct WriteFileConfig {
+    /// Content size in bytes above which a write needs confirmation, even
+    /// when write_file is allowed or was "always" allowed this session;
+    /// 0 turns the check off.
+    pub confirm_above_bytes: Option<usize>,
+}
+
+/// Network guarantees (`[privacy]`).
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct PrivacyConfig {
+    /// Make no network requests besides those to the configured provider
+    /// endpoints: the `web_fetch` and `web_search` tools are denied, chat
+    /// skips its startup reachability probe, failed requests aren't
+    /// diagnosed by probing the API host, and `kaze models` neither queries
+    /// Ollama nor allows `--probe`. Either config file can turn it on;
+    /// neither can turn it off once the other has. Commands the model runs
+    /// through `bash` are not covered; deny `bash` too if they must be.
+    pub offline_extras: Option<bool>,
+}
+
+/// Configuration for the `task` tool (`[tools.task]`).
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct TaskConfig {
+    /// Model for subagents, as `model` or `provider/model` (e.g. a cheaper
+    /// `anthropic/claude-haiku-4-5`). Defaults to the conversation's model.
+    pub model: Option<String>,
+    /// Most tool-calling round trips a subagent may take.
+    pub max_turns: Option<usize>,
+}
+
+/// Configuration for the `web_search` tool (`[tools.web_search]`).
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct WebSearchConfig {
+    /// Search backend: `"duckduckgo"` (default, no key needed), `"brave"`,
+    /// or `"searxng"`.
+    pub backend: Option<String>,
+    /// API key for backends that need one (Brave). Supports `{env:VAR}`.
+    pub api_key: Option<String>,
+    /// Instance URL for SearXNG (e.g. `"https://searx.example.org"`).
+    pub base_url: Option<String>,
+    /// Results returned per search.
+    pub max_results: Option<usize>,
+}
+
+/// A provider + model pinned for a project, written by `kaze config pin`.
+///
+/// Committed in `kaze.toml` so every teammate and CI run resolves the same
+/// model, the way a lockfile pins dependency versions.
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct PinConfig {
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    /// Date the pin was written (`YYYY-MM-DD`).
+    pub pinned_at: Option<String>,
+    /// kaze version that wrote the pin.
+    pub kaze_version: Option<String>,
+}
+
+/// A saved headless task for `kaze run --template`.
+///
+/// Unset fields fall back to the same defaults as the `kaze run` flags;
+/// flags given on the command line win over the template.
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct RunTemplate {
+    /// The task sent to the agent.
+    pub task: String,
+    pub model: Option<String>,
+    pub provider: Option<String>,
+    pub max_turns: Option<usize>,
+    /// Allow tools whose permission is "ask", as `--auto-approve` does.
+    pub auto_approve: Option<bool>,
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
             model: default_model(),
             provider: ProviderConfig::default(),
             system_prompt: default_system_prompt(),
+            prompt: PromptConfig::default(),
             default_provider: None,
             compaction: CompactionConfig::default(),
+            context: ContextConfig::default(),
+            generation: GenerationConfig::default(),
             permissions: PermissionConfig::default(),
+            cache: CacheConfig::default(),
+            output: OutputConfig::default(),
+            tools: ToolsConfig::default(),
+            suggest: SuggestConfig::default(),
+            sessions: SessionsConfig::default(),
+            locale: None,
+            offline_fallback: None,
+            turn_timeout_secs: None,
+            privacy: PrivacyConfig::default(),
+            pin: PinConfig::default(),
+            templates: BTreeMap::new(),
+            prompt_layers: PromptLayers {
+                global: default_system_prompt(),
+                project: None,
+                instructions: None,
+            },
         }
     }
 }
@@ -23,6 +23,17 @@ pub const CONFIG_FILENAME: &str = "config.toml";
 /// Per-project configuration filename.
 pub const PROJECT_CONFIG_FILENAME: &str = "kaze.toml";
 
+/// Model name of the scripted offline provider (`--model mock/echo`).
+pub const MOCK_DEFAULT_MODEL: &str = "echo";
+
+/// Project instruction files added to the system prompt, in order of
+/// preference; the first one found in a directory is used.
+pub const PROJECT_INSTRUCTION_FILES: [&str; 2] = ["KAZE.md", "AGENTS.md"];
+
+/// Default for `[prompt] instructions_tokens`: most tokens of project
+/// instructions added to the system prompt.
+pub const PROJECT_INSTRUCTIONS_TOKENS_DEFAULT: usize = 8_000;
+
 /// Readline history filename.
 pub const HISTORY_FILENAME: &str = "chat_history.txt";
 
@@ -32,12 +43,36 @@ pub const DEFAULT_OPENAI_MODEL: &str = "gpt-4.1";
 /// Default LLM model identifier for OpenRouter.
 pub const DEFAULT_OPENROUTER_MODEL: &str = "arcee-ai/trinity-large-preview:free";
 
+/// Default LLM model identifier for AWS Bedrock (cross-region inference profile).
+pub const DEFAULT_BEDROCK_MODEL: &str = "us.anthropic.claude-sonnet-4-5-20250929-v1:0";
+
 /// Default base URL for local Ollama server.
 pub const OLLAMA_DEFAULT_BASE_URL: &str = "http://localhost:11434";
 
 /// Default LLM model identifier for Ollama.
 pub const OLLAMA_DEFAULT_MODEL: &str = "llama3";
 
+/// How long `kaze models` waits for a dynamic provider listing (seconds).
+pub const MODEL_LISTING_TIMEOUT_SECS: u64 = 2;
+
+/// Prompt `kaze models --probe` sends to each configured provider.
+pub const MODEL_PROBE_PROMPT: &str = "Reply with just OK.";
+
+/// How long `kaze models --probe` waits for a provider's reply (seconds).
+pub const MODEL_PROBE_TIMEOUT_SECS: u64 = 20;
+
+// --- HTTP ---
+
+/// How long an idle pooled connection is kept open for reuse (seconds).
+pub const HTTP_POOL_IDLE_TIMEOUT_SECS: u64 = 90;
+
+/// TCP and HTTP/2 keep-alive ping interval for provider connections (seconds).
+pub const HTTP_KEEPALIVE_SECS: u64 = 30;
+
+/// How long the connectivity probe waits for a DNS lookup or TCP connect
+/// to the provider's API host (milliseconds).
+pub const NETWORK_PROBE_TIMEOUT_MS: u64 = 1500;
+
 // --- Provider defaults ---
 
 /// Default provider when none is configured.
@@ -81,10 +116,104 @@ pub const COMPACTION_PROMPT: &str = "Summarize the following conversation contex
 Preserve key decisions, code snippets, file paths, and technical details mentioned. \
 Do not add commentary. Return only the summary.\n\n";
 
+/// Largest share of the context window the rolling strategy keeps verbatim,
+/// leaving room for the summary, the system prompt, and the response.
+pub const ROLLING_WINDOW_MAX_RATIO: f64 = 0.6;
+
+/// Estimated prompt size above which chat asks before sending a turn,
+/// unless `[context] confirm_above_tokens` says otherwise.
+pub const CONFIRM_ABOVE_TOKENS_DEFAULT: usize = 100_000;
+
+/// Tokens of diffs chat adds to a turn for files that changed on disk
+/// since the model last read or wrote them, unless `[context]
+/// refresh_tokens` says otherwise. Files past the budget are only named.
+pub const FILE_REFRESH_TOKENS_DEFAULT: usize = 2_000;
+
+/// Tokens of repo map chat sends with each request unless `[context]
+/// repo_map_tokens` says otherwise; 0 leaves the map out.
+pub const REPO_MAP_TOKENS_DEFAULT: usize = 0;
+
+/// Files larger than this (bytes) aren't tracked for refreshes.
+pub const FILE_REFRESH_MAX_BYTES: u64 = 256 * 1024;
+
+/// Whether chat tells the model about its environment (OS, shell,
+/// toolchains, git state) unless `[context] environment` says otherwise.
+pub const ENVIRONMENT_FACTS_DEFAULT: bool = true;
+
+/// Seconds before the git state in the environment block is re-read.
+pub const ENVIRONMENT_REFRESH_SECS: u64 = 30;
+
+/// Seconds a `--version` or git probe for the environment block may take
+/// before it is killed and left out.
+pub const ENVIRONMENT_COMMAND_TIMEOUT_SECS: u64 = 5;
+
+/// Toolchains reported in the environment block, as (label, program run
+/// with `--version`). The first program found for a label wins.
+pub const ENVIRONMENT_TOOLCHAINS: &[(&str, &str)] = &[
+    ("rustc", "rustc"),
+    ("node", "node"),
+    ("python", "python3"),
+    ("python", "python"),
+];
+
+/// Prompt for folding messages that left the rolling window into the
+/// running summary. Followed by the current summary and the messages.
+pub const ROLLING_SUMMARY_PROMPT: &str = "You maintain a running summary of a conversation \
+between a user and a coding assistant. Update the summary below with the new messages. \
+Keep the user's goals, decisions, file paths, code identifiers, and open problems; drop \
+pleasantries and superseded details. Keep it under 400 words. Return only the updated summary.\n\n";
+
+// --- Piped input ---
+
+/// Most tokens of piped stdin attached to a `kaze ask` prompt. Also capped
+/// at half the model's context window.
+pub const STDIN_CONTEXT_MAX_TOKENS: usize = 32_000;
+
+/// Most tokens of `kaze chat --attach-cmd` output attached to a message.
+/// Also capped at half the model's context window.
+pub const ATTACH_CMD_MAX_TOKENS: usize = 16_000;
+
+/// How long an attached command may run before it is killed.
+pub const ATTACH_CMD_TIMEOUT_SECS: u64 = 300;
+
+// --- Chat review ---
+
+/// Most tokens of diff or file content `/review` sends. Also capped at
+/// half the model's context window.
+pub const REVIEW_MAX_TOKENS: usize = 24_000;
+
+/// Instructions `/review` puts before the material to review.
+pub const REVIEW_PROMPT: &str = "Review the code below as a careful senior engineer. Report \
+only real problems: bugs, security issues, data loss, race conditions, broken edge cases, \
+misleading names or docs, and missing tests for risky logic. For each finding, most severe \
+first, write one list item as `**[severity] path:line** problem`, then a sentence on why it \
+matters and the fix, with a short code suggestion where it helps. Severity is one of \
+critical, major, minor, or nit. Skip praise and restating the change. End with a one-line \
+verdict. If nothing needs changing, say so.";
+
+// --- Response cache ---
+
+/// Default lifetime of a cached `kaze ask` response (24 hours).
+pub const RESPONSE_CACHE_TTL_DEFAULT_SECS: u64 = 86_400;
+
 // --- Tool limits ---
 
-/// Maximum file size (bytes) the read_file tool will read.
-pub const READ_FILE_MAX_SIZE: u64 = 100 * 1024;
+/// Read-only tools whose earlier copies of a repeated identical output,
+/// within one agent turn, are replaced by a short stub.
+pub const TOOL_DEDUP_TOOLS: &[&str] = &["read_file", "grep", "glob", "list_dir", "web_fetch"];
+
+/// Smallest tool output (bytes) worth replacing with a stub when repeated.
+pub const TOOL_DEDUP_MIN_CHARS: usize = 400;
+
+/// Most bytes of file content one read_file call returns; larger files are
+/// read in windows with `offset` and `limit`.
+pub const READ_FILE_MAX_SIZE: usize = 100 * 1024;
+
+/// Lines read_file returns when the call sets no `limit`.
+pub const READ_FILE_DEFAULT_LIMIT: usize = 2000;
+
+/// Longest line (characters) read_file shows before cutting it short.
+pub const READ_FILE_MAX_LINE_CHARS: usize = 2000;
 
 /// Byte threshold for binary file detection (check first N bytes for null).
 pub const BINARY_DETECTION_BYTES: usize = 8192;
@@ -92,12 +221,95 @@ pub const BINARY_DETECTION_BYTES: usize = 8192;
 /// Maximum number of results the glob tool returns.
 pub const GLOB_MAX_RESULTS: usize = 1000;
 
+/// Maximum number of entries the list_dir tool returns.
+pub const LIST_DIR_MAX_ENTRIES: usize = 500;
+
+/// Deepest level the list_dir tool descends to.
+pub const LIST_DIR_MAX_DEPTH: usize = 5;
+
 /// Maximum number of matching lines the grep tool returns.
 pub const GREP_MAX_MATCHES: usize = 50;
 
+/// Files the grep tool searches in parallel at a time. Batches are taken in
+/// walk order, so the search can stop at [`GREP_MAX_MATCHES`] early while
+/// returning the same matches as a sequential scan.
+pub const GREP_PARALLEL_BATCH: usize = 256;
+
+/// Content size (bytes) above which a write_file call asks first, whatever
+/// its permission; `[tools.write_file] confirm_above_bytes` overrides it.
+pub const WRITE_FILE_CONFIRM_BYTES_DEFAULT: usize = 1024 * 1024;
+
+/// Most characters the scratchpad tool holds.
+pub const SCRATCHPAD_MAX_CHARS: usize = 8000;
+
+/// Most scratchpad characters shown to the model at the start of a turn.
+pub const SCRATCHPAD_CONTEXT_MAX_CHARS: usize = 2000;
+
 /// Number of context lines shown around diffs in the edit tool.
 pub const DIFF_CONTEXT_LINES: usize = 3;
 
+/// Most context lines the apply_patch tool drops from each end of a hunk
+/// when it does not match as written.
+pub const PATCH_MAX_FUZZ: usize = 2;
+
+// --- File backups ---
+
+/// Directory under the data dir holding pre-change copies of files written
+/// by tools, one subdirectory per kaze process.
+pub const BACKUPS_DIR: &str = "backups";
+
+/// Days a process's backups are kept before a later run deletes them.
+pub const BACKUP_RETENTION_DAYS: u64 = 7;
+
+// --- Git tool ---
+
+/// Timeout (seconds) for one git tool invocation.
+pub const GIT_TIMEOUT_SECS: u64 = 60;
+
+/// Commits `git log` shows when the model doesn't ask for a number.
+pub const GIT_LOG_DEFAULT_LIMIT: usize = 20;
+
+// --- Web fetch tool ---
+
+/// Timeout (seconds) for a whole web_fetch request, including the body.
+pub const WEB_FETCH_TIMEOUT_SECS: u64 = 20;
+
+/// Most bytes web_fetch downloads; the rest of the body is dropped.
+pub const WEB_FETCH_MAX_BYTES: usize = 2 * 1024 * 1024;
+
+/// Most characters of converted text web_fetch returns to the model.
+pub const WEB_FETCH_MAX_CHARS: usize = 100_000;
+
+/// Redirects `web_fetch` follows before giving up.
+pub const WEB_FETCH_MAX_REDIRECTS: usize = 10;
+
+// --- Web search tool ---
+
+/// Timeout (seconds) for a web_search backend request.
+pub const WEB_SEARCH_TIMEOUT_SECS: u64 = 15;
+
+/// Results web_search returns when neither the model nor config asks for a number.
+pub const WEB_SEARCH_DEFAULT_RESULTS: usize = 8;
+
+/// Most results web_search returns per query.
+pub const WEB_SEARCH_MAX_RESULTS: usize = 20;
+
+/// Tools `privacy.offline_extras` denies: every built-in tool that reaches
+/// hosts other than the provider's.
+pub const OFFLINE_EXTRAS_DENIED_TOOLS: &[&str] = &["web_fetch", "web_search"];
+
+// --- Task tool ---
+
+/// Default most tool-calling round trips for a `task` subagent.
+pub const TASK_MAX_TURNS_DEFAULT: usize = 20;
+
+/// System prompt for `task` subagents.
+pub const TASK_SYSTEM_PROMPT: &str = "You are a subagent of kaze, a coding assistant. \
+Another agent delegated the task below to you. Use your tools to complete it, working \
+autonomously: nobody will answer questions. When done, reply with a concise report of what \
+you found or changed, with file paths and line numbers where relevant. The report is all the \
+other agent will see.";
+
 // --- Bash tool ---
 
 /// Default timeout (seconds) for bash command execution.
@@ -116,12 +328,249 @@ pub const BASH_STRIPPED_ENV_VARS: &[&str] = &[
     "OPENROUTER_API_KEY",
 ];
 
+// --- Help lookup tool ---
+
+/// Seconds a `man` or `--help` run may take before help_lookup gives up.
+pub const HELP_LOOKUP_TIMEOUT_SECS: u64 = 10;
+
+/// Most bytes of cleaned documentation help_lookup returns.
+pub const HELP_LOOKUP_MAX_OUTPUT: usize = 64 * 1024;
+
+/// Column width man pages are formatted to.
+pub const HELP_LOOKUP_MAN_WIDTH: &str = "100";
+
+/// Commands help_lookup reads man pages for but never runs with `--help`:
+/// shells, interpreters, and wrappers that would run their next argument
+/// (`sh x --help` runs `x`), and programs whose `-h` isn't help. `python`
+/// followed by a version (`python3.12`) counts as `python`.
+pub const HELP_LOOKUP_REFUSED_COMMANDS: &[&str] = &[
+    "sh", "bash", "dash", "zsh", "fish", "ksh", "env", "python", "python2", "python3", "node",
+    "deno", "bun", "perl", "ruby", "php", "xargs", "nohup", "sudo", "doas", "su", "timeout",
+    "nice", "ionice", "time", "watch", "exec", "shutdown", "reboot", "halt", "poweroff",
+];
+
+// --- Audit log ---
+
+/// Characters of a string argument (file contents, a patch) kept in the
+/// audit log; the rest is replaced by its length.
+pub const AUDIT_ARG_MAX_CHARS: usize = 200;
+
+/// Calls `kaze audit show` prints by default, the most recent.
+pub const AUDIT_SHOW_LIMIT: usize = 50;
+
+// --- Sessions ---
+
+/// Maximum number of session files parsed concurrently by `kaze session reindex`.
+pub const SESSION_REINDEX_CONCURRENCY: usize = 8;
+
+// --- Live view ---
+
+/// Port `kaze serve` listens on unless `--port` says otherwise.
+pub const SERVE_DEFAULT_PORT: u16 = 8733;
+
+/// How often the live view polls for new messages (milliseconds).
+pub const SERVE_POLL_MS: u64 = 1000;
+
+/// Most bytes of an HTTP request `kaze serve` reads before giving up.
+pub const SERVE_REQUEST_MAX_BYTES: usize = 8 * 1024;
+
+/// How long `kaze serve` waits for a client to send its request (seconds).
+pub const SERVE_REQUEST_TIMEOUT_SECS: u64 = 5;
+
+// --- Duplicate questions ---
+
+/// Most recently updated sessions searched for an earlier, similar question.
+pub const DUPLICATE_SCAN_SESSIONS: usize = 50;
+
+/// Share of distinct words two questions must have in common (Jaccard
+/// similarity) to count as the same question.
+pub const DUPLICATE_SIMILARITY: f64 = 0.8;
+
+/// Questions with fewer distinct words are never matched; short ones like
+/// "continue" or "why?" repeat on purpose.
+pub const DUPLICATE_MIN_WORDS: usize = 4;
+
+/// Longer messages, usually pasted files, attachments, or `/review`
+/// material, are never matched.
+pub const DUPLICATE_MAX_CHARS: usize = 2_000;
+
+/// Characters of the earlier answer shown in the hint.
+pub const DUPLICATE_ANSWER_PREVIEW_CHARS: usize = 200;
+
+// --- Chat commands ---
+
+/// Directory of custom slash command files under the config directory.
+pub const COMMANDS_DIRNAME: &str = "commands";
+
+/// Directory of a project's custom slash commands, relative to its root.
+pub const PROJECT_COMMANDS_DIR: &str = ".kaze/commands";
+
+/// Placeholder in a custom command's template for the text typed after it.
+pub const COMMAND_ARGUMENTS: &str = "$ARGUMENTS";
+
+/// Lines `/view` prints when no range is given.
+pub const VIEW_MAX_LINES: usize = 400;
+
+/// Most text `/copy` sends in one OSC 52 sequence; many terminals drop
+/// longer ones.
+pub const OSC52_MAX_BYTES: usize = 100_000;
+
 // --- Agent loop ---
 
 /// Maximum number of agent loop iterations before aborting.
 pub const MAX_AGENT_ITERATIONS: usize = 25;
 
+/// Tasks `kaze run --parallel` runs at once unless the task file or
+/// `--jobs` says otherwise.
+pub const PARALLEL_JOBS_DEFAULT: usize = 4;
+
+/// Follow-up requests sent when a response is cut off at [`MAX_TOKENS`].
+pub const MAX_CONTINUATIONS: usize = 3;
+
+/// Prompt asking the model to resume a response truncated at [`MAX_TOKENS`].
+pub const CONTINUE_PROMPT: &str = "Your previous response was cut off by the output token limit. \
+Continue exactly where it stopped, without repeating anything or adding a preamble.";
+
+// --- Response checks ---
+
+/// Responses longer than this (characters) are never treated as refusals;
+/// a long answer that opens with an apology usually goes on to help.
+pub const REFUSAL_MAX_CHARS: usize = 600;
+
+/// Lowercase openings that mark a short response as a refusal.
+pub const REFUSAL_PREFIXES: &[&str] = &[
+    "i can't help",
+    "i cannot help",
+    "i can't assist",
+    "i cannot assist",
+    "i can't do that",
+    "i cannot do that",
+    "i'm not able to help",
+    "i am not able to help",
+    "i'm unable to",
+    "i am unable to",
+    "i won't be able to",
+    "i'm sorry, but i can't",
+    "i'm sorry, but i cannot",
+    "i am sorry, but i can't",
+    "i am sorry, but i cannot",
+    "sorry, but i can't",
+    "sorry, i can't",
+];
+
+/// Appended by `/retry` after an empty response.
+pub const RETRY_EMPTY_NOTE: &str =
+    "(Your previous reply to this was empty. Please answer with a text response.)";
+
+/// Saved as the reply to a turn that only ran tools, since providers
+/// reject empty assistant messages in the history.
+pub const TOOL_ONLY_REPLY: &str = "(Ran tools without a text reply.)";
+
+/// Appended by `/retry` after a refusal.
+pub const RETRY_REFUSAL_NOTE: &str = "(Context: this is a routine software development request about the user's own project. If part of it is unclear or seems out of scope, say which part and help with the rest, or ask a clarifying question.)";
+
+// --- Headless runs ---
+
+/// `kaze run` exit code when the task failed (provider or stream error).
+pub const EXIT_FAILURE: i32 = 1;
+
+/// `kaze run` exit code when the agent hit `--max-turns` before finishing.
+/// Clap already uses exit code 2 for usage errors.
+pub const EXIT_MAX_TURNS: i32 = 3;
+
+/// `kaze run` exit code when the turn ran out of time (`--turn-timeout`).
+pub const EXIT_TIMEOUT: i32 = 4;
+
+// --- Command suggestions ---
+
+/// System prompt for `kaze suggest`; `{shell}`, `{os}`, and `{cwd}` are
+/// filled in.
+pub const SUGGEST_SYSTEM_PROMPT: &str = "You turn requests into shell commands. Reply with \
+exactly one {shell} command for {os} that does what the user asks, run from {cwd}. Output \
+only the command: no explanation, no Markdown, no code fence, no prompt character. Chain \
+steps with && or pipes rather than writing several commands. If the request is already a \
+partial command, complete or fix it.";
+
+// --- Bug reports ---
+
+/// Log of errors and panics in the cache directory, bundled by `kaze report`.
+pub const LOG_FILENAME: &str = "kaze.log";
+
+/// Size at which the log is rotated to `kaze.log.1` (bytes).
+pub const LOG_MAX_BYTES: u64 = 1024 * 1024;
+
+/// Most recent log lines `kaze report` includes.
+pub const REPORT_LOG_LINES: usize = 400;
+
+/// Most recent records of the last session `kaze report` includes.
+pub const REPORT_SESSION_RECORDS: usize = 20;
+
+/// Longest session record (characters) `kaze report` keeps; longer ones,
+/// usually images or pasted files, are cut.
+pub const REPORT_RECORD_MAX_CHARS: usize = 2000;
+
+// --- Scheduled runs ---
+
+/// Prefix of the systemd units `kaze schedule` writes (`kaze-<template>`).
+pub const SCHEDULE_UNIT_PREFIX: &str = "kaze-";
+
+/// Comment that tags `kaze schedule` entries in the crontab, followed by
+/// the template name.
+pub const SCHEDULE_CRON_MARKER: &str = "# kaze schedule:";
+
+/// Directory in the cache where cron-scheduled runs append their output.
+pub const SCHEDULE_LOG_DIR: &str = "schedule";
+
+// --- Rendering ---
+
+/// Window (milliseconds) within which streamed tokens are batched before drawing.
+pub const STREAM_BATCH_WINDOW_MS: u64 = 16;
+
+/// Default characters of a tool call's arguments shown while it runs
+/// (`[output] tool_args_chars`).
+pub const TOOL_ARGS_DISPLAY_CHARS: usize = 80;
+
+/// Default characters of a tool's result shown when it finishes
+/// (`[output] tool_result_chars`).
+pub const TOOL_RESULT_DISPLAY_CHARS: usize = 200;
+
+/// Tool calls kept in full for `/expand`.
+pub const EXPAND_MAX_CALLS: usize = 50;
+
+// --- Identifier completion ---
+
+/// Most source files the chat REPL's symbol index scans.
+pub const SYMBOL_INDEX_MAX_FILES: usize = 5_000;
+
+/// Largest source file (bytes) the symbol index reads.
+pub const SYMBOL_INDEX_MAX_FILE_SIZE: u64 = 256 * 1024;
+
+/// Characters typed before plain text (without `@`) is completed.
+pub const SYMBOL_COMPLETE_MIN_PREFIX: usize = 3;
+
+/// Most completions listed for one Tab press.
+pub const SYMBOL_COMPLETE_MAX_CANDIDATES: usize = 50;
+
 // --- TUI ---
 
 /// ASCII spinner frames for the TUI "thinking" indicator.
 pub const SPINNER_FRAMES: [&str; 4] = ["|", "/", "-", "\\"];
+
+/// How long the TUI waits on exit for an interrupted turn to save its
+/// partial response (milliseconds).
+pub const TUI_SHUTDOWN_GRACE_MS: u64 = 2000;
+
+/// Widest the TUI todo panel grows (columns); it takes a third of the
+/// history pane up to this.
+pub const TODO_PANEL_MAX_WIDTH: u16 = 40;
+
+/// Recent sessions listed on the TUI dashboard below the open ones.
+pub const DASHBOARD_RECENT_SESSIONS: usize = 10;
+
+// --- Stats ---
+
+/// Default number of rows in the `kaze stats` tool and file tables.
+pub const STATS_TOP_ROWS: usize = 10;
+
+/// Default number of recent weeks in the `kaze stats` spend table.
+pub const STATS_WEEKS: usize = 8;
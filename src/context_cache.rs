@@ -0,0 +1,209 @@
+//! On-disk cache for expensive startup context artifacts.
+//!
+//! Artifacts like the identifier index behind chat completion (see
+//! [`SymbolIndex::load`](crate::symbols::SymbolIndex::load)), the repo
+//! map chat can send the model, and the assembled `KAZE.md` instructions
+//! are stored under `~/.cache/kaze/context/` and keyed by the git `HEAD`
+//! commit plus a hash of every dirty file, so a new session on a large repo
+//! only recomputes an artifact when the working tree actually changed.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::config::Config;
+
+/// A cached artifact together with the key it was computed for.
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    key: String,
+    content: String,
+}
+
+/// Computes the cache key for the working tree at `project_root`.
+///
+/// Combines `git rev-parse HEAD` with the path and contents of every file
+/// reported by `git status --porcelain -z`, untracked files included one
+/// by one. Returns `None` outside a git repository, in which case callers
+/// should skip caching.
+pub fn tree_key(project_root: &Path) -> Option<String> {
+    let rev = git_output(project_root, &["rev-parse", "--show-toplevel", "HEAD"])?;
+    let (top, head) = rev.trim().split_once('\n')?;
+    // Reading the status mustn't take the index lock from the user's git
+    let status = git_output(
+        project_root,
+        &[
+            "--no-optional-locks",
+            "status",
+            "--porcelain",
+            "-z",
+            "--untracked-files=all",
+        ],
+    )?;
+
+    let mut hasher = DefaultHasher::new();
+    head.hash(&mut hasher);
+    // NUL-separated records are "XY path", paths unquoted and relative to
+    // the top level; a rename or copy is followed by its source path
+    let mut records = status.split('\0').filter(|record| !record.is_empty());
+    while let Some(record) = records.next() {
+        record.hash(&mut hasher);
+        let (Some(code), Some(path)) = (record.get(..2), record.get(3..)) else {
+            continue;
+        };
+        if let Ok(bytes) = fs::read(Path::new(top).join(path)) {
+            bytes.hash(&mut hasher);
+        }
+        if code.contains(['R', 'C']) {
+            records.next().hash(&mut hasher);
+        }
+    }
+    Some(format!("{}-{:016x}", head, hasher.finish()))
+}
+
+/// Returns the cached artifact `name`, computing and storing it on a miss.
+///
+/// When `project_root` is not a git repository the artifact is computed
+/// every time. Cache read/write failures are never fatal.
+pub fn get_or_compute<F>(project_root: &Path, name: &str, compute: F) -> Result<String>
+where
+    F: FnOnce() -> Result<String>,
+{
+    get_or_compute_with(project_root, name, "", compute)
+}
+
+/// Like [`get_or_compute`], for an artifact that depends on more than the
+/// tree, such as settings or files git ignores: `inputs` joins the key.
+pub fn get_or_compute_with<F>(
+    project_root: &Path,
+    name: &str,
+    inputs: &str,
+    compute: F,
+) -> Result<String>
+where
+    F: FnOnce() -> Result<String>,
+{
+    let Some(tree) = tree_key(project_root) else {
+        return compute();
+    };
+    let mut hasher = DefaultHasher::new();
+    inputs.hash(&mut hasher);
+    let key = format!("{}-{:016x}", tree, hasher.finish());
+    let path = entry_path(project_root, name)?;
+
+    if let Ok(contents) = fs::read_to_string(&path) {
+        if let Ok(entry) = serde_json::from_str::<CacheEntry>(&contents) {
+            if entry.key == key {
+                return Ok(entry.content);
+            }
+        }
+    }
+
+    let content = compute()?;
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    let entry = CacheEntry {
+        key,
+        content: content.clone(),
+    };
+    if let Ok(json) = serde_json::to_string(&entry) {
+        let _ = fs::write(&path, json);
+    }
+    Ok(content)
+}
+
+/// Returns the cache file path for an artifact of a given project.
+fn entry_path(project_root: &Path, name: &str) -> Result<PathBuf> {
+    let mut hasher = DefaultHasher::new();
+    project_root.hash(&mut hasher);
+    Ok(Config::cache_dir()?
+        .join("context")
+        .join(format!("{}-{:016x}.json", name, hasher.finish())))
+}
+
+/// Runs a git command in `dir` and returns its stdout on success.
+fn git_output(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tree_key_reads_renamed_and_quoted_paths_from_a_subdirectory() {
+        let dir = std::env::temp_dir().join(format!("kaze_test_tree_key_z_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args(args)
+                .current_dir(&dir)
+                .output()
+                .unwrap()
+        };
+        git(&["init", "-q"]);
+        git(&["config", "user.name", "Test"]);
+        git(&["config", "user.email", "test@example.com"]);
+        fs::write(dir.join("old name.txt"), "one\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-qm", "Add a file"]);
+
+        // Only the contents differ between these keys, through a staged
+        // rename to a name git would quote without -z
+        git(&["mv", "old name.txt", "nëw name.txt"]);
+        let sub = dir.join("sub");
+        let renamed = tree_key(&sub).unwrap();
+        fs::write(dir.join("nëw name.txt"), "two\n").unwrap();
+        assert_ne!(tree_key(&sub).unwrap(), renamed);
+        assert_eq!(tree_key(&dir), tree_key(&sub));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_context_cache_key_follows_dirty_files() {
+        let dir = std::env::temp_dir().join(format!("kaze_test_tree_key_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args(args)
+                .current_dir(&dir)
+                .output()
+                .unwrap()
+        };
+        git(&["init", "-q"]);
+        git(&["config", "user.name", "Test"]);
+        git(&["config", "user.email", "test@example.com"]);
+        // No commit yet, so no key to cache under
+        assert_eq!(tree_key(&dir), None);
+        fs::write(dir.join("a.txt"), "hello\n").unwrap();
+        git(&["add", "a.txt"]);
+        git(&["commit", "-qm", "Add a.txt"]);
+
+        let clean = tree_key(&dir).unwrap();
+        assert_eq!(tree_key(&dir).unwrap(), clean);
+        // A file in a new untracked directory counts by its contents
+        fs::create_dir_all(dir.join("new")).unwrap();
+        fs::write(dir.join("new/b.rs"), "fn one() {}\n").unwrap();
+        let dirty = tree_key(&dir).unwrap();
+        assert_ne!(dirty, clean);
+        fs::write(dir.join("new/b.rs"), "fn two() {}\n").unwrap();
+        assert_ne!(tree_key(&dir).unwrap(), dirty);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,11 +1,16 @@
 //! Diff generation and colored rendering for file changes.
 //!
 //! Provides [`unified_diff`] for comparing old vs new content and
-//! [`new_file_preview`] for all-additions preview of new files.
+//! [`new_file_preview`] for all-additions preview of new files, and
+//! [`colorize_patch`] for diffs the model writes itself. [`hunks`] and
+//! [`apply_hunks`] back per-hunk approval of a change. [`plain_diff`] is
+//! the uncolored form sent to the model.
 //! Used by [`crate::hooks::KazeHook`] for pre-write diff display.
 
 use colored::Colorize;
+use similar::udiff::UnifiedDiffHunk;
 use similar::{ChangeTag, TextDiff};
+use std::ops::Range;
 
 /// Generate a colored unified diff string.
 ///
@@ -21,26 +26,112 @@ pub fn unified_diff(old: &str, new: &str, path: &str) -> String {
     output.push_str(&format!("+++ b/{}\n", path));
 
     for hunk in diff.unified_diff().context_radius(3).iter_hunks() {
-        output.push_str(&format!("{}", hunk.header()));
-
-        for change in hunk.iter_changes() {
-            match change.tag() {
-                ChangeTag::Delete => {
-                    output.push_str(&format!("{}", format!("-{}", change).red()));
-                }
-                ChangeTag::Insert => {
-                    output.push_str(&format!("{}", format!("+{}", change).green()));
-                }
-                ChangeTag::Equal => {
-                    output.push_str(&format!(" {}", change));
-                }
-            };
-        }
+        output.push_str(&render_hunk(&hunk));
     }
 
     output
 }
 
+/// One hunk of a change between two versions of a file.
+#[derive(Debug, Clone)]
+pub struct Hunk {
+    /// Lines of the old text the hunk covers, context included (0-based).
+    old_range: Range<usize>,
+    /// The text that replaces those lines, with line endings.
+    pub new_text: String,
+    /// Colored rendering, as it appears in [`unified_diff`].
+    pub display: String,
+}
+
+/// Splits the change from `old` to `new` into the hunks [`unified_diff`] shows.
+pub fn hunks(old: &str, new: &str) -> Vec<Hunk> {
+    let diff = TextDiff::from_lines(old, new);
+    let new_lines = diff.new_slices();
+    diff.unified_diff()
+        .context_radius(3)
+        .iter_hunks()
+        .filter_map(|hunk| {
+            let (first, last) = (hunk.ops().first()?, hunk.ops().last()?);
+            let old_range = first.old_range().start..last.old_range().end;
+            let new_range = first.new_range().start..last.new_range().end;
+            Some(Hunk {
+                old_range,
+                new_text: new_lines[new_range].concat(),
+                display: render_hunk(&hunk),
+            })
+        })
+        .collect()
+}
+
+/// Applies `accepted` (hunks of a change to `old`, in order) and leaves the
+/// lines of the rejected ones as they were.
+pub fn apply_hunks(old: &str, accepted: &[Hunk]) -> String {
+    let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+    let mut output = String::with_capacity(old.len());
+    let mut next = 0;
+    for hunk in accepted {
+        output.push_str(&old_lines[next..hunk.old_range.start].concat());
+        output.push_str(&hunk.new_text);
+        next = hunk.old_range.end;
+    }
+    output.push_str(&old_lines[next..].concat());
+    output
+}
+
+fn render_hunk<'a>(hunk: &UnifiedDiffHunk<'_, 'a, 'a, '_, str>) -> String {
+    let mut output = format!("{}", hunk.header());
+
+    for change in hunk.iter_changes() {
+        match change.tag() {
+            ChangeTag::Delete => {
+                output.push_str(&format!("{}", format!("-{}", change).red()));
+            }
+            ChangeTag::Insert => {
+                output.push_str(&format!("{}", format!("+{}", change).green()));
+            }
+            ChangeTag::Equal => {
+                output.push_str(&format!(" {}", change));
+            }
+        };
+    }
+
+    output
+}
+
+/// An uncolored unified diff with two lines of context, for the model.
+/// Empty when the contents are identical.
+pub fn plain_diff(old: &str, new: &str, path: &str) -> String {
+    let diff = TextDiff::from_lines(old, new);
+    if diff.ratio() == 1.0 {
+        return String::new();
+    }
+    diff.unified_diff()
+        .context_radius(2)
+        .header(&format!("a/{}", path), &format!("b/{}", path))
+        .to_string()
+}
+
+/// Color an existing unified diff, e.g. one the model passed to apply_patch.
+pub fn colorize_patch(patch: &str) -> String {
+    let mut output = String::new();
+    for line in patch.lines() {
+        let colored = if line.starts_with("+++") || line.starts_with("---") {
+            line.bold().to_string()
+        } else if line.starts_with('+') {
+            line.green().to_string()
+        } else if line.starts_with('-') {
+            line.red().to_string()
+        } else if line.starts_with("@@") {
+            line.cyan().to_string()
+        } else {
+            line.to_string()
+        };
+        output.push_str(&colored);
+        output.push('\n');
+    }
+    output
+}
+
 /// Generate a colored preview for a new file (all lines are additions).
 ///
 /// Used when write_file targets a path that doesn't exist yet.
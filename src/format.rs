@@ -1,10 +1,14 @@
 use colored::Colorize;
 
+use crate::highlight::{self, Language};
 use crate::message::{Message, Role};
 
 /// Format a message for terminal display with role label and colors.
 pub fn format_message(msg: &Message) -> String {
-    let label = format_role_label(&msg.role);
+    let mut label = format_role_label(&msg.role);
+    if msg.incomplete {
+        label = format!("{} {}", label, "(interrupted)".dimmed());
+    }
     let body = format_body(msg.text(), &msg.role);
     format!("{}\n{}", label, body)
 }
@@ -31,29 +35,35 @@ fn format_body(text: &str, role: &Role) -> String {
 /// Minimal markdown renderer for terminal output.
 /// Not a full parser. Handles the three most common patterns
 /// in LLM output: bold, inline code, and fenced code blocks.
+///
+/// Code blocks are highlighted by their fence tag, or by
+/// [`Language::detect`] when the model left the fence bare.
 pub fn render_markdown_lite(text: &str) -> String {
+    let _span = tracing::info_span!("render_markdown", bytes = text.len()).entered();
     let mut output = String::new();
-    let mut in_code_block = false;
-    let mut code_lang = String::new();
+    // Tag and lines of the fenced block being collected, if inside one
+    let mut block: Option<(String, Vec<&str>)> = None;
 
     for line in text.lines() {
         if line.starts_with("```") {
-            if in_code_block {
-                in_code_block = false;
-                code_lang.clear();
-                output.push('\n');
-            } else {
-                in_code_block = true;
-                code_lang = line.trim_start_matches('`').to_string();
-                if !code_lang.is_empty() {
-                    output.push_str(&format!("  {}\n", code_lang.dimmed()));
+            match block.take() {
+                Some((tag, lines)) => {
+                    push_code_block(&mut output, &tag, &lines);
+                    output.push('\n');
+                }
+                None => {
+                    let tag = line.trim_start_matches('`').trim().to_string();
+                    if !tag.is_empty() {
+                        output.push_str(&format!("  {}\n", tag.dimmed()));
+                    }
+                    block = Some((tag, Vec::new()));
                 }
             }
             continue;
         }
 
-        if in_code_block {
-            output.push_str(&format!("  {}\n", line.dimmed()));
+        if let Some((_, ref mut lines)) = block {
+            lines.push(line);
             continue;
         }
 
@@ -62,12 +72,57 @@ pub fn render_markdown_lite(text: &str) -> String {
         output.push('\n');
     }
 
+    // An unterminated block (e.g. a truncated response) still renders
+    if let Some((tag, lines)) = block {
+        push_code_block(&mut output, &tag, &lines);
+    }
+
     if output.ends_with('\n') {
         output.pop();
     }
     output
 }
 
+/// The contents of the fenced code blocks in `text`, without the fences.
+///
+/// Fences are recognized the same way [`render_markdown_lite`] does; an
+/// unterminated block runs to the end of the text.
+pub fn code_blocks(text: &str) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut block: Option<Vec<&str>> = None;
+    for line in text.lines() {
+        if line.starts_with("```") {
+            match block.take() {
+                Some(lines) => blocks.push(lines.join("\n")),
+                None => block = Some(Vec::new()),
+            }
+        } else if let Some(ref mut lines) = block {
+            lines.push(line);
+        }
+    }
+    if let Some(lines) = block {
+        blocks.push(lines.join("\n"));
+    }
+    blocks
+}
+
+/// Appends an indented code block, highlighted when its language is known.
+fn push_code_block(output: &mut String, tag: &str, lines: &[&str]) {
+    let lang = if tag.is_empty() {
+        Language::detect(&lines.join("\n"))
+    } else {
+        Language::from_name(tag)
+    };
+    for line in lines {
+        let rendered = if lang == Language::Plain {
+            line.dimmed().to_string()
+        } else {
+            highlight::highlight_line(line, lang)
+        };
+        output.push_str(&format!("  {}\n", rendered));
+    }
+}
+
 /// Handle **bold** and `inline code` within a single line.
 fn render_inline(line: &str) -> String {
     let mut result = String::new();
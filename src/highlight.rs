@@ -0,0 +1,400 @@
+//! Lightweight syntax highlighting for terminal output.
+//!
+//! Not a real lexer: a single pass per line that colors comments, string
+//! literals, numbers, and a per-language keyword list. Good enough to make
+//! code scannable in `/view` and in chat output without pulling in a
+//! grammar library. [`Language::detect`] guesses the language of code
+//! blocks whose fence has no tag.
+
+use colored::Colorize;
+use std::path::Path;
+
+/// Languages with keyword tables, plus a plain fallback.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Language {
+    Rust,
+    Python,
+    JavaScript,
+    Go,
+    C,
+    Shell,
+    Toml,
+    Json,
+    Plain,
+}
+
+impl Language {
+    /// Guesses the language from a file extension.
+    pub fn from_path(path: &Path) -> Self {
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        Self::from_name(ext)
+    }
+
+    /// Maps a fence tag or extension (`rs`, `python`, `ts`, ...) to a language.
+    pub fn from_name(name: &str) -> Self {
+        match name.to_lowercase().as_str() {
+            "rs" | "rust" => Self::Rust,
+            "py" | "python" => Self::Python,
+            "js" | "jsx" | "ts" | "tsx" | "mjs" | "javascript" | "typescript" => Self::JavaScript,
+            "go" | "golang" => Self::Go,
+            "c" | "h" | "cc" | "cpp" | "hpp" | "cxx" | "java" | "cs" => Self::C,
+            "sh" | "bash" | "zsh" | "shell" => Self::Shell,
+            "toml" | "ini" | "cfg" => Self::Toml,
+            "json" => Self::Json,
+            _ => Self::Plain,
+        }
+    }
+
+    /// Guesses the language of an untagged code block from its content.
+    ///
+    /// Each language scores points for markers that rarely appear in the
+    /// others (`fn`/`let mut` for Rust, `def`/trailing colons for Python,
+    /// `:=` for Go, ...); the best score wins. Returns [`Language::Plain`]
+    /// when nothing matches, so prose and command output stay uncolored.
+    pub fn detect(code: &str) -> Self {
+        let trimmed = code.trim();
+        if trimmed.is_empty() {
+            return Self::Plain;
+        }
+        if trimmed.starts_with("#!") {
+            let shebang = trimmed.lines().next().unwrap_or("");
+            return if shebang.contains("python") {
+                Self::Python
+            } else if shebang.contains("node") {
+                Self::JavaScript
+            } else {
+                Self::Shell
+            };
+        }
+        let looks_like_json = (trimmed.starts_with('{') && trimmed.ends_with('}'))
+            || (trimmed.starts_with('[') && trimmed.ends_with(']') && trimmed.contains('"'));
+        if looks_like_json && serde_json::from_str::<serde_json::Value>(trimmed).is_ok() {
+            return Self::Json;
+        }
+
+        let mut scores = [
+            (Self::Rust, 0),
+            (Self::Python, 0),
+            (Self::JavaScript, 0),
+            (Self::Go, 0),
+            (Self::C, 0),
+            (Self::Shell, 0),
+            (Self::Toml, 0),
+        ];
+        for line in code.lines() {
+            let line = line.trim();
+            for (lang, score) in scores.iter_mut() {
+                *score += lang.line_score(line);
+            }
+        }
+
+        scores
+            .iter()
+            .filter(|(_, score)| *score > 0)
+            .max_by_key(|(_, score)| *score)
+            .map(|(lang, _)| *lang)
+            .unwrap_or(Self::Plain)
+    }
+
+    /// Evidence a single trimmed line gives for this language.
+    fn line_score(self, line: &str) -> u32 {
+        let starts = |prefixes: &[&str]| prefixes.iter().any(|p| line.starts_with(p));
+        let has = |needles: &[&str]| needles.iter().filter(|n| line.contains(*n)).count() as u32;
+        match self {
+            Self::Rust => {
+                let mut score = has(&[
+                    "let mut ", "&mut ", "impl ", "::new(", "Option<", "Result<", "println!",
+                    "Vec<",
+                ]);
+                if starts(&[
+                    "fn ",
+                    "pub fn ",
+                    "pub(crate) ",
+                    "use ",
+                    "mod ",
+                    "#[",
+                    "struct ",
+                    "enum ",
+                    "impl",
+                ]) {
+                    score += 2;
+                }
+                if line.contains("->") && line.contains("fn ") {
+                    score += 2;
+                }
+                score
+            }
+            Self::Python => {
+                let mut score = has(&["self.", "print(", "None", "elif ", "__init__", " is not "]);
+                if starts(&[
+                    "def ", "class ", "from ", "import ", "elif ", "except", "with ",
+                ]) && !line.ends_with(';')
+                {
+                    score += 2;
+                }
+                if line.ends_with(':')
+                    && starts(&["if ", "for ", "while ", "def ", "class ", "else", "try"])
+                {
+                    score += 2;
+                }
+                score
+            }
+            Self::JavaScript => {
+                let mut score = has(&[
+                    "=> ",
+                    "console.",
+                    "===",
+                    "!==",
+                    "require(",
+                    "document.",
+                    "undefined",
+                ]);
+                if starts(&[
+                    "const ",
+                    "function ",
+                    "export ",
+                    "import {",
+                    "async function",
+                    "var ",
+                ]) {
+                    score += 2;
+                }
+                score
+            }
+            Self::Go => {
+                let mut score = has(&[" := ", "fmt.", "err != nil", "chan "]);
+                if starts(&["package ", "func ", "defer ", "go func"]) {
+                    score += 3;
+                }
+                score
+            }
+            Self::C => {
+                let mut score = has(&["printf(", "std::", "nullptr", "->", "malloc("]);
+                if starts(&[
+                    "#include",
+                    "#define",
+                    "int main",
+                    "void ",
+                    "typedef ",
+                    "public class ",
+                ]) {
+                    score += 3;
+                }
+                score
+            }
+            Self::Shell => {
+                let mut score = has(&[" | ", " && ", "$(", "${"]);
+                if starts(&[
+                    "$ ", "echo ", "cd ", "export ", "sudo ", "cargo ", "npm ", "git ", "fi",
+                    "done", "mkdir ", "apt ", "brew ",
+                ]) {
+                    score += 2;
+                }
+                score
+            }
+            Self::Toml => {
+                let section = line.starts_with('[') && line.ends_with(']') && !line.contains(',');
+                let assignment = line.split_once(" = ").is_some_and(|(key, _)| {
+                    !key.is_empty()
+                        && key
+                            .chars()
+                            .all(|c| c.is_alphanumeric() || "_-.".contains(c))
+                });
+                u32::from(section) * 2 + u32::from(assignment)
+            }
+            Self::Json | Self::Plain => 0,
+        }
+    }
+
+    fn keywords(self) -> &'static [&'static str] {
+        match self {
+            Self::Rust => &[
+                "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else",
+                "enum", "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match",
+                "mod", "move", "mut", "pub", "ref", "return", "self", "Self", "static", "struct",
+                "super", "trait", "true", "type", "unsafe", "use", "where", "while",
+            ],
+            Self::Python => &[
+                "and", "as", "assert", "async", "await", "break", "class", "continue", "def",
+                "del", "elif", "else", "except", "False", "finally", "for", "from", "if", "import",
+                "in", "is", "lambda", "None", "not", "or", "pass", "raise", "return", "True",
+                "try", "while", "with", "yield",
+            ],
+            Self::JavaScript => &[
+                "async",
+                "await",
+                "break",
+                "case",
+                "catch",
+                "class",
+                "const",
+                "continue",
+                "default",
+                "else",
+                "export",
+                "extends",
+                "false",
+                "finally",
+                "for",
+                "function",
+                "if",
+                "import",
+                "in",
+                "interface",
+                "let",
+                "new",
+                "null",
+                "of",
+                "return",
+                "switch",
+                "this",
+                "throw",
+                "true",
+                "try",
+                "type",
+                "typeof",
+                "undefined",
+                "var",
+                "while",
+            ],
+            Self::Go => &[
+                "break",
+                "case",
+                "chan",
+                "const",
+                "continue",
+                "default",
+                "defer",
+                "else",
+                "false",
+                "for",
+                "func",
+                "go",
+                "if",
+                "import",
+                "interface",
+                "map",
+                "nil",
+                "package",
+                "range",
+                "return",
+                "select",
+                "struct",
+                "switch",
+                "true",
+                "type",
+                "var",
+            ],
+            Self::C => &[
+                "break", "case", "char", "class", "const", "continue", "default", "do", "double",
+                "else", "enum", "false", "float", "for", "if", "int", "long", "new", "null",
+                "nullptr", "private", "public", "return", "static", "struct", "switch", "this",
+                "true", "typedef", "unsigned", "void", "while",
+            ],
+            Self::Shell => &[
+                "case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function",
+                "if", "in", "local", "return", "then", "while",
+            ],
+            Self::Toml | Self::Json => &["true", "false", "null"],
+            Self::Plain => &[],
+        }
+    }
+
+    fn line_comment(self) -> Option<&'static str> {
+        match self {
+            Self::Rust | Self::JavaScript | Self::Go | Self::C => Some("//"),
+            Self::Python | Self::Shell | Self::Toml => Some("#"),
+            Self::Json | Self::Plain => None,
+        }
+    }
+}
+
+/// Colors a single line of code.
+pub fn highlight_line(line: &str, lang: Language) -> String {
+    if lang == Language::Plain {
+        return line.to_string();
+    }
+
+    let chars: Vec<char> = line.chars().collect();
+    let comment: Option<Vec<char>> = lang.line_comment().map(|c| c.chars().collect());
+    let mut out = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if let Some(ref marker) = comment {
+            if chars[i..].starts_with(marker) {
+                let rest: String = chars[i..].iter().collect();
+                out.push_str(&rest.dimmed().to_string());
+                break;
+            }
+        }
+
+        // A Rust quote that doesn't close within a char literal is a lifetime
+        let rust_lifetime = lang == Language::Rust
+            && c == '\''
+            && chars.get(i + 1) != Some(&'\\')
+            && chars.get(i + 2) != Some(&'\'');
+        if (c == '"' || c == '\'' || c == '`') && !rust_lifetime {
+            let end = string_end(&chars, i);
+            let literal: String = chars[i..end].iter().collect();
+            out.push_str(&literal.green().to_string());
+            i = end;
+            continue;
+        }
+
+        if c.is_ascii_digit() && (i == 0 || !is_ident(chars[i - 1])) {
+            let end = scan(&chars, i, |ch| {
+                ch.is_ascii_alphanumeric() || ch == '.' || ch == '_'
+            });
+            let number: String = chars[i..end].iter().collect();
+            out.push_str(&number.yellow().to_string());
+            i = end;
+            continue;
+        }
+
+        if is_ident(c) {
+            let end = scan(&chars, i, is_ident);
+            let word: String = chars[i..end].iter().collect();
+            if lang.keywords().contains(&word.as_str()) {
+                out.push_str(&word.magenta().bold().to_string());
+            } else {
+                out.push_str(&word);
+            }
+            i = end;
+            continue;
+        }
+
+        out.push(c);
+        i += 1;
+    }
+
+    out
+}
+
+fn is_ident(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Returns the index just past the end of a run of chars matching `pred`.
+fn scan(chars: &[char], start: usize, pred: impl Fn(char) -> bool) -> usize {
+    (start..chars.len())
+        .find(|&i| !pred(chars[i]))
+        .unwrap_or(chars.len())
+}
+
+/// Returns the index just past the closing quote, honoring backslash
+/// escapes. Unterminated strings run to the end of the line.
+fn string_end(chars: &[char], start: usize) -> usize {
+    let quote = chars[start];
+    let mut i = start + 1;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 2,
+            ch if ch == quote => return i + 1,
+            _ => i += 1,
+        }
+    }
+    chars.len()
+}
@@ -3,15 +3,31 @@
 //! [`KazeHook`] combines the permission system (from Step 23) with diff
 //! preview generation (Step 24) into a single hook. For write_file and edit
 //! tools, it generates a colored diff preview before prompting the user.
+//! At the terminal prompt, an existing file's change can also be reviewed
+//! hunk by hunk; the hook then writes the accepted hunks itself and tells
+//! the model what was applied instead of running the tool.
+//!
+//! The hook also sees each model request start, which is where a stream's
+//! `model_call` spans for `--profile` begin (see [`crate::profile`]).
 
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
 
-use rig::agent::{PromptHook, ToolCallHookAction};
+use rig::agent::{HookAction, PromptHook, ToolCallHookAction};
 use rig::completion::CompletionModel;
+use rig::message::Message as RigMessage;
+use tokio::sync::{mpsc, oneshot};
 
+use crate::async_fs;
 use crate::diff;
-use crate::permissions::{Permission, PermissionManager, PromptResponse};
+use crate::i18n::tf;
+use crate::permissions::{
+    HunkResponse, Permission, PermissionManager, PermissionRequest, PromptResponse,
+};
+use crate::tools::file_history::FileHistory;
+use crate::tools::multi_edit::{self, MultiEditInput};
+use crate::tools::write_file::WriteFileTool;
+use crate::tools::Tool;
 
 /// Combined hook for permission checks and diff previews.
 ///
@@ -23,6 +39,30 @@ pub struct KazeHook {
     manager: Arc<PermissionManager>,
     /// Project root directory for resolving relative file paths.
     project_root: PathBuf,
+    /// When set, prompts are sent here instead of being read from stdin.
+    /// Shared with every clone, including those made before the channel was
+    /// set (such as the one the `task` tool's subagents use).
+    prompt_tx: Arc<OnceLock<mpsc::UnboundedSender<PermissionRequest>>>,
+    /// Where hunks written by the per-hunk review are recorded for `/undo`.
+    file_history: FileHistory,
+    /// Model requests of the current stream; see [`track_calls`](Self::track_calls).
+    calls: Arc<Mutex<ModelCalls>>,
+}
+
+/// The model requests a hook has seen and the span of the latest, which
+/// lasts until the next begins or the stream ends, covering the tool calls
+/// rig-core runs while reading the response.
+#[derive(Default)]
+struct ModelCalls {
+    count: usize,
+    span: Option<tracing::Span>,
+}
+
+/// A write_file or edit call's effect on an existing file.
+struct FileChange {
+    path: String,
+    old: String,
+    new: String,
 }
 
 impl KazeHook {
@@ -30,49 +70,276 @@ impl KazeHook {
         Self {
             manager,
             project_root,
+            prompt_tx: Arc::new(OnceLock::new()),
+            file_history: FileHistory::default(),
+            calls: Arc::default(),
         }
     }
 
-    /// For write_file and edit tools, generate a diff preview from the args.
-    /// Returns None if args can't be parsed or the tool isn't a file-writing tool.
+    /// The permission manager the hook consults.
+    pub fn permissions(&self) -> &PermissionManager {
+        &self.manager
+    }
+
+    /// The permission manager, for tools that check more than the call.
+    pub fn shared_permissions(&self) -> Arc<PermissionManager> {
+        Arc::clone(&self.manager)
+    }
+
+    /// A clone that counts model requests afresh, for one stream. Other
+    /// clones, like the one the `task` tool's subagents use, keep theirs.
+    pub fn track_calls(&self) -> Self {
+        Self {
+            calls: Arc::default(),
+            ..self.clone()
+        }
+    }
+
+    /// How many model requests have started through this hook.
+    pub fn model_calls(&self) -> usize {
+        self.calls.lock().unwrap_or_else(|e| e.into_inner()).count
+    }
+
+    /// Closes the span of the last model request.
+    pub fn end_model_call(&self) {
+        self.calls.lock().unwrap_or_else(|e| e.into_inner()).span = None;
+    }
+
+    /// Records the hunks the per-hunk review writes in `history`, normally
+    /// the tool registry's.
+    pub fn with_file_history(mut self, history: FileHistory) -> Self {
+        self.file_history = history;
+        self
+    }
+
+    /// Forwards permission prompts and diff previews to `tx`.
     ///
-    /// NOTE: This uses `std::fs::read_to_string` (sync I/O) to read the current
-    /// file contents. This is acceptable for a CLI tool on a single-threaded tokio
-    /// runtime, because file reads are fast and the user is waiting at the terminal
-    /// anyway. For the TUI (Phase 7), consider using `tokio::fs` instead.
-    fn generate_diff(&self, tool_name: &str, args: &str) -> Option<String> {
-        let parsed: serde_json::Value = serde_json::from_str(args).ok()?;
+    /// A hook's channel can only be set once; later calls are ignored.
+    pub fn with_prompt_channel(self, tx: mpsc::UnboundedSender<PermissionRequest>) -> Self {
+        let _ = self.prompt_tx.set(tx);
+        self
+    }
 
-        match tool_name {
-            "write_file" => {
-                let path_str = parsed.get("path")?.as_str()?;
-                let new_content = parsed.get("content")?.as_str()?;
-                let full_path = self.project_root.join(path_str);
+    /// The channel set by [`with_prompt_channel`](Self::with_prompt_channel).
+    pub fn prompt_channel(&self) -> Option<mpsc::UnboundedSender<PermissionRequest>> {
+        self.prompt_tx.get().cloned()
+    }
 
-                if full_path.exists() {
-                    let old_content = std::fs::read_to_string(&full_path).ok()?;
-                    Some(diff::unified_diff(&old_content, new_content, path_str))
-                } else {
-                    Some(diff::new_file_preview(new_content, path_str))
-                }
+    /// For write_file, edit, multi_edit, and apply_patch, generate a diff
+    /// preview from the args.
+    /// Returns None if args can't be parsed or the tool isn't a file-writing tool.
+    ///
+    /// The current file contents are read through [`async_fs`] and the diff
+    /// itself is computed on tokio's blocking pool, so previewing a large
+    /// file never stalls streaming or the TUI's render loop.
+    async fn generate_diff(
+        project_root: PathBuf,
+        tool_name: String,
+        args: String,
+    ) -> Option<String> {
+        let parsed: serde_json::Value = serde_json::from_str(&args).ok()?;
+        if tool_name == "multi_edit" {
+            return Self::multi_edit_diff(project_root, parsed).await;
+        }
+        if tool_name == "apply_patch" {
+            return Some(diff::colorize_patch(parsed.get("patch")?.as_str()?));
+        }
+        if tool_name == "write_file" {
+            let path_str = parsed.get("path")?.as_str()?;
+            let content = parsed.get("content")?.as_str()?;
+            if !async_fs::exists(project_root.join(path_str)).await {
+                return Some(diff::new_file_preview(content, path_str));
             }
+        }
+
+        let change = Self::file_change(&project_root, &tool_name, &args).await?;
+        tokio::task::spawn_blocking(move || {
+            diff::unified_diff(&change.old, &change.new, &change.path)
+        })
+        .await
+        .ok()
+    }
+
+    /// The current and proposed contents of the existing file a write_file
+    /// or edit call changes. None for new files and other tools.
+    async fn file_change(project_root: &Path, tool_name: &str, args: &str) -> Option<FileChange> {
+        let parsed: serde_json::Value = serde_json::from_str(args).ok()?;
+        let path = parsed.get("path")?.as_str()?.to_string();
+        let old = async_fs::read_to_string(project_root.join(&path))
+            .await
+            .ok()?;
+
+        let new = match tool_name {
+            "write_file" => parsed.get("content")?.as_str()?.to_string(),
             "edit" => {
-                let path_str = parsed.get("path")?.as_str()?;
-                let full_path = self.project_root.join(path_str);
                 let old_text = parsed.get("old_text")?.as_str()?;
                 let new_text = parsed.get("new_text")?.as_str()?;
+                // As the edit tool applies it, so accepting every hunk
+                // accepts exactly what the tool will write
+                let replace_all = parsed.get("replace_all").and_then(|v| v.as_bool());
+                if replace_all == Some(true) {
+                    old.replace(old_text, new_text)
+                } else {
+                    old.replacen(old_text, new_text, 1)
+                }
+            }
+            _ => return None,
+        };
+        Some(FileChange { path, old, new })
+    }
+
+    /// Walks the user through `change` hunk by hunk and writes the accepted
+    /// subset. The result tells the model which part of its change was
+    /// applied, in place of the tool's own.
+    async fn apply_selected_hunks(
+        project_root: PathBuf,
+        history: FileHistory,
+        change: FileChange,
+    ) -> ToolCallHookAction {
+        let hunks = diff::hunks(&change.old, &change.new);
+        let (accepted, edited) = match select_hunks(&hunks) {
+            Ok(selected) => selected,
+            Err(e) => return ToolCallHookAction::skip(format!("Per-hunk review failed: {}", e)),
+        };
 
-                // Read the full file, apply the edit, diff the result
-                let original = std::fs::read_to_string(&full_path).ok()?;
-                let modified = original.replacen(old_text, new_text, 1);
-                Some(diff::unified_diff(&original, &modified, path_str))
+        let content = diff::apply_hunks(&change.old, &accepted);
+        if content == change.new {
+            return ToolCallHookAction::cont();
+        }
+        if content == change.old {
+            return ToolCallHookAction::skip(format!(
+                "User rejected every hunk of the change to {}",
+                change.path
+            ));
+        }
+
+        let input = serde_json::json!({ "path": change.path, "content": content });
+        match WriteFileTool::new(project_root, history)
+            .execute(input)
+            .await
+        {
+            Ok(result) if !result.is_error => ToolCallHookAction::skip(format!(
+                "User applied {} of {} hunks of the change to {}{}; the rest were rejected. \
+                 Read the file again before changing it further.",
+                accepted.len(),
+                hunks.len(),
+                change.path,
+                if edited > 0 {
+                    format!(" after editing {} of them", edited)
+                } else {
+                    String::new()
+                }
+            )),
+            Ok(result) => ToolCallHookAction::skip(result.content),
+            Err(e) => ToolCallHookAction::skip(format!(
+                "Failed to write the accepted hunks to {}: {}",
+                change.path, e
+            )),
+        }
+    }
+
+    /// Previews a multi_edit batch as one diff per file, applying the edits
+    /// the way the tool will. Returns None if any edit would not match, since
+    /// the tool then changes nothing.
+    async fn multi_edit_diff(project_root: PathBuf, parsed: serde_json::Value) -> Option<String> {
+        let input: MultiEditInput = serde_json::from_value(parsed).ok()?;
+        let keys: Vec<&str> = input.edits.iter().map(|e| e.path.as_str()).collect();
+        let (paths, file_of) = multi_edit::group_files(&keys);
+
+        let mut originals = Vec::with_capacity(paths.len());
+        for path in &paths {
+            originals.push(
+                async_fs::read_to_string(project_root.join(path))
+                    .await
+                    .ok()?,
+            );
+        }
+        let mut working = originals.clone();
+        multi_edit::apply_edits(&input.edits, &file_of, &mut working).ok()?;
+
+        let paths: Vec<String> = paths.into_iter().map(str::to_string).collect();
+        tokio::task::spawn_blocking(move || {
+            paths
+                .iter()
+                .zip(originals.iter().zip(&working))
+                .map(|(path, (old, new))| diff::unified_diff(old, new, path))
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .await
+        .ok()
+    }
+}
+
+/// Asks about each hunk in turn. Returns the hunks to apply, edited ones
+/// carrying the user's text, and how many were edited.
+fn select_hunks(hunks: &[diff::Hunk]) -> anyhow::Result<(Vec<diff::Hunk>, usize)> {
+    let mut accepted = Vec::new();
+    let mut edited = 0;
+    for (i, hunk) in hunks.iter().enumerate() {
+        eprintln!("\n{}", hunk.display.trim_end());
+        match PermissionManager::prompt_hunk(i + 1, hunks.len())? {
+            HunkResponse::Yes => accepted.push(hunk.clone()),
+            HunkResponse::No => {}
+            HunkResponse::Edit => {
+                let mut hunk = hunk.clone();
+                hunk.new_text = edit_in_editor(&hunk.new_text)?;
+                accepted.push(hunk);
+                edited += 1;
             }
-            _ => None,
+            HunkResponse::Quit => break,
         }
     }
+    Ok((accepted, edited))
+}
+
+/// Opens `text` in `$VISUAL` / `$EDITOR` (default `vi`) and returns the
+/// saved result.
+///
+/// The text goes in a new file with a random name that only the user can
+/// read, so nobody else can plant a link at the path or read the hunk.
+fn edit_in_editor(text: &str) -> anyhow::Result<String> {
+    let id = uuid::Uuid::new_v4().simple().to_string();
+    let path = std::env::temp_dir().join(format!("kaze-hunk-{}.txt", &id[..12]));
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    std::io::Write::write_all(&mut options.open(&path)?, text.as_bytes())?;
+
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    // Through the shell, so an editor with arguments ("code --wait") works
+    let status = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$1\"", editor))
+        .arg("sh")
+        .arg(&path)
+        .status();
+    let edited = std::fs::read_to_string(&path);
+    let _ = std::fs::remove_file(&path);
+
+    let status = status?;
+    if !status.success() {
+        anyhow::bail!("{} exited with {}", editor, status);
+    }
+    Ok(edited?)
 }
 
 impl<M: CompletionModel> PromptHook<M> for KazeHook {
+    fn on_completion_call(
+        &self,
+        _prompt: &RigMessage,
+        _history: &[RigMessage],
+    ) -> impl std::future::Future<Output = HookAction> + Send {
+        let mut calls = self.calls.lock().unwrap_or_else(|e| e.into_inner());
+        calls.count += 1;
+        // Replacing the previous call's span closes it
+        calls.span = Some(tracing::info_span!("model_call", n = calls.count));
+        async { HookAction::cont() }
+    }
+
     fn on_tool_call(
         &self,
         tool_name: &str,
@@ -81,15 +348,24 @@ impl<M: CompletionModel> PromptHook<M> for KazeHook {
         args: &str,
     ) -> impl std::future::Future<Output = ToolCallHookAction> + Send {
         let permission = self.manager.check(tool_name, args);
+        let oversized = self.manager.oversized_write(tool_name, args);
+        let read_only = self.manager.read_only_denies(tool_name, args);
         let tool_name = tool_name.to_string();
         let args = args.to_string();
         let manager = self.manager.clone();
-
-        // Generate diff before entering the async block (needs &self)
-        let diff_output = self.generate_diff(&tool_name, &args);
+        let prompt_tx = self.prompt_tx.get().cloned();
+        let project_root = self.project_root.clone();
+        let file_history = self.file_history.clone();
 
         async move {
             // Step 1: Check if the tool is outright denied
+            if read_only {
+                return ToolCallHookAction::skip(format!(
+                    "Tool '{}' is unavailable: kaze is in read-only mode, so answer from \
+                     what you can read",
+                    tool_name,
+                ));
+            }
             if permission == Permission::Deny {
                 return ToolCallHookAction::skip(format!(
                     "Tool '{}' is disabled by user configuration",
@@ -97,17 +373,91 @@ impl<M: CompletionModel> PromptHook<M> for KazeHook {
                 ));
             }
 
+            // A preview of megabytes of content would bury the prompt
+            let diff_output = match oversized {
+                Some((size, ceiling)) => Some(tf(
+                    "permission.oversized_write",
+                    &[
+                        ("size", &crate::tokens::format_number(size)),
+                        ("ceiling", &crate::tokens::format_number(ceiling)),
+                    ],
+                )),
+                None => {
+                    Self::generate_diff(project_root.clone(), tool_name.clone(), args.clone()).await
+                }
+            };
+
+            // Frontends that own the terminal render the diff and prompt themselves
+            if let Some(tx) = prompt_tx {
+                if permission != Permission::Ask && diff_output.is_none() {
+                    return ToolCallHookAction::cont();
+                }
+                let needs_answer = permission == Permission::Ask;
+                let (reply, answer) = oneshot::channel();
+                let request = PermissionRequest {
+                    tool_name: tool_name.clone(),
+                    args: args.clone(),
+                    diff: diff_output,
+                    reply: needs_answer.then_some(reply),
+                    question: None,
+                };
+                if tx.send(request).is_err() {
+                    return ToolCallHookAction::skip("Permission prompt is unavailable");
+                }
+                if !needs_answer {
+                    // Diff preview only
+                    return ToolCallHookAction::cont();
+                }
+                return match answer.await {
+                    Ok(PromptResponse::Yes) => ToolCallHookAction::cont(),
+                    Ok(PromptResponse::Always) => {
+                        manager.set_session_override(&tool_name, &args, Permission::Allow);
+                        ToolCallHookAction::cont()
+                    }
+                    // Frontends answer for the whole change
+                    Ok(PromptResponse::No | PromptResponse::PerHunk) | Err(_) => {
+                        ToolCallHookAction::skip(format!(
+                            "User rejected the change for '{}'",
+                            tool_name
+                        ))
+                    }
+                };
+            }
+
             // Step 2: Show diff preview (always, for write_file and edit)
             if let Some(ref diff_str) = diff_output {
                 eprintln!("\n{}", diff_str);
             }
 
-            // Step 3: If permission is Ask, prompt the user
+            // Step 3: If permission is Ask, prompt the user (unless unattended)
             if permission == Permission::Ask {
-                match PermissionManager::prompt_user(&tool_name, &args) {
+                if let Some(approve) = manager.unattended_approval() {
+                    return if approve {
+                        ToolCallHookAction::cont()
+                    } else {
+                        ToolCallHookAction::skip(format!(
+                            "Tool '{}' needs approval; rerun with --auto-approve to allow it",
+                            tool_name
+                        ))
+                    };
+                }
+                let change = match diff_output {
+                    Some(_) => Self::file_change(&project_root, &tool_name, &args).await,
+                    None => None,
+                };
+                match PermissionManager::prompt_user(&tool_name, &args, change.is_some()) {
                     Ok(PromptResponse::Yes) => ToolCallHookAction::cont(),
+                    Ok(PromptResponse::PerHunk) => match change {
+                        Some(change) => {
+                            Self::apply_selected_hunks(project_root, file_history, change).await
+                        }
+                        None => ToolCallHookAction::skip(format!(
+                            "User rejected the change for '{}'",
+                            tool_name
+                        )),
+                    },
                     Ok(PromptResponse::Always) => {
-                        manager.set_session_override(&tool_name, Permission::Allow);
+                        manager.set_session_override(&tool_name, &args, Permission::Allow);
                         ToolCallHookAction::cont()
                     }
                     Ok(PromptResponse::No) => ToolCallHookAction::skip(format!(
@@ -125,3 +475,32 @@ impl<M: CompletionModel> PromptHook<M> for KazeHook {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_hunk_review_of_replace_all_covers_every_match() {
+        let dir = std::env::temp_dir().join(format!("kaze_test_hunk_all_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let filler = "keep\n".repeat(10);
+        std::fs::write(dir.join("f.txt"), format!("old\n{}old\n", filler)).unwrap();
+
+        let args = r#"{"path":"f.txt","old_text":"old","new_text":"new","replace_all":true}"#;
+        let change = KazeHook::file_change(&dir, "edit", args).await.unwrap();
+        assert_eq!(change.new, format!("new\n{}new\n", filler));
+
+        // Accepting every hunk is the tool's own change; any less is not
+        let hunks = diff::hunks(&change.old, &change.new);
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(diff::apply_hunks(&change.old, &hunks), change.new);
+        assert_ne!(diff::apply_hunks(&change.old, &hunks[..1]), change.new);
+
+        let args = r#"{"path":"f.txt","old_text":"old","new_text":"new"}"#;
+        let change = KazeHook::file_change(&dir, "edit", args).await.unwrap();
+        assert_eq!(change.new, format!("new\n{}old\n", filler));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,379 @@
+//! Translatable user-facing strings.
+//!
+//! Prompts, banners, and command help are looked up by key with [`t`] (or
+//! [`tf`], which fills `{name}` placeholders). The built-in English catalog
+//! is the default; a translation bundle at `locales/<locale>.toml` in the
+//! config directory overrides any subset of it. Bundles may use flat keys
+//! (`"chat.goodbye" = "…"`) or tables (`[chat]` / `goodbye = "…"`), and
+//! missing keys fall back to English.
+//!
+//! The locale comes from the `locale` config key, then `KAZE_LANG`, then
+//! the usual `LC_ALL` / `LC_MESSAGES` / `LANG` variables. A regional locale
+//! such as `pt_BR` also tries `pt.toml`.
+//!
+//! The one-letter answers to prompts (`y`/`n`/`a`/`p`, `e`/`q`, `s`/`c`) are not
+//! translated, so translations should keep them in the prompt text.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::config::Config;
+
+/// The English catalog: every key and its default text.
+const EN: &[(&str, &str)] = &[
+    ("chat.exit_hint", "(Ctrl+D to exit)"),
+    ("chat.resuming", "resuming"),
+    ("chat.unknown_command", "Unknown command: {command}"),
+    ("chat.goodbye", "goodbye."),
+    ("error.label", "error:"),
+    (
+        "ask.no_prompt",
+        "No prompt provided. Usage: kaze ask \"your question here\"",
+    ),
+    (
+        "run.no_task",
+        "No task provided. Usage: kaze run \"your task here\"",
+    ),
+    (
+        "root.too_broad",
+        "kaze is running in {dir}, {what}, where file tools would search everything. \
+         cd into a project, or pass --allow-broad-root.",
+    ),
+    (
+        "run.unknown_template",
+        "No template named '{name}' in [templates]. Defined: {defined}",
+    ),
+    (
+        "session.stale_model",
+        "Session was recorded with model {recorded} but {configured} is configured.",
+    ),
+    ("session.continuing_with", "Continuing with {model}."),
+    (
+        "session.model_choice",
+        "Continue with [s]ession model ({recorded}) or [c]onfigured model ({configured})? [c]: ",
+    ),
+    (
+        "session.model_unusable",
+        "Cannot use {model}: {error}. Continuing with {configured}.",
+    ),
+    (
+        "permission.prompt",
+        "Tool '{tool}' wants to execute:\n{args}",
+    ),
+    ("permission.choices", "Allow? [y]es / [n]o / [a]lways"),
+    (
+        "permission.oversized_write",
+        "This write is {size} bytes, over the {ceiling}-byte limit ([tools.write_file] confirm_above_bytes); no preview shown.",
+    ),
+    (
+        "permission.choices_hunks",
+        "Allow? [y]es / [n]o / [a]lways / [p]er hunk",
+    ),
+    (
+        "permission.hunk_choices",
+        "Apply hunk {n}/{total}? [y]es / [n]o / [e]dit / [q]uit",
+    ),
+    ("compact.nothing", "Nothing to compact."),
+    (
+        "response.empty",
+        "The model returned an empty response; it was not added to the conversation. \
+Type /retry to ask again.",
+    ),
+    (
+        "response.refusal",
+        "The model declined this request; the exchange was kept out of the conversation. \
+Type /retry to resend it with added context.",
+    ),
+    ("retry.nothing", "Nothing to retry."),
+    (
+        "cost.confirm",
+        "This turn sends about {tokens} tokens of context, {cost} before any output.",
+    ),
+    (
+        "cost.confirm_unpriced",
+        "This turn sends about {tokens} tokens of context.",
+    ),
+    ("cost.not_sent", "Not sent."),
+    (
+        "duplicate.this_session",
+        "You asked something similar earlier in this session:\n  > {question}\n  {answer}",
+    ),
+    (
+        "duplicate.other_session",
+        "You asked something similar in session {id} ({date}):\n  > {question}\n  {answer}\n  The full answer: kaze session export {id} --format md",
+    ),
+    (
+        "duplicate.resend",
+        "Not sent. Send the same message again to ask anyway.",
+    ),
+    (
+        "rewind.done",
+        "Rewound {turns}; {messages} removed from the conversation.",
+    ),
+    ("undo.done", "Undid {tool}: reverted {files}."),
+    ("undo.nothing", "No file changes to undo."),
+    ("expand.nothing", "No tool calls yet."),
+    ("export.done", "Exported the conversation to {path}."),
+    ("shell.attached", "Output attached to your next message."),
+    (
+        "context.refreshed",
+        "Sending changes to {files} edited since the model last saw them.",
+    ),
+    (
+        "context.empty",
+        "No files tracked yet; files the model reads or writes are kept fresh.",
+    ),
+    ("context.cleared", "Stopped tracking files."),
+    ("extract.nothing", "No response to extract code from yet."),
+    (
+        "extract.no_names",
+        "None of the {blocks} in the last response names a file.",
+    ),
+    ("extract.skipped", "Skipping {blocks} without a file name."),
+    ("extract.confirm", "Write {files}?"),
+    ("extract.done", "Wrote {files}. /undo reverts them."),
+    ("extract.cancelled", "Nothing written."),
+    ("copy.usage", "Usage: /copy last|all|code"),
+    (
+        "title.current",
+        "Session title: {title}. Usage: /title <text>, or /title --clear",
+    ),
+    ("title.set", "Session title: {title}"),
+    ("copy.nothing", "Nothing to copy yet."),
+    ("copy.no_code", "The last response has no code blocks."),
+    ("copy.done", "Copied {what} to the clipboard ({method})."),
+    ("todos.empty", "No todo items yet."),
+    ("review.reviewing", "Reviewing {summary}"),
+    (
+        "trust.header",
+        "{path} allows more than your global config does:",
+    ),
+    (
+        "trust.prompt",
+        "Honor these permissions for this project? [y/N]",
+    ),
+    (
+        "trust.ignored",
+        "Ignoring them; the global config's settings apply. Run `kaze config trust` in the project to approve them.",
+    ),
+    (
+        "sessions.auto_pruned",
+        "Deleted {count} old sessions ([sessions] policy).",
+    ),
+    (
+        "sessions.auto_archived",
+        "Archived {count} old sessions ([sessions] policy).",
+    ),
+    ("sessions.prune_failed", "Session pruning failed: {error}"),
+    (
+        "offline.switched",
+        "Can't reach {host}; switched to {model} (offline_fallback). /model switches back.",
+    ),
+    (
+        "offline.unusable",
+        "The offline fallback {model} can't be used: {error}",
+    ),
+    (
+        "model.no_tools",
+        "{model} doesn't support tool use; chatting without tools.",
+    ),
+    (
+        "model.tools_refused",
+        "{model} refused the request for carrying tools; retrying, and chatting without tools from now on.",
+    ),
+    (
+        "model.no_seed",
+        "{provider} doesn't take a sampling seed; only the temperature is applied.",
+    ),
+    (
+        "model.no_vision",
+        "{model} doesn't accept images; use a vision model or drop --image.",
+    ),
+    (
+        "attach.attached",
+        "Attached {summary}; it will be sent with your next message.",
+    ),
+    (
+        "attach.none",
+        "No command to refresh. Start the chat with --attach-cmd \"<command>\".",
+    ),
+    ("help.title", "Commands:"),
+    ("help.history", "show conversation history"),
+    ("help.clear", "clear conversation"),
+    ("help.compact", "summarize old context to free tokens"),
+    ("help.model", "switch models for the rest of the session"),
+    ("help.view", "show a file with line numbers"),
+    (
+        "help.prompt",
+        "show the composed system prompt and its layers",
+    ),
+    ("help.rate", "rate the last response for later review"),
+    (
+        "help.review",
+        "review uncommitted changes, staged changes, or a file",
+    ),
+    (
+        "help.retry",
+        "regenerate the last answer, optionally with another model or an added instruction",
+    ),
+    ("help.rewind", "drop the last n turns (default 1) from the conversation"),
+    ("help.undo", "revert the last file change made by a tool"),
+    (
+        "help.expand",
+        "show the full arguments and results of the last n tool calls (default 1)",
+    ),
+    (
+        "help.copy",
+        "copy the last response, the transcript, or the last response's code blocks",
+    ),
+    (
+        "help.context",
+        "list the files whose changes are sent each turn, or stop tracking them",
+    ),
+    (
+        "help.export",
+        "write the conversation as Markdown (default: kaze-session-<id>.md)",
+    ),
+    (
+        "help.extract",
+        "write the last response's code blocks to the files they name",
+    ),
+    (
+        "help.refresh_cmd",
+        "rerun the --attach-cmd command and attach its output",
+    ),
+    (
+        "help.auto",
+        "run tool calls without asking for the rest of the session",
+    ),
+    (
+        "auto.banner",
+        "⚠ AUTO-APPROVE IS ON: tool calls run without asking, file writes and shell commands included. Denied tools, git pushes, and read-only mode still hold.",
+    ),
+    ("auto.off_hint", "Type `/auto off` to ask again."),
+    ("auto.off", "Auto-approve is off; tool calls ask again."),
+    ("auto.status_off", "Auto-approve is off. `/auto on` runs tool calls without asking."),
+    (
+        "help.title_cmd",
+        "show or set the session title (--clear to derive it again)",
+    ),
+    ("help.help", "show this help"),
+    ("help.exit", "exit"),
+    ("help.custom", "Custom commands:"),
+    (
+        "custom.shadowed",
+        "Custom command {path} is never used: /{name} is a built-in command. Rename the file to use it.",
+    ),
+];
+
+/// Translations loaded from the active bundle, if any.
+static OVERRIDES: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+/// Selects the locale and loads its bundle.
+///
+/// Called by [`Config::load`] with the configured `locale`. Only the first
+/// call has an effect; strings looked up before any call use the
+/// environment's locale.
+pub fn init(locale: Option<&str>) {
+    OVERRIDES.get_or_init(|| load_overrides(locale));
+}
+
+/// Returns the text for `key` in the active locale.
+///
+/// Unknown keys are returned as-is, so a typo shows up in the output
+/// rather than as an empty string.
+pub fn t(key: &'static str) -> &'static str {
+    let overrides = OVERRIDES.get_or_init(|| load_overrides(None));
+    if let Some(text) = overrides.get(key) {
+        return text;
+    }
+    EN.iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, text)| *text)
+        .unwrap_or(key)
+}
+
+/// Returns the text for `key` with each `{name}` placeholder replaced.
+pub fn tf(key: &'static str, args: &[(&str, &str)]) -> String {
+    let mut text = t(key).to_string();
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), value);
+    }
+    text
+}
+
+/// Resolves the locale name, or `None` for English.
+fn detect_locale(configured: Option<&str>) -> Option<String> {
+    let raw = configured.map(str::to_string).or_else(|| {
+        ["KAZE_LANG", "LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty())
+    })?;
+    // "de_DE.UTF-8@euro" -> "de_DE"
+    let name = raw.split(['.', '@']).next().unwrap_or_default().trim();
+    if name.is_empty() || name == "C" || name == "POSIX" || name == "en" || name.starts_with("en_")
+    {
+        None
+    } else {
+        Some(name.to_string())
+    }
+}
+
+/// Bundle files to try for a locale, most specific first.
+fn bundle_candidates(locale: &str) -> Vec<PathBuf> {
+    let Ok(dir) = Config::config_dir() else {
+        return Vec::new();
+    };
+    let dir = dir.join("locales");
+    let mut names = vec![locale.to_string()];
+    if let Some((language, _)) = locale.split_once(['_', '-']) {
+        names.push(language.to_string());
+    }
+    names
+        .into_iter()
+        .map(|name| dir.join(format!("{}.toml", name)))
+        .collect()
+}
+
+fn load_overrides(configured: Option<&str>) -> HashMap<String, String> {
+    let mut overrides = HashMap::new();
+    let Some(locale) = detect_locale(configured) else {
+        return overrides;
+    };
+    let Some(path) = bundle_candidates(&locale).into_iter().find(|p| p.is_file()) else {
+        return overrides;
+    };
+
+    let parsed = std::fs::read_to_string(&path)
+        .map_err(anyhow::Error::from)
+        .and_then(|text| Ok(text.parse::<toml::Table>()?));
+    match parsed {
+        Ok(table) => flatten("", &table, &mut overrides),
+        Err(e) => eprintln!(
+            "warning: ignoring translation bundle {}: {}",
+            path.display(),
+            e
+        ),
+    }
+    overrides
+}
+
+/// Collects string values from nested tables under dotted keys.
+fn flatten(prefix: &str, table: &toml::Table, out: &mut HashMap<String, String>) {
+    for (key, value) in table {
+        let full = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match value {
+            toml::Value::String(text) => {
+                out.insert(full, text.clone());
+            }
+            toml::Value::Table(inner) => flatten(&full, inner, out),
+            _ => {}
+        }
+    }
+}
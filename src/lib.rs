@@ -0,0 +1,58 @@
+//! kaze, a memory-minimal AI coding agent for the terminal, as a library.
+//!
+//! The `kaze` binary is a thin CLI over this crate. Other Rust programs
+//! (editor plugins, bots) can embed the same building blocks:
+//!
+//! - [`Config`] — layered TOML configuration (global + project)
+//! - [`Provider`] and [`resolve_model`] — LLM clients behind one interface
+//! - [`ToolRegistry`] — built-in tools exposed to the model
+//! - [`Session`] — JSONL-backed conversation persistence
+//! - [`agent_loop`] — a full tool-augmented turn
+//! - [`ChatEngine`] — session-aware turn orchestration used by the REPL and TUI
+//!
+//! Items re-exported at the crate root form the stable API; the modules
+//! themselves are public for advanced use but may change between releases.
+
+pub mod agent;
+pub mod async_fs;
+pub mod audit;
+pub mod chat;
+pub mod clipboard;
+pub mod compaction;
+pub mod config;
+pub mod constants;
+pub mod context_cache;
+pub mod diff;
+pub mod format;
+pub mod highlight;
+pub mod hooks;
+pub mod i18n;
+pub mod message;
+pub mod metrics;
+pub mod models;
+pub mod output;
+pub mod permissions;
+pub mod profile;
+pub mod provider;
+pub mod report;
+pub mod response_cache;
+pub mod schema;
+pub mod session;
+pub mod stats;
+pub mod symbols;
+pub mod terminal;
+pub mod tokens;
+pub mod tools;
+pub mod tui;
+pub mod usage;
+
+pub use agent::agent_loop;
+pub use chat::ChatEngine;
+pub use config::Config;
+pub use hooks::KazeHook;
+pub use message::{Message, Role};
+pub use output::{Renderer, StdoutRenderer};
+pub use permissions::PermissionManager;
+pub use provider::{resolve_model, ModelSelection, Provider};
+pub use session::Session;
+pub use tools::{Tool, ToolRegistry, ToolResult};
@@ -1,37 +1,27 @@
 //! Entry point for kaze, a memory-minimal AI coding agent for the terminal.
 //!
 //! This binary loads environment variables, parses CLI arguments via [`cli`],
-//! and dispatches to the appropriate subcommand handler.
+//! and dispatches to the appropriate subcommand handler. All agent logic
+//! lives in the `kaze` library crate.
 
-mod agent;
-mod chat;
 mod cli;
-mod compaction;
-mod config;
-mod constants;
-mod diff;
-mod format;
-mod hooks;
-mod message;
-mod models;
-mod output;
-mod permissions;
-mod provider;
-mod session;
-mod tokens;
-mod tools;
-mod tui;
 
 use anyhow::Result;
 
 /// Runs the kaze CLI.
 ///
-/// Loads `.env` files (silently ignored if absent), parses command-line
-/// arguments into a [`cli::Cli`] struct, and dispatches the chosen
-/// subcommand via [`cli::run`].
+/// Installs the terminal-restoring panic hook, loads `.env` files (silently
+/// ignored if absent), parses command-line arguments into a [`cli::Cli`]
+/// struct, and dispatches the chosen subcommand via [`cli::run`]. An error
+/// that ends the command is recorded in the log `kaze report` bundles.
 #[tokio::main(flavor = "current_thread")]
 async fn main() -> Result<()> {
+    kaze::terminal::install_panic_hook();
     dotenvy::dotenv().ok();
     let cli = cli::parse();
-    cli::run(cli).await
+    let result = cli::run(cli).await;
+    if let Err(ref e) = result {
+        kaze::report::log_event("error", &format!("{:#}", e));
+    }
+    result
 }
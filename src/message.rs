@@ -5,6 +5,9 @@
 //! converted to provider-specific formats (e.g. rig-core's `Message`) when
 //! sent to the LLM.
 
+use anyhow::{Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
@@ -22,8 +25,7 @@ pub struct ToolCall {
 /// A single message in a conversation.
 ///
 /// Contains a [`Role`] indicating who produced the message and [`Content`]
-/// representing the message body. The `Content` enum is `untagged` for serde
-/// to support future multimodal payloads.
+/// representing the message body, which may include images.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Message {
     pub role: Role,
@@ -32,6 +34,9 @@ pub struct Message {
     pub tool_calls: Vec<ToolCall>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub tool_call_id: Option<String>,
+    /// Set when the response was cut off (Ctrl+C, shutdown) before it finished.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub incomplete: bool,
 }
 
 /// The role of a message sender in the conversation.
@@ -44,11 +49,91 @@ pub enum Role {
     Tool,
 }
 
-/// Message content, currently text-only but structured for future multimodal support.
+/// Message content: plain text, or text and images for vision models.
+///
+/// Untagged, so text-only messages keep serializing as a bare string.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum Content {
     Text(String),
+    Parts(Vec<ContentPart>),
+}
+
+/// One part of a multimodal message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ContentPart {
+    Text { text: String },
+    Image(ImagePart),
+}
+
+/// An image attached to a user message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImagePart {
+    pub source: ImageSource,
+    /// MIME type, e.g. `image/png`; required for base64 data.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub media_type: Option<String>,
+}
+
+/// Where an image's bytes come from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageSource {
+    /// Base64-encoded image data, stored in the session.
+    Base64(String),
+    /// An `http(s)` URL the provider fetches.
+    Url(String),
+}
+
+impl ImagePart {
+    /// Builds an image from a file path, an `http(s)` URL, or a
+    /// `data:<mime>;base64,...` URI.
+    ///
+    /// Files are read and embedded as base64; their type comes from the
+    /// extension (png, jpg/jpeg, gif, or webp).
+    pub fn parse(spec: &str) -> Result<Self> {
+        if spec.starts_with("http://") || spec.starts_with("https://") {
+            return Ok(Self {
+                source: ImageSource::Url(spec.to_string()),
+                media_type: media_type_for(spec).map(str::to_string),
+            });
+        }
+        if let Some(uri) = spec.strip_prefix("data:") {
+            let (media_type, data) = uri
+                .split_once(";base64,")
+                .context("Only base64 data URIs are supported (data:<mime>;base64,...)")?;
+            return Ok(Self {
+                source: ImageSource::Base64(data.to_string()),
+                media_type: Some(media_type.to_string()),
+            });
+        }
+
+        let media_type = media_type_for(spec).with_context(|| {
+            format!(
+                "Unsupported image type: {} (use png, jpg, gif, or webp)",
+                spec
+            )
+        })?;
+        let bytes =
+            std::fs::read(spec).with_context(|| format!("Failed to read image {}", spec))?;
+        Ok(Self {
+            source: ImageSource::Base64(BASE64.encode(bytes)),
+            media_type: Some(media_type.to_string()),
+        })
+    }
+}
+
+/// The image MIME type implied by a path's extension.
+fn media_type_for(path: &str) -> Option<&'static str> {
+    let ext = path.rsplit('.').next()?.to_lowercase();
+    match ext.as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "webp" => Some("image/webp"),
+        _ => None,
+    }
 }
 
 impl Message {
@@ -58,6 +143,20 @@ impl Message {
             content: Content::Text(text.into()),
             tool_calls: Vec::new(),
             tool_call_id: None,
+            incomplete: false,
+        }
+    }
+    /// A user message with images after the text. Without images this is
+    /// the same as [`Message::user`].
+    pub fn user_with_images(text: impl Into<String>, images: Vec<ImagePart>) -> Self {
+        if images.is_empty() {
+            return Self::user(text);
+        }
+        let mut parts = vec![ContentPart::Text { text: text.into() }];
+        parts.extend(images.into_iter().map(ContentPart::Image));
+        Self {
+            content: Content::Parts(parts),
+            ..Self::user("")
         }
     }
     pub fn assistant(text: impl Into<String>) -> Self {
@@ -66,6 +165,7 @@ impl Message {
             content: Content::Text(text.into()),
             tool_calls: Vec::new(),
             tool_call_id: None,
+            incomplete: false,
         }
     }
     pub fn system(text: impl Into<String>) -> Self {
@@ -74,14 +174,40 @@ impl Message {
             content: Content::Text(text.into()),
             tool_calls: Vec::new(),
             tool_call_id: None,
+            incomplete: false,
         }
     }
+    /// Marks this message as cut off before the response finished.
+    pub fn mark_incomplete(mut self) -> Self {
+        self.incomplete = true;
+        self
+    }
+    /// The message text. For multimodal content, the first text part.
     pub fn text(&self) -> &str {
         match &self.content {
             Content::Text(s) => s,
+            Content::Parts(parts) => parts
+                .iter()
+                .find_map(|part| match part {
+                    ContentPart::Text { text } => Some(text.as_str()),
+                    ContentPart::Image(_) => None,
+                })
+                .unwrap_or_default(),
         }
     }
 
+    /// The images attached to this message.
+    pub fn images(&self) -> impl Iterator<Item = &ImagePart> {
+        let parts = match &self.content {
+            Content::Text(_) => &[][..],
+            Content::Parts(parts) => &parts[..],
+        };
+        parts.iter().filter_map(|part| match part {
+            ContentPart::Image(image) => Some(image),
+            ContentPart::Text { .. } => None,
+        })
+    }
+
     // Part of public API, used in future phases
     #[allow(dead_code)]
     /// Creates a tool result message to feed back to the LLM.
@@ -91,6 +217,7 @@ impl Message {
             content: Content::Text(content.into()),
             tool_calls: Vec::new(),
             tool_call_id: Some(tool_call_id.into()),
+            incomplete: false,
         }
     }
 
@@ -98,9 +225,7 @@ impl Message {
     #[allow(dead_code)]
     /// Returns the text content as an owned String.
     pub fn text_content(&self) -> String {
-        match &self.content {
-            Content::Text(s) => s.clone(),
-        }
+        self.text().to_string()
     }
 }
 
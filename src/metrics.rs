@@ -0,0 +1,282 @@
+//! Per-turn performance measurements.
+//!
+//! [`MeteredRenderer`] wraps any [`Renderer`] and timestamps the stream
+//! events passing through it, producing a [`TurnMetrics`] report when the
+//! turn ends: time to first token, total stream duration, and how much of
+//! that was spent running tools versus waiting on the model, plus how many
+//! tools ran, which files they changed, and the provider's token usage. It
+//! also keeps the text streamed
+//! so far, so an interrupted turn can still be saved, and every tool call
+//! in full for `/expand`.
+
+use serde_json::Value;
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use crate::output::{NoticeLevel, RecordedToolCall, Renderer};
+use crate::tokens::format_number;
+use crate::usage::{format_cost, TokenUsage};
+
+/// Tools whose successful results mean a file on disk changed.
+const FILE_TOOLS: &[&str] = &["write_file", "edit", "multi_edit", "apply_patch"];
+
+/// Timing breakdown for a single conversation turn.
+#[derive(Debug, Clone, Default)]
+pub struct TurnMetrics {
+    /// Time from sending the request to the first streamed output (text or
+    /// tool call). `None` if the model produced nothing.
+    pub time_to_first_token: Option<Duration>,
+    /// Wall-clock duration of the whole turn, including tool execution.
+    pub total: Duration,
+    /// Time spent between tool calls and their results. Includes time the
+    /// user spends answering permission prompts.
+    pub tool_time: Duration,
+    /// Number of tool calls the model made.
+    pub tool_calls: usize,
+    /// Names of the tools called, in call order.
+    pub tools: Vec<String>,
+    /// Distinct files successfully written or edited by tools, sorted.
+    pub modified_files: Vec<String>,
+    /// Token usage reported by the provider, if the stream included it.
+    pub usage: Option<TokenUsage>,
+}
+
+impl TurnMetrics {
+    /// Time attributed to the model: the total minus tool time.
+    pub fn model_time(&self) -> Duration {
+        self.total.saturating_sub(self.tool_time)
+    }
+
+    /// One-line summary for `--verbose` output.
+    pub fn summary(&self) -> String {
+        let ttft = self
+            .time_to_first_token
+            .map(format_duration)
+            .unwrap_or_else(|| "-".to_string());
+        format!(
+            "first token {} · total {} · model {} · tools {}",
+            ttft,
+            format_duration(self.total),
+            format_duration(self.model_time()),
+            format_duration(self.tool_time),
+        )
+    }
+
+    /// Compact line shown after each chat turn, e.g.
+    /// `(turn: 12.3s · 4 tool calls · 1 file modified · 8,214 → 9,030 tokens · $0.0412, session $0.38)`.
+    ///
+    /// `tokens_before` and `tokens_after` are conversation sizes around the
+    /// turn; `cost` is the turn's and the running session's cost in dollars,
+    /// when known. Zero counts are left out to keep simple turns short.
+    pub fn turn_line(
+        &self,
+        tokens_before: usize,
+        tokens_after: usize,
+        cost: Option<(f64, f64)>,
+    ) -> String {
+        let mut parts = vec![format!("turn: {:.1}s", self.total.as_secs_f64())];
+        if self.tool_calls > 0 {
+            parts.push(plural(self.tool_calls, "tool call", "tool calls"));
+        }
+        if !self.modified_files.is_empty() {
+            parts.push(plural(
+                self.modified_files.len(),
+                "file modified",
+                "files modified",
+            ));
+        }
+        parts.push(format!(
+            "{} → {} tokens",
+            format_number(tokens_before),
+            format_number(tokens_after)
+        ));
+        if let Some((turn, session)) = cost {
+            parts.push(format!(
+                "{}, session {}",
+                format_cost(turn),
+                format_cost(session)
+            ));
+        }
+        format!("({})", parts.join(" · "))
+    }
+}
+
+/// `n` with the singular or plural noun, e.g. `2 tool calls`.
+pub(crate) fn plural(n: usize, one: &str, many: &str) -> String {
+    format!("{} {}", n, if n == 1 { one } else { many })
+}
+
+/// Formats a duration as milliseconds below one second, seconds above.
+fn format_duration(d: Duration) -> String {
+    if d.as_millis() < 1000 {
+        format!("{}ms", d.as_millis())
+    } else {
+        format!("{:.2}s", d.as_secs_f64())
+    }
+}
+
+/// A [`Renderer`] that forwards everything to an inner renderer while
+/// recording stream timings.
+///
+/// The clock starts when the wrapper is created, so construct it right
+/// before issuing the provider request.
+pub struct MeteredRenderer<'a> {
+    inner: &'a mut dyn Renderer,
+    started: Instant,
+    first_token: Option<Instant>,
+    tool_started: Option<Instant>,
+    tool_time: Duration,
+    tools: Vec<String>,
+    /// Path arguments of the file tool currently running, if any.
+    pending_paths: Vec<String>,
+    modified: HashSet<String>,
+    usage: Option<TokenUsage>,
+    text: String,
+    calls: Vec<RecordedToolCall>,
+}
+
+impl<'a> MeteredRenderer<'a> {
+    pub fn new(inner: &'a mut dyn Renderer) -> Self {
+        Self {
+            inner,
+            started: Instant::now(),
+            first_token: None,
+            tool_started: None,
+            tool_time: Duration::ZERO,
+            tools: Vec::new(),
+            pending_paths: Vec::new(),
+            modified: HashSet::new(),
+            usage: None,
+            text: String::new(),
+            calls: Vec::new(),
+        }
+    }
+
+    /// Assistant text streamed so far.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The tool calls made so far with their full arguments and results.
+    pub fn take_tool_calls(&mut self) -> Vec<RecordedToolCall> {
+        std::mem::take(&mut self.calls)
+    }
+
+    /// Stops the clock and returns the collected measurements.
+    pub fn finish(self) -> TurnMetrics {
+        let mut modified_files: Vec<String> = self.modified.into_iter().collect();
+        modified_files.sort();
+        TurnMetrics {
+            time_to_first_token: self.first_token.map(|t| t - self.started),
+            total: self.started.elapsed(),
+            tool_time: self.tool_time,
+            tool_calls: self.tools.len(),
+            tools: self.tools,
+            modified_files,
+            usage: self.usage,
+        }
+    }
+
+    fn mark_first_output(&mut self) {
+        if self.first_token.is_none() {
+            self.first_token = Some(Instant::now());
+        }
+    }
+}
+
+/// The files a file tool call targets: its `path` argument, each
+/// `edits[].path` for `multi_edit`, or the headers of an `apply_patch` diff.
+fn file_paths(args: &Value) -> Vec<String> {
+    if let Some(patch) = args.get("patch").and_then(Value::as_str) {
+        return crate::tools::apply_patch::patch_paths(patch);
+    }
+    if let Some(edits) = args.get("edits").and_then(Value::as_array) {
+        return edits
+            .iter()
+            .filter_map(|e| e.get("path").and_then(Value::as_str))
+            .map(str::to_string)
+            .collect();
+    }
+    args.get("path")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .into_iter()
+        .collect()
+}
+
+impl Renderer for MeteredRenderer<'_> {
+    fn render_token(&mut self, token: &str) {
+        self.mark_first_output();
+        self.text.push_str(token);
+        self.inner.render_token(token);
+    }
+
+    fn render_done(&mut self) {
+        self.inner.render_done();
+    }
+
+    fn flush(&mut self) {
+        self.inner.flush();
+    }
+
+    fn render_error(&mut self, err: &str) {
+        self.inner.render_error(err);
+    }
+
+    fn tool_start(&mut self, name: &str, args: &Value) {
+        self.mark_first_output();
+        self.tool_started = Some(Instant::now());
+        self.tools.push(name.to_string());
+        self.calls.push(RecordedToolCall {
+            name: name.to_string(),
+            arguments: args.clone(),
+            result: None,
+        });
+        self.pending_paths = if FILE_TOOLS.contains(&name) {
+            file_paths(args)
+        } else {
+            Vec::new()
+        };
+        self.inner.tool_start(name, args);
+    }
+
+    fn tool_result(&mut self, name: &str, result: &str) {
+        if let Some(start) = self.tool_started.take() {
+            self.tool_time += start.elapsed();
+        }
+        // Errors and permission denials come back as results too; only the
+        // tools' success messages mean the file was written
+        let paths = std::mem::take(&mut self.pending_paths);
+        if ["Wrote ", "Edited ", "Patched "]
+            .iter()
+            .any(|prefix| result.starts_with(prefix))
+        {
+            self.modified.extend(paths);
+        }
+        if let Some(call) = self
+            .calls
+            .iter_mut()
+            .rev()
+            .find(|c| c.name == name && c.result.is_none())
+        {
+            call.result = Some(result.to_string());
+        }
+        self.inner.tool_result(name, result);
+    }
+
+    fn warn(&mut self, message: &str) {
+        self.inner.warn(message);
+    }
+
+    fn notice(&mut self, level: NoticeLevel, message: &str) {
+        self.inner.notice(level, message);
+    }
+
+    fn usage(&mut self, usage: TokenUsage) {
+        // Continuation requests report their usage separately
+        let total = self.usage.get_or_insert_with(TokenUsage::default);
+        total.input_tokens += usage.input_tokens;
+        total.output_tokens += usage.output_tokens;
+        self.inner.usage(usage);
+    }
+}
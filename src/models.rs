@@ -1,8 +1,9 @@
 //! Centralized model registry for kaze.
 //!
-//! Defines known models with their context window sizes. This is the single
-//! source of truth — both `provider.rs` (for model listing) and `tokens.rs`
-//! (for context window lookup) consume from here.
+//! Defines known models with their context window sizes, prices, and
+//! capabilities. This is the single source of truth — `provider.rs` (for
+//! model listing and deciding whether to send tools), `tokens.rs` (for
+//! context window lookup), and cost tracking consume from here.
 
 /// Information about a known LLM model.
 pub struct ModelInfo {
@@ -10,6 +11,85 @@ pub struct ModelInfo {
     pub name: &'static str,
     /// Context window size in tokens.
     pub context_window: usize,
+    /// List price, or `None` if unknown.
+    pub pricing: Option<Pricing>,
+    /// Whether the model accepts tool definitions and makes tool calls.
+    pub supports_tools: bool,
+    /// Whether the model accepts images.
+    pub supports_vision: bool,
+}
+
+/// Token prices in US dollars per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pricing {
+    pub input_per_mtok: f64,
+    pub output_per_mtok: f64,
+}
+
+impl Pricing {
+    /// Local models cost nothing per token.
+    pub const FREE: Pricing = Pricing::per_mtok(0.0, 0.0);
+
+    pub const fn per_mtok(input_per_mtok: f64, output_per_mtok: f64) -> Self {
+        Self {
+            input_per_mtok,
+            output_per_mtok,
+        }
+    }
+
+    /// Dollar cost of a request with the given token counts.
+    pub fn cost(&self, input_tokens: u64, output_tokens: u64) -> f64 {
+        (input_tokens as f64 * self.input_per_mtok + output_tokens as f64 * self.output_per_mtok)
+            / 1_000_000.0
+    }
+}
+
+/// Looks up a known model by name.
+///
+/// OpenRouter names (`anthropic/claude-sonnet-4-6`) fall back to the part
+/// after the last slash, which matches the upstream model, and Ollama tags
+/// (`llama3:8b`) to the name before the colon.
+pub fn model_info(model: &str) -> Option<&'static ModelInfo> {
+    let find = |name: &str| {
+        ANTHROPIC_MODELS
+            .iter()
+            .chain(OPENAI_MODELS)
+            .chain(BEDROCK_MODELS)
+            .chain(OLLAMA_MODELS)
+            .find(|m| m.name == name)
+    };
+    let base = model.rsplit('/').next().unwrap_or(model);
+    find(model)
+        .or_else(|| find(base))
+        .or_else(|| find(base.split(':').next()?))
+}
+
+/// Looks up the list price for a model.
+pub fn pricing_for(model: &str) -> Option<Pricing> {
+    model_info(model)?.pricing
+}
+
+/// Whether tools can be sent to a model. Unknown models are assumed to
+/// support them, as most current ones do.
+pub fn supports_tools(model: &str) -> bool {
+    model_info(model).is_none_or(|m| m.supports_tools)
+}
+
+/// Whether images can be sent to a model. Unknown models are assumed to
+/// accept them and left to the provider to refuse.
+pub fn supports_vision(model: &str) -> bool {
+    model_info(model).is_none_or(|m| m.supports_vision)
+}
+
+/// kaze's model list for a provider, or `None` for providers whose models
+/// are not tracked here (OpenRouter and Ollama serve whatever they host).
+pub fn known_models(provider: &str) -> Option<&'static [ModelInfo]> {
+    match provider {
+        "anthropic" => Some(ANTHROPIC_MODELS),
+        "openai" => Some(OPENAI_MODELS),
+        "bedrock" => Some(BEDROCK_MODELS),
+        _ => None,
+    }
 }
 
 /// Known Anthropic models.
@@ -17,22 +97,37 @@ pub const ANTHROPIC_MODELS: &[ModelInfo] = &[
     ModelInfo {
         name: "claude-opus-4-6",
         context_window: 200_000,
+        pricing: Some(Pricing::per_mtok(5.0, 25.0)),
+        supports_tools: true,
+        supports_vision: true,
     },
     ModelInfo {
         name: "claude-sonnet-4-6",
         context_window: 200_000,
+        pricing: Some(Pricing::per_mtok(3.0, 15.0)),
+        supports_tools: true,
+        supports_vision: true,
     },
     ModelInfo {
         name: "claude-haiku-4-5",
         context_window: 200_000,
+        pricing: Some(Pricing::per_mtok(1.0, 5.0)),
+        supports_tools: true,
+        supports_vision: true,
     },
     ModelInfo {
         name: "claude-sonnet-4-5",
         context_window: 200_000,
+        pricing: Some(Pricing::per_mtok(3.0, 15.0)),
+        supports_tools: true,
+        supports_vision: true,
     },
     ModelInfo {
         name: "claude-opus-4",
         context_window: 200_000,
+        pricing: Some(Pricing::per_mtok(15.0, 75.0)),
+        supports_tools: true,
+        supports_vision: true,
     },
 ];
 
@@ -41,34 +136,100 @@ pub const OPENAI_MODELS: &[ModelInfo] = &[
     ModelInfo {
         name: "gpt-5.2",
         context_window: 1_047_576,
+        pricing: Some(Pricing::per_mtok(1.75, 14.0)),
+        supports_tools: true,
+        supports_vision: true,
     },
     ModelInfo {
         name: "gpt-5-mini",
         context_window: 1_047_576,
+        pricing: Some(Pricing::per_mtok(0.25, 2.0)),
+        supports_tools: true,
+        supports_vision: true,
     },
     ModelInfo {
         name: "gpt-5-nano",
         context_window: 1_047_576,
+        pricing: Some(Pricing::per_mtok(0.05, 0.4)),
+        supports_tools: true,
+        supports_vision: true,
     },
     ModelInfo {
         name: "gpt-4.1",
         context_window: 1_047_576,
+        pricing: Some(Pricing::per_mtok(2.0, 8.0)),
+        supports_tools: true,
+        supports_vision: true,
     },
     ModelInfo {
         name: "gpt-4.1-mini",
         context_window: 1_047_576,
+        pricing: Some(Pricing::per_mtok(0.4, 1.6)),
+        supports_tools: true,
+        supports_vision: true,
     },
     ModelInfo {
         name: "gpt-4.1-nano",
         context_window: 1_047_576,
+        pricing: Some(Pricing::per_mtok(0.1, 0.4)),
+        supports_tools: true,
+        supports_vision: true,
     },
     ModelInfo {
         name: "o3",
         context_window: 200_000,
+        pricing: Some(Pricing::per_mtok(2.0, 8.0)),
+        supports_tools: true,
+        supports_vision: true,
     },
     ModelInfo {
         name: "o4-mini",
         context_window: 200_000,
+        pricing: Some(Pricing::per_mtok(1.1, 4.4)),
+        supports_tools: true,
+        supports_vision: true,
+    },
+];
+
+/// Claude models on AWS Bedrock.
+///
+/// Claude 4 models are served through cross-region inference profiles
+/// (the `us.` prefix); swap it for `eu.` or `apac.` to match your region.
+pub const BEDROCK_MODELS: &[ModelInfo] = &[
+    ModelInfo {
+        name: "us.anthropic.claude-sonnet-4-5-20250929-v1:0",
+        context_window: 200_000,
+        pricing: Some(Pricing::per_mtok(3.0, 15.0)),
+        supports_tools: true,
+        supports_vision: true,
+    },
+    ModelInfo {
+        name: "us.anthropic.claude-haiku-4-5-20251001-v1:0",
+        context_window: 200_000,
+        pricing: Some(Pricing::per_mtok(1.0, 5.0)),
+        supports_tools: true,
+        supports_vision: true,
+    },
+    ModelInfo {
+        name: "us.anthropic.claude-opus-4-1-20250805-v1:0",
+        context_window: 200_000,
+        pricing: Some(Pricing::per_mtok(15.0, 75.0)),
+        supports_tools: true,
+        supports_vision: true,
+    },
+    ModelInfo {
+        name: "us.anthropic.claude-sonnet-4-20250514-v1:0",
+        context_window: 200_000,
+        pricing: Some(Pricing::per_mtok(3.0, 15.0)),
+        supports_tools: true,
+        supports_vision: true,
+    },
+    ModelInfo {
+        name: "anthropic.claude-3-7-sonnet-20250219-v1:0",
+        context_window: 200_000,
+        pricing: Some(Pricing::per_mtok(3.0, 15.0)),
+        supports_tools: true,
+        supports_vision: true,
     },
 ];
 
@@ -79,21 +240,36 @@ pub const OLLAMA_MODELS: &[ModelInfo] = &[
     ModelInfo {
         name: "llama3",
         context_window: 8_192,
+        pricing: Some(Pricing::FREE),
+        supports_tools: false,
+        supports_vision: false,
     },
     ModelInfo {
         name: "llama3:70b",
         context_window: 8_192,
+        pricing: Some(Pricing::FREE),
+        supports_tools: false,
+        supports_vision: false,
     },
     ModelInfo {
         name: "codellama",
         context_window: 16_384,
+        pricing: Some(Pricing::FREE),
+        supports_tools: false,
+        supports_vision: false,
     },
     ModelInfo {
         name: "mistral",
         context_window: 32_768,
+        pricing: Some(Pricing::FREE),
+        supports_tools: true,
+        supports_vision: false,
     },
     ModelInfo {
         name: "mixtral",
         context_window: 32_768,
+        pricing: Some(Pricing::FREE),
+        supports_tools: true,
+        supports_vision: false,
     },
 ];
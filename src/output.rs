@@ -4,9 +4,85 @@
 //! layer. [`StdoutRenderer`] prints tokens directly to the terminal; a future
 //! `TuiRenderer` (Phase 7) will render to ratatui widgets instead.
 
+use anyhow::{Context, Result};
 use colored::Colorize;
+use serde::Serialize;
 use serde_json::Value;
+use std::fs::{File, OpenOptions};
 use std::io::{self, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::constants::{
+    STREAM_BATCH_WINDOW_MS, TOOL_ARGS_DISPLAY_CHARS, TOOL_RESULT_DISPLAY_CHARS,
+};
+use crate::usage::TokenUsage;
+
+/// Set by [`set_accessible`]; read wherever output would rely on color,
+/// symbols, or cursor movement.
+static ACCESSIBLE: AtomicBool = AtomicBool::new(false);
+
+/// Switches to screen-reader friendly output (`output.accessible = true`).
+///
+/// Turns off colors globally, makes [`StdoutRenderer`] label tool activity
+/// in words, and tells the frontends to skip spinners and the
+/// erase-and-reprint pass.
+pub fn set_accessible(on: bool) {
+    ACCESSIBLE.store(on, Ordering::Relaxed);
+    if on {
+        colored::control::set_override(false);
+    }
+}
+
+/// Whether accessible output is enabled.
+pub fn is_accessible() -> bool {
+    ACCESSIBLE.load(Ordering::Relaxed)
+}
+
+/// Display limits set by [`set_tool_display_chars`].
+static TOOL_ARGS_CHARS: AtomicUsize = AtomicUsize::new(TOOL_ARGS_DISPLAY_CHARS);
+static TOOL_RESULT_CHARS: AtomicUsize = AtomicUsize::new(TOOL_RESULT_DISPLAY_CHARS);
+
+/// Sets how much of a tool call's arguments and result the renderers show
+/// (`[output] tool_args_chars` and `tool_result_chars`); 0 shows all of it.
+pub fn set_tool_display_chars(args: usize, result: usize) {
+    TOOL_ARGS_CHARS.store(args, Ordering::Relaxed);
+    TOOL_RESULT_CHARS.store(result, Ordering::Relaxed);
+}
+
+/// Tool arguments as displayed, cut to the configured length.
+pub fn tool_args_display(args: &str) -> String {
+    truncate_display(args, TOOL_ARGS_CHARS.load(Ordering::Relaxed))
+}
+
+/// A tool result as displayed, cut to the configured length.
+pub fn tool_result_display(result: &str) -> String {
+    truncate_display(result, TOOL_RESULT_CHARS.load(Ordering::Relaxed))
+}
+
+/// `text` cut to `max` bytes, the last three replaced by `...`; whole
+/// when it fits or `max` is 0.
+fn truncate_display(text: &str, max: usize) -> String {
+    if max == 0 || text.len() <= max {
+        return text.to_string();
+    }
+    let end = text.floor_char_boundary(max.saturating_sub(3));
+    format!("{}...", &text[..end])
+}
+
+/// Severity of a [`Renderer::notice`] line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NoticeLevel {
+    /// Printed as-is (command output such as `/history`).
+    Plain,
+    /// Dimmed status line (token usage, compaction results).
+    Info,
+    /// Highlighted line that needs the user's attention.
+    Warning,
+    /// Urgent status line (context nearly exhausted, etc.).
+    Critical,
+}
 
 /// Trait for rendering LLM output.
 /// StdoutRenderer prints to terminal now.
@@ -31,16 +107,33 @@ pub trait Renderer {
     #[allow(dead_code)]
     /// Display a warning message to the user.
     fn warn(&mut self, message: &str);
+
+    /// Display a status or command output line outside the streamed response.
+    fn notice(&mut self, level: NoticeLevel, message: &str);
+
+    /// Called when the stream has gone quiet for a batching window, to draw
+    /// any tokens still held back (see [`STREAM_BATCH_WINDOW_MS`]).
+    fn flush(&mut self) {}
+
+    /// Called once at the end of a stream with the provider's token usage.
+    /// Display renderers ignore it; [`MeteredRenderer`](crate::metrics::MeteredRenderer)
+    /// records it for cost tracking.
+    fn usage(&mut self, _usage: TokenUsage) {}
 }
 
 /// Renders streaming LLM output directly to stdout.
 ///
-/// Each token is printed immediately with an explicit flush so the user
-/// sees a "typing" effect. Tracks the total number of tokens received
-/// and buffers the raw text for accurate visual line counting.
+/// Tokens are batched within a short window (see
+/// [`STREAM_BATCH_WINDOW_MS`]) and written with one flush per batch, which
+/// still looks live but avoids a syscall and redraw per token at high
+/// stream rates. Tracks the total number of tokens received and buffers the
+/// raw text for accurate visual line counting.
 pub struct StdoutRenderer {
     token_count: usize,
     buffer: String,
+    /// Tokens received since the last flush.
+    pending: String,
+    last_flush: Instant,
 }
 
 impl StdoutRenderer {
@@ -48,7 +141,20 @@ impl StdoutRenderer {
         Self {
             token_count: 0,
             buffer: String::new(),
+            pending: String::new(),
+            last_flush: Instant::now(),
+        }
+    }
+
+    /// Writes any batched tokens to stdout.
+    fn flush_pending(&mut self) {
+        if !self.pending.is_empty() {
+            let _span = tracing::info_span!("render_flush", bytes = self.pending.len()).entered();
+            print!("{}", self.pending);
+            io::stdout().flush().ok();
+            self.pending.clear();
         }
+        self.last_flush = Instant::now();
     }
 
     /// Calculates the number of cursor-up movements needed to erase
@@ -83,48 +189,214 @@ impl StdoutRenderer {
     }
 }
 
+impl Default for StdoutRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Renderer for StdoutRenderer {
     fn render_token(&mut self, token: &str) {
         self.buffer.push_str(token);
-        print!("{}", token);
-        // Flush immediately so each token appears as it arrives
-        io::stdout().flush().ok();
+        self.pending.push_str(token);
         self.token_count += 1;
+        if self.last_flush.elapsed() >= Duration::from_millis(STREAM_BATCH_WINDOW_MS) {
+            self.flush_pending();
+        }
+    }
+
+    fn flush(&mut self) {
+        self.flush_pending();
     }
 
     fn render_done(&mut self) {
+        self.flush_pending();
         println!(); // Final newline after stream ends
         println!();
-        println!("{}", format!("[{} tokens]", self.token_count).dimmed());
+        if is_accessible() {
+            println!("End of response, {} tokens.", self.token_count);
+        } else {
+            println!("{}", format!("[{} tokens]", self.token_count).dimmed());
+        }
     }
 
     fn render_error(&mut self, err: &str) {
+        self.flush_pending();
         eprintln!();
         eprintln!("{} {}", "error:".red().bold(), err);
     }
 
     fn tool_start(&mut self, name: &str, args: &Value) {
-        let args_str = args.to_string();
-        let truncated = if args_str.len() > 80 {
-            let end = args_str.floor_char_boundary(77);
-            format!("{}...", &args_str[..end])
+        self.flush_pending();
+        let truncated = tool_args_display(&args.to_string());
+        if is_accessible() {
+            eprintln!("Tool call: {} {}", name, truncated);
         } else {
-            args_str
-        };
-        eprintln!("⚡ {} {}", name.yellow(), truncated.dimmed());
+            eprintln!("⚡ {} {}", name.yellow(), truncated.dimmed());
+        }
     }
 
     fn tool_result(&mut self, name: &str, result: &str) {
-        let truncated = if result.len() > 200 {
-            let end = result.floor_char_boundary(197);
-            format!("{}...", &result[..end])
+        let truncated = tool_result_display(result);
+        if is_accessible() {
+            eprintln!("Tool result from {}: {}", name, truncated);
         } else {
-            result.to_string()
-        };
-        eprintln!("{} {}", format!("✓ {}", name).green(), truncated.dimmed());
+            eprintln!("{} {}", format!("✓ {}", name).green(), truncated.dimmed());
+        }
     }
 
     fn warn(&mut self, message: &str) {
+        self.flush_pending();
         eprintln!("{} {}", "warning:".yellow().bold(), message);
     }
+
+    fn notice(&mut self, level: NoticeLevel, message: &str) {
+        self.flush_pending();
+        match level {
+            NoticeLevel::Plain => println!("{}", message),
+            NoticeLevel::Info => println!("{}", message.dimmed()),
+            NoticeLevel::Warning => println!("{}", message.yellow()),
+            NoticeLevel::Critical => println!("{}", message.red()),
+        }
+    }
+}
+
+/// A tool call observed by a [`RecordingRenderer`].
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordedToolCall {
+    pub name: String,
+    pub arguments: Value,
+    /// The tool's output, once it has finished.
+    pub result: Option<String>,
+}
+
+/// Renders nothing and records tool calls, for machine-readable output
+/// (`kaze ask --json`).
+///
+/// Warnings still go to stderr so they are not lost, but stdout is left
+/// to the caller.
+#[derive(Debug, Default)]
+pub struct RecordingRenderer {
+    pub tool_calls: Vec<RecordedToolCall>,
+}
+
+impl RecordingRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Renderer for RecordingRenderer {
+    fn render_token(&mut self, _token: &str) {}
+
+    fn render_done(&mut self) {}
+
+    fn render_error(&mut self, _err: &str) {}
+
+    fn tool_start(&mut self, name: &str, args: &Value) {
+        self.tool_calls.push(RecordedToolCall {
+            name: name.to_string(),
+            arguments: args.clone(),
+            result: None,
+        });
+    }
+
+    fn tool_result(&mut self, name: &str, result: &str) {
+        if let Some(call) = self
+            .tool_calls
+            .iter_mut()
+            .rev()
+            .find(|c| c.name == name && c.result.is_none())
+        {
+            call.result = Some(result.to_string());
+        }
+    }
+
+    fn warn(&mut self, message: &str) {
+        eprintln!("warning: {}", message);
+    }
+
+    fn notice(&mut self, _level: NoticeLevel, _message: &str) {}
+}
+
+/// Opens a `--tee` file for appending, creating it if needed.
+pub fn open_tee(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open tee file {}", path.display()))
+}
+
+/// Wraps another renderer and appends the raw stream and tool events to a
+/// file as they arrive (`--tee`).
+///
+/// Writes are unbuffered, so the file keeps everything received so far even
+/// if the terminal goes away mid-generation. A failed write is reported once
+/// through the inner renderer, after which teeing stops.
+pub struct TeeRenderer<'a> {
+    inner: &'a mut dyn Renderer,
+    file: Option<&'a mut File>,
+}
+
+impl<'a> TeeRenderer<'a> {
+    pub fn new(inner: &'a mut dyn Renderer, file: &'a mut File) -> Self {
+        Self {
+            inner,
+            file: Some(file),
+        }
+    }
+
+    fn write(&mut self, text: &str) {
+        let Some(file) = self.file.as_mut() else {
+            return;
+        };
+        if let Err(e) = file.write_all(text.as_bytes()) {
+            self.file = None;
+            self.inner.warn(&format!("--tee stopped: {}", e));
+        }
+    }
+}
+
+impl Renderer for TeeRenderer<'_> {
+    fn render_token(&mut self, token: &str) {
+        self.write(token);
+        self.inner.render_token(token);
+    }
+
+    fn render_done(&mut self) {
+        self.write("\n");
+        self.inner.render_done();
+    }
+
+    fn render_error(&mut self, err: &str) {
+        self.write(&format!("\n[error] {}\n", err));
+        self.inner.render_error(err);
+    }
+
+    fn tool_start(&mut self, name: &str, args: &Value) {
+        self.write(&format!("\n[tool call] {} {}\n", name, args));
+        self.inner.tool_start(name, args);
+    }
+
+    fn tool_result(&mut self, name: &str, result: &str) {
+        self.write(&format!("[tool result] {}\n{}\n", name, result));
+        self.inner.tool_result(name, result);
+    }
+
+    fn warn(&mut self, message: &str) {
+        self.inner.warn(message);
+    }
+
+    fn notice(&mut self, level: NoticeLevel, message: &str) {
+        self.inner.notice(level, message);
+    }
+
+    fn flush(&mut self) {
+        self.inner.flush();
+    }
+
+    fn usage(&mut self, usage: TokenUsage) {
+        self.inner.usage(usage);
+    }
 }
@@ -8,8 +8,11 @@ use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Mutex;
 
+use crate::i18n::{t, tf};
+
 /// Permission level for a tool.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -29,6 +32,59 @@ pub struct PermissionConfig {
     /// Per-command permissions for bash: command_pattern -> Permission
     #[serde(default)]
     pub bash_commands: HashMap<String, Permission>,
+
+    /// Per-subcommand permissions for the git tool: subcommand -> Permission.
+    /// A force push is looked up as `"push --force"`.
+    #[serde(default)]
+    pub git_commands: HashMap<String, Permission>,
+
+    /// Per-host permissions for network tools: host_pattern -> Permission.
+    /// `*.example.com` matches example.com and its subdomains; `*` matches any host.
+    #[serde(default)]
+    pub network: HashMap<String, Permission>,
+
+    /// Deny every tool that can change files, whatever the entries above
+    /// say: write_file, edit, multi_edit, apply_patch, bash, and git's
+    /// subcommands other than the read-only ones. Also set by `--read-only`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_only: Option<bool>,
+}
+
+/// Tools that reach the network, checked against [`PermissionConfig::network`].
+const NETWORK_TOOLS: &[&str] = &["web_fetch"];
+
+/// Tools allowed when `[permissions.tools]` doesn't mention them; every
+/// other tool asks. help_lookup only reads documentation.
+const DEFAULT_ALLOWED_TOOLS: &[&str] = &["help_lookup"];
+
+/// The permission a tool the config leaves out falls back to.
+pub fn default_permission(tool_name: &str) -> Permission {
+    if DEFAULT_ALLOWED_TOOLS.contains(&tool_name) {
+        Permission::Allow
+    } else {
+        Permission::Ask
+    }
+}
+
+/// Tools read-only mode denies outright; git is decided by subcommand.
+const WRITING_TOOLS: &[&str] = &["write_file", "edit", "multi_edit", "apply_patch", "bash"];
+
+/// Set by `--read-only` for every manager the process creates.
+static FORCE_READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+/// Set by `--yes`: managers start in auto-approve mode.
+static START_AUTO_APPROVE: AtomicBool = AtomicBool::new(false);
+
+/// Starts every [`PermissionManager`] created from now on in auto-approve
+/// mode (`--yes`).
+pub fn start_auto_approved() {
+    START_AUTO_APPROVE.store(true, Ordering::Relaxed);
+}
+
+/// Puts every [`PermissionManager`] created from now on in read-only mode,
+/// whatever its config says (`--read-only`).
+pub fn force_read_only() {
+    FORCE_READ_ONLY.store(true, Ordering::Relaxed);
 }
 
 /// Manages runtime permission checks.
@@ -37,6 +93,14 @@ pub struct PermissionManager {
     /// Session-level overrides (e.g., user chose "always allow" during session).
     /// Wrapped in Mutex because PromptHook requires &self (not &mut self).
     session_overrides: Mutex<HashMap<String, Permission>>,
+    /// Set for headless runs with nobody to prompt: `Some(true)` approves
+    /// every "ask" tool, `Some(false)` refuses them.
+    unattended: Option<bool>,
+    /// Content size above which write_file calls ask.
+    write_ceiling: Option<usize>,
+    /// Answer every "ask" with yes (`--yes`, `/auto`); switched at runtime,
+    /// so it lives behind the shared reference the hook holds.
+    auto_approve: AtomicBool,
 }
 
 impl PermissionManager {
@@ -44,31 +108,136 @@ impl PermissionManager {
         Self {
             config,
             session_overrides: Mutex::new(HashMap::new()),
+            unattended: None,
+            write_ceiling: None,
+            auto_approve: AtomicBool::new(START_AUTO_APPROVE.load(Ordering::Relaxed)),
         }
     }
 
-    /// Create with sensible defaults (bash=ask, everything else=allow).
+    /// Turns auto-approve mode on or off: every call that would ask is
+    /// allowed, oversized writes included. Denies, whether configured, by
+    /// git's defaults, or by read-only mode, still hold.
+    pub fn set_auto_approve(&self, on: bool) {
+        self.auto_approve.store(on, Ordering::Relaxed);
+    }
+
+    /// Whether auto-approve mode is on.
+    pub fn auto_approve(&self) -> bool {
+        self.auto_approve.load(Ordering::Relaxed)
+    }
+
+    /// Makes write_file calls whose content is over `bytes` ask, however
+    /// write_file is configured (short of deny) or was answered before.
+    pub fn with_write_ceiling(mut self, bytes: Option<usize>) -> Self {
+        self.write_ceiling = bytes;
+        self
+    }
+
+    /// The content size of a write_file call over the ceiling, and the
+    /// ceiling; `None` for other calls.
+    pub fn oversized_write(&self, tool_name: &str, args: &str) -> Option<(usize, usize)> {
+        let ceiling = self.write_ceiling.filter(|_| tool_name == "write_file")?;
+        let parsed: serde_json::Value = serde_json::from_str(args).ok()?;
+        let size = parsed.get("content")?.as_str()?.len();
+        (size > ceiling).then_some((size, ceiling))
+    }
+
+    /// Answers "ask" permissions without prompting (`kaze run`).
+    ///
+    /// With `auto_approve` they are allowed, otherwise refused. Tools set
+    /// to deny stay denied either way.
+    pub fn unattended(mut self, auto_approve: bool) -> Self {
+        self.unattended = Some(auto_approve);
+        self
+    }
+
+    /// The automatic answer to "ask" permissions, if running unattended.
+    pub fn unattended_approval(&self) -> Option<bool> {
+        self.unattended
+    }
+
+    /// Create with sensible defaults (bash and network=ask, everything else=allow).
     #[allow(dead_code)]
     pub fn with_defaults() -> Self {
         let mut tools = HashMap::new();
         tools.insert("read_file".into(), Permission::Allow);
         tools.insert("glob".into(), Permission::Allow);
+        tools.insert("list_dir".into(), Permission::Allow);
         tools.insert("grep".into(), Permission::Allow);
         tools.insert("write_file".into(), Permission::Allow);
         tools.insert("edit".into(), Permission::Allow);
+        tools.insert("multi_edit".into(), Permission::Allow);
+        tools.insert("apply_patch".into(), Permission::Allow);
+        tools.insert("git".into(), Permission::Ask);
         tools.insert("bash".into(), Permission::Ask);
+        tools.insert("help_lookup".into(), Permission::Allow);
+        tools.insert("web_fetch".into(), Permission::Ask);
+        tools.insert("web_search".into(), Permission::Ask);
+        tools.insert("todo".into(), Permission::Allow);
+        tools.insert("task".into(), Permission::Allow);
 
         Self::new(PermissionConfig {
             tools,
-            bash_commands: HashMap::new(),
+            ..PermissionConfig::default()
         })
     }
 
+    /// Whether read-only mode is on.
+    pub fn is_read_only(&self) -> bool {
+        self.config.read_only == Some(true) || FORCE_READ_ONLY.load(Ordering::Relaxed)
+    }
+
+    /// Whether read-only mode rules out this call.
+    pub fn read_only_denies(&self, tool_name: &str, args: &str) -> bool {
+        if !self.is_read_only() {
+            return false;
+        }
+        if tool_name == "git" {
+            let command = serde_json::from_str::<serde_json::Value>(args)
+                .ok()
+                .and_then(|parsed| parsed.get("command")?.as_str().map(str::to_string));
+            return !command
+                .is_some_and(|c| crate::tools::git_tool::GIT_READ_ONLY.contains(&c.as_str()));
+        }
+        WRITING_TOOLS.contains(&tool_name)
+    }
+
     /// Check permission for a tool call. Returns the action to take.
     pub fn check(&self, tool_name: &str, args: &str) -> Permission {
-        // Session overrides take priority
-        if let Some(perm) = self.session_overrides.lock().unwrap().get(tool_name) {
-            return perm.clone();
+        match self.configured(tool_name, args) {
+            Permission::Ask if self.auto_approve() => Permission::Allow,
+            permission => permission,
+        }
+    }
+
+    /// The permission the config and session answers give a call.
+    fn configured(&self, tool_name: &str, args: &str) -> Permission {
+        // Read-only mode can't be answered or overridden away
+        if self.read_only_denies(tool_name, args) {
+            return Permission::Deny;
+        }
+
+        // Git decides per subcommand, "always" included, so allowing one
+        // commit never extends to pushing
+        if tool_name == "git" {
+            if let Some(perm) = self.check_git(args) {
+                return perm;
+            }
+        }
+
+        // Oversized writes ask even after "always allow"; only a deny wins
+        if self.oversized_write(tool_name, args).is_some() {
+            return match self.config.tools.get(tool_name) {
+                Some(Permission::Deny) => Permission::Deny,
+                _ => Permission::Ask,
+            };
+        }
+
+        // Session overrides take priority; git's were checked per subcommand
+        if tool_name != "git" {
+            if let Some(perm) = self.session_overrides.lock().unwrap().get(tool_name) {
+                return perm.clone();
+            }
         }
 
         // For bash, check command-specific permissions first
@@ -82,12 +251,19 @@ impl PermissionManager {
             }
         }
 
+        // For network tools, check host-specific permissions first
+        if NETWORK_TOOLS.contains(&tool_name) {
+            if let Some(perm) = self.match_network_host(args) {
+                return perm;
+            }
+        }
+
         // Fall back to tool-level permission
         self.config
             .tools
             .get(tool_name)
             .cloned()
-            .unwrap_or(Permission::Ask) // Unknown tools default to ask
+            .unwrap_or_else(|| default_permission(tool_name))
     }
 
     /// Match a bash command against wildcard patterns.
@@ -100,6 +276,68 @@ impl PermissionManager {
         None
     }
 
+    /// Decides a git tool call from its subcommand, or `None` to fall back
+    /// to the tool-level permission.
+    ///
+    /// A configured deny, or a tool-level deny without an explicit
+    /// `git_commands` entry, denies. Then "always" for this subcommand, then
+    /// its explicit entry wins. Otherwise read-only subcommands are allowed,
+    /// and pushes (forced or not) are denied.
+    fn check_git(&self, args: &str) -> Option<Permission> {
+        let key = git_key(args)?;
+        let command = key.split(' ').next().unwrap_or_default();
+
+        let configured = self.config.git_commands.get(&key);
+        let tool_denied = self.config.tools.get("git") == Some(&Permission::Deny);
+        if configured == Some(&Permission::Deny) || configured.is_none() && tool_denied {
+            return Some(Permission::Deny);
+        }
+        let overridden = self
+            .session_overrides
+            .lock()
+            .unwrap()
+            .get(&format!("git {}", key))
+            .cloned();
+        if let Some(perm) = overridden.or_else(|| configured.cloned()) {
+            return Some(perm);
+        }
+        if crate::tools::git_tool::GIT_READ_ONLY.contains(&command) {
+            return Some(Permission::Allow);
+        }
+        if command == "push" {
+            return Some(Permission::Deny);
+        }
+        None
+    }
+
+    /// Match the host of a network tool's `url` argument against host patterns.
+    ///
+    /// An exact host entry wins over `*.domain` entries, which win over `*`.
+    fn match_network_host(&self, args: &str) -> Option<Permission> {
+        let parsed: serde_json::Value = serde_json::from_str(args).ok()?;
+        let url = reqwest::Url::parse(parsed.get("url")?.as_str()?).ok()?;
+        let host = url.host_str()?.to_lowercase();
+
+        if let Some(perm) = self.config.network.get(&host) {
+            return Some(perm.clone());
+        }
+        // Longest matching domain suffix is the most specific
+        let domain = self
+            .config
+            .network
+            .iter()
+            .filter_map(|(pattern, perm)| {
+                let domain = pattern.strip_prefix("*.")?.to_lowercase();
+                let matches = host == domain || host.ends_with(&format!(".{}", domain));
+                matches.then_some((domain.len(), perm))
+            })
+            .max_by_key(|(len, _)| *len);
+        if let Some((_, perm)) = domain {
+            return Some(perm.clone());
+        }
+        self.config.network.get("*").cloned()
+    }
+
     /// Simple wildcard matching: "git *" matches "git status", "git push", etc.
     fn wildcard_match(pattern: &str, text: &str) -> bool {
         if let Some(prefix) = pattern.strip_suffix(" *") {
@@ -109,17 +347,43 @@ impl PermissionManager {
         }
     }
 
+    /// Ask `question` on stderr with the yes/no/always choices and read the
+    /// answer from stdin.
+    pub fn prompt_question(question: &str) -> Result<PromptResponse> {
+        eprint!("\n{}\n{}: ", question, t("permission.choices"));
+        io::stderr().flush()?;
+
+        let mut response = String::new();
+        io::stdin().read_line(&mut response)?;
+
+        match response.trim().to_lowercase().as_str() {
+            "y" | "yes" => Ok(PromptResponse::Yes),
+            "a" | "always" => Ok(PromptResponse::Always),
+            _ => Ok(PromptResponse::No),
+        }
+    }
+
     /// Prompt the user for permission. Returns the user's choice.
-    pub fn prompt_user(tool_name: &str, args: &str) -> Result<PromptResponse> {
+    ///
+    /// With `per_hunk`, the change can also be reviewed hunk by hunk.
+    pub fn prompt_user(tool_name: &str, args: &str, per_hunk: bool) -> Result<PromptResponse> {
         let display = if args.len() > 200 {
-            format!("{}...", &args[..200])
+            format!("{}...", &args[..args.floor_char_boundary(200)])
         } else {
             args.to_string()
         };
 
         eprint!(
-            "\nTool '{}' wants to execute:\n{}\n\nAllow? [y]es / [n]o / [a]lways: ",
-            tool_name, display
+            "\n{}\n\n{}: ",
+            tf(
+                "permission.prompt",
+                &[("tool", tool_name), ("args", &display)]
+            ),
+            t(if per_hunk {
+                "permission.choices_hunks"
+            } else {
+                "permission.choices"
+            })
         );
         io::stderr().flush()?;
 
@@ -130,22 +394,93 @@ impl PermissionManager {
             "y" | "yes" => Ok(PromptResponse::Yes),
             "n" | "no" => Ok(PromptResponse::No),
             "a" | "always" => Ok(PromptResponse::Always),
+            "p" | "per hunk" if per_hunk => Ok(PromptResponse::PerHunk),
             _ => Ok(PromptResponse::No),
         }
     }
 
-    /// Set a session-level override (used when user chooses "always").
-    pub fn set_session_override(&self, tool_name: &str, perm: Permission) {
-        self.session_overrides
-            .lock()
-            .unwrap()
-            .insert(tool_name.to_string(), perm);
+    /// Ask whether to apply hunk `number` of `total`, like `git add -p`.
+    pub fn prompt_hunk(number: usize, total: usize) -> Result<HunkResponse> {
+        eprint!(
+            "{}: ",
+            tf(
+                "permission.hunk_choices",
+                &[("n", &number.to_string()), ("total", &total.to_string())]
+            )
+        );
+        io::stderr().flush()?;
+
+        let mut response = String::new();
+        io::stdin().read_line(&mut response)?;
+
+        match response.trim().to_lowercase().as_str() {
+            "y" | "yes" => Ok(HunkResponse::Yes),
+            "e" | "edit" => Ok(HunkResponse::Edit),
+            "q" | "quit" => Ok(HunkResponse::Quit),
+            _ => Ok(HunkResponse::No),
+        }
+    }
+
+    /// Set a session-level override (used when user chooses "always") for
+    /// calls like this one: the tool, or for git only the subcommand.
+    pub fn set_session_override(&self, tool_name: &str, args: &str, perm: Permission) {
+        let key = match git_key(args).filter(|_| tool_name == "git") {
+            Some(key) => format!("git {}", key),
+            None => tool_name.to_string(),
+        };
+        self.session_overrides.lock().unwrap().insert(key, perm);
     }
 }
 
+/// The `git_commands` key of a git call: its subcommand, or
+/// `"push --force"` for a forced push.
+fn git_key(args: &str) -> Option<String> {
+    let parsed: serde_json::Value = serde_json::from_str(args).ok()?;
+    let command = parsed.get("command")?.as_str()?;
+    let forced = parsed.get("force").and_then(|f| f.as_bool()) == Some(true);
+    Some(if command == "push" && forced {
+        "push --force".to_string()
+    } else {
+        command.to_string()
+    })
+}
+
 #[derive(Debug, PartialEq)]
 pub enum PromptResponse {
     Yes,
     No,
     Always,
+    /// Review a file change hunk by hunk.
+    PerHunk,
+}
+
+/// Answer for one hunk during per-hunk review.
+#[derive(Debug, PartialEq)]
+pub enum HunkResponse {
+    Yes,
+    No,
+    /// Apply the hunk after editing its new text.
+    Edit,
+    /// Reject this hunk and all remaining ones.
+    Quit,
+}
+
+/// A permission prompt forwarded to a frontend that owns the terminal.
+///
+/// Sent by [`crate::hooks::KazeHook`] when a prompt channel is configured;
+/// the frontend shows the request and answers through `reply`.
+#[derive(Debug)]
+pub struct PermissionRequest {
+    /// Tool requesting permission.
+    pub tool_name: String,
+    /// Raw JSON arguments of the tool call.
+    pub args: String,
+    /// Colored diff preview for file-writing tools.
+    pub diff: Option<String>,
+    /// Channel for the user's answer; `None` for preview-only requests
+    /// where the tool is already allowed.
+    pub reply: Option<tokio::sync::oneshot::Sender<PromptResponse>>,
+    /// Shown instead of the tool call for confirmations that aren't one,
+    /// such as a large turn's cost.
+    pub question: Option<String>,
 }
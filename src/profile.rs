@@ -0,0 +1,266 @@
+//! `--profile`: tracing spans written as a Chrome trace.
+//!
+//! The agent loop, provider streams and model calls, tool calls,
+//! compaction, and rendering are instrumented with [`tracing`] spans, as is
+//! much of rig-core. Nothing listens unless `--profile <path>` installs the
+//! [`ChromeTrace`] subscriber, which writes each span at INFO or above as a
+//! complete ("X") event in the Chrome trace JSON format, timed from its
+//! creation to its close. Open the file in Perfetto (ui.perfetto.dev),
+//! `chrome://tracing`, or speedscope for a flame chart of the run.
+//!
+//! Events are written as they finish and flushed whenever no span is open,
+//! so a run that exits early still leaves a readable trace up to that point
+//! (the format allows the closing bracket to be missing).
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use anyhow::{Context, Result};
+use serde_json::{json, Map, Value};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+
+/// Installs the profiler for the rest of the process, writing to `path`.
+/// Dropping the returned guard finishes the file.
+///
+/// # Errors
+///
+/// Returns an error if the file can't be created or another subscriber is
+/// already installed.
+pub fn start(path: &Path) -> Result<ProfileGuard> {
+    let file =
+        File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    let trace = Arc::new(ChromeTrace::new(file));
+    tracing::subscriber::set_global_default(Arc::clone(&trace))
+        .context("A tracing subscriber is already installed")?;
+    Ok(ProfileGuard { trace })
+}
+
+/// Finishes the trace file when dropped.
+pub struct ProfileGuard {
+    trace: Arc<ChromeTrace>,
+}
+
+impl Drop for ProfileGuard {
+    fn drop(&mut self) {
+        self.trace.finish();
+    }
+}
+
+/// A span being timed.
+struct OpenSpan {
+    name: String,
+    args: Map<String, Value>,
+    /// Whether the span is kaze's, whose fields are kept. Other crates'
+    /// (rig-core's carry whole prompts) are reduced to their names.
+    ours: bool,
+    start: Instant,
+    tid: u64,
+    /// Handles to the span still alive; it closes when the last goes.
+    refs: usize,
+}
+
+struct Output {
+    out: BufWriter<File>,
+    /// Whether an event has been written, so the next needs a comma.
+    written: bool,
+}
+
+/// A [`Subscriber`] that writes spans as Chrome trace events.
+pub struct ChromeTrace {
+    epoch: Instant,
+    next_id: AtomicU64,
+    spans: Mutex<HashMap<u64, OpenSpan>>,
+    output: Mutex<Output>,
+}
+
+impl ChromeTrace {
+    fn new(file: File) -> Self {
+        let mut out = BufWriter::new(file);
+        let _ = out.write_all(b"[\n");
+        Self {
+            epoch: Instant::now(),
+            next_id: AtomicU64::new(1),
+            spans: Mutex::default(),
+            output: Mutex::new(Output {
+                out,
+                written: false,
+            }),
+        }
+    }
+
+    fn micros(&self, at: Instant) -> u64 {
+        at.duration_since(self.epoch).as_micros() as u64
+    }
+
+    fn write(&self, event: Value, flush: bool) {
+        let mut output = self.output.lock().unwrap_or_else(|e| e.into_inner());
+        let separator = if output.written { ",\n" } else { "" };
+        output.written = true;
+        let _ = write!(output.out, "{}{}", separator, event);
+        if flush {
+            let _ = output.out.flush();
+        }
+    }
+
+    /// Writes the closing bracket.
+    fn finish(&self) {
+        let mut output = self.output.lock().unwrap_or_else(|e| e.into_inner());
+        let _ = output.out.write_all(b"\n]\n");
+        let _ = output.out.flush();
+    }
+}
+
+impl Subscriber for ChromeTrace {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        *metadata.level() <= Level::INFO
+    }
+
+    fn max_level_hint(&self) -> Option<tracing::level_filters::LevelFilter> {
+        Some(tracing::level_filters::LevelFilter::INFO)
+    }
+
+    fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+        let ours = attrs.metadata().target().starts_with("kaze");
+        let mut args = Map::new();
+        if ours {
+            attrs.record(&mut Fields(&mut args));
+        }
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let span = OpenSpan {
+            name: attrs.metadata().name().to_string(),
+            args,
+            ours,
+            start: Instant::now(),
+            tid: thread_number(),
+            refs: 1,
+        };
+        self.spans
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(id, span);
+        Id::from_u64(id)
+    }
+
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        let mut spans = self.spans.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(span) = spans.get_mut(&span.into_u64()).filter(|s| s.ours) {
+            values.record(&mut Fields(&mut span.args));
+        }
+    }
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    /// kaze's own events become instant events; other crates' are noise
+    /// at this level of detail.
+    fn event(&self, event: &Event<'_>) {
+        if !event.metadata().target().starts_with("kaze") {
+            return;
+        }
+        let mut args = Map::new();
+        event.record(&mut Fields(&mut args));
+        let name = args
+            .remove("message")
+            .and_then(|m| m.as_str().map(str::to_string))
+            .unwrap_or_else(|| event.metadata().name().to_string());
+        self.write(
+            json!({
+                "name": name,
+                "ph": "i",
+                "s": "t",
+                "ts": self.micros(Instant::now()),
+                "pid": std::process::id(),
+                "tid": thread_number(),
+                "args": args,
+            }),
+            false,
+        );
+    }
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+
+    fn clone_span(&self, id: &Id) -> Id {
+        let mut spans = self.spans.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(span) = spans.get_mut(&id.into_u64()) {
+            span.refs += 1;
+        }
+        id.clone()
+    }
+
+    fn try_close(&self, id: Id) -> bool {
+        let (span, idle) = {
+            let mut spans = self.spans.lock().unwrap_or_else(|e| e.into_inner());
+            let Some(span) = spans.get_mut(&id.into_u64()) else {
+                return false;
+            };
+            span.refs -= 1;
+            if span.refs > 0 {
+                return false;
+            }
+            let span = spans.remove(&id.into_u64()).expect("span is open");
+            (span, spans.is_empty())
+        };
+        // A tool span reads as "tool read_file" rather than a row of "tool"s
+        let name = match span.args.get("name").and_then(Value::as_str) {
+            Some(detail) => format!("{} {}", span.name, detail),
+            None => span.name,
+        };
+        let start = self.micros(span.start);
+        self.write(
+            json!({
+                "name": name,
+                "ph": "X",
+                "ts": start,
+                "dur": self.micros(Instant::now()).saturating_sub(start),
+                "pid": std::process::id(),
+                "tid": span.tid,
+                "args": span.args,
+            }),
+            idle,
+        );
+        true
+    }
+}
+
+/// Collects span and event fields as JSON.
+struct Fields<'a>(&'a mut Map<String, Value>);
+
+impl Visit for Fields<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), json!(format!("{:?}", value)));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+}
+
+/// A small stable number for the current thread, for the `tid` column.
+fn thread_number() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(1);
+    thread_local! {
+        static NUMBER: u64 = NEXT.fetch_add(1, Ordering::Relaxed);
+    }
+    NUMBER.with(|n| *n)
+}
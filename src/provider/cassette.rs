@@ -0,0 +1,227 @@
+//! Record and replay of model responses, for reproducing streaming bugs
+//! and writing regression tests without API keys.
+//!
+//! With `KAZE_RECORD=path`, every model call's response is appended to a
+//! cassette at `path`, one JSON line per call: the text as it streamed,
+//! chunk by chunk, the tool calls made, the usage reported, and the error
+//! the stream failed with, if it did. With
+//! `KAZE_REPLAY=path`, whichever provider is selected is swapped for the
+//! mock, which plays the cassette back in order and fails once it runs out.
+//! Tools still run for real on replay; only the model is replaced.
+//!
+//! Calls are recorded at the level kaze sees them, after rig-core has parsed
+//! the provider's stream, so a cassette replays against any provider.
+//! Non-streaming calls (compaction, titles, subagents) are recorded as a
+//! single chunk of their final text.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+use anyhow::{Context, Result};
+use rig::completion::Usage;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::mock::{MockReply, MockScript};
+
+/// The environment variable naming the cassette to record to.
+const RECORD_VAR: &str = "KAZE_RECORD";
+/// The environment variable naming the cassette to replay.
+const REPLAY_VAR: &str = "KAZE_REPLAY";
+
+/// One model call as recorded: a line of a cassette.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecordedCall {
+    /// The text, split as the provider streamed it.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chunks: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<RecordedToolCall>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<Usage>,
+    /// The error the stream ended with, after the chunks above.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedToolCall {
+    pub name: String,
+    pub args: Value,
+}
+
+static RECORDER: OnceLock<Option<Mutex<BufWriter<File>>>> = OnceLock::new();
+static REPLAY: OnceLock<MockScript> = OnceLock::new();
+
+/// Opens the `KAZE_RECORD` cassette, truncating it, the first time a
+/// provider is created in this process. Later providers (subagents, model
+/// switches) append to the same one.
+pub(super) fn start_recording() -> Result<()> {
+    if RECORDER.get().is_some() {
+        return Ok(());
+    }
+    let writer = match std::env::var_os(RECORD_VAR) {
+        Some(path) => {
+            anyhow::ensure!(
+                std::env::var_os(REPLAY_VAR).is_none(),
+                "{} and {} can't be set together",
+                RECORD_VAR,
+                REPLAY_VAR
+            );
+            let file = File::create(&path).with_context(|| {
+                format!("Failed to create cassette {}", Path::new(&path).display())
+            })?;
+            Some(Mutex::new(BufWriter::new(file)))
+        }
+        None => None,
+    };
+    let _ = RECORDER.set(writer);
+    Ok(())
+}
+
+/// The script for the `KAZE_REPLAY` cassette, if one is set, loaded once
+/// and shared by every provider in the process.
+pub(super) fn replay_script() -> Result<Option<MockScript>> {
+    let Some(path) = std::env::var_os(REPLAY_VAR) else {
+        return Ok(None);
+    };
+    if let Some(script) = REPLAY.get() {
+        return Ok(Some(script.clone()));
+    }
+    let script = load_cassette(Path::new(&path))?;
+    Ok(Some(REPLAY.get_or_init(|| script).clone()))
+}
+
+/// Loads a cassette as a [`MockScript`] that replays it and then fails,
+/// e.g. for a regression test built from a user's recording.
+///
+/// # Errors
+///
+/// Returns an error if the file can't be read or a line isn't a recorded
+/// call.
+pub fn load_cassette(path: &Path) -> Result<MockScript> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read cassette {}", path.display()))?;
+    let calls = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str::<RecordedCall>(line)
+                .with_context(|| format!("{}:{} is not a recorded call", path.display(), index + 1))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(MockScript::replay(
+        calls.into_iter().map(MockReply::Recorded),
+    ))
+}
+
+/// Collects the model calls of a stream for the cassette. Does nothing
+/// unless `KAZE_RECORD` is set.
+///
+/// rig-core only marks the end of calls that produced text, so calls are
+/// told apart by the model requests the stream's hook has counted (see
+/// [`KazeHook::model_calls`](crate::hooks::KazeHook::model_calls)): each
+/// new request writes the call before it.
+pub(super) struct Tape {
+    recording: bool,
+    call: RecordedCall,
+    /// Whether `call` belongs to a request that has started.
+    open: bool,
+    /// Requests accounted for so far.
+    seen: usize,
+}
+
+impl Tape {
+    /// Starts a tape for a stream whose hook has counted `calls` requests.
+    pub fn start(calls: usize) -> Self {
+        Self {
+            recording: recorder().is_some(),
+            call: RecordedCall::default(),
+            open: false,
+            seen: calls,
+        }
+    }
+
+    /// Catches up with the hook's request count, writing each finished
+    /// call, including any that streamed nothing.
+    pub fn sync(&mut self, calls: usize) {
+        if !self.recording {
+            return;
+        }
+        while self.seen < calls {
+            if self.open {
+                write(&std::mem::take(&mut self.call));
+            }
+            self.open = true;
+            self.seen += 1;
+        }
+    }
+
+    pub fn text(&mut self, text: &str) {
+        if self.recording {
+            self.call.chunks.push(text.to_string());
+        }
+    }
+
+    pub fn tool_call(&mut self, name: &str, args: &Value) {
+        if self.recording {
+            self.call.tool_calls.push(RecordedToolCall {
+                name: name.to_string(),
+                args: args.clone(),
+            });
+        }
+    }
+
+    pub fn usage(&mut self, usage: Option<Usage>) {
+        self.call.usage = usage;
+    }
+
+    /// Marks the current call as failed with `message`, for [`Self::finish`]
+    /// to write along with whatever streamed before it.
+    pub fn error(&mut self, message: &str) {
+        if self.recording {
+            self.call.error = Some(message.to_string());
+            self.open = true;
+        }
+    }
+
+    /// Writes the last call once the stream has ended, or failed.
+    pub fn finish(&mut self, calls: usize) {
+        self.sync(calls);
+        if self.open {
+            write(&std::mem::take(&mut self.call));
+            self.open = false;
+        }
+    }
+}
+
+/// Records a non-streaming call's answer as a single chunk.
+pub(super) fn record_text(text: &str, usage: Option<Usage>) {
+    if recorder().is_some() {
+        write(&RecordedCall {
+            chunks: vec![text.to_string()],
+            tool_calls: Vec::new(),
+            usage,
+            error: None,
+        });
+    }
+}
+
+fn recorder() -> Option<&'static Mutex<BufWriter<File>>> {
+    RECORDER.get().and_then(Option::as_ref)
+}
+
+/// Appends `call` to the cassette. Failing to record must not fail the
+/// session being recorded, so write errors are dropped.
+fn write(call: &RecordedCall) {
+    let (Some(recorder), Ok(line)) = (recorder(), serde_json::to_string(call)) else {
+        return;
+    };
+    if let Ok(mut out) = recorder.lock() {
+        let _ = writeln!(out, "{}", line);
+        let _ = out.flush();
+    }
+}
@@ -2,15 +2,17 @@
 //!
 //! Contains the [`Provider`] struct which wraps rig-core provider clients
 //! behind enum dispatch, keeping provider-specific details out of the CLI
-//! layer. Supports Anthropic, OpenAI, OpenRouter, and Ollama.
+//! layer. Supports Anthropic, OpenAI, OpenRouter, Ollama, and (with the
+//! `bedrock` feature) AWS Bedrock.
 
 use anyhow::{Context, Result};
 use futures::StreamExt;
 use rig::agent::MultiTurnStreamItem;
 use rig::client::CompletionClient;
-use rig::completion::Prompt;
+use rig::completion::{CompletionModel, GetTokenUsage, Prompt, ToolDefinition};
 use rig::message::{
-    AssistantContent, Message as RigMessage, Text, ToolCall as RigToolCall, ToolFunction,
+    AssistantContent, ImageMediaType, Message as RigMessage, MimeType, Text,
+    ToolCall as RigToolCall, ToolChoice, ToolFunction, UserContent,
 };
 use rig::providers::{anthropic, openai, openrouter};
 use rig::streaming::{
@@ -18,12 +20,21 @@ use rig::streaming::{
 };
 use rig::OneOrMany;
 
+use reqwest::header::HeaderMap;
+use serde_json::{json, Value};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::OnceCell;
 
+use super::cassette::{self, Tape};
 use super::kind::ProviderKind;
+use super::mock::{MockClient, MockScript};
 use super::resolve::ModelSelection;
+use super::supersede::Superseding;
 use crate::config::Config;
+use crate::message::{ImagePart, ImageSource};
 use crate::output::Renderer;
+use crate::schema::OutputSchema;
 use crate::tools::ToolRegistry;
 
 /// Internal enum wrapping provider-specific clients.
@@ -32,17 +43,145 @@ enum ClientKind {
     OpenAI(openai::Client),
     OpenRouter(openrouter::Client),
     Ollama(openai::Client),
+    #[cfg(feature = "bedrock")]
+    Bedrock(rig_bedrock::client::Client),
+    Mock(MockClient),
+}
+
+/// Error shown when Bedrock is selected in a build without the feature.
+#[cfg(not(feature = "bedrock"))]
+const BEDROCK_UNAVAILABLE: &str =
+    "This build of kaze does not include Bedrock support. Rebuild with `--features bedrock`";
+
+/// Resolved connection settings for a provider, captured at construction.
+///
+/// Building the actual HTTP client (TLS setup, root certificates, AWS
+/// credential resolution) is comparatively slow, so [`Provider`] keeps this
+/// spec and only builds the client on first use.
+enum ClientSpec {
+    Anthropic {
+        api_key: String,
+    },
+    OpenAI {
+        api_key: String,
+    },
+    OpenRouter {
+        api_key: String,
+    },
+    Ollama {
+        base_url: String,
+    },
+    // Only read when the `bedrock` feature is enabled
+    #[allow(dead_code)]
+    Bedrock {
+        region: Option<String>,
+        profile: Option<String>,
+    },
+    Mock {
+        script: MockScript,
+    },
+}
+
+impl ClientSpec {
+    /// Builds the rig-core client described by this spec, sending `headers`
+    /// with every request.
+    async fn build(&self, headers: &HeaderMap) -> Result<ClientKind> {
+        let http = super::http::shared_client();
+        match self {
+            ClientSpec::Anthropic { api_key } => Ok(ClientKind::Anthropic(
+                anthropic::Client::<reqwest::Client>::builder()
+                    .api_key(api_key)
+                    .http_client(http)
+                    .http_headers(headers.clone())
+                    .build()
+                    .context("Failed to create Anthropic client")?,
+            )),
+            ClientSpec::OpenAI { api_key } => Ok(ClientKind::OpenAI(
+                openai::Client::<reqwest::Client>::builder()
+                    .api_key(api_key)
+                    .http_client(http)
+                    .http_headers(headers.clone())
+                    .build()
+                    .context("Failed to create OpenAI client")?,
+            )),
+            ClientSpec::OpenRouter { api_key } => Ok(ClientKind::OpenRouter(
+                openrouter::Client::<reqwest::Client>::builder()
+                    .api_key(api_key)
+                    .http_client(http)
+                    .http_headers(headers.clone())
+                    .build()
+                    .context("Failed to create OpenRouter client")?,
+            )),
+            ClientSpec::Ollama { base_url } => Ok(ClientKind::Ollama(
+                openai::Client::<reqwest::Client>::builder()
+                    .api_key("ollama")
+                    .base_url(format!("{}/v1", base_url))
+                    .http_client(http)
+                    .http_headers(headers.clone())
+                    .build()
+                    .context("Failed to create Ollama client")?,
+            )),
+            #[cfg(feature = "bedrock")]
+            ClientSpec::Bedrock { region, profile } => {
+                // Credentials come from the standard AWS chain: env vars,
+                // shared config/credentials files, SSO, and instance roles
+                let mut loader = aws_config::defaults(aws_config::BehaviorVersion::latest());
+                if let Some(region) = region {
+                    loader = loader.region(aws_config::Region::new(region.clone()));
+                }
+                if let Some(profile) = profile {
+                    loader = loader.profile_name(profile);
+                }
+                let sdk_config = loader.load().await;
+                Ok(ClientKind::Bedrock(rig_bedrock::client::Client::from(
+                    aws_sdk_bedrockruntime::Client::new(&sdk_config),
+                )))
+            }
+            #[cfg(not(feature = "bedrock"))]
+            ClientSpec::Bedrock { .. } => anyhow::bail!(BEDROCK_UNAVAILABLE),
+            ClientSpec::Mock { script } => Ok(ClientKind::Mock(MockClient {
+                script: script.clone(),
+            })),
+        }
+    }
+}
+
+/// The parameters a [`Provider`] sends with each request, recorded in
+/// sessions and `kaze run` reports so a run can be reproduced and compared.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct RequestParams {
+    pub provider: &'static str,
+    pub model: String,
+    pub temperature: Option<f64>,
+    /// `None` when no seed is set or the backend doesn't take one.
+    pub seed: Option<u64>,
+    pub max_tokens: u64,
+    /// Names of the tools offered to the model.
+    pub tools: Vec<String>,
 }
 
 /// A configured LLM provider ready to handle completion requests.
 ///
 /// Wraps a rig-core provider client and the target model name. Supports
-/// Anthropic, OpenAI, OpenRouter, and Ollama via internal enum dispatch. Agents are
+/// Anthropic, OpenAI, OpenRouter, and Ollama via internal enum dispatch. The
+/// client is built lazily on the first request and memoized. Agents are
 /// constructed on each call since they are cheap to create and may use
 /// different system prompts.
 pub struct Provider {
-    client: ClientKind,
+    spec: ClientSpec,
+    /// `[provider.<name>.headers]`, added to every request.
+    headers: HeaderMap,
+    client: OnceCell<ClientKind>,
     model: String,
+    /// `[generation]` sampling settings; see [`Config::temperature`].
+    temperature: Option<f64>,
+    seed: Option<u64>,
+    /// Set once the model has been reported as lacking tool support.
+    tools_notice_shown: AtomicBool,
+    /// Set once the provider has refused a request for carrying tools.
+    tools_rejected: AtomicBool,
+    /// Set once an unsupported seed has been reported.
+    seed_notice_shown: AtomicBool,
 }
 
 /// Helper macro to reduce duplication across provider match arms.
@@ -50,34 +189,47 @@ pub struct Provider {
 /// Builds an agent from the given client, model, and optional system prompt,
 /// then executes the provided block with the agent bound to `$agent`.
 macro_rules! with_agent {
-    ($client:expr, $model:expr, $sys:expr, |$agent:ident| $body:expr) => {{
-        let $agent = if let Some(sys) = $sys {
-            $client
-                .agent($model)
-                .preamble(sys)
-                .max_tokens(crate::constants::MAX_TOKENS)
-                .build()
-        } else {
-            $client
-                .agent($model)
-                .max_tokens(crate::constants::MAX_TOKENS)
-                .build()
-        };
+    ($client:expr, $provider:expr, $sys:expr, |$agent:ident| $body:expr) => {{
+        let $agent = agent_builder!($client, $provider, $sys).build();
         $body
     }};
 }
 
+/// Starts an agent builder with the system prompt, token limit, and
+/// sampling settings shared by [`with_agent!`] and [`with_agent_tools!`].
+macro_rules! agent_builder {
+    ($client:expr, $provider:expr, $sys:expr) => {
+        agent_builder!(@from $client.agent(&$provider.model), $provider, $sys)
+    };
+    (@from $builder:expr, $provider:expr, $sys:expr) => {{
+        let mut builder = $builder.max_tokens(crate::constants::MAX_TOKENS);
+        if let Some(sys) = $sys {
+            builder = builder.preamble(sys);
+        }
+        if let Some(temperature) = $provider.temperature {
+            builder = builder.temperature(temperature);
+        }
+        if let Some(params) = $provider.seed_params() {
+            builder = builder.additional_params(params);
+        }
+        builder
+    }};
+}
+
 /// Dispatches an operation across provider-specific clients.
 ///
 /// Matches on [`ClientKind`] and executes the same block for each variant,
 /// letting the compiler monomorphize per provider.
 macro_rules! dispatch {
     ($self:expr, |$client:ident| $body:expr) => {
-        match &$self.client {
+        match $self.client().await? {
             ClientKind::Anthropic($client) => $body,
             ClientKind::OpenAI($client) => $body,
             ClientKind::OpenRouter($client) => $body,
             ClientKind::Ollama($client) => $body,
+            #[cfg(feature = "bedrock")]
+            ClientKind::Bedrock($client) => $body,
+            ClientKind::Mock($client) => $body,
         }
     };
 }
@@ -88,7 +240,7 @@ macro_rules! dispatch {
 /// across all providers.
 macro_rules! process_stream {
     ($stream:expr, $renderer:expr, $full_response:expr) => {
-        while let Some(chunk) = $stream.next().await {
+        while let Some(chunk) = next_flushing(&mut $stream, &mut *$renderer).await {
             match chunk {
                 Ok(MultiTurnStreamItem::StreamAssistantItem(StreamedAssistantContent::Text(
                     Text { text },
@@ -96,8 +248,8 @@ macro_rules! process_stream {
                     $renderer.render_token(&text);
                     $full_response.push_str(&text);
                 }
-                Ok(MultiTurnStreamItem::FinalResponse(_)) => {
-                    // Stream complete
+                Ok(MultiTurnStreamItem::FinalResponse(response)) => {
+                    $renderer.usage(crate::usage::TokenUsage::from(response.usage()));
                 }
                 Err(err) => {
                     $renderer.render_error(&err.to_string());
@@ -111,29 +263,37 @@ macro_rules! process_stream {
     };
 }
 
+/// The next item of `stream`, first drawing the tokens `renderer` holds
+/// back if the stream goes quiet for a batching window, so the tail of a
+/// slow stream doesn't wait for the next chunk.
+async fn next_flushing<S>(stream: &mut S, renderer: &mut dyn Renderer) -> Option<S::Item>
+where
+    S: futures::Stream + Unpin,
+{
+    let window = std::time::Duration::from_millis(crate::constants::STREAM_BATCH_WINDOW_MS);
+    match tokio::time::timeout(window, stream.next()).await {
+        Ok(item) => item,
+        Err(_) => {
+            renderer.flush();
+            stream.next().await
+        }
+    }
+}
+
 /// Builds an agent with tools registered for LLM function calling.
 ///
 /// Like [`with_agent!`] but adds rig-core tool definitions via `.tools()`.
 /// The type-state change from `NoToolConfig` to `WithBuilderTools` means
 /// this must be a separate macro — the two builder paths produce different types.
+/// The model is wrapped in [`Superseding`], since only tool loops repeat
+/// tool results.
 macro_rules! with_agent_tools {
-    ($client:expr, $model:expr, $sys:expr, $hook:expr, $rig_tools:expr, |$agent:ident| $body:expr) => {{
-        let $agent = if let Some(sys) = $sys {
-            $client
-                .agent($model)
-                .preamble(sys)
-                .max_tokens(crate::constants::MAX_TOKENS)
-                .hook($hook)
-                .tools($rig_tools)
-                .build()
-        } else {
-            $client
-                .agent($model)
-                .max_tokens(crate::constants::MAX_TOKENS)
-                .hook($hook)
-                .tools($rig_tools)
-                .build()
-        };
+    ($client:expr, $provider:expr, $sys:expr, $hook:expr, $rig_tools:expr, |$agent:ident| $body:expr) => {{
+        let model = Superseding($client.completion_model(&$provider.model));
+        let $agent = agent_builder!(@from rig::agent::AgentBuilder::new(model), $provider, $sys)
+            .hook($hook)
+            .tools($rig_tools)
+            .build();
         $body
     }};
 }
@@ -144,17 +304,25 @@ macro_rules! with_agent_tools {
 /// - `StreamAssistantItem(Text)` → render token + accumulate text
 /// - `StreamAssistantItem(ToolCall)` → render tool start, track name by internal ID
 /// - `StreamUserItem(ToolResult)` → render tool result
-/// - `FinalResponse` → stream complete
+/// - `StreamAssistantItem(Final)` → record the model call's output tokens
+///   in `$last_call_output`, to detect truncation at `MAX_TOKENS`
+/// - `FinalResponse` → report aggregated token usage
 /// - Everything else (ToolCallDelta, Reasoning) → ignored
+///
+/// With `KAZE_RECORD` set, each model call, told apart by `$hook`'s request
+/// count, goes to the cassette.
 macro_rules! process_stream_with_tools {
-    ($stream:expr, $renderer:expr, $full_response:expr, $tool_names:expr) => {
-        while let Some(chunk) = $stream.next().await {
+    ($stream:expr, $renderer:expr, $full_response:expr, $tool_names:expr, $last_call_output:expr, $hook:expr) => {
+        let mut tape = Tape::start($hook.model_calls());
+        while let Some(chunk) = next_flushing(&mut $stream, &mut *$renderer).await {
+            tape.sync($hook.model_calls());
             match chunk {
                 Ok(MultiTurnStreamItem::StreamAssistantItem(StreamedAssistantContent::Text(
                     Text { text },
                 ))) => {
                     $renderer.render_token(&text);
                     $full_response.push_str(&text);
+                    tape.text(&text);
                 }
                 Ok(MultiTurnStreamItem::StreamAssistantItem(
                     StreamedAssistantContent::ToolCall {
@@ -164,6 +332,7 @@ macro_rules! process_stream_with_tools {
                 )) => {
                     let name = tool_call.function.name.clone();
                     $renderer.tool_start(&name, &tool_call.function.arguments);
+                    tape.tool_call(&name, &tool_call.function.arguments);
                     $tool_names.insert(internal_call_id, name);
                 }
                 Ok(MultiTurnStreamItem::StreamUserItem(StreamedUserContent::ToolResult {
@@ -185,11 +354,20 @@ macro_rules! process_stream_with_tools {
                         .join("\n");
                     $renderer.tool_result(name, &result_text);
                 }
-                Ok(MultiTurnStreamItem::FinalResponse(_)) => {
-                    // Stream complete
+                Ok(MultiTurnStreamItem::StreamAssistantItem(StreamedAssistantContent::Final(
+                    response,
+                ))) => {
+                    let usage = response.token_usage();
+                    $last_call_output = usage.map(|u| u.output_tokens);
+                    tape.usage(usage);
+                }
+                Ok(MultiTurnStreamItem::FinalResponse(response)) => {
+                    $renderer.usage(crate::usage::TokenUsage::from(response.usage()));
                 }
                 Err(err) => {
                     $renderer.render_error(&err.to_string());
+                    tape.error(&err.to_string());
+                    tape.finish($hook.model_calls());
                     anyhow::bail!("Streaming error: {}", err);
                 }
                 _ => {
@@ -197,6 +375,7 @@ macro_rules! process_stream_with_tools {
                 }
             }
         }
+        tape.finish($hook.model_calls());
     };
 }
 
@@ -211,61 +390,171 @@ impl Provider {
     ///
     /// Returns an error if no API key is found for the selected provider
     /// or if client construction fails.
+    ///
+    /// With `KAZE_REPLAY` set, the provider plays back that cassette instead
+    /// of connecting anywhere; see [`super::cassette`].
     pub fn from_config(config: &Config, selection: &ModelSelection) -> Result<Self> {
-        match selection.provider {
-            ProviderKind::Anthropic => {
-                let api_key = config
+        cassette::start_recording()?;
+        if let Some(script) = cassette::replay_script()? {
+            return Ok(Self {
+                model: selection.model.clone(),
+                ..Self::mock(config, script)
+            });
+        }
+
+        #[cfg(not(feature = "bedrock"))]
+        if selection.provider == ProviderKind::Bedrock {
+            anyhow::bail!(BEDROCK_UNAVAILABLE);
+        }
+
+        let spec = match selection.provider {
+            ProviderKind::Anthropic => ClientSpec::Anthropic {
+                api_key: config
                     .resolve_api_key("anthropic")
-                    .context("No API key found for Anthropic. Set ANTHROPIC_API_KEY or configure it in config.toml")?;
-                let client = anthropic::Client::new(&api_key)
-                    .context("Failed to create Anthropic client")?;
-                Ok(Self {
-                    client: ClientKind::Anthropic(client),
-                    model: selection.model.clone(),
-                })
-            }
-            ProviderKind::OpenAI => {
-                let api_key = config
+                    .context("No API key found for Anthropic. Set ANTHROPIC_API_KEY or configure it in config.toml")?,
+            },
+            ProviderKind::OpenAI => ClientSpec::OpenAI {
+                api_key: config
                     .resolve_api_key("openai")
-                    .context("No API key found for OpenAI. Set OPENAI_API_KEY or configure it in config.toml")?;
-                let client =
-                    openai::Client::new(&api_key).context("Failed to create OpenAI client")?;
-                Ok(Self {
-                    client: ClientKind::OpenAI(client),
-                    model: selection.model.clone(),
-                })
-            }
-            ProviderKind::OpenRouter => {
-                let api_key = config
+                    .context("No API key found for OpenAI. Set OPENAI_API_KEY or configure it in config.toml")?,
+            },
+            ProviderKind::OpenRouter => ClientSpec::OpenRouter {
+                api_key: config
                     .resolve_api_key("openrouter")
-                    .context("No API key found for OpenRouter. Set OPENROUTER_API_KEY or configure it in config.toml")?;
-                let client = openrouter::Client::new(&api_key)
-                    .context("Failed to create OpenRouter client")?;
-                Ok(Self {
-                    client: ClientKind::OpenRouter(client),
-                    model: selection.model.clone(),
-                })
-            }
-            ProviderKind::Ollama => {
-                let base_url = config
+                    .context("No API key found for OpenRouter. Set OPENROUTER_API_KEY or configure it in config.toml")?,
+            },
+            ProviderKind::Ollama => ClientSpec::Ollama {
+                base_url: config
                     .provider
                     .ollama
                     .as_ref()
-                    .and_then(|o| o.base_url.as_deref())
-                    .unwrap_or(crate::constants::OLLAMA_DEFAULT_BASE_URL);
-                let client = openai::Client::builder()
-                    .api_key("ollama")
-                    .base_url(format!("{}/v1", base_url))
-                    .build()
-                    .context("Failed to create Ollama client")?;
-                Ok(Self {
-                    client: ClientKind::Ollama(client),
-                    model: selection.model.clone(),
-                })
+                    .and_then(|o| o.base_url.clone())
+                    .unwrap_or_else(|| crate::constants::OLLAMA_DEFAULT_BASE_URL.to_string()),
+            },
+            ProviderKind::Bedrock => {
+                let entry = config.provider.bedrock.as_ref();
+                ClientSpec::Bedrock {
+                    region: entry.and_then(|e| e.region.clone()),
+                    profile: entry.and_then(|e| e.profile.clone()),
+                }
             }
+            ProviderKind::Mock => ClientSpec::Mock {
+                script: MockScript::default(),
+            },
+        };
+        Ok(Self {
+            spec,
+            headers: super::http::custom_headers(config, selection.provider.as_str())?,
+            client: OnceCell::new(),
+            model: selection.model.clone(),
+            temperature: config.temperature(),
+            seed: config.generation.seed,
+            tools_notice_shown: AtomicBool::new(false),
+            tools_rejected: AtomicBool::new(false),
+            seed_notice_shown: AtomicBool::new(false),
+        })
+    }
+
+    /// A [`ProviderKind::Mock`] provider that plays `script`, with the
+    /// sampling settings of `config`.
+    pub fn mock(config: &Config, script: MockScript) -> Self {
+        Self {
+            spec: ClientSpec::Mock { script },
+            headers: HeaderMap::new(),
+            client: OnceCell::new(),
+            model: crate::constants::MOCK_DEFAULT_MODEL.to_string(),
+            temperature: config.temperature(),
+            seed: config.generation.seed,
+            tools_notice_shown: AtomicBool::new(false),
+            tools_rejected: AtomicBool::new(false),
+            seed_notice_shown: AtomicBool::new(false),
+        }
+    }
+
+    /// Which backend this provider talks to.
+    pub fn kind(&self) -> ProviderKind {
+        match self.spec {
+            ClientSpec::Anthropic { .. } => ProviderKind::Anthropic,
+            ClientSpec::OpenAI { .. } => ProviderKind::OpenAI,
+            ClientSpec::OpenRouter { .. } => ProviderKind::OpenRouter,
+            ClientSpec::Ollama { .. } => ProviderKind::Ollama,
+            ClientSpec::Bedrock { .. } => ProviderKind::Bedrock,
+            ClientSpec::Mock { .. } => ProviderKind::Mock,
+        }
+    }
+
+    /// Whether tools are sent to this model; see [`crate::models::supports_tools`].
+    pub fn supports_tools(&self) -> bool {
+        !self.tools_rejected.load(Ordering::Relaxed) && crate::models::supports_tools(&self.model)
+    }
+
+    /// Stops sending tools if `err` is the provider refusing them, which a
+    /// model kaze doesn't know (most Ollama ones) only reveals by failing.
+    /// Returns whether the request should be retried without tools.
+    fn tools_refused(&self, err: &anyhow::Error) -> bool {
+        static REFUSED: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+        let refused = REFUSED.get_or_init(|| {
+            regex::Regex::new(
+                r"(?i)(does not|doesn't) support (tools|tool use|function calling)|(tools|tool use|function calling) (is|are) not supported",
+            )
+            .expect("tools pattern is valid")
+        });
+        if !self.supports_tools() || !refused.is_match(&format!("{:#}", err)) {
+            return false;
+        }
+        self.tools_rejected.store(true, Ordering::Relaxed);
+        self.tools_notice_shown.store(true, Ordering::Relaxed);
+        true
+    }
+
+    /// Whether the backend takes a sampling seed. Only OpenRouter's API
+    /// has one among those kaze speaks; the others reject or ignore it.
+    pub fn supports_seed(&self) -> bool {
+        self.kind() == ProviderKind::OpenRouter
+    }
+
+    /// The request parameters that carry the seed, when one is set and
+    /// supported.
+    fn seed_params(&self) -> Option<Value> {
+        self.seed
+            .filter(|_| self.supports_seed())
+            .map(|seed| json!({ "seed": seed }))
+    }
+
+    /// The parameters a tool-calling request with `tools` is sent with.
+    pub fn request_params(&self, tools: &ToolRegistry) -> RequestParams {
+        let tools = if self.supports_tools() {
+            tools.names().map(str::to_string).collect()
+        } else {
+            Vec::new()
+        };
+        RequestParams {
+            provider: self.kind().as_str(),
+            model: self.model.clone(),
+            temperature: self.temperature,
+            seed: self.seed.filter(|_| self.supports_seed()),
+            max_tokens: crate::constants::MAX_TOKENS,
+            tools,
+        }
+    }
+
+    /// The tools to send: none for a model without tool support, which
+    /// then gets a plain chat request instead of one it would reject.
+    fn rig_tools(&self, tools: &ToolRegistry) -> Vec<Box<dyn rig::tool::ToolDyn>> {
+        if self.supports_tools() {
+            tools.to_rig_tools()
+        } else {
+            Vec::new()
         }
     }
 
+    /// Returns the provider client, building it on first use.
+    async fn client(&self) -> Result<&ClientKind> {
+        self.client
+            .get_or_try_init(|| self.spec.build(&self.headers))
+            .await
+    }
+
     // Part of public API, used in future phases
     #[allow(dead_code)]
     /// Streams a prompt response, rendering tokens as they arrive via the given [`Renderer`].
@@ -278,7 +567,7 @@ impl Provider {
         let mut full_response = String::new();
 
         dispatch!(self, |client| {
-            let mut stream = with_agent!(client, &self.model, system_prompt, |agent| {
+            let mut stream = with_agent!(client, self, system_prompt, |agent| {
                 agent.stream_prompt(prompt).await
             });
             process_stream!(stream, renderer, full_response);
@@ -334,7 +623,7 @@ impl Provider {
         let mut full_response = String::new();
 
         dispatch!(self, |client| {
-            let mut stream = with_agent!(client, &self.model, system_prompt, |agent| {
+            let mut stream = with_agent!(client, self, system_prompt, |agent| {
                 agent
                     .stream_chat(prompt_text.clone(), chat_history.clone())
                     .await
@@ -352,13 +641,75 @@ impl Provider {
     /// output is not needed.
     pub async fn prompt(&self, prompt_text: &str) -> Result<String> {
         dispatch!(self, |client| {
-            let response = with_agent!(client, &self.model, None::<&str>, |agent| {
+            let response = with_agent!(client, self, None::<&str>, |agent| {
                 agent.prompt(prompt_text).await
-            });
-            Ok(response?)
+            })?;
+            cassette::record_text(&response, None);
+            Ok(response)
+        })
+    }
+
+    /// Like [`prompt`](Self::prompt), with a system prompt. Used by
+    /// `kaze suggest`, which only needs the final text.
+    pub async fn prompt_with_system(
+        &self,
+        system_prompt: &str,
+        prompt_text: &str,
+    ) -> Result<String> {
+        dispatch!(self, |client| {
+            let response = with_agent!(client, self, Some(system_prompt), |agent| {
+                agent.prompt(prompt_text).await
+            })?;
+            cassette::record_text(&response, None);
+            Ok(response)
         })
     }
 
+    /// Runs a non-streaming multi-turn tool loop and returns the final answer
+    /// with the token usage summed over every model call.
+    ///
+    /// Used by the `task` tool's subagents, whose intermediate output is
+    /// never shown; without a renderer the future is `Send`, as tool calls
+    /// must be.
+    pub async fn prompt_with_tools(
+        &self,
+        system_prompt: &str,
+        prompt_text: &str,
+        tools: &ToolRegistry,
+        max_turns: usize,
+        hook: crate::hooks::KazeHook,
+    ) -> Result<(String, crate::usage::TokenUsage)> {
+        let _span = tracing::info_span!("provider_prompt", model = %self.model);
+        let hook = hook.track_calls();
+        let attempt = || async {
+            let result = dispatch!(self, |client| {
+                let rig_tools = self.rig_tools(tools);
+                let response = with_agent_tools!(
+                    client,
+                    self,
+                    Some(system_prompt),
+                    hook.clone(),
+                    rig_tools,
+                    |agent| {
+                        agent
+                            .prompt(prompt_text)
+                            .max_turns(max_turns)
+                            .extended_details()
+                            .await
+                    }
+                )?;
+                cassette::record_text(&response.output, Some(response.total_usage));
+                Ok((response.output, response.total_usage.into()))
+            });
+            hook.end_model_call();
+            result
+        };
+        match attempt().await {
+            Err(err) if self.tools_refused(&err) => attempt().await,
+            result => result,
+        }
+    }
+
     /// Streams a multi-turn response with tool execution driven by rig-core.
     ///
     /// Builds an agent with rig-core tool adapters registered and uses
@@ -366,6 +717,14 @@ impl Provider {
     /// automatically executes tool calls and feeds results back to the LLM.
     /// kaze subscribes to the stream purely for rendering.
     ///
+    /// Models known to lack tool support (see [`crate::models`]) get no
+    /// tool definitions, with a warning the first time.
+    ///
+    /// When the final answer is cut off at [`MAX_TOKENS`](crate::constants::MAX_TOKENS),
+    /// up to [`MAX_CONTINUATIONS`](crate::constants::MAX_CONTINUATIONS)
+    /// follow-up requests ask the model to carry on, and the pieces are
+    /// streamed and returned as one response.
+    ///
     /// # Arguments
     ///
     /// * `history` — Full conversation history including system, user, assistant,
@@ -387,11 +746,11 @@ impl Provider {
             .find(|m| m.role == crate::message::Role::System)
             .map(|m| m.text());
 
-        // Last message is the user's prompt
-        let prompt_text = history
+        // Last message is the user's prompt, images included
+        let prompt = history
             .last()
-            .map(|m| m.text().to_string())
-            .unwrap_or_default();
+            .and_then(convert_message_to_rig)
+            .unwrap_or_else(|| RigMessage::user(""));
 
         // Convert history to rig messages (skip system msgs and the last user msg)
         let chat_history: Vec<RigMessage> = history
@@ -401,44 +760,215 @@ impl Provider {
             .filter_map(convert_message_to_rig)
             .collect();
 
+        if !self.supports_tools() && !self.tools_notice_shown.swap(true, Ordering::Relaxed) {
+            renderer.warn(&crate::i18n::tf(
+                "model.no_tools",
+                &[("model", &self.model)],
+            ));
+        }
+
+        if self.seed.is_some()
+            && !self.supports_seed()
+            && !self.seed_notice_shown.swap(true, Ordering::Relaxed)
+        {
+            renderer.warn(&crate::i18n::tf(
+                "model.no_seed",
+                &[("provider", self.kind().as_str())],
+            ));
+        }
+
+        let _span = tracing::info_span!(
+            "provider_stream",
+            provider = self.kind().as_str(),
+            model = %self.model
+        );
+        let hook = hook.track_calls();
         let mut full_response = String::new();
         let mut tool_names: HashMap<String, String> = HashMap::new();
+        let mut prompt = prompt;
+        let mut chat_history = chat_history;
+        let mut continuations = 0;
 
-        dispatch!(self, |client| {
-            // Build rig_tools inside dispatch! so each match arm gets a fresh Vec
-            let rig_tools = tools.to_rig_tools();
-            let mut stream = with_agent_tools!(
-                client,
-                &self.model,
-                system_prompt,
-                hook.clone(),
-                rig_tools,
-                |agent| {
-                    agent
-                        .stream_chat(prompt_text.clone(), chat_history.clone())
-                        .multi_turn(max_turns)
-                        .await
+        loop {
+            let mut segment = String::new();
+            let mut last_call_output: Option<u64> = None;
+
+            let streamed: Result<()> = async {
+                dispatch!(self, |client| {
+                    // Build rig_tools inside dispatch! so each match arm gets a fresh Vec
+                    let rig_tools = self.rig_tools(tools);
+                    let mut stream = with_agent_tools!(
+                        client,
+                        self,
+                        system_prompt,
+                        hook.clone(),
+                        rig_tools,
+                        |agent| {
+                            agent
+                                .stream_chat(prompt.clone(), chat_history.clone())
+                                .multi_turn(max_turns)
+                                .await
+                        }
+                    );
+                    process_stream_with_tools!(
+                        stream,
+                        renderer,
+                        segment,
+                        tool_names,
+                        last_call_output,
+                        hook
+                    );
+                });
+                Ok(())
+            }
+            .await;
+            hook.end_model_call();
+            match streamed {
+                // A refusal comes before anything streams; the retry sends
+                // no tools, so it happens once
+                Err(err) if segment.is_empty() && self.tools_refused(&err) => {
+                    renderer.warn(&crate::i18n::tf(
+                        "model.tools_refused",
+                        &[("model", &self.model)],
+                    ));
+                    continue;
                 }
-            );
-            process_stream_with_tools!(stream, renderer, full_response, tool_names);
-        });
+                streamed => streamed?,
+            }
+            full_response.push_str(&segment);
+
+            // Providers don't surface a uniform stop reason, but a final model
+            // call that used the whole output budget was cut off
+            let truncated = last_call_output.is_some_and(|n| n >= crate::constants::MAX_TOKENS);
+            if !truncated || continuations == crate::constants::MAX_CONTINUATIONS {
+                break;
+            }
+            continuations += 1;
+            chat_history.push(prompt);
+            chat_history.push(RigMessage::assistant(segment));
+            prompt = RigMessage::user(crate::constants::CONTINUE_PROMPT);
+        }
 
         renderer.render_done();
         Ok(full_response)
     }
+
+    /// Asks for a JSON answer conforming to `schema`, without tools, and
+    /// returns it with the token usage.
+    ///
+    /// OpenAI and Ollama get the schema as native structured output. The
+    /// other providers are made to call a single [`STRUCTURED_TOOL`] whose
+    /// parameters are the schema, and its arguments are the answer. Schemas
+    /// whose root isn't an object are wrapped in one, since both mechanisms
+    /// require that. The answer is parsed but not validated; see
+    /// [`OutputSchema::validate`].
+    pub async fn prompt_structured(
+        &self,
+        system_prompt: Option<&str>,
+        prompt: &crate::message::Message,
+        schema: &OutputSchema,
+    ) -> Result<(Value, crate::usage::TokenUsage)> {
+        let native = matches!(self.kind(), ProviderKind::OpenAI | ProviderKind::Ollama);
+        let wrapped = schema.value().get("type") != Some(&json!("object"));
+        let mut parameters = if wrapped {
+            json!({
+                "type": "object",
+                "properties": { "value": schema.value() },
+                "required": ["value"],
+            })
+        } else {
+            schema.value().clone()
+        };
+        if native {
+            if let (Some(title), Some(obj)) = (schema.title(), parameters.as_object_mut()) {
+                obj.insert("title".into(), json!(title));
+            }
+        }
+        let prompt = convert_message_to_rig(prompt).unwrap_or_else(|| RigMessage::user(""));
+
+        let (answer, usage) = dispatch!(self, |client| {
+            let model = client.completion_model(&self.model);
+            let mut request = model
+                .completion_request(prompt.clone())
+                .max_tokens(crate::constants::MAX_TOKENS)
+                .temperature_opt(self.temperature)
+                .additional_params_opt(self.seed_params());
+            if let Some(sys) = system_prompt {
+                request = request.preamble(sys.to_string());
+            }
+            request = if native {
+                let schema = schemars::Schema::try_from(parameters.clone())
+                    .map_err(|e| anyhow::anyhow!("Invalid JSON schema: {}", e))?;
+                request.output_schema(schema)
+            } else {
+                request
+                    .tool(ToolDefinition {
+                        name: STRUCTURED_TOOL.to_string(),
+                        description: "Give your answer. Its arguments are the whole response."
+                            .to_string(),
+                        parameters: parameters.clone(),
+                    })
+                    .tool_choice(ToolChoice::Specific {
+                        function_names: vec![STRUCTURED_TOOL.to_string()],
+                    })
+            };
+            let response = request.send().await?;
+            (structured_answer(response.choice)?, response.usage)
+        });
+        cassette::record_text(&answer.to_string(), Some(usage));
+
+        let answer = if wrapped {
+            answer
+                .get("value")
+                .cloned()
+                .context("The response is missing the wrapped \"value\"")?
+        } else {
+            answer
+        };
+        Ok((answer, usage.into()))
+    }
+}
+
+/// The tool non-native providers must call to give a structured answer.
+const STRUCTURED_TOOL: &str = "respond";
+
+/// Pulls the JSON answer out of a structured response: the arguments of a
+/// [`STRUCTURED_TOOL`] call, or else the text parsed as JSON.
+fn structured_answer(choice: OneOrMany<AssistantContent>) -> Result<Value> {
+    let mut text = String::new();
+    for content in choice {
+        match content {
+            AssistantContent::ToolCall(call) if call.function.name == STRUCTURED_TOOL => {
+                return Ok(call.function.arguments);
+            }
+            AssistantContent::Text(Text { text: t }) => text.push_str(&t),
+            _ => {}
+        }
+    }
+    crate::schema::parse_response(&text)
 }
 
 /// Converts a kaze [`Message`](crate::message::Message) to a rig-core [`RigMessage`].
 ///
 /// Handles all message roles:
-/// - **User** → `RigMessage::User` with text content
+/// - **User** → `RigMessage::User` with text content, then any images
 /// - **Assistant** (text only) → `RigMessage::Assistant` with text content
 /// - **Assistant** (with tool calls) → `RigMessage::Assistant` with `ToolCall` content items
 /// - **Tool** (result) → `RigMessage::User` with `ToolResult` content
 /// - **System** → `None` (system messages are extracted as preamble separately)
 fn convert_message_to_rig(msg: &crate::message::Message) -> Option<RigMessage> {
     match msg.role {
-        crate::message::Role::User => Some(RigMessage::user(msg.text())),
+        crate::message::Role::User => {
+            let images: Vec<UserContent> = msg.images().map(convert_image).collect();
+            if images.is_empty() {
+                return Some(RigMessage::user(msg.text()));
+            }
+            let mut content = vec![UserContent::text(msg.text())];
+            content.extend(images);
+            Some(RigMessage::User {
+                content: OneOrMany::many(content).ok()?,
+            })
+        }
         crate::message::Role::Assistant => {
             if msg.tool_calls.is_empty() {
                 Some(RigMessage::assistant(msg.text()))
@@ -477,3 +1007,15 @@ fn convert_message_to_rig(msg: &crate::message::Message) -> Option<RigMessage> {
         crate::message::Role::System => None,
     }
 }
+
+/// Converts an attached image to rig-core's image content.
+fn convert_image(image: &ImagePart) -> UserContent {
+    let media_type = image
+        .media_type
+        .as_deref()
+        .and_then(ImageMediaType::from_mime_type);
+    match &image.source {
+        ImageSource::Base64(data) => UserContent::image_base64(data.clone(), media_type, None),
+        ImageSource::Url(url) => UserContent::image_url(url.clone(), media_type, None),
+    }
+}
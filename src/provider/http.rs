@@ -0,0 +1,109 @@
+//! Shared HTTP client for all provider traffic.
+//!
+//! rig-core clients each own an HTTP backend. Handing every provider the
+//! same pooled [`reqwest::Client`] means a multi-turn tool loop, compaction
+//! calls, and model listing all reuse warm connections (HTTP/2 where the
+//! server supports it) instead of paying a TLS handshake per round trip.
+//! The `web_fetch` tool gets a sibling that doesn't follow redirects, so
+//! it can check each redirect target against the network permissions.
+
+use anyhow::{Context, Result};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::constants::{HTTP_KEEPALIVE_SECS, HTTP_POOL_IDLE_TIMEOUT_SECS};
+
+static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+static NO_REDIRECT_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+
+/// Returns the process-wide HTTP client, building it on first use.
+///
+/// `reqwest::Client` is reference-counted internally, so the returned
+/// clone shares the same connection pool.
+pub(crate) fn shared_client() -> reqwest::Client {
+    CLIENT.get_or_init(|| build(true)).clone()
+}
+
+/// Like [`shared_client`], but returns redirect responses instead of
+/// following them.
+pub(crate) fn no_redirect_client() -> reqwest::Client {
+    NO_REDIRECT_CLIENT.get_or_init(|| build(false)).clone()
+}
+
+fn build(follow_redirects: bool) -> reqwest::Client {
+    let keepalive = Duration::from_secs(HTTP_KEEPALIVE_SECS);
+    let mut builder = reqwest::Client::builder()
+        .pool_idle_timeout(Duration::from_secs(HTTP_POOL_IDLE_TIMEOUT_SECS))
+        .tcp_keepalive(keepalive)
+        .http2_keep_alive_interval(keepalive)
+        .http2_keep_alive_while_idle(true);
+    if !follow_redirects {
+        builder = builder.redirect(reqwest::redirect::Policy::none());
+    }
+    builder
+        .build()
+        // Only fails if the TLS backend cannot initialize; fall back to
+        // reqwest's defaults rather than aborting
+        .unwrap_or_default()
+}
+
+/// The `[provider.<name>.headers]` configured for `provider`, validated.
+///
+/// For OpenAI, `organization` and `project` (or `OPENAI_ORG_ID` and
+/// `OPENAI_PROJECT_ID`) become the `OpenAI-Organization` and
+/// `OpenAI-Project` headers; an explicit entry in `headers` wins.
+pub(crate) fn custom_headers(config: &Config, provider: &str) -> Result<HeaderMap> {
+    let entry = match provider {
+        "openai" => &config.provider.openai,
+        "anthropic" => &config.provider.anthropic,
+        "ollama" => &config.provider.ollama,
+        "openrouter" => &config.provider.openrouter,
+        _ => &None,
+    };
+    let mut headers = HeaderMap::new();
+    if provider == "openai" {
+        let ids = [
+            (
+                "openai-organization",
+                "organization",
+                entry.as_ref().and_then(|e| e.organization.clone()),
+                "OPENAI_ORG_ID",
+            ),
+            (
+                "openai-project",
+                "project",
+                entry.as_ref().and_then(|e| e.project.clone()),
+                "OPENAI_PROJECT_ID",
+            ),
+        ];
+        for (name, key, configured, env) in ids {
+            let Some(id) = configured
+                .or_else(|| std::env::var(env).ok())
+                .filter(|id| !id.is_empty())
+            else {
+                continue;
+            };
+            let value = HeaderValue::from_str(&id)
+                .with_context(|| format!("Invalid {} in [provider.openai]", key))?;
+            headers.insert(name, value);
+        }
+    }
+    for (name, value) in entry.iter().flat_map(|e| &e.headers) {
+        let key = HeaderName::from_bytes(name.as_bytes()).with_context(|| {
+            format!(
+                "Invalid header name '{}' in [provider.{}.headers]",
+                name, provider
+            )
+        })?;
+        let value = HeaderValue::from_str(value).with_context(|| {
+            format!(
+                "Invalid value for header '{}' in [provider.{}.headers]",
+                name, provider
+            )
+        })?;
+        headers.insert(key, value);
+    }
+    Ok(headers)
+}
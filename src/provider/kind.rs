@@ -4,6 +4,7 @@
 //! and [`default_model_for`] which returns the default model for each provider.
 
 use anyhow::{anyhow, Result};
+use std::str::FromStr;
 
 /// Identifies which LLM provider to use.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -16,25 +17,47 @@ pub enum ProviderKind {
     OpenRouter,
     /// Ollama (local models via OpenAI-compatible API).
     Ollama,
+    /// AWS Bedrock (Claude models, SigV4 auth via the AWS credential chain).
+    Bedrock,
+    /// The scripted offline provider used by `kaze selftest` and tests.
+    Mock,
 }
 
-impl ProviderKind {
+impl FromStr for ProviderKind {
+    type Err = anyhow::Error;
+
     /// Parses a provider name string into a [`ProviderKind`].
     ///
     /// Matching is case-insensitive. Returns an error for unknown providers.
-    pub fn from_str(s: &str) -> Result<Self> {
+    fn from_str(s: &str) -> Result<Self> {
         match s.to_lowercase().as_str() {
             "anthropic" => Ok(Self::Anthropic),
             "openai" => Ok(Self::OpenAI),
             "openrouter" => Ok(Self::OpenRouter),
             "ollama" => Ok(Self::Ollama),
+            "bedrock" => Ok(Self::Bedrock),
+            "mock" => Ok(Self::Mock),
             other => Err(anyhow!(
-                "Unknown provider: {other}. Supported: anthropic, openai, openrouter, ollama"
+                "Unknown provider: {other}. Supported: anthropic, openai, openrouter, ollama, bedrock, mock"
             )),
         }
     }
 }
 
+impl ProviderKind {
+    /// The lowercase name used in config files and CLI flags.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Anthropic => "anthropic",
+            Self::OpenAI => "openai",
+            Self::OpenRouter => "openrouter",
+            Self::Ollama => "ollama",
+            Self::Bedrock => "bedrock",
+            Self::Mock => "mock",
+        }
+    }
+}
+
 /// Returns the default model identifier for a given provider.
 pub fn default_model_for(provider: &ProviderKind) -> &'static str {
     match provider {
@@ -42,5 +65,7 @@ pub fn default_model_for(provider: &ProviderKind) -> &'static str {
         ProviderKind::OpenAI => crate::constants::DEFAULT_OPENAI_MODEL,
         ProviderKind::OpenRouter => crate::constants::DEFAULT_OPENROUTER_MODEL,
         ProviderKind::Ollama => crate::constants::OLLAMA_DEFAULT_MODEL,
+        ProviderKind::Bedrock => crate::constants::DEFAULT_BEDROCK_MODEL,
+        ProviderKind::Mock => crate::constants::MOCK_DEFAULT_MODEL,
     }
 }
@@ -2,11 +2,35 @@
 //!
 //! Displays available models grouped by provider, including dynamically
 //! queried Ollama models. Isolates display/UI concerns from the provider core.
+//!
+//! Static sections print immediately. Dynamic sections are queried
+//! concurrently, each bounded by [`MODEL_LISTING_TIMEOUT_SECS`], and printed
+//! in the order they resolve, so an unreachable server never stalls the
+//! whole listing. [`probe_models`] times a real request to each configured
+//! provider the same way.
 
 use anyhow::Result;
+use futures::future::{BoxFuture, LocalBoxFuture};
+use futures::stream::{FuturesUnordered, StreamExt};
+use std::time::Duration;
 
-use super::resolve::resolve_model;
+use super::client::Provider;
+use super::kind::{default_model_for, ProviderKind};
+use super::offline::{diagnose, Failure};
+use super::resolve::{resolve_model, ModelSelection};
 use crate::config::Config;
+use crate::constants::{MODEL_LISTING_TIMEOUT_SECS, MODEL_PROBE_PROMPT, MODEL_PROBE_TIMEOUT_SECS};
+use crate::metrics::MeteredRenderer;
+use crate::output::RecordingRenderer;
+
+/// Outcome of querying one dynamic provider.
+enum SectionResult {
+    Models(Vec<String>),
+    Unreachable,
+    TimedOut,
+    /// Not queried because `privacy.offline_extras` is set.
+    Skipped,
+}
 
 /// List all available models, grouped by provider.
 pub async fn list_models(config: &Config) -> Result<()> {
@@ -37,26 +61,78 @@ pub async fn list_models(config: &Config) -> Result<()> {
         println!("    {}{marker}", info.name);
     }
 
-    // Ollama (dynamic)
-    println!("\n  ollama:");
-    match list_ollama_models(config).await {
-        Ok(models) if models.is_empty() => {
-            println!("    (no models found -- run `ollama pull llama3`)");
-        }
-        Ok(models) => {
-            for model in &models {
-                let marker = if model == current { " (default)" } else { "" };
-                println!("    {model}{marker}");
+    // Bedrock
+    println!("\n  bedrock:");
+    for info in crate::models::BEDROCK_MODELS {
+        let marker = if info.name == current {
+            " (default)"
+        } else {
+            ""
+        };
+        println!("    {}{marker}", info.name);
+    }
+
+    // Dynamic providers, printed as each one resolves
+    let timeout = Duration::from_secs(MODEL_LISTING_TIMEOUT_SECS);
+    let mut pending: FuturesUnordered<BoxFuture<'_, (&'static str, SectionResult)>> =
+        FuturesUnordered::new();
+    if config.offline_extras() {
+        pending.push(Box::pin(async { ("ollama", SectionResult::Skipped) }));
+    } else {
+        pending.push(Box::pin(query_section(
+            "ollama",
+            timeout,
+            list_ollama_models(config),
+        )));
+    }
+
+    while let Some((name, result)) = pending.next().await {
+        println!("\n  {name}:");
+        match result {
+            SectionResult::Models(models) if models.is_empty() => {
+                println!("    (no models found -- run `ollama pull llama3`)");
+            }
+            SectionResult::Models(models) => {
+                for model in &models {
+                    let marker = if model == current { " (default)" } else { "" };
+                    let tools = if crate::models::supports_tools(model) {
+                        ""
+                    } else {
+                        " (no tools)"
+                    };
+                    println!("    {model}{marker}{tools}");
+                }
+            }
+            SectionResult::Unreachable => {
+                println!("    ({name} not running)");
+            }
+            SectionResult::TimedOut => {
+                println!("    ({name} did not respond within {MODEL_LISTING_TIMEOUT_SECS}s)");
+            }
+            SectionResult::Skipped => {
+                println!("    (not queried: privacy.offline_extras is set)");
             }
-        }
-        Err(_) => {
-            println!("    (ollama not running)");
         }
     }
 
     Ok(())
 }
 
+/// Runs a dynamic listing query under `timeout`, tagging the result with
+/// the section name.
+async fn query_section(
+    name: &'static str,
+    timeout: Duration,
+    query: impl std::future::Future<Output = Result<Vec<String>>>,
+) -> (&'static str, SectionResult) {
+    let result = match tokio::time::timeout(timeout, query).await {
+        Ok(Ok(models)) => SectionResult::Models(models),
+        Ok(Err(_)) => SectionResult::Unreachable,
+        Err(_) => SectionResult::TimedOut,
+    };
+    (name, result)
+}
+
 /// Query Ollama's local API for available models.
 async fn list_ollama_models(config: &Config) -> Result<Vec<String>> {
     let base_url = config
@@ -68,7 +144,13 @@ async fn list_ollama_models(config: &Config) -> Result<Vec<String>> {
 
     let url = format!("{base_url}/api/tags");
 
-    let resp: serde_json::Value = reqwest::get(&url).await?.json().await?;
+    let resp: serde_json::Value = super::http::shared_client()
+        .get(&url)
+        .headers(super::http::custom_headers(config, "ollama")?)
+        .send()
+        .await?
+        .json()
+        .await?;
 
     let models = resp["models"]
         .as_array()
@@ -81,3 +163,133 @@ async fn list_ollama_models(config: &Config) -> Result<Vec<String>> {
 
     Ok(models)
 }
+
+/// Outcome of probing one provider.
+enum ProbeResult {
+    /// Time to the first token and to the end of the reply.
+    Answered(Duration, Duration),
+    Failed(String),
+    TimedOut,
+}
+
+/// Sends [`MODEL_PROBE_PROMPT`] to every configured provider at once and
+/// prints each one's first-token latency as it answers, then the fastest.
+///
+/// A provider counts as configured when it has an API key (or, for Ollama
+/// and Bedrock, a config section), or is the one currently selected. Each
+/// uses the selected model if it's the current provider, otherwise that
+/// provider's default.
+pub async fn probe_models(config: &Config) -> Result<()> {
+    anyhow::ensure!(
+        !config.offline_extras(),
+        "--probe calls every configured provider, which privacy.offline_extras rules out"
+    );
+    let current = resolve_model(None, None, config)?;
+    let selections: Vec<ModelSelection> = [
+        ProviderKind::Anthropic,
+        ProviderKind::OpenAI,
+        ProviderKind::OpenRouter,
+        ProviderKind::Ollama,
+        ProviderKind::Bedrock,
+    ]
+    .into_iter()
+    .filter(|kind| *kind == current.provider || is_configured(*kind, config))
+    .map(|kind| ModelSelection {
+        provider: kind,
+        model: if kind == current.provider {
+            current.model.clone()
+        } else {
+            default_model_for(&kind).to_string()
+        },
+    })
+    .collect();
+
+    println!("\nProbing providers ({MODEL_PROBE_PROMPT:?}):\n");
+    let mut pending: FuturesUnordered<LocalBoxFuture<'_, (&ModelSelection, ProbeResult)>> =
+        FuturesUnordered::new();
+    for selection in &selections {
+        pending.push(Box::pin(async move {
+            (selection, probe_model(config, selection).await)
+        }));
+    }
+
+    let mut fastest: Option<(&ModelSelection, Duration)> = None;
+    while let Some((selection, result)) = pending.next().await {
+        let name = selection.provider.as_str();
+        let model = &selection.model;
+        match result {
+            ProbeResult::Answered(first, total) => {
+                println!(
+                    "  {name:<11} {model:<28} first token {}ms, total {}ms",
+                    first.as_millis(),
+                    total.as_millis()
+                );
+                if fastest.is_none_or(|(_, best)| first < best) {
+                    fastest = Some((selection, first));
+                }
+            }
+            ProbeResult::Failed(reason) => println!("  {name:<11} {model:<28} {reason}"),
+            ProbeResult::TimedOut => {
+                println!("  {name:<11} {model:<28} no reply within {MODEL_PROBE_TIMEOUT_SECS}s")
+            }
+        }
+    }
+    if let Some((selection, first)) = fastest {
+        println!(
+            "\nFastest right now: {}/{} ({}ms to first token)",
+            selection.provider.as_str(),
+            selection.model,
+            first.as_millis()
+        );
+    }
+    Ok(())
+}
+
+/// Whether the user has set up `kind`, without contacting it.
+fn is_configured(kind: ProviderKind, config: &Config) -> bool {
+    match kind {
+        ProviderKind::Ollama => config.provider.ollama.is_some(),
+        ProviderKind::Bedrock => cfg!(feature = "bedrock") && config.provider.bedrock.is_some(),
+        other => config.resolve_api_key(other.as_str()).is_some(),
+    }
+}
+
+/// Streams the probe prompt through `selection`, timing the first token.
+async fn probe_model(config: &Config, selection: &ModelSelection) -> ProbeResult {
+    let provider = match Provider::from_config(config, selection) {
+        Ok(provider) => provider,
+        Err(e) => return ProbeResult::Failed(first_line(&format!("{:#}", e))),
+    };
+    let mut recorder = RecordingRenderer::new();
+    let mut metered = MeteredRenderer::new(&mut recorder);
+    let timeout = Duration::from_secs(MODEL_PROBE_TIMEOUT_SECS);
+    let result = tokio::time::timeout(
+        timeout,
+        provider.stream(MODEL_PROBE_PROMPT, None, &mut metered),
+    )
+    .await;
+    let metrics = metered.finish();
+    match result {
+        Err(_) => ProbeResult::TimedOut,
+        Ok(Err(e)) => ProbeResult::Failed(match diagnose(&e, selection.provider, config).await {
+            Some(Failure::Dns(host)) => format!("can't resolve {host} (offline?)"),
+            Some(Failure::Unreachable(host)) => format!("can't connect to {host}"),
+            Some(Failure::Auth) => "credentials rejected".to_string(),
+            Some(Failure::RateLimit) => "rate limited".to_string(),
+            None => first_line(&format!("{:#}", e)),
+        }),
+        Ok(Ok(_)) => match metrics.time_to_first_token {
+            Some(first) => ProbeResult::Answered(first, metrics.total),
+            None => ProbeResult::Failed("answered with nothing".to_string()),
+        },
+    }
+}
+
+/// The first line of an error message, cut to fit a table row.
+fn first_line(message: &str) -> String {
+    let line = message.lines().next().unwrap_or_default();
+    match line.char_indices().nth(100) {
+        Some((end, _)) => format!("{}...", &line[..end]),
+        None => line.to_string(),
+    }
+}
@@ -0,0 +1,282 @@
+//! A scripted provider that never touches the network, for `kaze selftest`
+//! and tests of the agent loop.
+//!
+//! A [`MockScript`] holds the replies the model gives, in order: text, or a
+//! tool call that rig-core executes like a real one. Once the script runs
+//! out the mock echoes the last message it was sent, so `--model mock/echo`
+//! works as a stand-in for a real model. Every request's last message is
+//! recorded for checking what the model was shown. A script loaded from a
+//! cassette (see [`super::cassette`]) fails instead of echoing.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use rig::client::CompletionClient;
+use rig::completion::{
+    self, AssistantContent, CompletionError, CompletionRequest, CompletionResponse, GetTokenUsage,
+    Usage,
+};
+use rig::message::{Message as RigMessage, ToolResultContent, UserContent};
+use rig::streaming::{RawStreamingChoice, RawStreamingToolCall, StreamingCompletionResponse};
+use rig::OneOrMany;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::cassette::RecordedCall;
+
+/// One reply of the scripted model.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MockReply {
+    Text(String),
+    /// A call of the tool `name` with arguments `args`.
+    ToolCall {
+        name: String,
+        args: Value,
+    },
+    /// A call replayed from a cassette: its text streamed in the recorded
+    /// chunks, then its tool calls, then its error if it failed.
+    Recorded(RecordedCall),
+}
+
+impl MockReply {
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::Text(text.into())
+    }
+
+    pub fn tool(name: impl Into<String>, args: Value) -> Self {
+        Self::ToolCall {
+            name: name.into(),
+            args,
+        }
+    }
+}
+
+/// The replies still to give and the requests seen so far. Clones share
+/// both, so a test keeps one to inspect after handing another to the
+/// provider.
+#[derive(Debug, Clone, Default)]
+pub struct MockScript {
+    replies: Arc<Mutex<VecDeque<MockReply>>>,
+    requests: Arc<Mutex<Vec<Value>>>,
+    /// Fail rather than echo once the replies run out.
+    strict: bool,
+}
+
+impl MockScript {
+    pub fn new(replies: impl IntoIterator<Item = MockReply>) -> Self {
+        Self {
+            replies: Arc::new(Mutex::new(replies.into_iter().collect())),
+            requests: Arc::default(),
+            strict: false,
+        }
+    }
+
+    /// Like [`new`](Self::new), but a request past the last reply is an
+    /// error.
+    pub fn replay(replies: impl IntoIterator<Item = MockReply>) -> Self {
+        Self {
+            strict: true,
+            ..Self::new(replies)
+        }
+    }
+
+    /// The last message of each request so far, as rig-core JSON.
+    pub fn requests(&self) -> Vec<Value> {
+        self.requests.lock().expect("mock lock").clone()
+    }
+
+    /// How many scripted replies are left.
+    pub fn remaining(&self) -> usize {
+        self.replies.lock().expect("mock lock").len()
+    }
+
+    /// Records `request` and takes the next reply, echoing once the script
+    /// is used up.
+    fn reply(&self, request: &CompletionRequest) -> Result<(MockReply, Usage), CompletionError> {
+        let last = request.chat_history.last();
+        let sent = serde_json::to_value(&last).unwrap_or_default();
+        let next = self.replies.lock().expect("mock lock").pop_front();
+        let reply = match next {
+            Some(reply) => reply,
+            None if self.strict => {
+                return Err(CompletionError::ProviderError(format!(
+                    "The cassette has no response left for request {}",
+                    self.requests.lock().expect("mock lock").len() + 1
+                )))
+            }
+            None => MockReply::Text(echo(&last)),
+        };
+
+        // Rough and deterministic, enough for usage and cost reporting
+        let mut usage = Usage::new();
+        usage.input_tokens =
+            (serde_json::to_string(&request.chat_history).map_or(0, |s| s.len()) / 4) as u64;
+        usage.output_tokens = match &reply {
+            MockReply::Text(text) => text.len() / 4,
+            MockReply::ToolCall { args, .. } => args.to_string().len() / 4,
+            MockReply::Recorded(call) => serde_json::to_string(call).map_or(0, |s| s.len()) / 4,
+        } as u64;
+        usage.total_tokens = usage.input_tokens + usage.output_tokens;
+        if let MockReply::Recorded(RecordedCall {
+            usage: Some(recorded),
+            ..
+        }) = &reply
+        {
+            usage = *recorded;
+        }
+
+        self.requests.lock().expect("mock lock").push(sent);
+        Ok((reply, usage))
+    }
+}
+
+/// The text of `message`, or of the tool results it carries.
+fn echo(message: &RigMessage) -> String {
+    let RigMessage::User { content } = message else {
+        return String::new();
+    };
+    content
+        .iter()
+        .filter_map(|part| match part {
+            UserContent::Text(text) => Some(text.text.clone()),
+            UserContent::ToolResult(result) => Some(
+                result
+                    .content
+                    .iter()
+                    .filter_map(|c| match c {
+                        ToolResultContent::Text(text) => Some(text.text.clone()),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            ),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The rig-core client for [`MockModel`].
+#[derive(Clone)]
+pub(super) struct MockClient {
+    pub script: MockScript,
+}
+
+impl CompletionClient for MockClient {
+    type CompletionModel = MockModel;
+}
+
+#[derive(Clone)]
+pub(super) struct MockModel {
+    script: MockScript,
+}
+
+/// The mock's raw response, carrying only its usage.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct MockResponse {
+    usage: Usage,
+}
+
+impl GetTokenUsage for MockResponse {
+    fn token_usage(&self) -> Option<Usage> {
+        Some(self.usage)
+    }
+}
+
+#[allow(refining_impl_trait)]
+impl completion::CompletionModel for MockModel {
+    type Response = MockResponse;
+    type StreamingResponse = MockResponse;
+    type Client = MockClient;
+
+    fn make(client: &Self::Client, _model: impl Into<String>) -> Self {
+        Self {
+            script: client.script.clone(),
+        }
+    }
+
+    async fn completion(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<CompletionResponse<MockResponse>, CompletionError> {
+        let (reply, usage) = self.script.reply(&request)?;
+        if let MockReply::Recorded(RecordedCall {
+            error: Some(error), ..
+        }) = &reply
+        {
+            return Err(CompletionError::ProviderError(error.clone()));
+        }
+        let mut content = Vec::new();
+        for choice in choices(reply) {
+            match choice {
+                RawStreamingChoice::Message(text) => match content.last_mut() {
+                    Some(AssistantContent::Text(last)) => last.text.push_str(&text),
+                    _ => content.push(AssistantContent::text(text)),
+                },
+                RawStreamingChoice::ToolCall(call) => content.push(AssistantContent::tool_call(
+                    call.id,
+                    call.name,
+                    call.arguments,
+                )),
+                _ => {}
+            }
+        }
+        let choice =
+            OneOrMany::many(content).unwrap_or_else(|_| OneOrMany::one(AssistantContent::text("")));
+        Ok(CompletionResponse {
+            choice,
+            usage,
+            raw_response: MockResponse { usage },
+            message_id: None,
+        })
+    }
+
+    async fn stream(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<StreamingCompletionResponse<MockResponse>, CompletionError> {
+        let (reply, usage) = self.script.reply(&request)?;
+        // A recorded failure streams what came before it, then the error
+        let error = match &reply {
+            MockReply::Recorded(call) => call.error.clone(),
+            _ => None,
+        };
+        let end = match error {
+            Some(error) => Err(CompletionError::ProviderError(error)),
+            None => Ok(RawStreamingChoice::FinalResponse(MockResponse { usage })),
+        };
+        let items: Vec<_> = choices(reply).into_iter().map(Ok).chain([end]).collect();
+        Ok(StreamingCompletionResponse::stream(Box::pin(
+            futures::stream::iter(items),
+        )))
+    }
+}
+
+/// The streamed pieces of `reply`, without the final response.
+fn choices(reply: MockReply) -> Vec<RawStreamingChoice<MockResponse>> {
+    let tool_call = |name: String, args: Value| {
+        let id = format!("mock_{}", call_id());
+        RawStreamingChoice::ToolCall(
+            RawStreamingToolCall::new(id.clone(), name, args).with_call_id(id),
+        )
+    };
+    match reply {
+        MockReply::Text(text) => vec![RawStreamingChoice::Message(text)],
+        MockReply::ToolCall { name, args } => vec![tool_call(name, args)],
+        MockReply::Recorded(call) => call
+            .chunks
+            .into_iter()
+            .map(RawStreamingChoice::Message)
+            .chain(
+                call.tool_calls
+                    .into_iter()
+                    .map(|call| tool_call(call.name, call.args)),
+            )
+            .collect(),
+    }
+}
+
+/// A short unique suffix for tool call IDs.
+fn call_id() -> String {
+    uuid::Uuid::new_v4().simple().to_string()[..12].to_string()
+}
@@ -4,13 +4,22 @@
 //! dispatch, keeping provider-specific details out of the CLI layer. Supports
 //! Anthropic, OpenAI, OpenRouter, and Ollama (local) via [`ProviderKind`].
 
+mod cassette;
 mod client;
+mod http;
 mod kind;
 mod listing;
+mod mock;
+mod offline;
 mod resolve;
+pub(crate) mod supersede;
 
-pub use client::Provider;
+pub use cassette::{load_cassette, RecordedCall, RecordedToolCall};
+pub use client::{Provider, RequestParams};
+pub(crate) use http::{no_redirect_client, shared_client};
 #[allow(unused_imports)]
 pub use kind::{default_model_for, ProviderKind};
-pub use listing::list_models;
-pub use resolve::{resolve_model, ModelSelection};
+pub use listing::{list_models, probe_models};
+pub use mock::{MockReply, MockScript};
+pub use offline::{explain, explain_error, probe, Failure};
+pub use resolve::{resolve_model, resolve_model_spec, ModelSelection};
@@ -0,0 +1,194 @@
+//! Network failure diagnosis for provider requests.
+//!
+//! rig folds the HTTP client's error chain into its message, so a failed
+//! request arrives as flat text. Rejected credentials and rate limiting are
+//! recognised by status code and wording; a transport failure is followed
+//! by a quick probe of the API host, which tells a failed DNS lookup (the
+//! usual sign of being offline) from a host that can't be reached. Chat
+//! runs the same [`probe`] at startup to switch to `offline_fallback`.
+
+use regex::Regex;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use super::kind::ProviderKind;
+use crate::config::Config;
+use crate::constants::NETWORK_PROBE_TIMEOUT_MS;
+
+/// Why a provider request failed, when kaze can tell.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Failure {
+    /// The API host's name doesn't resolve.
+    Dns(String),
+    /// The name resolves but no connection can be made.
+    Unreachable(String),
+    /// The provider rejected the credentials.
+    Auth,
+    /// The provider is rate limiting requests.
+    RateLimit,
+}
+
+impl Failure {
+    /// The host a network failure is about.
+    pub fn host(&self) -> Option<&str> {
+        match self {
+            Failure::Dns(host) | Failure::Unreachable(host) => Some(host),
+            Failure::Auth | Failure::RateLimit => None,
+        }
+    }
+}
+
+/// Host and port of the provider's API, if kaze knows it.
+fn api_host(kind: ProviderKind, config: &Config) -> Option<(String, u16)> {
+    let host = match kind {
+        ProviderKind::Anthropic => "api.anthropic.com",
+        ProviderKind::OpenAI => "api.openai.com",
+        ProviderKind::OpenRouter => "openrouter.ai",
+        ProviderKind::Ollama => {
+            let base_url = config
+                .provider
+                .ollama
+                .as_ref()
+                .and_then(|o| o.base_url.as_deref())
+                .unwrap_or(crate::constants::OLLAMA_DEFAULT_BASE_URL);
+            let url = reqwest::Url::parse(base_url).ok()?;
+            return Some((url.host_str()?.to_string(), url.port_or_known_default()?));
+        }
+        ProviderKind::Bedrock => {
+            let region = config
+                .provider
+                .bedrock
+                .as_ref()
+                .and_then(|b| b.region.clone())
+                .or_else(|| std::env::var("AWS_REGION").ok())?;
+            return Some((format!("bedrock-runtime.{}.amazonaws.com", region), 443));
+        }
+        ProviderKind::Mock => return None,
+    };
+    Some((host.to_string(), 443))
+}
+
+/// Checks that the provider's API host resolves and accepts connections,
+/// giving each step [`NETWORK_PROBE_TIMEOUT_MS`]. Returns `None` when it
+/// does, when the host isn't known, or when `privacy.offline_extras` rules
+/// out connections kaze doesn't need.
+pub async fn probe(kind: ProviderKind, config: &Config) -> Option<Failure> {
+    if config.offline_extras() {
+        return None;
+    }
+    let (host, port) = api_host(kind, config)?;
+    let timeout = Duration::from_millis(NETWORK_PROBE_TIMEOUT_MS);
+
+    let lookup = tokio::net::lookup_host(format!("{}:{}", host, port));
+    let addrs: Vec<_> = match tokio::time::timeout(timeout, lookup).await {
+        Ok(Ok(addrs)) => addrs.collect(),
+        _ => return Some(Failure::Dns(host)),
+    };
+    if addrs.is_empty() {
+        return Some(Failure::Dns(host));
+    }
+    let connect = tokio::net::TcpStream::connect(&addrs[..]);
+    match tokio::time::timeout(timeout, connect).await {
+        Ok(Ok(_)) => None,
+        _ => Some(Failure::Unreachable(format!("{}:{}", host, port))),
+    }
+}
+
+/// Works out why `err` happened, probing the network for transport errors.
+pub async fn diagnose(err: &anyhow::Error, kind: ProviderKind, config: &Config) -> Option<Failure> {
+    static AUTH: OnceLock<Regex> = OnceLock::new();
+    static RATE: OnceLock<Regex> = OnceLock::new();
+    static TRANSPORT: OnceLock<Regex> = OnceLock::new();
+    let auth = AUTH.get_or_init(|| {
+        Regex::new(r"(?i)\b40[13]\b|unauthori[sz]ed|authentication|invalid[ _-]?(x-)?api[ _-]?key|incorrect api key")
+            .expect("auth pattern is valid")
+    });
+    let rate = RATE.get_or_init(|| {
+        Regex::new(r"(?i)\b429\b|rate[ _-]?limit|too many requests").expect("rate pattern is valid")
+    });
+    let transport = TRANSPORT.get_or_init(|| {
+        Regex::new(r"(?i)error sending request|dns error|failed to lookup address|connection refused|tcp connect|connect error|timed out")
+            .expect("transport pattern is valid")
+    });
+
+    let text = format!("{:#}", err);
+    if auth.is_match(&text) {
+        return Some(Failure::Auth);
+    }
+    if rate.is_match(&text) {
+        return Some(Failure::RateLimit);
+    }
+    if !transport.is_match(&text) {
+        return None;
+    }
+    probe(kind, config).await
+}
+
+/// Describes `failure` in a sentence or two, with what to do about it.
+pub fn explain(failure: &Failure, kind: ProviderKind, config: &Config) -> String {
+    let provider = kind.as_str();
+    match failure {
+        Failure::Dns(host) => format!(
+            "Can't resolve {}: you appear to be offline, or DNS is failing.{}",
+            host,
+            fallback_hint(kind, config)
+        ),
+        Failure::Unreachable(host) if kind == ProviderKind::Ollama => {
+            format!(
+                "Can't connect to Ollama at {}. Is `ollama serve` running?",
+                host
+            )
+        }
+        Failure::Unreachable(host) => format!(
+            "Can't connect to {}: the network or a firewall is blocking it.{}",
+            host,
+            fallback_hint(kind, config)
+        ),
+        Failure::Auth => match kind {
+            ProviderKind::Bedrock => {
+                "Bedrock rejected the AWS credentials. Check the profile and region under \
+                 [provider.bedrock]."
+                    .to_string()
+            }
+            _ => format!(
+                "{} rejected the API key. Check {}_API_KEY or the api_key under [provider.{}] \
+                 in config.toml.",
+                provider,
+                provider.to_uppercase(),
+                provider
+            ),
+        },
+        Failure::RateLimit => format!(
+            "{} is rate limiting requests. Wait a moment and try again.",
+            provider
+        ),
+    }
+}
+
+/// Suggests the configured offline fallback, or configuring one.
+fn fallback_hint(kind: ProviderKind, config: &Config) -> String {
+    match config.offline_fallback.as_deref() {
+        Some(fallback) if fallback.split_once('/').map(|(p, _)| p) != Some(kind.as_str()) => {
+            format!(
+                " To continue with the local model, use `/model {}` in chat or `--model {}`.",
+                fallback, fallback
+            )
+        }
+        Some(_) => String::new(),
+        None => " Set offline_fallback = \"ollama/llama3\" in config.toml to have chat switch \
+                 to a local model when offline."
+            .to_string(),
+    }
+}
+
+/// Puts an explanation in front of `err` when [`diagnose`] finds the cause.
+pub async fn explain_error(
+    err: anyhow::Error,
+    kind: ProviderKind,
+    config: &Config,
+) -> anyhow::Error {
+    match diagnose(&err, kind, config).await {
+        Some(failure) => err.context(explain(&failure, kind, config)),
+        None => err,
+    }
+}
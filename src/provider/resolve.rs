@@ -4,12 +4,18 @@
 //! and hardcoded defaults. Supports `provider/model` shorthand syntax.
 
 use anyhow::Result;
+use colored::Colorize;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use super::kind::{default_model_for, ProviderKind};
 use crate::config::Config;
 
 use crate::constants::DEFAULT_PROVIDER;
 
+/// Set once the stale-pin warning has been shown.
+static PIN_WARNED: AtomicBool = AtomicBool::new(false);
+
 /// Resolved provider + model pair.
 pub struct ModelSelection {
     pub provider: ProviderKind,
@@ -17,14 +23,17 @@ pub struct ModelSelection {
 }
 
 /// Resolve which provider and model to use.
-/// Priority: CLI flags > config.toml > defaults.
+/// Priority: CLI flags > `[pin]` > config.toml > defaults.
 ///
 /// Accepts these formats:
 ///   --model anthropic/claude-sonnet-4-5  (provider/model shorthand, only when --provider is omitted)
 ///   --provider openrouter --model "org/model-name"  (slash preserved as model name)
 ///   --provider anthropic --model claude-sonnet-4-5
 ///   --provider anthropic  (uses provider's default model)
-///   (nothing)  (uses config.toml, then hardcoded default)
+///   (nothing)  (uses the project pin, then config.toml, then hardcoded default)
+///
+/// A pinned model missing from kaze's model list is still used, with a
+/// warning printed once per process.
 pub fn resolve_model(
     cli_provider: Option<&str>,
     cli_model: Option<&str>,
@@ -42,6 +51,20 @@ pub fn resolve_model(
         }
     }
 
+    if cli_provider.is_none() && cli_model.is_none() {
+        if let Some((provider, model)) = config.pinned_selection() {
+            if let Some(warning) = config.pin_warning() {
+                if !PIN_WARNED.swap(true, Ordering::Relaxed) {
+                    eprintln!("{} {}", "warning:".yellow().bold(), warning);
+                }
+            }
+            return Ok(ModelSelection {
+                provider: ProviderKind::from_str(provider)?,
+                model: model.to_string(),
+            });
+        }
+    }
+
     // Resolve provider
     let provider_str = cli_provider
         .or(config.provider_name())
@@ -56,3 +79,25 @@ pub fn resolve_model(
 
     Ok(ModelSelection { provider, model })
 }
+
+/// Resolves a `[provider/]model` spec relative to the provider in use, as
+/// `/model` and `[tools.task] model` take it.
+///
+/// A prefix names a provider only if it parses as one, so slash-containing
+/// OpenRouter names (`openai/gpt-4o`) stay on the current provider; spell
+/// out `openrouter/openai/gpt-4o` to switch to OpenRouter.
+pub fn resolve_model_spec(
+    spec: &str,
+    current: ProviderKind,
+    config: &Config,
+) -> Result<ModelSelection> {
+    let names_provider = spec
+        .split_once('/')
+        .is_some_and(|(prefix, _)| prefix.parse::<ProviderKind>().is_ok());
+    let cli_provider = if names_provider {
+        None
+    } else {
+        Some(current.as_str())
+    };
+    resolve_model(cli_provider, Some(spec), config)
+}
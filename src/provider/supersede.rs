@@ -0,0 +1,114 @@
+//! Deduplication of repeated read-only tool results in the live context.
+//!
+//! When the agent re-reads an unchanged file or repeats a grep, glob,
+//! listing, or fetch within a turn, every copy of the same output would be
+//! sent again with each later request. [`Superseding`] wraps a provider's
+//! completion model and, before each request, replaces all but the newest
+//! copy with a short stub, so the model keeps the latest output in full
+//! where it last asked for it. Only the request is rewritten; the saved
+//! session is untouched.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+use rig::completion::{
+    CompletionError, CompletionModel, CompletionRequest, CompletionResponse, Message,
+};
+use rig::message::{AssistantContent, Text, ToolResultContent, UserContent};
+use rig::streaming::StreamingCompletionResponse;
+
+use crate::constants::{TOOL_DEDUP_MIN_CHARS, TOOL_DEDUP_TOOLS};
+
+/// A completion model whose requests have superseded tool results stubbed.
+#[derive(Clone)]
+pub(super) struct Superseding<M>(pub M);
+
+impl<M> CompletionModel for Superseding<M>
+where
+    M: CompletionModel + 'static,
+{
+    type Response = M::Response;
+    type StreamingResponse = M::StreamingResponse;
+    type Client = M::Client;
+
+    fn make(client: &Self::Client, model: impl Into<String>) -> Self {
+        Self(M::make(client, model))
+    }
+
+    async fn completion(
+        &self,
+        mut request: CompletionRequest,
+    ) -> Result<CompletionResponse<M::Response>, CompletionError> {
+        supersede_results(request.chat_history.iter_mut());
+        self.0.completion(request).await
+    }
+
+    async fn stream(
+        &self,
+        mut request: CompletionRequest,
+    ) -> Result<StreamingCompletionResponse<M::StreamingResponse>, CompletionError> {
+        supersede_results(request.chat_history.iter_mut());
+        self.0.stream(request).await
+    }
+}
+
+/// Replaces the output of each deduplicated tool call with a stub when a
+/// later call with the same arguments returned the same output, returning
+/// how many were replaced.
+pub(crate) fn supersede_results<'a>(messages: impl IntoIterator<Item = &'a mut Message>) -> usize {
+    let mut messages: Vec<&mut Message> = messages.into_iter().collect();
+
+    // The tool and arguments of each call, by ID; serde_json orders object
+    // keys, so equal arguments give equal keys
+    let mut calls: HashMap<String, (String, String)> = HashMap::new();
+    for message in &messages {
+        if let Message::Assistant { content, .. } = &**message {
+            for part in content.iter() {
+                if let AssistantContent::ToolCall(call) = part {
+                    if TOOL_DEDUP_TOOLS.contains(&call.function.name.as_str()) {
+                        let key = format!("{} {}", call.function.name, call.function.arguments);
+                        calls.insert(call.id.clone(), (call.function.name.clone(), key));
+                    }
+                }
+            }
+        }
+    }
+
+    // Newest first, so the first copy seen is the one kept
+    let mut seen: HashSet<(String, u64)> = HashSet::new();
+    let mut replaced = 0;
+    for message in messages.iter_mut().rev() {
+        let Message::User { content } = &mut **message else {
+            continue;
+        };
+        let parts: Vec<&mut UserContent> = content.iter_mut().collect();
+        for part in parts.into_iter().rev() {
+            let UserContent::ToolResult(result) = part else {
+                continue;
+            };
+            let Some((name, key)) = calls.get(&result.id) else {
+                continue;
+            };
+            let ToolResultContent::Text(Text { text }) = result.content.first_ref() else {
+                continue;
+            };
+            if result.content.len() > 1 || text.len() < TOOL_DEDUP_MIN_CHARS {
+                continue;
+            }
+            let mut hasher = DefaultHasher::new();
+            text.hash(&mut hasher);
+            if seen.insert((key.clone(), hasher.finish())) {
+                continue;
+            }
+            *result.content.first_mut() = ToolResultContent::Text(Text {
+                text: format!(
+                    "(Superseded: a later {} call with these arguments returned the same output.)",
+                    name
+                ),
+            });
+            replaced += 1;
+        }
+    }
+    replaced
+}
@@ -0,0 +1,269 @@
+//! Bug-report bundles for `kaze report`, and the error log they draw on.
+//!
+//! Errors that end a command and panics are appended to `kaze.log` in the
+//! cache directory by [`log_event`]. [`write_report`] gathers that log's
+//! tail, the config with secrets redacted, version and OS details, and the
+//! last few records of the most recent session into a `.tar.gz` that can
+//! be attached to a GitHub issue.
+
+use anyhow::{Context, Result};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::constants::{
+    LOG_FILENAME, LOG_MAX_BYTES, REPORT_LOG_LINES, REPORT_RECORD_MAX_CHARS, REPORT_SESSION_RECORDS,
+};
+use crate::session;
+
+/// Config keys whose values are replaced by `<redacted>`, matched as
+/// suffixes of the lowercased key (`api_key`, `auth_token`, ...).
+const SECRET_KEYS: &[&str] = &["key", "token", "secret", "password"];
+
+/// Config keys whose values are redacted when the key matches exactly:
+/// the provider account IDs a key's usage is billed to.
+const ACCOUNT_KEYS: &[&str] = &["organization", "project"];
+
+/// Tables whose every value is redacted (provider headers often carry auth,
+/// MCP server env often carries API keys).
+const SECRET_TABLES: &[&str] = &["headers", "env"];
+
+/// What [`write_report`] produced.
+pub struct Report {
+    /// The `.tar.gz` written.
+    pub path: PathBuf,
+    /// Names of the files inside it.
+    pub files: Vec<String>,
+}
+
+/// Path of the error log.
+pub fn log_path() -> Result<PathBuf> {
+    Ok(Config::cache_dir()?.join(LOG_FILENAME))
+}
+
+/// Appends a timestamped entry to the error log, rotating it to
+/// `kaze.log.1` once it grows past [`LOG_MAX_BYTES`].
+///
+/// Failures are ignored: logging must never turn into a second error.
+pub fn log_event(kind: &str, message: &str) {
+    let _ = try_log(kind, message);
+}
+
+fn try_log(kind: &str, message: &str) -> Result<()> {
+    let path = log_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if fs::metadata(&path).is_ok_and(|m| m.len() > LOG_MAX_BYTES) {
+        fs::rename(&path, path.with_extension("log.1"))?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    writeln!(
+        file,
+        "[{}] {}: {}",
+        chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        kind,
+        message
+    )?;
+    Ok(())
+}
+
+/// Builds the report archive at `output`, or `./kaze-report-<timestamp>.tar.gz`.
+///
+/// The session tail is left out when `include_session` is false. Needs a
+/// `tar` on `PATH`.
+pub fn write_report(output: Option<PathBuf>, include_session: bool) -> Result<Report> {
+    let name = format!(
+        "kaze-report-{}",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    );
+    let output = output.unwrap_or_else(|| PathBuf::from(format!("{}.tar.gz", name)));
+    let private = staging_dir()?;
+    let staging = private.join(&name);
+    let result = fs::create_dir(&staging)
+        .with_context(|| format!("Failed to create {}", staging.display()))
+        .and_then(|()| stage(&staging, include_session))
+        .and_then(|files| {
+            archive(&staging, &output)?;
+            Ok(files)
+        });
+    let _ = fs::remove_dir_all(&private);
+    Ok(Report {
+        path: output,
+        files: result?,
+    })
+}
+
+/// A new, private directory under the data directory to stage a report
+/// in. Its name is random and creating it fails if it exists, so no other
+/// user can swap in their own directory or links to the redacted files.
+fn staging_dir() -> Result<PathBuf> {
+    let parent = Config::data_dir()?;
+    fs::create_dir_all(&parent)
+        .with_context(|| format!("Failed to create {}", parent.display()))?;
+    let id = uuid::Uuid::new_v4().simple().to_string();
+    let dir = parent.join(format!("report-{}", &id[..12]));
+    let mut builder = fs::DirBuilder::new();
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder
+        .create(&dir)
+        .with_context(|| format!("Failed to create {}", dir.display()))?;
+    Ok(dir)
+}
+
+/// Writes the report's files into `dir`, returning their names.
+fn stage(dir: &Path, include_session: bool) -> Result<Vec<String>> {
+    let mut files = Vec::new();
+    let mut put = |name: &str, contents: String| -> Result<()> {
+        fs::write(dir.join(name), contents).with_context(|| format!("Failed to write {}", name))?;
+        files.push(name.to_string());
+        Ok(())
+    };
+
+    put("version.txt", version_info())?;
+    // A broken config is often the bug being reported, so note it rather than fail
+    let config = match Config::load_unresolved() {
+        Ok(config) => redacted_config(&config)?,
+        Err(e) => format!("# Failed to load config: {:#}\n", e),
+    };
+    put("config.toml", config)?;
+    if let Some(log) = log_tail()? {
+        put(LOG_FILENAME, log)?;
+    }
+    if include_session {
+        if let Some(tail) = session_tail()? {
+            put("session.jsonl", tail)?;
+        }
+    }
+    Ok(files)
+}
+
+/// kaze version, enabled features, OS, and the environment it runs in.
+fn version_info() -> String {
+    let mut features = Vec::new();
+    if cfg!(feature = "bedrock") {
+        features.push("bedrock");
+    }
+    if cfg!(feature = "sqlite") {
+        features.push("sqlite");
+    }
+    let mut lines = vec![
+        format!("kaze {}", env!("CARGO_PKG_VERSION")),
+        format!(
+            "features: {}",
+            if features.is_empty() {
+                "none".to_string()
+            } else {
+                features.join(", ")
+            }
+        ),
+        format!("os: {} {}", std::env::consts::OS, std::env::consts::ARCH),
+    ];
+    if let Ok(out) = std::process::Command::new("uname").arg("-srm").output() {
+        if out.status.success() {
+            lines.push(format!(
+                "uname: {}",
+                String::from_utf8_lossy(&out.stdout).trim()
+            ));
+        }
+    }
+    for var in ["TERM", "COLORTERM", "LANG", "SHELL"] {
+        if let Ok(value) = std::env::var(var) {
+            lines.push(format!("{}: {}", var, value));
+        }
+    }
+    if let Ok(path) = Config::config_path() {
+        lines.push(format!("config: {}", path.display()));
+    }
+    if let Ok(Some(path)) = Config::project_config_path() {
+        lines.push(format!("project config: {}", path.display()));
+    }
+    if let Ok(dir) = Config::data_dir() {
+        lines.push(format!("data dir: {}", dir.display()));
+    }
+    lines.join("\n") + "\n"
+}
+
+/// The merged, unresolved config as TOML, with keys, tokens, account IDs,
+/// and header values replaced.
+pub(crate) fn redacted_config(config: &Config) -> Result<String> {
+    let mut value = toml::Value::try_from(config).context("Failed to serialize config")?;
+    redact(&mut value, false);
+    toml::to_string_pretty(&value).context("Failed to serialize config")
+}
+
+fn redact(value: &mut toml::Value, all: bool) {
+    match value {
+        toml::Value::Table(table) => {
+            for (key, entry) in table.iter_mut() {
+                let key = key.to_lowercase();
+                let secret = all || SECRET_TABLES.contains(&key.as_str());
+                let sensitive = SECRET_KEYS.iter().any(|k| key.ends_with(k))
+                    || ACCOUNT_KEYS.contains(&key.as_str());
+                if !entry.is_table() && (secret || sensitive) {
+                    *entry = toml::Value::String("<redacted>".to_string());
+                } else {
+                    redact(entry, secret);
+                }
+            }
+        }
+        toml::Value::Array(items) => items.iter_mut().for_each(|item| redact(item, all)),
+        _ => {}
+    }
+}
+
+/// The last [`REPORT_LOG_LINES`] lines of the error log, if there is one.
+fn log_tail() -> Result<Option<String>> {
+    let path = log_path()?;
+    let Ok(contents) = fs::read_to_string(&path) else {
+        return Ok(None);
+    };
+    let lines: Vec<&str> = contents.lines().collect();
+    let skip = lines.len().saturating_sub(REPORT_LOG_LINES);
+    Ok(Some(lines[skip..].join("\n") + "\n"))
+}
+
+/// The last [`REPORT_SESSION_RECORDS`] records of the most recently
+/// updated session, each cut to [`REPORT_RECORD_MAX_CHARS`].
+fn session_tail() -> Result<Option<String>> {
+    let mut sessions = session::Session::list_all().unwrap_or_default();
+    sessions.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    let Some(latest) = sessions.first() else {
+        return Ok(None);
+    };
+    let records = session::store()?.records(&latest.id)?;
+    let skip = records.len().saturating_sub(REPORT_SESSION_RECORDS);
+    let mut tail = format!("# session {} ({})\n", latest.id, latest.model);
+    for record in &records[skip..] {
+        if record.chars().count() > REPORT_RECORD_MAX_CHARS {
+            let cut: String = record.chars().take(REPORT_RECORD_MAX_CHARS).collect();
+            tail.push_str(&cut);
+            tail.push_str(" ...[truncated]");
+        } else {
+            tail.push_str(record);
+        }
+        tail.push('\n');
+    }
+    Ok(Some(tail))
+}
+
+/// Packs `dir` into a gzipped tarball at `output`.
+fn archive(dir: &Path, output: &Path) -> Result<()> {
+    let parent = dir.parent().unwrap_or(Path::new("."));
+    let name = dir.file_name().unwrap_or_default();
+    let output = std::path::absolute(output).unwrap_or_else(|_| output.to_path_buf());
+    let status = std::process::Command::new("tar")
+        .arg("-czf")
+        .arg(&output)
+        .arg("-C")
+        .arg(parent)
+        .arg(name)
+        .status()
+        .context("Failed to run tar")?;
+    if !status.success() {
+        anyhow::bail!("tar exited with {}", status);
+    }
+    Ok(())
+}
@@ -0,0 +1,80 @@
+//! Local cache for one-shot `kaze ask` responses.
+//!
+//! Responses are stored under `~/.cache/kaze/responses/`, one JSON file per
+//! request, keyed by a hash of the provider, model, system prompt, and
+//! question. Entries older than the configured TTL are treated as misses.
+//! The cache is opt-in (`[cache] enabled = true`) because a cached answer
+//! skips any tool calls the original run performed.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::Config;
+
+/// A stored response and when it was written.
+#[derive(Serialize, Deserialize)]
+struct CachedResponse {
+    /// Seconds since the Unix epoch.
+    created_at: u64,
+    response: String,
+}
+
+/// Computes the cache key for a one-shot request.
+pub fn request_key(
+    provider: &str,
+    model: &str,
+    system_prompt: Option<&str>,
+    prompt: &str,
+) -> String {
+    let mut hasher = DefaultHasher::new();
+    provider.hash(&mut hasher);
+    model.hash(&mut hasher);
+    system_prompt.hash(&mut hasher);
+    prompt.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Returns the cached response for `key` if it is younger than `ttl_secs`.
+///
+/// Unreadable or corrupt entries are treated as misses.
+pub fn lookup(key: &str, ttl_secs: u64) -> Option<String> {
+    let contents = fs::read_to_string(entry_path(key).ok()?).ok()?;
+    let entry: CachedResponse = serde_json::from_str(&contents).ok()?;
+    if now_secs().saturating_sub(entry.created_at) > ttl_secs {
+        return None;
+    }
+    Some(entry.response)
+}
+
+/// Stores `response` under `key`, replacing any previous entry.
+pub fn store(key: &str, response: &str) -> Result<()> {
+    let path = entry_path(key)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let entry = CachedResponse {
+        created_at: now_secs(),
+        response: response.to_string(),
+    };
+    fs::write(&path, serde_json::to_string(&entry)?)?;
+    Ok(())
+}
+
+/// Returns the cache file path for a request key.
+fn entry_path(key: &str) -> Result<PathBuf> {
+    Ok(Config::cache_dir()?
+        .join("responses")
+        .join(format!("{}.json", key)))
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
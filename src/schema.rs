@@ -0,0 +1,309 @@
+//! JSON Schema-constrained output, for `kaze ask --schema`.
+//!
+//! The schema is sent to the provider (see
+//! [`Provider::prompt_structured`](crate::provider::Provider::prompt_structured)),
+//! and the answer is checked against it here before anyone sees it, since
+//! not every provider enforces the schema strictly. The validator covers the
+//! keywords structured-output schemas use in practice: `type`, `enum`,
+//! `const`, `properties`, `required`, `additionalProperties`, `items`,
+//! `anyOf`/`oneOf`/`allOf`, and the length and range bounds. Other keywords
+//! (including `$ref`) are passed to the provider but not checked.
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::path::Path;
+
+/// A JSON Schema the response must conform to.
+#[derive(Debug, Clone)]
+pub struct OutputSchema {
+    value: Value,
+}
+
+impl OutputSchema {
+    /// Reads a schema from a JSON file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read schema {}", path.display()))?;
+        let value = serde_json::from_str(&text)
+            .with_context(|| format!("{} is not valid JSON", path.display()))?;
+        Self::from_value(value)
+    }
+
+    /// Wraps a schema value, which must be a JSON object.
+    pub fn from_value(value: Value) -> Result<Self> {
+        if !value.is_object() {
+            anyhow::bail!("A JSON schema must be an object");
+        }
+        Ok(Self { value })
+    }
+
+    /// The schema as JSON.
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    /// The schema's `title`, used to name it in provider requests.
+    pub fn title(&self) -> Option<&str> {
+        self.value.get("title").and_then(Value::as_str)
+    }
+
+    /// Checks `instance` against the schema, listing every violation.
+    pub fn validate(&self, instance: &Value) -> Result<()> {
+        let mut errors = Vec::new();
+        check(&self.value, instance, "$", &mut errors);
+        if errors.is_empty() {
+            return Ok(());
+        }
+        anyhow::bail!(
+            "The response does not match the schema:\n  {}",
+            errors.join("\n  ")
+        )
+    }
+}
+
+/// Parses a model's text answer as JSON, tolerating a surrounding
+/// Markdown code fence.
+pub fn parse_response(text: &str) -> Result<Value> {
+    let trimmed = text.trim();
+    let body = trimmed
+        .strip_prefix("```")
+        .and_then(|rest| rest.strip_suffix("```"))
+        .map(|inner| inner.split_once('\n').map_or("", |(_, body)| body))
+        .unwrap_or(trimmed);
+    serde_json::from_str(body).context("The response is not valid JSON")
+}
+
+/// The JSON Schema type name of `value`.
+fn type_of(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        // As in JSON Schema, 1.0 is an integer too
+        Value::Number(n) if n.as_f64().is_some_and(|f| f.fract() == 0.0) => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn type_matches(expected: &str, value: &Value) -> bool {
+    let actual = type_of(value);
+    actual == expected || (expected == "number" && actual == "integer")
+}
+
+fn check(schema: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+    let Some(schema) = schema.as_object() else {
+        // `true` accepts anything; `false` accepts nothing
+        if schema == &Value::Bool(false) {
+            errors.push(format!("{}: not allowed", path));
+        }
+        return;
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let allowed: Vec<&str> = match expected {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !allowed.is_empty() && !allowed.iter().any(|t| type_matches(t, value)) {
+            errors.push(format!(
+                "{}: expected {}, got {}",
+                path,
+                allowed.join(" or "),
+                type_of(value)
+            ));
+            return;
+        }
+    }
+    if let Some(Value::Array(options)) = schema.get("enum") {
+        if !options.contains(value) {
+            errors.push(format!(
+                "{}: {} is not one of the allowed values",
+                path, value
+            ));
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if expected != value {
+            errors.push(format!("{}: expected {}", path, expected));
+        }
+    }
+
+    check_combinators(schema, value, path, errors);
+
+    match value {
+        Value::Object(map) => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            if let Some(Value::Array(required)) = schema.get("required") {
+                for name in required.iter().filter_map(Value::as_str) {
+                    if !map.contains_key(name) {
+                        errors.push(format!("{}: missing required property \"{}\"", path, name));
+                    }
+                }
+            }
+            for (name, item) in map {
+                let item_path = format!("{}.{}", path, name);
+                match properties.and_then(|p| p.get(name)) {
+                    Some(sub) => check(sub, item, &item_path, errors),
+                    None => match schema.get("additionalProperties") {
+                        Some(Value::Bool(false)) => {
+                            errors.push(format!("{}: unexpected property", item_path))
+                        }
+                        Some(sub @ Value::Object(_)) => check(sub, item, &item_path, errors),
+                        _ => {}
+                    },
+                }
+            }
+        }
+        Value::Array(items) => {
+            check_len(schema, items.len(), "minItems", "maxItems", path, errors);
+            if let Some(sub) = schema.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    check(sub, item, &format!("{}[{}]", path, i), errors);
+                }
+            }
+        }
+        Value::String(s) => {
+            check_len(
+                schema,
+                s.chars().count(),
+                "minLength",
+                "maxLength",
+                path,
+                errors,
+            );
+        }
+        Value::Number(n) => {
+            let n = n.as_f64().unwrap_or_default();
+            if let Some(min) = schema.get("minimum").and_then(Value::as_f64) {
+                if n < min {
+                    errors.push(format!("{}: {} is below the minimum {}", path, n, min));
+                }
+            }
+            if let Some(max) = schema.get("maximum").and_then(Value::as_f64) {
+                if n > max {
+                    errors.push(format!("{}: {} is above the maximum {}", path, n, max));
+                }
+            }
+        }
+        Value::Null | Value::Bool(_) => {}
+    }
+}
+
+fn check_combinators(
+    schema: &serde_json::Map<String, Value>,
+    value: &Value,
+    path: &str,
+    errors: &mut Vec<String>,
+) {
+    let passes = |sub: &Value| {
+        let mut sub_errors = Vec::new();
+        check(sub, value, path, &mut sub_errors);
+        sub_errors.is_empty()
+    };
+    if let Some(Value::Array(all)) = schema.get("allOf") {
+        for sub in all {
+            check(sub, value, path, errors);
+        }
+    }
+    if let Some(Value::Array(any)) = schema.get("anyOf") {
+        if !any.iter().any(passes) {
+            errors.push(format!("{}: matches none of the anyOf schemas", path));
+        }
+    }
+    if let Some(Value::Array(one)) = schema.get("oneOf") {
+        let matched = one.iter().filter(|sub| passes(sub)).count();
+        if matched != 1 {
+            errors.push(format!(
+                "{}: must match exactly one oneOf schema (matched {})",
+                path, matched
+            ));
+        }
+    }
+}
+
+fn check_len(
+    schema: &serde_json::Map<String, Value>,
+    len: usize,
+    min_key: &str,
+    max_key: &str,
+    path: &str,
+    errors: &mut Vec<String>,
+) {
+    if let Some(min) = schema.get(min_key).and_then(Value::as_u64) {
+        if (len as u64) < min {
+            errors.push(format!(
+                "{}: length {} is below {} {}",
+                path, len, min_key, min
+            ));
+        }
+    }
+    if let Some(max) = schema.get(max_key).and_then(Value::as_u64) {
+        if (len as u64) > max {
+            errors.push(format!(
+                "{}: length {} is above {} {}",
+                path, len, max_key, max
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_output_schema_checks_types_required_enum_and_nesting() {
+        let schema = OutputSchema::from_value(json!({
+            "type": "object",
+            "properties": {
+                "count": {"type": "integer"},
+                "level": {"enum": ["low", "high"]},
+                "owner": {
+                    "type": "object",
+                    "properties": {"name": {"type": "string"}},
+                    "required": ["name"]
+                }
+            },
+            "required": ["count", "level"]
+        }))
+        .unwrap();
+
+        let valid = json!({"count": 3, "level": "low", "owner": {"name": "ana"}});
+        schema.validate(&valid).unwrap();
+        // A number with no fractional part is an integer
+        schema
+            .validate(&json!({"count": 3.0, "level": "high"}))
+            .unwrap();
+
+        let err = schema
+            .validate(&json!({"count": 2.5, "level": "mid", "owner": {}}))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("$.count: expected integer, got number"),
+            "{}",
+            err
+        );
+        assert!(err.contains("$.level: \"mid\" is not one of"), "{}", err);
+        assert!(
+            err.contains("$.owner: missing required property \"name\""),
+            "{}",
+            err
+        );
+
+        let err = schema
+            .validate(&json!({"count": 1}))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("missing required property \"level\""),
+            "{}",
+            err
+        );
+        assert!(schema.validate(&json!([])).is_err());
+    }
+}
@@ -0,0 +1,292 @@
+//! Shareable renderings of a session transcript, for `kaze session export`,
+//! the chat `/export` command, and the live view of `kaze serve`.
+//!
+//! Markdown keeps the assistant's text as written, so its code blocks
+//! survive untouched. HTML is a single self-contained page: text is
+//! escaped, fenced code blocks become `<pre><code>`, and everything else
+//! becomes paragraphs. Tool calls and their results are left out unless
+//! asked for; system messages are never shown.
+
+use super::Session;
+use crate::message::{Message, Role, ToolCall};
+
+/// One rendered turn of the conversation.
+enum Entry<'a> {
+    User(&'a Message),
+    Assistant(&'a Message),
+    ToolCall(&'a ToolCall),
+    ToolResult(&'a Message),
+}
+
+/// The entries to render, in transcript order.
+fn entries(session: &Session, include_tools: bool) -> Vec<Entry<'_>> {
+    let mut out = Vec::new();
+    for msg in &session.messages {
+        match msg.role {
+            Role::System => {}
+            Role::User => out.push(Entry::User(msg)),
+            Role::Assistant => {
+                if !msg.text().trim().is_empty() {
+                    out.push(Entry::Assistant(msg));
+                }
+                if include_tools {
+                    out.extend(msg.tool_calls.iter().map(Entry::ToolCall));
+                }
+            }
+            Role::Tool if include_tools => out.push(Entry::ToolResult(msg)),
+            Role::Tool => {}
+        }
+    }
+    out
+}
+
+/// A backtick fence longer than any run of backticks in `text`, so the
+/// text can't close it early.
+fn fence_for(text: &str) -> String {
+    let longest = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    "`".repeat(longest.max(2) + 1)
+}
+
+/// `text` in a fenced code block tagged `lang`.
+fn code_block(text: &str, lang: &str) -> String {
+    let fence = fence_for(text);
+    format!("{}{}\n{}\n{}", fence, lang, text.trim_end(), fence)
+}
+
+/// Pretty JSON for tool arguments.
+fn pretty_args(call: &ToolCall) -> String {
+    serde_json::to_string_pretty(&call.arguments).unwrap_or_else(|_| call.arguments.to_string())
+}
+
+fn image_note(msg: &Message) -> Option<String> {
+    match msg.images().count() {
+        0 => None,
+        1 => Some("(1 image attached)".to_string()),
+        n => Some(format!("({} images attached)", n)),
+    }
+}
+
+/// Renders the transcript as Markdown.
+pub fn render_markdown(session: &Session, include_tools: bool) -> String {
+    let title = session.title().unwrap_or_else(|| "Untitled session".into());
+    let mut out = format!("# {}\n\n- Session: `{}`\n", title, session.id);
+    if !session.model.is_empty() {
+        out.push_str(&format!("- Model: `{}`\n", session.model));
+    }
+
+    for entry in entries(session, include_tools) {
+        out.push('\n');
+        match entry {
+            Entry::User(msg) => {
+                out.push_str("## User\n\n");
+                out.push_str(msg.text().trim_end());
+                if let Some(note) = image_note(msg) {
+                    out.push_str(&format!("\n\n_{}_", note));
+                }
+            }
+            Entry::Assistant(msg) => {
+                out.push_str("## Assistant\n\n");
+                out.push_str(msg.text().trim_end());
+                if msg.incomplete {
+                    out.push_str("\n\n_(response interrupted)_");
+                }
+            }
+            Entry::ToolCall(call) => {
+                out.push_str(&format!("### Tool call: `{}`\n\n", call.name));
+                out.push_str(&code_block(&pretty_args(call), "json"));
+            }
+            Entry::ToolResult(msg) => {
+                out.push_str("### Tool result\n\n");
+                out.push_str(&code_block(msg.text(), ""));
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Escapes text for HTML element content and attribute values.
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Converts message text to HTML: fenced code blocks become `<pre><code>`,
+/// blank-line separated paragraphs become `<p>`, and inline markup is
+/// shown as written.
+fn text_to_html(text: &str) -> String {
+    let mut out = String::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut code: Option<(String, String, Vec<&str>)> = None;
+
+    let flush = |paragraph: &mut Vec<&str>, out: &mut String| {
+        if !paragraph.is_empty() {
+            let body: Vec<String> = paragraph.iter().map(|l| escape_html(l)).collect();
+            out.push_str(&format!("<p>{}</p>\n", body.join("<br>\n")));
+            paragraph.clear();
+        }
+    };
+
+    for line in text.lines() {
+        if let Some((fence, lang, lines)) = &mut code {
+            if line.trim_start().starts_with(fence.as_str())
+                && line.trim().chars().all(|c| c == '`')
+            {
+                let class = if lang.is_empty() {
+                    String::new()
+                } else {
+                    format!(" class=\"language-{}\"", escape_html(lang))
+                };
+                out.push_str(&format!(
+                    "<pre><code{}>{}</code></pre>\n",
+                    class,
+                    escape_html(&lines.join("\n"))
+                ));
+                code = None;
+            } else {
+                lines.push(line);
+            }
+            continue;
+        }
+
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") {
+            flush(&mut paragraph, &mut out);
+            let ticks = trimmed.chars().take_while(|&c| c == '`').count();
+            let lang = trimmed[ticks..].trim().to_string();
+            code = Some(("`".repeat(ticks), lang, Vec::new()));
+        } else if trimmed.is_empty() {
+            flush(&mut paragraph, &mut out);
+        } else {
+            paragraph.push(line);
+        }
+    }
+    flush(&mut paragraph, &mut out);
+    // An unclosed fence still shows its contents as code
+    if let Some((_, _, lines)) = code {
+        out.push_str(&format!(
+            "<pre><code>{}</code></pre>\n",
+            escape_html(&lines.join("\n"))
+        ));
+    }
+    out
+}
+
+const HTML_STYLE: &str = "body{font-family:system-ui,sans-serif;max-width:50rem;margin:2rem auto;\
+padding:0 1rem;line-height:1.5;color:#222}\
+header{border-bottom:1px solid #ddd;margin-bottom:1.5rem}\
+section{margin:1rem 0;padding:.5rem 1rem;border-radius:6px}\
+.user{background:#eef4ff}.assistant{background:#f7f7f7}\
+.tool{background:#fff8e6;font-size:.9em}\
+h2{font-size:.8rem;text-transform:uppercase;letter-spacing:.05em;color:#666;margin:.25rem 0}\
+pre{background:#272822;color:#f8f8f2;padding:.75rem;border-radius:4px;overflow-x:auto}\
+.note{color:#666;font-style:italic}";
+
+/// Renders the transcript as a standalone HTML page.
+pub fn render_html(session: &Session, include_tools: bool) -> String {
+    html_page(session, &render_html_transcript(session, include_tools), "")
+}
+
+/// Renders the transcript as a page for `kaze serve`, which polls
+/// `poll_path` every `poll_ms` for a fresh [`render_html_transcript`] and
+/// swaps it in, following along if the reader is at the bottom.
+pub fn render_html_live(session: &Session, poll_path: &str, poll_ms: u64) -> String {
+    let body = format!(
+        "<main id=\"transcript\">\n{}</main>\n\
+         <p class=\"note\" id=\"status\">Live · refreshes every {}s</p>\n",
+        render_html_transcript(session, false),
+        poll_ms as f64 / 1000.0
+    );
+    let script = LIVE_SCRIPT
+        .replace("{poll_path}", poll_path)
+        .replace("{poll_ms}", &poll_ms.to_string());
+    html_page(session, &body, &format!("<script>{}</script>\n", script))
+}
+
+/// Polls for the transcript and replaces it when it changed.
+const LIVE_SCRIPT: &str = "let last=null;\
+async function poll(){const status=document.getElementById('status');\
+try{const r=await fetch('{poll_path}',{cache:'no-store'});\
+if(!r.ok)throw new Error(r.status);const html=await r.text();\
+if(html!==last){const bottom=innerHeight+scrollY>=document.body.scrollHeight-40;\
+if(last!==null)document.getElementById('transcript').innerHTML=html;last=html;\
+if(bottom)scrollTo(0,document.body.scrollHeight);}\
+status.textContent='Live · updated '+new Date().toLocaleTimeString();}\
+catch(e){status.textContent='Disconnected; retrying ('+e.message+')';}\
+setTimeout(poll,{poll_ms});}\
+addEventListener('load',poll);";
+
+/// The transcript's sections, without the page around them.
+pub fn render_html_transcript(session: &Session, include_tools: bool) -> String {
+    let mut body = String::new();
+
+    for entry in entries(session, include_tools) {
+        match entry {
+            Entry::User(msg) => {
+                body.push_str("<section class=\"user\">\n<h2>User</h2>\n");
+                body.push_str(&text_to_html(msg.text()));
+                if let Some(note) = image_note(msg) {
+                    body.push_str(&format!("<p class=\"note\">{}</p>\n", note));
+                }
+            }
+            Entry::Assistant(msg) => {
+                body.push_str("<section class=\"assistant\">\n<h2>Assistant</h2>\n");
+                body.push_str(&text_to_html(msg.text()));
+                if msg.incomplete {
+                    body.push_str("<p class=\"note\">(response interrupted)</p>\n");
+                }
+            }
+            Entry::ToolCall(call) => {
+                body.push_str(&format!(
+                    "<section class=\"tool\">\n<h2>Tool call: {}</h2>\n",
+                    escape_html(&call.name)
+                ));
+                body.push_str(&format!(
+                    "<pre><code class=\"language-json\">{}</code></pre>\n",
+                    escape_html(&pretty_args(call))
+                ));
+            }
+            Entry::ToolResult(msg) => {
+                body.push_str("<section class=\"tool\">\n<h2>Tool result</h2>\n");
+                body.push_str(&format!(
+                    "<pre><code>{}</code></pre>\n",
+                    escape_html(msg.text().trim_end())
+                ));
+            }
+        }
+        body.push_str("</section>\n");
+    }
+    body
+}
+
+/// `body` in a page headed with the session's title, ID, and model.
+fn html_page(session: &Session, body: &str, script: &str) -> String {
+    let title = escape_html(&session.title().unwrap_or_else(|| "Untitled session".into()));
+    let model = if session.model.is_empty() {
+        String::new()
+    } else {
+        format!(" · model <code>{}</code>", escape_html(&session.model))
+    };
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{title}</title>\n<style>{style}</style>\n{script}</head>\n<body>\n\
+         <header>\n<h1>{title}</h1>\n<p>Session <code>{id}</code>{model}</p>\n\
+         </header>\n{body}</body>\n</html>\n",
+        title = title,
+        style = HTML_STYLE,
+        script = script,
+        id = escape_html(&session.id),
+        model = model,
+        body = body,
+    )
+}
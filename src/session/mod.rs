@@ -0,0 +1,787 @@
+//! Session persistence for kaze.
+//!
+//! A session is a list of records, each a message or an event (compaction
+//! markers, ratings, todos, ...), plus an entry in the session index with
+//! its metadata. Both live in a [`SessionStore`] under
+//! `~/.local/share/kaze/sessions/`: by default a JSONL file per session and
+//! `index.json`, or with `[sessions] storage = "sqlite"` a single database.
+
+pub mod export;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod store;
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::config::{Config, SessionStorage, SessionsConfig};
+use crate::message::{Message, Role};
+use crate::tools::todo::TodoItem;
+
+/// Metadata for a single session, stored in the session index.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionMeta {
+    pub id: String,
+    pub title: Option<String>,
+    pub model: String,
+    pub created_at: String,
+    pub updated_at: String,
+    pub message_count: usize,
+    /// Approximate conversation size in tokens, filled in by `kaze session reindex`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_count: Option<usize>,
+    /// Accumulated cost in US dollars of turns with known pricing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+    /// The project (git root, or working directory outside a repository)
+    /// the session was started in, for `kaze chat --continue`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_dir: Option<String>,
+}
+
+/// A reviewer's verdict on an assistant message.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Rating {
+    Good,
+    Bad,
+}
+
+impl std::str::FromStr for Rating {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "good" | "+" => Ok(Self::Good),
+            "bad" | "-" => Ok(Self::Bad),
+            _ => anyhow::bail!("Unknown rating '{}'. Use good or bad", s),
+        }
+    }
+}
+
+impl std::fmt::Display for Rating {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Rating::Good => write!(f, "good"),
+            Rating::Bad => write!(f, "bad"),
+        }
+    }
+}
+
+/// A rating attached to one message of a session (the `/rate` command).
+///
+/// Stored as an `annotation` event record, so the message records
+/// themselves are never rewritten.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Annotation {
+    /// Position of the rated message among the messages in the session's records.
+    pub message_index: usize,
+    pub rating: Rating,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    pub timestamp: String,
+}
+
+/// Summary of a `kaze session reindex` run.
+#[derive(Debug, Default)]
+pub struct ReindexReport {
+    /// Sessions scanned.
+    pub scanned: usize,
+    /// Existing index entries whose metadata changed.
+    pub updated: usize,
+    /// Sessions that had no index entry and were added.
+    pub added: usize,
+}
+
+/// Which sessions [`Session::prune`] removes.
+#[derive(Debug, Clone, Default)]
+pub struct PrunePolicy {
+    /// Remove sessions not updated for this many days.
+    pub max_age_days: Option<u64>,
+    /// Keep at most this many sessions, the most recently updated.
+    pub max_count: Option<usize>,
+    /// Move sessions to `sessions/archive/<id>.jsonl` instead of deleting them.
+    pub archive: bool,
+    /// A session never removed, such as the one chat has open.
+    pub keep: Option<String>,
+}
+
+impl PrunePolicy {
+    /// The `[sessions]` policy from `config`.
+    pub fn from_config(config: &SessionsConfig) -> Self {
+        Self {
+            max_age_days: config.max_age_days,
+            max_count: config.max_count,
+            archive: config.archive.unwrap_or(false),
+            keep: None,
+        }
+    }
+
+    /// Whether the policy has no limit to prune by.
+    pub fn is_empty(&self) -> bool {
+        self.max_age_days.is_none() && self.max_count.is_none()
+    }
+}
+
+/// Summary of a [`Session::prune`] run.
+#[derive(Debug, Default)]
+pub struct PruneReport {
+    /// Sessions removed (or, on a dry run, that would be), newest first.
+    pub removed: Vec<SessionMeta>,
+    /// Index entries dropped because their records are gone or they repeat an ID.
+    pub stale: usize,
+    /// Where removed sessions were moved, when archiving.
+    pub archive_dir: Option<PathBuf>,
+}
+
+/// Index of all sessions, persisted as `index.json` by the JSONL store.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct SessionIndex {
+    pub sessions: Vec<SessionMeta>,
+}
+
+pub use store::{JsonlStore, SessionStore};
+
+/// The session store chosen by `[sessions] storage`, opened on first use.
+pub fn store() -> Result<Arc<dyn SessionStore>> {
+    static STORE: OnceLock<Arc<dyn SessionStore>> = OnceLock::new();
+    if let Some(store) = STORE.get() {
+        return Ok(store.clone());
+    }
+    let storage = Config::load()?.sessions.storage.unwrap_or_default();
+    let store = open_store(storage, Config::data_dir()?.join("sessions"))?;
+    Ok(STORE.get_or_init(|| store).clone())
+}
+
+/// Opens the `storage` backend in `dir`.
+pub fn open_store(storage: SessionStorage, dir: PathBuf) -> Result<Arc<dyn SessionStore>> {
+    match storage {
+        SessionStorage::Jsonl => Ok(Arc::new(JsonlStore::open(dir)?)),
+        #[cfg(feature = "sqlite")]
+        SessionStorage::Sqlite => Ok(Arc::new(sqlite::SqliteStore::open(dir)?)),
+        #[cfg(not(feature = "sqlite"))]
+        SessionStorage::Sqlite => anyhow::bail!(
+            "[sessions] storage = \"sqlite\" needs a kaze built with the `sqlite` feature \
+             (cargo install kaze-cli --features sqlite)"
+        ),
+    }
+}
+
+/// The project sessions started here belong to: the enclosing git root,
+/// or the working directory outside a repository.
+pub fn current_project_dir() -> Option<String> {
+    crate::config::Config::project_root_dir()
+        .ok()
+        .map(|dir| dir.display().to_string())
+}
+
+/// An active conversation session.
+///
+/// Appends each message to the store as it happens and updates the
+/// session index, so a crash loses at most the message being written.
+pub struct Session {
+    pub id: String,
+    pub messages: Vec<Message>,
+    pub model: String,
+    /// Running cost in US dollars, persisted in the session index.
+    pub cost_usd: f64,
+    /// Title set with `/title` or `kaze session rename`, used instead of
+    /// the one derived from the first user message.
+    pub custom_title: Option<String>,
+    /// Recorded in the index; see [`SessionMeta::project_dir`].
+    project_dir: Option<String>,
+    store: Arc<dyn SessionStore>,
+    /// Messages in the stored records, which compaction and `/clear` never
+    /// shorten.
+    stored_messages: usize,
+}
+
+impl Session {
+    /// Creates a new session with a UUID v4 identifier.
+    ///
+    /// Nothing is written until the first message is appended.
+    pub fn new(model: &str) -> Result<Self> {
+        Ok(Self::new_in(store()?, model))
+    }
+
+    /// Like [`Session::new`], in `store` instead of the configured one.
+    pub fn new_in(store: Arc<dyn SessionStore>, model: &str) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            messages: Vec::new(),
+            model: model.to_string(),
+            cost_usd: 0.0,
+            custom_title: None,
+            project_dir: current_project_dir(),
+            store,
+            stored_messages: 0,
+        }
+    }
+
+    /// Loads an existing session.
+    ///
+    /// Reads the model and running cost from the session index and the
+    /// messages and title from the session's records.
+    pub fn load(id: &str) -> Result<Self> {
+        Self::load_from(store()?, id)
+    }
+
+    /// Like [`Session::load`], from `store` instead of the configured one.
+    pub fn load_from(store: Arc<dyn SessionStore>, id: &str) -> Result<Self> {
+        let short = &id[..8.min(id.len())];
+        anyhow::ensure!(store.contains(id)?, "Session {} not found", short);
+
+        let entry = store.meta(id)?;
+        let model = entry.as_ref().map(|s| s.model.clone()).unwrap_or_default();
+        let cost_usd = entry.as_ref().and_then(|s| s.cost_usd).unwrap_or(0.0);
+        // Sessions from before projects were recorded take the current one
+        let project_dir = entry
+            .and_then(|s| s.project_dir)
+            .or_else(current_project_dir);
+
+        let records = read_records(store.as_ref(), id)?;
+        let messages = messages_in(&records)?;
+        let custom_title = custom_title_in(&records);
+
+        Ok(Self {
+            id: id.to_string(),
+            stored_messages: messages.len(),
+            messages,
+            model,
+            cost_usd,
+            custom_title,
+            project_dir,
+            store,
+        })
+    }
+
+    /// Appends a message to the session.
+    ///
+    /// Writes the message as a record, which the JSONL store flushes
+    /// immediately for crash safety, and updates the session index.
+    pub fn append(&mut self, msg: Message) -> Result<()> {
+        let json = serde_json::to_string(&msg)?;
+        self.store.append(&self.id, &json)?;
+
+        self.messages.push(msg);
+        self.stored_messages += 1;
+        self.update_index()?;
+
+        Ok(())
+    }
+
+    /// Switches the model recorded for this session and updates the index.
+    pub fn set_model(&mut self, model: &str) -> Result<()> {
+        self.model = model.to_string();
+        self.update_index()
+    }
+
+    /// Adds a turn's cost to the running total and updates the index.
+    pub fn add_cost(&mut self, cost: f64) -> Result<()> {
+        self.cost_usd += cost;
+        self.update_index()
+    }
+
+    /// Appends an arbitrary JSON event to the session's records.
+    ///
+    /// Used for recording non-message events like compaction markers
+    /// without adding them to the in-memory message list.
+    pub fn append_event(&mut self, event: &Value) -> Result<()> {
+        let json = serde_json::to_string(event)?;
+        self.store.append(&self.id, &json)
+    }
+
+    /// Records a `checkpoint` event marking the end of a turn, which
+    /// [`Session::rewind`] goes back to.
+    pub fn checkpoint(&mut self) -> Result<()> {
+        self.append_event(&serde_json::json!({
+            "event": "checkpoint",
+            "messages": self.stored_messages,
+            "timestamp": Utc::now().to_rfc3339(),
+        }))
+    }
+
+    /// Drops the last `turns` turns, returning how many messages left the
+    /// in-memory history.
+    ///
+    /// Only turns still verbatim in memory can go, not ones compaction
+    /// summarized or `/clear` removed. On disk a `rewind` event tells
+    /// readers to discard the stored messages after the checkpoint the
+    /// turns started from, so the records stay append-only. Sessions saved
+    /// before checkpoints existed rewind to their user messages instead.
+    pub fn rewind(&mut self, turns: usize) -> Result<usize> {
+        anyhow::ensure!(turns > 0, "Nothing to rewind");
+        let floor = self
+            .messages
+            .iter()
+            .rposition(|m| m.role == Role::System)
+            .map_or(0, |i| i + 1);
+        let starts: Vec<usize> = (floor..self.messages.len())
+            .filter(|&i| self.messages[i].role == Role::User)
+            .collect();
+        anyhow::ensure!(
+            turns <= starts.len(),
+            "Only {} turn{} can be rewound; earlier ones were compacted or cleared",
+            starts.len(),
+            if starts.len() == 1 { "" } else { "s" }
+        );
+        let start = starts[starts.len() - turns];
+
+        let records = self.records()?;
+        let checkpoints = checkpoints_in(&records);
+        let to = if checkpoints.len() >= turns {
+            match checkpoints.len() - turns {
+                0 => 0,
+                kept => checkpoints[kept - 1],
+            }
+        } else {
+            let stored = messages_in(&records)?;
+            let users: Vec<usize> = (0..stored.len())
+                .filter(|&i| stored[i].role == Role::User)
+                .collect();
+            users.len().checked_sub(turns).map_or(0, |keep| users[keep])
+        };
+
+        self.append_event(&serde_json::json!({
+            "event": "rewind",
+            "to": to,
+            "timestamp": Utc::now().to_rfc3339(),
+        }))?;
+        self.stored_messages = to;
+        let dropped = self.messages.len() - start;
+        self.messages.truncate(start);
+        self.update_index()?;
+        Ok(dropped)
+    }
+
+    /// The session's records, oldest first.
+    fn records(&self) -> Result<Vec<Value>> {
+        read_records(self.store.as_ref(), &self.id)
+    }
+
+    /// Rates the most recent assistant message in the session's records.
+    ///
+    /// The index refers to the stored messages rather than the in-memory
+    /// history, which compaction may have shortened.
+    pub fn annotate(&mut self, rating: Rating, note: Option<String>) -> Result<Annotation> {
+        let messages = messages_in(&self.records()?)?;
+        let message_index = messages
+            .iter()
+            .rposition(|m| m.role == Role::Assistant)
+            .context("No assistant response to rate yet")?;
+
+        let annotation = Annotation {
+            message_index,
+            rating,
+            note,
+            timestamp: Utc::now().to_rfc3339(),
+        };
+        let mut event = serde_json::to_value(&annotation)?;
+        event["event"] = serde_json::json!("annotation");
+        self.append_event(&event)?;
+        Ok(annotation)
+    }
+
+    /// Returns every annotation recorded in this session, oldest first.
+    pub fn annotations(&self) -> Result<Vec<Annotation>> {
+        events(&self.records()?, "annotation")
+            .map(|value| {
+                serde_json::from_value(value.clone())
+                    .with_context(|| "Failed to parse annotation from session file")
+            })
+            .collect()
+    }
+
+    /// Records the `todo` tool's list as a `todos` event.
+    ///
+    /// Each event holds the whole list; the last one wins on resume.
+    pub fn save_todos(&mut self, items: &[TodoItem]) -> Result<()> {
+        self.append_event(&serde_json::json!({
+            "event": "todos",
+            "items": items,
+        }))
+    }
+
+    /// Returns the most recently saved todo list, or an empty list.
+    pub fn todos(&self) -> Result<Vec<TodoItem>> {
+        match events(&self.records()?, "todos").last() {
+            Some(value) => serde_json::from_value(value["items"].clone())
+                .with_context(|| "Failed to parse todos from session file"),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Records the `scratchpad` tool's note as a `scratchpad` event.
+    ///
+    /// Each event holds the whole note; the last one wins on resume.
+    pub fn save_scratchpad(&mut self, text: &str) -> Result<()> {
+        self.append_event(&serde_json::json!({
+            "event": "scratchpad",
+            "text": text,
+        }))
+    }
+
+    /// Returns the most recently saved scratchpad note, or an empty one.
+    pub fn scratchpad(&self) -> Result<String> {
+        Ok(events(&self.records()?, "scratchpad")
+            .last()
+            .and_then(|value| value["text"].as_str())
+            .unwrap_or_default()
+            .to_string())
+    }
+
+    /// Records the parameters requests are sent with as a `params` event.
+    ///
+    /// Written before the first turn each time the session is opened and
+    /// whenever they change (a model switch), so each turn can be matched
+    /// to the settings that produced it. `messages` is how many stored
+    /// messages came before.
+    pub fn save_params(&mut self, params: &impl Serialize) -> Result<()> {
+        self.append_event(&serde_json::json!({
+            "event": "params",
+            "params": params,
+            "messages": self.stored_messages,
+            "timestamp": Utc::now().to_rfc3339(),
+        }))
+    }
+
+    /// Returns every recorded `params` event, oldest first.
+    pub fn params(&self) -> Result<Vec<Value>> {
+        Ok(events(&self.records()?, "params").cloned().collect())
+    }
+
+    /// Returns the session title: the custom one if set, otherwise one
+    /// derived from the first user message.
+    ///
+    /// Derived titles are truncated to 50 characters. Returns `None` if
+    /// there is neither.
+    pub fn title(&self) -> Option<String> {
+        self.custom_title
+            .clone()
+            .or_else(|| Self::derive_title(&self.messages))
+    }
+
+    /// Sets a custom title, or with `None` goes back to the derived one.
+    ///
+    /// Recorded as a `title` event in the session's records, so the title
+    /// survives `kaze session reindex`, and mirrored in the index.
+    pub fn rename(&mut self, title: Option<&str>) -> Result<()> {
+        let title = title
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(str::to_string);
+        self.append_event(&serde_json::json!({
+            "event": "title",
+            "title": title,
+        }))?;
+        self.custom_title = title;
+        self.update_index()
+    }
+
+    /// Derives a title from the first user message in `messages`.
+    fn derive_title(messages: &[Message]) -> Option<String> {
+        messages.iter().find(|m| m.role == Role::User).map(|m| {
+            let text = m.text();
+            if text.chars().count() > 50 {
+                let truncated: String = text.chars().take(50).collect();
+                format!("{}...", truncated)
+            } else {
+                text.to_string()
+            }
+        })
+    }
+
+    /// Updates (or creates) this session's entry in the index.
+    fn update_index(&self) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        self.store.upsert_meta(&SessionMeta {
+            id: self.id.clone(),
+            title: self.title(),
+            model: self.model.clone(),
+            created_at: now.clone(),
+            updated_at: now,
+            message_count: self.messages.len(),
+            token_count: None,
+            cost_usd: self.recorded_cost(),
+            project_dir: self.project_dir.clone(),
+        })
+    }
+
+    /// The cost to store in the index; sessions that never cost anything
+    /// leave the field out.
+    fn recorded_cost(&self) -> Option<f64> {
+        (self.cost_usd > 0.0).then_some(self.cost_usd)
+    }
+
+    /// Returns the sessions directory path (`~/.local/share/kaze/sessions/`).
+    fn sessions_dir() -> Result<PathBuf> {
+        Ok(Config::data_dir()?.join("sessions"))
+    }
+
+    /// Returns metadata for all sessions.
+    pub fn list_all() -> Result<Vec<SessionMeta>> {
+        store()?.list()
+    }
+
+    /// Returns metadata for the sessions whose ID starts with `prefix`.
+    pub fn find(prefix: &str) -> Result<Vec<SessionMeta>> {
+        store()?.find(prefix)
+    }
+
+    /// The most recently updated session with messages that was started in
+    /// the current project.
+    pub fn latest_in_project() -> Result<Option<SessionMeta>> {
+        let Some(project) = current_project_dir() else {
+            return Ok(None);
+        };
+        Ok(Self::list_all()?
+            .into_iter()
+            .filter(|s| s.message_count > 0 && s.project_dir.as_deref() == Some(&project))
+            .max_by(|a, b| a.updated_at.cmp(&b.updated_at)))
+    }
+
+    /// Deletes a session's records and removes it from the index.
+    pub fn delete(id: &str) -> Result<()> {
+        let store = store()?;
+        store.delete_records(id, None)?;
+        store.remove_meta(id)
+    }
+
+    /// Removes sessions outside `policy` and compacts the index.
+    ///
+    /// Age is measured from each session's last update. Entries whose
+    /// timestamp can't be parsed are kept. Index entries without records,
+    /// and repeats of an ID, are dropped as well. With `dry_run` nothing is
+    /// changed; the report says what would be.
+    pub fn prune(policy: &PrunePolicy, dry_run: bool) -> Result<PruneReport> {
+        let mut report = PruneReport::default();
+        let store = store()?;
+
+        let mut seen = std::collections::HashSet::new();
+        let mut sessions: Vec<(SessionMeta, Option<chrono::DateTime<Utc>>)> = Vec::new();
+        for meta in store.list()? {
+            if !seen.insert(meta.id.clone()) || !store.contains(&meta.id)? {
+                report.stale += 1;
+                continue;
+            }
+            let updated = chrono::DateTime::parse_from_rfc3339(&meta.updated_at)
+                .ok()
+                .map(|t| t.with_timezone(&Utc));
+            sessions.push((meta, updated));
+        }
+        // Newest first; unparseable timestamps count as newest
+        sessions.sort_by(|a, b| match (a.1, b.1) {
+            (Some(a), Some(b)) => b.cmp(&a),
+            (None, Some(_)) => std::cmp::Ordering::Less,
+            (Some(_), None) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        });
+
+        let cutoff = policy
+            .max_age_days
+            .map(|days| Utc::now() - chrono::Duration::days(days.min(i64::MAX as u64) as i64));
+        let mut kept = Vec::new();
+        for (meta, updated) in sessions {
+            let protected = policy.keep.as_deref() == Some(meta.id.as_str());
+            let too_old =
+                matches!((cutoff, updated), (Some(cutoff), Some(updated)) if updated < cutoff);
+            let too_many = policy.max_count.is_some_and(|max| kept.len() >= max);
+            if !protected && (too_old || too_many) {
+                report.removed.push(meta);
+            } else {
+                kept.push(meta);
+            }
+        }
+
+        if policy.archive {
+            report.archive_dir = Some(Self::sessions_dir()?.join("archive"));
+        }
+        if dry_run || (report.removed.is_empty() && report.stale == 0) {
+            return Ok(report);
+        }
+
+        if let Some(ref archive) = report.archive_dir {
+            if !report.removed.is_empty() {
+                fs::create_dir_all(archive)
+                    .with_context(|| format!("Failed to create {:?}", archive))?;
+            }
+        }
+        for meta in &report.removed {
+            store.delete_records(&meta.id, report.archive_dir.as_deref())?;
+        }
+        store.replace_index(&kept)?;
+        Ok(report)
+    }
+
+    /// Rebuilds index metadata from the stored records.
+    ///
+    /// Sessions are parsed concurrently on the blocking thread pool.
+    /// Missing titles, message counts, and token counts are backfilled, and
+    /// sessions without an index entry are added. Sessions that fail to
+    /// parse are reported on stderr and left untouched.
+    pub async fn reindex() -> Result<ReindexReport> {
+        let store = store()?;
+        let mut report = ReindexReport::default();
+
+        let mut sessions = store.list()?;
+        let ids = store.record_ids()?;
+        report.scanned = ids.len();
+
+        let models: std::collections::HashMap<String, String> = sessions
+            .iter()
+            .map(|s| (s.id.clone(), s.model.clone()))
+            .collect();
+
+        let scans = futures::stream::iter(ids.into_iter().map(|id| {
+            let model = models.get(&id).cloned().unwrap_or_default();
+            let store = store.clone();
+            tokio::task::spawn_blocking(move || Self::scan(store.as_ref(), &id, &model))
+        }))
+        .buffer_unordered(crate::constants::SESSION_REINDEX_CONCURRENCY)
+        .collect::<Vec<_>>()
+        .await;
+
+        for scan in scans {
+            let meta = match scan {
+                Ok(Ok(meta)) => meta,
+                Ok(Err(e)) => {
+                    eprintln!("warning: skipping session: {:#}", e);
+                    continue;
+                }
+                Err(e) => {
+                    eprintln!("warning: reindex task failed: {}", e);
+                    continue;
+                }
+            };
+            if let Some(entry) = sessions.iter_mut().find(|s| s.id == meta.id) {
+                let changed = entry.title != meta.title
+                    || entry.message_count != meta.message_count
+                    || entry.token_count != meta.token_count;
+                if changed {
+                    entry.title = meta.title;
+                    entry.message_count = meta.message_count;
+                    entry.token_count = meta.token_count;
+                    report.updated += 1;
+                }
+            } else {
+                sessions.push(meta);
+                report.added += 1;
+            }
+        }
+
+        store.replace_index(&sessions)?;
+        Ok(report)
+    }
+
+    /// Parses one session's records into fresh index metadata.
+    ///
+    /// `model` comes from the existing index entry; timestamps for sessions
+    /// without an entry fall back to when the records last changed.
+    fn scan(store: &dyn SessionStore, id: &str, model: &str) -> Result<SessionMeta> {
+        let records =
+            read_records(store, id).with_context(|| format!("Failed to read session {}", id))?;
+        let messages = messages_in(&records)?;
+
+        let msg_pairs: Vec<(String, String)> = messages
+            .iter()
+            .map(|m| (m.role.to_string(), m.text().to_string()))
+            .collect();
+        let token_count = crate::tokens::count_conversation_tokens(&msg_pairs, model).ok();
+
+        let modified = store
+            .modified(id)?
+            .unwrap_or_else(|| Utc::now().to_rfc3339());
+
+        let title = match custom_title_in(&records) {
+            Some(title) => Some(title),
+            None => Self::derive_title(&messages),
+        };
+
+        Ok(SessionMeta {
+            id: id.to_string(),
+            title,
+            model: model.to_string(),
+            created_at: modified.clone(),
+            updated_at: modified,
+            message_count: messages.len(),
+            token_count,
+            cost_usd: None,
+            project_dir: None,
+        })
+    }
+}
+
+/// Reads and parses the records of session `id`.
+fn read_records(store: &dyn SessionStore, id: &str) -> Result<Vec<Value>> {
+    store
+        .records(id)?
+        .iter()
+        .map(|line| {
+            serde_json::from_str(line).with_context(|| "Failed to parse line from session file")
+        })
+        .collect()
+}
+
+/// The event records of type `kind`, oldest first.
+fn events<'a>(records: &'a [Value], kind: &'a str) -> impl Iterator<Item = &'a Value> {
+    records
+        .iter()
+        .filter(move |value| value.get("event").and_then(|e| e.as_str()) == Some(kind))
+}
+
+/// The message count a `rewind` event cuts the stored messages back to.
+fn rewind_point(value: &Value) -> Option<usize> {
+    if value.get("event").and_then(|e| e.as_str()) != Some("rewind") {
+        return None;
+    }
+    value["to"].as_u64().map(|to| to as usize)
+}
+
+/// The messages among `records`, minus those a later `rewind` event
+/// discarded; other event records written by [`Session::append_event`]
+/// are skipped.
+fn messages_in(records: &[Value]) -> Result<Vec<Message>> {
+    let mut messages = Vec::new();
+    for value in records {
+        if let Some(to) = rewind_point(value) {
+            messages.truncate(to);
+        } else if value.get("event").is_none() {
+            messages.push(
+                serde_json::from_value(value.clone())
+                    .with_context(|| "Failed to parse message from session file")?,
+            );
+        }
+    }
+    Ok(messages)
+}
+
+/// The stored message count at each `checkpoint` still in effect, oldest
+/// first.
+fn checkpoints_in(records: &[Value]) -> Vec<usize> {
+    let mut checkpoints: Vec<usize> = Vec::new();
+    for value in records {
+        if let Some(to) = rewind_point(value) {
+            checkpoints.retain(|&c| c <= to);
+        } else if value.get("event").and_then(|e| e.as_str()) == Some("checkpoint") {
+            if let Some(count) = value["messages"].as_u64() {
+                checkpoints.push(count as usize);
+            }
+        }
+    }
+    checkpoints
+}
+
+/// The title from the last `title` event among `records`.
+fn custom_title_in(records: &[Value]) -> Option<String> {
+    events(records, "title")
+        .last()
+        .and_then(|value| value["title"].as_str().map(str::to_string))
+}
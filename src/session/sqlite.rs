@@ -0,0 +1,287 @@
+//! SQLite session store (`[sessions] storage = "sqlite"`).
+//!
+//! Sessions and their records live in `sessions/sessions.db`. Every index
+//! change is a single statement or transaction, so listing and resolving
+//! IDs stay fast with thousands of sessions and a crash never leaves a
+//! half-written index. The first time the database is opened, existing
+//! JSONL sessions are imported; their files stay in place.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use super::store::{JsonlStore, SessionStore};
+use super::SessionMeta;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS sessions (
+        id TEXT PRIMARY KEY,
+        title TEXT,
+        model TEXT NOT NULL,
+        created_at TEXT NOT NULL,
+        updated_at TEXT NOT NULL,
+        message_count INTEGER NOT NULL,
+        token_count INTEGER,
+        cost_usd REAL,
+        project_dir TEXT
+    );
+    CREATE TABLE IF NOT EXISTS records (
+        seq INTEGER PRIMARY KEY AUTOINCREMENT,
+        session_id TEXT NOT NULL,
+        written_at TEXT NOT NULL,
+        body TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS records_by_session ON records (session_id, seq);
+";
+
+const META_COLUMNS: &str =
+    "id, title, model, created_at, updated_at, message_count, token_count, cost_usd, project_dir";
+
+/// Sessions in a SQLite database.
+pub struct SqliteStore {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteStore {
+    /// Opens (or creates) `dir/sessions.db`, importing the JSONL sessions in
+    /// `dir` if that hasn't happened yet.
+    pub fn open(dir: PathBuf) -> Result<Self> {
+        std::fs::create_dir_all(&dir).context("Failed to create sessions directory")?;
+        let path = dir.join("sessions.db");
+        let conn = Connection::open(&path).with_context(|| format!("Failed to open {:?}", path))?;
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.execute_batch(SCHEMA)
+            .with_context(|| format!("Failed to initialize {:?}", path))?;
+        // Databases created before session projects were recorded
+        if conn
+            .prepare("SELECT project_dir FROM sessions LIMIT 0")
+            .is_err()
+        {
+            conn.execute_batch("ALTER TABLE sessions ADD COLUMN project_dir TEXT")?;
+        }
+        let store = Self {
+            conn: Mutex::new(conn),
+        };
+        let version: i64 = store
+            .conn()
+            .query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if version == 0 {
+            store
+                .import_jsonl(JsonlStore::open(dir)?)
+                .context("Failed to import JSONL sessions")?;
+        }
+        Ok(store)
+    }
+
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Copies every session of `jsonl` into the database in one
+    /// transaction, which also marks the import as done.
+    fn import_jsonl(&self, jsonl: JsonlStore) -> Result<()> {
+        let sessions = jsonl.list()?;
+        let ids = jsonl.record_ids()?;
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        for id in &ids {
+            let written_at = jsonl
+                .modified(id)?
+                .unwrap_or_else(|| Utc::now().to_rfc3339());
+            for record in jsonl.records(id)? {
+                tx.execute(
+                    "INSERT INTO records (session_id, written_at, body) VALUES (?1, ?2, ?3)",
+                    params![id, written_at, record],
+                )?;
+            }
+        }
+        for meta in &sessions {
+            insert_meta(&tx, meta)?;
+        }
+        tx.pragma_update(None, "user_version", 1)?;
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+/// Writes `records` as `dir/<id>.jsonl`, the format `kaze session prune --archive` leaves.
+fn write_archive(dir: &Path, id: &str, records: &[String]) -> Result<()> {
+    let path = dir.join(format!("{}.jsonl", id));
+    let mut text = records.join("\n");
+    if !text.is_empty() {
+        text.push('\n');
+    }
+    std::fs::write(&path, text).with_context(|| format!("Failed to write {:?}", path))
+}
+
+fn insert_meta(conn: &Connection, meta: &SessionMeta) -> Result<()> {
+    conn.execute(
+        &format!(
+            "INSERT OR REPLACE INTO sessions ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            META_COLUMNS
+        ),
+        params![
+            meta.id,
+            meta.title,
+            meta.model,
+            meta.created_at,
+            meta.updated_at,
+            meta.message_count as i64,
+            meta.token_count.map(|n| n as i64),
+            meta.cost_usd,
+            meta.project_dir,
+        ],
+    )?;
+    Ok(())
+}
+
+fn row_to_meta(row: &rusqlite::Row<'_>) -> rusqlite::Result<SessionMeta> {
+    Ok(SessionMeta {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        model: row.get(2)?,
+        created_at: row.get(3)?,
+        updated_at: row.get(4)?,
+        message_count: row.get::<_, i64>(5)? as usize,
+        token_count: row.get::<_, Option<i64>>(6)?.map(|n| n as usize),
+        cost_usd: row.get(7)?,
+        project_dir: row.get(8)?,
+    })
+}
+
+impl SessionStore for SqliteStore {
+    fn append(&self, id: &str, record: &str) -> Result<()> {
+        self.conn().execute(
+            "INSERT INTO records (session_id, written_at, body) VALUES (?1, ?2, ?3)",
+            params![id, Utc::now().to_rfc3339(), record],
+        )?;
+        Ok(())
+    }
+
+    fn records(&self, id: &str) -> Result<Vec<String>> {
+        let conn = self.conn();
+        let mut stmt =
+            conn.prepare_cached("SELECT body FROM records WHERE session_id = ?1 ORDER BY seq")?;
+        let rows = stmt.query_map([id], |row| row.get(0))?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    fn contains(&self, id: &str) -> Result<bool> {
+        Ok(self
+            .conn()
+            .query_row(
+                "SELECT 1 FROM records WHERE session_id = ?1 LIMIT 1",
+                [id],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some())
+    }
+
+    fn record_ids(&self) -> Result<Vec<String>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare("SELECT DISTINCT session_id FROM records")?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    fn modified(&self, id: &str) -> Result<Option<String>> {
+        Ok(self.conn().query_row(
+            "SELECT MAX(written_at) FROM records WHERE session_id = ?1",
+            [id],
+            |row| row.get(0),
+        )?)
+    }
+
+    fn delete_records(&self, id: &str, archive_to: Option<&Path>) -> Result<()> {
+        if let Some(dir) = archive_to {
+            let records = self.records(id)?;
+            if !records.is_empty() {
+                write_archive(dir, id, &records)?;
+            }
+        }
+        self.conn()
+            .execute("DELETE FROM records WHERE session_id = ?1", [id])?;
+        Ok(())
+    }
+
+    fn meta(&self, id: &str) -> Result<Option<SessionMeta>> {
+        Ok(self
+            .conn()
+            .query_row(
+                &format!("SELECT {} FROM sessions WHERE id = ?1", META_COLUMNS),
+                [id],
+                row_to_meta,
+            )
+            .optional()?)
+    }
+
+    fn list(&self) -> Result<Vec<SessionMeta>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM sessions ORDER BY rowid",
+            META_COLUMNS
+        ))?;
+        let rows = stmt.query_map([], row_to_meta)?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    fn find(&self, prefix: &str) -> Result<Vec<SessionMeta>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM sessions WHERE substr(id, 1, length(?1)) = ?1 ORDER BY rowid",
+            META_COLUMNS
+        ))?;
+        let rows = stmt.query_map([prefix], row_to_meta)?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    fn upsert_meta(&self, meta: &SessionMeta) -> Result<()> {
+        self.conn().execute(
+            &format!(
+                "INSERT INTO sessions ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                 ON CONFLICT (id) DO UPDATE SET
+                     title = excluded.title,
+                     model = excluded.model,
+                     updated_at = excluded.updated_at,
+                     message_count = excluded.message_count,
+                     cost_usd = excluded.cost_usd,
+                     project_dir = COALESCE(sessions.project_dir, excluded.project_dir)",
+                META_COLUMNS
+            ),
+            params![
+                meta.id,
+                meta.title,
+                meta.model,
+                meta.created_at,
+                meta.updated_at,
+                meta.message_count as i64,
+                meta.token_count.map(|n| n as i64),
+                meta.cost_usd,
+                meta.project_dir,
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn remove_meta(&self, id: &str) -> Result<()> {
+        self.conn()
+            .execute("DELETE FROM sessions WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    fn replace_index(&self, sessions: &[SessionMeta]) -> Result<()> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM sessions", [])?;
+        for meta in sessions {
+            insert_meta(&tx, meta)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,211 @@
+//! Storage backends for sessions.
+//!
+//! A session is a list of JSON records (messages and events) plus an entry
+//! in the session index. [`SessionStore`] hides where both live: the
+//! default [`JsonlStore`] keeps one append-only file per session and the
+//! index in `index.json`, while the SQLite store (the `sqlite` feature)
+//! keeps everything in one database.
+
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+
+use super::{SessionIndex, SessionMeta};
+
+/// Persistence for session records and the session index.
+///
+/// Records are opaque JSON lines; [`super::Session`] decides what they mean.
+pub trait SessionStore: Send + Sync {
+    /// Appends one record to a session, creating the session if needed.
+    fn append(&self, id: &str, record: &str) -> Result<()>;
+
+    /// A session's records, oldest first; empty for an unknown session.
+    fn records(&self, id: &str) -> Result<Vec<String>>;
+
+    /// Whether a session has any records.
+    fn contains(&self, id: &str) -> Result<bool>;
+
+    /// IDs of every session with records, in no particular order.
+    fn record_ids(&self) -> Result<Vec<String>>;
+
+    /// When a session's records last changed, as RFC 3339.
+    fn modified(&self, id: &str) -> Result<Option<String>>;
+
+    /// Deletes a session's records, first writing them to
+    /// `archive_to/<id>.jsonl` when given. The index is left alone.
+    fn delete_records(&self, id: &str, archive_to: Option<&Path>) -> Result<()>;
+
+    /// The index entry for `id`.
+    fn meta(&self, id: &str) -> Result<Option<SessionMeta>>;
+
+    /// Every index entry, in the order they were added.
+    fn list(&self) -> Result<Vec<SessionMeta>>;
+
+    /// Index entries whose ID starts with `prefix`.
+    fn find(&self, prefix: &str) -> Result<Vec<SessionMeta>> {
+        let mut sessions = self.list()?;
+        sessions.retain(|s| s.id.starts_with(prefix));
+        Ok(sessions)
+    }
+
+    /// Adds an index entry, or updates an existing one while keeping its
+    /// `created_at`, `token_count`, and any `project_dir` already recorded.
+    fn upsert_meta(&self, meta: &SessionMeta) -> Result<()>;
+
+    /// Removes the index entry for `id`.
+    fn remove_meta(&self, id: &str) -> Result<()>;
+
+    /// Replaces the whole index at once.
+    fn replace_index(&self, sessions: &[SessionMeta]) -> Result<()>;
+}
+
+/// The default store: `sessions/<id>.jsonl` files and `sessions/index.json`.
+///
+/// JSONL is crash-safe (append-only) and human-readable.
+pub struct JsonlStore {
+    dir: PathBuf,
+}
+
+impl JsonlStore {
+    /// Opens the store in `dir`, creating the directory if needed.
+    pub fn open(dir: PathBuf) -> Result<Self> {
+        fs::create_dir_all(&dir).context("Failed to create sessions directory")?;
+        Ok(Self { dir })
+    }
+
+    /// Returns the JSONL file path for a given session ID.
+    fn session_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.jsonl", id))
+    }
+
+    /// Returns the path to the session index file.
+    fn index_path(&self) -> PathBuf {
+        self.dir.join("index.json")
+    }
+
+    /// Loads the session index, returning a default empty index if the file doesn't exist.
+    fn load_index(&self) -> Result<SessionIndex> {
+        let path = self.index_path();
+        if !path.exists() {
+            return Ok(SessionIndex::default());
+        }
+        let contents = fs::read_to_string(&path).with_context(|| "Failed to read session index")?;
+        let index: SessionIndex =
+            serde_json::from_str(&contents).with_context(|| "Failed to parse session index")?;
+        Ok(index)
+    }
+
+    /// Writes the session index to disk.
+    fn save_index(&self, index: &SessionIndex) -> Result<()> {
+        let json = serde_json::to_string_pretty(index)?;
+        fs::write(self.index_path(), json).with_context(|| "Failed to write session index")?;
+        Ok(())
+    }
+}
+
+impl SessionStore for JsonlStore {
+    fn append(&self, id: &str, record: &str) -> Result<()> {
+        let path = self.session_path(id);
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open session file {:?}", path))?;
+        writeln!(file, "{}", record)?;
+        file.flush()?;
+        Ok(())
+    }
+
+    fn records(&self, id: &str) -> Result<Vec<String>> {
+        let path = self.session_path(id);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let file = fs::File::open(&path)
+            .with_context(|| format!("Failed to open session file {:?}", path))?;
+        let mut records = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                records.push(line);
+            }
+        }
+        Ok(records)
+    }
+
+    fn contains(&self, id: &str) -> Result<bool> {
+        Ok(self.session_path(id).exists())
+    }
+
+    fn record_ids(&self) -> Result<Vec<String>> {
+        Ok(fs::read_dir(&self.dir)?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|ext| ext == "jsonl"))
+            .filter_map(|p| p.file_stem().map(|s| s.to_string_lossy().into_owned()))
+            .collect())
+    }
+
+    fn modified(&self, id: &str) -> Result<Option<String>> {
+        Ok(fs::metadata(self.session_path(id))
+            .and_then(|m| m.modified())
+            .map(|t| chrono::DateTime::<Utc>::from(t).to_rfc3339())
+            .ok())
+    }
+
+    fn delete_records(&self, id: &str, archive_to: Option<&Path>) -> Result<()> {
+        let path = self.session_path(id);
+        if !path.exists() {
+            return Ok(());
+        }
+        match archive_to {
+            Some(archive) => {
+                let dest = archive.join(format!("{}.jsonl", id));
+                fs::rename(&path, &dest)
+                    .with_context(|| format!("Failed to archive session file {:?}", path))
+            }
+            None => fs::remove_file(&path)
+                .with_context(|| format!("Failed to delete session file {:?}", path)),
+        }
+    }
+
+    fn meta(&self, id: &str) -> Result<Option<SessionMeta>> {
+        Ok(self.load_index()?.sessions.into_iter().find(|s| s.id == id))
+    }
+
+    fn list(&self) -> Result<Vec<SessionMeta>> {
+        Ok(self.load_index()?.sessions)
+    }
+
+    fn upsert_meta(&self, meta: &SessionMeta) -> Result<()> {
+        let mut index = self.load_index()?;
+        if let Some(entry) = index.sessions.iter_mut().find(|s| s.id == meta.id) {
+            entry.title = meta.title.clone();
+            entry.model = meta.model.clone();
+            entry.updated_at = meta.updated_at.clone();
+            entry.message_count = meta.message_count;
+            entry.cost_usd = meta.cost_usd;
+            if entry.project_dir.is_none() {
+                entry.project_dir = meta.project_dir.clone();
+            }
+        } else {
+            index.sessions.push(meta.clone());
+        }
+        self.save_index(&index)
+    }
+
+    fn remove_meta(&self, id: &str) -> Result<()> {
+        let mut index = self.load_index()?;
+        index.sessions.retain(|s| s.id != id);
+        self.save_index(&index)
+    }
+
+    fn replace_index(&self, sessions: &[SessionMeta]) -> Result<()> {
+        self.save_index(&SessionIndex {
+            sessions: sessions.to_vec(),
+        })
+    }
+}
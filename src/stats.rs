@@ -0,0 +1,123 @@
+//! Usage statistics aggregated across sessions, for `kaze stats`.
+//!
+//! Everything is computed from the usage ledger ([`crate::usage`]), which
+//! gains one record per completed turn in chat, `kaze ask`, and `kaze run`.
+//! Turns recorded before the ledger tracked tools and files simply
+//! contribute nothing to those tables.
+
+use chrono::{DateTime, Datelike, Local, NaiveDate};
+use std::collections::{HashMap, HashSet};
+
+use crate::usage::UsageRecord;
+
+/// Spend and activity for one calendar week (Monday to Sunday, local time).
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeekSpend {
+    /// The Monday the week starts on.
+    pub week_start: NaiveDate,
+    pub turns: usize,
+    pub cost_usd: f64,
+}
+
+/// Totals over a set of ledger records.
+#[derive(Debug, Clone, Default)]
+pub struct UsageStats {
+    pub turns: usize,
+    /// Distinct chat sessions with at least one recorded turn.
+    pub sessions: usize,
+    /// Turns from `kaze ask` and `kaze run`, which have no session.
+    pub one_shot_turns: usize,
+    pub tool_calls: usize,
+    pub cost_usd: f64,
+    /// Mean wall-clock turn time in milliseconds.
+    pub avg_turn_ms: Option<f64>,
+    /// Mean time to first token in milliseconds, over turns that streamed.
+    pub avg_ttft_ms: Option<f64>,
+    /// Calls per tool, most used first.
+    pub tools: Vec<(String, usize)>,
+    /// Turns that modified each file, most edited first.
+    pub files: Vec<(String, usize)>,
+    /// Spend per week, oldest first. Weeks without turns are left out.
+    pub weeks: Vec<WeekSpend>,
+}
+
+/// Aggregates ledger records into [`UsageStats`].
+pub fn aggregate(records: &[UsageRecord]) -> UsageStats {
+    let mut stats = UsageStats {
+        turns: records.len(),
+        ..Default::default()
+    };
+    let mut sessions = HashSet::new();
+    let mut tools: HashMap<&str, usize> = HashMap::new();
+    let mut files: HashMap<&str, usize> = HashMap::new();
+    let mut weeks: HashMap<NaiveDate, WeekSpend> = HashMap::new();
+    let mut ttft_total = 0u64;
+    let mut ttft_count = 0usize;
+    let mut turn_total = 0u64;
+
+    for record in records {
+        match &record.session_id {
+            Some(id) => {
+                sessions.insert(id.as_str());
+            }
+            None => stats.one_shot_turns += 1,
+        }
+        stats.tool_calls += record.tools.len();
+        for tool in &record.tools {
+            *tools.entry(tool).or_default() += 1;
+        }
+        for file in &record.files {
+            *files.entry(file).or_default() += 1;
+        }
+        let cost = record.cost_usd.unwrap_or(0.0);
+        stats.cost_usd += cost;
+        turn_total += record.total_ms;
+        if let Some(ttft) = record.ttft_ms {
+            ttft_total += ttft;
+            ttft_count += 1;
+        }
+        if let Some(week_start) = week_of(&record.timestamp) {
+            let week = weeks.entry(week_start).or_insert(WeekSpend {
+                week_start,
+                turns: 0,
+                cost_usd: 0.0,
+            });
+            week.turns += 1;
+            week.cost_usd += cost;
+        }
+    }
+
+    stats.sessions = sessions.len();
+    if !records.is_empty() {
+        stats.avg_turn_ms = Some(turn_total as f64 / records.len() as f64);
+    }
+    if ttft_count > 0 {
+        stats.avg_ttft_ms = Some(ttft_total as f64 / ttft_count as f64);
+    }
+    stats.tools = ranked(tools);
+    stats.files = ranked(files);
+    stats.weeks = weeks.into_values().collect();
+    stats.weeks.sort_by_key(|w| w.week_start);
+    stats
+}
+
+/// The Monday starting the local week a timestamp falls in.
+fn week_of(timestamp: &str) -> Option<NaiveDate> {
+    let date = DateTime::parse_from_rfc3339(timestamp)
+        .ok()?
+        .with_timezone(&Local)
+        .date_naive();
+    date.checked_sub_days(chrono::Days::new(
+        date.weekday().num_days_from_monday() as u64
+    ))
+}
+
+/// Sorts counts by descending count, then by name for a stable order.
+fn ranked(counts: HashMap<&str, usize>) -> Vec<(String, usize)> {
+    let mut ranked: Vec<(String, usize)> = counts
+        .into_iter()
+        .map(|(name, count)| (name.to_string(), count))
+        .collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    ranked
+}
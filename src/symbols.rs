@@ -0,0 +1,199 @@
+//! Lightweight index of identifiers defined in the repository.
+//!
+//! Feeds identifier completion in the chat REPL. Source files are scanned
+//! with one regex for definition keywords (`fn`, `struct`, `class`, `def`,
+//! ...) rather than parsed, so the index is approximate but cheap to build
+//! and language-agnostic. The walk skips the same directories the grep tool
+//! does and stops after [`SYMBOL_INDEX_MAX_FILES`] files. The same scan
+//! gives the [`repo_map`] chat can send the model. In a git repository
+//! both are cached across sessions until the tree changes.
+
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::constants::{SYMBOL_INDEX_MAX_FILES, SYMBOL_INDEX_MAX_FILE_SIZE};
+use crate::context_cache;
+
+/// File extensions scanned for definitions.
+const SOURCE_EXTENSIONS: &[&str] = &[
+    "rs", "py", "js", "jsx", "ts", "tsx", "go", "java", "kt", "c", "h", "cc", "cpp", "hpp", "cs",
+    "rb", "swift", "scala", "php", "lua", "zig",
+];
+
+/// Sorted, deduplicated identifiers defined in a project.
+#[derive(Debug, Default)]
+pub struct SymbolIndex {
+    names: Vec<String>,
+}
+
+impl SymbolIndex {
+    /// Scans source files under `root`.
+    pub fn build(root: &Path) -> Self {
+        let mut names: Vec<String> = scan(root)
+            .into_iter()
+            .flat_map(|(_, names)| names)
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        Self { names }
+    }
+
+    /// Like [`build`](Self::build), but reuses the index cached for the
+    /// same working tree (see [`context_cache`]), so a session on an
+    /// unchanged repository doesn't rescan it.
+    pub fn load(root: &Path) -> Self {
+        let cached = context_cache::get_or_compute(root, "symbols", || {
+            Ok(Self::build(root).names.join("\n"))
+        });
+        match cached {
+            Ok(text) => Self {
+                names: text.lines().map(str::to_string).collect(),
+            },
+            Err(_) => Self::build(root),
+        }
+    }
+
+    /// Number of distinct identifiers.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Whether no identifiers were found.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Identifiers starting with `prefix`, in sorted order, at most `limit`.
+    pub fn complete(&self, prefix: &str, limit: usize) -> Vec<&str> {
+        let start = self.names.partition_point(|name| name.as_str() < prefix);
+        self.names[start..]
+            .iter()
+            .take_while(|name| name.starts_with(prefix))
+            .filter(|name| name.len() > prefix.len())
+            .take(limit)
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+/// A map of the project for the model: each scanned source file under
+/// `root`, relative to it, with the names it defines, one file per line.
+pub fn repo_map(root: &Path) -> String {
+    scan(root)
+        .into_iter()
+        .map(|(path, names)| {
+            let path = path
+                .strip_prefix(root)
+                .unwrap_or(&path)
+                .display()
+                .to_string();
+            let mut seen = std::collections::HashSet::new();
+            let names: Vec<String> = names
+                .into_iter()
+                .filter(|n| seen.insert(n.clone()))
+                .collect();
+            if names.is_empty() {
+                path
+            } else {
+                format!("{}: {}", path, names.join(", "))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Like [`repo_map`], but reuses the map cached for the same working tree.
+pub fn load_repo_map(root: &Path) -> String {
+    context_cache::get_or_compute(root, "repo_map", || Ok(repo_map(root)))
+        .unwrap_or_else(|_| repo_map(root))
+}
+
+/// Each source file under `root`, in path order, with the names defined in
+/// it, in file order.
+fn scan(root: &Path) -> Vec<(PathBuf, Vec<String>)> {
+    let pattern = Regex::new(
+        r"\b(?:fn|struct|enum|trait|type|union|mod|const|static|macro_rules!|class|interface|def|func|function|record|object)\s+([A-Za-z_][A-Za-z0-9_]{2,})",
+    )
+    .expect("symbol pattern is valid");
+    let mut files = Vec::new();
+    scan_dir(root, &pattern, &mut files);
+    files
+}
+
+fn scan_dir(dir: &Path, pattern: &Regex, files: &mut Vec<(PathBuf, Vec<String>)>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut entries: Vec<_> = entries.filter_map(|e| e.ok()).collect();
+    entries.sort_by_key(|e| e.file_name());
+
+    for entry in entries {
+        if files.len() >= SYMBOL_INDEX_MAX_FILES {
+            return;
+        }
+        let path = entry.path();
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if path.is_dir() {
+            // Skip hidden dirs, target/, node_modules/
+            if name.starts_with('.') || name == "target" || name == "node_modules" {
+                continue;
+            }
+            scan_dir(&path, pattern, files);
+        } else if is_source(&path) {
+            let small = entry
+                .metadata()
+                .is_ok_and(|m| m.len() <= SYMBOL_INDEX_MAX_FILE_SIZE);
+            if !small {
+                continue;
+            }
+            let Ok(text) = fs::read_to_string(&path) else {
+                continue;
+            };
+            let names = pattern
+                .captures_iter(&text)
+                .map(|c| c[1].to_string())
+                .collect();
+            files.push((path, names));
+        }
+    }
+}
+
+fn is_source(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| SOURCE_EXTENSIONS.contains(&ext))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repo_map_lists_files_with_their_definitions() {
+        let dir = std::env::temp_dir().join(format!("kaze_test_repo_map_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::create_dir_all(dir.join("target")).unwrap();
+        fs::write(
+            dir.join("src/lib.rs"),
+            "pub struct Config;\nfn load() {}\nfn load() {}\n",
+        )
+        .unwrap();
+        fs::write(dir.join("src/empty.py"), "x = 1\n").unwrap();
+        fs::write(dir.join("target/gen.rs"), "fn generated() {}\n").unwrap();
+        fs::write(dir.join("notes.txt"), "fn not_code() {}\n").unwrap();
+
+        assert_eq!(
+            repo_map(&dir),
+            format!(
+                "src{0}empty.py\nsrc{0}lib.rs: Config, load",
+                std::path::MAIN_SEPARATOR
+            )
+        );
+        assert_eq!(SymbolIndex::build(&dir).complete("lo", 5), ["load"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,170 @@
+//! Terminal state management shared by the REPL and TUI.
+//!
+//! The TUI switches the terminal into raw mode and the alternate screen;
+//! the REPL rewrites its own output with cursor movement and ANSI colors.
+//! Either can leave the terminal unusable if kaze dies halfway through, so
+//! [`install_panic_hook`] restores it before the panic message is printed,
+//! and [`TerminalSignals`] lets event loops exit cleanly on SIGTERM/SIGHUP
+//! and redraw after the process is resumed from job control.
+
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use anyhow::Result;
+use crossterm::cursor::Show;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use crossterm::ExecutableCommand;
+
+/// Whether kaze currently has the terminal in raw mode + alternate screen.
+static FULLSCREEN: AtomicBool = AtomicBool::new(false);
+
+/// Installs a panic hook that restores the terminal, records the panic
+/// and a backtrace in the error log, then runs the previously installed
+/// hook (which prints the panic message).
+pub fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        restore();
+        let backtrace = std::backtrace::Backtrace::force_capture();
+        crate::report::log_event("panic", &format!("{}\n{}", info, backtrace));
+        previous(info);
+    }));
+}
+
+/// Puts the terminal back into a usable state.
+///
+/// Leaves the alternate screen and raw mode if kaze entered them, then
+/// resets colors and shows the cursor in case output stopped mid-sequence.
+/// Safe to call more than once.
+pub fn restore() {
+    let _ = leave_fullscreen();
+    let mut out = io::stdout();
+    let _ = write!(out, "\x1b[0m");
+    let _ = out.execute(Show);
+    let _ = out.flush();
+}
+
+/// Enables raw mode and enters the alternate screen.
+pub fn enter_fullscreen() -> Result<()> {
+    enable_raw_mode()?;
+    io::stdout().execute(EnterAlternateScreen)?;
+    FULLSCREEN.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Leaves the alternate screen and disables raw mode, if active.
+pub fn leave_fullscreen() -> Result<()> {
+    if FULLSCREEN.swap(false, Ordering::SeqCst) {
+        disable_raw_mode()?;
+        io::stdout().execute(LeaveAlternateScreen)?;
+    }
+    Ok(())
+}
+
+/// Re-applies raw mode and the alternate screen after the process resumes.
+///
+/// The shell may reset terminal modes while kaze is stopped, so raw mode is
+/// toggled off and on rather than assumed to still be active.
+pub fn refresh_fullscreen() -> Result<()> {
+    if FULLSCREEN.load(Ordering::SeqCst) {
+        disable_raw_mode()?;
+        enable_raw_mode()?;
+        io::stdout().execute(EnterAlternateScreen)?;
+    }
+    Ok(())
+}
+
+/// Stops kaze like a shell job (Ctrl+Z), handing the terminal back in its
+/// normal state and re-entering fullscreen once resumed with `fg`.
+#[cfg(unix)]
+pub fn suspend_fullscreen() -> Result<()> {
+    use nix::sys::signal::{raise, Signal};
+
+    leave_fullscreen()?;
+    raise(Signal::SIGTSTP)?;
+    // Execution continues here after SIGCONT
+    enter_fullscreen()
+}
+
+/// Job control is unix-only; elsewhere Ctrl+Z is ignored.
+#[cfg(not(unix))]
+pub fn suspend_fullscreen() -> Result<()> {
+    Ok(())
+}
+
+/// Restores the terminal when dropped, covering early `?` returns from a
+/// fullscreen event loop.
+pub struct FullscreenGuard;
+
+impl FullscreenGuard {
+    /// Enters fullscreen mode and returns a guard that leaves it on drop.
+    pub fn enter() -> Result<Self> {
+        enter_fullscreen()?;
+        Ok(Self)
+    }
+}
+
+impl Drop for FullscreenGuard {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+/// A process signal relevant to a fullscreen event loop.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TerminalSignal {
+    /// SIGTERM or SIGHUP: exit cleanly.
+    Shutdown,
+    /// SIGCONT: the process was resumed and should redraw.
+    Resume,
+}
+
+/// Signals an event loop should react to.
+///
+/// Registering a handler replaces the default "terminate immediately"
+/// behavior for the rest of the process, so only create this where the
+/// runtime keeps polling it (the TUI loop), never around blocking reads.
+pub struct TerminalSignals {
+    #[cfg(unix)]
+    terminate: tokio::signal::unix::Signal,
+    #[cfg(unix)]
+    hangup: tokio::signal::unix::Signal,
+    #[cfg(unix)]
+    resume: tokio::signal::unix::Signal,
+}
+
+impl TerminalSignals {
+    pub fn new() -> Result<Self> {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+            Ok(Self {
+                terminate: signal(SignalKind::terminate())?,
+                hangup: signal(SignalKind::hangup())?,
+                resume: signal(SignalKind::from_raw(nix::libc::SIGCONT))?,
+            })
+        }
+        #[cfg(not(unix))]
+        {
+            Ok(Self {})
+        }
+    }
+
+    /// Resolves with the next signal received.
+    pub async fn recv(&mut self) -> TerminalSignal {
+        #[cfg(unix)]
+        {
+            tokio::select! {
+                _ = self.terminate.recv() => TerminalSignal::Shutdown,
+                _ = self.hangup.recv() => TerminalSignal::Shutdown,
+                _ = self.resume.recv() => TerminalSignal::Resume,
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            std::future::pending::<TerminalSignal>().await
+        }
+    }
+}
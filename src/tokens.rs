@@ -10,29 +10,93 @@ use crate::constants::{
 };
 use anyhow::Result;
 use std::collections::HashMap;
-use std::sync::LazyLock;
-use tiktoken_rs::get_bpe_from_model;
+use std::sync::{Arc, LazyLock, Mutex};
+use tiktoken_rs::tokenizer::{get_tokenizer, Tokenizer};
+use tiktoken_rs::{get_bpe_from_tokenizer, CoreBPE};
+
+/// Memoized BPE tokenizers, built on first use.
+static BPE_CACHE: LazyLock<Mutex<HashMap<Tokenizer, Arc<CoreBPE>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Returns the memoized BPE tokenizer for a model.
+///
+/// Loading BPE ranks is slow, so each encoding is built lazily on first use
+/// and shared for the rest of the process. Commands that never count tokens
+/// (e.g. `kaze session list`) never pay the load cost. Unknown models use
+/// cl100k_base.
+fn bpe_for(model: &str) -> Arc<CoreBPE> {
+    let tokenizer = get_tokenizer(model).unwrap_or(Tokenizer::Cl100kBase);
+    let mut cache = BPE_CACHE.lock().unwrap();
+    if let Some(bpe) = cache.get(&tokenizer) {
+        return Arc::clone(bpe);
+    }
+    let bpe = Arc::new(
+        get_bpe_from_tokenizer(tokenizer)
+            .or_else(|_| tiktoken_rs::cl100k_base())
+            .expect("Failed to load cl100k_base tokenizer"),
+    );
+    cache.insert(tokenizer, Arc::clone(&bpe));
+    bpe
+}
 
 /// Count tokens for a text string using the appropriate tokenizer for the model.
 ///
 /// For OpenAI models, uses the exact BPE tokenizer.
 /// For Anthropic/Ollama, falls back to cl100k_base as a reasonable approximation.
 pub fn count_tokens(text: &str, model: &str) -> Result<usize> {
-    let bpe = get_bpe_from_model(model).unwrap_or_else(|_| {
-        tiktoken_rs::cl100k_base().expect("Failed to load cl100k_base tokenizer")
-    });
+    let bpe = bpe_for(model);
     Ok(bpe.encode_ordinary(text).len())
 }
 
+/// Keeps the end of `text` within `max_tokens`, cutting at line boundaries.
+///
+/// Returns the kept text and how many lines were dropped from the start.
+/// The end is kept because that is where logs and command output usually
+/// put the interesting part (the error).
+pub fn truncate_to_last_tokens(text: &str, max_tokens: usize, model: &str) -> (String, usize) {
+    let bpe = bpe_for(model);
+    let lines: Vec<&str> = text.lines().collect();
+    let mut used = 0;
+    let mut keep_from = lines.len();
+    while keep_from > 0 {
+        // +1 for the newline joining the lines back together
+        let cost = bpe.encode_ordinary(lines[keep_from - 1]).len() + 1;
+        if used + cost > max_tokens {
+            break;
+        }
+        used += cost;
+        keep_from -= 1;
+    }
+    (lines[keep_from..].join("\n"), keep_from)
+}
+
+/// Keeps the start of `text` within `max_tokens`, cutting at line
+/// boundaries, for text read top to bottom such as diffs.
+///
+/// Returns the kept text and how many lines were dropped from the end.
+pub fn truncate_to_first_tokens(text: &str, max_tokens: usize, model: &str) -> (String, usize) {
+    let bpe = bpe_for(model);
+    let lines: Vec<&str> = text.lines().collect();
+    let mut used = 0;
+    let mut keep = 0;
+    while keep < lines.len() {
+        let cost = bpe.encode_ordinary(lines[keep]).len() + 1;
+        if used + cost > max_tokens {
+            break;
+        }
+        used += cost;
+        keep += 1;
+    }
+    (lines[..keep].join("\n"), lines.len() - keep)
+}
+
 /// Count tokens across all messages in a conversation.
 /// Each message has ~4 tokens overhead for role markers.
 pub fn count_conversation_tokens(
     messages: &[(String, String)], // (role, content) pairs
     model: &str,
 ) -> Result<usize> {
-    let bpe = get_bpe_from_model(model).unwrap_or_else(|_| {
-        tiktoken_rs::cl100k_base().expect("Failed to load cl100k_base tokenizer")
-    });
+    let bpe = bpe_for(model);
     let mut total = 0;
     for (_role, content) in messages {
         total += TOKENS_PER_MESSAGE_OVERHEAD; // ~4 tokens overhead per message
@@ -65,6 +129,7 @@ static CONTEXT_WINDOWS: LazyLock<HashMap<&'static str, usize>> = LazyLock::new(|
         .iter()
         .chain(crate::models::OPENAI_MODELS.iter())
         .chain(crate::models::OLLAMA_MODELS.iter())
+        .chain(crate::models::BEDROCK_MODELS.iter())
     {
         m.insert(info.name, info.context_window);
     }
@@ -0,0 +1,801 @@
+//! Apply-patch tool — applies a unified diff to files within the project root.
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+
+use super::file_history::{read_existing, Change, FileEdit, FileHistory};
+use super::multi_edit::{staging_path, FileChange};
+use super::{Tool, ToolResult};
+use crate::async_fs;
+use crate::constants::PATCH_MAX_FUZZ;
+
+/// Tool that applies a unified diff, possibly touching several files.
+///
+/// Hunks are located by their context rather than trusted line numbers:
+/// an exact match nearest the stated line wins, then a match ignoring
+/// trailing whitespace, then one with up to [`PATCH_MAX_FUZZ`] outer
+/// context lines dropped. Every hunk must apply before anything is
+/// written; otherwise each failing hunk is reported and no file changes.
+///
+/// Lines keep their CRLF or LF endings. A patch may change each file in
+/// one section only, and a rename never replaces an existing file.
+pub struct ApplyPatchTool {
+    /// Project root directory. Paths are resolved relative to this.
+    project_root: PathBuf,
+    /// Where the previous contents are recorded for `/undo`.
+    history: FileHistory,
+}
+
+impl ApplyPatchTool {
+    pub fn new(project_root: PathBuf, history: FileHistory) -> Self {
+        Self {
+            project_root,
+            history,
+        }
+    }
+
+    /// Resolve a patch path and validate that it stays within the project
+    /// root. The file may not exist yet (new files), so the check is made
+    /// on its nearest existing ancestor.
+    fn resolve_path(&self, path: &str) -> Result<PathBuf> {
+        let relative = Path::new(path);
+        if relative.is_absolute()
+            || relative
+                .components()
+                .any(|c| matches!(c, Component::ParentDir))
+        {
+            anyhow::bail!("Path escapes project directory: {}", path);
+        }
+        let resolved = self.project_root.join(relative);
+        let root_canonical = self.project_root.canonicalize()?;
+        let mut ancestor = resolved.as_path();
+        let existing = loop {
+            if ancestor.exists() {
+                break ancestor.canonicalize()?;
+            }
+            ancestor = ancestor
+                .parent()
+                .ok_or_else(|| anyhow::anyhow!("Invalid path: {}", path))?;
+        };
+        if !existing.starts_with(&root_canonical) {
+            anyhow::bail!("Path escapes project directory: {}", path);
+        }
+        Ok(resolved)
+    }
+}
+
+#[derive(Deserialize)]
+struct ApplyPatchInput {
+    patch: String,
+}
+
+/// One line of a hunk body.
+#[derive(Debug, Clone, PartialEq)]
+enum HunkLine {
+    Context(String),
+    Remove(String),
+    Add(String),
+}
+
+#[derive(Debug)]
+struct Hunk {
+    /// The `@@ ... @@` line, for error messages.
+    header: String,
+    /// 1-based line the hunk claims to start at in the old file.
+    old_start: usize,
+    lines: Vec<HunkLine>,
+    /// A `\ No newline at end of file` marker followed the hunk's new side.
+    no_newline_at_end: bool,
+}
+
+/// The changes a patch makes to one file. `None` paths are `/dev/null`.
+#[derive(Debug)]
+struct FilePatch {
+    old_path: Option<String>,
+    new_path: Option<String>,
+    hunks: Vec<Hunk>,
+}
+
+impl FilePatch {
+    /// The path named in messages.
+    fn display(&self) -> &str {
+        self.new_path
+            .as_deref()
+            .or(self.old_path.as_deref())
+            .unwrap_or("/dev/null")
+    }
+}
+
+/// Strips the `a/`/`b/` prefix and any trailing timestamp from a
+/// `---`/`+++` header path. Returns `None` for `/dev/null`.
+fn header_path(rest: &str) -> Option<String> {
+    let path = rest.split('\t').next().unwrap_or(rest).trim();
+    if path == "/dev/null" {
+        return None;
+    }
+    let path = path
+        .strip_prefix("a/")
+        .or_else(|| path.strip_prefix("b/"))
+        .unwrap_or(path);
+    Some(path.to_string())
+}
+
+/// Parses the old-side start line from `@@ -12,7 +12,8 @@`.
+fn hunk_start(header: &str) -> Option<usize> {
+    let old = header.strip_prefix("@@")?.trim_start().strip_prefix('-')?;
+    let start = old.split([',', ' ']).next()?;
+    start.parse().ok()
+}
+
+/// Parses a unified diff into per-file patches.
+///
+/// Line counts in hunk headers are ignored (models often get them wrong);
+/// a hunk runs until the next hunk or file header. A blank line inside a
+/// hunk is read as an empty context line.
+fn parse_patch(patch: &str) -> Result<Vec<FilePatch>> {
+    let lines: Vec<&str> = patch.lines().collect();
+    let mut files: Vec<FilePatch> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        let is_file_header = line.starts_with("--- ")
+            && lines
+                .get(i + 1)
+                .is_some_and(|next| next.starts_with("+++ "));
+        if is_file_header {
+            files.push(FilePatch {
+                old_path: header_path(&line[4..]),
+                new_path: header_path(&lines[i + 1][4..]),
+                hunks: Vec::new(),
+            });
+            i += 2;
+            continue;
+        }
+        if line.starts_with("@@") {
+            let file = files
+                .last_mut()
+                .ok_or_else(|| anyhow::anyhow!("Hunk before any '---'/'+++' file header"))?;
+            let old_start = hunk_start(line)
+                .ok_or_else(|| anyhow::anyhow!("Malformed hunk header: {}", line))?;
+            let mut hunk = Hunk {
+                header: line.to_string(),
+                old_start,
+                lines: Vec::new(),
+                no_newline_at_end: false,
+            };
+            i += 1;
+            while i < lines.len() {
+                let body = lines[i];
+                let next_is_header = body.starts_with("--- ")
+                    && lines
+                        .get(i + 1)
+                        .is_some_and(|next| next.starts_with("+++ "));
+                if body.starts_with("@@") || body.starts_with("diff ") || next_is_header {
+                    break;
+                }
+                match body.chars().next() {
+                    Some(' ') => hunk.lines.push(HunkLine::Context(body[1..].to_string())),
+                    Some('-') => hunk.lines.push(HunkLine::Remove(body[1..].to_string())),
+                    Some('+') => hunk.lines.push(HunkLine::Add(body[1..].to_string())),
+                    Some('\\') => {
+                        if !matches!(hunk.lines.last(), Some(HunkLine::Remove(_))) {
+                            hunk.no_newline_at_end = true;
+                        }
+                    }
+                    None => hunk.lines.push(HunkLine::Context(String::new())),
+                    Some(_) => break,
+                }
+                i += 1;
+            }
+            file.hunks.push(hunk);
+            continue;
+        }
+        // `diff --git`, `index`, mode lines, and commentary are skipped
+        i += 1;
+    }
+
+    if files.is_empty() {
+        anyhow::bail!("No file headers ('--- a/path' / '+++ b/path') found in the patch");
+    }
+    Ok(files)
+}
+
+/// Paths a patch touches, for metrics. Unparseable patches touch nothing.
+pub(crate) fn patch_paths(patch: &str) -> Vec<String> {
+    parse_patch(patch)
+        .map(|files| files.iter().map(|f| f.display().to_string()).collect())
+        .unwrap_or_default()
+}
+
+/// Finds `needle` in `haystack` at or after `min`, preferring the position
+/// closest to `expected`.
+fn find_block(
+    haystack: &[String],
+    needle: &[&str],
+    expected: usize,
+    min: usize,
+    eq: impl Fn(&str, &str) -> bool,
+) -> Option<usize> {
+    if needle.len() > haystack.len() {
+        return None;
+    }
+    let last = haystack.len() - needle.len();
+    let matches_at = |pos: usize| {
+        haystack[pos..pos + needle.len()]
+            .iter()
+            .zip(needle)
+            .all(|(a, b)| eq(a, b))
+    };
+    let expected = expected.clamp(min, last.max(min));
+    for distance in 0..=haystack.len() {
+        let below = expected.checked_sub(distance).filter(|&p| p >= min);
+        let above = Some(expected + distance).filter(|&p| p <= last);
+        if below.is_none() && above.is_none() {
+            break;
+        }
+        if let Some(pos) = below.filter(|&p| p <= last && matches_at(p)) {
+            return Some(pos);
+        }
+        if let Some(pos) = above.filter(|&p| matches_at(p)) {
+            return Some(pos);
+        }
+    }
+    None
+}
+
+/// Splits `text` into its lines and each line's terminator (`"\r\n"`,
+/// `"\n"`, or `""` for an unterminated last line).
+fn split_lines(text: &str) -> (Vec<String>, Vec<&str>) {
+    text.split_inclusive('\n')
+        .map(|line| match line.strip_suffix("\r\n") {
+            Some(body) => (body.to_string(), "\r\n"),
+            None => match line.strip_suffix('\n') {
+                Some(body) => (body.to_string(), "\n"),
+                None => (line.to_string(), ""),
+            },
+        })
+        .unzip()
+}
+
+/// Joins lines back with their terminators. Only the last line may go
+/// without one, and it gets `ending` if `final_newline` is set.
+fn join_lines(lines: &[String], endings: &[&str], ending: &str, final_newline: bool) -> String {
+    let mut text = String::new();
+    for (i, line) in lines.iter().enumerate() {
+        text.push_str(line);
+        let last = i + 1 == lines.len();
+        match endings[i] {
+            _ if last && !final_newline => {}
+            "" => text.push_str(ending),
+            own => text.push_str(own),
+        }
+    }
+    text
+}
+
+/// Applies one file's hunks to its lines in order. Context lines keep their
+/// terminators in `endings`; added lines get `ending`, the file's own.
+///
+/// Returns notes about hunks that needed an offset or fuzz, or an error
+/// message per hunk that could not be placed.
+fn apply_hunks<'a>(
+    lines: &mut Vec<String>,
+    endings: &mut Vec<&'a str>,
+    ending: &'a str,
+    hunks: &[Hunk],
+    display: &str,
+) -> std::result::Result<Vec<String>, Vec<String>> {
+    let mut notes = Vec::new();
+    let mut errors = Vec::new();
+    // Old-file line numbers shift as earlier hunks add or remove lines
+    let mut delta: isize = 0;
+    // Hunks apply in order and never overlap
+    let mut min = 0;
+
+    for (n, hunk) in hunks.iter().enumerate() {
+        let expected = (hunk.old_start.saturating_sub(1) as isize + delta).max(0) as usize;
+        let leading = hunk
+            .lines
+            .iter()
+            .take_while(|l| matches!(l, HunkLine::Context(_)))
+            .count();
+        let trailing = hunk
+            .lines
+            .iter()
+            .rev()
+            .take_while(|l| matches!(l, HunkLine::Context(_)))
+            .count()
+            .min(hunk.lines.len() - leading);
+
+        let mut placed = None;
+        'search: for fuzz in 0..=PATCH_MAX_FUZZ {
+            let skip_front = fuzz.min(leading);
+            let skip_back = fuzz.min(trailing);
+            if fuzz > 0 && skip_front == 0 && skip_back == 0 {
+                break;
+            }
+            let body = &hunk.lines[skip_front..hunk.lines.len() - skip_back];
+            let old: Vec<&str> = body
+                .iter()
+                .filter_map(|l| match l {
+                    HunkLine::Context(s) | HunkLine::Remove(s) => Some(s.as_str()),
+                    HunkLine::Add(_) => None,
+                })
+                .collect();
+            let want = expected + skip_front;
+            if old.is_empty() {
+                // Pure insertion: trust the line number
+                placed = Some((want.clamp(min, lines.len()), body, fuzz, false));
+                break;
+            }
+            for loose in [false, true] {
+                let found = if loose {
+                    find_block(lines, &old, want, min, |a, b| a.trim_end() == b.trim_end())
+                } else {
+                    find_block(lines, &old, want, min, |a, b| a == b)
+                };
+                if let Some(pos) = found {
+                    placed = Some((pos, body, fuzz, loose));
+                    break 'search;
+                }
+            }
+        }
+
+        let Some((pos, body, fuzz, loose)) = placed else {
+            let preview: Vec<&str> = hunk
+                .lines
+                .iter()
+                .filter_map(|l| match l {
+                    HunkLine::Context(s) | HunkLine::Remove(s) => Some(s.as_str()),
+                    HunkLine::Add(_) => None,
+                })
+                .take(3)
+                .collect();
+            errors.push(format!(
+                "{}: hunk {} ({}) does not match the file near line {}. Expected lines starting with:\n{}",
+                display,
+                n + 1,
+                hunk.header,
+                hunk.old_start,
+                preview
+                    .iter()
+                    .map(|l| format!("  {}", l))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ));
+            continue;
+        };
+
+        let old_len = body
+            .iter()
+            .filter(|l| !matches!(l, HunkLine::Add(_)))
+            .count();
+        let mut new = Vec::new();
+        let mut new_endings = Vec::new();
+        let mut old_line = pos;
+        for line in body {
+            match line {
+                HunkLine::Context(s) => {
+                    new.push(s.clone());
+                    new_endings.push(endings[old_line]);
+                    old_line += 1;
+                }
+                HunkLine::Remove(_) => old_line += 1,
+                HunkLine::Add(s) => {
+                    new.push(s.clone());
+                    new_endings.push(ending);
+                }
+            }
+        }
+        let new_len = new.len();
+        lines.splice(pos..pos + old_len, new);
+        endings.splice(pos..pos + old_len, new_endings);
+
+        let offset = pos as isize - (expected as isize);
+        if offset != 0 || fuzz > 0 || loose {
+            let mut how = Vec::new();
+            if offset != 0 {
+                how.push(format!("offset {:+}", offset));
+            }
+            if fuzz > 0 {
+                how.push(format!("fuzz {}", fuzz));
+            }
+            if loose {
+                how.push("ignoring trailing whitespace".to_string());
+            }
+            notes.push(format!(
+                "{}: hunk {} applied at line {} ({})",
+                display,
+                n + 1,
+                pos + 1,
+                how.join(", ")
+            ));
+        }
+        delta += new_len as isize - old_len as isize;
+        min = pos + new_len;
+    }
+
+    if errors.is_empty() {
+        Ok(notes)
+    } else {
+        Err(errors)
+    }
+}
+
+/// The outcome of one file's patch, ready to write.
+enum Planned {
+    /// An existing file rewritten in place (or renamed if `from` is set).
+    Modify {
+        change: FileChange,
+        from: Option<PathBuf>,
+    },
+    Create {
+        display: String,
+        path: PathBuf,
+        content: String,
+    },
+    Delete {
+        display: String,
+        path: PathBuf,
+        original: String,
+    },
+}
+
+/// A file the patch writes: its new contents, and the file whose mode to
+/// keep (itself, or the file it was renamed from; none for new files).
+struct Write<'a> {
+    display: &'a str,
+    path: &'a Path,
+    content: &'a str,
+    mode_of: Option<&'a Path>,
+}
+
+/// A file the patch removes, deleted or renamed away, with what it held.
+struct Removal<'a> {
+    display: &'a str,
+    path: &'a Path,
+    original: &'a str,
+}
+
+/// Carries out the whole plan or none of it. Every written file is staged
+/// beside its target first, so a failure there changes nothing; then the
+/// staged files are moved into place and the deleted and renamed-away
+/// files removed, and a failure in that step puts back what was done.
+async fn commit_plan(planned: &[Planned]) -> Result<()> {
+    let mut writes = Vec::new();
+    let mut removals = Vec::new();
+    for plan in planned {
+        match plan {
+            Planned::Modify { change, from } => {
+                writes.push(Write {
+                    display: &change.display,
+                    path: &change.path,
+                    content: &change.edited,
+                    mode_of: Some(from.as_deref().unwrap_or(&change.path)),
+                });
+                if let Some(from) = from {
+                    removals.push(Removal {
+                        display: &change.display,
+                        path: from,
+                        original: &change.original,
+                    });
+                }
+            }
+            Planned::Create {
+                display,
+                path,
+                content,
+            } => writes.push(Write {
+                display,
+                path,
+                content,
+                mode_of: None,
+            }),
+            Planned::Delete {
+                display,
+                path,
+                original,
+            } => removals.push(Removal {
+                display,
+                path,
+                original,
+            }),
+        }
+    }
+
+    // What each target holds now (a rename target may already exist)
+    let mut previous = Vec::new();
+    for write in &writes {
+        previous.push(read_existing(write.path).await?);
+    }
+
+    for (i, write) in writes.iter().enumerate() {
+        let staging = staging_path(write.path);
+        let staged = async {
+            if let Some(parent) = write.path.parent() {
+                async_fs::create_dir_all(parent).await?;
+            }
+            async_fs::write(&staging, write.content).await?;
+            if let Some(source) = write.mode_of {
+                // Keep the original mode (e.g. the executable bit) across the rename
+                let perm = async_fs::metadata(source).await?.permissions();
+                async_fs::set_permissions(&staging, perm).await?;
+            }
+            std::io::Result::Ok(())
+        };
+        if let Err(e) = staged.await {
+            for staged in &writes[..=i] {
+                let _ = async_fs::remove_file(&staging_path(staged.path)).await;
+            }
+            anyhow::bail!(
+                "Failed to stage {}: {}. No files were changed.",
+                write.display,
+                e
+            );
+        }
+    }
+
+    for (i, write) in writes.iter().enumerate() {
+        if let Err(e) = async_fs::rename(&staging_path(write.path), write.path).await {
+            restore(&writes[..i], &previous, &[]).await;
+            for pending in &writes[i..] {
+                let _ = async_fs::remove_file(&staging_path(pending.path)).await;
+            }
+            anyhow::bail!(
+                "Failed to write {}: {}. Earlier files in the patch were restored.",
+                write.display,
+                e
+            );
+        }
+    }
+    for (i, removal) in removals.iter().enumerate() {
+        if let Err(e) = async_fs::remove_file(removal.path).await {
+            restore(&writes, &previous, &removals[..i]).await;
+            anyhow::bail!(
+                "Failed to remove {}: {}. Earlier files in the patch were restored.",
+                removal.display,
+                e
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Puts back the targets of `writes` as `previous` recorded them, and the
+/// files `removed` took away. Best effort: the commit has already failed.
+async fn restore(writes: &[Write<'_>], previous: &[Option<Vec<u8>>], removed: &[Removal<'_>]) {
+    for (write, before) in writes.iter().zip(previous) {
+        let _ = match before {
+            Some(contents) => async_fs::write(write.path, contents).await,
+            None => async_fs::remove_file(write.path).await,
+        };
+    }
+    for removal in removed {
+        let _ = async_fs::write(removal.path, removal.original).await;
+    }
+}
+
+impl ApplyPatchTool {
+    /// Records what every planned file holds now. A rename target is
+    /// usually new, so it is read rather than assumed to hold the source.
+    async fn backup(&self, planned: &[Planned]) -> Result<Change> {
+        let mut targets = Vec::new();
+        for plan in planned {
+            if let Planned::Modify {
+                change,
+                from: Some(_),
+            } = plan
+            {
+                targets.push(read_existing(&change.path).await?);
+            }
+        }
+        let mut targets = targets.iter();
+        let mut edits = Vec::new();
+        for plan in planned {
+            match plan {
+                Planned::Modify { change, from } => {
+                    let before = match from {
+                        Some(from) => {
+                            edits.push(FileEdit {
+                                path: from,
+                                before: Some(change.original.as_bytes()),
+                                after: None,
+                            });
+                            targets.next().and_then(|t| t.as_deref())
+                        }
+                        None => Some(change.original.as_bytes()),
+                    };
+                    edits.push(FileEdit {
+                        path: &change.path,
+                        before,
+                        after: Some(change.edited.as_bytes()),
+                    });
+                }
+                Planned::Create { path, content, .. } => edits.push(FileEdit {
+                    path,
+                    before: None,
+                    after: Some(content.as_bytes()),
+                }),
+                Planned::Delete { path, original, .. } => edits.push(FileEdit {
+                    path,
+                    before: Some(original.as_bytes()),
+                    after: None,
+                }),
+            }
+        }
+        self.history.backup(self.name(), &edits).await
+    }
+}
+
+#[async_trait::async_trait]
+impl Tool for ApplyPatchTool {
+    fn name(&self) -> &str {
+        "apply_patch"
+    }
+
+    fn description(&self) -> &str {
+        "Apply a unified diff (as produced by `diff -u` or `git diff`) to one or more files. \
+         Prefer this over write_file for large changes to existing files. Use '--- a/path' and \
+         '+++ b/path' headers ('/dev/null' to create or delete a file) and include a few lines \
+         of context per hunk. Either every hunk applies or nothing is changed. Paths are \
+         relative to the project root."
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "patch": {
+                    "type": "string",
+                    "description": "Unified diff text"
+                }
+            },
+            "required": ["patch"]
+        })
+    }
+
+    async fn execute(&self, input: Value) -> Result<ToolResult> {
+        let input: ApplyPatchInput = serde_json::from_value(input)?;
+        let files = match parse_patch(&input.patch) {
+            Ok(files) => files,
+            Err(e) => return Ok(ToolResult::error(format!("Invalid patch: {}", e))),
+        };
+
+        let mut planned = Vec::new();
+        let mut notes = Vec::new();
+        let mut errors = Vec::new();
+        // Each section starts from the file on disk, so a second section for
+        // the same file would silently undo the first
+        let mut touched = HashSet::new();
+        for file in &files {
+            let display = file.display().to_string();
+            let old_path = file
+                .old_path
+                .as_deref()
+                .map(|p| self.resolve_path(p))
+                .transpose()?;
+            let new_path = file
+                .new_path
+                .as_deref()
+                .map(|p| self.resolve_path(p))
+                .transpose()?;
+            let paths: HashSet<&PathBuf> = old_path.iter().chain(&new_path).collect();
+            if paths.into_iter().any(|p| !touched.insert(p.clone())) {
+                errors.push(format!(
+                    "{}: the patch changes this file in more than one section; \
+                     combine its hunks under one header",
+                    display
+                ));
+                continue;
+            }
+            if let (Some(old), Some(new)) = (&old_path, &new_path) {
+                if old != new && new.exists() {
+                    errors.push(format!(
+                        "{}: patch renames a file onto one that already exists",
+                        display
+                    ));
+                    continue;
+                }
+            }
+
+            let original = match old_path {
+                Some(ref path) => match async_fs::read_to_string(path).await {
+                    Ok(content) => content,
+                    Err(e) => {
+                        errors.push(format!("{}: cannot read file: {}", display, e));
+                        continue;
+                    }
+                },
+                None => {
+                    if new_path.as_ref().is_some_and(|p| p.exists()) {
+                        errors.push(format!(
+                            "{}: patch creates this file but it already exists",
+                            display
+                        ));
+                        continue;
+                    }
+                    String::new()
+                }
+            };
+
+            let (mut lines, mut endings) = split_lines(&original);
+            let ending = match endings.first() {
+                Some(&"\r\n") => "\r\n",
+                _ => "\n",
+            };
+            match apply_hunks(&mut lines, &mut endings, ending, &file.hunks, &display) {
+                Ok(file_notes) => notes.extend(file_notes),
+                Err(file_errors) => {
+                    errors.extend(file_errors);
+                    continue;
+                }
+            }
+            let no_newline = file.hunks.last().is_some_and(|h| h.no_newline_at_end);
+            let final_newline = !no_newline && (original.ends_with('\n') || old_path.is_none());
+            let edited = join_lines(&lines, &endings, ending, final_newline);
+
+            planned.push(match (old_path, new_path) {
+                (Some(path), None) => Planned::Delete {
+                    display,
+                    path,
+                    original,
+                },
+                (None, Some(path)) => Planned::Create {
+                    display,
+                    path,
+                    content: edited,
+                },
+                (Some(old), Some(new)) => Planned::Modify {
+                    from: (old != new).then_some(old),
+                    change: FileChange {
+                        display,
+                        path: new,
+                        original,
+                        edited,
+                    },
+                },
+                (None, None) => {
+                    errors.push("A file patch has /dev/null on both sides".to_string());
+                    continue;
+                }
+            });
+        }
+
+        if !errors.is_empty() {
+            return Ok(ToolResult::error(format!(
+                "Patch not applied; no files were changed.\n\n{}",
+                errors.join("\n\n")
+            )));
+        }
+
+        let change = self.backup(&planned).await?;
+        commit_plan(&planned).await?;
+        let summary: Vec<String> = planned
+            .iter()
+            .map(|plan| match plan {
+                Planned::Modify { change, .. } => format!("M {}", change.display),
+                Planned::Create { display, .. } => format!("A {}", display),
+                Planned::Delete { display, .. } => format!("D {}", display),
+            })
+            .collect();
+
+        let hunks: usize = files.iter().map(|f| f.hunks.len()).sum();
+        let mut output = format!(
+            "Patched {} file{} ({} hunk{})\n{}",
+            planned.len(),
+            if planned.len() == 1 { "" } else { "s" },
+            hunks,
+            if hunks == 1 { "" } else { "s" },
+            summary.join("\n")
+        );
+        if !notes.is_empty() {
+            output.push_str("\n\n");
+            output.push_str(&notes.join("\n"));
+        }
+        self.history.record(change);
+        Ok(ToolResult::success(output))
+    }
+}
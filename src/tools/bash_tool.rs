@@ -34,7 +34,7 @@ struct BashInput {
 
 /// Truncate `output` to at most `BASH_MAX_OUTPUT_SIZE` bytes, appending a
 /// notice when truncation occurs.
-fn cap_output(output: &str) -> String {
+pub(super) fn cap_output(output: &str) -> String {
     if output.len() <= BASH_MAX_OUTPUT_SIZE {
         return output.to_string();
     }
@@ -95,6 +95,14 @@ impl Tool for BashTool {
         cmd.stdout(std::process::Stdio::piped());
         cmd.stderr(std::process::Stdio::piped());
 
+        // Run in a process group of its own, so a timeout or a cancelled
+        // turn kills whatever the command started, not just sh. Outside the
+        // terminal's foreground group, reading the terminal would stop it.
+        cmd.stdin(std::process::Stdio::null());
+        cmd.kill_on_drop(true);
+        #[cfg(unix)]
+        cmd.process_group(0);
+
         let child = cmd.spawn();
         let child = match child {
             Ok(c) => c,
@@ -106,9 +114,15 @@ impl Tool for BashTool {
             }
         };
 
+        let mut group = ProcessGroup(child.id());
+
         // Wait with timeout.
         let result =
             tokio::time::timeout(Duration::from_secs(timeout_secs), child.wait_with_output()).await;
+        if let Ok(Ok(_)) = result {
+            group.0 = None;
+        }
+        drop(group);
 
         match result {
             Ok(Ok(output)) => {
@@ -145,3 +159,17 @@ impl Tool for BashTool {
         }
     }
 }
+
+/// The process group led by a command's `sh`, killed on drop unless the
+/// command finished first.
+struct ProcessGroup(Option<u32>);
+
+impl Drop for ProcessGroup {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Some(pid) = self.0 {
+            use nix::sys::signal::{killpg, Signal};
+            let _ = killpg(nix::unistd::Pid::from_raw(pid as i32), Signal::SIGKILL);
+        }
+    }
+}
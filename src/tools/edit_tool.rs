@@ -5,7 +5,9 @@ use serde::Deserialize;
 use serde_json::{json, Value};
 use std::path::{Path, PathBuf};
 
+use super::file_history::{FileEdit, FileHistory};
 use super::{Tool, ToolResult};
+use crate::async_fs;
 use crate::constants::DIFF_CONTEXT_LINES;
 
 /// Tool that performs search-and-replace edits on existing files.
@@ -20,6 +22,8 @@ use crate::constants::DIFF_CONTEXT_LINES;
 pub struct EditTool {
     /// Project root directory. Paths are resolved relative to this.
     project_root: PathBuf,
+    /// Where the previous contents are recorded for `/undo`.
+    history: FileHistory,
 }
 
 impl EditTool {
@@ -28,8 +32,11 @@ impl EditTool {
     /// # Errors
     ///
     /// None — construction is infallible.
-    pub fn new(project_root: PathBuf) -> Self {
-        Self { project_root }
+    pub fn new(project_root: PathBuf, history: FileHistory) -> Self {
+        Self {
+            project_root,
+            history,
+        }
     }
 
     /// Resolve and validate that the path stays within the project root.
@@ -65,7 +72,7 @@ struct EditInput {
 }
 
 /// Produce a simplified before/after diff with context lines around each change.
-fn format_diff(old: &str, new: &str) -> String {
+pub(super) fn format_diff(old: &str, new: &str) -> String {
     let old_lines: Vec<&str> = old.lines().collect();
     let new_lines: Vec<&str> = new.lines().collect();
 
@@ -169,7 +176,7 @@ impl Tool for EditTool {
         let input: EditInput = serde_json::from_value(input)?;
         let path = self.resolve_path(&input.path)?;
 
-        let content = std::fs::read_to_string(&path)?;
+        let content = async_fs::read_to_string(&path).await?;
 
         if !content.contains(&input.old_text) {
             return Ok(ToolResult::error(format!(
@@ -185,7 +192,19 @@ impl Tool for EditTool {
             content.replacen(&input.old_text, &input.new_text, 1)
         };
 
-        std::fs::write(&path, &new_content)?;
+        let change = self
+            .history
+            .backup(
+                self.name(),
+                &[FileEdit {
+                    path: &path,
+                    before: Some(content.as_bytes()),
+                    after: Some(new_content.as_bytes()),
+                }],
+            )
+            .await?;
+        async_fs::write(&path, &new_content).await?;
+        self.history.record(change);
 
         let diff = format_diff(&content, &new_content);
         Ok(ToolResult::success(format!(
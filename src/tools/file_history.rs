@@ -0,0 +1,306 @@
+//! Pre-change copies of files written by tools, so `/undo` can put back the
+//! most recent change.
+//!
+//! Before `write_file`, `edit`, `multi_edit`, or `apply_patch` touches a
+//! file, its contents are copied to `backups/<run>/` in the data directory
+//! (files that don't exist yet need no copy). Each successful call becomes
+//! one [`Change`] on a stack kept for the life of the process, shared with
+//! subagents. A later run deletes a run's copies once that run has ended
+//! and started more than [`BACKUP_RETENTION_DAYS`] ago. Files changed
+//! through `bash` are not tracked.
+
+use anyhow::{Context, Result};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::async_fs;
+use crate::config::Config;
+use crate::constants::{BACKUPS_DIR, BACKUP_RETENTION_DAYS};
+
+/// The stack of file changes made by tools, shared between the write tools
+/// and the chat engine.
+#[derive(Clone, Default)]
+pub struct FileHistory(Arc<Mutex<History>>);
+
+#[derive(Default)]
+struct History {
+    /// This run's backup directory, once it has been created.
+    dir: Option<PathBuf>,
+    /// Backups written so far, for unique file names.
+    copies: usize,
+    /// Changes recorded so far, numbering each.
+    recorded: usize,
+    changes: Vec<Change>,
+}
+
+/// One file a tool is about to write or delete.
+pub struct FileEdit<'a> {
+    pub path: &'a Path,
+    /// Current contents; `None` when the file doesn't exist.
+    pub before: Option<&'a [u8]>,
+    /// Contents the tool writes; `None` when it deletes the file.
+    pub after: Option<&'a [u8]>,
+}
+
+/// The files one tool call changed, with copies of what they held before.
+#[derive(Clone)]
+pub struct Change {
+    /// Its place in the order changes were recorded, set by `record`.
+    seq: usize,
+    tool: String,
+    files: Vec<Snapshot>,
+}
+
+#[derive(Clone)]
+struct Snapshot {
+    path: PathBuf,
+    /// Copy of the previous contents; `None` when the file was created.
+    backup: Option<PathBuf>,
+    /// Hash of what the tool left; `None` when it deleted the file.
+    written: Option<u64>,
+}
+
+/// What `/undo` reverted.
+pub struct Undone {
+    pub tool: String,
+    pub paths: Vec<PathBuf>,
+}
+
+impl FileHistory {
+    /// Copies the current contents of every file in `edits` aside. Pass the
+    /// result to [`record`](Self::record) once the tool's writes succeed.
+    pub async fn backup(&self, tool: &str, edits: &[FileEdit<'_>]) -> Result<Change> {
+        let dir = self.run_dir().await?;
+        let mut files = Vec::with_capacity(edits.len());
+        for edit in edits {
+            let backup = match edit.before {
+                Some(contents) => {
+                    let name = edit.path.file_name().unwrap_or_default().to_string_lossy();
+                    let copy = dir.join(format!("{}-{}", self.next_copy(), name));
+                    async_fs::write(&copy, contents)
+                        .await
+                        .with_context(|| format!("Failed to back up {}", edit.path.display()))?;
+                    Some(copy)
+                }
+                None => None,
+            };
+            files.push(Snapshot {
+                path: edit.path.to_path_buf(),
+                backup,
+                written: edit.after.map(hash),
+            });
+        }
+        Ok(Change {
+            seq: 0,
+            tool: tool.to_string(),
+            files,
+        })
+    }
+
+    /// Adds a completed change to the stack.
+    pub fn record(&self, mut change: Change) {
+        let mut history = self.lock();
+        history.recorded += 1;
+        change.seq = history.recorded;
+        history.changes.push(change);
+    }
+
+    /// Puts back the files of the most recent change; `None` when there is
+    /// nothing to undo.
+    ///
+    /// A file edited since the tool wrote it is left alone and the change
+    /// stays on the stack, unless `force` is set. The change also stays if
+    /// restoring fails, so the undo can be tried again.
+    pub async fn undo(&self, force: bool) -> Result<Option<Undone>> {
+        let Some(change) = self.lock().changes.last().cloned() else {
+            return Ok(None);
+        };
+        if !force {
+            for file in &change.files {
+                let current = read_existing(&file.path).await?;
+                if current.as_deref().map(hash) != file.written {
+                    let path = file.path.display().to_string();
+                    anyhow::bail!(
+                        "{} has changed since it was written; use /undo --force to restore it anyway",
+                        path
+                    );
+                }
+            }
+        }
+        // In reverse, so a path listed twice ends up as it was first
+        for file in change.files.iter().rev() {
+            match &file.backup {
+                Some(copy) => {
+                    let contents = async_fs::read(copy)
+                        .await
+                        .with_context(|| format!("Backup of {} is missing", file.path.display()))?;
+                    if let Some(parent) = file.path.parent() {
+                        async_fs::create_dir_all(parent).await?;
+                    }
+                    async_fs::write(&file.path, contents)
+                        .await
+                        .with_context(|| format!("Failed to restore {}", file.path.display()))?;
+                }
+                None => match async_fs::remove_file(&file.path).await {
+                    Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                        return Err(e)
+                            .with_context(|| format!("Failed to remove {}", file.path.display()));
+                    }
+                    _ => {}
+                },
+            }
+        }
+        // By number, since a subagent may have recorded another meanwhile
+        self.lock().changes.retain(|c| c.seq != change.seq);
+        Ok(Some(Undone {
+            tool: change.tool,
+            paths: change.files.into_iter().map(|f| f.path).collect(),
+        }))
+    }
+
+    /// This run's backup directory, created (and old runs' pruned) on first use.
+    async fn run_dir(&self) -> Result<PathBuf> {
+        if let Some(dir) = self.lock().dir.clone() {
+            return Ok(dir);
+        }
+        let root = Config::data_dir()?.join(BACKUPS_DIR);
+        let dir = root.join(format!(
+            "{}-{}",
+            chrono::Utc::now().format("%Y%m%dT%H%M%S"),
+            std::process::id()
+        ));
+        async_fs::create_dir_all(&dir)
+            .await
+            .context("Failed to create backups directory")?;
+        let keep = dir.clone();
+        let _ = tokio::task::spawn_blocking(move || prune(&root, &keep)).await;
+        self.lock().dir = Some(dir.clone());
+        Ok(dir)
+    }
+
+    fn next_copy(&self) -> usize {
+        let mut history = self.lock();
+        history.copies += 1;
+        history.copies
+    }
+
+    fn lock(&self) -> MutexGuard<'_, History> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A file's contents, or `None` if it doesn't exist.
+pub(crate) async fn read_existing(path: &Path) -> Result<Option<Vec<u8>>> {
+    match async_fs::read(path).await {
+        Ok(contents) => Ok(Some(contents)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+fn hash(contents: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    contents.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Deletes run directories under `root` started more than
+/// [`BACKUP_RETENTION_DAYS`] ago, other than `keep` and those of runs still
+/// going, whose `/undo` may yet need them.
+///
+/// A run's age is read from the start time in its directory's name rather
+/// than from modification times, which copying or touching files changes.
+fn prune(root: &Path, keep: &Path) {
+    let max_age = chrono::Duration::days(BACKUP_RETENTION_DAYS as i64);
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return;
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        let name = entry.file_name();
+        let Some((started, pid)) = name.to_str().and_then(|name| name.split_once('-')) else {
+            continue;
+        };
+        let Ok(started) = chrono::NaiveDateTime::parse_from_str(started, "%Y%m%dT%H%M%S") else {
+            continue;
+        };
+        let expired = chrono::Utc::now().naive_utc() - started > max_age;
+        let running = pid.parse().is_ok_and(is_running);
+        if expired && !running && path != keep && path.is_dir() {
+            let _ = std::fs::remove_dir_all(&path);
+        }
+    }
+}
+
+/// Whether a process with this ID exists (possibly a later one reusing it,
+/// which only keeps a backup longer).
+#[cfg(unix)]
+fn is_running(pid: i32) -> bool {
+    use nix::errno::Errno;
+    use nix::sys::signal::kill;
+    use nix::unistd::Pid;
+    pid > 0 && !matches!(kill(Pid::from_raw(pid), None), Err(Errno::ESRCH))
+}
+
+#[cfg(not(unix))]
+fn is_running(_pid: i32) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_failed_undo_keeps_the_change() {
+        let dir = std::env::temp_dir().join(format!("kaze_test_undo_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("a.txt");
+        std::fs::write(&path, "new\n").unwrap();
+
+        let history = FileHistory::default();
+        let edit = FileEdit {
+            path: &path,
+            before: Some(b"old\n"),
+            after: Some(b"new\n"),
+        };
+        let change = history.backup("write_file", &[edit]).await.unwrap();
+        let copy = change.files[0].backup.clone().unwrap();
+        history.record(change);
+
+        std::fs::rename(&copy, dir.join("aside")).unwrap();
+        assert!(history.undo(false).await.is_err());
+        std::fs::rename(dir.join("aside"), &copy).unwrap();
+        let undone = history.undo(false).await.unwrap().unwrap();
+        assert_eq!(undone.paths, vec![path.clone()]);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "old\n");
+        assert!(history.undo(false).await.unwrap().is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_prune_goes_by_start_time_and_spares_running_runs() {
+        let root = std::env::temp_dir().join(format!("kaze_test_prune_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let now = chrono::Utc::now().format("%Y%m%dT%H%M%S");
+        let runs = [
+            (format!("20000101T000000-{}", i32::MAX), false),
+            (format!("20000101T000000-{}", std::process::id()), true),
+            (format!("{}-{}", now, i32::MAX), true),
+            ("not-a-run".to_string(), true),
+        ];
+        for (name, _) in &runs {
+            std::fs::create_dir_all(root.join(name)).unwrap();
+        }
+
+        prune(&root, &root.join("keep"));
+        for (name, kept) in &runs {
+            assert_eq!(root.join(name).exists(), *kept, "{}", name);
+        }
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
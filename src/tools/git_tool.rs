@@ -0,0 +1,282 @@
+//! Git tool — structured git subcommands instead of freeform bash.
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use super::bash_tool::cap_output;
+use super::{Tool, ToolResult};
+use crate::constants::{BASH_STRIPPED_ENV_VARS, GIT_LOG_DEFAULT_LIMIT, GIT_TIMEOUT_SECS};
+
+/// Subcommands that never change the repository.
+pub const GIT_READ_ONLY: &[&str] = &["status", "diff", "log", "show", "blame"];
+
+/// Tool that runs a fixed set of git subcommands with validated arguments.
+///
+/// Each subcommand maps to one git invocation built here, so the model
+/// can't smuggle in options such as `--force` or `--amend`: revisions may
+/// not start with `-`, paths always follow `--`, and a force push is only
+/// ever `--force-with-lease`. Which subcommands may run is decided by the
+/// permission system (read-only ones are allowed and pushes denied unless
+/// configured otherwise).
+pub struct GitTool {
+    project_root: PathBuf,
+}
+
+impl GitTool {
+    pub fn new(project_root: PathBuf) -> Self {
+        Self { project_root }
+    }
+}
+
+#[derive(Deserialize)]
+struct GitInput {
+    command: String,
+    #[serde(default)]
+    paths: Vec<String>,
+    revision: Option<String>,
+    #[serde(default)]
+    staged: bool,
+    limit: Option<usize>,
+    message: Option<String>,
+    #[serde(default)]
+    all: bool,
+    remote: Option<String>,
+    branch: Option<String>,
+    #[serde(default)]
+    force: bool,
+}
+
+/// Rejects values git would parse as options.
+fn checked<'a>(value: &'a str, what: &str) -> std::result::Result<&'a str, String> {
+    if value.starts_with('-') || value.trim().is_empty() {
+        Err(format!("Invalid {}: {:?}", what, value))
+    } else {
+        Ok(value)
+    }
+}
+
+/// Builds the git arguments for a request.
+fn build_args(input: &GitInput) -> std::result::Result<Vec<String>, String> {
+    let mut args: Vec<String> = Vec::new();
+    let revision = input
+        .revision
+        .as_deref()
+        .map(|r| checked(r, "revision"))
+        .transpose()?;
+    let with_paths = |args: &mut Vec<String>| {
+        if !input.paths.is_empty() {
+            args.push("--".into());
+            args.extend(input.paths.iter().cloned());
+        }
+    };
+
+    match input.command.as_str() {
+        "status" => {
+            args.extend(["status".into(), "--short".into(), "--branch".into()]);
+            with_paths(&mut args);
+        }
+        "diff" => {
+            args.extend(["diff".into(), "--no-color".into()]);
+            if input.staged {
+                args.push("--cached".into());
+            }
+            args.extend(revision.map(str::to_string));
+            with_paths(&mut args);
+        }
+        "log" => {
+            let limit = input.limit.unwrap_or(GIT_LOG_DEFAULT_LIMIT);
+            args.extend([
+                "log".into(),
+                "--no-color".into(),
+                format!("--max-count={}", limit),
+                "--format=%h %ad %an %s".into(),
+                "--date=short".into(),
+            ]);
+            args.extend(revision.map(str::to_string));
+            with_paths(&mut args);
+        }
+        "show" => {
+            args.extend(["show".into(), "--no-color".into(), "--stat".into(), "--patch".into()]);
+            args.push(revision.unwrap_or("HEAD").to_string());
+            with_paths(&mut args);
+        }
+        "blame" => {
+            let [path] = input.paths.as_slice() else {
+                return Err("blame takes exactly one path".into());
+            };
+            args.push("blame".into());
+            args.extend(revision.map(str::to_string));
+            args.extend(["--".into(), path.clone()]);
+        }
+        "add" => {
+            if input.paths.is_empty() {
+                return Err("add needs at least one path".into());
+            }
+            args.push("add".into());
+            with_paths(&mut args);
+        }
+        "commit" => {
+            let message = input
+                .message
+                .as_deref()
+                .filter(|m| !m.trim().is_empty())
+                .ok_or("commit needs a message")?;
+            args.push("commit".into());
+            if input.all {
+                args.push("--all".into());
+            }
+            args.extend(["--message".into(), message.to_string()]);
+            with_paths(&mut args);
+        }
+        "push" => {
+            args.push("push".into());
+            if input.force {
+                args.push("--force-with-lease".into());
+            }
+            if let Some(remote) = &input.remote {
+                args.push(checked(remote, "remote")?.to_string());
+                if let Some(branch) = &input.branch {
+                    args.push(checked(branch, "branch")?.to_string());
+                }
+            } else if input.branch.is_some() {
+                return Err("push needs a remote when a branch is given".into());
+            }
+        }
+        other => {
+            return Err(format!(
+                "Unknown git command '{}'. Supported: status, diff, log, show, blame, add, commit, push",
+                other
+            ))
+        }
+    }
+    Ok(args)
+}
+
+#[async_trait::async_trait]
+impl Tool for GitTool {
+    fn name(&self) -> &str {
+        "git"
+    }
+
+    fn description(&self) -> &str {
+        "Run a git subcommand in the project: status, diff (optionally staged or against a \
+         revision), log, show, blame, add, commit (with a message), or push. Prefer this \
+         over bash for git. Pushing may be disabled by the user's permissions."
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "command": {
+                    "type": "string",
+                    "enum": ["status", "diff", "log", "show", "blame", "add", "commit", "push"],
+                    "description": "Git subcommand to run"
+                },
+                "paths": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Limit to these paths (required for add; exactly one for blame)"
+                },
+                "revision": {
+                    "type": "string",
+                    "description": "Revision or range for diff, log, show, and blame (e.g. HEAD~3, main..HEAD)"
+                },
+                "staged": {
+                    "type": "boolean",
+                    "description": "diff: show staged changes"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": format!("log: number of commits (default {})", GIT_LOG_DEFAULT_LIMIT)
+                },
+                "message": {
+                    "type": "string",
+                    "description": "commit: commit message"
+                },
+                "all": {
+                    "type": "boolean",
+                    "description": "commit: stage all modified tracked files first"
+                },
+                "remote": {
+                    "type": "string",
+                    "description": "push: remote name"
+                },
+                "branch": {
+                    "type": "string",
+                    "description": "push: branch name"
+                },
+                "force": {
+                    "type": "boolean",
+                    "description": "push: force with lease (usually not permitted)"
+                }
+            },
+            "required": ["command"]
+        })
+    }
+
+    async fn execute(&self, input: Value) -> Result<ToolResult> {
+        let input: GitInput = serde_json::from_value(input)?;
+        let args = match build_args(&input) {
+            Ok(args) => args,
+            Err(message) => return Ok(ToolResult::error(message)),
+        };
+
+        let mut cmd = tokio::process::Command::new("git");
+        cmd.arg("--no-pager")
+            .args(&args)
+            .current_dir(&self.project_root)
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true);
+        for var in BASH_STRIPPED_ENV_VARS {
+            cmd.env_remove(var);
+        }
+
+        let child = match cmd.spawn() {
+            Ok(child) => child,
+            Err(e) => return Ok(ToolResult::error(format!("Failed to run git: {}", e))),
+        };
+        let timeout = Duration::from_secs(GIT_TIMEOUT_SECS);
+        let output = match tokio::time::timeout(timeout, child.wait_with_output()).await {
+            Ok(Ok(output)) => output,
+            Ok(Err(e)) => return Ok(ToolResult::error(format!("Failed to run git: {}", e))),
+            Err(_) => {
+                return Ok(ToolResult::error(format!(
+                    "git {} timed out after {}s",
+                    input.command, GIT_TIMEOUT_SECS
+                )))
+            }
+        };
+
+        let mut text = String::from_utf8_lossy(&output.stdout).to_string();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !stderr.trim().is_empty() {
+            if !text.is_empty() {
+                text.push('\n');
+            }
+            text.push_str(&stderr);
+        }
+        let text = cap_output(text.trim());
+        if !output.status.success() {
+            return Ok(ToolResult::error(format!(
+                "git {} failed (exit code {}):\n{}",
+                input.command,
+                output.status.code().unwrap_or(-1),
+                text
+            )));
+        }
+        if text.is_empty() {
+            return Ok(ToolResult::success(format!(
+                "git {}: no output",
+                input.command
+            )));
+        }
+        Ok(ToolResult::success(text))
+    }
+}
@@ -1,7 +1,7 @@
 use anyhow::Result;
 use serde::Deserialize;
 use serde_json::{json, Value};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use super::{Tool, ToolResult};
 
@@ -47,30 +47,12 @@ impl Tool for GlobTool {
 
     async fn execute(&self, input: Value) -> Result<ToolResult> {
         let input: GlobInput = serde_json::from_value(input)?;
-        let full_pattern = self.project_root.join(&input.pattern);
-        let pattern_str = full_pattern.to_string_lossy();
+        let project_root = self.project_root.clone();
 
-        let root_canonical = self.project_root.canonicalize()?;
-
-        let mut paths: Vec<String> = Vec::new();
-        for entry in glob::glob(&pattern_str)? {
-            if paths.len() >= GLOB_MAX_RESULTS {
-                paths.push(format!("... truncated at {} results", GLOB_MAX_RESULTS));
-                break;
-            }
-            let entry = entry?;
-            // Skip entries outside project root
-            if let Ok(canonical) = entry.canonicalize() {
-                if !canonical.starts_with(&root_canonical) {
-                    continue;
-                }
-            } else {
-                continue; // Skip entries that can't be canonicalized (broken symlinks, etc.)
-            }
-            // Show paths relative to project root
-            let relative = entry.strip_prefix(&self.project_root).unwrap_or(&entry);
-            paths.push(relative.display().to_string());
-        }
+        // Directory walking is blocking work; keep it off the runtime thread
+        let paths =
+            tokio::task::spawn_blocking(move || collect_matches(&project_root, &input.pattern))
+                .await??;
 
         if paths.is_empty() {
             Ok(ToolResult::success("No files matched the pattern.".into()))
@@ -79,3 +61,34 @@ impl Tool for GlobTool {
         }
     }
 }
+
+/// Expands `pattern` under `project_root`, returning project-relative paths.
+///
+/// Entries that resolve outside the project root are skipped.
+fn collect_matches(project_root: &Path, pattern: &str) -> Result<Vec<String>> {
+    let full_pattern = project_root.join(pattern);
+    let pattern_str = full_pattern.to_string_lossy();
+
+    let root_canonical = project_root.canonicalize()?;
+
+    let mut paths: Vec<String> = Vec::new();
+    for entry in glob::glob(&pattern_str)? {
+        if paths.len() >= GLOB_MAX_RESULTS {
+            paths.push(format!("... truncated at {} results", GLOB_MAX_RESULTS));
+            break;
+        }
+        let entry = entry?;
+        // Skip entries outside project root
+        if let Ok(canonical) = entry.canonicalize() {
+            if !canonical.starts_with(&root_canonical) {
+                continue;
+            }
+        } else {
+            continue; // Skip entries that can't be canonicalized (broken symlinks, etc.)
+        }
+        // Show paths relative to project root
+        let relative = entry.strip_prefix(project_root).unwrap_or(&entry);
+        paths.push(relative.display().to_string());
+    }
+    Ok(paths)
+}
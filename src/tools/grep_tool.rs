@@ -1,4 +1,5 @@
 use anyhow::Result;
+use rayon::prelude::*;
 use regex::Regex;
 use serde::Deserialize;
 use serde_json::{json, Value};
@@ -7,8 +8,9 @@ use std::path::{Path, PathBuf};
 
 use super::{Tool, ToolResult};
 
-use crate::constants::{BINARY_DETECTION_BYTES, GREP_MAX_MATCHES};
+use crate::constants::{BINARY_DETECTION_BYTES, GREP_MAX_MATCHES, GREP_PARALLEL_BATCH};
 
+#[derive(Clone)]
 pub struct GrepTool {
     project_root: PathBuf,
 }
@@ -20,29 +22,38 @@ impl GrepTool {
 
     /// Search files under `search_root` for lines matching `regex`.
     /// Optionally filter files by an include glob pattern.
+    ///
+    /// Files are listed in sorted walk order, then searched in parallel
+    /// batches of [`GREP_PARALLEL_BATCH`]; each batch's results are appended
+    /// in file order, so the output is the same as a sequential search.
     fn search(&self, regex: &Regex, search_root: &Path, include: Option<&str>) -> Vec<String> {
         let include_pattern = include.and_then(|pat| {
             let full = self.project_root.join("**").join(pat);
             glob::Pattern::new(&full.to_string_lossy()).ok()
         });
 
+        let mut files = Vec::new();
+        Self::collect_files(search_root, &include_pattern, &mut files);
+
         let mut matches = Vec::new();
-        self.walk_and_search(search_root, regex, &include_pattern, &mut matches);
+        for batch in files.chunks(GREP_PARALLEL_BATCH) {
+            let found: Vec<Vec<String>> = batch
+                .par_iter()
+                .map(|path| self.search_file(path, regex))
+                .collect();
+            for file_matches in found {
+                let room = GREP_MAX_MATCHES - matches.len();
+                matches.extend(file_matches.into_iter().take(room));
+                if matches.len() >= GREP_MAX_MATCHES {
+                    return matches;
+                }
+            }
+        }
         matches
     }
 
-    /// Recursively walk directories, searching files for regex matches.
-    fn walk_and_search(
-        &self,
-        dir: &Path,
-        regex: &Regex,
-        include: &Option<glob::Pattern>,
-        matches: &mut Vec<String>,
-    ) {
-        if matches.len() >= GREP_MAX_MATCHES {
-            return;
-        }
-
+    /// Recursively walk directories, collecting the files to search.
+    fn collect_files(dir: &Path, include: &Option<glob::Pattern>, files: &mut Vec<PathBuf>) {
         let entries = match fs::read_dir(dir) {
             Ok(entries) => entries,
             Err(_) => return, // silently skip unreadable dirs
@@ -52,10 +63,6 @@ impl GrepTool {
         entries.sort_by_key(|e| e.file_name());
 
         for entry in entries {
-            if matches.len() >= GREP_MAX_MATCHES {
-                return;
-            }
-
             let path = entry.path();
             let file_name = entry.file_name();
             let name = file_name.to_string_lossy();
@@ -65,7 +72,7 @@ impl GrepTool {
                 if name.starts_with('.') || name == "target" || name == "node_modules" {
                     continue;
                 }
-                self.walk_and_search(&path, regex, include, matches);
+                Self::collect_files(&path, include, files);
             } else if path.is_file() {
                 // Apply include filter if present
                 if let Some(ref pattern) = include {
@@ -73,40 +80,39 @@ impl GrepTool {
                         continue;
                     }
                 }
-                self.search_file(&path, regex, matches);
+                files.push(path);
             }
         }
     }
 
-    /// Search a single file for regex matches, appending results as `path:line:content`.
-    fn search_file(&self, path: &Path, regex: &Regex, matches: &mut Vec<String>) {
+    /// Search a single file for regex matches, formatted as `path:line:content`.
+    /// Returns at most [`GREP_MAX_MATCHES`] lines.
+    fn search_file(&self, path: &Path, regex: &Regex) -> Vec<String> {
         // Read file, silently skip binary/unreadable
         let content = match fs::read(path) {
             Ok(bytes) => bytes,
-            Err(_) => return,
+            Err(_) => return Vec::new(),
         };
 
         // Check for binary content (null bytes in first 8KB)
         let check_len = content.len().min(BINARY_DETECTION_BYTES);
         if content[..check_len].contains(&0) {
-            return;
+            return Vec::new();
         }
 
         let text = match String::from_utf8(content) {
             Ok(s) => s,
-            Err(_) => return,
+            Err(_) => return Vec::new(),
         };
 
         let relative = path.strip_prefix(&self.project_root).unwrap_or(path);
 
-        for (line_num, line) in text.lines().enumerate() {
-            if matches.len() >= GREP_MAX_MATCHES {
-                return;
-            }
-            if regex.is_match(line) {
-                matches.push(format!("{}:{}:{}", relative.display(), line_num + 1, line));
-            }
-        }
+        text.lines()
+            .enumerate()
+            .filter(|(_, line)| regex.is_match(line))
+            .take(GREP_MAX_MATCHES)
+            .map(|(line_num, line)| format!("{}:{}:{}", relative.display(), line_num + 1, line))
+            .collect()
     }
 }
 
@@ -174,7 +180,12 @@ impl Tool for GrepTool {
             self.project_root.clone()
         };
 
-        let matches = self.search(&regex, &search_root, input.include.as_deref());
+        // Walking a large tree is blocking work; keep it off the runtime thread
+        let tool = self.clone();
+        let matches = tokio::task::spawn_blocking(move || {
+            tool.search(&regex, &search_root, input.include.as_deref())
+        })
+        .await?;
 
         if matches.is_empty() {
             Ok(ToolResult::success("No matches found.".into()))
@@ -0,0 +1,323 @@
+//! Help lookup tool — local man pages and `--help` output, without a shell.
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+use super::{Tool, ToolResult};
+
+use crate::constants::{
+    BASH_STRIPPED_ENV_VARS, HELP_LOOKUP_MAN_WIDTH, HELP_LOOKUP_MAX_OUTPUT,
+    HELP_LOOKUP_REFUSED_COMMANDS, HELP_LOOKUP_TIMEOUT_SECS,
+};
+
+/// Tool that reads the documentation of an installed command.
+///
+/// Only `man -P cat <page>`, `<command> [subcommand] --help`, and
+/// `<command> <subcommand> -h` are run, directly rather than through a
+/// shell, in a fresh empty directory, with names restricted to plain command
+/// characters. Shells, interpreters, and wrappers only get their man page
+/// (see [`HELP_LOOKUP_REFUSED_COMMANDS`]). Programs are looked up on `PATH`
+/// but never inside the project, so a checked-in script can't pose as a
+/// system command.
+pub struct HelpLookupTool {
+    project_root: PathBuf,
+}
+
+impl HelpLookupTool {
+    pub fn new(project_root: PathBuf) -> Self {
+        Self { project_root }
+    }
+
+    /// Runs `program` with `args`, returning its output, or why there is
+    /// none. A failing exit status counts as no output unless `lenient`,
+    /// for the programs that print their usage and exit non-zero.
+    async fn run(&self, program: &str, args: &[&str], lenient: bool) -> Result<String, String> {
+        let Some(path) = resolve_program(&self.project_root, program) else {
+            return Err(format!("'{}' is not installed", program));
+        };
+        let dir = empty_dir().map_err(|e| format!("Failed to run {}: {}", program, e))?;
+        let output = run_in(&dir, &path, program, args).await;
+        let _ = std::fs::remove_dir_all(&dir);
+        let output = output?;
+
+        // Plenty of programs print their usage on stderr
+        let stdout = clean_help_text(&String::from_utf8_lossy(&output.stdout));
+        let text = if stdout.is_empty() {
+            clean_help_text(&String::from_utf8_lossy(&output.stderr))
+        } else {
+            stdout
+        };
+        if !output.status.success() && (!lenient || text.is_empty()) {
+            let reason = text.lines().next().unwrap_or("no output").to_string();
+            return Err(format!("{} {}: {}", program, args.join(" "), reason));
+        }
+        if text.is_empty() {
+            return Err(format!("{} {} printed nothing", program, args.join(" ")));
+        }
+        Ok(text)
+    }
+}
+
+/// Runs the program at `path` in `dir` with help-friendly pager settings.
+async fn run_in(
+    dir: &Path,
+    path: &Path,
+    program: &str,
+    args: &[&str],
+) -> Result<std::process::Output, String> {
+    let mut cmd = tokio::process::Command::new(path);
+    cmd.args(args)
+        .current_dir(dir)
+        .env("MANPAGER", "cat")
+        .env("PAGER", "cat")
+        .env("MANWIDTH", HELP_LOOKUP_MAN_WIDTH)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    for var in BASH_STRIPPED_ENV_VARS {
+        cmd.env_remove(var);
+    }
+    let child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    tokio::time::timeout(
+        Duration::from_secs(HELP_LOOKUP_TIMEOUT_SECS),
+        child.wait_with_output(),
+    )
+    .await
+    .map_err(|_| format!("{} timed out after {}s", program, HELP_LOOKUP_TIMEOUT_SECS))?
+    .map_err(|e| format!("Failed to run {}: {}", program, e))
+}
+
+/// A new, empty, private directory to run a program in, so it finds
+/// nothing to pick up where it starts.
+fn empty_dir() -> std::io::Result<PathBuf> {
+    let id = uuid::Uuid::new_v4().simple().to_string();
+    let dir = std::env::temp_dir().join(format!("kaze-help-{}", &id[..12]));
+    let mut builder = std::fs::DirBuilder::new();
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(&dir)?;
+    Ok(dir)
+}
+
+/// Whether `command` may be run for its `--help`: not a shell, interpreter,
+/// or wrapper that would run its next argument.
+pub(super) fn may_run_for_help(command: &str) -> bool {
+    let base = match command.strip_prefix("python") {
+        Some(version) if version.chars().all(|c| c.is_ascii_digit() || c == '.') => "python",
+        _ => command,
+    };
+    !HELP_LOOKUP_REFUSED_COMMANDS.contains(&base)
+}
+
+/// The first executable named `program` in an absolute `PATH` entry
+/// outside `project_root`, so a checked-in script can't pose as a system
+/// command.
+pub(crate) fn resolve_program(project_root: &Path, program: &str) -> Option<PathBuf> {
+    let root = project_root.canonicalize().ok();
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .filter(|dir| dir.is_absolute())
+        .filter(|dir| match (&root, dir.canonicalize()) {
+            (Some(root), Ok(dir)) => !dir.starts_with(root),
+            (None, Ok(_)) => true,
+            (_, Err(_)) => false,
+        })
+        .map(|dir| dir.join(program))
+        .find(|candidate| is_executable(candidate))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Whether `name` can be passed on as a command or man page name: plain
+/// characters only, and not an option.
+pub(super) fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('-')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '+' | '-'))
+}
+
+/// Strips the backspace overstrike man uses for bold and underline and
+/// ANSI escapes, trims trailing whitespace, and collapses runs of blank
+/// lines.
+pub(super) fn clean_help_text(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\x08' => {
+                plain.pop();
+            }
+            '\x1b' if chars.peek() == Some(&'[') => {
+                chars.next();
+                for next in chars.by_ref() {
+                    if next.is_ascii_alphabetic() {
+                        break;
+                    }
+                }
+            }
+            '\r' => {}
+            _ => plain.push(c),
+        }
+    }
+
+    let mut out = String::with_capacity(plain.len());
+    let mut blank = false;
+    for line in plain.lines().map(str::trim_end) {
+        if line.is_empty() {
+            blank = !out.is_empty();
+            continue;
+        }
+        if blank {
+            out.push('\n');
+            blank = false;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    out.truncate(out.trim_end().len());
+    out
+}
+
+/// Truncates `text` to `HELP_LOOKUP_MAX_OUTPUT` bytes on a line boundary.
+fn cap(text: String) -> String {
+    if text.len() <= HELP_LOOKUP_MAX_OUTPUT {
+        return text;
+    }
+    let end = text[..text.floor_char_boundary(HELP_LOOKUP_MAX_OUTPUT)]
+        .rfind('\n')
+        .unwrap_or(0);
+    format!(
+        "{}\n... truncated at {} bytes; ask for a subcommand or the other source for less",
+        &text[..end],
+        HELP_LOOKUP_MAX_OUTPUT
+    )
+}
+
+#[derive(Deserialize)]
+struct HelpLookupInput {
+    command: String,
+    subcommand: Option<String>,
+    source: Option<String>,
+}
+
+#[async_trait::async_trait]
+impl Tool for HelpLookupTool {
+    fn name(&self) -> &str {
+        "help_lookup"
+    }
+
+    fn description(&self) -> &str {
+        "Read the local documentation of an installed command: its man page, or the output of \
+`<command> --help`. Use it to check flags and usage instead of guessing or running bash."
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "command": {
+                    "type": "string",
+                    "description": "Command to look up, e.g. \"tar\" or \"git\""
+                },
+                "subcommand": {
+                    "type": "string",
+                    "description": "Optional subcommand, e.g. \"commit\" for git commit"
+                },
+                "source": {
+                    "type": "string",
+                    "enum": ["man", "help"],
+                    "description": "Where to look: the man page or --help output (default: man, falling back to --help)"
+                }
+            },
+            "required": ["command"]
+        })
+    }
+
+    async fn execute(&self, input: Value) -> Result<ToolResult> {
+        let input: HelpLookupInput = serde_json::from_value(input)?;
+        let subcommand = input.subcommand.filter(|s| !s.is_empty());
+        for name in std::iter::once(&input.command).chain(&subcommand) {
+            if !is_valid_name(name) {
+                return Ok(ToolResult::error(format!(
+                    "'{}' is not a command name: use letters, digits, and . _ + - only",
+                    name
+                )));
+            }
+        }
+
+        let page = match &subcommand {
+            Some(sub) => format!("{}-{}", input.command, sub),
+            None => input.command.clone(),
+        };
+        let with_flag = |flag| {
+            let mut args: Vec<&str> = subcommand.iter().map(String::as_str).collect();
+            args.push(flag);
+            args
+        };
+
+        let (try_man, try_help) = match input.source.as_deref() {
+            Some("man") => (true, false),
+            Some("help") => (false, true),
+            None => (true, true),
+            Some(other) => {
+                return Ok(ToolResult::error(format!(
+                    "Unknown source '{}': use \"man\" or \"help\"",
+                    other
+                )))
+            }
+        };
+
+        let mut failures = Vec::new();
+        if try_man {
+            match self.run("man", &["-P", "cat", &page], false).await {
+                Ok(text) => return Ok(ToolResult::success(cap(text))),
+                Err(e) => failures.push(e),
+            }
+        }
+        if try_help && !may_run_for_help(&input.command) {
+            failures.push(format!(
+                "{} is only looked up by its man page, since it runs other commands",
+                input.command
+            ));
+        } else if try_help {
+            // A subcommand's `--help` sometimes defers to man (git's does),
+            // where `-h` gives the short usage instead. Bare `-h` isn't
+            // always help, so it's only tried after a subcommand
+            let flags: &[(&str, bool)] = match subcommand {
+                Some(_) => &[("--help", false), ("-h", true)],
+                None => &[("--help", false)],
+            };
+            for &(flag, lenient) in flags {
+                match self.run(&input.command, &with_flag(flag), lenient).await {
+                    Ok(text) => return Ok(ToolResult::success(cap(text))),
+                    Err(e) => failures.push(e),
+                }
+            }
+        }
+        failures.dedup();
+        Ok(ToolResult::error(format!(
+            "No documentation found for {}:\n{}",
+            page,
+            failures.join("\n")
+        )))
+    }
+}
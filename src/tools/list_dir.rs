@@ -0,0 +1,191 @@
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::{Tool, ToolResult};
+
+use crate::constants::{LIST_DIR_MAX_DEPTH, LIST_DIR_MAX_ENTRIES};
+
+pub struct ListDirTool {
+    /// Project root directory. Paths are resolved relative to this.
+    project_root: PathBuf,
+}
+
+impl ListDirTool {
+    pub fn new(project_root: PathBuf) -> Self {
+        Self { project_root }
+    }
+
+    /// Resolve and validate that the directory stays within the project root.
+    fn resolve_path(&self, path: &str) -> Result<PathBuf> {
+        let resolved = if Path::new(path).is_absolute() {
+            PathBuf::from(path)
+        } else {
+            self.project_root.join(path)
+        };
+        let canonical = resolved.canonicalize()?;
+        let root_canonical = self.project_root.canonicalize()?;
+        if !canonical.starts_with(&root_canonical) {
+            anyhow::bail!("Path escapes project directory: {}", path);
+        }
+        Ok(canonical)
+    }
+}
+
+#[derive(Deserialize)]
+struct ListDirInput {
+    #[serde(default = "default_path")]
+    path: String,
+    #[serde(default = "default_depth")]
+    depth: usize,
+    #[serde(default)]
+    hidden: bool,
+}
+
+fn default_path() -> String {
+    ".".into()
+}
+
+fn default_depth() -> usize {
+    1
+}
+
+/// Listing state shared across the recursive walk.
+struct Listing {
+    root: PathBuf,
+    hidden: bool,
+    max_depth: usize,
+    lines: Vec<String>,
+    truncated: bool,
+}
+
+#[async_trait::async_trait]
+impl Tool for ListDirTool {
+    fn name(&self) -> &str {
+        "list_dir"
+    }
+
+    fn description(&self) -> &str {
+        "List a directory's entries with type, size, and modification time. \
+Path is relative to the project root; depth > 1 descends into subdirectories."
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Directory relative to project root (default: '.')"
+                },
+                "depth": {
+                    "type": "integer",
+                    "description": format!(
+                        "How many levels to list (default: 1, max: {})",
+                        LIST_DIR_MAX_DEPTH
+                    )
+                },
+                "hidden": {
+                    "type": "boolean",
+                    "description": "Include dotfiles and dot-directories (default: false)"
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, input: Value) -> Result<ToolResult> {
+        let input: ListDirInput = serde_json::from_value(input)?;
+        let dir = self.resolve_path(&input.path)?;
+        if !dir.is_dir() {
+            return Ok(ToolResult::error(format!(
+                "Not a directory: {}",
+                input.path
+            )));
+        }
+
+        let mut listing = Listing {
+            root: self.project_root.canonicalize()?,
+            hidden: input.hidden,
+            max_depth: input.depth.clamp(1, LIST_DIR_MAX_DEPTH),
+            lines: Vec::new(),
+            truncated: false,
+        };
+
+        // Directory walking is blocking work; keep it off the runtime thread
+        let listing = tokio::task::spawn_blocking(move || {
+            walk(&dir, 1, &mut listing);
+            listing
+        })
+        .await?;
+
+        if listing.lines.is_empty() {
+            return Ok(ToolResult::success("Directory is empty.".into()));
+        }
+        let mut output = listing.lines.join("\n");
+        if listing.truncated {
+            output.push_str(&format!(
+                "\n... truncated at {} entries",
+                LIST_DIR_MAX_ENTRIES
+            ));
+        }
+        Ok(ToolResult::success(output))
+    }
+}
+
+/// Appends one line per entry of `dir`, directories first, recursing until
+/// `max_depth`. Symlinks are listed but not followed, so the walk cannot
+/// leave the project root.
+fn walk(dir: &Path, depth: usize, listing: &mut Listing) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut entries: Vec<_> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| listing.hidden || !e.file_name().to_string_lossy().starts_with('.'))
+        .filter_map(|e| Some((e.path(), fs::symlink_metadata(e.path()).ok()?)))
+        .collect();
+    entries.sort_by(|(a_path, a_meta), (b_path, b_meta)| {
+        b_meta
+            .is_dir()
+            .cmp(&a_meta.is_dir())
+            .then_with(|| a_path.file_name().cmp(&b_path.file_name()))
+    });
+
+    for (path, metadata) in entries {
+        if listing.lines.len() >= LIST_DIR_MAX_ENTRIES {
+            listing.truncated = true;
+            return;
+        }
+        let relative = path.strip_prefix(&listing.root).unwrap_or(&path);
+        let (kind, size) = if metadata.is_symlink() {
+            ("symlink", "-".to_string())
+        } else if metadata.is_dir() {
+            ("dir", "-".to_string())
+        } else {
+            ("file", metadata.len().to_string())
+        };
+        let modified = metadata
+            .modified()
+            .map(|t| {
+                chrono::DateTime::<chrono::Local>::from(t)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_else(|_| "-".into());
+        let suffix = if kind == "dir" { "/" } else { "" };
+        listing.lines.push(format!(
+            "{:<7} {:>10}  {}  {}{}",
+            kind,
+            size,
+            modified,
+            relative.display(),
+            suffix
+        ));
+
+        if kind == "dir" && depth < listing.max_depth {
+            walk(&path, depth + 1, listing);
+        }
+    }
+}
@@ -1,22 +1,49 @@
+pub mod apply_patch;
 pub mod bash_tool;
 pub mod edit_tool;
+pub mod file_history;
+pub mod git_tool;
 pub mod glob_tool;
 pub mod grep_tool;
+pub mod help_lookup;
+pub mod list_dir;
+pub mod multi_edit;
 pub mod read_file;
 pub mod rig_adapter;
+pub mod scratchpad;
+pub mod task;
+pub mod todo;
+pub mod web_fetch;
+pub mod web_search;
 pub mod write_file;
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+use crate::audit::AuditLog;
+use crate::config::{Config, ToolsConfig};
+use crate::hooks::KazeHook;
+use crate::provider::{ModelSelection, Provider};
+
+use apply_patch::ApplyPatchTool;
 use bash_tool::BashTool;
 use edit_tool::EditTool;
+use file_history::FileHistory;
+use git_tool::GitTool;
 use glob_tool::GlobTool;
 use grep_tool::GrepTool;
+use help_lookup::HelpLookupTool;
+use list_dir::ListDirTool;
+use multi_edit::MultiEditTool;
 use read_file::ReadFileTool;
+use scratchpad::{Scratchpad, ScratchpadTool};
+use task::TaskTool;
+use todo::{TodoList, TodoTool};
+use web_fetch::{RedirectPermissions, WebFetchTool};
+use web_search::WebSearchTool;
 use write_file::WriteFileTool;
 
 /// The result of executing a tool.
@@ -70,11 +97,50 @@ pub trait Tool: Send + Sync {
 /// Holds all registered tools and dispatches calls by name.
 pub struct ToolRegistry {
     tools: Vec<Arc<dyn Tool>>,
+    /// The plan kept by the built-in `todo` tool.
+    todos: TodoList,
+    /// The note kept by the built-in `scratchpad` tool.
+    scratchpad: Scratchpad,
+    /// Files changed by the built-in write tools, for `/undo`.
+    file_history: FileHistory,
+    /// Where executed calls are recorded; shared with subagents' tools.
+    audit: AuditLog,
+    /// What `web_fetch` checks redirects against, set by [`with_task`](Self::with_task).
+    redirect_permissions: RedirectPermissions,
 }
 
 impl ToolRegistry {
     pub fn new() -> Self {
-        Self { tools: Vec::new() }
+        Self {
+            tools: Vec::new(),
+            todos: TodoList::default(),
+            scratchpad: Scratchpad::default(),
+            file_history: FileHistory::default(),
+            audit: AuditLog::default(),
+            redirect_permissions: RedirectPermissions::default(),
+        }
+    }
+
+    /// The list the `todo` tool edits, for persisting and displaying it.
+    pub fn todos(&self) -> &TodoList {
+        &self.todos
+    }
+
+    /// The note the `scratchpad` tool edits, for persisting it and showing
+    /// it to the model.
+    pub fn scratchpad(&self) -> &Scratchpad {
+        &self.scratchpad
+    }
+
+    /// The changes the write tools made, for undoing them.
+    pub fn file_history(&self) -> &FileHistory {
+        &self.file_history
+    }
+
+    /// The audit log calls made through [`to_rig_tools`](Self::to_rig_tools)
+    /// are recorded in, once started.
+    pub fn audit(&self) -> &AuditLog {
+        &self.audit
     }
 
     /// Register a tool. Called during startup.
@@ -82,6 +148,11 @@ impl ToolRegistry {
         self.tools.push(Arc::from(tool));
     }
 
+    /// Names of the registered tools, in registration order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.tools.iter().map(|t| t.name())
+    }
+
     /// Produce definitions for the LLM (sent in the API request).
     #[cfg(test)]
     pub fn definitions(&self) -> Vec<ToolDefinition> {
@@ -95,8 +166,7 @@ impl ToolRegistry {
             .collect()
     }
 
-    /// Look up a tool by name and execute it.
-    #[cfg(test)]
+    /// Look up a tool by name and execute it, without the permission hook.
     pub async fn execute(&self, name: &str, input: Value) -> Result<ToolResult> {
         let tool = self
             .tools
@@ -125,25 +195,133 @@ impl ToolRegistry {
         self.tools
             .iter()
             .map(|t| {
-                Box::new(rig_adapter::RigToolAdapter::new(Arc::clone(t)))
-                    as Box<dyn rig::tool::ToolDyn>
+                Box::new(rig_adapter::RigToolAdapter::new(
+                    Arc::clone(t),
+                    self.audit.clone(),
+                )) as Box<dyn rig::tool::ToolDyn>
             })
             .collect()
     }
 }
 
+impl Default for ToolRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Refuses a project root of `/` or the home directory unless
+/// `allow_broad` is set, since `glob` and `grep` there would walk the whole
+/// disk or every private file the user has.
+pub fn check_project_root(root: &Path, allow_broad: bool) -> Result<()> {
+    if allow_broad {
+        return Ok(());
+    }
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    let home = dirs::home_dir().map(|h| h.canonicalize().unwrap_or(h));
+    let what = if root.parent().is_none() {
+        "the filesystem root"
+    } else if home.as_deref() == Some(root.as_path()) {
+        "your home directory"
+    } else {
+        return Ok(());
+    };
+    anyhow::bail!(crate::i18n::tf(
+        "root.too_broad",
+        &[("dir", &root.display().to_string()), ("what", what)]
+    ))
+}
+
 impl ToolRegistry {
-    /// Create a registry with all built-in tools.
+    /// Create a registry with all built-in tools, using default tool settings.
     pub fn with_builtins(project_root: PathBuf) -> Self {
+        Self::with_settings(project_root, &ToolsConfig::default())
+    }
+
+    /// Create a registry with all built-in tools, configured from `[tools]`.
+    pub fn with_settings(project_root: PathBuf, settings: &ToolsConfig) -> Self {
+        Self::with_history(project_root, settings, FileHistory::default())
+    }
+
+    /// Like [`with_settings`](Self::with_settings), recording file changes
+    /// in `file_history`.
+    fn with_history(
+        project_root: PathBuf,
+        settings: &ToolsConfig,
+        file_history: FileHistory,
+    ) -> Self {
         let mut registry = Self::new();
+        registry.file_history = file_history;
+        let history = registry.file_history.clone();
         registry.register(Box::new(ReadFileTool::new(project_root.clone())));
         registry.register(Box::new(GlobTool::new(project_root.clone())));
+        registry.register(Box::new(ListDirTool::new(project_root.clone())));
         registry.register(Box::new(GrepTool::new(project_root.clone())));
-        registry.register(Box::new(WriteFileTool::new(project_root.clone())));
-        registry.register(Box::new(EditTool::new(project_root.clone())));
-        registry.register(Box::new(BashTool::new(project_root)));
+        registry.register(Box::new(WriteFileTool::new(
+            project_root.clone(),
+            history.clone(),
+        )));
+        registry.register(Box::new(EditTool::new(
+            project_root.clone(),
+            history.clone(),
+        )));
+        registry.register(Box::new(MultiEditTool::new(
+            project_root.clone(),
+            history.clone(),
+        )));
+        registry.register(Box::new(ApplyPatchTool::new(
+            project_root.clone(),
+            history.clone(),
+        )));
+        registry.register(Box::new(GitTool::new(project_root.clone())));
+        registry.register(Box::new(BashTool::new(project_root.clone())));
+        registry.register(Box::new(HelpLookupTool::new(project_root)));
+        registry.register(Box::new(WebFetchTool::new(
+            registry.redirect_permissions.clone(),
+        )));
+        registry.register(Box::new(WebSearchTool::new(settings.web_search.clone())));
+        registry.register(Box::new(TodoTool::new(registry.todos.clone())));
+        registry.register(Box::new(ScratchpadTool::new(registry.scratchpad.clone())));
         registry
     }
+
+    /// Adds the `task` tool for delegating subtasks to a subagent.
+    ///
+    /// Subagents use `[tools.task] model` (a `[provider/]model` spec
+    /// resolved against `selection`), or `selection` itself, and get their
+    /// own built-in tools checked by `hook`. Their file changes go on this
+    /// registry's history, so `/undo` reverts them too. Both registries'
+    /// `web_fetch` checks redirects against `hook`'s permissions.
+    pub fn with_task(
+        mut self,
+        config: &Config,
+        selection: &ModelSelection,
+        project_root: PathBuf,
+        hook: KazeHook,
+    ) -> Result<Self> {
+        let selection = match config.tools.task.model.as_deref() {
+            Some(spec) => crate::provider::resolve_model_spec(spec, selection.provider, config)?,
+            None => ModelSelection {
+                provider: selection.provider,
+                model: selection.model.clone(),
+            },
+        };
+        let provider = Provider::from_config(config, &selection)?;
+        let mut tools = Self::with_history(project_root, &config.tools, self.file_history.clone());
+        tools.audit = self.audit.clone();
+        for registry in [&self, &tools] {
+            let _ = registry.redirect_permissions.set(hook.shared_permissions());
+        }
+        let max_turns = config
+            .tools
+            .task
+            .max_turns
+            .unwrap_or(crate::constants::TASK_MAX_TURNS_DEFAULT);
+        self.register(Box::new(TaskTool::new(
+            provider, selection, tools, hook, max_turns,
+        )));
+        Ok(self)
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,297 @@
+//! Multi-edit tool — batched search-and-replace edits applied all-or-nothing.
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+use super::edit_tool::format_diff;
+use super::file_history::{FileEdit, FileHistory};
+use super::{Tool, ToolResult};
+use crate::async_fs;
+
+/// Tool that applies several search-and-replace edits, possibly across
+/// files, as one change.
+///
+/// Every edit is matched against an in-memory copy first (edits to the same
+/// file apply in order, each seeing the previous ones), so a missing match
+/// leaves the tree untouched. Files are then written to temporary siblings
+/// and renamed into place; if a rename fails, the files already replaced
+/// are restored.
+pub struct MultiEditTool {
+    /// Project root directory. Paths are resolved relative to this.
+    project_root: PathBuf,
+    /// Where the previous contents are recorded for `/undo`.
+    history: FileHistory,
+}
+
+impl MultiEditTool {
+    pub fn new(project_root: PathBuf, history: FileHistory) -> Self {
+        Self {
+            project_root,
+            history,
+        }
+    }
+
+    /// Resolve and validate that the path stays within the project root.
+    ///
+    /// The target file must already exist, so we canonicalize it directly.
+    fn resolve_path(&self, path: &str) -> Result<PathBuf> {
+        let resolved = if Path::new(path).is_absolute() {
+            PathBuf::from(path)
+        } else {
+            self.project_root.join(path)
+        };
+        let canonical = resolved.canonicalize()?;
+        let root_canonical = self.project_root.canonicalize()?;
+        if !canonical.starts_with(&root_canonical) {
+            anyhow::bail!("Path escapes project directory: {}", path);
+        }
+        Ok(canonical)
+    }
+}
+
+#[derive(Deserialize)]
+pub(crate) struct MultiEditInput {
+    pub edits: Vec<EditSpec>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct EditSpec {
+    pub path: String,
+    pub old_text: String,
+    pub new_text: String,
+    #[serde(default)]
+    pub replace_all: bool,
+}
+
+/// A file touched by the batch: its original and edited contents.
+#[derive(Clone)]
+pub(super) struct FileChange {
+    /// Path as first given by the model, for messages.
+    pub display: String,
+    pub path: PathBuf,
+    pub original: String,
+    pub edited: String,
+}
+
+/// Groups per-edit file keys into distinct files in first-touched order.
+///
+/// Returns the distinct keys and, for each edit, the index of its file.
+pub(crate) fn group_files<K: PartialEq + Clone>(keys: &[K]) -> (Vec<K>, Vec<usize>) {
+    let mut distinct: Vec<K> = Vec::new();
+    let file_of = keys
+        .iter()
+        .map(|key| match distinct.iter().position(|k| k == key) {
+            Some(index) => index,
+            None => {
+                distinct.push(key.clone());
+                distinct.len() - 1
+            }
+        })
+        .collect();
+    (distinct, file_of)
+}
+
+/// Applies `edits` in order to the working copies, where edit `i` targets
+/// `working[file_of[i]]`.
+///
+/// Returns a message naming the first edit whose `old_text` is missing; the
+/// working copies are then partially edited and must be discarded.
+pub(crate) fn apply_edits(
+    edits: &[EditSpec],
+    file_of: &[usize],
+    working: &mut [String],
+) -> std::result::Result<(), String> {
+    for (i, edit) in edits.iter().enumerate() {
+        let content = &mut working[file_of[i]];
+        if !content.contains(&edit.old_text) {
+            return Err(format!(
+                "Edit {} of {}: text not found in {}. No files were changed. \
+                 Make sure each old_text matches exactly (after earlier edits \
+                 to the same file), including whitespace and indentation.",
+                i + 1,
+                edits.len(),
+                edit.path
+            ));
+        }
+        *content = if edit.replace_all {
+            content.replace(&edit.old_text, &edit.new_text)
+        } else {
+            content.replacen(&edit.old_text, &edit.new_text, 1)
+        };
+    }
+    Ok(())
+}
+
+#[async_trait::async_trait]
+impl Tool for MultiEditTool {
+    fn name(&self) -> &str {
+        "multi_edit"
+    }
+
+    fn description(&self) -> &str {
+        "Apply several search-and-replace edits, across one or more files, atomically: \
+         all matches are checked before anything is written, so either every edit \
+         applies or none do. Edits to the same file apply in order. Paths are relative \
+         to the project root."
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "edits": {
+                    "type": "array",
+                    "description": "Edits to apply, in order",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "path": {
+                                "type": "string",
+                                "description": "File path relative to project root"
+                            },
+                            "old_text": {
+                                "type": "string",
+                                "description": "Exact text to search for in the file"
+                            },
+                            "new_text": {
+                                "type": "string",
+                                "description": "Text to replace old_text with"
+                            },
+                            "replace_all": {
+                                "type": "boolean",
+                                "description": "Replace all occurrences (default: false, replaces first only)"
+                            }
+                        },
+                        "required": ["path", "old_text", "new_text"]
+                    }
+                }
+            },
+            "required": ["edits"]
+        })
+    }
+
+    async fn execute(&self, input: Value) -> Result<ToolResult> {
+        let input: MultiEditInput = serde_json::from_value(input)?;
+        if input.edits.is_empty() {
+            return Ok(ToolResult::error("No edits given.".into()));
+        }
+
+        // Validate every path before reading anything
+        let resolved = input
+            .edits
+            .iter()
+            .map(|edit| self.resolve_path(&edit.path))
+            .collect::<Result<Vec<_>>>()?;
+        let (paths, file_of) = group_files(&resolved);
+
+        let mut originals = Vec::with_capacity(paths.len());
+        for path in &paths {
+            originals.push(async_fs::read_to_string(path).await?);
+        }
+        let mut working = originals.clone();
+        if let Err(message) = apply_edits(&input.edits, &file_of, &mut working) {
+            return Ok(ToolResult::error(message));
+        }
+
+        let changes: Vec<FileChange> = paths
+            .into_iter()
+            .zip(originals.into_iter().zip(working))
+            .enumerate()
+            .map(|(index, (path, (original, edited)))| {
+                // Name each file as its first edit did
+                let first_edit = file_of.iter().position(|&f| f == index).unwrap_or(0);
+                FileChange {
+                    display: input.edits[first_edit].path.clone(),
+                    path,
+                    original,
+                    edited,
+                }
+            })
+            .collect();
+        let change = self
+            .history
+            .backup(self.name(), &file_edits(&changes))
+            .await?;
+        commit(&changes).await?;
+        self.history.record(change);
+
+        let mut output = format!(
+            "Edited {} file{} ({} edit{})\n",
+            changes.len(),
+            if changes.len() == 1 { "" } else { "s" },
+            input.edits.len(),
+            if input.edits.len() == 1 { "" } else { "s" },
+        );
+        for change in &changes {
+            output.push_str(&format!(
+                "\n{}\n{}",
+                change.display,
+                format_diff(&change.original, &change.edited)
+            ));
+        }
+        Ok(ToolResult::success(output))
+    }
+}
+
+/// The history entries for `changes`.
+fn file_edits(changes: &[FileChange]) -> Vec<FileEdit<'_>> {
+    changes
+        .iter()
+        .map(|change| FileEdit {
+            path: &change.path,
+            before: Some(change.original.as_bytes()),
+            after: Some(change.edited.as_bytes()),
+        })
+        .collect()
+}
+
+/// Temporary sibling a file's new contents are staged in.
+pub(super) fn staging_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".kaze-multi-edit");
+    path.with_file_name(name)
+}
+
+/// Writes all changes, staging them first so a failure leaves every file
+/// either fully old or fully new.
+pub(super) async fn commit(changes: &[FileChange]) -> Result<()> {
+    for (i, change) in changes.iter().enumerate() {
+        let staging = staging_path(&change.path);
+        let staged = async {
+            async_fs::write(&staging, &change.edited).await?;
+            // Keep the original mode (e.g. the executable bit) across the rename
+            let perm = async_fs::metadata(&change.path).await?.permissions();
+            async_fs::set_permissions(&staging, perm).await
+        };
+        if let Err(e) = staged.await {
+            for staged in &changes[..=i] {
+                let _ = async_fs::remove_file(&staging_path(&staged.path)).await;
+            }
+            anyhow::bail!(
+                "Failed to stage {}: {}. No files were changed.",
+                change.display,
+                e
+            );
+        }
+    }
+
+    for (i, change) in changes.iter().enumerate() {
+        if let Err(e) = async_fs::rename(&staging_path(&change.path), &change.path).await {
+            // Put back the files already replaced and drop the rest of the staging
+            for done in &changes[..i] {
+                let _ = async_fs::write(&done.path, &done.original).await;
+            }
+            for pending in &changes[i..] {
+                let _ = async_fs::remove_file(&staging_path(&pending.path)).await;
+            }
+            anyhow::bail!(
+                "Failed to write {}: {}. Earlier files in the batch were restored.",
+                change.display,
+                e
+            );
+        }
+    }
+    Ok(())
+}
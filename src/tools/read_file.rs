@@ -1,11 +1,15 @@
 use anyhow::Result;
 use serde::Deserialize;
 use serde_json::{json, Value};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
 
 use super::{Tool, ToolResult};
 
-use crate::constants::{BINARY_DETECTION_BYTES, READ_FILE_MAX_SIZE};
+use crate::constants::{
+    BINARY_DETECTION_BYTES, READ_FILE_DEFAULT_LIMIT, READ_FILE_MAX_LINE_CHARS, READ_FILE_MAX_SIZE,
+};
 
 pub struct ReadFileTool {
     /// Project root directory. Paths are resolved relative to this.
@@ -36,6 +40,72 @@ impl ReadFileTool {
 #[derive(Deserialize)]
 struct ReadFileInput {
     path: String,
+    /// First line to return, 1-based.
+    #[serde(default)]
+    offset: Option<usize>,
+    /// Most lines to return.
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+/// The part of a file one call returns.
+struct Window {
+    /// Numbered lines, `{number}\t{text}`.
+    text: String,
+    /// Number of the first line after the window.
+    next: usize,
+    /// Lines after the window.
+    remaining: usize,
+    /// Total lines in the file.
+    total: usize,
+}
+
+/// Reads lines `offset..offset + limit` of `path` (1-based), stopping early
+/// at [`READ_FILE_MAX_SIZE`] bytes of output, and counts the rest.
+/// Returns `None` for binary files.
+fn read_window(path: &Path, offset: usize, limit: usize) -> Result<Option<Window>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    // Check for binary content (null bytes in first 8KB)
+    let head = reader.fill_buf()?;
+    if head[..head.len().min(BINARY_DETECTION_BYTES)].contains(&0) {
+        return Ok(None);
+    }
+
+    let mut window = Window {
+        text: String::new(),
+        next: offset,
+        remaining: 0,
+        total: 0,
+    };
+    let mut shown = 0;
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+        window.total += 1;
+        if window.total < offset {
+            continue;
+        }
+        if shown == limit || window.text.len() >= READ_FILE_MAX_SIZE {
+            window.remaining += 1;
+            continue;
+        }
+        let text = std::str::from_utf8(&line)
+            .map_err(|_| anyhow::anyhow!("File is not valid UTF-8 (line {})", window.total))?
+            .trim_end_matches(['\n', '\r']);
+        let text = match text.char_indices().nth(READ_FILE_MAX_LINE_CHARS) {
+            Some((cut, _)) => format!("{}... [line truncated]", &text[..cut]),
+            None => text.to_string(),
+        };
+        window
+            .text
+            .push_str(&format!("{:>6}\t{}\n", window.total, text));
+        shown += 1;
+        window.next = window.total + 1;
+    }
+    Ok(Some(window))
 }
 
 #[async_trait::async_trait]
@@ -45,7 +115,9 @@ impl Tool for ReadFileTool {
     }
 
     fn description(&self) -> &str {
-        "Read the contents of a file. Path is relative to the project root."
+        "Read the contents of a file. Path is relative to the project root. Lines are \
+         prefixed with their number and a tab (not part of the file). Long files are returned \
+         a window at a time; use offset and limit to read further."
     }
 
     fn schema(&self) -> Value {
@@ -55,6 +127,14 @@ impl Tool for ReadFileTool {
                 "path": {
                     "type": "string",
                     "description": "File path relative to project root"
+                },
+                "offset": {
+                    "type": "integer",
+                    "description": "Line number to start reading from (1-based, default 1)"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": format!("Maximum number of lines to return (default {})", READ_FILE_DEFAULT_LIMIT)
                 }
             },
             "required": ["path"]
@@ -64,27 +144,37 @@ impl Tool for ReadFileTool {
     async fn execute(&self, input: Value) -> Result<ToolResult> {
         let input: ReadFileInput = serde_json::from_value(input)?;
         let path = self.resolve_path(&input.path)?;
-
-        let metadata = std::fs::metadata(&path)?;
-        if metadata.len() > READ_FILE_MAX_SIZE {
-            return Ok(ToolResult::error(format!(
-                "File too large: {} bytes (max {})",
-                metadata.len(),
-                READ_FILE_MAX_SIZE
-            )));
+        let offset = input.offset.unwrap_or(1).max(1);
+        let limit = input.limit.unwrap_or(READ_FILE_DEFAULT_LIMIT);
+        if limit == 0 {
+            return Ok(ToolResult::error("limit must be at least 1".into()));
         }
 
-        let content = std::fs::read(&path)?;
-        // Check for binary content (null bytes in first 8KB)
-        let check_len = content.len().min(BINARY_DETECTION_BYTES);
-        if content[..check_len].contains(&0) {
+        // Large files are blocking work; keep it off the runtime thread
+        let window =
+            tokio::task::spawn_blocking(move || read_window(&path, offset, limit)).await??;
+        let Some(window) = window else {
             return Ok(ToolResult::error(
                 "Binary file detected. Cannot display binary content.".into(),
             ));
-        }
+        };
 
-        let text =
-            String::from_utf8(content).map_err(|_| anyhow::anyhow!("File is not valid UTF-8"))?;
+        if window.total == 0 {
+            return Ok(ToolResult::success("(empty file)".into()));
+        }
+        if offset > window.total {
+            return Ok(ToolResult::error(format!(
+                "offset {} is past the end of the file ({} lines)",
+                offset, window.total
+            )));
+        }
+        let mut text = window.text;
+        if window.remaining > 0 {
+            text.push_str(&format!(
+                "... (truncated, {} more lines; continue with offset={})",
+                window.remaining, window.next
+            ));
+        }
         Ok(ToolResult::success(text))
     }
 }
@@ -4,14 +4,20 @@
 //! tool and implements rig-core's dynamic tool interface. This allows kaze's
 //! built-in tools to be registered with rig-core's agent builder so that tool
 //! definitions are included in LLM API requests.
+//!
+//! Repeated read-only results are deduplicated per request, newest copy
+//! kept, by [`Superseding`](crate::provider::supersede::Superseding).
 
 use std::pin::Pin;
 use std::sync::Arc;
+use std::time::Instant;
 
 use rig::completion::ToolDefinition as RigToolDefinition;
 use rig::tool::{ToolDyn, ToolError};
+use tracing::Instrument;
 
-use super::Tool;
+use super::{Tool, ToolResult};
+use crate::audit::AuditLog;
 
 /// Bridges a kaze [`Tool`] to rig-core's [`ToolDyn`] trait.
 ///
@@ -22,12 +28,14 @@ use super::Tool;
 ///   and returns the result string
 pub struct RigToolAdapter {
     tool: Arc<dyn Tool>,
+    audit: AuditLog,
 }
 
 impl RigToolAdapter {
-    /// Creates a new adapter wrapping the given kaze tool.
-    pub fn new(tool: Arc<dyn Tool>) -> Self {
-        Self { tool }
+    /// Creates a new adapter wrapping the given kaze tool, recording its
+    /// calls in `audit`.
+    pub fn new(tool: Arc<dyn Tool>, audit: AuditLog) -> Self {
+        Self { tool, audit }
     }
 }
 
@@ -59,7 +67,19 @@ impl ToolDyn for RigToolAdapter {
         Box::pin(async move {
             let input: serde_json::Value =
                 serde_json::from_str(&args).map_err(ToolError::JsonError)?;
-            match self.tool.execute(input).await {
+            let name = self.tool.name();
+            let started = Instant::now();
+            let execute = self
+                .tool
+                .execute(input.clone())
+                .instrument(tracing::info_span!("tool", name));
+            let result = execute.await;
+            let audited = match &result {
+                Ok(result) => result.clone(),
+                Err(e) => ToolResult::error(format!("Error: {}", e)),
+            };
+            self.audit.record(name, &input, &audited, started.elapsed());
+            match result {
                 Ok(result) => Ok(result.content),
                 Err(e) => {
                     // Return tool errors as result strings instead of ToolError.
@@ -0,0 +1,151 @@
+//! Scratchpad tool — a free-form note the model keeps for the session.
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+use super::{Tool, ToolResult};
+use crate::constants::{SCRATCHPAD_CONTEXT_MAX_CHARS, SCRATCHPAD_MAX_CHARS};
+
+/// The note, shared between the tool and the chat engine, which saves it
+/// in the session and shows it to the model at the start of every turn.
+///
+/// It lives outside the message history, so compaction never summarizes
+/// it away.
+#[derive(Debug, Clone, Default)]
+pub struct Scratchpad(Arc<Mutex<String>>);
+
+impl Scratchpad {
+    /// A copy of the note.
+    pub fn text(&self) -> String {
+        self.lock().clone()
+    }
+
+    /// Replaces the note, e.g. with the one saved in a resumed session.
+    pub fn set(&self, text: String) {
+        *self.lock() = text;
+    }
+
+    /// The note as shown in the system prompt: runs of blank lines and
+    /// trailing spaces removed, cut at [`SCRATCHPAD_CONTEXT_MAX_CHARS`].
+    /// `None` when it is empty.
+    pub fn condensed(&self) -> Option<String> {
+        let text = self.lock();
+        let lines: Vec<&str> = text
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty())
+            .collect();
+        if lines.is_empty() {
+            return None;
+        }
+        let joined = lines.join("\n");
+        if joined.chars().count() <= SCRATCHPAD_CONTEXT_MAX_CHARS {
+            return Some(joined);
+        }
+        let cut: String = joined.chars().take(SCRATCHPAD_CONTEXT_MAX_CHARS).collect();
+        Some(format!(
+            "{}\n... (cut short; use the scratchpad tool's read action for the rest)",
+            cut
+        ))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, String> {
+        // A panic mid-update leaves a usable note; keep going with it
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Tool that reads and rewrites the scratchpad.
+pub struct ScratchpadTool {
+    pad: Scratchpad,
+}
+
+impl ScratchpadTool {
+    pub fn new(pad: Scratchpad) -> Self {
+        Self { pad }
+    }
+}
+
+#[derive(Deserialize)]
+struct ScratchpadInput {
+    action: String,
+    #[serde(default)]
+    text: String,
+}
+
+#[async_trait::async_trait]
+impl Tool for ScratchpadTool {
+    fn name(&self) -> &str {
+        "scratchpad"
+    }
+
+    fn description(&self) -> &str {
+        "A private note that persists for the whole session and survives context \
+         compaction: keep plans, findings, decisions, and intermediate results here instead \
+         of relying on earlier messages. Its contents are shown to you at the start of every \
+         turn. write replaces the note, append adds to the end, read returns it, clear \
+         empties it."
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["read", "write", "append", "clear"],
+                    "description": "What to do with the note"
+                },
+                "text": {
+                    "type": "string",
+                    "description": "write, append: the text"
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    async fn execute(&self, input: Value) -> Result<ToolResult> {
+        let input: ScratchpadInput = serde_json::from_value(input)?;
+        let mut pad = self.pad.lock();
+
+        let updated = match input.action.as_str() {
+            "read" => {
+                if pad.is_empty() {
+                    return Ok(ToolResult::success("The scratchpad is empty.".into()));
+                }
+                return Ok(ToolResult::success(pad.clone()));
+            }
+            "write" => input.text,
+            "append" => {
+                if pad.is_empty() || pad.ends_with('\n') {
+                    format!("{}{}", pad, input.text)
+                } else {
+                    format!("{}\n{}", pad, input.text)
+                }
+            }
+            "clear" => String::new(),
+            other => {
+                return Ok(ToolResult::error(format!(
+                    "Unknown scratchpad action '{}'. Supported: read, write, append, clear",
+                    other
+                )))
+            }
+        };
+
+        let len = updated.chars().count();
+        if len > SCRATCHPAD_MAX_CHARS {
+            return Ok(ToolResult::error(format!(
+                "The scratchpad would hold {} characters (limit {}). Condense it with write.",
+                len, SCRATCHPAD_MAX_CHARS
+            )));
+        }
+        *pad = updated;
+        Ok(ToolResult::success(format!(
+            "Scratchpad saved ({} characters).",
+            len
+        )))
+    }
+}
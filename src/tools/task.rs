@@ -0,0 +1,134 @@
+//! Task tool — delegates a focused subtask to a subagent.
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::time::Instant;
+
+use super::{Tool, ToolRegistry, ToolResult};
+use crate::hooks::KazeHook;
+use crate::metrics::TurnMetrics;
+use crate::provider::{ModelSelection, Provider};
+use crate::usage::{format_cost, UsageRecord};
+
+/// Tool that runs a nested agent loop and returns only its final answer.
+///
+/// The subagent starts from an empty conversation with its own copy of
+/// the other built-in tools (but not `task`, so it can't recurse). Its
+/// tool calls go through the same permission hook as the parent's, and
+/// its token usage is recorded in the usage ledger. Only the summary it
+/// returns enters the parent conversation, keeping the main context small.
+pub struct TaskTool {
+    provider: Provider,
+    selection: ModelSelection,
+    tools: ToolRegistry,
+    hook: KazeHook,
+    max_turns: usize,
+}
+
+impl TaskTool {
+    pub fn new(
+        provider: Provider,
+        selection: ModelSelection,
+        tools: ToolRegistry,
+        hook: KazeHook,
+        max_turns: usize,
+    ) -> Self {
+        Self {
+            provider,
+            selection,
+            tools,
+            hook,
+            max_turns,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct TaskInput {
+    description: String,
+    prompt: String,
+}
+
+#[async_trait::async_trait]
+impl Tool for TaskTool {
+    fn name(&self) -> &str {
+        "task"
+    }
+
+    fn description(&self) -> &str {
+        "Delegate a self-contained subtask (e.g. \"find all call sites of X and summarize how \
+         each uses it\") to a subagent with the same tools and a fresh context. It returns only \
+         its final report, so use it for searches and investigations whose intermediate output \
+         you don't need. The subagent can't see this conversation: put everything it needs in \
+         the prompt, including what the report should contain."
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "description": {
+                    "type": "string",
+                    "description": "Short label for the subtask (3-5 words)"
+                },
+                "prompt": {
+                    "type": "string",
+                    "description": "Complete instructions for the subagent"
+                }
+            },
+            "required": ["description", "prompt"]
+        })
+    }
+
+    async fn execute(&self, input: Value) -> Result<ToolResult> {
+        let input: TaskInput = serde_json::from_value(input)?;
+        if input.prompt.trim().is_empty() {
+            return Ok(ToolResult::error("Empty task prompt.".into()));
+        }
+
+        let started = Instant::now();
+        let result = self
+            .provider
+            .prompt_with_tools(
+                crate::constants::TASK_SYSTEM_PROMPT,
+                &input.prompt,
+                &self.tools,
+                self.max_turns,
+                self.hook.clone(),
+            )
+            .await;
+        let (report, usage) = match result {
+            Ok(done) => done,
+            Err(e) => {
+                return Ok(ToolResult::error(format!(
+                    "Subagent for \"{}\" failed: {:#}",
+                    input.description, e
+                )))
+            }
+        };
+
+        let metrics = TurnMetrics {
+            total: started.elapsed(),
+            usage: Some(usage),
+            ..TurnMetrics::default()
+        };
+        let provider = self.selection.provider.as_str();
+        let record = UsageRecord::new(provider, &self.selection.model, None, 0, &metrics);
+        // The figures still reach the model below, so a ledger failure is not fatal
+        let _ = record.append();
+
+        let cost = record
+            .cost_usd
+            .map(|c| format!(", {}", format_cost(c)))
+            .unwrap_or_default();
+        Ok(ToolResult::success(format!(
+            "{}\n\n(subagent {}: {} input / {} output tokens{})",
+            report.trim(),
+            self.selection.model,
+            usage.input_tokens,
+            usage.output_tokens,
+            cost
+        )))
+    }
+}
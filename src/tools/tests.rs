@@ -4,16 +4,25 @@ use serde_json::json;
 #[tokio::test]
 async fn test_registry_with_builtins() {
     let registry = ToolRegistry::with_builtins(PathBuf::from("."));
-    assert_eq!(registry.len(), 6);
+    assert_eq!(registry.len(), 15);
     assert!(!registry.is_empty());
     let defs = registry.definitions();
-    assert_eq!(defs.len(), 6);
+    assert_eq!(defs.len(), 15);
     assert_eq!(defs[0].name, "read_file");
     assert_eq!(defs[1].name, "glob");
-    assert_eq!(defs[2].name, "grep");
-    assert_eq!(defs[3].name, "write_file");
-    assert_eq!(defs[4].name, "edit");
-    assert_eq!(defs[5].name, "bash");
+    assert_eq!(defs[2].name, "list_dir");
+    assert_eq!(defs[3].name, "grep");
+    assert_eq!(defs[4].name, "write_file");
+    assert_eq!(defs[5].name, "edit");
+    assert_eq!(defs[6].name, "multi_edit");
+    assert_eq!(defs[7].name, "apply_patch");
+    assert_eq!(defs[8].name, "git");
+    assert_eq!(defs[9].name, "bash");
+    assert_eq!(defs[10].name, "help_lookup");
+    assert_eq!(defs[11].name, "web_fetch");
+    assert_eq!(defs[12].name, "web_search");
+    assert_eq!(defs[13].name, "todo");
+    assert_eq!(defs[14].name, "scratchpad");
 }
 
 #[tokio::test]
@@ -45,6 +54,39 @@ async fn test_read_file_path_escape() {
     assert!(result.is_err());
 }
 
+#[tokio::test]
+async fn test_read_file_offset_and_limit() {
+    let dir = std::env::temp_dir().join(format!("kaze_test_read_window_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let text: String = (1..=10).map(|i| format!("line {}\n", i)).collect();
+    std::fs::write(dir.join("ten.txt"), text).unwrap();
+
+    let registry = ToolRegistry::with_builtins(dir.clone());
+    let result = registry
+        .execute(
+            "read_file",
+            json!({"path": "ten.txt", "offset": 4, "limit": 3}),
+        )
+        .await
+        .unwrap();
+    assert!(!result.is_error);
+    let lines: Vec<&str> = result.content.lines().collect();
+    assert_eq!(lines[0], "     4\tline 4");
+    assert_eq!(lines[2], "     6\tline 6");
+    assert_eq!(
+        lines[3],
+        "... (truncated, 4 more lines; continue with offset=7)"
+    );
+
+    let past = registry
+        .execute("read_file", json!({"path": "ten.txt", "offset": 11}))
+        .await
+        .unwrap();
+    assert!(past.is_error);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
 #[tokio::test]
 async fn test_glob_rs_files() {
     let registry = ToolRegistry::with_builtins(PathBuf::from("."));
@@ -66,6 +108,36 @@ async fn test_glob_no_matches() {
     assert!(result.content.contains("No files matched"));
 }
 
+#[tokio::test]
+async fn test_list_dir_root() {
+    let registry = ToolRegistry::with_builtins(PathBuf::from("."));
+    let result = registry.execute("list_dir", json!({})).await.unwrap();
+    assert!(!result.is_error);
+    assert!(result.content.contains("src/"));
+    assert!(result.content.contains("Cargo.toml"));
+    // Hidden entries and nested files are left out by default
+    assert!(!result.content.contains(".git/"));
+    assert!(!result.content.contains("main.rs"));
+}
+
+#[tokio::test]
+async fn test_list_dir_depth_and_hidden() {
+    let registry = ToolRegistry::with_builtins(PathBuf::from("."));
+    let result = registry
+        .execute("list_dir", json!({"depth": 2, "hidden": true}))
+        .await
+        .unwrap();
+    assert!(result.content.contains(".git/"));
+    assert!(result.content.contains("src/main.rs"));
+}
+
+#[tokio::test]
+async fn test_list_dir_path_escape() {
+    let registry = ToolRegistry::with_builtins(PathBuf::from("."));
+    let result = registry.execute("list_dir", json!({"path": "../.."})).await;
+    assert!(result.is_err());
+}
+
 #[tokio::test]
 async fn test_unknown_tool() {
     let registry = ToolRegistry::with_builtins(PathBuf::from("."));
@@ -106,6 +178,29 @@ async fn test_grep_invalid_regex() {
     assert!(result.content.contains("Invalid regex"));
 }
 
+#[tokio::test]
+async fn test_grep_parallel_results_in_walk_order() {
+    let dir = std::env::temp_dir().join(format!("kaze_test_grep_order_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    // More files than one parallel batch, two matches each
+    for i in 0..300 {
+        std::fs::write(dir.join(format!("f{:03}.txt", i)), "needle\nhay\nneedle\n").unwrap();
+    }
+
+    let registry = ToolRegistry::with_builtins(dir.clone());
+    let result = registry
+        .execute("grep", json!({"pattern": "needle"}))
+        .await
+        .unwrap();
+    let lines: Vec<&str> = result.content.lines().collect();
+    assert_eq!(lines[0], "f000.txt:1:needle");
+    assert_eq!(lines[1], "f000.txt:3:needle");
+    assert_eq!(lines[49], "f024.txt:3:needle");
+    assert!(result.content.contains("truncated at 50 matches"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
 #[tokio::test]
 async fn test_write_file_basic() {
     let dir = std::env::temp_dir().join(format!("kaze_test_write_{}", std::process::id()));
@@ -278,6 +373,181 @@ async fn test_edit_multiline() {
     std::fs::remove_dir_all(&dir).unwrap();
 }
 
+#[tokio::test]
+async fn test_multi_edit_across_files() {
+    let dir = std::env::temp_dir().join(format!("kaze_test_multi_edit_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("a.txt"), "one two").unwrap();
+    std::fs::write(dir.join("b.txt"), "three").unwrap();
+
+    let registry = ToolRegistry::with_builtins(dir.clone());
+    let result = registry
+        .execute(
+            "multi_edit",
+            json!({"edits": [
+                {"path": "a.txt", "old_text": "one", "new_text": "1"},
+                {"path": "b.txt", "old_text": "three", "new_text": "3"},
+                {"path": "a.txt", "old_text": "1 two", "new_text": "1 2"},
+            ]}),
+        )
+        .await
+        .unwrap();
+    assert!(!result.is_error);
+    assert!(result.content.starts_with("Edited 2 files (3 edits)"));
+    assert_eq!(std::fs::read_to_string(dir.join("a.txt")).unwrap(), "1 2");
+    assert_eq!(std::fs::read_to_string(dir.join("b.txt")).unwrap(), "3");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_multi_edit_all_or_nothing() {
+    let dir = std::env::temp_dir().join(format!("kaze_test_multi_atomic_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("a.txt"), "alpha").unwrap();
+    std::fs::write(dir.join("b.txt"), "beta").unwrap();
+
+    let registry = ToolRegistry::with_builtins(dir.clone());
+    let result = registry
+        .execute(
+            "multi_edit",
+            json!({"edits": [
+                {"path": "a.txt", "old_text": "alpha", "new_text": "ALPHA"},
+                {"path": "b.txt", "old_text": "missing", "new_text": "x"},
+            ]}),
+        )
+        .await
+        .unwrap();
+    assert!(result.is_error);
+    assert!(result.content.contains("Edit 2 of 2"));
+    assert_eq!(std::fs::read_to_string(dir.join("a.txt")).unwrap(), "alpha");
+    assert_eq!(std::fs::read_to_string(dir.join("b.txt")).unwrap(), "beta");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_apply_patch_with_offset_and_new_file() {
+    let dir = std::env::temp_dir().join(format!("kaze_test_apply_patch_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    // Two extra lines at the top shift the hunk away from its stated line
+    std::fs::write(dir.join("a.txt"), "x\ny\none\ntwo\nthree\n").unwrap();
+
+    let patch = "--- a/a.txt\n+++ b/a.txt\n@@ -1,3 +1,3 @@\n one\n-two\n+TWO\n three\n\
+                 --- /dev/null\n+++ b/sub/new.txt\n@@ -0,0 +1,2 @@\n+hello\n+world\n";
+    let registry = ToolRegistry::with_builtins(dir.clone());
+    let result = registry
+        .execute("apply_patch", json!({"patch": patch}))
+        .await
+        .unwrap();
+    assert!(!result.is_error, "{}", result.content);
+    assert!(result.content.starts_with("Patched 2 files (2 hunks)"));
+    assert!(result.content.contains("offset +2"));
+    assert_eq!(
+        std::fs::read_to_string(dir.join("a.txt")).unwrap(),
+        "x\ny\none\nTWO\nthree\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(dir.join("sub/new.txt")).unwrap(),
+        "hello\nworld\n"
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_apply_patch_renames_and_deletes() {
+    let dir = std::env::temp_dir().join(format!("kaze_test_patch_rename_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("old.txt"), "one\ntwo\n").unwrap();
+    std::fs::write(dir.join("gone.txt"), "bye\n").unwrap();
+
+    let patch = "--- a/old.txt\n+++ b/moved/new.txt\n@@ -1,2 +1,2 @@\n one\n-two\n+TWO\n\
+                 --- a/gone.txt\n+++ /dev/null\n@@ -1 +0,0 @@\n-bye\n";
+    let registry = ToolRegistry::with_builtins(dir.clone());
+    let result = registry
+        .execute("apply_patch", json!({"patch": patch}))
+        .await
+        .unwrap();
+    assert!(!result.is_error, "{}", result.content);
+    assert_eq!(
+        std::fs::read_to_string(dir.join("moved/new.txt")).unwrap(),
+        "one\nTWO\n"
+    );
+    assert!(!dir.join("old.txt").exists());
+    assert!(!dir.join("gone.txt").exists());
+    assert!(!dir.join("moved/new.txt.kaze-multi-edit").exists());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_apply_patch_reports_failed_hunk() {
+    let dir = std::env::temp_dir().join(format!("kaze_test_patch_fail_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("a.txt"), "alpha\nbeta\n").unwrap();
+    std::fs::write(dir.join("b.txt"), "gamma\n").unwrap();
+
+    let patch = "--- a/a.txt\n+++ b/a.txt\n@@ -1,2 +1,2 @@\n alpha\n-beta\n+BETA\n\
+                 --- a/b.txt\n+++ b/b.txt\n@@ -1 +1 @@\n-delta\n+DELTA\n";
+    let registry = ToolRegistry::with_builtins(dir.clone());
+    let result = registry
+        .execute("apply_patch", json!({"patch": patch}))
+        .await
+        .unwrap();
+    assert!(result.is_error);
+    assert!(result.content.contains("b.txt: hunk 1"));
+    assert!(!result.content.contains("a.txt: hunk"));
+    assert_eq!(
+        std::fs::read_to_string(dir.join("a.txt")).unwrap(),
+        "alpha\nbeta\n"
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_apply_patch_keeps_crlf_and_refuses_clobbering() {
+    let dir = std::env::temp_dir().join(format!("kaze_test_patch_crlf_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("dos.txt"), "one\r\ntwo\r\nthree\r\n").unwrap();
+    std::fs::write(dir.join("a.txt"), "a\n").unwrap();
+    std::fs::write(dir.join("b.txt"), "b\n").unwrap();
+    let registry = ToolRegistry::with_builtins(dir.clone());
+    let apply = |patch: &str| registry.execute("apply_patch", json!({ "patch": patch }));
+
+    let patch = "--- a/dos.txt\n+++ b/dos.txt\n@@ -1,3 +1,4 @@\n one\n-two\n+TWO\n+2\n three\n";
+    let result = apply(patch).await.unwrap();
+    assert!(!result.is_error, "{}", result.content);
+    assert_eq!(
+        std::fs::read_to_string(dir.join("dos.txt")).unwrap(),
+        "one\r\nTWO\r\n2\r\nthree\r\n"
+    );
+
+    let rename = "--- a/a.txt\n+++ b/b.txt\n@@ -1 +1 @@\n-a\n+A\n";
+    let result = apply(rename).await.unwrap();
+    assert!(result.is_error);
+    assert!(
+        result.content.contains("already exists"),
+        "{}",
+        result.content
+    );
+
+    let twice = "--- a/a.txt\n+++ b/a.txt\n@@ -1 +1 @@\n-a\n+A\n\
+                 --- a/a.txt\n+++ b/a.txt\n@@ -1 +1,2 @@\n a\n+more\n";
+    let result = apply(twice).await.unwrap();
+    assert!(result.is_error);
+    assert!(
+        result.content.contains("more than one section"),
+        "{}",
+        result.content
+    );
+    assert_eq!(std::fs::read_to_string(dir.join("a.txt")).unwrap(), "a\n");
+    assert_eq!(std::fs::read_to_string(dir.join("b.txt")).unwrap(), "b\n");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
 #[tokio::test]
 async fn test_bash_echo() {
     let registry = ToolRegistry::with_builtins(PathBuf::from("."));
@@ -312,6 +582,28 @@ async fn test_bash_timeout() {
     assert!(result.content.contains("timed out"));
 }
 
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_bash_timeout_kills_background_jobs() {
+    let dir = std::env::temp_dir().join(format!("kaze_test_bash_group_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let registry = ToolRegistry::with_builtins(dir.clone());
+    let command = "sleep 30 & echo $! > job.pid; wait";
+    let result = registry
+        .execute("bash", json!({"command": command, "timeout": 1}))
+        .await
+        .unwrap();
+    assert!(result.content.contains("timed out"));
+
+    // Gone, or a zombie waiting for init to reap it
+    let pid = std::fs::read_to_string(dir.join("job.pid")).unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid.trim())).unwrap_or_default();
+    assert!(stat.is_empty() || stat.contains(") Z "), "{}", stat);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
 #[tokio::test]
 async fn test_bash_stderr() {
     let registry = ToolRegistry::with_builtins(PathBuf::from("."));
@@ -321,3 +613,629 @@ async fn test_bash_stderr() {
         .unwrap();
     assert!(result.content.contains("--- stderr ---"));
 }
+
+#[test]
+fn test_web_fetch_html_to_text() {
+    let html = "<html><head><title>Docs &amp; Guides</title><style>p { color: red }</style></head>\
+                <body><h2>Install</h2><p>Run   the <a href=\"/setup\">setup</a> script.</p>\
+                <ul><li>one</li><li>two</li></ul><pre>fn main() {\n    run();\n}</pre>\
+                <script>alert(1)</script></body></html>";
+    let text = web_fetch::html_to_text(html);
+    assert_eq!(
+        text,
+        "# Docs & Guides\n\n## Install\n\nRun the [setup](/setup) script.\n\n- one\n- two\n\n\
+         ```\nfn main() {\n    run();\n}\n```"
+    );
+}
+
+#[tokio::test]
+async fn test_web_fetch_checks_cross_host_redirects() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut buf = vec![0; 4096];
+                while let Ok(n) = socket.read(&mut buf).await {
+                    if n == 0 {
+                        break;
+                    }
+                    let request = String::from_utf8_lossy(&buf[..n]);
+                    // Same host first, then off to another host name
+                    let location = if request.starts_with("GET /start ") {
+                        "/same".to_string()
+                    } else {
+                        format!("http://localhost:{}/other", port)
+                    };
+                    let response = format!(
+                        "HTTP/1.1 302 Found\r\nLocation: {}\r\nContent-Length: 0\r\n\r\n",
+                        location
+                    );
+                    if socket.write_all(response.as_bytes()).await.is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    });
+
+    let registry = ToolRegistry::with_builtins(PathBuf::from("."));
+    let url = format!("http://127.0.0.1:{}/start", port);
+    let result = registry
+        .execute("web_fetch", json!({ "url": url }))
+        .await
+        .unwrap();
+    assert!(result.is_error);
+    assert!(
+        result.content.starts_with(&format!(
+            "http://127.0.0.1:{}/same redirects to http://localhost:{}/other",
+            port, port
+        )),
+        "{}",
+        result.content
+    );
+}
+
+#[test]
+fn test_web_search_parses_duckduckgo_results() {
+    let html = r#"<div class="result results_links"><h2 class="result__title">
+        <a rel="nofollow" class="result__a" href="//duckduckgo.com/l/?uddg=https%3A%2F%2Fdocs.rs%2Fserde&amp;rut=abc">serde &amp; <b>docs</b></a></h2>
+        <a class="result__snippet" href="x">A <b>serialization</b> framework.</a></div>
+        <div class="result"><a class="result__a" href="https://duckduckgo.com/y.js?ad=1">Ad</a></div>"#;
+    let results = web_search::parse_duckduckgo(html);
+    assert_eq!(
+        results,
+        vec![web_search::SearchResult {
+            title: "serde & docs".into(),
+            url: "https://docs.rs/serde".into(),
+            snippet: "A serialization framework.".into(),
+        }]
+    );
+}
+
+#[tokio::test]
+async fn test_environment_block_reports_git_state() {
+    use crate::chat::environment::{git_line, Environment};
+
+    let dir = std::env::temp_dir().join(format!("kaze_test_environment_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    assert_eq!(git_line(&dir).await, None);
+
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(&dir)
+            .output()
+            .unwrap()
+    };
+    git(&["init", "-q"]);
+    git(&["symbolic-ref", "HEAD", "refs/heads/trunk"]);
+    assert_eq!(
+        git_line(&dir).await.as_deref(),
+        Some("git: branch trunk, clean")
+    );
+    std::fs::write(dir.join("a.txt"), "hello\n").unwrap();
+    let line = "git: branch trunk, 1 changed file";
+    assert_eq!(git_line(&dir).await.as_deref(), Some(line));
+
+    let mut environment = Environment::new(dir.clone());
+    assert_eq!(
+        environment.block(),
+        "The environment you are working in:\n<environment>\n\n</environment>"
+    );
+    environment.refresh().await;
+    let block = environment.block();
+    assert!(block.contains(&format!("\nos: {} (", std::env::consts::OS)));
+    assert!(block.contains("\nshell: "));
+    assert!(block.ends_with(&format!("\n{}\n</environment>", line)));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_git_commit_and_log() {
+    let dir = std::env::temp_dir().join(format!("kaze_test_git_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(&dir)
+            .output()
+            .unwrap()
+    };
+    git(&["init", "-q"]);
+    git(&["config", "user.name", "Test"]);
+    git(&["config", "user.email", "test@example.com"]);
+    std::fs::write(dir.join("a.txt"), "hello\n").unwrap();
+
+    let registry = ToolRegistry::with_builtins(dir.clone());
+    let add = registry
+        .execute("git", json!({"command": "add", "paths": ["a.txt"]}))
+        .await
+        .unwrap();
+    assert!(!add.is_error, "{}", add.content);
+    let commit = registry
+        .execute("git", json!({"command": "commit", "message": "Add a.txt"}))
+        .await
+        .unwrap();
+    assert!(!commit.is_error, "{}", commit.content);
+    let log = registry
+        .execute("git", json!({"command": "log"}))
+        .await
+        .unwrap();
+    assert!(log.content.contains("Add a.txt"));
+
+    // Option-like revisions are refused before git runs
+    let diff = registry
+        .execute(
+            "git",
+            json!({"command": "diff", "revision": "--output=/tmp/x"}),
+        )
+        .await
+        .unwrap();
+    assert!(diff.is_error);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_todo_add_check_off_and_remove() {
+    let registry = ToolRegistry::with_builtins(PathBuf::from("."));
+    registry
+        .execute(
+            "todo",
+            json!({"action": "add", "items": ["find call sites", "update them", "run tests"]}),
+        )
+        .await
+        .unwrap();
+    let result = registry
+        .execute("todo", json!({"action": "done", "ids": [1]}))
+        .await
+        .unwrap();
+    assert!(!result.is_error);
+    assert!(result.content.starts_with("1 of 3 done"));
+    assert!(result.content.contains("[x] 1. find call sites"));
+
+    registry
+        .execute("todo", json!({"action": "remove", "ids": [2]}))
+        .await
+        .unwrap();
+    let items = registry.todos().items();
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[1].text, "run tests");
+
+    let result = registry
+        .execute("todo", json!({"action": "done", "ids": [5]}))
+        .await
+        .unwrap();
+    assert!(result.is_error);
+}
+
+#[tokio::test]
+async fn test_scratchpad_write_append_and_condense() {
+    let registry = ToolRegistry::with_builtins(PathBuf::from("."));
+    registry
+        .execute(
+            "scratchpad",
+            json!({"action": "write", "text": "plan: split parser\n\n\n"}),
+        )
+        .await
+        .unwrap();
+    let result = registry
+        .execute(
+            "scratchpad",
+            json!({"action": "append", "text": "found: 3 callers   "}),
+        )
+        .await
+        .unwrap();
+    assert!(!result.is_error);
+    assert_eq!(
+        registry.scratchpad().condensed().as_deref(),
+        Some("plan: split parser\nfound: 3 callers")
+    );
+
+    let too_long = "x".repeat(crate::constants::SCRATCHPAD_MAX_CHARS + 1);
+    let result = registry
+        .execute("scratchpad", json!({"action": "write", "text": too_long}))
+        .await
+        .unwrap();
+    assert!(result.is_error);
+    assert!(registry.scratchpad().text().starts_with("plan:"));
+
+    registry
+        .execute("scratchpad", json!({"action": "clear"}))
+        .await
+        .unwrap();
+    assert_eq!(registry.scratchpad().condensed(), None);
+}
+
+#[test]
+fn test_repeated_read_keeps_only_the_newest_copy() {
+    use crate::provider::supersede::supersede_results;
+    use rig::completion::{AssistantContent, Message as RigMessage};
+    use rig::message::{ToolResultContent, UserContent};
+    use rig::OneOrMany;
+
+    let call = |id: &str, path: &str| RigMessage::Assistant {
+        id: None,
+        content: OneOrMany::one(AssistantContent::tool_call(
+            id,
+            "read_file",
+            json!({"path": path}),
+        )),
+    };
+    let big = "line of text\n".repeat(100);
+    let other = "other text\n".repeat(100);
+    let mut history = vec![
+        call("1", "big.txt"),
+        RigMessage::tool_result("1", big.clone()),
+        call("2", "big.txt"),
+        RigMessage::tool_result("2", big.clone()),
+        call("3", "small.txt"),
+        RigMessage::tool_result("3", "tiny"),
+        call("4", "small.txt"),
+        RigMessage::tool_result("4", "tiny"),
+        call("5", "big.txt"),
+        RigMessage::tool_result("5", other.clone()),
+        call("6", "big.txt"),
+        RigMessage::tool_result("6", big.clone()),
+    ];
+    assert_eq!(supersede_results(history.iter_mut()), 2);
+
+    let results: Vec<String> = history
+        .iter()
+        .filter_map(|message| match message {
+            RigMessage::User { content } => match content.first() {
+                UserContent::ToolResult(result) => match result.content.first() {
+                    ToolResultContent::Text(text) => Some(text.text),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+    assert!(results[0].starts_with("(Superseded"));
+    assert!(results[1].starts_with("(Superseded"));
+    // Short output and changed output are left alone, the newest in full
+    assert_eq!(results[2..4], ["tiny", "tiny"]);
+    assert_eq!(results[4], other);
+    assert_eq!(results[5], big);
+
+    // Once stubbed, a history stays the same on the next request
+    assert_eq!(supersede_results(history.iter_mut()), 0);
+}
+
+/// A scratch project for an agent loop test, with a registry and a hook
+/// that lets `write_file` run unasked.
+fn mock_agent_setup(name: &str) -> (PathBuf, ToolRegistry, crate::hooks::KazeHook) {
+    use crate::permissions::{Permission, PermissionConfig, PermissionManager};
+
+    let dir = std::env::temp_dir().join(format!("kaze_test_{}_{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let registry = ToolRegistry::with_builtins(dir.clone());
+    let mut permissions = PermissionConfig::default();
+    permissions
+        .tools
+        .insert("write_file".into(), Permission::Allow);
+    let hook = crate::hooks::KazeHook::new(
+        std::sync::Arc::new(PermissionManager::new(permissions).unattended(false)),
+        dir.clone(),
+    );
+    (dir, registry, hook)
+}
+
+#[tokio::test]
+async fn test_agent_loop_runs_mock_tool_calls() {
+    use crate::provider::{MockReply, MockScript, Provider};
+
+    let (dir, registry, hook) = mock_agent_setup("mock_agent");
+    let script = MockScript::new([
+        MockReply::tool("write_file", json!({"path": "a.txt", "content": "hi\n"})),
+        MockReply::text("done"),
+    ]);
+    let provider = Provider::mock(&crate::config::Config::default(), script.clone());
+    let mut messages = vec![crate::message::Message::user("write a.txt")];
+    let mut renderer = crate::output::RecordingRenderer::new();
+
+    let answer =
+        crate::agent::agent_loop(&provider, &mut messages, &registry, &mut renderer, 5, hook)
+            .await
+            .unwrap();
+    assert_eq!(answer, "done");
+    assert_eq!(std::fs::read_to_string(dir.join("a.txt")).unwrap(), "hi\n");
+    assert_eq!(script.requests().len(), 2);
+    assert_eq!(script.remaining(), 0);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_cassette_replays_recorded_calls() {
+    use crate::provider::{load_cassette, Provider};
+
+    let (dir, registry, hook) = mock_agent_setup("cassette");
+    let cassette = dir.join("cassette.jsonl");
+    std::fs::write(
+        &cassette,
+        concat!(
+            r#"{"tool_calls":[{"name":"write_file","args":{"path":"a.txt","content":"hi\n"}}]}"#,
+            "\n",
+            r#"{"chunks":["do","ne"]}"#,
+            "\n",
+            r#"{"chunks":["par"],"error":"overloaded"}"#,
+            "\n",
+        ),
+    )
+    .unwrap();
+    let script = load_cassette(&cassette).unwrap();
+    let provider = Provider::mock(&crate::config::Config::default(), script.clone());
+    let mut messages = vec![crate::message::Message::user("write a.txt")];
+    let mut renderer = crate::output::RecordingRenderer::new();
+    let answer = crate::agent::agent_loop(
+        &provider,
+        &mut messages,
+        &registry,
+        &mut renderer,
+        5,
+        hook.clone(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(answer, "done");
+    assert_eq!(std::fs::read_to_string(dir.join("a.txt")).unwrap(), "hi\n");
+    assert_eq!(script.remaining(), 1);
+
+    // A recorded stream error is replayed as one
+    messages.push(crate::message::Message::user("again"));
+    let err = crate::agent::agent_loop(
+        &provider,
+        &mut messages,
+        &registry,
+        &mut renderer,
+        5,
+        hook.clone(),
+    )
+    .await
+    .unwrap_err();
+    assert!(err.to_string().contains("overloaded"), "{}", err);
+
+    // A replay past the end of the cassette fails rather than echoing
+    let err = crate::agent::agent_loop(
+        &provider,
+        &mut messages,
+        &registry,
+        &mut renderer,
+        5,
+        hook.clone(),
+    )
+    .await
+    .unwrap_err();
+    assert!(err.to_string().contains("no response left"), "{}", err);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_refused_tools_are_dropped_and_the_request_retried() {
+    use crate::provider::{load_cassette, Provider};
+
+    let (dir, registry, hook) = mock_agent_setup("tools_refused");
+    let cassette = dir.join("cassette.jsonl");
+    std::fs::write(
+        &cassette,
+        concat!(
+            r#"{"error":"registry.ollama.ai/library/gemma:2b does not support tools"}"#,
+            "\n",
+            r#"{"chunks":["hi"]}"#,
+            "\n",
+        ),
+    )
+    .unwrap();
+    let script = load_cassette(&cassette).unwrap();
+    let provider = Provider::mock(&crate::config::Config::default(), script.clone());
+    assert!(provider.supports_tools());
+    let mut messages = vec![crate::message::Message::user("hello")];
+    let mut renderer = crate::output::RecordingRenderer::new();
+
+    let answer =
+        crate::agent::agent_loop(&provider, &mut messages, &registry, &mut renderer, 5, hook)
+            .await
+            .unwrap();
+    assert_eq!(answer, "hi");
+    assert_eq!(script.remaining(), 0);
+    assert!(!provider.supports_tools());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_oversized_write_asks_despite_allow() {
+    use crate::permissions::{Permission, PermissionConfig, PermissionManager};
+
+    let mut permissions = PermissionConfig::default();
+    permissions
+        .tools
+        .insert("write_file".into(), Permission::Allow);
+    let manager = PermissionManager::new(permissions).with_write_ceiling(Some(10));
+    manager.set_session_override("write_file", "{}", Permission::Allow);
+    let small = json!({"path": "a.txt", "content": "short"}).to_string();
+    let large = json!({"path": "a.txt", "content": "x".repeat(11)}).to_string();
+
+    assert_eq!(manager.check("write_file", &small), Permission::Allow);
+    assert_eq!(manager.check("write_file", &large), Permission::Ask);
+    assert_eq!(
+        manager.oversized_write("write_file", &large),
+        Some((11, 10))
+    );
+    assert_eq!(manager.oversized_write("edit", &large), None);
+}
+
+#[test]
+fn test_read_only_denies_writing_tools() {
+    use crate::permissions::{Permission, PermissionConfig, PermissionManager};
+
+    let mut permissions = PermissionConfig {
+        read_only: Some(true),
+        ..PermissionConfig::default()
+    };
+    for tool in ["write_file", "bash", "git", "read_file"] {
+        permissions.tools.insert(tool.into(), Permission::Allow);
+    }
+    let manager = PermissionManager::new(permissions);
+    manager.set_session_override("bash", "{}", Permission::Allow);
+
+    assert_eq!(manager.check("write_file", "{}"), Permission::Deny);
+    assert_eq!(
+        manager.check("bash", r#"{"command":"ls"}"#),
+        Permission::Deny
+    );
+    assert_eq!(
+        manager.check("git", r#"{"command":"commit"}"#),
+        Permission::Deny
+    );
+    assert_eq!(
+        manager.check("git", r#"{"command":"log"}"#),
+        Permission::Allow
+    );
+    assert_eq!(manager.check("read_file", "{}"), Permission::Allow);
+}
+
+#[test]
+fn test_always_for_git_covers_only_that_subcommand() {
+    use crate::permissions::{Permission, PermissionConfig, PermissionManager};
+
+    let manager = PermissionManager::new(PermissionConfig::default());
+    let commit = r#"{"command":"commit","message":"m"}"#;
+    manager.set_session_override("git", commit, Permission::Allow);
+
+    assert_eq!(manager.check("git", commit), Permission::Allow);
+    assert_eq!(
+        manager.check("git", r#"{"command":"add"}"#),
+        Permission::Ask
+    );
+    assert_eq!(
+        manager.check("git", r#"{"command":"push"}"#),
+        Permission::Deny
+    );
+
+    manager.set_session_override("git", r#"{"command":"push"}"#, Permission::Allow);
+    assert_eq!(
+        manager.check("git", r#"{"command":"push"}"#),
+        Permission::Allow
+    );
+    assert_eq!(
+        manager.check("git", r#"{"command":"push","force":true}"#),
+        Permission::Deny
+    );
+}
+
+#[test]
+fn test_auto_approve_allows_asks_but_keeps_denies() {
+    use crate::permissions::{Permission, PermissionConfig, PermissionManager};
+
+    let mut permissions = PermissionConfig::default();
+    permissions
+        .tools
+        .insert("write_file".into(), Permission::Ask);
+    permissions
+        .tools
+        .insert("edit_file".into(), Permission::Deny);
+    let manager = PermissionManager::new(permissions.clone()).with_write_ceiling(Some(4));
+    let push = r#"{"command":"push"}"#;
+    let big_write = r#"{"path":"a","content":"too long"}"#;
+
+    assert_eq!(manager.check("write_file", "{}"), Permission::Ask);
+    manager.set_auto_approve(true);
+    assert_eq!(manager.check("write_file", "{}"), Permission::Allow);
+    assert_eq!(manager.check("write_file", big_write), Permission::Allow);
+    assert_eq!(manager.check("edit_file", "{}"), Permission::Deny);
+    assert_eq!(manager.check("git", push), Permission::Deny);
+    manager.set_auto_approve(false);
+    assert_eq!(manager.check("write_file", big_write), Permission::Ask);
+
+    permissions.read_only = Some(true);
+    let read_only = PermissionManager::new(permissions);
+    read_only.set_auto_approve(true);
+    assert_eq!(read_only.check("write_file", "{}"), Permission::Deny);
+    assert_eq!(
+        read_only.check("bash", r#"{"command":"ls"}"#),
+        Permission::Deny
+    );
+}
+
+#[test]
+fn test_help_lookup_cleans_man_output() {
+    use help_lookup::{clean_help_text, is_valid_name};
+    assert!(is_valid_name("git-commit"));
+    assert!(is_valid_name("g++"));
+    assert!(!is_valid_name("--version"));
+    assert!(!is_valid_name("ls; rm -rf ."));
+    assert!(!is_valid_name("../bin/ls"));
+
+    let raw = "N\x08NA\x08AM\x08ME\x08E\n\n\n\n  \x1b[1mls\x1b[0m - list   \n\n";
+    assert_eq!(clean_help_text(raw), "NAME\n\n  ls - list");
+}
+
+#[tokio::test]
+async fn test_help_lookup_refuses_wrappers_and_defaults_to_allow() {
+    use crate::permissions::{default_permission, Permission, PermissionConfig, PermissionManager};
+    use help_lookup::may_run_for_help;
+
+    assert!(may_run_for_help("git"));
+    assert!(may_run_for_help("pythonic"));
+    for wrapper in [
+        "sh",
+        "env",
+        "python3",
+        "python3.12",
+        "xargs",
+        "sudo",
+        "shutdown",
+    ] {
+        assert!(!may_run_for_help(wrapper), "{}", wrapper);
+    }
+    let registry = ToolRegistry::with_builtins(PathBuf::from("."));
+    let result = registry
+        .execute(
+            "help_lookup",
+            json!({"command": "sh", "subcommand": "x", "source": "help"}),
+        )
+        .await
+        .unwrap();
+    assert!(result.is_error);
+    assert!(result.content.contains("only looked up by its man page"));
+
+    // Allowed when the config says nothing, unlike other tools
+    assert_eq!(default_permission("help_lookup"), Permission::Allow);
+    let manager = PermissionManager::new(PermissionConfig::default());
+    assert_eq!(manager.check("help_lookup", "{}"), Permission::Allow);
+    assert_eq!(manager.check("bash", "{}"), Permission::Ask);
+}
+
+#[test]
+fn test_audit_record_shortens_args() {
+    use crate::audit::AuditRecord;
+    let content = "x".repeat(5_000);
+    let args = json!({ "path": "big.txt", "content": content });
+    let result = ToolResult::success("Wrote big.txt".into());
+    let record = AuditRecord::new(None, "write_file", &args, &result, Default::default());
+    assert_eq!(record.args["path"], "big.txt");
+    let kept = record.args["content"].as_str().unwrap();
+    assert!(kept.len() < 300 && kept.ends_with("(5000 bytes)"));
+    assert_eq!(record.status, "ok");
+
+    let failed = ToolResult::error("no such file\nExit code: 2".into());
+    let record = AuditRecord::new(
+        Some("s1".into()),
+        "bash",
+        &json!({ "command": "ls nope" }),
+        &failed,
+        Default::default(),
+    );
+    assert_eq!(
+        (record.status.as_str(), record.exit_code),
+        ("error", Some(2))
+    );
+}
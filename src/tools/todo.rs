@@ -0,0 +1,174 @@
+//! Todo tool — a plan the model keeps for multi-step tasks.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+use super::{Tool, ToolResult};
+
+/// One plan item.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TodoItem {
+    pub text: String,
+    #[serde(default)]
+    pub done: bool,
+}
+
+/// The plan, shared between the tool, the chat engine (which persists it
+/// in the session), and the frontends that display it.
+#[derive(Debug, Clone, Default)]
+pub struct TodoList(Arc<Mutex<Vec<TodoItem>>>);
+
+impl TodoList {
+    /// A copy of the current items.
+    pub fn items(&self) -> Vec<TodoItem> {
+        self.lock().clone()
+    }
+
+    /// Replaces the items, e.g. with those saved in a resumed session.
+    pub fn set(&self, items: Vec<TodoItem>) {
+        *self.lock() = items;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<TodoItem>> {
+        // A panic mid-update leaves a usable list; keep going with it
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Formats items as a numbered checklist with a progress line.
+pub fn format_todos(items: &[TodoItem]) -> String {
+    let done = items.iter().filter(|i| i.done).count();
+    let mut out = format!("{} of {} done", done, items.len());
+    for (i, item) in items.iter().enumerate() {
+        let mark = if item.done { "x" } else { " " };
+        out.push_str(&format!("\n[{}] {}. {}", mark, i + 1, item.text));
+    }
+    out
+}
+
+/// Tool that creates, checks off, and removes plan items.
+pub struct TodoTool {
+    todos: TodoList,
+}
+
+impl TodoTool {
+    pub fn new(todos: TodoList) -> Self {
+        Self { todos }
+    }
+}
+
+#[derive(Deserialize)]
+struct TodoInput {
+    action: String,
+    #[serde(default)]
+    items: Vec<String>,
+    #[serde(default)]
+    ids: Vec<usize>,
+}
+
+#[async_trait::async_trait]
+impl Tool for TodoTool {
+    fn name(&self) -> &str {
+        "todo"
+    }
+
+    fn description(&self) -> &str {
+        "Track a plan for multi-step tasks: add items, mark them done (or not done), \
+         remove them, clear the list, or list it. Items are referred to by their 1-based \
+         number. Add the plan before starting and check items off as you finish them; \
+         the user sees the list."
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["add", "done", "undone", "remove", "clear", "list"],
+                    "description": "What to do with the list"
+                },
+                "items": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "add: the new items, appended in order"
+                },
+                "ids": {
+                    "type": "array",
+                    "items": { "type": "integer" },
+                    "description": "done, undone, remove: item numbers"
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    async fn execute(&self, input: Value) -> Result<ToolResult> {
+        let input: TodoInput = serde_json::from_value(input)?;
+        let mut items = self.todos.lock();
+
+        if matches!(input.action.as_str(), "done" | "undone" | "remove") {
+            if input.ids.is_empty() {
+                return Ok(ToolResult::error(format!(
+                    "{} needs the item numbers in ids",
+                    input.action
+                )));
+            }
+            if let Some(bad) = input.ids.iter().find(|&&id| id == 0 || id > items.len()) {
+                return Ok(ToolResult::error(format!(
+                    "No item {} (the list has {})",
+                    bad,
+                    items.len()
+                )));
+            }
+        }
+
+        match input.action.as_str() {
+            "add" => {
+                let new: Vec<TodoItem> = input
+                    .items
+                    .iter()
+                    .map(|text| text.trim())
+                    .filter(|text| !text.is_empty())
+                    .map(|text| TodoItem {
+                        text: text.to_string(),
+                        done: false,
+                    })
+                    .collect();
+                if new.is_empty() {
+                    return Ok(ToolResult::error("add needs at least one item".into()));
+                }
+                items.extend(new);
+            }
+            "done" | "undone" => {
+                let done = input.action == "done";
+                for id in &input.ids {
+                    items[id - 1].done = done;
+                }
+            }
+            "remove" => {
+                let mut ids = input.ids.clone();
+                ids.sort_unstable();
+                ids.dedup();
+                for id in ids.into_iter().rev() {
+                    items.remove(id - 1);
+                }
+            }
+            "clear" => items.clear(),
+            "list" => {}
+            other => {
+                return Ok(ToolResult::error(format!(
+                    "Unknown todo action '{}'. Supported: add, done, undone, remove, clear, list",
+                    other
+                )))
+            }
+        }
+
+        if items.is_empty() {
+            return Ok(ToolResult::success("The todo list is empty.".into()));
+        }
+        Ok(ToolResult::success(format_todos(&items)))
+    }
+}
@@ -0,0 +1,517 @@
+//! Web fetch tool — retrieves a URL and returns it as readable text.
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use super::{Tool, ToolResult};
+use crate::constants::{
+    WEB_FETCH_MAX_BYTES, WEB_FETCH_MAX_CHARS, WEB_FETCH_MAX_REDIRECTS, WEB_FETCH_TIMEOUT_SECS,
+};
+use crate::permissions::{Permission, PermissionManager};
+
+/// The permissions web_fetch checks redirect targets against: those of the
+/// hook its calls go through, filled in once the registry has one.
+pub(crate) type RedirectPermissions = Arc<OnceLock<Arc<PermissionManager>>>;
+
+/// Tool that fetches an http(s) URL.
+///
+/// HTML is reduced to markdown-ish text (headings, list items, links, and
+/// preformatted blocks survive; scripts, styles, and tags do not). Other
+/// text types are returned as-is and binary content is refused. Downloads
+/// stop at [`WEB_FETCH_MAX_BYTES`] and the whole request is bounded by a
+/// timeout. Whether the call runs at all is decided by the permission
+/// system, which can allow or deny individual hosts. Redirects within a
+/// host are followed; one to another host only if the permissions allow
+/// that host outright, and otherwise the model is told where it leads.
+pub struct WebFetchTool {
+    permissions: RedirectPermissions,
+}
+
+impl WebFetchTool {
+    pub fn new(permissions: RedirectPermissions) -> Self {
+        Self { permissions }
+    }
+
+    /// Whether the redirect from `from` to `to` may be followed, or the
+    /// error to return instead.
+    fn check_redirect(&self, from: &reqwest::Url, to: &reqwest::Url) -> Result<(), String> {
+        if to.host_str() == from.host_str() {
+            return Ok(());
+        }
+        let args = json!({ "url": to.as_str() }).to_string();
+        match self.permissions.get().map(|m| m.check("web_fetch", &args)) {
+            Some(Permission::Allow) => Ok(()),
+            Some(Permission::Deny) => Err(format!(
+                "{} redirects to {}, which the network permissions deny",
+                from, to
+            )),
+            _ => Err(format!(
+                "{} redirects to {}. Call web_fetch with that URL to follow it; the new host \
+                 needs its own permission.",
+                from, to
+            )),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct WebFetchInput {
+    url: String,
+}
+
+#[async_trait::async_trait]
+impl Tool for WebFetchTool {
+    fn name(&self) -> &str {
+        "web_fetch"
+    }
+
+    fn description(&self) -> &str {
+        "Fetch an http(s) URL and return its content as readable text (HTML is converted \
+         to markdown-like text). Use it to read documentation or error pages. Large pages \
+         are truncated."
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "url": {
+                    "type": "string",
+                    "description": "Absolute http or https URL to fetch"
+                }
+            },
+            "required": ["url"]
+        })
+    }
+
+    async fn execute(&self, input: Value) -> Result<ToolResult> {
+        let input: WebFetchInput = serde_json::from_value(input)?;
+        let url = match reqwest::Url::parse(&input.url) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => url,
+            Ok(url) => {
+                return Ok(ToolResult::error(format!(
+                    "Unsupported URL scheme '{}'; only http and https can be fetched",
+                    url.scheme()
+                )))
+            }
+            Err(e) => return Ok(ToolResult::error(format!("Invalid URL: {}", e))),
+        };
+
+        let client = crate::provider::no_redirect_client();
+        let mut url = url;
+        let mut redirects = 0;
+        let mut response = loop {
+            let request = client
+                .get(url.clone())
+                .timeout(Duration::from_secs(WEB_FETCH_TIMEOUT_SECS))
+                .send();
+            let response = match request.await {
+                Ok(response) => response,
+                Err(e) if e.is_timeout() => {
+                    return Ok(ToolResult::error(format!(
+                        "Timed out after {}s fetching {}",
+                        WEB_FETCH_TIMEOUT_SECS, url
+                    )))
+                }
+                Err(e) => return Ok(ToolResult::error(format!("Failed to fetch {}: {}", url, e))),
+            };
+            let next = response
+                .status()
+                .is_redirection()
+                .then(|| response.headers().get(reqwest::header::LOCATION))
+                .flatten()
+                .and_then(|location| location.to_str().ok())
+                .and_then(|location| url.join(location).ok());
+            let Some(next) = next else {
+                break response;
+            };
+            if !matches!(next.scheme(), "http" | "https") {
+                return Ok(ToolResult::error(format!(
+                    "{} redirects to {}, which is not an http or https URL",
+                    url, next
+                )));
+            }
+            if redirects == WEB_FETCH_MAX_REDIRECTS {
+                return Ok(ToolResult::error(format!(
+                    "Gave up on {} after {} redirects",
+                    input.url, WEB_FETCH_MAX_REDIRECTS
+                )));
+            }
+            if let Err(e) = self.check_redirect(&url, &next) {
+                return Ok(ToolResult::error(e));
+            }
+            redirects += 1;
+            url = next;
+        };
+
+        let status = response.status();
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("")
+            .to_lowercase();
+        let final_url = response.url().to_string();
+
+        let mut body = Vec::new();
+        let mut truncated = false;
+        loop {
+            match response.chunk().await {
+                Ok(Some(chunk)) => {
+                    let room = WEB_FETCH_MAX_BYTES - body.len();
+                    if chunk.len() >= room {
+                        body.extend_from_slice(&chunk[..room]);
+                        truncated = true;
+                        break;
+                    }
+                    body.extend_from_slice(&chunk);
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    return Ok(ToolResult::error(format!(
+                        "Failed to read response from {}: {}",
+                        url, e
+                    )))
+                }
+            }
+        }
+
+        let is_html =
+            content_type.contains("html") || (content_type.is_empty() && body.starts_with(b"<"));
+        let is_text = is_html
+            || content_type.is_empty()
+            || content_type.starts_with("text/")
+            || content_type.contains("json")
+            || content_type.contains("xml")
+            || content_type.contains("javascript");
+        if !is_text || body.contains(&0) {
+            return Ok(ToolResult::error(format!(
+                "{} returned binary content ({}); only text can be fetched",
+                url,
+                if content_type.is_empty() {
+                    "unknown type"
+                } else {
+                    &content_type
+                }
+            )));
+        }
+
+        let raw = String::from_utf8_lossy(&body);
+        let mut text = if is_html {
+            html_to_text(&raw)
+        } else {
+            raw.into_owned()
+        };
+        if text.chars().count() > WEB_FETCH_MAX_CHARS {
+            text = text.chars().take(WEB_FETCH_MAX_CHARS).collect();
+            truncated = true;
+        }
+
+        let mut output = format!("{} ({})\n\n{}", final_url, status, text.trim());
+        if truncated {
+            output.push_str("\n\n... content truncated");
+        }
+        if status.is_client_error() || status.is_server_error() {
+            return Ok(ToolResult::error(output));
+        }
+        Ok(ToolResult::success(output))
+    }
+}
+
+/// Elements whose content is never shown.
+const HIDDEN_ELEMENTS: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "head", "iframe",
+];
+
+/// Elements that start a new line (or paragraph) in the output.
+const BLOCK_ELEMENTS: &[&str] = &[
+    "p",
+    "div",
+    "section",
+    "article",
+    "main",
+    "header",
+    "footer",
+    "nav",
+    "aside",
+    "table",
+    "tr",
+    "ul",
+    "ol",
+    "dl",
+    "dt",
+    "dd",
+    "blockquote",
+    "figure",
+    "form",
+    "hr",
+];
+
+/// Converts HTML to markdown-like plain text.
+///
+/// This is a tolerant single pass rather than a real parser: good enough
+/// for documentation pages, and it never fails on malformed markup.
+/// Headings become `#` lines, list items `- ` lines, links `[text](href)`,
+/// and `<pre>` blocks keep their whitespace inside ``` fences. The page
+/// `<title>` (inside the otherwise hidden `<head>`) becomes the first line.
+pub(crate) fn html_to_text(html: &str) -> String {
+    let mut out = String::new();
+    let mut title = None;
+    let mut hidden: Option<String> = None;
+    let mut pre_depth = 0usize;
+    let mut link: Option<(String, usize)> = None;
+    let mut rest = html;
+
+    while let Some(lt) = rest.find('<') {
+        let text = &rest[..lt];
+        if hidden.is_none() {
+            push_text(&mut out, &decode_entities(text), pre_depth > 0);
+        }
+        rest = &rest[lt..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let Some(gt) = rest.find('>') else {
+            rest = "";
+            break;
+        };
+        let tag = &rest[1..gt];
+        rest = &rest[gt + 1..];
+
+        let closing = tag.starts_with('/');
+        let name: String = tag
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_ascii_lowercase();
+        if name.is_empty() {
+            // `<!DOCTYPE>`, `<?xml?>`, or a stray `<`
+            continue;
+        }
+
+        if name == "title" && !closing && title.is_none() {
+            let end = rest
+                .to_ascii_lowercase()
+                .find("</title")
+                .unwrap_or(rest.len());
+            let text = decode_entities(&rest[..end]);
+            let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+            if !text.is_empty() {
+                title = Some(text);
+            }
+            rest = &rest[end..];
+            continue;
+        }
+        if let Some(open) = &hidden {
+            // `</head>` is optional in HTML; the body ends it regardless
+            if (closing && *open == name) || (open == "head" && name == "body") {
+                hidden = None;
+            }
+            continue;
+        }
+        if HIDDEN_ELEMENTS.contains(&name.as_str()) {
+            if !closing && !tag.ends_with('/') {
+                hidden = Some(name);
+            }
+            continue;
+        }
+
+        match (name.as_str(), closing) {
+            ("br", _) => out.push('\n'),
+            ("pre", false) => {
+                pre_depth += 1;
+                block_break(&mut out);
+                out.push_str("```\n");
+            }
+            ("pre", true) => {
+                pre_depth = pre_depth.saturating_sub(1);
+                if !out.ends_with('\n') {
+                    out.push('\n');
+                }
+                out.push_str("```\n\n");
+            }
+            ("code", _) if pre_depth == 0 => out.push('`'),
+            ("li", false) => {
+                line_break(&mut out);
+                out.push_str("- ");
+            }
+            ("td" | "th", false) if !out.ends_with('\n') && !out.is_empty() => {
+                out.push_str(" | ");
+            }
+            ("a", false) => link = attribute(tag, "href").map(|href| (href, out.len())),
+            ("a", true) => {
+                if let Some((href, start)) = link.take().filter(|(_, start)| *start <= out.len()) {
+                    let text = out[start..].trim();
+                    if !text.is_empty()
+                        && !href.starts_with('#')
+                        && !href.starts_with("javascript:")
+                    {
+                        let text = text.to_string();
+                        out.truncate(start);
+                        out.push_str(&format!("[{}]({})", text, href));
+                    }
+                }
+            }
+            (heading, false) if is_heading(heading) => {
+                block_break(&mut out);
+                let level = heading[1..].parse::<usize>().unwrap_or(1);
+                out.push_str(&"#".repeat(level));
+                out.push(' ');
+            }
+            (heading, true) if is_heading(heading) => block_break(&mut out),
+            (block, _) if BLOCK_ELEMENTS.contains(&block) => block_break(&mut out),
+            _ => {}
+        }
+    }
+    if hidden.is_none() {
+        push_text(&mut out, &decode_entities(rest), pre_depth > 0);
+    }
+
+    let body = tidy_lines(&out);
+    match title {
+        Some(title) if !body.starts_with(&format!("# {}", title)) => {
+            format!("# {}\n\n{}", title, body)
+        }
+        _ => body,
+    }
+}
+
+fn is_heading(name: &str) -> bool {
+    name.len() == 2 && name.starts_with('h') && matches!(name.as_bytes()[1], b'1'..=b'6')
+}
+
+/// Appends text, collapsing whitespace unless inside `<pre>`.
+fn push_text(out: &mut String, text: &str, preformatted: bool) {
+    if preformatted {
+        out.push_str(text);
+        return;
+    }
+    for (i, word) in text.split_whitespace().enumerate() {
+        let starts_with_space = i > 0 || text.starts_with(char::is_whitespace);
+        if starts_with_space && !out.is_empty() && !out.ends_with(['\n', ' ', '`', '[']) {
+            out.push(' ');
+        }
+        out.push_str(word);
+    }
+    if text.ends_with(char::is_whitespace) && !text.trim().is_empty() {
+        out.push(' ');
+    }
+}
+
+/// Ends the current line if it has content.
+fn line_break(out: &mut String) {
+    let trimmed = out.trim_end_matches(' ').len();
+    out.truncate(trimmed);
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+}
+
+/// Ends the current paragraph with a blank line.
+fn block_break(out: &mut String) {
+    line_break(out);
+    if !out.is_empty() && !out.ends_with("\n\n") {
+        out.push('\n');
+    }
+}
+
+/// Trims each line's trailing spaces and collapses runs of blank lines,
+/// leaving ``` fenced blocks untouched.
+fn tidy_lines(text: &str) -> String {
+    let mut lines: Vec<&str> = Vec::new();
+    let mut in_fence = false;
+    for line in text.lines() {
+        if line.starts_with("```") {
+            in_fence = !in_fence;
+        }
+        let line = if in_fence { line } else { line.trim_end() };
+        if !in_fence && line.trim().is_empty() && lines.last().is_none_or(|l| l.is_empty()) {
+            continue;
+        }
+        lines.push(if line.trim().is_empty() && !in_fence {
+            ""
+        } else {
+            line
+        });
+    }
+    lines.join("\n").trim().to_string()
+}
+
+/// Reads `name="value"` (or single-quoted/unquoted) from a tag's source.
+pub(super) fn attribute(tag: &str, name: &str) -> Option<String> {
+    let lower = tag.to_ascii_lowercase();
+    let mut search = 0;
+    while let Some(found) = lower[search..].find(name) {
+        let start = search + found;
+        search = start + name.len();
+        let preceded = lower[..start].ends_with(char::is_whitespace);
+        let after = lower[search..].trim_start();
+        if !preceded || !after.starts_with('=') {
+            continue;
+        }
+        let value = tag[tag.len() - after.len() + 1..].trim_start();
+        let value = match value.chars().next()? {
+            quote @ ('"' | '\'') => value[1..].split(quote).next()?,
+            _ => value
+                .split(|c: char| c.is_whitespace() || c == '>')
+                .next()?,
+        };
+        return Some(decode_entities(value));
+    }
+    None
+}
+
+/// Decodes the common named entities and all numeric ones.
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let entity = &rest[1..end];
+            let ch = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                "mdash" => Some('—'),
+                "ndash" => Some('–'),
+                "hellip" => Some('…'),
+                "copy" => Some('©'),
+                _ => {
+                    let code = entity.strip_prefix('#')?;
+                    let value = match code.strip_prefix(['x', 'X']) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                        None => code.parse().ok()?,
+                    };
+                    char::from_u32(value)
+                }
+            };
+            ch.map(|ch| (ch, end))
+        });
+        match decoded {
+            Some((ch, end)) => {
+                out.push(ch);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
@@ -0,0 +1,328 @@
+//! Web search tool — queries a configurable search backend.
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+use super::web_fetch::{attribute, html_to_text};
+use super::{Tool, ToolResult};
+use crate::config::WebSearchConfig;
+use crate::constants::{
+    WEB_SEARCH_DEFAULT_RESULTS, WEB_SEARCH_MAX_RESULTS, WEB_SEARCH_TIMEOUT_SECS,
+};
+
+/// Where searches are sent, from `[tools.web_search] backend`.
+#[derive(Debug, Clone, PartialEq)]
+enum Backend {
+    /// DuckDuckGo's HTML results page; needs no key.
+    DuckDuckGo,
+    /// Brave Search API; needs `api_key`.
+    Brave,
+    /// A SearXNG instance's JSON API at `base_url`.
+    SearXng,
+}
+
+impl Backend {
+    fn from_config(name: Option<&str>) -> Result<Self, String> {
+        match name.map(str::to_lowercase).as_deref() {
+            None | Some("duckduckgo") | Some("ddg") => Ok(Self::DuckDuckGo),
+            Some("brave") => Ok(Self::Brave),
+            Some("searxng") | Some("searx") => Ok(Self::SearXng),
+            Some(other) => Err(format!(
+                "Unknown web_search backend '{}'. Supported: duckduckgo, brave, searxng",
+                other
+            )),
+        }
+    }
+}
+
+/// One search hit.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SearchResult {
+    pub title: String,
+    pub url: String,
+    pub snippet: String,
+}
+
+/// Tool that searches the web and returns titles, URLs, and snippets.
+///
+/// The backend and its credentials come from `[tools.web_search]`;
+/// misconfiguration is reported to the model as a tool error rather than
+/// failing at startup, so the other tools keep working.
+pub struct WebSearchTool {
+    config: WebSearchConfig,
+}
+
+impl WebSearchTool {
+    pub fn new(config: WebSearchConfig) -> Self {
+        Self { config }
+    }
+
+    /// Results to return: the model's request, else the configured default,
+    /// capped at [`WEB_SEARCH_MAX_RESULTS`].
+    fn limit(&self, requested: Option<usize>) -> usize {
+        requested
+            .or(self.config.max_results)
+            .unwrap_or(WEB_SEARCH_DEFAULT_RESULTS)
+            .clamp(1, WEB_SEARCH_MAX_RESULTS)
+    }
+
+    async fn search(
+        &self,
+        backend: &Backend,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<SearchResult>, String> {
+        let client = crate::provider::shared_client();
+        let timeout = Duration::from_secs(WEB_SEARCH_TIMEOUT_SECS);
+        let fail = |e: reqwest::Error| {
+            if e.is_timeout() {
+                format!("Search timed out after {}s", WEB_SEARCH_TIMEOUT_SECS)
+            } else {
+                format!("Search request failed: {}", e)
+            }
+        };
+
+        match backend {
+            Backend::DuckDuckGo => {
+                let url = build_url("https://html.duckduckgo.com/html/", &[("q", query)])?;
+                let html = client
+                    .get(url)
+                    .header(reqwest::header::USER_AGENT, user_agent())
+                    .timeout(timeout)
+                    .send()
+                    .await
+                    .and_then(|r| r.error_for_status())
+                    .map_err(fail)?
+                    .text()
+                    .await
+                    .map_err(fail)?;
+                let mut results = parse_duckduckgo(&html);
+                results.truncate(limit);
+                Ok(results)
+            }
+            Backend::Brave => {
+                let key = self
+                    .config
+                    .api_key
+                    .as_deref()
+                    .filter(|k| !k.is_empty())
+                    .ok_or("The brave backend needs [tools.web_search] api_key".to_string())?;
+                let count = limit.to_string();
+                let url = build_url(
+                    "https://api.search.brave.com/res/v1/web/search",
+                    &[("q", query), ("count", &count)],
+                )?;
+                let body: Value = client
+                    .get(url)
+                    .header("X-Subscription-Token", key)
+                    .header(reqwest::header::ACCEPT, "application/json")
+                    .timeout(timeout)
+                    .send()
+                    .await
+                    .and_then(|r| r.error_for_status())
+                    .map_err(fail)?
+                    .json()
+                    .await
+                    .map_err(fail)?;
+                Ok(json_results(&body["web"]["results"], "description", limit))
+            }
+            Backend::SearXng => {
+                let base =
+                    self.config.base_url.as_deref().ok_or(
+                        "The searxng backend needs [tools.web_search] base_url".to_string(),
+                    )?;
+                let url = build_url(
+                    &format!("{}/search", base.trim_end_matches('/')),
+                    &[("q", query), ("format", "json")],
+                )?;
+                let body: Value = client
+                    .get(url)
+                    .header(reqwest::header::USER_AGENT, user_agent())
+                    .timeout(timeout)
+                    .send()
+                    .await
+                    .and_then(|r| r.error_for_status())
+                    .map_err(fail)?
+                    .json()
+                    .await
+                    .map_err(fail)?;
+                Ok(json_results(&body["results"], "content", limit))
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct WebSearchInput {
+    query: String,
+    max_results: Option<usize>,
+}
+
+#[async_trait::async_trait]
+impl Tool for WebSearchTool {
+    fn name(&self) -> &str {
+        "web_search"
+    }
+
+    fn description(&self) -> &str {
+        "Search the web and return result titles, URLs, and snippets. Use it to look up \
+         APIs, library docs, and error messages; follow up with web_fetch to read a result."
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "Search query"
+                },
+                "max_results": {
+                    "type": "integer",
+                    "description": format!("Number of results (default {})", self.limit(None))
+                }
+            },
+            "required": ["query"]
+        })
+    }
+
+    async fn execute(&self, input: Value) -> Result<ToolResult> {
+        let input: WebSearchInput = serde_json::from_value(input)?;
+        let query = input.query.trim();
+        if query.is_empty() {
+            return Ok(ToolResult::error("Empty search query.".into()));
+        }
+        let backend = match Backend::from_config(self.config.backend.as_deref()) {
+            Ok(backend) => backend,
+            Err(message) => return Ok(ToolResult::error(message)),
+        };
+
+        let results = match self
+            .search(&backend, query, self.limit(input.max_results))
+            .await
+        {
+            Ok(results) => results,
+            Err(message) => return Ok(ToolResult::error(message)),
+        };
+        if results.is_empty() {
+            return Ok(ToolResult::success(format!("No results for \"{}\"", query)));
+        }
+        Ok(ToolResult::success(format_results(query, &results)))
+    }
+}
+
+fn user_agent() -> String {
+    format!("kaze/{}", env!("CARGO_PKG_VERSION"))
+}
+
+fn build_url(base: &str, params: &[(&str, &str)]) -> Result<reqwest::Url, String> {
+    reqwest::Url::parse_with_params(base, params).map_err(|e| format!("Invalid search URL: {}", e))
+}
+
+/// Reads `{title, url, <snippet_key>}` objects from a JSON results array.
+fn json_results(results: &Value, snippet_key: &str, limit: usize) -> Vec<SearchResult> {
+    results
+        .as_array()
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| {
+                    Some(SearchResult {
+                        title: html_to_text(item["title"].as_str()?),
+                        url: item["url"].as_str()?.to_string(),
+                        snippet: html_to_text(item[snippet_key].as_str().unwrap_or("")),
+                    })
+                })
+                .take(limit)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Extracts results from DuckDuckGo's HTML results page.
+///
+/// Result links are `<a class="result__a" href="...">` and snippets
+/// `<a class="result__snippet">`. Links go through a `/l/?uddg=` redirect
+/// that is unwrapped to the target URL; ads (`y.js` links) are skipped.
+pub(crate) fn parse_duckduckgo(html: &str) -> Vec<SearchResult> {
+    let mut results: Vec<SearchResult> = Vec::new();
+    let mut rest = html;
+    while let Some(start) = rest.find("<a") {
+        rest = &rest[start..];
+        let Some(tag_end) = rest.find('>') else {
+            break;
+        };
+        let tag = &rest[1..tag_end];
+        let body_start = tag_end + 1;
+        let body_end = rest[body_start..]
+            .find("</a>")
+            .map_or(rest.len(), |end| body_start + end);
+        let inner = &rest[body_start..body_end];
+        rest = &rest[body_end..];
+
+        let class = attribute(tag, "class").unwrap_or_default();
+        let classes: Vec<&str> = class.split_whitespace().collect();
+        if classes.contains(&"result__a") {
+            let Some(url) = attribute(tag, "href").and_then(|href| unwrap_redirect(&href)) else {
+                continue;
+            };
+            results.push(SearchResult {
+                title: collapse(&html_to_text(inner)),
+                url,
+                snippet: String::new(),
+            });
+        } else if classes.contains(&"result__snippet") {
+            if let Some(last) = results.last_mut().filter(|r| r.snippet.is_empty()) {
+                last.snippet = collapse(&html_to_text(inner));
+            }
+        }
+    }
+    results
+}
+
+/// Resolves DuckDuckGo's `//duckduckgo.com/l/?uddg=<url>` links; `None` for ads.
+fn unwrap_redirect(href: &str) -> Option<String> {
+    let absolute = if href.starts_with("//") {
+        format!("https:{}", href)
+    } else {
+        href.to_string()
+    };
+    let url = reqwest::Url::parse(&absolute).ok()?;
+    if url
+        .host_str()
+        .is_some_and(|h| h.ends_with("duckduckgo.com"))
+    {
+        if url.path() == "/y.js" {
+            return None;
+        }
+        return url
+            .query_pairs()
+            .find(|(key, _)| key == "uddg")
+            .map(|(_, target)| target.into_owned());
+    }
+    Some(absolute)
+}
+
+/// Joins text onto one line.
+fn collapse(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Formats results as a numbered list the model can cite.
+fn format_results(query: &str, results: &[SearchResult]) -> String {
+    let mut output = format!("Results for \"{}\":\n", query);
+    for (i, result) in results.iter().enumerate() {
+        output.push_str(&format!(
+            "\n{}. {}\n   {}\n",
+            i + 1,
+            result.title,
+            result.url
+        ));
+        if !result.snippet.is_empty() {
+            output.push_str(&format!("   {}\n", result.snippet));
+        }
+    }
+    output
+}
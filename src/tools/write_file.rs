@@ -3,10 +3,11 @@
 use anyhow::Result;
 use serde::Deserialize;
 use serde_json::{json, Value};
-use std::fs;
 use std::path::{Path, PathBuf};
 
+use super::file_history::{read_existing, FileEdit, FileHistory};
 use super::{Tool, ToolResult};
+use crate::async_fs;
 
 /// Tool that writes string content to a file within the project root.
 ///
@@ -20,6 +21,8 @@ use super::{Tool, ToolResult};
 pub struct WriteFileTool {
     /// Project root directory. Paths are resolved relative to this.
     project_root: PathBuf,
+    /// Where the previous contents are recorded for `/undo`.
+    history: FileHistory,
 }
 
 impl WriteFileTool {
@@ -28,8 +31,11 @@ impl WriteFileTool {
     /// # Errors
     ///
     /// None — construction is infallible.
-    pub fn new(project_root: PathBuf) -> Self {
-        Self { project_root }
+    pub fn new(project_root: PathBuf, history: FileHistory) -> Self {
+        Self {
+            project_root,
+            history,
+        }
     }
 
     /// Resolve and validate that the path stays within the project root.
@@ -41,7 +47,7 @@ impl WriteFileTool {
     /// # Errors
     ///
     /// Returns an error if the resolved path would escape the project root.
-    fn resolve_path(&self, path: &str) -> Result<PathBuf> {
+    async fn resolve_path(&self, path: &str) -> Result<PathBuf> {
         let resolved = if Path::new(path).is_absolute() {
             PathBuf::from(path)
         } else {
@@ -53,7 +59,7 @@ impl WriteFileTool {
             .ok_or_else(|| anyhow::anyhow!("Path has no parent directory: {}", path))?;
 
         // Create parent directories if they don't exist yet.
-        fs::create_dir_all(parent)?;
+        async_fs::create_dir_all(parent).await?;
 
         let parent_canonical = parent.canonicalize()?;
         let root_canonical = self.project_root.canonicalize()?;
@@ -105,9 +111,22 @@ impl Tool for WriteFileTool {
 
     async fn execute(&self, input: Value) -> Result<ToolResult> {
         let input: WriteFileInput = serde_json::from_value(input)?;
-        let path = self.resolve_path(&input.path)?;
-
-        fs::write(&path, &input.content)?;
+        let path = self.resolve_path(&input.path).await?;
+
+        let before = read_existing(&path).await?;
+        let change = self
+            .history
+            .backup(
+                self.name(),
+                &[FileEdit {
+                    path: &path,
+                    before: before.as_deref(),
+                    after: Some(input.content.as_bytes()),
+                }],
+            )
+            .await?;
+        async_fs::write(&path, &input.content).await?;
+        self.history.record(change);
 
         let bytes_written = input.content.len();
         Ok(ToolResult::success(format!(
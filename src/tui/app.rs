@@ -4,6 +4,10 @@
 //! that drive the terminal UI layout.
 
 use super::renderer::RenderEvent;
+use crate::i18n::{t, tf};
+use crate::output::NoticeLevel;
+use crate::permissions::{PermissionRequest, PromptResponse};
+use crate::tools::todo::TodoList;
 
 /// A single chat message displayed in the TUI message history.
 #[derive(Debug, Clone)]
@@ -31,6 +35,16 @@ pub struct App {
     pub waiting: bool,
     /// Current animation frame for the spinner.
     pub spinner_frame: usize,
+    /// Permission prompt awaiting a y/n/a answer from the user.
+    pub pending_permission: Option<PermissionRequest>,
+    /// The model's plan, shown in a side panel while it has items.
+    pub todos: TodoList,
+}
+
+impl Default for App {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl App {
@@ -43,25 +57,78 @@ impl App {
             streaming: false,
             waiting: false,
             spinner_frame: 0,
+            pending_permission: None,
+            todos: TodoList::default(),
         }
     }
 
+    /// Appends a message to the history pane, stripping ANSI escapes.
+    pub fn push_message(&mut self, role: &str, content: &str) {
+        self.messages.push(ChatMessage {
+            role: role.to_string(),
+            content: strip_ansi(content),
+        });
+        self.scroll_offset = 0;
+    }
+
     /// Submits the current input as a user message.
     ///
     /// If the input is empty, this is a no-op. Otherwise, the input text
     /// is moved into a new [`ChatMessage`] with role "user", appended to
     /// the message history, and the scroll offset is reset to zero.
-    pub fn submit_input(&mut self) {
+    /// Returns the submitted text so the caller can start a turn.
+    pub fn submit_input(&mut self) -> Option<String> {
         if self.input.is_empty() {
-            return;
+            return None;
         }
         let text = std::mem::take(&mut self.input);
         self.messages.push(ChatMessage {
             role: "user".to_string(),
-            content: text,
+            content: text.clone(),
         });
         self.scroll_offset = 0;
         self.waiting = true;
+        Some(text)
+    }
+
+    /// Shows a permission request from the hook.
+    ///
+    /// Preview-only requests just display the diff; requests with a reply
+    /// channel become the pending prompt answered via [`App::answer_permission`].
+    pub fn show_permission(&mut self, request: PermissionRequest) {
+        if let Some(ref diff) = request.diff {
+            self.push_message("diff", diff);
+        }
+        if request.reply.is_some() {
+            let question = request.question.clone().unwrap_or_else(|| {
+                let display = if request.args.len() > 200 {
+                    let end = request.args.floor_char_boundary(200);
+                    format!("{}...", &request.args[..end])
+                } else {
+                    request.args.clone()
+                };
+                tf(
+                    "permission.prompt",
+                    &[("tool", &request.tool_name), ("args", &display)],
+                )
+            });
+            self.push_message(
+                "permission",
+                &format!("{}\n{}", question, t("permission.choices")),
+            );
+            self.waiting = false;
+            self.pending_permission = Some(request);
+        }
+    }
+
+    /// Answers the pending permission prompt, if any.
+    pub fn answer_permission(&mut self, response: PromptResponse) {
+        if let Some(request) = self.pending_permission.take() {
+            if let Some(reply) = request.reply {
+                let _ = reply.send(response);
+            }
+            self.waiting = true;
+        }
     }
 
     /// Scrolls the message history up by one line.
@@ -102,12 +169,7 @@ impl App {
             RenderEvent::ToolResult { name: _, result } => {
                 if let Some(last) = self.messages.last_mut() {
                     if last.role == "tool" {
-                        if result.len() > 200 {
-                            let end = result.floor_char_boundary(197);
-                            last.content = format!("{}...", &result[..end]);
-                        } else {
-                            last.content = result;
-                        }
+                        last.content = crate::output::tool_result_display(&result);
                     }
                 }
             }
@@ -129,6 +191,14 @@ impl App {
                     content: msg,
                 });
             }
+            RenderEvent::Notice { level, message } => {
+                let role = match level {
+                    NoticeLevel::Plain | NoticeLevel::Info => "info",
+                    NoticeLevel::Warning => "warning",
+                    NoticeLevel::Critical => "error",
+                };
+                self.push_message(role, message.trim_end());
+            }
         }
     }
 
@@ -139,3 +209,25 @@ impl App {
         }
     }
 }
+
+/// Removes ANSI escape sequences (CSI `ESC [ ... letter`) from `text`.
+///
+/// Shared formatting helpers emit colored output for the REPL; ratatui
+/// would render the raw escape bytes.
+fn strip_ansi(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' && chars.peek() == Some(&'[') {
+            chars.next();
+            for next in chars.by_ref() {
+                if next.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+            continue;
+        }
+        out.push(c);
+    }
+    out
+}
@@ -3,14 +3,16 @@
 //! Provides an alternative ratatui-based interface launched via `kaze chat --tui`.
 //! The event loop runs asynchronously using [`tokio::select!`] with a 60 fps
 //! render tick and crossterm's async [`EventStream`](crossterm::event::EventStream).
+//! Conversation turns run through the shared [`ChatEngine`], so the TUI gets
+//! the same session handling, permissions, and context management as the REPL.
 
 mod app;
-mod renderer;
+pub(crate) mod renderer;
+mod sessions;
 mod ui;
 
 pub use app::App;
 pub use renderer::RenderEvent;
-#[allow(unused_imports)]
 pub use renderer::TuiRenderer;
 pub use ui::draw;
 
@@ -18,80 +20,340 @@ use std::io;
 
 use anyhow::Result;
 use crossterm::event::{Event, EventStream, KeyCode, KeyEvent, KeyModifiers};
-use crossterm::terminal::{
-    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
-};
-use crossterm::ExecutableCommand;
 use futures::StreamExt;
 use ratatui::backend::CrosstermBackend;
 use ratatui::Terminal;
 use tokio::sync::mpsc;
 use tokio::time::{interval, Duration};
 
+use sessions::{Dashboard, Slot, SlotStatus, Turn};
+
+use crate::chat::{commands, ChatEngine, ChatOptions};
+use crate::config::Config;
+use crate::permissions::PromptResponse;
+use crate::provider::ModelSelection;
+use crate::terminal::{self, FullscreenGuard, TerminalSignal, TerminalSignals};
+
 /// Render tick interval (~60 fps).
 const TICK_DURATION: Duration = Duration::from_millis(16);
 
+/// What the event loop should do after a key press.
+enum KeyAction {
+    /// Nothing beyond the state change already applied.
+    None,
+    /// The user submitted a line of input.
+    Submit(String),
+    /// Exit the TUI.
+    Quit,
+    /// Stop the process for shell job control (Ctrl+Z).
+    Suspend,
+    /// Show or hide the session dashboard (Ctrl+S).
+    Dashboard,
+}
+
+/// What the dashboard asks for after a key press.
+enum DashboardAction {
+    None,
+    /// Return to the active session.
+    Close,
+    /// Switch to the open session at this index.
+    Switch(usize),
+    /// Open this recent session alongside the others, or a new one.
+    Open(Option<String>),
+    /// Close the open session at this index.
+    Remove(usize),
+}
+
 /// Launches the TUI event loop.
 ///
 /// Enters raw mode and the alternate screen, then loops at ~60 fps:
 /// - Redraws the UI each tick
 /// - Handles crossterm key events (typing, scrolling, submit, quit)
+/// - Applies render events and permission prompts from every open session's
+///   running turn, including those not on screen
+/// - Suspends on Ctrl+Z and redraws from scratch when resumed
 ///
-/// On exit (Ctrl+C), restores the terminal to its normal state.
-pub async fn run_tui() -> Result<()> {
+/// Ctrl+S opens the session dashboard, from which more sessions can be
+/// started or resumed in the same process and switched between while
+/// their turns run.
+///
+/// On exit (Ctrl+C, SIGTERM, SIGHUP, or an error), restores the terminal to
+/// its normal state.
+pub async fn run_tui(
+    config: Config,
+    session_id: Option<String>,
+    selection: &ModelSelection,
+    options: ChatOptions,
+) -> Result<()> {
+    // Sessions opened from the dashboard share the flags that aren't tied
+    // to the first one
+    let extra_options = ChatOptions {
+        verbose: options.verbose,
+        ..ChatOptions::default()
+    };
+    let (mut engine, prompts) = Slot::engine(config.clone(), session_id, selection, options)?;
+    // Prompts on the normal screen, before fullscreen mode is entered
+    crate::chat::reconcile_session_model(&mut engine)?;
+    let offline = engine.check_network().await;
+    let pruned = engine.auto_prune();
+    let attached = engine.refresh_attachment().await;
+
+    let auto_approve = engine.auto_approve();
+    let mut first = Slot::new(engine, prompts);
+    if auto_approve {
+        first.app.push_message(
+            "error",
+            &format!(
+                "{} {}",
+                crate::i18n::t("auto.banner"),
+                crate::i18n::t("auto.off_hint")
+            ),
+        );
+    }
+    if let Some(notice) = offline {
+        first.app.push_message("error", &notice);
+    }
+    for warning in crate::chat::commands::shadowed_command_warnings() {
+        first.app.push_message("error", &warning);
+    }
+    if let Some(notice) = pruned {
+        first.app.push_message("info", &notice);
+    }
+    match attached {
+        Ok(Some(summary)) => first.app.push_message(
+            "info",
+            &crate::i18n::tf("attach.attached", &[("summary", &summary)]),
+        ),
+        Ok(None) => {}
+        Err(e) => first.app.push_message("error", &format!("{:#}", e)),
+    }
+    let mut slots = vec![first];
+    let mut active = 0;
+    let mut dashboard: Option<Dashboard> = None;
+
     // --- Terminal setup ---
-    enable_raw_mode()?;
-    io::stdout().execute(EnterAlternateScreen)?;
+    // The guard restores the terminal on every exit path, including errors
+    let guard = FullscreenGuard::enter()?;
+    let mut signals = TerminalSignals::new()?;
     let backend = CrosstermBackend::new(io::stdout());
     let mut terminal = Terminal::new(backend)?;
 
-    let mut app = App::new();
     let mut events = EventStream::new();
     let mut tick = interval(TICK_DURATION);
 
-    // Channel for streaming LLM events into the TUI.
-    let (tx, mut rx) = mpsc::channel::<RenderEvent>(1000);
-    let _tx = tx; // keep sender alive so rx doesn't immediately close
-
     // --- Main event loop ---
     loop {
         tokio::select! {
             _ = tick.tick() => {
-                app.tick_spinner();
-                terminal.draw(|f| draw(f, &app))?;
+                for slot in &mut slots {
+                    slot.app.tick_spinner();
+                }
+                let _span = tracing::info_span!("tui_draw").entered();
+                terminal.draw(|f| match &dashboard {
+                    Some(board) => ui::draw_dashboard(f, &slots, active, board),
+                    None => ui::draw_session(f, &slots[active].app, &session_title(&slots, active)),
+                })?;
             }
             event = events.next() => {
-                match event {
-                    Some(Ok(Event::Key(key))) => {
-                        if !handle_key(&mut app, key) {
-                            break;
+                let key = match event {
+                    Some(Ok(Event::Key(key))) => key,
+                    Some(Err(_)) | None => break,
+                    _ => continue, // ignore mouse / resize for now
+                };
+                let action = match dashboard.as_mut() {
+                    Some(board) => handle_dashboard_key(board, &slots, key),
+                    None => match handle_key(&mut slots[active].app, key) {
+                        KeyAction::Quit => break,
+                        KeyAction::Suspend => {
+                            terminal::suspend_fullscreen()?;
+                            terminal.clear()?;
+                            continue;
+                        }
+                        KeyAction::Submit(text) => {
+                            let slot = &mut slots[active];
+                            if let Some(eng) = slot.engine.take() {
+                                slot.turn = Some(start_turn(eng, text, slot.tx.clone()));
+                            }
+                            continue;
                         }
+                        KeyAction::Dashboard => {
+                            dashboard = Some(Dashboard::new(&slots, active));
+                            continue;
+                        }
+                        KeyAction::None => continue,
+                    },
+                };
+                match action {
+                    None => break,
+                    Some(DashboardAction::None) => {}
+                    Some(DashboardAction::Close) => dashboard = None,
+                    Some(DashboardAction::Switch(index)) => {
+                        active = index;
+                        dashboard = None;
+                    }
+                    Some(DashboardAction::Open(id)) => {
+                        // A session that fails to open is reported where the user was
+                        match open_slot(&config, id, selection, &extra_options) {
+                            Ok(slot) => {
+                                slots.push(slot);
+                                active = slots.len() - 1;
+                            }
+                            Err(e) => slots[active]
+                                .app
+                                .push_message("error", &format!("Failed to open session: {:#}", e)),
+                        }
+                        dashboard = None;
+                    }
+                    Some(DashboardAction::Remove(index)) => {
+                        slots.remove(index);
+                        if active >= index && active > 0 {
+                            active -= 1;
+                        }
+                        dashboard = Some(Dashboard::new(&slots, active));
                     }
-                    Some(Err(_)) | None => break,
-                    _ => {} // ignore mouse / resize for now
                 }
             }
-            Some(render_event) = rx.recv() => {
-                app.handle_render_event(render_event);
+            (index, event) = sessions::next_event(&mut slots) => {
+                slots[index].handle(event);
             }
+            signal = signals.recv() => match signal {
+                TerminalSignal::Shutdown => break,
+                TerminalSignal::Resume => {
+                    terminal::refresh_fullscreen()?;
+                    terminal.clear()?;
+                }
+            },
         }
     }
 
+    // Let in-flight turns save their partial responses before exiting
+    let mut running = Vec::new();
+    for slot in &mut slots {
+        if let Some(turn) = slot.turn.take() {
+            slot.app.pending_permission = None;
+            slot.interrupt.notify_waiters();
+            running.push(turn);
+        }
+    }
+    if !running.is_empty() {
+        let grace = Duration::from_millis(crate::constants::TUI_SHUTDOWN_GRACE_MS);
+        let _ = tokio::time::timeout(grace, futures::future::join_all(running)).await;
+    }
+
     // --- Terminal teardown ---
-    disable_raw_mode()?;
-    io::stdout().execute(LeaveAlternateScreen)?;
+    drop(guard);
     Ok(())
 }
 
-/// Processes a single key event, returning `false` when the loop should exit.
-fn handle_key(app: &mut App, key: KeyEvent) -> bool {
+/// The history pane title: which session is shown when there are several,
+/// and how many others wait for approval.
+fn session_title(slots: &[Slot], active: usize) -> String {
+    if slots.len() == 1 {
+        return " kaze ".to_string();
+    }
+    let waiting = slots
+        .iter()
+        .enumerate()
+        .filter(|&(i, slot)| i != active && slot.status() == SlotStatus::Approval)
+        .count();
+    let mut title = format!(" kaze · session {}/{} ", active + 1, slots.len());
+    if waiting > 0 {
+        title.push_str(&format!("· {} waiting for approval (Ctrl+S) ", waiting));
+    }
+    title
+}
+
+/// Opens another session from the dashboard.
+fn open_slot(
+    config: &Config,
+    session_id: Option<String>,
+    selection: &ModelSelection,
+    options: &ChatOptions,
+) -> Result<Slot> {
+    let (mut engine, prompts) =
+        Slot::engine(config.clone(), session_id, selection, options.clone())?;
+    // No terminal to ask on, so a resumed session takes the configured model
+    if engine.stale_model().is_some() {
+        engine.adopt_configured_model()?;
+    }
+    Ok(Slot::new(engine, prompts))
+}
+
+/// Runs one submitted line (slash command or chat message) on the engine.
+fn start_turn(mut engine: ChatEngine, text: String, tx: mpsc::Sender<RenderEvent>) -> Turn {
+    Box::pin(async move {
+        let mut renderer = TuiRenderer::new(tx);
+        let result = run_turn(&mut engine, &text, &mut renderer).await;
+        (engine, result)
+    })
+}
+
+/// Dispatches slash commands or sends a message and manages context.
+async fn run_turn(engine: &mut ChatEngine, text: &str, renderer: &mut TuiRenderer) -> Result<()> {
+    use crate::output::{NoticeLevel, Renderer};
+
+    if text.starts_with('!') {
+        return commands::handle_shell_escape(text, engine, renderer).await;
+    }
+    let mut message = text.to_string();
+    if text.starts_with('/') {
+        match commands::handle_slash_command(text, engine, renderer).await? {
+            commands::CommandAction::Continue => return Ok(()),
+            commands::CommandAction::Unknown(cmd) => {
+                renderer.notice(NoticeLevel::Warning, &format!("Unknown command: {}", cmd));
+                return Ok(());
+            }
+            commands::CommandAction::Send(text) => message = text,
+        }
+    }
+    if let Some(hint) = engine.duplicate_hint(&message) {
+        renderer.notice(NoticeLevel::Warning, &hint);
+        renderer.notice(NoticeLevel::Info, crate::i18n::t("duplicate.resend"));
+        return Ok(());
+    }
+    if !engine.confirm_turn(&message).await? {
+        renderer.notice(NoticeLevel::Info, crate::i18n::t("cost.not_sent"));
+        return Ok(());
+    }
+    engine.send(&message, renderer).await?;
+    engine.manage_context(renderer).await
+}
+
+/// Processes a single key event, returning what the event loop should do.
+fn handle_key(app: &mut App, key: KeyEvent) -> KeyAction {
     // Ctrl+C → quit
     if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
-        return false;
+        return KeyAction::Quit;
+    }
+
+    // Ctrl+Z → suspend (raw mode delivers it as a key, not SIGTSTP)
+    if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('z') {
+        return KeyAction::Suspend;
+    }
+
+    // Ctrl+S → session dashboard, also while a prompt waits
+    if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('s') {
+        return KeyAction::Dashboard;
+    }
+
+    // A pending permission prompt captures y/n/a
+    if app.pending_permission.is_some() {
+        let response = match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => PromptResponse::Yes,
+            KeyCode::Char('a') | KeyCode::Char('A') => PromptResponse::Always,
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => PromptResponse::No,
+            _ => return KeyAction::None,
+        };
+        app.answer_permission(response);
+        return KeyAction::None;
     }
 
     match key.code {
-        KeyCode::Enter => app.submit_input(),
+        KeyCode::Enter => {
+            if let Some(text) = app.submit_input() {
+                return KeyAction::Submit(text);
+            }
+        }
         KeyCode::Char(c) => app.input.push(c),
         KeyCode::Backspace => {
             app.input.pop();
@@ -100,5 +362,46 @@ fn handle_key(app: &mut App, key: KeyEvent) -> bool {
         KeyCode::Down => app.scroll_down(),
         _ => {}
     }
-    true
+    KeyAction::None
+}
+
+/// Processes a key on the dashboard. `None` means quit.
+fn handle_dashboard_key(
+    board: &mut Dashboard,
+    slots: &[Slot],
+    key: KeyEvent,
+) -> Option<DashboardAction> {
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        return match key.code {
+            KeyCode::Char('c') => None,
+            KeyCode::Char('s') => Some(DashboardAction::Close),
+            _ => Some(DashboardAction::None),
+        };
+    }
+    let action = match key.code {
+        KeyCode::Esc => DashboardAction::Close,
+        KeyCode::Up | KeyCode::Char('k') => {
+            board.up();
+            DashboardAction::None
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            board.down(slots.len());
+            DashboardAction::None
+        }
+        KeyCode::Enter => match board.selected.checked_sub(slots.len()) {
+            None => DashboardAction::Switch(board.selected),
+            Some(recent) => DashboardAction::Open(Some(board.recent[recent].id.clone())),
+        },
+        KeyCode::Char('n') => DashboardAction::Open(None),
+        // Only idle sessions close, and never the last one
+        KeyCode::Char('x')
+            if board.selected < slots.len()
+                && slots.len() > 1
+                && slots[board.selected].status() == SlotStatus::Idle =>
+        {
+            DashboardAction::Remove(board.selected)
+        }
+        _ => DashboardAction::None,
+    };
+    Some(action)
 }
@@ -6,9 +6,11 @@
 //! accordingly.
 
 use serde_json::Value;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 
-use crate::output::Renderer;
+use crate::constants::STREAM_BATCH_WINDOW_MS;
+use crate::output::{NoticeLevel, Renderer};
 
 /// Events sent from the renderer to the TUI event loop.
 #[allow(dead_code)]
@@ -36,40 +38,73 @@ pub enum RenderEvent {
     },
     /// A warning to display.
     Warn(String),
+    /// A status line or command output.
+    Notice {
+        /// Severity used to pick the message style.
+        level: NoticeLevel,
+        /// Notice text (may contain ANSI colors).
+        message: String,
+    },
 }
 
 /// Renderer that sends events to the TUI via an mpsc channel.
 ///
 /// All trait methods are fire-and-forget: if the channel is full or
-/// closed the event is silently dropped.
-#[allow(dead_code)]
+/// closed the event is silently dropped. Tokens are coalesced into one
+/// [`RenderEvent::Token`] per batching window, so a fast stream neither
+/// floods the channel nor forces a redraw per token.
 pub struct TuiRenderer {
     /// Channel sender for dispatching render events.
     tx: mpsc::Sender<RenderEvent>,
+    /// Tokens received since the last send.
+    pending: String,
+    last_flush: Instant,
 }
 
-#[allow(dead_code)]
 impl TuiRenderer {
     /// Creates a new [`TuiRenderer`] backed by the given channel sender.
     pub fn new(tx: mpsc::Sender<RenderEvent>) -> Self {
-        Self { tx }
+        Self {
+            tx,
+            pending: String::new(),
+            last_flush: Instant::now(),
+        }
+    }
+
+    /// Sends any batched tokens as a single event.
+    fn flush_pending(&mut self) {
+        if !self.pending.is_empty() {
+            let batch = std::mem::take(&mut self.pending);
+            let _ = self.tx.try_send(RenderEvent::Token(batch));
+        }
+        self.last_flush = Instant::now();
     }
 }
 
 impl Renderer for TuiRenderer {
     fn render_token(&mut self, token: &str) {
-        let _ = self.tx.try_send(RenderEvent::Token(token.to_string()));
+        self.pending.push_str(token);
+        if self.last_flush.elapsed() >= Duration::from_millis(STREAM_BATCH_WINDOW_MS) {
+            self.flush_pending();
+        }
+    }
+
+    fn flush(&mut self) {
+        self.flush_pending();
     }
 
     fn render_done(&mut self) {
+        self.flush_pending();
         let _ = self.tx.try_send(RenderEvent::Done);
     }
 
     fn render_error(&mut self, err: &str) {
+        self.flush_pending();
         let _ = self.tx.try_send(RenderEvent::Error(err.to_string()));
     }
 
     fn tool_start(&mut self, name: &str, args: &Value) {
+        self.flush_pending();
         let _ = self.tx.try_send(RenderEvent::ToolStart {
             name: name.to_string(),
             args: args.to_string(),
@@ -84,6 +119,35 @@ impl Renderer for TuiRenderer {
     }
 
     fn warn(&mut self, message: &str) {
+        self.flush_pending();
         let _ = self.tx.try_send(RenderEvent::Warn(message.to_string()));
     }
+
+    fn notice(&mut self, level: NoticeLevel, message: &str) {
+        self.flush_pending();
+        let _ = self.tx.try_send(RenderEvent::Notice {
+            level,
+            message: message.to_string(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tui_renderer_batches_tokens_until_flushed() {
+        let (tx, mut rx) = tokio::sync::mpsc::channel(8);
+        let mut renderer = TuiRenderer::new(tx);
+        renderer.render_token("a");
+        renderer.render_token("b");
+        assert!(rx.try_recv().is_err());
+
+        // An idle stream flushes what's held back, once
+        renderer.flush();
+        renderer.flush();
+        assert!(matches!(rx.try_recv(), Ok(RenderEvent::Token(batch)) if batch == "ab"));
+        assert!(rx.try_recv().is_err());
+    }
 }
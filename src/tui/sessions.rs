@@ -0,0 +1,232 @@
+//! Concurrent sessions in one TUI and the dashboard that switches them.
+//!
+//! Each [`Slot`] is an open session with its own [`App`] state, engine,
+//! render channel, and permission prompts, so a turn keeps streaming (or
+//! waits for approval) while another session is on screen. The dashboard
+//! (Ctrl+S) lists the open sessions with their live status, followed by
+//! recent sessions from the index that can be opened alongside them.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use anyhow::Result;
+use tokio::sync::{mpsc, Notify};
+
+use super::{App, RenderEvent};
+use crate::chat::{ChatEngine, ChatOptions};
+use crate::config::Config;
+use crate::constants::DASHBOARD_RECENT_SESSIONS;
+use crate::message::Role;
+use crate::permissions::PermissionRequest;
+use crate::provider::ModelSelection;
+use crate::session::{Session, SessionMeta};
+
+/// An in-flight turn. The engine is moved into the future and handed back
+/// on completion so key handling and rendering continue while it streams.
+pub(super) type Turn = Pin<Box<dyn Future<Output = (ChatEngine, Result<()>)>>>;
+
+/// What a session is doing, as shown on the dashboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlotStatus {
+    /// A turn is running: waiting for the model, streaming, or in a tool.
+    Streaming,
+    /// A tool call waits for a y/n/a answer.
+    Approval,
+    /// Ready for the next message.
+    Idle,
+}
+
+impl SlotStatus {
+    pub fn label(self) -> &'static str {
+        match self {
+            SlotStatus::Streaming => "streaming",
+            SlotStatus::Approval => "waiting for approval",
+            SlotStatus::Idle => "idle",
+        }
+    }
+}
+
+/// An open session.
+pub(super) struct Slot {
+    pub app: App,
+    /// `None` while a turn has it.
+    pub engine: Option<ChatEngine>,
+    pub turn: Option<Turn>,
+    pub tx: mpsc::Sender<RenderEvent>,
+    rx: mpsc::Receiver<RenderEvent>,
+    prompts: mpsc::UnboundedReceiver<PermissionRequest>,
+    pub interrupt: Arc<Notify>,
+    pub id: String,
+    /// The session title and model, refreshed when a turn hands the engine
+    /// back.
+    pub title: String,
+    pub model: String,
+}
+
+/// Something that happened in one of the slots.
+pub(super) enum SlotEvent {
+    Render(RenderEvent),
+    Prompt(PermissionRequest),
+    TurnDone(Box<ChatEngine>, Result<()>),
+}
+
+impl Slot {
+    /// Creates the engine for a new (`session_id` of `None`) or resumed
+    /// session, with prompts routed to the slot. Returns the receiver for
+    /// them alongside, since [`Slot::new`] takes the finished engine.
+    pub fn engine(
+        config: Config,
+        session_id: Option<String>,
+        selection: &ModelSelection,
+        options: ChatOptions,
+    ) -> Result<(ChatEngine, mpsc::UnboundedReceiver<PermissionRequest>)> {
+        let (prompt_tx, prompts) = mpsc::unbounded_channel();
+        let engine = ChatEngine::new(config, session_id, selection)?
+            .with_options(options)?
+            .with_prompt_channel(prompt_tx);
+        Ok((engine, prompts))
+    }
+
+    /// Wraps an engine, filling the history pane from its session.
+    pub fn new(engine: ChatEngine, prompts: mpsc::UnboundedReceiver<PermissionRequest>) -> Self {
+        let mut app = App::new();
+        app.todos = engine.todos().clone();
+        let header = format!(
+            "session: {} · model: {}",
+            &engine.session.id[..8],
+            engine.model_name()
+        );
+        app.push_message("info", &header);
+        for msg in &engine.session.messages {
+            let role = match msg.role {
+                Role::System => continue,
+                Role::User => "user",
+                Role::Assistant => "assistant",
+                Role::Tool => "tool",
+            };
+            app.push_message(role, msg.text());
+        }
+        let (tx, rx) = mpsc::channel(1000);
+        let mut slot = Self {
+            app,
+            interrupt: engine.interrupt_handle(),
+            id: engine.session.id.clone(),
+            title: String::new(),
+            model: String::new(),
+            engine: None,
+            turn: None,
+            tx,
+            rx,
+            prompts,
+        };
+        slot.give_back(engine);
+        slot
+    }
+
+    /// Returns the engine after a turn and refreshes what the dashboard shows.
+    pub fn give_back(&mut self, engine: ChatEngine) {
+        self.title = engine
+            .session
+            .title()
+            .unwrap_or_else(|| "(new)".to_string());
+        self.model = engine.model_name().to_string();
+        self.engine = Some(engine);
+    }
+
+    pub fn status(&self) -> SlotStatus {
+        if self.app.pending_permission.is_some() {
+            SlotStatus::Approval
+        } else if self.turn.is_some() {
+            SlotStatus::Streaming
+        } else {
+            SlotStatus::Idle
+        }
+    }
+
+    /// Polls the slot's turn and channels.
+    fn poll_event(&mut self, cx: &mut Context<'_>) -> Poll<SlotEvent> {
+        if let Poll::Ready(Some(event)) = self.rx.poll_recv(cx) {
+            return Poll::Ready(SlotEvent::Render(event));
+        }
+        if let Poll::Ready(Some(request)) = self.prompts.poll_recv(cx) {
+            return Poll::Ready(SlotEvent::Prompt(request));
+        }
+        if let Some(turn) = self.turn.as_mut() {
+            if let Poll::Ready((engine, result)) = turn.as_mut().poll(cx) {
+                self.turn = None;
+                return Poll::Ready(SlotEvent::TurnDone(Box::new(engine), result));
+            }
+        }
+        Poll::Pending
+    }
+
+    /// Applies an event to the slot's state.
+    pub fn handle(&mut self, event: SlotEvent) {
+        match event {
+            SlotEvent::Render(event) => self.app.handle_render_event(event),
+            SlotEvent::Prompt(request) => self.app.show_permission(request),
+            SlotEvent::TurnDone(engine, result) => {
+                self.give_back(*engine);
+                // Drain events emitted just before the turn finished
+                while let Ok(event) = self.rx.try_recv() {
+                    self.app.handle_render_event(event);
+                }
+                if let Err(e) = result {
+                    self.app
+                        .handle_render_event(RenderEvent::Error(e.to_string()));
+                }
+                self.app.waiting = false;
+                self.app.streaming = false;
+            }
+        }
+    }
+}
+
+/// The next event from any slot, with its index.
+pub(super) async fn next_event(slots: &mut [Slot]) -> (usize, SlotEvent) {
+    std::future::poll_fn(|cx| {
+        for (index, slot) in slots.iter_mut().enumerate() {
+            if let Poll::Ready(event) = slot.poll_event(cx) {
+                return Poll::Ready((index, event));
+            }
+        }
+        Poll::Pending
+    })
+    .await
+}
+
+/// Dashboard state: the selected row and the recent sessions listed under
+/// the open ones.
+#[derive(Default)]
+pub struct Dashboard {
+    /// Index into the open slots followed by `recent`.
+    pub selected: usize,
+    pub recent: Vec<SessionMeta>,
+}
+
+impl Dashboard {
+    /// Opens the dashboard on the active slot, listing recent sessions that
+    /// aren't open.
+    pub(super) fn new(slots: &[Slot], active: usize) -> Self {
+        let mut recent = Session::list_all().unwrap_or_default();
+        recent.retain(|meta| meta.message_count > 0 && slots.iter().all(|s| s.id != meta.id));
+        recent.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        recent.truncate(DASHBOARD_RECENT_SESSIONS);
+        Self {
+            selected: active,
+            recent,
+        }
+    }
+
+    pub fn up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn down(&mut self, open: usize) {
+        if self.selected + 1 < open + self.recent.len() {
+            self.selected += 1;
+        }
+    }
+}
@@ -1,24 +1,36 @@
 //! TUI drawing logic for kaze.
 //!
 //! Renders the two-pane layout: a scrollable message history area on top
-//! and an auto-growing input box on the bottom.
+//! and an auto-growing input box on the bottom. While the model keeps a
+//! todo list, it is shown in a panel to the right of the history. The
+//! session dashboard (Ctrl+S) replaces both with a list of sessions.
 
-use ratatui::layout::{Constraint, Layout, Position};
+use ratatui::layout::{Constraint, Layout, Position, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
 use ratatui::Frame;
 
+use super::sessions::{Dashboard, Slot, SlotStatus};
 use super::App;
+use crate::constants::TODO_PANEL_MAX_WIDTH;
+use crate::tools::todo::TodoItem;
 
 /// Draws the TUI frame with message history and input box.
 ///
 /// The layout is split vertically:
 /// - Top pane: scrollable message history with border and title ` kaze `
 /// - Bottom pane (dynamic height): auto-growing input box with border and title ` > `
+/// - Right of the history, while the todo list has items: a ` todo ` panel
+///   with its progress
 ///
 /// The cursor is placed at the end of the current input text.
 pub fn draw(f: &mut Frame, app: &App) {
+    draw_session(f, app, " kaze ");
+}
+
+/// [`draw`] with `title` on the history pane.
+pub(super) fn draw_session(f: &mut Frame, app: &App, title: &str) {
     // Inner width = total area width minus 2 for left/right borders
     let inner_width = f.area().width.saturating_sub(2).max(1) as usize;
 
@@ -37,6 +49,18 @@ pub fn draw(f: &mut Frame, app: &App) {
     let [messages_area, input_area] =
         Layout::vertical([Constraint::Min(3), Constraint::Length(input_height)]).areas(f.area());
 
+    // --- Todo panel ---
+    let todos = app.todos.items();
+    let messages_area = if todos.is_empty() {
+        messages_area
+    } else {
+        let width = (messages_area.width / 3).clamp(20, TODO_PANEL_MAX_WIDTH);
+        let [history, panel] = Layout::horizontal([Constraint::Min(20), Constraint::Length(width)])
+            .areas(messages_area);
+        draw_todos(f, panel, &todos);
+        history
+    };
+
     // --- Message history pane ---
     let mut lines: Vec<Line<'_>> = Vec::new();
     for msg in &app.messages {
@@ -69,7 +93,7 @@ pub fn draw(f: &mut Frame, app: &App) {
     }
 
     let messages_widget = Paragraph::new(lines)
-        .block(Block::default().borders(Borders::ALL).title(" kaze "))
+        .block(Block::default().borders(Borders::ALL).title(title))
         .wrap(Wrap { trim: false })
         .scroll((app.scroll_offset, 0));
     f.render_widget(messages_widget, messages_area);
@@ -87,3 +111,109 @@ pub fn draw(f: &mut Frame, app: &App) {
     let cursor_y = input_area.y + 1 + (len / iw) as u16;
     f.set_cursor_position(Position::new(cursor_x, cursor_y));
 }
+
+/// Draws the todo list with finished items dimmed and a done count title.
+fn draw_todos(f: &mut Frame, area: Rect, todos: &[TodoItem]) {
+    let done = todos.iter().filter(|item| item.done).count();
+    let lines: Vec<Line<'_>> = todos
+        .iter()
+        .map(|item| {
+            if item.done {
+                Line::from(Span::styled(
+                    format!("✓ {}", item.text),
+                    Style::default()
+                        .fg(Color::DarkGray)
+                        .add_modifier(Modifier::CROSSED_OUT),
+                ))
+            } else {
+                Line::from(format!("○ {}", item.text))
+            }
+        })
+        .collect();
+    let panel = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(format!(
+            " todo {}/{} ",
+            done,
+            todos.len()
+        )))
+        .wrap(Wrap { trim: false });
+    f.render_widget(panel, area);
+}
+
+/// Draws the session dashboard: the open sessions with their status, then
+/// recent ones that can be opened, and the keys that act on them.
+pub(super) fn draw_dashboard(f: &mut Frame, slots: &[Slot], active: usize, dashboard: &Dashboard) {
+    let [list_area, keys_area] =
+        Layout::vertical([Constraint::Min(3), Constraint::Length(1)]).areas(f.area());
+
+    let mut lines: Vec<Line<'_>> = Vec::new();
+    for (index, slot) in slots.iter().enumerate() {
+        let status = slot.status();
+        let color = match status {
+            SlotStatus::Streaming => Color::Green,
+            SlotStatus::Approval => Color::Yellow,
+            SlotStatus::Idle => Color::DarkGray,
+        };
+        let marker = if index == active { "▶" } else { " " };
+        let mut line = Line::from(vec![
+            Span::raw(format!("{} ", marker)),
+            Span::styled(
+                format!("● {:<20}", status.label()),
+                Style::default().fg(color),
+            ),
+            Span::styled(
+                format!(" {} ", &slot.id[..8]),
+                Style::default().fg(Color::Cyan),
+            ),
+            Span::raw(slot.title.as_str()),
+            Span::styled(
+                format!("  {}", slot.model),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]);
+        if index == dashboard.selected {
+            line = line.style(Style::default().add_modifier(Modifier::REVERSED));
+        }
+        lines.push(line);
+    }
+    if !dashboard.recent.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "Recent",
+            Style::default().add_modifier(Modifier::BOLD),
+        )));
+        for (index, meta) in dashboard.recent.iter().enumerate() {
+            let title = meta.title.as_deref().unwrap_or("(untitled)");
+            let updated = meta.updated_at.get(..16).unwrap_or(&meta.updated_at);
+            let mut line = Line::from(vec![
+                Span::raw("  "),
+                Span::styled(
+                    format!("○ {:<20}", updated.replace('T', " ")),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(
+                    format!(" {} ", &meta.id[..8.min(meta.id.len())]),
+                    Style::default().fg(Color::Cyan),
+                ),
+                Span::raw(title),
+                Span::styled(
+                    format!("  {}", meta.model),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]);
+            if slots.len() + index == dashboard.selected {
+                line = line.style(Style::default().add_modifier(Modifier::REVERSED));
+            }
+            lines.push(line);
+        }
+    }
+    let list =
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(" sessions "));
+    f.render_widget(list, list_area);
+
+    let keys = Paragraph::new(Line::from(Span::styled(
+        " ↑/↓ select · Enter open · n new session · x close · Esc back",
+        Style::default().fg(Color::DarkGray),
+    )));
+    f.render_widget(keys, keys_area);
+}
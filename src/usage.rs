@@ -0,0 +1,161 @@
+//! Usage ledger for kaze.
+//!
+//! Every completed turn appends one [`UsageRecord`] to
+//! `~/.local/share/kaze/usage.jsonl`, one JSON object per line. The ledger
+//! is append-only so it can be compared across providers and models later.
+//!
+//! Token counts come from the provider's own usage report when it sends
+//! one ([`TokenUsage`]), and are priced with the table in [`crate::models`].
+//! `kaze stats` reads the ledger back through [`read_ledger`].
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::metrics::TurnMetrics;
+
+/// Token counts reported by the provider for a request, summed over every
+/// model call in a multi-turn tool loop.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+impl TokenUsage {
+    /// Dollar cost at the model's list price, or `None` if the price is
+    /// unknown. Ollama runs locally and is always free.
+    pub fn cost(&self, provider: &str, model: &str) -> Option<f64> {
+        let pricing = if provider == "ollama" {
+            crate::models::Pricing::FREE
+        } else {
+            crate::models::pricing_for(model)?
+        };
+        Some(pricing.cost(self.input_tokens, self.output_tokens))
+    }
+}
+
+impl From<rig::completion::Usage> for TokenUsage {
+    fn from(usage: rig::completion::Usage) -> Self {
+        Self {
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+        }
+    }
+}
+
+/// Formats a dollar amount, with more precision for small values.
+pub fn format_cost(cost: f64) -> String {
+    if cost > 0.0 && cost < 0.01 {
+        format!("${:.4}", cost)
+    } else {
+        format!("${:.2}", cost)
+    }
+}
+
+/// A single ledger entry describing one turn.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageRecord {
+    /// RFC 3339 timestamp of when the turn finished.
+    pub timestamp: String,
+    /// Session the turn belongs to; `None` for one-shot `kaze ask`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    pub provider: String,
+    pub model: String,
+    /// Output tokens: the provider's count if reported, otherwise the
+    /// tokens in the assistant's final response.
+    pub output_tokens: usize,
+    /// Input tokens reported by the provider.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_tokens: Option<u64>,
+    /// Cost in US dollars, when usage was reported and the price is known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+    /// Time to first token in milliseconds, if any output was streamed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttft_ms: Option<u64>,
+    pub total_ms: u64,
+    pub model_ms: u64,
+    pub tool_ms: u64,
+    /// Names of the tools the turn called, in call order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<String>,
+    /// Files the turn's tools wrote or edited.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<String>,
+}
+
+impl UsageRecord {
+    /// Builds a record stamped with the current time.
+    ///
+    /// `output_tokens` is the locally counted fallback, replaced by the
+    /// provider's figure when the stream reported usage.
+    pub fn new(
+        provider: &str,
+        model: &str,
+        session_id: Option<&str>,
+        output_tokens: usize,
+        metrics: &TurnMetrics,
+    ) -> Self {
+        let usage = metrics.usage;
+        Self {
+            timestamp: Utc::now().to_rfc3339(),
+            session_id: session_id.map(str::to_string),
+            provider: provider.to_string(),
+            model: model.to_string(),
+            output_tokens: usage.map_or(output_tokens, |u| u.output_tokens as usize),
+            input_tokens: usage.map(|u| u.input_tokens),
+            cost_usd: usage.and_then(|u| u.cost(provider, model)),
+            ttft_ms: metrics.time_to_first_token.map(|d| d.as_millis() as u64),
+            total_ms: metrics.total.as_millis() as u64,
+            model_ms: metrics.model_time().as_millis() as u64,
+            tool_ms: metrics.tool_time.as_millis() as u64,
+            tools: metrics.tools.clone(),
+            files: metrics.modified_files.clone(),
+        }
+    }
+
+    /// Appends this record to the ledger file.
+    pub fn append(&self) -> Result<()> {
+        let path = ledger_path()?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open usage ledger: {}", path.display()))?;
+        let line = serde_json::to_string(self)?;
+        writeln!(file, "{}", line).with_context(|| "Failed to write usage ledger")?;
+        Ok(())
+    }
+}
+
+/// Reads every record in the ledger, oldest first.
+///
+/// A missing ledger reads as empty; lines that fail to parse (e.g. a
+/// partial write) are skipped.
+pub fn read_ledger() -> Result<Vec<UsageRecord>> {
+    let path = ledger_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let file = std::fs::File::open(&path)
+        .with_context(|| format!("Failed to open usage ledger: {}", path.display()))?;
+    Ok(BufReader::new(file)
+        .lines()
+        .map_while(|line| line.ok())
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect())
+}
+
+/// Returns the ledger path (`~/.local/share/kaze/usage.jsonl`).
+pub fn ledger_path() -> Result<PathBuf> {
+    Ok(Config::data_dir()?.join("usage.jsonl"))
+}